@@ -12,6 +12,7 @@ use std::format;
 
 use std::borrow::Cow;
 
+use proc_macro2::Span;
 use quote::{quote, quote_spanned, ToTokens};
 use syn::{
     parse::{Parse, ParseStream, Parser},
@@ -747,7 +748,7 @@ impl ExportedFn {
                         p => p,
                     };
                     let downcast_span = quote_spanned!(
-                        arg_type.span()=> &mut args[0usize].write_lock::<#arg_type>().unwrap());
+                        arg_type.span().resolved_at(Span::call_site())=> &mut args[0usize].write_lock::<#arg_type>().unwrap());
                     unpack_stmts.push(
                         syn::parse2::<syn::Stmt>(quote! {
                             let #var = #downcast_span;
@@ -792,7 +793,7 @@ impl ExportedFn {
                             syn::Type::Path(ref p) if p.path == str_type_path => {
                                 is_string = true;
                                 is_ref = true;
-                                quote_spanned!(arg_type.span()=>
+                                quote_spanned!(arg_type.span().resolved_at(Span::call_site())=>
                                                mem::take(args[#i]).take_immutable_string().unwrap())
                             }
                             _ => panic!("internal error: why wasn't this found earlier!?"),
@@ -800,13 +801,13 @@ impl ExportedFn {
                         syn::Type::Path(ref p) if p.path == string_type_path => {
                             is_string = true;
                             is_ref = false;
-                            quote_spanned!(arg_type.span()=>
+                            quote_spanned!(arg_type.span().resolved_at(Span::call_site())=>
                                            mem::take(args[#i]).take_string().unwrap())
                         }
                         _ => {
                             is_string = false;
                             is_ref = false;
-                            quote_spanned!(arg_type.span()=>
+                            quote_spanned!(arg_type.span().resolved_at(Span::call_site())=>
                                            mem::take(args[#i]).cast::<#arg_type>())
                         }
                     };
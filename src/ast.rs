@@ -7,11 +7,12 @@ use crate::stdlib::{
     borrow::Cow,
     boxed::Box,
     collections::HashMap,
-    fmt,
+    fmt, format,
     hash::Hash,
+    iter::once,
     num::{NonZeroU64, NonZeroUsize},
-    ops::{Add, AddAssign},
-    string::String,
+    ops::{Add, AddAssign, RangeInclusive},
+    string::{String, ToString},
     vec,
     vec::Vec,
 };
@@ -19,7 +20,8 @@ use crate::syntax::FnCustomSyntaxEval;
 use crate::token::Token;
 use crate::utils::StraightHasherBuilder;
 use crate::{
-    Dynamic, FnNamespace, FnPtr, ImmutableString, Module, Position, Shared, StaticVec, INT,
+    CharRange, Dynamic, FnNamespace, FnPtr, ImmutableString, Module, Position, Shared, StaticVec,
+    INT,
 };
 
 #[cfg(not(feature = "no_float"))]
@@ -78,8 +80,16 @@ pub struct ScriptFnDef {
     pub name: ImmutableString,
     /// Function access mode.
     pub access: FnAccess,
+    /// Is the function explicitly exported (via `export fn` or `export` followed by its name)?
+    #[cfg(not(feature = "no_module"))]
+    pub exported: bool,
     /// Names of function parameters.
     pub params: StaticVec<ImmutableString>,
+    /// Default values of the last function parameters (before the rest parameter), if any.
+    pub defaults: StaticVec<Dynamic>,
+    /// Is the last parameter a rest parameter (e.g. `...args`) collecting all remaining arguments
+    /// into an array?
+    pub variadic: bool,
     /// Access to external variables.
     #[cfg(not(feature = "no_closure"))]
     pub externals: Vec<ImmutableString>,
@@ -101,16 +111,48 @@ impl fmt::Display for ScriptFnDef {
             self.name,
             self.params
                 .iter()
-                .map(|s| s.as_str())
+                .enumerate()
+                .map(|(i, s)| match self.default_value(i) {
+                    Some(value) => format!("{} = {:?}", s, value),
+                    None if i == self.num_fixed_params() => format!("...{}", s),
+                    None => s.to_string(),
+                })
                 .collect::<Vec<_>>()
                 .join(", ")
         )
     }
 }
 
+impl ScriptFnDef {
+    /// Number of parameters, not counting the rest parameter (if any).
+    #[inline(always)]
+    pub fn num_fixed_params(&self) -> usize {
+        if self.variadic {
+            self.params.len() - 1
+        } else {
+            self.params.len()
+        }
+    }
+    /// Minimum number of arguments that this function can be called with.
+    #[inline(always)]
+    pub fn min_params(&self) -> usize {
+        self.num_fixed_params() - self.defaults.len()
+    }
+    /// Get the default value of a parameter, if any.
+    #[inline(always)]
+    pub fn default_value(&self, index: usize) -> Option<&Dynamic> {
+        index
+            .checked_sub(self.min_params())
+            .and_then(|index| self.defaults.get(index))
+    }
+}
+
 /// A type containing the metadata of a script-defined function.
 ///
 /// Created by [`AST::iter_functions`].
+///
+/// Not available under the `no_function` feature.
+#[cfg(not(feature = "no_function"))]
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
 pub struct ScriptFnMetadata<'a> {
     /// Function doc-comments (if any).
@@ -130,6 +172,7 @@ pub struct ScriptFnMetadata<'a> {
     pub params: Vec<&'a str>,
 }
 
+#[cfg(not(feature = "no_function"))]
 impl fmt::Display for ScriptFnMetadata<'_> {
     #[inline(always)]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -147,6 +190,7 @@ impl fmt::Display for ScriptFnMetadata<'_> {
     }
 }
 
+#[cfg(not(feature = "no_function"))]
 impl<'a> Into<ScriptFnMetadata<'a>> for &'a ScriptFnDef {
     #[inline(always)]
     fn into(self) -> ScriptFnMetadata<'a> {
@@ -159,6 +203,50 @@ impl<'a> Into<ScriptFnMetadata<'a>> for &'a ScriptFnDef {
     }
 }
 
+/// A function call made in an [`AST`].
+///
+/// Created by [`AST::collect_fn_calls`].
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct FnCallSite<'a> {
+    /// Namespace path of the function (e.g. `foo::bar` for `foo::bar::baz(x)`),
+    /// or [`None`] if the call is not namespace-qualified.
+    pub namespace: Option<String>,
+    /// Function name.
+    pub name: &'a str,
+    /// Number of arguments passed to the function.
+    ///
+    /// For method calls (e.g. `x.foo(y)`) this includes the object itself.
+    pub num_args: usize,
+    /// Position of the function call.
+    pub pos: Position,
+}
+
+impl fmt::Display for FnCallSite<'_> {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ref namespace) = self.namespace {
+            write!(f, "{}{}", namespace, Token::DoubleColon.syntax())?;
+        }
+        write!(f, "{}/{}", self.name, self.num_args)
+    }
+}
+
+/// The function calls made by the global statements, or by a script-defined function,
+/// of an [`AST`].
+///
+/// Created by [`AST::collect_fn_calls`].
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct FnCalls<'a> {
+    /// Name of the script-defined function making the calls,
+    /// or [`None`] for the global statements.
+    pub caller: Option<&'a str>,
+    /// Number of parameters of the script-defined function making the calls
+    /// (always zero for the global statements).
+    pub num_params: usize,
+    /// Function calls made, in order of position.
+    pub calls: Vec<FnCallSite<'a>>,
+}
+
 /// Compiled AST (abstract syntax tree) of a Rhai script.
 ///
 /// # Thread Safety
@@ -172,6 +260,8 @@ pub struct AST {
     statements: Vec<Stmt>,
     /// Script-defined functions.
     functions: Shared<Module>,
+    /// Original script text of the [`AST`], if retained.
+    source_text: Option<Shared<str>>,
 }
 
 impl Default for AST {
@@ -181,6 +271,7 @@ impl Default for AST {
             source: None,
             statements: Vec::with_capacity(16),
             functions: Default::default(),
+            source_text: None,
         }
     }
 }
@@ -196,6 +287,7 @@ impl AST {
             source: None,
             statements: statements.into_iter().collect(),
             functions: functions.into(),
+            source_text: None,
         }
     }
     /// Create a new [`AST`] with a source name.
@@ -209,6 +301,7 @@ impl AST {
             source: Some(source.into()),
             statements: statements.into_iter().collect(),
             functions: functions.into(),
+            source_text: None,
         }
     }
     /// Get the source.
@@ -222,9 +315,31 @@ impl AST {
         self.source.clone()
     }
     /// Set the source.
+    ///
+    /// The source is also reported for calls made inside the script-defined functions of this [`AST`].
     #[inline(always)]
     pub fn set_source<S: Into<ImmutableString>>(&mut self, source: Option<S>) {
-        self.source = source.map(|s| s.into())
+        let source = source.map(|s| s.into());
+        shared_make_mut(&mut self.functions).set_id(source.clone());
+        self.source = source;
+    }
+    /// Get the original script text, if retained.
+    ///
+    /// The text is only kept when [`Engine::set_retain_source`][crate::Engine::set_retain_source]
+    /// is turned on before compiling.
+    #[inline(always)]
+    pub fn source_text(&self) -> Option<&str> {
+        self.source_text.as_ref().map(|s| s.as_ref())
+    }
+    /// Clone the original script text.
+    #[inline(always)]
+    pub(crate) fn clone_source_text(&self) -> Option<Shared<str>> {
+        self.source_text.clone()
+    }
+    /// Set the original script text.
+    #[inline(always)]
+    pub fn set_source_text<S: Into<Shared<str>>>(&mut self, text: Option<S>) {
+        self.source_text = text.map(|s| s.into())
     }
     /// Get the statements.
     #[cfg(not(feature = "internals"))]
@@ -292,6 +407,7 @@ impl AST {
             source: self.source.clone(),
             statements: Default::default(),
             functions: functions.into(),
+            source_text: self.source_text.clone(),
         }
     }
     /// Clone the [`AST`]'s script statements into a new [`AST`].
@@ -302,6 +418,7 @@ impl AST {
             source: self.source.clone(),
             statements: self.statements.clone(),
             functions: Default::default(),
+            source_text: self.source_text.clone(),
         }
     }
     /// Merge two [`AST`] into one.  Both [`AST`]'s are untouched and a new, merged, version
@@ -493,11 +610,13 @@ impl AST {
         let mut functions = functions.as_ref().clone();
         functions.merge_filtered(&other.functions, &mut filter);
 
-        if let Some(source) = source {
+        let mut ast = if let Some(source) = source {
             Self::new_with_source(ast, functions, source)
         } else {
             Self::new(ast, functions)
-        }
+        };
+        ast.source_text = Self::merge_source_text(&self.source_text, &other.source_text);
+        ast
     }
     /// Combine one [`AST`] with another.  The second [`AST`] is consumed.
     ///
@@ -556,12 +675,27 @@ impl AST {
         other: Self,
         mut filter: impl FnMut(FnNamespace, FnAccess, bool, &str, usize) -> bool,
     ) -> &mut Self {
+        self.source_text = Self::merge_source_text(&self.source_text, &other.source_text);
         self.statements.extend(other.statements.into_iter());
         if !other.functions.is_empty() {
             shared_make_mut(&mut self.functions).merge_filtered(&other.functions, &mut filter);
         }
         self
     }
+    /// Pick the original script text to keep when joining two [`AST`]'s.
+    ///
+    /// Positions in a joined [`AST`] refer to different scripts, so the text is only kept
+    /// when just one side has any.
+    #[inline(always)]
+    fn merge_source_text(
+        text1: &Option<Shared<str>>,
+        text2: &Option<Shared<str>>,
+    ) -> Option<Shared<str>> {
+        match (text1, text2) {
+            (Some(text), None) | (None, Some(text)) => Some(text.clone()),
+            _ => None,
+        }
+    }
     /// Filter out the functions, retaining only some based on a filter predicate.
     ///
     /// # Example
@@ -596,7 +730,38 @@ impl AST {
         }
         self
     }
-    /// Iterate through all function definitions.
+    /// Iterate through all function definitions, yielding the [metadata][ScriptFnMetadata]
+    /// of each function, including its doc-comments.
+    ///
+    /// Not available under the `no_function` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::{Engine, FnAccess};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile(
+    ///     "
+    ///         /// Add two numbers.
+    ///         private fn add(x, y) { x + y }
+    ///     ",
+    /// )?;
+    ///
+    /// let f = ast.iter_functions().next().unwrap();
+    ///
+    /// assert_eq!(f.name, "add");
+    /// assert_eq!(f.params, ["x", "y"]);
+    /// assert_eq!(f.access, FnAccess::Private);
+    /// assert_eq!(f.comments, ["/// Add two numbers."]);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
     #[cfg(not(feature = "no_function"))]
     #[inline(always)]
     pub fn iter_functions<'a>(&'a self) -> impl Iterator<Item = ScriptFnMetadata> + 'a {
@@ -604,12 +769,188 @@ impl AST {
             .iter_script_fn()
             .map(|(_, _, _, _, fn_def)| fn_def.into())
     }
+    /// Collect all the function calls made in the [`AST`], for the global statements
+    /// (always listed first) and for each script-defined function (including closures).
+    ///
+    /// Calls inside closures are listed under the closures themselves (i.e. the anonymous
+    /// functions they are compiled into), and calls inside custom syntax are also collected.
+    ///
+    /// Operators are also function calls, and are listed as such (e.g. `+` for `x + y`).
+    ///
+    /// The [`AST`] is inspected as compiled, so calls eliminated by the optimizer
+    /// (e.g. `1 + 2` folded into `3`) are not listed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # #[cfg(not(feature = "no_object"))]
+    /// # {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile(
+    ///     "
+    ///         fn add(x, y) { x.plus(y) }
+    ///
+    ///         print(add(40, 2));
+    ///     ",
+    /// )?;
+    ///
+    /// let fn_calls = ast.collect_fn_calls();
+    ///
+    /// assert_eq!(fn_calls[0].caller, None);
+    /// assert_eq!(
+    ///     fn_calls[0].calls.iter().map(|f| f.to_string()).collect::<Vec<_>>(),
+    ///     ["print/1", "add/2"]
+    /// );
+    ///
+    /// assert_eq!(fn_calls[1].caller, Some("add"));
+    /// assert_eq!(fn_calls[1].calls[0].to_string(), "plus/2");
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn collect_fn_calls(&self) -> Vec<FnCalls<'_>> {
+        let list = once(FnCalls {
+            caller: None,
+            num_params: 0,
+            calls: collect_stmts_fn_calls(&self.statements),
+        });
+
+        #[cfg(not(feature = "no_function"))]
+        let list = {
+            let mut functions: Vec<_> = self
+                .functions
+                .iter_script_fn()
+                .map(|(_, _, _, _, fn_def)| fn_def)
+                .collect();
+            functions.sort_by(|a, b| (&a.name, a.params.len()).cmp(&(&b.name, b.params.len())));
+
+            list.chain(functions.into_iter().map(|fn_def| FnCalls {
+                caller: Some(fn_def.name.as_str()),
+                num_params: fn_def.params.len(),
+                calls: collect_stmts_fn_calls(once(&fn_def.body)),
+            }))
+        };
+
+        list.collect()
+    }
+    /// _(INTERNALS)_ Rewrite the [`AST`] by calling a function on every [statement][Stmt],
+    /// including those in the bodies of script-defined functions.
+    /// Exported under the `internals` feature only.
+    ///
+    /// Statements nested within a statement are visited before the statement itself, so
+    /// statements added by the function are not visited again.
+    ///
+    /// Variable accesses may be resolved by position when the script is compiled, so statements
+    /// declaring variables must not be added or removed.  To run the optimizer again afterwards,
+    /// use [`Engine::optimize_ast`][crate::Engine::optimize_ast].
+    ///
+    /// # Example
+    ///
+    /// Inject a call to `tick` at the start of every loop iteration.
+    ///
+    /// ```
+    /// # #![allow(deprecated)]
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Expr, RegisterFn, Stmt};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// let ticks = Arc::new(AtomicUsize::new(0));
+    /// let counter = ticks.clone();
+    ///
+    /// engine.register_fn("tick", move || {
+    ///     counter.fetch_add(1, Ordering::Relaxed);
+    /// });
+    ///
+    /// let mut ast = engine.compile(
+    ///     "
+    ///         let x = 0;
+    ///         while x < 10 { x += 1; }
+    ///         for i in range(0, 5) { x += i; }
+    ///         x
+    ///     ",
+    /// )?;
+    ///
+    /// fn inject_tick(body: &mut Stmt) {
+    ///     let pos = body.position();
+    ///     let tick = Stmt::Expr(Expr::new_fn_call("tick", vec![], pos));
+    ///     let body_stmt = std::mem::take(body);
+    ///     *body = Stmt::new_block(vec![tick, body_stmt], pos);
+    /// }
+    ///
+    /// ast.transform(|stmt| match stmt {
+    ///     Stmt::While(_, x, _) | Stmt::Do(x, _, _, _) => inject_tick(&mut x.0),
+    ///     Stmt::For(_, x, _) => inject_tick(&mut x.2),
+    ///     _ => (),
+    /// });
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 20);
+    /// assert_eq!(ticks.load(Ordering::Relaxed), 15);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "internals")]
+    #[deprecated = "this method is volatile and may change"]
+    #[inline(always)]
+    pub fn transform(&mut self, mut on_stmt: impl FnMut(&mut Stmt)) {
+        self.walk_mut(&mut on_stmt, &mut |_| ());
+    }
+    /// _(INTERNALS)_ Rewrite the [`AST`] by calling a function on every [expression][Expr],
+    /// including those in the bodies of script-defined functions.
+    /// Exported under the `internals` feature only.
+    ///
+    /// Expressions nested within an expression are visited before the expression itself, so
+    /// expressions added by the function are not visited again.
+    ///
+    /// The right-hand side of a [`Dot`][Expr::Dot] expression is a property or method call,
+    /// and must remain so.
+    ///
+    /// Variable accesses may be resolved by position when the script is compiled, so statements
+    /// declaring variables must not be added or removed.  To run the optimizer again afterwards,
+    /// use [`Engine::optimize_ast`][crate::Engine::optimize_ast].
+    #[cfg(feature = "internals")]
+    #[deprecated = "this method is volatile and may change"]
+    #[inline(always)]
+    pub fn transform_exprs(&mut self, mut on_expr: impl FnMut(&mut Expr)) {
+        self.walk_mut(&mut |_| (), &mut on_expr);
+    }
+    /// Visit every statement and expression in the [`AST`] mutably, including those in the
+    /// bodies of script-defined functions.
+    #[cfg(feature = "internals")]
+    fn walk_mut(&mut self, on_stmt: &mut dyn FnMut(&mut Stmt), on_expr: &mut dyn FnMut(&mut Expr)) {
+        for stmt in self.statements.iter_mut() {
+            stmt.walk_mut(on_stmt, on_expr);
+        }
+
+        #[cfg(not(feature = "no_function"))]
+        if self.functions.iter_script_fn().next().is_some() {
+            shared_make_mut(&mut self.functions)
+                .transform_script_fn(|fn_def| fn_def.body.walk_mut(on_stmt, on_expr));
+        }
+    }
     /// Clear all function definitions in the [`AST`].
     #[cfg(not(feature = "no_function"))]
     #[inline(always)]
     pub fn clear_functions(&mut self) {
         self.functions = Default::default();
     }
+    /// Move all function definitions of another [`AST`] into this [`AST`], overwriting
+    /// similarly-named functions with the same number of parameters, then make the other
+    /// [`AST`] share all the function definitions of this [`AST`].
+    #[inline]
+    pub(crate) fn absorb_functions(&mut self, other: &mut Self) {
+        if !other.functions.is_empty() {
+            shared_make_mut(&mut self.functions).merge(&other.functions);
+        }
+        other.functions = self.functions.clone();
+    }
     /// Clear all statements in the [`AST`], leaving only function definitions.
     #[inline(always)]
     pub fn clear_statements(&mut self) {
@@ -647,6 +988,41 @@ impl AsRef<Module> for AST {
     }
 }
 
+/// Collect all the function calls made in a list of statements, sorted by position.
+fn collect_stmts_fn_calls<'a>(stmts: impl IntoIterator<Item = &'a Stmt>) -> Vec<FnCallSite<'a>> {
+    let mut calls = Vec::new();
+    stmts
+        .into_iter()
+        .for_each(|stmt| stmt.collect_fn_calls(&mut calls));
+    calls.sort_by_key(|f| f.pos);
+    calls
+}
+
+/// A compiled expression, created by the `compile_expression` function in scripts
+/// and evaluated via its `eval` method against an [object map][Map] of variables.
+///
+/// The expression is compiled with the expression-only parser, so it cannot contain
+/// statements, function definitions or `import`'s.
+///
+/// Not available under the `no_object` feature.
+#[cfg(not(feature = "no_object"))]
+#[derive(Debug, Clone)]
+pub struct CompiledExpr(Shared<AST>);
+
+#[cfg(not(feature = "no_object"))]
+impl CompiledExpr {
+    /// Create a new [`CompiledExpr`] from an [`AST`] containing a single expression.
+    #[inline(always)]
+    pub(crate) fn new(ast: AST) -> Self {
+        Self(ast.into())
+    }
+    /// Get the [`AST`] of the compiled expression.
+    #[inline(always)]
+    pub fn ast(&self) -> &AST {
+        &self.0
+    }
+}
+
 /// _(INTERNALS)_ An identifier containing an [immutable string][ImmutableString] name and a [position][Position].
 /// Exported under the `internals` feature only.
 ///
@@ -682,6 +1058,91 @@ pub enum ReturnType {
     Exception,
 }
 
+/// _(INTERNALS)_ A `catch` clause of a `try` statement:
+/// the error variable, an optional guard and the catch block.
+/// Exported under the `internals` feature only.
+///
+/// ## WARNING
+///
+/// This type is volatile and may change.
+pub type CatchClause = (Option<Ident>, Option<Ident>, Option<Expr>, Stmt);
+
+/// _(INTERNALS)_ The cases of a `switch` statement:
+/// the hashed literal cases, the default case and the range cases in order.
+/// Exported under the `internals` feature only.
+///
+/// ## WARNING
+///
+/// This type is volatile and may change.
+pub type SwitchCases = (
+    HashMap<u64, Stmt, StraightHasherBuilder>,
+    Option<Stmt>,
+    StaticVec<(SwitchRange, Stmt)>,
+);
+
+/// _(INTERNALS)_ A range case of a `switch` statement, e.g. `'a'..='z'` or `0..10`.
+/// Exported under the `internals` feature only.
+///
+/// ## WARNING
+///
+/// This type is volatile and may change.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SwitchRange {
+    /// Inclusive range of characters.
+    Char(CharRange),
+    /// Inclusive range of integers.
+    Int(RangeInclusive<INT>),
+}
+
+impl SwitchRange {
+    /// Does this range contain a value?
+    ///
+    /// Values of other types are never contained.
+    pub fn contains(&self, value: &Dynamic) -> bool {
+        match self {
+            Self::Char(range) => value.as_char().is_ok_and(|ch| range.contains(&ch)),
+            Self::Int(range) => value.as_int().is_ok_and(|n| range.contains(&n)),
+        }
+    }
+}
+
+/// _(INTERNALS)_ A destructuring pattern of a `let` or `const` statement.
+/// Exported under the `internals` feature only.
+///
+/// ## WARNING
+///
+/// This type is volatile and may change.
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// `[` var `,` ... \[`,` `..`var\] `]`
+    ///
+    /// Not available under `no_index`.
+    #[cfg(not(feature = "no_index"))]
+    Array(StaticVec<Ident>, Option<Ident>),
+    /// `#{` var `,` ... \[`,` `..`var\] `}`
+    ///
+    /// Not available under `no_object`.
+    #[cfg(not(feature = "no_object"))]
+    Map(StaticVec<Ident>, Option<Ident>),
+}
+
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+impl Pattern {
+    /// Iterate through all the variables bound by this pattern, in order.
+    #[inline(always)]
+    pub fn iter_vars(&self) -> impl Iterator<Item = &Ident> {
+        let (vars, rest) = match self {
+            #[cfg(not(feature = "no_index"))]
+            Self::Array(vars, rest) => (vars, rest),
+            #[cfg(not(feature = "no_object"))]
+            Self::Map(vars, rest) => (vars, rest),
+        };
+
+        vars.iter().chain(rest.iter())
+    }
+}
+
 /// _(INTERNALS)_ A statement.
 /// Exported under the `internals` feature only.
 ///
@@ -694,34 +1155,37 @@ pub enum Stmt {
     Noop(Position),
     /// `if` expr `{` stmt `}` `else` `{` stmt `}`
     If(Expr, Box<(Stmt, Option<Stmt>)>, Position),
-    /// `switch` expr `{` literal or _ `=>` stmt `,` ... `}`
-    Switch(
+    /// `switch` expr `{` literal, range or _ `=>` stmt `,` ... `}`
+    Switch(Expr, Box<SwitchCases>, Position),
+    /// \[`'`label`:`\] `while` expr `{` stmt `}`
+    While(Expr, Box<(Stmt, Option<ImmutableString>)>, Position),
+    /// \[`'`label`:`\] `do` `{` stmt `}` `while`|`until` expr
+    Do(Box<(Stmt, Option<ImmutableString>)>, Expr, bool, Position),
+    /// \[`'`label`:`\] `for` \[`(`counter`,`\] id \[`)`\] `in` expr `{` stmt `}`
+    For(
         Expr,
-        Box<(HashMap<u64, Stmt, StraightHasherBuilder>, Option<Stmt>)>,
+        Box<(Option<String>, String, Stmt, Option<ImmutableString>)>,
         Position,
     ),
-    /// `while` expr `{` stmt `}`
-    While(Expr, Box<Stmt>, Position),
-    /// `do` `{` stmt `}` `while`|`until` expr
-    Do(Box<Stmt>, Expr, bool, Position),
-    /// `for` id `in` expr `{` stmt `}`
-    For(Expr, Box<(String, Stmt)>, Position),
     /// \[`export`\] `let` id `=` expr
     Let(Box<Ident>, Option<Expr>, bool, Position),
     /// \[`export`\] `const` id `=` expr
     Const(Box<Ident>, Option<Expr>, bool, Position),
+    /// \[`export`\] `let`|`const` pattern `=` expr
+    #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+    LetPattern(Box<(Pattern, Expr)>, AccessMode, bool, Position),
     /// expr op`=` expr
     Assignment(Box<(Expr, Cow<'static, str>, Expr)>, Position),
     /// `{` stmt`;` ... `}`
     Block(Vec<Stmt>, Position),
-    /// `try` `{` stmt; ... `}` `catch` `(` var `)` `{` stmt; ... `}`
-    TryCatch(Box<(Stmt, Option<Ident>, Stmt)>, Position, Position),
+    /// `try` `{` stmt; ... `}` `catch` `(` var \[`,` pos_var\] \[`if` expr\] `)` `{` stmt; ... `}` ...
+    TryCatch(Box<(Stmt, StaticVec<CatchClause>)>, Position, Position),
     /// [expression][Expr]
     Expr(Expr),
-    /// `continue`
-    Continue(Position),
-    /// `break`
-    Break(Position),
+    /// `continue` \[`'`label\]
+    Continue(Option<ImmutableString>, Position),
+    /// `break` \[`'`label\] \[expr\]
+    Break(Option<ImmutableString>, Option<Expr>, Position),
     /// `return`/`throw`
     Return((ReturnType, Position), Option<Expr>, Position),
     /// `import` expr `as` var
@@ -755,8 +1219,8 @@ impl Stmt {
     pub fn position(&self) -> Position {
         match self {
             Self::Noop(pos)
-            | Self::Continue(pos)
-            | Self::Break(pos)
+            | Self::Continue(_, pos)
+            | Self::Break(_, _, pos)
             | Self::Block(_, pos)
             | Self::Assignment(_, pos)
             | Self::If(_, _, pos)
@@ -771,6 +1235,9 @@ impl Stmt {
 
             Self::Expr(x) => x.position(),
 
+            #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+            Self::LetPattern(_, _, _, pos) => *pos,
+
             #[cfg(not(feature = "no_module"))]
             Self::Import(_, _, pos) => *pos,
             #[cfg(not(feature = "no_module"))]
@@ -784,8 +1251,8 @@ impl Stmt {
     pub fn set_position(&mut self, new_pos: Position) -> &mut Self {
         match self {
             Self::Noop(pos)
-            | Self::Continue(pos)
-            | Self::Break(pos)
+            | Self::Continue(_, pos)
+            | Self::Break(_, _, pos)
             | Self::Block(_, pos)
             | Self::Assignment(_, pos)
             | Self::If(_, _, pos)
@@ -802,6 +1269,9 @@ impl Stmt {
                 x.set_position(new_pos);
             }
 
+            #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+            Self::LetPattern(_, _, _, pos) => *pos = new_pos,
+
             #[cfg(not(feature = "no_module"))]
             Self::Import(_, _, pos) => *pos = new_pos,
             #[cfg(not(feature = "no_module"))]
@@ -831,10 +1301,13 @@ impl Stmt {
             | Self::Assignment(_, _)
             | Self::Expr(_)
             | Self::Do(_, _, _, _)
-            | Self::Continue(_)
-            | Self::Break(_)
+            | Self::Continue(_, _)
+            | Self::Break(_, _, _)
             | Self::Return(_, _, _) => false,
 
+            #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+            Self::LetPattern(_, _, _, _) => false,
+
             #[cfg(not(feature = "no_module"))]
             Self::Import(_, _, _) | Self::Export(_, _) => false,
 
@@ -858,15 +1331,23 @@ impl Stmt {
                 expr.is_pure()
                     && x.0.values().all(Stmt::is_pure)
                     && x.1.as_ref().map(Stmt::is_pure).unwrap_or(true)
+                    && x.2.iter().all(|(_, stmt)| stmt.is_pure())
             }
             Self::While(condition, block, _) | Self::Do(block, condition, _, _) => {
-                condition.is_pure() && block.is_pure()
+                condition.is_pure() && block.0.is_pure()
             }
-            Self::For(iterable, x, _) => iterable.is_pure() && x.1.is_pure(),
+            Self::For(iterable, x, _) => iterable.is_pure() && x.2.is_pure(),
             Self::Let(_, _, _, _) | Self::Const(_, _, _, _) | Self::Assignment(_, _) => false,
             Self::Block(block, _) => block.iter().all(|stmt| stmt.is_pure()),
-            Self::Continue(_) | Self::Break(_) | Self::Return(_, _, _) => false,
-            Self::TryCatch(x, _, _) => x.0.is_pure() && x.2.is_pure(),
+            Self::Continue(_, _) | Self::Break(_, _, _) | Self::Return(_, _, _) => false,
+            #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+            Self::LetPattern(_, _, _, _) => false,
+            Self::TryCatch(x, _, _) => {
+                x.0.is_pure()
+                    && x.1.iter().all(|(_, _, guard, block)| {
+                        guard.as_ref().map(Expr::is_pure).unwrap_or(true) && block.is_pure()
+                    })
+            }
 
             #[cfg(not(feature = "no_module"))]
             Self::Import(_, _, _) => false,
@@ -877,6 +1358,160 @@ impl Stmt {
             Self::Share(_) => false,
         }
     }
+    /// _(INTERNALS)_ Create a statement block.
+    /// Exported under the `internals` feature only.
+    ///
+    /// Variables declared within the block go out of scope at the end of the block.
+    #[cfg(feature = "internals")]
+    #[inline(always)]
+    pub fn new_block(statements: impl IntoIterator<Item = Stmt>, pos: Position) -> Self {
+        Self::Block(statements.into_iter().collect(), pos)
+    }
+    /// Visit this statement, and all statements and expressions nested within it, mutably.
+    ///
+    /// Nested statements and expressions are visited before the statements and expressions
+    /// containing them.
+    #[cfg(feature = "internals")]
+    pub(crate) fn walk_mut(
+        &mut self,
+        on_stmt: &mut dyn FnMut(&mut Stmt),
+        on_expr: &mut dyn FnMut(&mut Expr),
+    ) {
+        match self {
+            Self::Noop(_) | Self::Continue(_, _) => (),
+            Self::Expr(expr) => expr.walk_mut(on_stmt, on_expr),
+            Self::If(condition, x, _) => {
+                condition.walk_mut(on_stmt, on_expr);
+                x.0.walk_mut(on_stmt, on_expr);
+                if let Some(ref mut stmt) = x.1 {
+                    stmt.walk_mut(on_stmt, on_expr);
+                }
+            }
+            Self::Switch(expr, x, _) => {
+                expr.walk_mut(on_stmt, on_expr);
+                for stmt in x.0.values_mut() {
+                    stmt.walk_mut(on_stmt, on_expr);
+                }
+                if let Some(ref mut stmt) = x.1 {
+                    stmt.walk_mut(on_stmt, on_expr);
+                }
+                for (_, stmt) in x.2.iter_mut() {
+                    stmt.walk_mut(on_stmt, on_expr);
+                }
+            }
+            Self::While(condition, block, _) | Self::Do(block, condition, _, _) => {
+                condition.walk_mut(on_stmt, on_expr);
+                block.0.walk_mut(on_stmt, on_expr);
+            }
+            Self::For(iterable, x, _) => {
+                iterable.walk_mut(on_stmt, on_expr);
+                x.2.walk_mut(on_stmt, on_expr);
+            }
+            Self::Let(_, expr, _, _)
+            | Self::Const(_, expr, _, _)
+            | Self::Break(_, expr, _)
+            | Self::Return(_, expr, _) => {
+                if let Some(expr) = expr {
+                    expr.walk_mut(on_stmt, on_expr);
+                }
+            }
+            #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+            Self::LetPattern(x, _, _, _) => x.1.walk_mut(on_stmt, on_expr),
+            Self::Assignment(x, _) => {
+                x.0.walk_mut(on_stmt, on_expr);
+                x.2.walk_mut(on_stmt, on_expr);
+            }
+            Self::Block(block, _) => {
+                for stmt in block.iter_mut() {
+                    stmt.walk_mut(on_stmt, on_expr);
+                }
+            }
+            Self::TryCatch(x, _, _) => {
+                x.0.walk_mut(on_stmt, on_expr);
+                for (_, _, guard, block) in x.1.iter_mut() {
+                    if let Some(guard) = guard {
+                        guard.walk_mut(on_stmt, on_expr);
+                    }
+                    block.walk_mut(on_stmt, on_expr);
+                }
+            }
+
+            #[cfg(not(feature = "no_module"))]
+            Self::Import(expr, _, _) => expr.walk_mut(on_stmt, on_expr),
+            #[cfg(not(feature = "no_module"))]
+            Self::Export(_, _) => (),
+
+            #[cfg(not(feature = "no_closure"))]
+            Self::Share(_) => (),
+        }
+
+        on_stmt(self);
+    }
+    /// Collect all the function calls made in this statement.
+    fn collect_fn_calls<'a>(&'a self, calls: &mut Vec<FnCallSite<'a>>) {
+        match self {
+            Self::Noop(_) | Self::Continue(_, _) => (),
+            Self::Expr(expr) => expr.collect_fn_calls(false, calls),
+            Self::If(condition, x, _) => {
+                condition.collect_fn_calls(false, calls);
+                x.0.collect_fn_calls(calls);
+                if let Some(ref stmt) = x.1 {
+                    stmt.collect_fn_calls(calls);
+                }
+            }
+            Self::Switch(expr, x, _) => {
+                expr.collect_fn_calls(false, calls);
+                x.0.values().for_each(|stmt| stmt.collect_fn_calls(calls));
+                if let Some(ref stmt) = x.1 {
+                    stmt.collect_fn_calls(calls);
+                }
+                x.2.iter()
+                    .for_each(|(_, stmt)| stmt.collect_fn_calls(calls));
+            }
+            Self::While(condition, block, _) | Self::Do(block, condition, _, _) => {
+                condition.collect_fn_calls(false, calls);
+                block.0.collect_fn_calls(calls);
+            }
+            Self::For(iterable, x, _) => {
+                iterable.collect_fn_calls(false, calls);
+                x.2.collect_fn_calls(calls);
+            }
+            Self::Let(_, expr, _, _) | Self::Const(_, expr, _, _) | Self::Break(_, expr, _) => {
+                if let Some(expr) = expr {
+                    expr.collect_fn_calls(false, calls);
+                }
+            }
+            Self::Return(_, expr, _) => {
+                if let Some(expr) = expr {
+                    expr.collect_fn_calls(false, calls);
+                }
+            }
+            #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+            Self::LetPattern(x, _, _, _) => x.1.collect_fn_calls(false, calls),
+            Self::Assignment(x, _) => {
+                x.0.collect_fn_calls(false, calls);
+                x.2.collect_fn_calls(false, calls);
+            }
+            Self::Block(block, _) => block.iter().for_each(|stmt| stmt.collect_fn_calls(calls)),
+            Self::TryCatch(x, _, _) => {
+                x.0.collect_fn_calls(calls);
+                x.1.iter().for_each(|(_, _, guard, block)| {
+                    if let Some(guard) = guard {
+                        guard.collect_fn_calls(false, calls);
+                    }
+                    block.collect_fn_calls(calls);
+                });
+            }
+
+            #[cfg(not(feature = "no_module"))]
+            Self::Import(expr, _, _) => expr.collect_fn_calls(false, calls),
+            #[cfg(not(feature = "no_module"))]
+            Self::Export(_, _) => (),
+
+            #[cfg(not(feature = "no_closure"))]
+            Self::Share(_) => (),
+        }
+    }
 }
 
 /// _(INTERNALS)_ A custom syntax definition.
@@ -938,13 +1573,45 @@ pub struct FnCallExpr {
     /// Namespace of the function, if any. Boxed because it occurs rarely.
     pub namespace: Option<NamespaceRef>,
     /// Function name.
-    /// Use [`Cow<'static, str>`][Cow] because a lot of operators (e.g. `==`, `>=`) are implemented as
-    /// function calls and the function names are predictable, so no need to allocate a new [`String`].
-    pub name: Cow<'static, str>,
+    /// Interned during parsing, so that all calls to the same function share the same string.
+    pub name: ImmutableString,
     /// List of function call arguments.
     pub args: StaticVec<Expr>,
 }
 
+/// _(INTERNALS)_ The key of a property in an object map literal.
+/// Exported under the `internals` feature only.
+///
+/// ## WARNING
+///
+/// This type is volatile and may change.
+#[derive(Debug, Clone)]
+pub enum MapKey {
+    /// Property name.
+    Name(Ident),
+    /// `[` expr `]` - computed at runtime.
+    Computed(Expr),
+}
+
+impl MapKey {
+    /// Get the property name, if it is not computed.
+    #[inline(always)]
+    pub fn name(&self) -> Option<&ImmutableString> {
+        match self {
+            Self::Name(x) => Some(&x.name),
+            Self::Computed(_) => None,
+        }
+    }
+    /// Get the [position][Position] of the key.
+    #[inline(always)]
+    pub fn position(&self) -> Position {
+        match self {
+            Self::Name(x) => x.pos,
+            Self::Computed(expr) => expr.position(),
+        }
+    }
+}
+
 /// _(INTERNALS)_ An expression sub-tree.
 /// Exported under the `internals` feature only.
 ///
@@ -972,8 +1639,8 @@ pub enum Expr {
     FnPointer(ImmutableString, Position),
     /// [ expr, ... ]
     Array(Box<StaticVec<Expr>>, Position),
-    /// #{ name:expr, ... }
-    Map(Box<StaticVec<(Ident, Expr)>>, Position),
+    /// #{ name:expr, \[expr\]:expr, ... }
+    Map(Box<StaticVec<(MapKey, Expr)>>, Position),
     /// ()
     Unit(Position),
     /// Variable access - (optional index, optional (hash, modules), variable name)
@@ -990,16 +1657,20 @@ pub enum Expr {
     Stmt(Box<StaticVec<Stmt>>, Position),
     /// func `(` expr `,` ... `)`
     FnCall(Box<FnCallExpr>, Position),
-    /// lhs `.` rhs
-    Dot(Box<BinaryExpr>, Position),
-    /// expr `[` expr `]`
-    Index(Box<BinaryExpr>, Position),
+    /// lhs `.` rhs | lhs `?.` rhs - bool indicates whether it is null-propagating (`?.`)
+    Dot(Box<BinaryExpr>, bool, Position),
+    /// expr `[` expr `]` | expr `?[` expr `]` - bool indicates whether it is null-propagating (`?[`)
+    Index(Box<BinaryExpr>, bool, Position),
+    /// `[` expr `,` expr `,` ... `]` - multiple indices, only valid as the index of an [`Index`][Expr::Index]
+    IndexArgs(Box<StaticVec<Expr>>, Position),
     /// lhs `in` rhs
     In(Box<BinaryExpr>, Position),
     /// lhs `&&` rhs
     And(Box<BinaryExpr>, Position),
     /// lhs `||` rhs
     Or(Box<BinaryExpr>, Position),
+    /// lhs `??` rhs
+    Coalesce(Box<BinaryExpr>, Position),
     /// Custom syntax
     Custom(Box<CustomExpr>, Position),
 }
@@ -1042,13 +1713,10 @@ impl Expr {
 
             #[cfg(not(feature = "no_object"))]
             Self::Map(x, _) if self.is_constant() => {
-                let mut map = Map::with_capacity(crate::stdlib::cmp::max(
-                    crate::engine::TYPICAL_MAP_SIZE,
-                    x.len(),
-                ));
+                let mut map = Map::with_capacity(x.len());
                 map.extend(
                     x.iter()
-                        .map(|(k, v)| (k.name.clone(), v.get_constant_value().unwrap())),
+                        .map(|(k, v)| (k.name().unwrap().clone(), v.get_constant_value().unwrap())),
                 );
                 Dynamic(Union::Map(Box::new(map), AccessMode::ReadOnly))
             }
@@ -1077,17 +1745,20 @@ impl Expr {
             Self::StringConstant(_, pos) => *pos,
             Self::FnPointer(_, pos) => *pos,
             Self::Array(_, pos) => *pos,
+            Self::IndexArgs(_, pos) => *pos,
             Self::Map(_, pos) => *pos,
             Self::Property(x) => (x.2).pos,
             Self::Stmt(_, pos) => *pos,
             Self::Variable(x) => (x.2).pos,
             Self::FnCall(_, pos) => *pos,
 
-            Self::And(x, _) | Self::Or(x, _) | Self::In(x, _) => x.lhs.position(),
+            Self::And(x, _) | Self::Or(x, _) | Self::Coalesce(x, _) | Self::In(x, _) => {
+                x.lhs.position()
+            }
 
             Self::Unit(pos) => *pos,
 
-            Self::Dot(x, _) | Self::Index(x, _) => x.lhs.position(),
+            Self::Dot(x, _, _) | Self::Index(x, _, _) => x.lhs.position(),
 
             Self::Custom(_, pos) => *pos,
         }
@@ -1105,14 +1776,17 @@ impl Expr {
             Self::StringConstant(_, pos) => *pos = new_pos,
             Self::FnPointer(_, pos) => *pos = new_pos,
             Self::Array(_, pos) => *pos = new_pos,
+            Self::IndexArgs(_, pos) => *pos = new_pos,
             Self::Map(_, pos) => *pos = new_pos,
             Self::Variable(x) => (x.2).pos = new_pos,
             Self::Property(x) => (x.2).pos = new_pos,
             Self::Stmt(_, pos) => *pos = new_pos,
             Self::FnCall(_, pos) => *pos = new_pos,
-            Self::And(_, pos) | Self::Or(_, pos) | Self::In(_, pos) => *pos = new_pos,
+            Self::And(_, pos) | Self::Or(_, pos) | Self::Coalesce(_, pos) | Self::In(_, pos) => {
+                *pos = new_pos
+            }
             Self::Unit(pos) => *pos = new_pos,
-            Self::Dot(_, pos) | Self::Index(_, pos) => *pos = new_pos,
+            Self::Dot(_, _, pos) | Self::Index(_, _, pos) => *pos = new_pos,
             Self::Custom(_, pos) => *pos = new_pos,
         }
 
@@ -1123,13 +1797,21 @@ impl Expr {
     /// A pure expression has no side effects.
     pub fn is_pure(&self) -> bool {
         match self {
-            Self::Array(x, _) => x.iter().all(Self::is_pure),
-
-            Self::Map(x, _) => x.iter().map(|(_, v)| v).all(Self::is_pure),
-
-            Self::Index(x, _) | Self::And(x, _) | Self::Or(x, _) | Self::In(x, _) => {
-                x.lhs.is_pure() && x.rhs.is_pure()
-            }
+            Self::Array(x, _) | Self::IndexArgs(x, _) => x.iter().all(Self::is_pure),
+
+            Self::Map(x, _) => x.iter().all(|(k, v)| {
+                let key_is_pure = match k {
+                    MapKey::Computed(k) => k.is_pure(),
+                    MapKey::Name(_) => true,
+                };
+                key_is_pure && v.is_pure()
+            }),
+
+            Self::Index(x, _, _)
+            | Self::And(x, _)
+            | Self::Or(x, _)
+            | Self::Coalesce(x, _)
+            | Self::In(x, _) => x.lhs.is_pure() && x.rhs.is_pure(),
 
             Self::Stmt(x, _) => x.iter().all(Stmt::is_pure),
 
@@ -1163,8 +1845,10 @@ impl Expr {
             // An array literal is constant if all items are constant
             Self::Array(x, _) => x.iter().all(Self::is_constant),
 
-            // An map literal is constant if all items are constant
-            Self::Map(x, _) => x.iter().map(|(_, expr)| expr).all(Self::is_constant),
+            // An map literal is constant if all items are constant and no key is computed
+            Self::Map(x, _) => x
+                .iter()
+                .all(|(k, expr)| k.name().is_some() && expr.is_constant()),
 
             // Check in expression
             Self::In(x, _) => match (&x.lhs, &x.rhs) {
@@ -1180,7 +1864,7 @@ impl Expr {
     pub fn is_valid_postfix(&self, token: &Token) -> bool {
         match token {
             #[cfg(not(feature = "no_object"))]
-            Token::Period => return true,
+            Token::Period | Token::Elvis => return true,
             _ => (),
         }
 
@@ -1190,29 +1874,35 @@ impl Expr {
 
             Self::DynamicConstant(_, _)
             | Self::BoolConstant(_, _)
-            | Self::IntegerConstant(_, _)
             | Self::CharConstant(_, _)
             | Self::FnPointer(_, _)
             | Self::In(_, _)
             | Self::And(_, _)
             | Self::Or(_, _)
+            | Self::Coalesce(_, _)
+            | Self::IndexArgs(_, _)
             | Self::Unit(_) => false,
 
-            Self::StringConstant(_, _)
-            | Self::FnCall(_, _)
+            Self::FnCall(_, _) | Self::Dot(_, _, _) | Self::Index(_, _, _) => match token {
+                #[cfg(not(feature = "no_index"))]
+                Token::LeftBracket | Token::QuestionBracket => true,
+                Token::LeftParen => true,
+                _ => false,
+            },
+
+            Self::IntegerConstant(_, _)
+            | Self::StringConstant(_, _)
             | Self::Stmt(_, _)
-            | Self::Dot(_, _)
-            | Self::Index(_, _)
             | Self::Array(_, _)
             | Self::Map(_, _) => match token {
                 #[cfg(not(feature = "no_index"))]
-                Token::LeftBracket => true,
+                Token::LeftBracket | Token::QuestionBracket => true,
                 _ => false,
             },
 
             Self::Variable(_) => match token {
                 #[cfg(not(feature = "no_index"))]
-                Token::LeftBracket => true,
+                Token::LeftBracket | Token::QuestionBracket => true,
                 Token::LeftParen => true,
                 Token::Bang => true,
                 Token::DoubleColon => true,
@@ -1221,7 +1911,7 @@ impl Expr {
 
             Self::Property(_) => match token {
                 #[cfg(not(feature = "no_index"))]
-                Token::LeftBracket => true,
+                Token::LeftBracket | Token::QuestionBracket => true,
                 Token::LeftParen => true,
                 _ => false,
             },
@@ -1229,6 +1919,139 @@ impl Expr {
             Self::Custom(_, _) => false,
         }
     }
+    /// _(INTERNALS)_ Create a call to a function (not qualified by a namespace).
+    /// Exported under the `internals` feature only.
+    #[cfg(feature = "internals")]
+    pub fn new_fn_call(
+        name: impl Into<ImmutableString>,
+        args: impl IntoIterator<Item = Expr>,
+        pos: Position,
+    ) -> Self {
+        let name = name.into();
+        let args: StaticVec<_> = args.into_iter().collect();
+
+        // Script functions can only be valid identifiers
+        let hash_script = if crate::token::is_valid_identifier(name.chars()) {
+            crate::calc_script_fn_hash(crate::stdlib::iter::empty(), &name, args.len())
+        } else {
+            None
+        };
+
+        Self::FnCall(
+            Box::new(FnCallExpr {
+                name,
+                hash_script,
+                args,
+                ..Default::default()
+            }),
+            pos,
+        )
+    }
+    /// Visit this expression, and all statements and expressions nested within it, mutably.
+    ///
+    /// Nested statements and expressions are visited before the statements and expressions
+    /// containing them.
+    #[cfg(feature = "internals")]
+    pub(crate) fn walk_mut(
+        &mut self,
+        on_stmt: &mut dyn FnMut(&mut Stmt),
+        on_expr: &mut dyn FnMut(&mut Expr),
+    ) {
+        match self {
+            Self::FnCall(x, _) => {
+                for expr in x.args.iter_mut() {
+                    expr.walk_mut(on_stmt, on_expr);
+                }
+            }
+            Self::Dot(x, _, _)
+            | Self::Index(x, _, _)
+            | Self::In(x, _)
+            | Self::And(x, _)
+            | Self::Or(x, _)
+            | Self::Coalesce(x, _) => {
+                x.lhs.walk_mut(on_stmt, on_expr);
+                x.rhs.walk_mut(on_stmt, on_expr);
+            }
+            Self::Array(x, _) | Self::IndexArgs(x, _) => {
+                for expr in x.iter_mut() {
+                    expr.walk_mut(on_stmt, on_expr);
+                }
+            }
+            Self::Map(x, _) => {
+                for (key, expr) in x.iter_mut() {
+                    if let MapKey::Computed(key) = key {
+                        key.walk_mut(on_stmt, on_expr);
+                    }
+                    expr.walk_mut(on_stmt, on_expr);
+                }
+            }
+            Self::Stmt(x, _) => {
+                for stmt in x.iter_mut() {
+                    stmt.walk_mut(on_stmt, on_expr);
+                }
+            }
+            Self::Custom(x, _) => {
+                for expr in x.keywords.iter_mut() {
+                    expr.walk_mut(on_stmt, on_expr);
+                }
+            }
+            _ => (),
+        }
+
+        on_expr(self);
+    }
+    /// Collect all the function calls made in this expression.
+    ///
+    /// If `is_method` is `true`, a function call is a method call on the object on the left of a
+    /// dot (i.e. the expression is part of the right-hand side of a [`Dot`][Expr::Dot]).
+    fn collect_fn_calls<'a>(&'a self, is_method: bool, calls: &mut Vec<FnCallSite<'a>>) {
+        match self {
+            Self::FnCall(x, pos) => {
+                calls.push(FnCallSite {
+                    namespace: x.namespace.as_ref().map(|namespace| {
+                        namespace
+                            .iter()
+                            .map(|Ident { name, .. }| name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(Token::DoubleColon.syntax().as_ref())
+                    }),
+                    name: x.name.as_str(),
+                    num_args: x.args.len() + if is_method { 1 } else { 0 },
+                    pos: *pos,
+                });
+                x.args
+                    .iter()
+                    .for_each(|expr| expr.collect_fn_calls(false, calls));
+            }
+            Self::Dot(x, _, _) => {
+                x.lhs.collect_fn_calls(is_method, calls);
+                x.rhs.collect_fn_calls(true, calls);
+            }
+            Self::Index(x, _, _) => {
+                x.lhs.collect_fn_calls(is_method, calls);
+                x.rhs.collect_fn_calls(false, calls);
+            }
+            Self::In(x, _) | Self::And(x, _) | Self::Or(x, _) | Self::Coalesce(x, _) => {
+                x.lhs.collect_fn_calls(false, calls);
+                x.rhs.collect_fn_calls(false, calls);
+            }
+            Self::Array(x, _) | Self::IndexArgs(x, _) => x
+                .iter()
+                .for_each(|expr| expr.collect_fn_calls(false, calls)),
+            Self::Map(x, _) => x.iter().for_each(|(key, expr)| {
+                if let MapKey::Computed(key) = key {
+                    key.collect_fn_calls(false, calls);
+                }
+                expr.collect_fn_calls(false, calls);
+            }),
+            Self::Stmt(x, _) => x.iter().for_each(|stmt| stmt.collect_fn_calls(calls)),
+            Self::Custom(x, _) => x
+                .keywords
+                .iter()
+                .for_each(|expr| expr.collect_fn_calls(false, calls)),
+            _ => (),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1245,9 +2068,34 @@ mod tests {
         assert_eq!(size_of::<Option<crate::ast::Expr>>(), 16);
         assert_eq!(size_of::<crate::ast::Stmt>(), 32);
         assert_eq!(size_of::<Option<crate::ast::Stmt>>(), 32);
-        assert_eq!(size_of::<crate::Scope>(), 48);
-        assert_eq!(size_of::<crate::LexError>(), 56);
+        assert_eq!(size_of::<crate::Scope>(), 96);
+        assert_eq!(size_of::<crate::LexError>(), 48);
         assert_eq!(size_of::<crate::ParseError>(), 16);
-        assert_eq!(size_of::<crate::EvalAltResult>(), 72);
+        assert_eq!(size_of::<crate::EvalAltResult>(), 64);
+    }
+
+    /// This test is to make sure function names are interned, not allocated per call site.
+    #[test]
+    #[allow(deprecated)]
+    fn check_fn_call_names_shared() {
+        use super::{Expr, Stmt};
+
+        let script = "foo(x + 1);\n".repeat(10_000);
+        let ast = crate::Engine::new_raw().compile(&script).unwrap();
+
+        let names: Vec<_> = ast
+            .statements()
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::Expr(Expr::FnCall(x, _)) => match &x.args[0] {
+                    Expr::FnCall(op, _) => (x.name.as_ptr(), op.name.as_ptr()),
+                    expr => unreachable!("expecting Expr::FnCall, but gets {:?}", expr),
+                },
+                stmt => unreachable!("expecting Stmt::Expr, but gets {:?}", stmt),
+            })
+            .collect();
+
+        assert_eq!(names.len(), 10_000);
+        assert!(names.iter().all(|&name| name == names[0]));
     }
 }
@@ -0,0 +1,128 @@
+//! Module defining the record of functions called during an evaluation.
+
+use crate::stdlib::collections::HashMap;
+use crate::token::Token;
+use crate::{ImmutableString, StaticVec};
+
+/// Record of the functions called during evaluations, with the number of calls to each function
+/// by name and number of arguments.
+///
+/// Filled by [`Engine::eval_with_call_audit`][crate::Engine::eval_with_call_audit] and
+/// [`Engine::eval_ast_with_call_audit`][crate::Engine::eval_ast_with_call_audit].
+///
+/// By default, only calls to native Rust functions (including getters, setters and indexers,
+/// under their internal names) are recorded, but not calls to operators.
+/// Functions in namespaces are recorded with their qualified names, e.g. `foo::bar`.
+///
+/// Calls made by native Rust functions themselves (e.g. via
+/// [`NativeCallContext::call_fn_dynamic_raw`][crate::NativeCallContext::call_fn_dynamic_raw])
+/// are not recorded.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use rhai::{CallAudit, Engine, RegisterFn, Scope, INT};
+///
+/// let mut engine = Engine::new();
+/// engine.register_fn("double", |x: INT| x * 2);
+///
+/// let mut audit = CallAudit::new();
+///
+/// let result: INT = engine.eval_with_call_audit(
+///     &mut Scope::new(),
+///     "double(1) + double(2)",
+///     &mut audit,
+/// )?;
+///
+/// assert_eq!(result, 6);
+/// assert_eq!(audit.count("double", 1), 2);
+/// assert_eq!(audit.count("+", 2), 0);     // operators are not recorded by default
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct CallAudit {
+    /// Record calls to operators?
+    operators: bool,
+    /// Record calls to script-defined functions?
+    script_fns: bool,
+    /// Number of calls by function name and number of arguments.
+    calls: HashMap<ImmutableString, StaticVec<(usize, u64)>>,
+}
+
+impl CallAudit {
+    /// Create a new, empty [`CallAudit`] recording calls to native Rust functions only.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Set whether calls to operators (e.g. `+`, `==`, `+=`) are recorded.
+    #[inline(always)]
+    pub fn set_include_operators(&mut self, enable: bool) -> &mut Self {
+        self.operators = enable;
+        self
+    }
+    /// Set whether calls to script-defined functions are recorded.
+    #[inline(always)]
+    pub fn set_include_script_fns(&mut self, enable: bool) -> &mut Self {
+        self.script_fns = enable;
+        self
+    }
+    /// Record a call to a native Rust function.
+    #[inline]
+    pub(crate) fn record_native(&mut self, name: &str, num_args: usize) {
+        match Token::lookup_from_syntax(name) {
+            Some(token) if !self.operators && token.is_operator() => (),
+            _ => self.record(name, num_args),
+        }
+    }
+    /// Record a call to a script-defined function.
+    #[inline(always)]
+    pub(crate) fn record_script(&mut self, name: &str, num_args: usize) {
+        if self.script_fns {
+            self.record(name, num_args);
+        }
+    }
+    /// Increment the count of a function.
+    fn record(&mut self, name: &str, num_args: usize) {
+        if !self.calls.contains_key(name) {
+            self.calls.insert(name.into(), Default::default());
+        }
+
+        let counts = self.calls.get_mut(name).unwrap();
+
+        match counts.iter_mut().find(|(n, _)| *n == num_args) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((num_args, 1)),
+        }
+    }
+    /// Number of calls recorded to a function with a particular number of arguments.
+    #[inline]
+    pub fn count(&self, name: &str, num_args: usize) -> u64 {
+        self.calls
+            .get(name)
+            .and_then(|counts| counts.iter().find(|(n, _)| *n == num_args))
+            .map_or(0, |(_, count)| *count)
+    }
+    /// Is no call recorded?
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+    /// Remove all calls recorded, keeping the settings.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.calls.clear();
+    }
+    /// Get an iterator over all the functions called, as `(name, number of arguments, count)`,
+    /// in no particular order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize, u64)> {
+        self.calls.iter().flat_map(|(name, counts)| {
+            counts
+                .iter()
+                .map(move |&(num_args, count)| (name.as_str(), num_args, count))
+        })
+    }
+}
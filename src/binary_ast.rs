@@ -0,0 +1,1355 @@
+//! Module implementing saving and loading a compiled [`AST`] in a compact binary format.
+
+#![cfg(feature = "binary_ast")]
+
+use crate::ast::{
+    BinaryExpr, CustomExpr, Expr, FnCallExpr, Ident, MapKey, ReturnType, Stmt, SwitchRange,
+};
+use crate::dynamic::{AccessMode, Union};
+use crate::module::NamespaceRef;
+use crate::stdlib::{
+    borrow::Cow,
+    boxed::Box,
+    collections::HashMap,
+    convert::TryFrom,
+    fmt,
+    hash::Hasher,
+    num::{NonZeroU64, NonZeroUsize},
+    string::{String, ToString},
+    vec::Vec,
+};
+use crate::utils::{get_hasher, StraightHasherBuilder};
+use crate::{
+    CharRange, Dynamic, Engine, EvalAltResult, FnPtr, ImmutableString, Module, Position, StaticVec,
+    AST, INT,
+};
+
+#[cfg(not(feature = "no_function"))]
+use crate::{ast::ScriptFnDef, FnAccess};
+
+#[cfg(not(feature = "no_float"))]
+use crate::FLOAT;
+
+#[cfg(not(feature = "no_object"))]
+use crate::Map;
+
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+use crate::ast::Pattern;
+
+/// Magic bytes at the beginning of a compiled [`AST`].
+const MAGIC: &[u8] = b"RHAI";
+
+/// Version of the binary format.
+///
+/// Bump this whenever the encoding of any part of the [`AST`] changes.
+pub const BINARY_AST_VERSION: u16 = 9;
+
+/// Feature flags that change the shape of an [`AST`], and their bits in the header.
+const FEATURES: &[(&str, bool)] = &[
+    ("only_i32", cfg!(feature = "only_i32")),
+    ("no_float", cfg!(feature = "no_float")),
+    ("f32_float", cfg!(feature = "f32_float")),
+    ("no_index", cfg!(feature = "no_index")),
+    ("no_object", cfg!(feature = "no_object")),
+    ("no_function", cfg!(feature = "no_function")),
+    ("no_closure", cfg!(feature = "no_closure")),
+    ("no_module", cfg!(feature = "no_module")),
+];
+
+/// Error when saving or loading a compiled [`AST`] in binary format.
+///
+/// Available under the `binary_ast` feature only.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum BinaryAstError {
+    /// The data is not a compiled [`AST`], or is corrupted.
+    Malformed,
+    /// The data uses an unsupported version of the binary format.
+    /// Wrapped value is the version.
+    Version(u16),
+    /// The data was compiled with different feature flags.
+    /// Wrapped values are the features turned on when compiling but not when loading,
+    /// and those turned on when loading but not when compiling.
+    Features(Vec<String>, Vec<String>),
+    /// The data was compiled with an incompatible hashing scheme (e.g. `no_std` vs. `std`).
+    Hasher,
+    /// A constant value in the [`AST`] is of a type that cannot be saved.
+    /// Wrapped value is the type name.
+    UnsupportedValue(String),
+    /// The [`AST`] uses a custom syntax that is not registered with the loading [`Engine`].
+    /// Wrapped value is the custom syntax keyword.
+    CustomSyntax(String),
+    /// The [`AST`] is nested deeper than the maximum expression depth of the loading [`Engine`].
+    TooDeep,
+}
+
+impl BinaryAstError {
+    /// Get the description of this error.
+    pub fn desc(&self) -> &str {
+        match self {
+            Self::Malformed => "Invalid compiled AST",
+            Self::Version(_) => "Unsupported version of compiled AST",
+            Self::Features(_, _) => "Compiled AST requires different feature flags",
+            Self::Hasher => "Compiled AST uses an incompatible hashing scheme",
+            Self::UnsupportedValue(_) => "Cannot save a constant value in compiled AST",
+            Self::CustomSyntax(_) => "Custom syntax in compiled AST is not registered",
+            Self::TooDeep => "Compiled AST is nested too deeply",
+        }
+    }
+}
+
+impl fmt::Display for BinaryAstError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Version(v) => write!(
+                f,
+                "{}: version {} (expecting {})",
+                self.desc(),
+                v,
+                BINARY_AST_VERSION
+            ),
+            Self::Features(compiled, loading) => {
+                f.write_str(self.desc())?;
+                if !compiled.is_empty() {
+                    write!(f, "; compiled with {}", compiled.join(", "))?;
+                }
+                if !loading.is_empty() {
+                    write!(f, "; loading with {}", loading.join(", "))?;
+                }
+                Ok(())
+            }
+            Self::UnsupportedValue(s) | Self::CustomSyntax(s) => {
+                write!(f, "{}: '{}'", self.desc(), s)
+            }
+            _ => f.write_str(self.desc()),
+        }
+    }
+}
+
+impl crate::stdlib::error::Error for BinaryAstError {
+    fn description(&self) -> &str {
+        self.desc()
+    }
+}
+
+type Result<T> = crate::stdlib::result::Result<T, BinaryAstError>;
+
+/// Fingerprint of the hashing scheme used for pre-calculated hashes in an [`AST`].
+fn hasher_fingerprint() -> u64 {
+    let mut s = get_hasher();
+    s.write(MAGIC);
+    s.write_u64(BINARY_AST_VERSION as u64);
+    s.finish()
+}
+
+/// Bit mask of the feature flags that change the shape of an [`AST`].
+fn feature_flags() -> u64 {
+    FEATURES
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, on))| *on)
+        .fold(0, |flags, (i, _)| flags | (1 << i))
+}
+
+/// Output buffer for a compiled [`AST`].
+struct Writer {
+    buf: Vec<u8>,
+    strings: HashMap<ImmutableString, u64>,
+}
+
+impl Writer {
+    fn byte(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+    fn bool(&mut self, value: bool) {
+        self.byte(value as u8);
+    }
+    /// Write an unsigned integer in LEB128 format.
+    fn varint(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.byte(byte);
+                break;
+            }
+            self.byte(byte | 0x80);
+        }
+    }
+    fn usize(&mut self, value: usize) {
+        self.varint(value as u64);
+    }
+    /// Write a fixed-size integer, for values (such as hashes) that are not small.
+    fn fixed(&mut self, value: u64) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+    /// Write a string, or its index if it has been written before.
+    fn str(&mut self, value: &ImmutableString) {
+        if let Some(&index) = self.strings.get(value) {
+            self.varint(index + 1);
+        } else {
+            self.varint(0);
+            self.usize(value.len());
+            self.buf.extend_from_slice(value.as_bytes());
+            let index = self.strings.len() as u64;
+            self.strings.insert(value.clone(), index);
+        }
+    }
+    fn pos(&mut self, pos: Position) {
+        self.usize(pos.line().unwrap_or(0));
+        self.usize(pos.position().unwrap_or(0));
+    }
+}
+
+/// Input buffer for a compiled [`AST`].
+struct Reader<'a> {
+    engine: &'a Engine,
+    bytes: &'a [u8],
+    strings: Vec<ImmutableString>,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8]> {
+        if len > self.bytes.len() {
+            return Err(BinaryAstError::Malformed);
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+    /// Enter a nested statement or expression, failing if it is nested too deeply.
+    fn enter(&mut self) -> Result<()> {
+        self.depth += 1;
+        if self.max_depth > 0 && self.depth > self.max_depth {
+            return Err(BinaryAstError::TooDeep);
+        }
+        Ok(())
+    }
+    /// Leave a nested statement or expression.
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+    fn bool(&mut self) -> Result<bool> {
+        match self.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(BinaryAstError::Malformed),
+        }
+    }
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0_u64;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(BinaryAstError::Malformed)
+    }
+    fn usize(&mut self) -> Result<usize> {
+        usize::try_from(self.varint()?).map_err(|_| BinaryAstError::Malformed)
+    }
+    /// Read the length of a list, making sure that it is not larger than the remaining data
+    /// so as not to allocate excessive memory for malformed data.
+    fn len(&mut self) -> Result<usize> {
+        match self.usize()? {
+            len if len > self.bytes.len() => Err(BinaryAstError::Malformed),
+            len => Ok(len),
+        }
+    }
+    fn fixed(&mut self) -> Result<u64> {
+        let mut bytes = [0_u8; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+    fn str(&mut self) -> Result<ImmutableString> {
+        match self.usize()? {
+            0 => {
+                let len = self.len()?;
+                let s: ImmutableString = crate::stdlib::str::from_utf8(self.take(len)?)
+                    .map_err(|_| BinaryAstError::Malformed)?
+                    .into();
+                self.strings.push(s.clone());
+                Ok(s)
+            }
+            index => self
+                .strings
+                .get(index - 1)
+                .cloned()
+                .ok_or(BinaryAstError::Malformed),
+        }
+    }
+    fn pos(&mut self) -> Result<Position> {
+        let line = u16::try_from(self.usize()?).map_err(|_| BinaryAstError::Malformed)?;
+        let pos = u16::try_from(self.usize()?).map_err(|_| BinaryAstError::Malformed)?;
+
+        Ok(if line == 0 {
+            Position::NONE
+        } else {
+            Position::new(line, pos)
+        })
+    }
+}
+
+/// A type that can be written into a compiled [`AST`].
+trait Encode {
+    fn encode(&self, w: &mut Writer) -> Result<()>;
+}
+
+/// A type that can be read from a compiled [`AST`].
+trait Decode: Sized {
+    fn decode(r: &mut Reader) -> Result<Self>;
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.bool(self.is_some());
+        self.as_ref().map_or(Ok(()), |x| x.encode(w))
+    }
+}
+impl<T: Decode> Decode for Option<T> {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        Ok(if r.bool()? { Some(T::decode(r)?) } else { None })
+    }
+}
+
+impl<T: Encode> Encode for Box<T> {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        self.as_ref().encode(w)
+    }
+}
+impl<T: Decode> Decode for Box<T> {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        Ok(Box::new(T::decode(r)?))
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.usize(self.len());
+        self.iter().try_for_each(|x| x.encode(w))
+    }
+}
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        (0..r.len()?).map(|_| T::decode(r)).collect()
+    }
+}
+impl<T: Decode> Decode for StaticVec<T> {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        (0..r.len()?).map(|_| T::decode(r)).collect()
+    }
+}
+
+impl Encode for ImmutableString {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.str(self);
+        Ok(())
+    }
+}
+impl Decode for ImmutableString {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        r.str()
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.str(&self.as_str().into());
+        Ok(())
+    }
+}
+impl Decode for String {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        Ok(r.str()?.to_string())
+    }
+}
+
+impl Encode for Position {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.pos(*self);
+        Ok(())
+    }
+}
+impl Decode for Position {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        r.pos()
+    }
+}
+
+impl Encode for Ident {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.str(&self.name);
+        w.pos(self.pos);
+        Ok(())
+    }
+}
+impl Decode for Ident {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            name: r.str()?,
+            pos: r.pos()?,
+        })
+    }
+}
+
+impl Encode for MapKey {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match self {
+            Self::Name(name) => {
+                w.byte(0);
+                name.encode(w)
+            }
+            Self::Computed(expr) => {
+                w.byte(1);
+                expr.encode(w)
+            }
+        }
+    }
+}
+impl Decode for MapKey {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        Ok(match r.byte()? {
+            0 => Self::Name(Ident::decode(r)?),
+            1 => Self::Computed(Expr::decode(r)?),
+            _ => return Err(BinaryAstError::Malformed),
+        })
+    }
+}
+
+impl Encode for NamespaceRef {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.usize(self.index().map_or(0, NonZeroUsize::get));
+        self.as_ref().encode(w)?;
+        self.shadowing_var().encode(w)
+    }
+}
+impl Decode for NamespaceRef {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        let _index = NonZeroUsize::new(r.usize()?);
+        let mut _namespace: Self = StaticVec::<Ident>::decode(r)?.into();
+        let _shadowing_var = Option::<Position>::decode(r)?;
+        #[cfg(not(feature = "no_module"))]
+        {
+            _namespace.set_index(_index);
+            _namespace.set_shadowing_var(_shadowing_var);
+        }
+        Ok(_namespace)
+    }
+}
+
+impl Encode for INT {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        // Zig-zag encoding so that small negative numbers are also small
+        #[allow(clippy::useless_conversion)]
+        let value = i64::from(*self);
+        w.varint(((value << 1) ^ (value >> 63)) as u64);
+        Ok(())
+    }
+}
+impl Decode for INT {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        let value = r.varint()?;
+        let value = ((value >> 1) as i64) ^ -((value & 1) as i64);
+        INT::try_from(value).map_err(|_| BinaryAstError::Malformed)
+    }
+}
+
+#[cfg(not(feature = "no_float"))]
+impl Encode for FLOAT {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.buf.extend_from_slice(&self.to_le_bytes());
+        Ok(())
+    }
+}
+#[cfg(not(feature = "no_float"))]
+impl Decode for FLOAT {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        let mut bytes = [0_u8; crate::stdlib::mem::size_of::<FLOAT>()];
+        let len = bytes.len();
+        bytes.copy_from_slice(r.take(len)?);
+        Ok(FLOAT::from_le_bytes(bytes))
+    }
+}
+
+impl Encode for char {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.varint(*self as u64);
+        Ok(())
+    }
+}
+impl Decode for char {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        u32::try_from(r.varint()?)
+            .ok()
+            .and_then(crate::stdlib::char::from_u32)
+            .ok_or(BinaryAstError::Malformed)
+    }
+}
+
+impl Encode for CharRange {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        self.start().encode(w)?;
+        self.end().encode(w)
+    }
+}
+impl Decode for CharRange {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        Ok(char::decode(r)?..=char::decode(r)?)
+    }
+}
+
+impl Encode for SwitchRange {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match self {
+            Self::Char(range) => {
+                w.byte(0);
+                range.encode(w)
+            }
+            Self::Int(range) => {
+                w.byte(1);
+                range.start().encode(w)?;
+                range.end().encode(w)
+            }
+        }
+    }
+}
+impl Decode for SwitchRange {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        Ok(match r.byte()? {
+            0 => Self::Char(CharRange::decode(r)?),
+            1 => Self::Int(INT::decode(r)?..=INT::decode(r)?),
+            _ => return Err(BinaryAstError::Malformed),
+        })
+    }
+}
+
+impl Encode for Dynamic {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match &self.0 {
+            Union::Unit(_, _) => w.byte(0),
+            Union::Bool(value, _) => {
+                w.byte(1);
+                w.bool(*value);
+            }
+            Union::Str(value, _) => {
+                w.byte(2);
+                w.str(value);
+            }
+            Union::Char(value, _) => {
+                w.byte(3);
+                value.encode(w)?;
+            }
+            Union::Int(value, _) => {
+                w.byte(4);
+                value.encode(w)?;
+            }
+            #[cfg(not(feature = "no_float"))]
+            Union::Float(value, _) => {
+                w.byte(5);
+                value.encode(w)?;
+            }
+            #[cfg(not(feature = "no_index"))]
+            Union::Array(value, _) => {
+                w.byte(6);
+                value.as_slice().encode(w)?;
+            }
+            #[cfg(not(feature = "no_object"))]
+            Union::Map(value, _) => {
+                w.byte(7);
+                w.usize(value.len());
+                value.iter().try_for_each(|(k, v)| {
+                    w.str(k);
+                    v.encode(w)
+                })?;
+            }
+            Union::FnPtr(value, _) => {
+                w.byte(8);
+                w.str(value.get_fn_name());
+                value.curry().encode(w)?;
+            }
+            _ if self.is::<CharRange>() => {
+                w.byte(9);
+                self.downcast_ref::<CharRange>().unwrap().encode(w)?;
+            }
+            _ => return Err(BinaryAstError::UnsupportedValue(self.type_name().into())),
+        }
+
+        w.bool(self.is_read_only());
+        Ok(())
+    }
+}
+impl Decode for Dynamic {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        let mut value: Dynamic = match r.byte()? {
+            0 => Dynamic::UNIT,
+            1 => r.bool()?.into(),
+            2 => r.str()?.into(),
+            3 => char::decode(r)?.into(),
+            4 => INT::decode(r)?.into(),
+            #[cfg(not(feature = "no_float"))]
+            5 => FLOAT::decode(r)?.into(),
+            #[cfg(not(feature = "no_index"))]
+            6 => Vec::<Dynamic>::decode(r)?.into(),
+            #[cfg(not(feature = "no_object"))]
+            7 => {
+                let mut map: Map = Default::default();
+                for _ in 0..r.len()? {
+                    map.insert(r.str()?, Dynamic::decode(r)?);
+                }
+                map.into()
+            }
+            8 => FnPtr::new_unchecked(r.str()?, StaticVec::decode(r)?).into(),
+            9 => Dynamic::from(CharRange::decode(r)?),
+            _ => return Err(BinaryAstError::Malformed),
+        };
+
+        if r.bool()? {
+            value.set_access_mode(AccessMode::ReadOnly);
+        }
+        Ok(value)
+    }
+}
+
+impl Encode for FnCallExpr {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.fixed(self.hash_script.map_or(0, NonZeroU64::get));
+        w.bool(self.capture);
+        self.def_value.encode(w)?;
+        self.namespace.encode(w)?;
+        w.str(&self.name);
+        self.args.as_slice().encode(w)
+    }
+}
+impl Decode for FnCallExpr {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            hash_script: NonZeroU64::new(r.fixed()?),
+            capture: r.bool()?,
+            def_value: Decode::decode(r)?,
+            namespace: Decode::decode(r)?,
+            name: r.str()?,
+            args: Decode::decode(r)?,
+        })
+    }
+}
+
+impl Encode for BinaryExpr {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        self.lhs.encode(w)?;
+        self.rhs.encode(w)
+    }
+}
+impl Decode for BinaryExpr {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            lhs: Expr::decode(r)?,
+            rhs: Expr::decode(r)?,
+        })
+    }
+}
+
+impl Encode for Expr {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match self {
+            Self::DynamicConstant(value, pos) => {
+                w.byte(0);
+                value.encode(w)?;
+                w.pos(*pos);
+            }
+            Self::BoolConstant(value, pos) => {
+                w.byte(1);
+                w.bool(*value);
+                w.pos(*pos);
+            }
+            Self::IntegerConstant(value, pos) => {
+                w.byte(2);
+                value.encode(w)?;
+                w.pos(*pos);
+            }
+            #[cfg(not(feature = "no_float"))]
+            Self::FloatConstant(value, pos) => {
+                w.byte(3);
+                value.encode(w)?;
+                w.pos(*pos);
+            }
+            Self::CharConstant(value, pos) => {
+                w.byte(4);
+                value.encode(w)?;
+                w.pos(*pos);
+            }
+            Self::StringConstant(value, pos) => {
+                w.byte(5);
+                w.str(value);
+                w.pos(*pos);
+            }
+            Self::FnPointer(value, pos) => {
+                w.byte(6);
+                w.str(value);
+                w.pos(*pos);
+            }
+            Self::Array(x, pos) => {
+                w.byte(7);
+                x.as_slice().encode(w)?;
+                w.pos(*pos);
+            }
+            Self::Map(x, pos) => {
+                w.byte(8);
+                w.usize(x.len());
+                x.iter().try_for_each(|(key, expr)| {
+                    key.encode(w)?;
+                    expr.encode(w)
+                })?;
+                w.pos(*pos);
+            }
+            Self::Unit(pos) => {
+                w.byte(9);
+                w.pos(*pos);
+            }
+            Self::Variable(x) => {
+                w.byte(10);
+                w.usize(x.0.map_or(0, NonZeroUsize::get));
+                w.bool(x.1.is_some());
+                if let Some((hash, namespace)) = &x.1 {
+                    w.fixed(hash.get());
+                    namespace.encode(w)?;
+                }
+                x.2.encode(w)?;
+            }
+            Self::Property(x) => {
+                w.byte(11);
+                w.str(&x.0);
+                w.str(&x.1);
+                x.2.encode(w)?;
+            }
+            Self::Stmt(x, pos) => {
+                w.byte(12);
+                x.as_slice().encode(w)?;
+                w.pos(*pos);
+            }
+            Self::FnCall(x, pos) => {
+                w.byte(13);
+                x.encode(w)?;
+                w.pos(*pos);
+            }
+            Self::Dot(x, optional, pos) | Self::Index(x, optional, pos) => {
+                w.byte(match self {
+                    Self::Dot(_, _, _) => 14,
+                    _ => 15,
+                });
+                x.encode(w)?;
+                w.bool(*optional);
+                w.pos(*pos);
+            }
+            Self::In(x, pos) | Self::And(x, pos) | Self::Or(x, pos) | Self::Coalesce(x, pos) => {
+                w.byte(match self {
+                    Self::In(_, _) => 16,
+                    Self::And(_, _) => 17,
+                    Self::Or(_, _) => 18,
+                    _ => 21,
+                });
+                x.encode(w)?;
+                w.pos(*pos);
+            }
+            Self::Custom(x, pos) => {
+                // The implementation function is looked up again when loading
+                w.byte(19);
+                x.keywords.as_slice().encode(w)?;
+                x.tokens.encode(w)?;
+                w.pos(*pos);
+            }
+            Self::IndexArgs(x, pos) => {
+                w.byte(20);
+                x.as_slice().encode(w)?;
+                w.pos(*pos);
+            }
+        }
+
+        Ok(())
+    }
+}
+impl Decode for Expr {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        r.enter()?;
+
+        let expr = match r.byte()? {
+            0 => Self::DynamicConstant(Decode::decode(r)?, r.pos()?),
+            1 => Self::BoolConstant(r.bool()?, r.pos()?),
+            2 => Self::IntegerConstant(Decode::decode(r)?, r.pos()?),
+            #[cfg(not(feature = "no_float"))]
+            3 => Self::FloatConstant(Decode::decode(r)?, r.pos()?),
+            4 => Self::CharConstant(Decode::decode(r)?, r.pos()?),
+            5 => Self::StringConstant(r.str()?, r.pos()?),
+            6 => Self::FnPointer(r.str()?, r.pos()?),
+            7 => Self::Array(Decode::decode(r)?, r.pos()?),
+            8 => {
+                let list = (0..r.len()?)
+                    .map(|_| Ok((MapKey::decode(r)?, Expr::decode(r)?)))
+                    .collect::<Result<StaticVec<_>>>()?;
+                Self::Map(Box::new(list), r.pos()?)
+            }
+            9 => Self::Unit(r.pos()?),
+            10 => {
+                let index = NonZeroUsize::new(r.usize()?);
+                let namespace = if r.bool()? {
+                    let hash = NonZeroU64::new(r.fixed()?).ok_or(BinaryAstError::Malformed)?;
+                    Some((hash, NamespaceRef::decode(r)?))
+                } else {
+                    None
+                };
+                Self::Variable(Box::new((index, namespace, Ident::decode(r)?)))
+            }
+            11 => Self::Property(Box::new((r.str()?, r.str()?, Ident::decode(r)?))),
+            12 => Self::Stmt(Decode::decode(r)?, r.pos()?),
+            13 => Self::FnCall(Decode::decode(r)?, r.pos()?),
+            14 => Self::Dot(Decode::decode(r)?, r.bool()?, r.pos()?),
+            15 => Self::Index(Decode::decode(r)?, r.bool()?, r.pos()?),
+            16 => Self::In(Decode::decode(r)?, r.pos()?),
+            17 => Self::And(Decode::decode(r)?, r.pos()?),
+            18 => Self::Or(Decode::decode(r)?, r.pos()?),
+            19 => {
+                let keywords = StaticVec::decode(r)?;
+                let tokens = Vec::<ImmutableString>::decode(r)?;
+                let key = tokens.first().ok_or(BinaryAstError::Malformed)?;
+                let func = r
+                    .engine
+                    .custom_syntax
+                    .get(key)
+                    .map(|syntax| syntax.func.clone())
+                    .ok_or_else(|| BinaryAstError::CustomSyntax(key.to_string()))?;
+
+                Self::Custom(
+                    Box::new(CustomExpr {
+                        keywords,
+                        func,
+                        tokens,
+                    }),
+                    r.pos()?,
+                )
+            }
+            20 => Self::IndexArgs(Decode::decode(r)?, r.pos()?),
+            21 => Self::Coalesce(Decode::decode(r)?, r.pos()?),
+            _ => return Err(BinaryAstError::Malformed),
+        };
+
+        r.leave();
+        Ok(expr)
+    }
+}
+
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+impl Encode for Pattern {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        let (vars, rest) = match self {
+            #[cfg(not(feature = "no_index"))]
+            Self::Array(vars, rest) => {
+                w.byte(0);
+                (vars, rest)
+            }
+            #[cfg(not(feature = "no_object"))]
+            Self::Map(vars, rest) => {
+                w.byte(1);
+                (vars, rest)
+            }
+        };
+        vars.encode(w)?;
+        rest.encode(w)
+    }
+}
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+impl Decode for Pattern {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        Ok(match r.byte()? {
+            #[cfg(not(feature = "no_index"))]
+            0 => Self::Array(Decode::decode(r)?, Decode::decode(r)?),
+            #[cfg(not(feature = "no_object"))]
+            1 => Self::Map(Decode::decode(r)?, Decode::decode(r)?),
+            _ => return Err(BinaryAstError::Malformed),
+        })
+    }
+}
+
+impl Encode for Stmt {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match self {
+            Self::Noop(pos) => {
+                w.byte(0);
+                w.pos(*pos);
+            }
+            Self::If(expr, x, pos) => {
+                w.byte(1);
+                expr.encode(w)?;
+                x.0.encode(w)?;
+                x.1.encode(w)?;
+                w.pos(*pos);
+            }
+            Self::Switch(expr, x, pos) => {
+                w.byte(2);
+                expr.encode(w)?;
+                w.usize(x.0.len());
+                x.0.iter().try_for_each(|(&hash, stmt)| {
+                    w.fixed(hash);
+                    stmt.encode(w)
+                })?;
+                x.1.encode(w)?;
+                w.usize(x.2.len());
+                x.2.iter().try_for_each(|(range, stmt)| {
+                    range.encode(w)?;
+                    stmt.encode(w)
+                })?;
+                w.pos(*pos);
+            }
+            Self::While(expr, x, pos) => {
+                w.byte(3);
+                expr.encode(w)?;
+                x.0.encode(w)?;
+                x.1.encode(w)?;
+                w.pos(*pos);
+            }
+            Self::Do(x, expr, is_while, pos) => {
+                w.byte(4);
+                x.0.encode(w)?;
+                x.1.encode(w)?;
+                expr.encode(w)?;
+                w.bool(*is_while);
+                w.pos(*pos);
+            }
+            Self::For(expr, x, pos) => {
+                w.byte(5);
+                expr.encode(w)?;
+                x.0.encode(w)?;
+                x.1.encode(w)?;
+                x.2.encode(w)?;
+                x.3.encode(w)?;
+                w.pos(*pos);
+            }
+            Self::Let(var_def, expr, export, pos) | Self::Const(var_def, expr, export, pos) => {
+                w.byte(if matches!(self, Self::Let(_, _, _, _)) {
+                    6
+                } else {
+                    7
+                });
+                var_def.encode(w)?;
+                expr.encode(w)?;
+                w.bool(*export);
+                w.pos(*pos);
+            }
+            Self::Assignment(x, pos) => {
+                w.byte(8);
+                x.0.encode(w)?;
+                w.str(&x.1.as_ref().into());
+                x.2.encode(w)?;
+                w.pos(*pos);
+            }
+            Self::Block(statements, pos) => {
+                w.byte(9);
+                statements.as_slice().encode(w)?;
+                w.pos(*pos);
+            }
+            Self::TryCatch(x, try_pos, catch_pos) => {
+                w.byte(10);
+                x.0.encode(w)?;
+                w.usize(x.1.len());
+                x.1.iter().try_for_each(|(var, pos_var, guard, block)| {
+                    var.encode(w)?;
+                    pos_var.encode(w)?;
+                    guard.encode(w)?;
+                    block.encode(w)
+                })?;
+                w.pos(*try_pos);
+                w.pos(*catch_pos);
+            }
+            Self::Expr(expr) => {
+                w.byte(11);
+                expr.encode(w)?;
+            }
+            Self::Continue(label, pos) => {
+                w.byte(12);
+                label.encode(w)?;
+                w.pos(*pos);
+            }
+            Self::Break(label, expr, pos) => {
+                w.byte(13);
+                label.encode(w)?;
+                expr.encode(w)?;
+                w.pos(*pos);
+            }
+            Self::Return((return_type, return_pos), expr, pos) => {
+                w.byte(14);
+                w.bool(*return_type == ReturnType::Exception);
+                w.pos(*return_pos);
+                expr.encode(w)?;
+                w.pos(*pos);
+            }
+            #[cfg(not(feature = "no_module"))]
+            Self::Import(expr, alias, pos) => {
+                w.byte(15);
+                expr.encode(w)?;
+                alias.encode(w)?;
+                w.pos(*pos);
+            }
+            #[cfg(not(feature = "no_module"))]
+            Self::Export(list, pos) => {
+                w.byte(16);
+                w.usize(list.len());
+                list.iter().try_for_each(|(name, rename)| {
+                    name.encode(w)?;
+                    rename.encode(w)
+                })?;
+                w.pos(*pos);
+            }
+            #[cfg(not(feature = "no_closure"))]
+            Self::Share(var_def) => {
+                w.byte(17);
+                var_def.encode(w)?;
+            }
+            #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+            Self::LetPattern(x, entry_type, export, pos) => {
+                w.byte(18);
+                x.0.encode(w)?;
+                x.1.encode(w)?;
+                w.bool(*entry_type == AccessMode::ReadOnly);
+                w.bool(*export);
+                w.pos(*pos);
+            }
+        }
+
+        Ok(())
+    }
+}
+impl Decode for Stmt {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        r.enter()?;
+
+        let stmt = match r.byte()? {
+            0 => Self::Noop(r.pos()?),
+            1 => Self::If(
+                Expr::decode(r)?,
+                Box::new((Stmt::decode(r)?, Decode::decode(r)?)),
+                r.pos()?,
+            ),
+            2 => {
+                let expr = Expr::decode(r)?;
+                let len = r.len()?;
+                let mut table = HashMap::with_capacity_and_hasher(len, StraightHasherBuilder);
+                for _ in 0..len {
+                    table.insert(r.fixed()?, Stmt::decode(r)?);
+                }
+                let def_stmt = Decode::decode(r)?;
+                let ranges = (0..r.len()?)
+                    .map(|_| Ok((SwitchRange::decode(r)?, Stmt::decode(r)?)))
+                    .collect::<Result<StaticVec<_>>>()?;
+                Self::Switch(expr, Box::new((table, def_stmt, ranges)), r.pos()?)
+            }
+            3 => Self::While(
+                Expr::decode(r)?,
+                Box::new((Stmt::decode(r)?, Decode::decode(r)?)),
+                r.pos()?,
+            ),
+            4 => Self::Do(
+                Box::new((Stmt::decode(r)?, Decode::decode(r)?)),
+                Expr::decode(r)?,
+                r.bool()?,
+                r.pos()?,
+            ),
+            5 => Self::For(
+                Expr::decode(r)?,
+                Box::new((
+                    Decode::decode(r)?,
+                    Decode::decode(r)?,
+                    Stmt::decode(r)?,
+                    Decode::decode(r)?,
+                )),
+                r.pos()?,
+            ),
+            6 => Self::Let(Decode::decode(r)?, Decode::decode(r)?, r.bool()?, r.pos()?),
+            7 => Self::Const(Decode::decode(r)?, Decode::decode(r)?, r.bool()?, r.pos()?),
+            8 => Self::Assignment(
+                Box::new((
+                    Expr::decode(r)?,
+                    Cow::Owned(r.str()?.to_string()),
+                    Expr::decode(r)?,
+                )),
+                r.pos()?,
+            ),
+            9 => Self::Block(Decode::decode(r)?, r.pos()?),
+            10 => {
+                let body = Stmt::decode(r)?;
+                let clauses = (0..r.len()?)
+                    .map(|_| {
+                        Ok((
+                            Decode::decode(r)?,
+                            Decode::decode(r)?,
+                            Decode::decode(r)?,
+                            Stmt::decode(r)?,
+                        ))
+                    })
+                    .collect::<Result<StaticVec<_>>>()?;
+                Self::TryCatch(Box::new((body, clauses)), r.pos()?, r.pos()?)
+            }
+            11 => Self::Expr(Expr::decode(r)?),
+            12 => Self::Continue(Decode::decode(r)?, r.pos()?),
+            13 => Self::Break(Decode::decode(r)?, Decode::decode(r)?, r.pos()?),
+            14 => {
+                let return_type = if r.bool()? {
+                    ReturnType::Exception
+                } else {
+                    ReturnType::Return
+                };
+                Self::Return((return_type, r.pos()?), Decode::decode(r)?, r.pos()?)
+            }
+            #[cfg(not(feature = "no_module"))]
+            15 => Self::Import(Expr::decode(r)?, Decode::decode(r)?, r.pos()?),
+            #[cfg(not(feature = "no_module"))]
+            16 => {
+                let list = (0..r.len()?)
+                    .map(|_| Ok((Ident::decode(r)?, Decode::decode(r)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                Self::Export(list, r.pos()?)
+            }
+            #[cfg(not(feature = "no_closure"))]
+            17 => Self::Share(Ident::decode(r)?),
+            #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+            18 => {
+                let x = Box::new((Pattern::decode(r)?, Expr::decode(r)?));
+                let entry_type = if r.bool()? {
+                    AccessMode::ReadOnly
+                } else {
+                    AccessMode::ReadWrite
+                };
+                Self::LetPattern(x, entry_type, r.bool()?, r.pos()?)
+            }
+            _ => return Err(BinaryAstError::Malformed),
+        };
+
+        r.leave();
+        Ok(stmt)
+    }
+}
+
+#[cfg(not(feature = "no_function"))]
+impl Encode for ScriptFnDef {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.str(&self.name);
+        w.bool(self.access.is_private());
+        #[cfg(not(feature = "no_module"))]
+        w.bool(self.exported);
+        self.params.as_slice().encode(w)?;
+        self.defaults.as_slice().encode(w)?;
+        w.bool(self.variadic);
+        #[cfg(not(feature = "no_closure"))]
+        self.externals.as_slice().encode(w)?;
+        self.comments.as_slice().encode(w)?;
+        self.body.encode(w)
+    }
+}
+#[cfg(not(feature = "no_function"))]
+impl Decode for ScriptFnDef {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            name: r.str()?,
+            access: if r.bool()? {
+                FnAccess::Private
+            } else {
+                FnAccess::Public
+            },
+            #[cfg(not(feature = "no_module"))]
+            exported: r.bool()?,
+            params: Decode::decode(r)?,
+            defaults: Decode::decode(r)?,
+            variadic: r.bool()?,
+            #[cfg(not(feature = "no_closure"))]
+            externals: Decode::decode(r)?,
+            comments: Decode::decode(r)?,
+            body: Stmt::decode(r)?,
+            lib: None,
+            #[cfg(not(feature = "no_module"))]
+            mods: Default::default(),
+        })
+    }
+}
+
+/// Save an [`AST`] in binary format.
+fn encode_ast(ast: &AST) -> Result<Vec<u8>> {
+    let mut w = Writer {
+        buf: Vec::with_capacity(256),
+        strings: Default::default(),
+    };
+
+    w.buf.extend_from_slice(MAGIC);
+    w.varint(BINARY_AST_VERSION as u64);
+    w.varint(feature_flags());
+    w.fixed(hasher_fingerprint());
+
+    ast.clone_source().encode(&mut w)?;
+    ast.statements().encode(&mut w)?;
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        let functions: Vec<_> = ast
+            .lib()
+            .iter_script_fn()
+            .map(|(_, _, _, _, f)| f)
+            .collect();
+        w.usize(functions.len());
+        functions.into_iter().try_for_each(|f| f.encode(&mut w))?;
+    }
+    #[cfg(feature = "no_function")]
+    w.usize(0);
+
+    Ok(w.buf)
+}
+
+/// Load an [`AST`] saved in binary format.
+fn decode_ast(engine: &Engine, bytes: &[u8]) -> Result<AST> {
+    let mut r = Reader {
+        engine,
+        bytes,
+        strings: Default::default(),
+        depth: 0,
+        #[cfg(not(feature = "unchecked"))]
+        max_depth: engine.max_expr_depth(),
+        #[cfg(feature = "unchecked")]
+        max_depth: 0,
+    };
+
+    if r.take(MAGIC.len()).ok() != Some(MAGIC) {
+        return Err(BinaryAstError::Malformed);
+    }
+
+    let version = u16::try_from(r.varint()?).map_err(|_| BinaryAstError::Malformed)?;
+
+    if version != BINARY_AST_VERSION {
+        return Err(BinaryAstError::Version(version));
+    }
+
+    let flags = r.varint()?;
+    let current = feature_flags();
+
+    if flags != current {
+        let diff = |flags: u64, other: u64| {
+            FEATURES
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| flags & (1 << i) != 0 && other & (1 << i) == 0)
+                .map(|(_, (name, _))| name.to_string())
+                .collect::<Vec<_>>()
+        };
+        return Err(BinaryAstError::Features(
+            diff(flags, current),
+            diff(current, flags),
+        ));
+    }
+
+    if r.fixed()? != hasher_fingerprint() {
+        return Err(BinaryAstError::Hasher);
+    }
+
+    let source = Option::<ImmutableString>::decode(&mut r)?;
+    let statements = Vec::<Stmt>::decode(&mut r)?;
+
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_function"))]
+    {
+        r.max_depth = engine.max_function_expr_depth();
+    }
+
+    #[allow(unused_mut)]
+    let mut lib = Module::new();
+
+    for _ in 0..r.len()? {
+        #[cfg(not(feature = "no_function"))]
+        lib.set_script_fn(ScriptFnDef::decode(&mut r)?);
+        #[cfg(feature = "no_function")]
+        return Err(BinaryAstError::Malformed);
+    }
+
+    if !r.bytes.is_empty() {
+        return Err(BinaryAstError::Malformed);
+    }
+
+    let mut ast = AST::new(statements, lib);
+    ast.set_source(source);
+    Ok(ast)
+}
+
+impl AST {
+    /// Save this [`AST`] in a compact binary format, which can be loaded via
+    /// [`Engine::load_compiled_ast`].
+    ///
+    /// Script-defined functions are saved together with the statements.
+    ///
+    /// Available under the `binary_ast` feature only.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`ErrorSystem`][EvalAltResult::ErrorSystem] wrapping
+    /// [`BinaryAstError::UnsupportedValue`] if the [`AST`] contains a constant value
+    /// of a custom type (e.g. from eager evaluation of a function during optimization).
+    pub fn to_bytes(&self) -> crate::stdlib::result::Result<Vec<u8>, Box<EvalAltResult>> {
+        encode_ast(self).map_err(|err| {
+            EvalAltResult::ErrorSystem("Cannot save compiled AST".into(), Box::new(err)).into()
+        })
+    }
+}
+
+impl Engine {
+    /// Compile a string into an [`AST`] and save it in a compact binary format,
+    /// which can be loaded via [`Engine::load_compiled_ast`].
+    ///
+    /// Available under the `binary_ast` feature only.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let bytes = engine.compile_to_bytes("fn add(x) { x + 1 } add(41)")?;
+    ///
+    /// let ast = engine.load_compiled_ast(&bytes)?;
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn compile_to_bytes(
+        &self,
+        script: &str,
+    ) -> crate::stdlib::result::Result<Vec<u8>, Box<EvalAltResult>> {
+        self.compile(script)?.to_bytes()
+    }
+    /// Load an [`AST`] saved in binary format by [`AST::to_bytes`] or
+    /// [`Engine::compile_to_bytes`].
+    ///
+    /// The binary format is versioned, and an [`AST`] can only be loaded by an [`Engine`] built
+    /// with the same feature flags (e.g. `no_float`, `only_i32`) as the one that saved it.
+    ///
+    /// Custom syntax used in the [`AST`] must be registered with this [`Engine`].
+    /// Modules are not saved, but resolved again when the `import` statements are run.
+    ///
+    /// Available under the `binary_ast` feature only.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`ErrorSystem`][EvalAltResult::ErrorSystem] wrapping a [`BinaryAstError`].
+    pub fn load_compiled_ast(
+        &self,
+        bytes: &[u8],
+    ) -> crate::stdlib::result::Result<AST, Box<EvalAltResult>> {
+        decode_ast(self, bytes).map_err(|err| {
+            EvalAltResult::ErrorSystem("Cannot load compiled AST".into(), Box::new(err)).into()
+        })
+    }
+}
@@ -0,0 +1,339 @@
+//! Module implementing the compilation cache of an [`Engine`].
+
+use crate::ast::AST;
+use crate::engine::Engine;
+use crate::fn_native::{Locked, Shared};
+use crate::optimize::OptimizationLevel;
+use crate::stdlib::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    string::{String, ToString},
+    vec::Vec,
+};
+use crate::utils::get_hasher;
+use crate::{ParseError, Scope};
+
+/// A compiled [`AST`] kept in the compilation cache.
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    /// Text of the scripts compiled, to guard against hash collisions.
+    scripts: Vec<String>,
+    /// The compiled [`AST`].
+    ast: Shared<AST>,
+    /// Optimization level the [`AST`] is compiled at.
+    optimization_level: OptimizationLevel,
+    /// Hash of the [`Engine`] settings affecting the compilation.
+    settings_hash: u64,
+    /// Tick of the last use of this entry, for LRU eviction.
+    last_used: u64,
+}
+
+/// Cache of compiled [`AST`]'s, keyed by the hash of the script text.
+///
+/// The script text is kept and compared on lookup, so a hash collision is a cache miss.
+///
+/// When the cache is full, the least recently used entry is evicted.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CompilationCache {
+    /// Maximum number of entries.
+    capacity: usize,
+    /// Counter incremented on every use of the cache.
+    tick: u64,
+    /// Cached entries.
+    entries: HashMap<u64, CacheEntry>,
+}
+
+impl CompilationCache {
+    /// Create a new [`CompilationCache`] holding at most `capacity` entries.
+    #[inline(always)]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: Default::default(),
+        }
+    }
+    /// Maximum number of entries.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// Number of entries.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    /// Remove all entries.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+    /// Get the [`AST`] cached for a script hash, if it is compiled from the same scripts
+    /// with the same settings.
+    pub fn get(
+        &mut self,
+        script_hash: u64,
+        scripts: &[&str],
+        optimization_level: OptimizationLevel,
+        settings_hash: u64,
+    ) -> Option<Shared<AST>> {
+        self.tick += 1;
+        let tick = self.tick;
+
+        self.entries
+            .get_mut(&script_hash)
+            .filter(|entry| {
+                entry.optimization_level == optimization_level
+                    && entry.settings_hash == settings_hash
+                    && entry
+                        .scripts
+                        .iter()
+                        .map(String::as_str)
+                        .eq(scripts.iter().copied())
+            })
+            .map(|entry| {
+                entry.last_used = tick;
+                entry.ast.clone()
+            })
+    }
+    /// Add an [`AST`] to the cache, evicting the least recently used entry if the cache is full.
+    pub fn insert(
+        &mut self,
+        script_hash: u64,
+        scripts: &[&str],
+        optimization_level: OptimizationLevel,
+        settings_hash: u64,
+        ast: Shared<AST>,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if !self.entries.contains_key(&script_hash) && self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(&hash, _)| hash);
+
+            if let Some(hash) = oldest {
+                self.entries.remove(&hash);
+            }
+        }
+
+        self.tick += 1;
+
+        self.entries.insert(
+            script_hash,
+            CacheEntry {
+                scripts: scripts.iter().map(|&script| script.to_string()).collect(),
+                ast,
+                optimization_level,
+                settings_hash,
+                last_used: self.tick,
+            },
+        );
+    }
+}
+
+/// Lock the compilation cache for writing.
+#[cfg(not(feature = "sync"))]
+#[inline(always)]
+fn lock(cache: &Locked<CompilationCache>) -> crate::stdlib::cell::RefMut<'_, CompilationCache> {
+    cache.borrow_mut()
+}
+/// Lock the compilation cache for writing.
+#[cfg(feature = "sync")]
+#[inline(always)]
+fn lock(
+    cache: &Locked<CompilationCache>,
+) -> crate::stdlib::sync::RwLockWriteGuard<'_, CompilationCache> {
+    cache.write().unwrap()
+}
+
+impl Engine {
+    /// Enable a compilation cache holding up to `capacity` compiled scripts.
+    ///
+    /// When enabled, [`compile`][Engine::compile], [`eval`][Engine::eval] and friends look up the
+    /// hash of the script text in the cache before parsing it, and the least recently used
+    /// entry is evicted when the cache is full.
+    ///
+    /// Cached scripts are only reused when compiled at the same optimization level and with the
+    /// same settings affecting the parser (e.g. disabled symbols, custom operators and custom
+    /// syntax).  Scripts compiled with a [`Scope`] holding constants are not cached unless
+    /// optimization is turned off, because the values of constants may be folded into the
+    /// compiled script.
+    ///
+    /// Use a `capacity` of zero to disable the cache (the default).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.set_compilation_cache(16);
+    ///
+    /// for _ in 0..10 {
+    ///     // The script is only parsed once
+    ///     assert_eq!(engine.eval::<i64>("40 + 2")?, 42);
+    /// }
+    ///
+    /// assert_eq!(engine.compilation_cache_len(), 1);
+    ///
+    /// engine.clear_compilation_cache();
+    /// assert_eq!(engine.compilation_cache_len(), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_compilation_cache(&mut self, capacity: usize) -> &mut Self {
+        self.compilation_cache = if capacity == 0 {
+            None
+        } else {
+            Some(Locked::new(CompilationCache::new(capacity)))
+        };
+        self
+    }
+    /// The maximum number of compiled scripts kept in the compilation cache
+    /// (zero if the cache is disabled).
+    #[inline(always)]
+    pub fn compilation_cache_capacity(&self) -> usize {
+        self.compilation_cache
+            .as_ref()
+            .map_or(0, |cache| lock(cache).capacity())
+    }
+    /// The number of compiled scripts currently kept in the compilation cache.
+    #[inline(always)]
+    pub fn compilation_cache_len(&self) -> usize {
+        self.compilation_cache
+            .as_ref()
+            .map_or(0, |cache| lock(cache).len())
+    }
+    /// Remove all compiled scripts from the compilation cache.
+    ///
+    /// The cache should be cleared after registering new functions if scripts are compiled with
+    /// [`OptimizationLevel::Full`], which may evaluate calls to those functions during
+    /// compilation.
+    #[inline(always)]
+    pub fn clear_compilation_cache(&self) -> &Self {
+        if let Some(ref cache) = self.compilation_cache {
+            lock(cache).clear();
+        }
+        self
+    }
+    /// Calculate a hash of the settings affecting the compilation of a script.
+    fn calc_compilation_settings_hash(&self, scope: &Scope) -> u64 {
+        let s = &mut get_hasher();
+
+        let mut disabled: Vec<_> = self.disabled_symbols.iter().collect();
+        disabled.sort();
+        disabled.hash(s);
+
+        let mut keywords: Vec<_> = self.custom_keywords.iter().collect();
+        keywords.sort_by_key(|(k, _)| *k);
+        keywords.hash(s);
+
+        let mut precedence: Vec<_> = self.operator_precedence.iter().collect();
+        precedence.sort();
+        precedence.hash(s);
+
+        // Custom syntax cannot be compared, so use the address of its implementation
+        // to detect when it is registered again
+        let mut syntax: Vec<_> = self
+            .custom_syntax
+            .iter()
+            .map(|(key, syntax)| (key, Shared::as_ptr(&syntax.func) as *const u8 as usize))
+            .collect();
+        syntax.sort();
+        syntax.hash(s);
+
+        self.disable_doc_comments.hash(s);
+        self.strict_variables.hash(s);
+        self.strict_fn_pointers.hash(s);
+        self.retain_source.hash(s);
+
+        // Variable resolvers turn off the check for undeclared variables
+        self.resolve_var.is_some().hash(s);
+        self.write_var.is_some().hash(s);
+
+        #[cfg(not(feature = "no_module"))]
+        {
+            self.strict_module_aliases.hash(s);
+            self.module_fn_private_by_default.hash(s);
+        }
+        #[cfg(not(feature = "no_object"))]
+        self.allow_compile_expression.hash(s);
+        #[cfg(not(feature = "unchecked"))]
+        {
+            self.limits.max_expr_depth.hash(s);
+            #[cfg(not(feature = "no_function"))]
+            self.limits.max_function_expr_depth.hash(s);
+            self.limits.max_string_size.hash(s);
+            #[cfg(not(feature = "no_index"))]
+            self.limits.max_array_size.hash(s);
+            #[cfg(not(feature = "no_object"))]
+            self.limits.max_map_size.hash(s);
+            self.limits.max_variables.hash(s);
+        }
+
+        // Global constants are declared variables under strict variables
+        self.global_constants.iter().for_each(|m| {
+            let mut names: Vec<_> = m.iter_var().map(|(name, _)| name).collect();
+            names.sort();
+            names.hash(s);
+        });
+
+        // Variables in scope are visible to the parser
+        scope.iter_raw().for_each(|(name, _, _)| name.hash(s));
+
+        s.finish()
+    }
+    /// Compile a list of strings into a shared [`AST`], using the compilation cache if enabled.
+    pub(crate) fn compile_shared(
+        &self,
+        scope: &Scope,
+        scripts: &[&str],
+        optimization_level: OptimizationLevel,
+    ) -> Result<Shared<AST>, ParseError> {
+        let cache = match self.compilation_cache {
+            // Constants in scope may be folded into the AST
+            Some(ref cache)
+                if optimization_level == OptimizationLevel::None
+                    || scope.iter_raw().all(|(_, constant, _)| !constant) =>
+            {
+                cache
+            }
+            _ => {
+                return self
+                    .compile_uncached(scope, scripts, optimization_level)
+                    .map(Into::into)
+            }
+        };
+
+        let script_hash = crate::engine_api::calc_hash_for_scripts(scripts);
+        let settings_hash = self.calc_compilation_settings_hash(scope);
+
+        if let Some(ast) = lock(cache).get(script_hash, scripts, optimization_level, settings_hash)
+        {
+            return Ok(ast);
+        }
+
+        // Do not hold the lock while parsing
+        let ast: Shared<AST> = self
+            .compile_uncached(scope, scripts, optimization_level)?
+            .into();
+
+        lock(cache).insert(
+            script_hash,
+            scripts,
+            optimization_level,
+            settings_hash,
+            ast.clone(),
+        );
+
+        Ok(ast)
+    }
+}
@@ -0,0 +1,60 @@
+//! Module defining the interface of a script debugger.
+
+use crate::{ImmutableString, StaticVec};
+
+/// Command returned by a debugger callback registered via
+/// [`Engine::on_debugger`][crate::Engine::on_debugger], deciding how the evaluation proceeds.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum DebuggerCommand {
+    /// Run the rest of the evaluation without calling the debugger again.
+    Continue,
+    /// Call the debugger again before the next statement, stepping into function calls.
+    StepInto,
+    /// Call the debugger again before the next statement of the current function (or the function
+    /// calling it, if there are no more statements), without stepping into function calls.
+    StepOver,
+    /// Abort the evaluation with [`EvalAltResult::ErrorTerminated`][crate::EvalAltResult::ErrorTerminated].
+    Abort,
+}
+
+/// _(INTERNALS)_ State of the debugger during an evaluation.
+/// Exported under the `internals` feature only.
+///
+/// ## WARNING
+///
+/// This type is volatile and may change.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Debugger {
+    /// Names of the script-defined functions currently being called, outermost first.
+    pub call_stack: StaticVec<ImmutableString>,
+    /// Maximum depth of the call stack at which the debugger is called, or [`None`]
+    /// if it is not called again during this evaluation.
+    pub break_depth: Option<usize>,
+}
+
+impl Default for Debugger {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            call_stack: Default::default(),
+            break_depth: Some(usize::MAX),
+        }
+    }
+}
+
+impl Debugger {
+    /// Should the debugger be called at the current depth of the call stack?
+    #[inline(always)]
+    pub fn is_break(&self) -> bool {
+        matches!(self.break_depth, Some(depth) if self.call_stack.len() <= depth)
+    }
+    /// Update the state according to a [command][DebuggerCommand] returned by the debugger.
+    #[inline]
+    pub fn apply(&mut self, command: DebuggerCommand) {
+        self.break_depth = match command {
+            DebuggerCommand::Continue | DebuggerCommand::Abort => None,
+            DebuggerCommand::StepInto => Some(usize::MAX),
+            DebuggerCommand::StepOver => Some(self.call_stack.len()),
+        };
+    }
+}
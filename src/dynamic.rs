@@ -11,7 +11,7 @@ use crate::stdlib::{
     ops::{Deref, DerefMut},
     string::{String, ToString},
 };
-use crate::{FnPtr, ImmutableString, INT};
+use crate::{CharRange, FnPtr, ImmutableString, INT};
 
 #[cfg(not(feature = "no_float"))]
 use crate::FLOAT;
@@ -227,7 +227,67 @@ enum DynamicWriteLockInner<'d, T: Variant + Clone> {
     /// A write guard to a shared [`RwLock`][std::sync::RwLock].
     #[cfg(not(feature = "no_closure"))]
     #[cfg(feature = "sync")]
-    Guard(crate::stdlib::sync::RwLockWriteGuard<'d, Dynamic>),
+    Guard(SharedWriteGuard<'d>),
+}
+
+#[cfg(not(feature = "no_closure"))]
+#[cfg(feature = "sync")]
+thread_local! {
+    /// Addresses of the shared values currently locked for write by this thread.
+    static WRITE_LOCKED: crate::stdlib::cell::RefCell<Vec<usize>> = Default::default();
+}
+
+/// A write guard to a shared [`RwLock`][std::sync::RwLock] which records, for the lifetime of the
+/// guard, that the shared value is locked by the current thread.
+#[cfg(not(feature = "no_closure"))]
+#[cfg(feature = "sync")]
+#[derive(Debug)]
+struct SharedWriteGuard<'d>(crate::stdlib::sync::RwLockWriteGuard<'d, Dynamic>, usize);
+
+#[cfg(not(feature = "no_closure"))]
+#[cfg(feature = "sync")]
+impl<'d> SharedWriteGuard<'d> {
+    #[inline(always)]
+    fn new(cell: &'d crate::Shared<crate::Locked<Dynamic>>) -> Self {
+        let guard = cell.write().unwrap();
+        let addr = crate::Shared::as_ptr(cell) as usize;
+        WRITE_LOCKED.with(|locked| locked.borrow_mut().push(addr));
+        Self(guard, addr)
+    }
+}
+
+#[cfg(not(feature = "no_closure"))]
+#[cfg(feature = "sync")]
+impl Drop for SharedWriteGuard<'_> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        WRITE_LOCKED.with(|locked| {
+            let mut locked = locked.borrow_mut();
+            if let Some(index) = locked.iter().rposition(|&addr| addr == self.1) {
+                locked.swap_remove(index);
+            }
+        });
+    }
+}
+
+#[cfg(not(feature = "no_closure"))]
+#[cfg(feature = "sync")]
+impl Deref for SharedWriteGuard<'_> {
+    type Target = Dynamic;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(not(feature = "no_closure"))]
+#[cfg(feature = "sync")]
+impl DerefMut for SharedWriteGuard<'_> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
 }
 
 impl<'d, T: Variant + Clone> Deref for DynamicWriteLock<'d, T> {
@@ -268,6 +328,16 @@ impl Dynamic {
     }
     /// Does this [`Dynamic`] hold a shared data type
     /// instead of one of the supported system primitive types?
+    ///
+    /// Shared values are created by [`into_shared`][Dynamic::into_shared], most commonly for
+    /// variables captured by closures.  Their types and contents are reported transparently,
+    /// so a shared value normally behaves the same as the value it holds.
+    ///
+    /// Use [`flatten`][Dynamic::flatten] or [`deep_flatten`][Dynamic::deep_flatten] to turn a
+    /// shared value back into a normal value, e.g. before passing it to code that does not
+    /// expect shared values.
+    ///
+    /// Always returns `false` under the `no_closure` feature.
     #[inline(always)]
     pub fn is_shared(&self) -> bool {
         match self.0 {
@@ -276,6 +346,18 @@ impl Dynamic {
             _ => false,
         }
     }
+    /// Do this [`Dynamic`] and another one refer to the same shared value?
+    ///
+    /// Only shared values (e.g. variables captured by closures) can refer to the same storage.
+    /// Always returns `false` for values that are not shared, even if they are equal.
+    #[inline(always)]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            #[cfg(not(feature = "no_closure"))]
+            (Union::Shared(a, _), Union::Shared(b, _)) => crate::Shared::ptr_eq(a, b),
+            _ => false,
+        }
+    }
     /// Is the value held by this [`Dynamic`] a particular type?
     ///
     /// If the [`Dynamic`] is a shared variant checking is performed on
@@ -363,7 +445,7 @@ impl Dynamic {
 
 impl Hash for Dynamic {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        mem::discriminant(self).hash(state);
+        mem::discriminant(&self.0).hash(state);
 
         match &self.0 {
             Union::Unit(_, _) => ().hash(state),
@@ -409,15 +491,25 @@ pub(crate) fn map_std_type_name(name: &str) -> &str {
         "string"
     } else if name == type_name::<FnPtr>() {
         "Fn"
+    } else if name == type_name::<CharRange>() {
+        "char_range"
     } else {
         #[cfg(not(feature = "no_index"))]
         if name == type_name::<Array>() {
             return "array";
         }
+        #[cfg(not(feature = "no_index"))]
+        if name == type_name::<crate::TypedArray>() {
+            return "typed_array";
+        }
         #[cfg(not(feature = "no_object"))]
         if name == type_name::<Map>() {
             return "map";
         }
+        #[cfg(not(feature = "no_object"))]
+        if name == type_name::<crate::CompiledExpr>() {
+            return "compiled_expr";
+        }
         #[cfg(not(feature = "no_std"))]
         if name == type_name::<Instant>() {
             return "timestamp";
@@ -538,6 +630,31 @@ impl Clone for Dynamic {
     }
 }
 
+/// Collapse a shared value nested directly inside another shared value, so that the data
+/// can be accessed through a single lock.
+///
+/// Nested shared values are never created by Rhai itself, but can be constructed via
+/// [`Dynamic::write_lock`].  The inner shared value is flattened, detaching it from its
+/// other references.
+#[cfg(not(feature = "no_closure"))]
+#[inline(always)]
+fn collapse_nested_shared(cell: &crate::Shared<crate::Locked<Dynamic>>) {
+    #[cfg(not(feature = "sync"))]
+    let nested = cell.borrow().is_shared();
+    #[cfg(feature = "sync")]
+    let nested = cell.read().unwrap().is_shared();
+
+    if nested {
+        #[cfg(not(feature = "sync"))]
+        let mut data = cell.borrow_mut();
+        #[cfg(feature = "sync")]
+        let mut data = cell.write().unwrap();
+
+        let value = mem::take(&mut *data).flatten();
+        *data = value;
+    }
+}
+
 impl Default for Dynamic {
     #[inline(always)]
     fn default() -> Self {
@@ -958,21 +1075,49 @@ impl Dynamic {
             )
         })
     }
+    /// Convert the [`Dynamic`] value into a [`TypedArray`][crate::TypedArray] holding elements
+    /// of type `T`.
+    ///
+    /// The elements are not checked again, as a [`TypedArray`][crate::TypedArray] is already
+    /// validated on every mutation.
+    ///
+    /// Returns [`None`] if the value is not a [`TypedArray`][crate::TypedArray], or if its
+    /// element type is not `T`.
+    ///
+    /// Not available under the `no_index` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Dynamic, TypedArray, INT};
+    ///
+    /// let x = Dynamic::from(TypedArray::new::<INT>());
+    ///
+    /// assert!(x.clone().try_cast_typed_array::<bool>().is_none());
+    /// assert!(x.try_cast_typed_array::<INT>().is_some());
+    /// ```
+    #[cfg(not(feature = "no_index"))]
+    #[inline(always)]
+    pub fn try_cast_typed_array<T: Variant + Clone>(self) -> Option<crate::TypedArray> {
+        self.try_cast::<crate::TypedArray>()
+            .filter(|array| array.is_of::<T>())
+    }
     /// Flatten the [`Dynamic`] and clone it.
     ///
     /// If the [`Dynamic`] is not a shared value, it returns a cloned copy.
     ///
     /// If the [`Dynamic`] is a shared value, it a cloned copy of the shared value.
+    /// A shared value nested inside another shared value is also flattened.
     #[inline(always)]
     pub fn flatten_clone(&self) -> Self {
         match &self.0 {
             #[cfg(not(feature = "no_closure"))]
             Union::Shared(cell, _) => {
                 #[cfg(not(feature = "sync"))]
-                return cell.borrow().clone();
+                return cell.borrow().flatten_clone();
 
                 #[cfg(feature = "sync")]
-                return cell.read().unwrap().clone();
+                return cell.read().unwrap().flatten_clone();
             }
             _ => self.clone(),
         }
@@ -983,6 +1128,10 @@ impl Dynamic {
     ///
     /// If the [`Dynamic`] is a shared value, it returns the shared value if there are no
     /// outstanding references, or a cloned copy.
+    /// A shared value nested inside another shared value is also flattened.
+    ///
+    /// Only the top level is flattened; shared values held inside arrays or object maps are kept.
+    /// Use [`deep_flatten`][Dynamic::deep_flatten] to flatten an entire value tree.
     #[inline(always)]
     pub fn flatten(self) -> Self {
         match self.0 {
@@ -990,27 +1139,253 @@ impl Dynamic {
             Union::Shared(cell, _) => crate::fn_native::shared_try_take(cell).map_or_else(
                 |cell| {
                     #[cfg(not(feature = "sync"))]
-                    return cell.borrow().clone();
+                    return cell.borrow().flatten_clone();
                     #[cfg(feature = "sync")]
-                    return cell.read().unwrap().clone();
+                    return cell.read().unwrap().flatten_clone();
                 },
                 |value| {
                     #[cfg(not(feature = "sync"))]
-                    return value.into_inner();
+                    return value.into_inner().flatten();
                     #[cfg(feature = "sync")]
-                    return value.into_inner().unwrap();
+                    return value.into_inner().unwrap().flatten();
                 },
             ),
             _ => self,
         }
     }
-    /// Is the [`Dynamic`] a shared value that is locked?
+    /// Recursively flatten the [`Dynamic`], including all items inside arrays and object maps
+    /// as well as the curried arguments of function pointers.
+    ///
+    /// The result contains no shared values, so it can be safely passed to code (for example,
+    /// another [`Engine`][crate::Engine]) that does not expect them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "no_closure"))]
+    /// # #[cfg(not(feature = "no_index"))]
+    /// # {
+    /// use rhai::{Array, Dynamic};
+    ///
+    /// let array: Array = vec![Dynamic::from(42_i64).into_shared()];
+    /// let value = Dynamic::from(array).into_shared();
+    ///
+    /// let value = value.deep_flatten();
+    ///
+    /// assert!(!value.is_shared());
+    /// assert!(!value.cast::<Array>()[0].is_shared());
+    /// # }
+    /// ```
+    pub fn deep_flatten(self) -> Self {
+        match self.flatten() {
+            #[cfg(not(feature = "no_index"))]
+            Dynamic(Union::Array(mut a, access)) => {
+                a.iter_mut().for_each(|v| *v = mem::take(v).deep_flatten());
+                Dynamic(Union::Array(a, access))
+            }
+            #[cfg(not(feature = "no_object"))]
+            Dynamic(Union::Map(mut m, access)) => {
+                m.iter_mut()
+                    .for_each(|(_, v)| *v = mem::take(v).deep_flatten());
+                Dynamic(Union::Map(m, access))
+            }
+            Dynamic(Union::FnPtr(f, access)) if f.is_curried() => {
+                let (name, curry) = f.take_data();
+                let curry = curry.into_iter().map(Dynamic::deep_flatten).collect();
+                Dynamic(Union::FnPtr(
+                    Box::new(FnPtr::new_unchecked(name, curry)),
+                    access,
+                ))
+            }
+            value => value,
+        }
+    }
+    /// Recursively copy the [`Dynamic`], including all items inside arrays and object maps
+    /// as well as the curried arguments of function pointers, severing all shared values.
+    ///
+    /// Unlike [`deep_flatten`][Dynamic::deep_flatten], the original value is left untouched.
+    ///
+    /// A shared value that contains itself (for example, an array holding a closure that
+    /// captures the array) cannot be copied and returns an error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "no_closure"))]
+    /// # #[cfg(not(feature = "no_index"))]
+    /// # {
+    /// use rhai::{Array, Dynamic};
+    ///
+    /// let item = Dynamic::from(42_i64).into_shared();
+    /// let array: Array = vec![item.clone()];
+    /// let value = Dynamic::from(array);
+    ///
+    /// let copy = value.deep_copy().unwrap();
+    ///
+    /// assert!(!copy.cast::<Array>()[0].is_shared());
+    /// assert!(value.cast::<Array>()[0].ptr_eq(&item));
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn deep_copy(&self) -> Result<Self, Box<crate::EvalAltResult>> {
+        self.deep_copy_with_limits(None, 0, 0)
+    }
+    /// Recursively copy the [`Dynamic`], severing all shared values, and fail when the copy
+    /// holds more array items or object map properties than the limits (zero for no limit).
+    ///
+    /// `container` is the shared value holding this value, if it is currently locked.
+    ///
+    /// Items are counted the same way as the engine's data size limits.
+    ///
+    /// [`Position`][crate::Position] in errors is [`NONE`][crate::Position::NONE] and must be set afterwards.
+    pub(crate) fn deep_copy_with_limits(
+        &self,
+        container: Option<&Dynamic>,
+        max_array_size: usize,
+        max_map_size: usize,
+    ) -> Result<Self, Box<crate::EvalAltResult>> {
+        #[allow(unused_imports)]
+        use crate::{EvalAltResult, Position};
+
+        #[allow(dead_code)]
+        struct State {
+            #[cfg(not(feature = "no_closure"))]
+            visiting: crate::stdlib::vec::Vec<*const crate::fn_native::Locked<Dynamic>>,
+            arrays: usize,
+            maps: usize,
+            max_array_size: usize,
+            max_map_size: usize,
+        }
+
+        #[allow(dead_code)]
+        fn is_container(value: &Dynamic) -> bool {
+            match value.0 {
+                #[cfg(not(feature = "no_index"))]
+                Union::Array(_, _) => true,
+                #[cfg(not(feature = "no_object"))]
+                Union::Map(_, _) => true,
+                _ => false,
+            }
+        }
+
+        fn copy(value: &Dynamic, state: &mut State) -> Result<Dynamic, Box<EvalAltResult>> {
+            match &value.0 {
+                #[cfg(not(feature = "no_closure"))]
+                Union::Shared(cell, _) => {
+                    let ptr: *const _ = &**cell;
+
+                    if state.visiting.contains(&ptr) {
+                        return EvalAltResult::ErrorRuntime(
+                            "Cannot deep-copy a shared value that contains itself".into(),
+                            Position::NONE,
+                        )
+                        .into();
+                    }
+
+                    #[cfg(not(feature = "sync"))]
+                    let inner = cell.try_borrow().ok();
+                    #[cfg(feature = "sync")]
+                    let inner = cell.try_read().ok();
+
+                    let inner = inner.ok_or_else(|| {
+                        EvalAltResult::ErrorDataRace(String::new(), Position::NONE)
+                    })?;
+
+                    state.visiting.push(ptr);
+                    let result = copy(&inner, state);
+                    state.visiting.pop();
+                    result
+                }
+                #[cfg(not(feature = "no_index"))]
+                Union::Array(a, access) => {
+                    let mut array = Array::with_capacity(a.len());
+
+                    for item in a.iter() {
+                        let item = copy(item, state)?;
+
+                        if !is_container(&item) {
+                            state.arrays += 1;
+
+                            if state.max_array_size > 0 && state.arrays > state.max_array_size {
+                                return EvalAltResult::ErrorDataTooLarge(
+                                    "Size of array".to_string(),
+                                    Position::NONE,
+                                )
+                                .into();
+                            }
+                        }
+
+                        array.push(item);
+                    }
+
+                    Ok(Dynamic(Union::Array(Box::new(array), *access)))
+                }
+                #[cfg(not(feature = "no_object"))]
+                Union::Map(m, access) => {
+                    let mut map = Map::new();
+
+                    for (key, item) in m.iter() {
+                        let item = copy(item, state)?;
+
+                        if !is_container(&item) {
+                            state.maps += 1;
+
+                            if state.max_map_size > 0 && state.maps > state.max_map_size {
+                                return EvalAltResult::ErrorDataTooLarge(
+                                    "Size of object map".to_string(),
+                                    Position::NONE,
+                                )
+                                .into();
+                            }
+                        }
+
+                        map.insert(key.clone(), item);
+                    }
+
+                    Ok(Dynamic(Union::Map(Box::new(map), *access)))
+                }
+                Union::FnPtr(f, access) if f.is_curried() => {
+                    let curry = f
+                        .curry()
+                        .iter()
+                        .map(|v| copy(v, state))
+                        .collect::<Result<_, _>>()?;
+
+                    Ok(Dynamic(Union::FnPtr(
+                        Box::new(FnPtr::new_unchecked(f.get_fn_name().clone(), curry)),
+                        *access,
+                    )))
+                }
+                _ => Ok(value.clone()),
+            }
+        }
+
+        let mut state = State {
+            #[cfg(not(feature = "no_closure"))]
+            visiting: Default::default(),
+            arrays: 0,
+            maps: 0,
+            max_array_size,
+            max_map_size,
+        };
+
+        // The container is locked, so it must never be read again
+        match container {
+            #[cfg(not(feature = "no_closure"))]
+            Some(Dynamic(Union::Shared(cell, _))) => state.visiting.push(&**cell),
+            _ => (),
+        }
+
+        copy(self, &mut state)
+    }
+    /// Is the [`Dynamic`] a shared value that is locked for write?
     ///
     /// ## Note
     ///
-    /// Under the `sync` feature, shared values use [`RwLock`][std::sync::RwLock] and they are never locked.
-    /// Access just waits until the [`RwLock`][std::sync::RwLock] is released.
-    /// So this method always returns [`false`] under [`Sync`].
+    /// Under the `sync` feature, shared values use [`RwLock`][std::sync::RwLock], and this method
+    /// returns [`true`] only if the value is currently locked for write by the _current_ thread,
+    /// because accessing it again would deadlock.  A value locked by another thread is not
+    /// considered locked, as accessing it simply waits for the other thread to release the lock.
     #[inline(always)]
     pub fn is_locked(&self) -> bool {
         match self.0 {
@@ -1020,7 +1395,10 @@ impl Dynamic {
                 return _cell.try_borrow().is_err();
 
                 #[cfg(feature = "sync")]
-                return false;
+                {
+                    let addr = crate::Shared::as_ptr(_cell) as usize;
+                    return WRITE_LOCKED.with(|locked| locked.borrow().contains(&addr));
+                }
             }
             _ => false,
         }
@@ -1030,6 +1408,9 @@ impl Dynamic {
     ///
     /// Returns [`None`] if the cast fails.
     ///
+    /// A shared value nested directly inside another shared value is first collapsed into a
+    /// single level, so the data can always be reached through one lock.
+    ///
     /// # Panics or Deadlocks When Value is Shared
     ///
     /// Under the `sync` feature, this call may deadlock, or [panic](https://doc.rust-lang.org/std/sync/struct.RwLock.html#panics-1).
@@ -1039,6 +1420,8 @@ impl Dynamic {
         match self.0 {
             #[cfg(not(feature = "no_closure"))]
             Union::Shared(ref cell, _) => {
+                collapse_nested_shared(cell);
+
                 #[cfg(not(feature = "sync"))]
                 let data = cell.borrow();
                 #[cfg(feature = "sync")]
@@ -1062,6 +1445,9 @@ impl Dynamic {
     ///
     /// Returns [`None`] if the cast fails.
     ///
+    /// A shared value nested directly inside another shared value is first collapsed into a
+    /// single level, so the data can always be reached through one lock.
+    ///
     /// # Panics or Deadlocks When Value is Shared
     ///
     /// Under the `sync` feature, this call may deadlock, or [panic](https://doc.rust-lang.org/std/sync/struct.RwLock.html#panics-1).
@@ -1071,10 +1457,12 @@ impl Dynamic {
         match self.0 {
             #[cfg(not(feature = "no_closure"))]
             Union::Shared(ref cell, _) => {
+                collapse_nested_shared(cell);
+
                 #[cfg(not(feature = "sync"))]
                 let data = cell.borrow_mut();
                 #[cfg(feature = "sync")]
-                let data = cell.write().unwrap();
+                let data = SharedWriteGuard::new(cell);
 
                 let type_id = (*data).type_id();
 
@@ -1417,6 +1805,13 @@ impl<T: Variant + Clone> From<&[T]> for Dynamic {
     }
 }
 #[cfg(not(feature = "no_object"))]
+impl From<Map> for Dynamic {
+    #[inline(always)]
+    fn from(value: Map) -> Self {
+        Self(Union::Map(Box::new(value), AccessMode::ReadWrite))
+    }
+}
+#[cfg(not(feature = "no_object"))]
 impl<K: Into<ImmutableString>, T: Variant + Clone> From<crate::stdlib::collections::HashMap<K, T>>
     for Dynamic
 {
@@ -1,11 +1,14 @@
 //! Main module defining the script evaluation [`Engine`].
 
-use crate::ast::{Expr, FnCallExpr, Ident, ReturnType, Stmt};
-use crate::dynamic::{map_std_type_name, AccessMode, Union, Variant};
+use crate::ast::{CatchClause, Expr, FnCallExpr, Ident, ReturnType, Stmt};
+use crate::compile_cache::CompilationCache;
+use crate::debugger::Debugger;
+use crate::dynamic::{map_std_type_name, AccessMode, DynamicReadLock, Union, Variant};
 use crate::fn_call::run_builtin_op_assignment;
 use crate::fn_native::{
-    CallableFunction, IteratorFn, OnDebugCallback, OnPrintCallback, OnProgressCallback,
-    OnVarCallback,
+    CallableFunction, Locked, OnCatchCallback, OnDebugCallback, OnDebuggerCallback,
+    OnLargeCopyCallback, OnPrintCallback, OnProgressCallback, OnVarCallback, OnVarWriteCallback,
+    TypeIterator,
 };
 use crate::module::NamespaceRef;
 use crate::optimize::OptimizationLevel;
@@ -15,10 +18,11 @@ use crate::stdlib::{
     any::{type_name, TypeId},
     borrow::Cow,
     boxed::Box,
-    collections::{HashMap, HashSet},
+    collections::HashMap,
     fmt, format,
     hash::{Hash, Hasher},
     iter::{empty, once, FromIterator},
+    mem::size_of,
     num::{NonZeroU64, NonZeroU8, NonZeroUsize},
     ops::DerefMut,
     string::{String, ToString},
@@ -26,21 +30,28 @@ use crate::stdlib::{
 use crate::syntax::CustomSyntax;
 use crate::utils::{get_hasher, StraightHasherBuilder};
 use crate::{
-    calc_native_fn_hash, Dynamic, EvalAltResult, FnPtr, ImmutableString, Module, Position, Scope,
-    Shared, StaticVec,
+    calc_native_fn_hash, Associativity, CallAudit, CapturedOutput, CharRange, DebuggerCommand,
+    Dynamic, EvalAltResult, EvalOptions, FnPtr, ImmutableString, Module, Position, Scope, Shared,
+    StaticVec, INT,
 };
 
+#[cfg(not(feature = "no_module"))]
+use crate::fn_native::OnImportCallback;
+
 #[cfg(not(feature = "no_index"))]
 use crate::Array;
 
 #[cfg(not(feature = "no_index"))]
 pub const TYPICAL_ARRAY_SIZE: usize = 8; // Small arrays are typical
 
-#[cfg(not(feature = "no_object"))]
-use crate::Map;
+/// Number of bits in an [`INT`].
+pub const INT_BITS: usize = INT::BITS as usize;
 
 #[cfg(not(feature = "no_object"))]
-pub const TYPICAL_MAP_SIZE: usize = 8; // Small maps are typical
+use crate::{ast::MapKey, Map};
+
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+use crate::ast::Pattern;
 
 /// _(INTERNALS)_ A stack of imported [modules][Module].
 /// Exported under the `internals` feature only.
@@ -54,8 +65,14 @@ pub const TYPICAL_MAP_SIZE: usize = 8; // Small maps are typical
 // We cannot use &str or Cow<str> here because `eval` may load a [module][Module] and
 // the module name will live beyond the AST of the eval script text.
 // The best we can do is a shared reference.
+//
+// Modules resolved during an evaluation are also kept, by path, so that importing the same
+// path again reuses the same module instead of loading it all over.
 #[derive(Debug, Clone, Default)]
-pub struct Imports(StaticVec<(ImmutableString, Shared<Module>)>);
+pub struct Imports(
+    StaticVec<(ImmutableString, Shared<Module>)>,
+    #[cfg(not(feature = "no_module"))] HashMap<ImmutableString, Shared<Module>>,
+);
 
 impl Imports {
     /// Get the length of this stack of imported [modules][Module].
@@ -142,29 +159,45 @@ impl Imports {
     }
     /// Get the specified [`TypeId`][std::any::TypeId] iterator.
     #[inline(always)]
-    pub fn get_iter(&self, id: TypeId) -> Option<IteratorFn> {
+    pub fn get_iter(&self, id: TypeId) -> Option<TypeIterator> {
         self.0
             .iter()
             .rev()
             .find_map(|(_, m)| m.get_qualified_iter(id))
     }
+    /// Get the [module][Module] previously resolved from a path during this evaluation.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn get_resolved(&self, path: &str) -> Option<Shared<Module>> {
+        self.1.get(path).cloned()
+    }
+    /// Keep the [module][Module] resolved from a path, for reuse during this evaluation.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn set_resolved(
+        &mut self,
+        path: impl Into<ImmutableString>,
+        module: impl Into<Shared<Module>>,
+    ) {
+        self.1.insert(path.into(), module.into());
+    }
 }
 
 impl<'a, T: IntoIterator<Item = (&'a ImmutableString, &'a Shared<Module>)>> From<T> for Imports {
     #[inline(always)]
     fn from(value: T) -> Self {
-        Self(
-            value
-                .into_iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect(),
-        )
+        value
+            .into_iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
     }
 }
 impl FromIterator<(ImmutableString, Shared<Module>)> for Imports {
     #[inline(always)]
     fn from_iter<T: IntoIterator<Item = (ImmutableString, Shared<Module>)>>(iter: T) -> Self {
-        Self(iter.into_iter().collect())
+        let mut imports = Self::default();
+        imports.extend(iter.into_iter());
+        imports
     }
 }
 
@@ -192,6 +225,13 @@ pub const MAX_EXPR_DEPTH: usize = 128;
 #[cfg(not(debug_assertions))]
 pub const MAX_FUNCTION_EXPR_DEPTH: usize = 32;
 
+#[cfg(not(feature = "no_function"))]
+#[cfg(debug_assertions)]
+pub const MAX_BACKTRACE_DEPTH: usize = 6;
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(debug_assertions))]
+pub const MAX_BACKTRACE_DEPTH: usize = 32;
+
 pub const KEYWORD_PRINT: &str = "print";
 pub const KEYWORD_DEBUG: &str = "debug";
 pub const KEYWORD_TYPE_OF: &str = "type_of";
@@ -201,9 +241,17 @@ pub const KEYWORD_FN_PTR_CALL: &str = "call";
 pub const KEYWORD_FN_PTR_CURRY: &str = "curry";
 #[cfg(not(feature = "no_closure"))]
 pub const KEYWORD_IS_SHARED: &str = "is_shared";
+pub const KEYWORD_SAME_INSTANCE: &str = "same_instance";
+pub const KEYWORD_DEEP_COPY: &str = "deep_copy";
 pub const KEYWORD_IS_DEF_VAR: &str = "is_def_var";
 pub const KEYWORD_THIS: &str = "this";
 #[cfg(not(feature = "no_object"))]
+pub const FN_COMPILE_EXPRESSION: &str = "compile_expression";
+#[cfg(not(feature = "no_object"))]
+pub const FN_COMPILE_CHECK: &str = "compile_check";
+#[cfg(not(feature = "no_object"))]
+pub const FN_RENDER: &str = "render";
+#[cfg(not(feature = "no_object"))]
 pub const FN_GET: &str = "get$";
 #[cfg(not(feature = "no_object"))]
 pub const FN_SET: &str = "set$";
@@ -215,6 +263,12 @@ pub const FN_IDX_SET: &str = "index$set$";
 pub const FN_ANONYMOUS: &str = "anon$";
 #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
 pub const OP_EQUALS: &str = "==";
+#[cfg(not(feature = "no_index"))]
+pub const OP_LESS_THAN: &str = "<";
+
+/// A new value to assign, together with the assignment operator (empty for a normal assignment)
+/// and its position.
+pub type NewValue<'a> = ((Dynamic, Position), (&'a str, Position));
 
 /// A type specifying the method of chaining.
 #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
@@ -295,6 +349,10 @@ pub enum Target<'a> {
     /// This is necessary because directly pointing to a char inside a String is impossible.
     #[cfg(not(feature = "no_index"))]
     StringChar(&'a mut Dynamic, usize, Dynamic),
+    /// The target is a bit inside an [`INT`].
+    /// This is necessary because directly pointing to a bit inside an [`INT`] is impossible.
+    #[cfg(not(feature = "no_index"))]
+    BitField(&'a mut Dynamic, usize, Dynamic),
 }
 
 impl<'a> Target<'a> {
@@ -310,6 +368,8 @@ impl<'a> Target<'a> {
             Self::Value(_) => false,
             #[cfg(not(feature = "no_index"))]
             Self::StringChar(_, _, _) => false,
+            #[cfg(not(feature = "no_index"))]
+            Self::BitField(_, _, _) => false,
         }
     }
     /// Is the `Target` an owned value?
@@ -324,6 +384,8 @@ impl<'a> Target<'a> {
             Self::Value(_) => true,
             #[cfg(not(feature = "no_index"))]
             Self::StringChar(_, _, _) => false,
+            #[cfg(not(feature = "no_index"))]
+            Self::BitField(_, _, _) => false,
         }
     }
     /// Is the `Target` a shared value?
@@ -338,6 +400,24 @@ impl<'a> Target<'a> {
             Self::Value(r) => r.is_shared(),
             #[cfg(not(feature = "no_index"))]
             Self::StringChar(_, _, _) => false,
+            #[cfg(not(feature = "no_index"))]
+            Self::BitField(_, _, _) => false,
+        }
+    }
+    /// Does the `Target` refer to the same shared value as another [`Dynamic`]?
+    #[allow(dead_code)]
+    #[inline(always)]
+    pub fn ptr_eq(&self, other: &Dynamic) -> bool {
+        match self {
+            Self::Ref(r) => r.ptr_eq(other),
+            #[cfg(not(feature = "no_closure"))]
+            #[cfg(not(feature = "no_object"))]
+            Self::LockGuard((_, orig)) => orig.ptr_eq(other),
+            Self::Value(r) => r.ptr_eq(other),
+            #[cfg(not(feature = "no_index"))]
+            Self::StringChar(_, _, _) => false,
+            #[cfg(not(feature = "no_index"))]
+            Self::BitField(_, _, _) => false,
         }
     }
     /// Is the `Target` a specific type?
@@ -352,6 +432,8 @@ impl<'a> Target<'a> {
             Target::Value(r) => r.is::<T>(),
             #[cfg(not(feature = "no_index"))]
             Target::StringChar(_, _, _) => TypeId::of::<T>() == TypeId::of::<char>(),
+            #[cfg(not(feature = "no_index"))]
+            Target::BitField(_, _, _) => TypeId::of::<T>() == TypeId::of::<bool>(),
         }
     }
     /// Get the value of the `Target` as a `Dynamic`, cloning a referenced value if necessary.
@@ -365,6 +447,8 @@ impl<'a> Target<'a> {
             Self::Value(v) => v,       // Owned value is simply taken
             #[cfg(not(feature = "no_index"))]
             Self::StringChar(_, _, ch) => ch, // Character is taken
+            #[cfg(not(feature = "no_index"))]
+            Self::BitField(_, _, bit) => bit, // Bit is taken
         }
     }
     /// Take a `&mut Dynamic` reference from the `Target`.
@@ -381,7 +465,7 @@ impl<'a> Target<'a> {
         self.take_or_clone().into()
     }
     /// Propagate a changed value back to the original source.
-    /// This has no effect except for string indexing.
+    /// This has no effect except for string and bit-field indexing.
     #[cfg(not(feature = "no_object"))]
     #[inline(always)]
     pub fn propagate_changed_value(&mut self) {
@@ -394,11 +478,19 @@ impl<'a> Target<'a> {
                 let char_value = ch.clone();
                 self.set_value(char_value, Position::NONE).unwrap();
             }
+            #[cfg(not(feature = "no_index"))]
+            Self::BitField(_, _, bit) => {
+                let bit_value = bit.clone();
+                self.set_value(bit_value, Position::NONE).unwrap();
+            }
         }
     }
     /// Update the value of the `Target`.
-    #[cfg(any(not(feature = "no_object"), not(feature = "no_index")))]
-    pub fn set_value(&mut self, new_val: Dynamic, pos: Position) -> Result<(), Box<EvalAltResult>> {
+    pub fn set_value(
+        &mut self,
+        new_val: Dynamic,
+        _pos: Position,
+    ) -> Result<(), Box<EvalAltResult>> {
         match self {
             Self::Ref(r) => **r = new_val,
             #[cfg(not(feature = "no_closure"))]
@@ -414,7 +506,7 @@ impl<'a> Target<'a> {
                     Box::new(EvalAltResult::ErrorMismatchDataType(
                         err.to_string(),
                         "char".to_string(),
-                        pos,
+                        _pos,
                     ))
                 })?;
 
@@ -428,6 +520,29 @@ impl<'a> Target<'a> {
             }
             #[cfg(not(feature = "no_index"))]
             Self::StringChar(_, _, _) => unreachable!(),
+            #[cfg(not(feature = "no_index"))]
+            Self::BitField(value, index, _) if value.is::<INT>() => {
+                let mut value = value.write_lock::<INT>().unwrap();
+
+                // Set or clear the bit at the specified index position
+                let new_bit = new_val.as_bool().map_err(|err| {
+                    Box::new(EvalAltResult::ErrorMismatchDataType(
+                        err.to_string(),
+                        "bool".to_string(),
+                        _pos,
+                    ))
+                })?;
+
+                let mask = 1 << *index;
+
+                if new_bit {
+                    *value |= mask;
+                } else {
+                    *value &= !mask;
+                }
+            }
+            #[cfg(not(feature = "no_index"))]
+            Self::BitField(_, _, _) => unreachable!(),
         }
 
         Ok(())
@@ -460,6 +575,8 @@ impl AsRef<Dynamic> for Target<'_> {
             Self::Value(ref r) => r,
             #[cfg(not(feature = "no_index"))]
             Self::StringChar(_, _, ref r) => r,
+            #[cfg(not(feature = "no_index"))]
+            Self::BitField(_, _, ref r) => r,
         }
     }
 }
@@ -475,6 +592,8 @@ impl AsMut<Dynamic> for Target<'_> {
             Self::Value(ref mut r) => r,
             #[cfg(not(feature = "no_index"))]
             Self::StringChar(_, _, ref mut r) => r,
+            #[cfg(not(feature = "no_index"))]
+            Self::BitField(_, _, ref mut r) => r,
         }
     }
 }
@@ -507,8 +626,21 @@ pub struct State {
     pub operations: u64,
     /// Number of modules loaded.
     pub modules: usize,
+    /// Deepest nesting level of script-defined function calls.
+    pub max_call_level: usize,
+    /// Estimated total number of bytes of data created during this evaluation.
+    #[cfg(not(feature = "unchecked"))]
+    pub data_size: usize,
     /// Cached lookup values for function hashes.
     pub functions_cache: HashMap<NonZeroU64, Option<CallableFunction>, StraightHasherBuilder>,
+    /// Overrides of the [`Engine`] settings for this evaluation.
+    pub options: EvalOptions,
+    /// Buffer capturing the output of `print` and `debug` during this evaluation, if enabled.
+    pub output: Option<CapturedOutput>,
+    /// State of the debugger during this evaluation, if one is attached.
+    pub debugger: Option<Debugger>,
+    /// Record of the functions called during this evaluation, if enabled.
+    pub audit: Option<CallAudit>,
 }
 
 impl State {
@@ -554,6 +686,10 @@ pub struct Limits {
     /// Not available under `no_object`.
     #[cfg(not(feature = "no_object"))]
     pub max_map_size: usize,
+    /// Maximum number of variables declared at the same time (0 = unlimited).
+    pub max_variables: usize,
+    /// Maximum estimated total number of bytes of data created during an evaluation (0 = unlimited).
+    pub max_total_data_size: usize,
 }
 
 /// Context of a script evaluation process.
@@ -612,6 +748,37 @@ impl<'e, 'x, 'px, 'a, 's, 'm, 'pm, 't, 'pt> EvalContext<'e, 'x, 'px, 'a, 's, 'm,
     pub fn call_level(&self) -> usize {
         self.level
     }
+    /// Names of the script-defined functions currently being called, outermost first.
+    ///
+    /// Only tracked when a debugger is attached via [`Engine::on_debugger`];
+    /// otherwise this is always empty.
+    #[inline(always)]
+    pub fn call_stack(&self) -> &[ImmutableString] {
+        self.state
+            .debugger
+            .as_ref()
+            .map_or(&[], |debugger| debugger.call_stack.as_slice())
+    }
+}
+
+/// Can a method call that modifies its receiver in place and returns `()` evaluate to (or continue
+/// a dot chain with) the receiver itself?
+///
+/// Only receivers of custom types qualify.  Many methods of built-in types take the receiver by
+/// reference for speed only, and return `()` as a value (e.g. `pop` on an empty array).
+#[cfg(not(feature = "no_object"))]
+#[inline(always)]
+fn is_fluent_receiver(target: &Target) -> bool {
+    target.as_ref().is_variant()
+}
+
+/// A custom keyword or symbol recognized by an [`Engine`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub(crate) struct CustomKeyword {
+    /// Precedence of the custom operator, or `None` if it is not an operator.
+    pub precedence: Option<NonZeroU8>,
+    /// Associativity of the custom operator.
+    pub associativity: Associativity,
 }
 
 /// Rhai main scripting engine.
@@ -645,24 +812,41 @@ pub struct Engine {
     pub(crate) global_namespace: Module,
     /// A collection of all modules loaded into the global namespace of the Engine.
     pub(crate) global_modules: StaticVec<Shared<Module>>,
+    /// A collection of all modules whose variables are global constants.
+    pub(crate) global_constants: StaticVec<Shared<Module>>,
     /// A collection of all sub-modules directly loaded into the Engine.
     pub(crate) global_sub_modules: HashMap<ImmutableString, Shared<Module>>,
 
     /// A module resolution service.
     #[cfg(not(feature = "no_module"))]
     pub(crate) module_resolver: Box<dyn crate::ModuleResolver>,
+    /// Callback closure for filtering module imports.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) module_filter: Option<OnImportCallback>,
+    /// Callback closure for deciding which errors can be caught by `try` ... `catch`.
+    pub(crate) catch_filter: Option<OnCatchCallback>,
 
     /// A hashmap mapping type names to pretty-print names.
     pub(crate) type_names: HashMap<String, String>,
-
-    /// A hashset containing symbols to disable.
-    pub(crate) disabled_symbols: HashSet<String>,
-    /// A hashmap containing custom keywords and precedence to recognize.
-    pub(crate) custom_keywords: HashMap<String, Option<NonZeroU8>>,
+    /// A hashmap containing serialization hooks of custom types.
+    #[cfg(feature = "serde")]
+    #[cfg(not(feature = "no_object"))]
+    pub(crate) custom_types_serde: HashMap<TypeId, crate::serde_impl::custom::CustomTypeSerde>,
+
+    /// A hashmap containing symbols to disable, with optional messages explaining why.
+    pub(crate) disabled_symbols: HashMap<String, Option<String>>,
+    /// A hashmap containing custom keywords, and precedence and associativity of custom operators, to recognize.
+    pub(crate) custom_keywords: HashMap<String, CustomKeyword>,
+    /// A hashmap containing overridden precedence of built-in operators.
+    pub(crate) operator_precedence: HashMap<String, NonZeroU8>,
     /// Custom syntax.
     pub(crate) custom_syntax: HashMap<ImmutableString, CustomSyntax>,
     /// Callback closure for resolving variable access.
     pub(crate) resolve_var: Option<OnVarCallback>,
+    /// Callback closure for intercepting variable assignments.
+    pub(crate) write_var: Option<OnVarWriteCallback>,
+    /// Size threshold and callback closure for reporting large implicit copies of variables.
+    pub(crate) large_copy: Option<(usize, OnLargeCopyCallback)>,
 
     /// Callback closure for implementing the `print` command.
     pub(crate) print: OnPrintCallback,
@@ -670,6 +854,8 @@ pub struct Engine {
     pub(crate) debug: OnDebugCallback,
     /// Callback closure for progress reporting.
     pub(crate) progress: Option<OnProgressCallback>,
+    /// Callback closure for debugging scripts.
+    pub(crate) debugger: Option<OnDebuggerCallback>,
 
     /// Optimize the AST after compilation.
     pub(crate) optimization_level: OptimizationLevel,
@@ -680,6 +866,35 @@ pub struct Engine {
 
     /// Disable doc-comments?
     pub(crate) disable_doc_comments: bool,
+
+    /// Disallow variables and module aliases with the same name in the same scope?
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) strict_module_aliases: bool,
+
+    /// Allow scripts to compile expressions via `compile_expression`?
+    #[cfg(not(feature = "no_object"))]
+    pub(crate) allow_compile_expression: bool,
+    /// Allow scripts to fill in string templates via `render`?
+    #[cfg(not(feature = "no_object"))]
+    pub(crate) allow_render: bool,
+
+    /// Disallow redeclared and undeclared variables?
+    pub(crate) strict_variables: bool,
+    /// Disallow function pointers to functions that do not exist?
+    pub(crate) strict_fn_pointers: bool,
+    /// Call functions taking `FLOAT` parameters with `INT` arguments if there is no exact match?
+    #[cfg(not(feature = "no_float"))]
+    pub(crate) int_to_float_promotion: bool,
+
+    /// Keep the original script text in compiled [`AST`]'s?
+    pub(crate) retain_source: bool,
+
+    /// Cache of compiled scripts, if enabled.
+    pub(crate) compilation_cache: Option<Locked<CompilationCache>>,
+
+    /// Are functions in module scripts private unless explicitly exported?
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) module_fn_private_by_default: bool,
 }
 
 impl fmt::Debug for Engine {
@@ -721,9 +936,127 @@ pub fn is_anonymous_fn(fn_name: &str) -> bool {
     fn_name.starts_with(FN_ANONYMOUS)
 }
 
+/// Set the value of a loop variable, which may be shared.
+#[inline(always)]
+fn set_loop_var(loop_var: &mut Dynamic, value: Dynamic) {
+    if cfg!(not(feature = "no_closure")) && loop_var.is_shared() {
+        *loop_var.write_lock().unwrap() = value;
+    } else {
+        *loop_var = value;
+    }
+}
+
+/// Is a `break`/`continue` with the target label (if any) meant for a loop with the specified label?
+#[inline(always)]
+fn is_loop_target(target: &Option<ImmutableString>, label: &Option<ImmutableString>) -> bool {
+    target.is_none() || target == label
+}
+
+/// Estimate the number of bytes taken up by a [string][ImmutableString], [`Array`] or [`Map`],
+/// including the values inside it only if `deep` is `true`.  Other values take up no bytes.
+#[cfg(not(feature = "unchecked"))]
+pub(crate) fn calc_data_footprint(value: &Dynamic, deep: bool) -> usize {
+    #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+    let inner_size = |value: &Dynamic| {
+        if deep {
+            calc_data_footprint(value, true)
+        } else {
+            0
+        }
+    };
+
+    match value.0 {
+        Union::Str(ref s, _) => s.len(),
+        #[cfg(not(feature = "no_index"))]
+        Union::Array(ref arr, _) => {
+            arr.len() * size_of::<Dynamic>() + arr.iter().map(inner_size).sum::<usize>()
+        }
+        #[cfg(not(feature = "no_object"))]
+        Union::Map(ref map, _) => map
+            .iter()
+            .map(|(key, value)| {
+                size_of::<ImmutableString>() + key.len() + size_of::<Dynamic>() + inner_size(value)
+            })
+            .sum(),
+        #[cfg(not(feature = "no_closure"))]
+        Union::Shared(_, _) => value
+            .read_lock::<Dynamic>()
+            .map_or(0, |value| calc_data_footprint(&value, deep)),
+        _ => 0,
+    }
+}
+
+/// Recursively calculate the size of a value (especially [`Array`] and [`Map`]), returning the
+/// total number of array items, object map properties and string characters.
+fn calc_data_sizes(value: &Dynamic) -> (usize, usize, usize) {
+    match value {
+        #[cfg(not(feature = "no_index"))]
+        Dynamic(Union::Array(arr, _)) => {
+            let mut arrays = 0;
+            let mut maps = 0;
+
+            arr.iter().for_each(|value| match value {
+                Dynamic(Union::Array(_, _)) => {
+                    let (a, m, _) = calc_data_sizes(value);
+                    arrays += a;
+                    maps += m;
+                }
+                #[cfg(not(feature = "no_object"))]
+                Dynamic(Union::Map(_, _)) => {
+                    let (a, m, _) = calc_data_sizes(value);
+                    arrays += a;
+                    maps += m;
+                }
+                _ => arrays += 1,
+            });
+
+            (arrays, maps, 0)
+        }
+        #[cfg(not(feature = "no_object"))]
+        Dynamic(Union::Map(map, _)) => {
+            let mut arrays = 0;
+            let mut maps = 0;
+
+            map.values().for_each(|value| match value {
+                #[cfg(not(feature = "no_index"))]
+                Dynamic(Union::Array(_, _)) => {
+                    let (a, m, _) = calc_data_sizes(value);
+                    arrays += a;
+                    maps += m;
+                }
+                Dynamic(Union::Map(_, _)) => {
+                    let (a, m, _) = calc_data_sizes(value);
+                    arrays += a;
+                    maps += m;
+                }
+                _ => maps += 1,
+            });
+
+            (arrays, maps, 0)
+        }
+        Dynamic(Union::Str(s, _)) => (0, 0, s.len()),
+        _ => (0, 0, 0),
+    }
+}
+
+/// Calculate the offset of a bit inside an [`INT`] from a bit index.
+/// Negative indices count backwards from the most-significant bit (i.e. `-1` is the MSB).
+/// The [`Position`] of the returned error is [`NONE`][Position::NONE] and must be set afterwards.
+pub(crate) fn calc_bit_offset(index: INT) -> Result<usize, Box<EvalAltResult>> {
+    let bits = INT_BITS as INT;
+
+    let offset = if index < 0 { bits + index } else { index };
+
+    if offset >= 0 && offset < bits {
+        Ok(offset as usize)
+    } else {
+        EvalAltResult::ErrorBitFieldBounds(INT_BITS, index, Position::NONE).into()
+    }
+}
+
 /// Print to stdout
 #[inline(always)]
-fn default_print(_s: &str) {
+fn default_print(_s: &str, _source: Option<&str>, _pos: Position) {
     #[cfg(not(feature = "no_std"))]
     #[cfg(not(target_arch = "wasm32"))]
     println!("{}", _s);
@@ -763,7 +1096,12 @@ pub fn search_imports(
     } else {
         mods.find(root)
             .map(|n| mods.get(n).expect("invalid index in Imports"))
-            .ok_or_else(|| EvalAltResult::ErrorModuleNotFound(root.to_string(), *pos))?
+            .ok_or_else(|| match namespace.shadowing_var() {
+                Some(var_pos) => {
+                    EvalAltResult::ErrorModuleShadowed(root.to_string(), var_pos, *pos)
+                }
+                None => EvalAltResult::ErrorModuleNotFound(root.to_string(), *pos),
+            })?
     })
 }
 
@@ -777,6 +1115,7 @@ impl Engine {
 
             global_namespace: Default::default(),
             global_modules: Default::default(),
+            global_constants: Default::default(),
             global_sub_modules: Default::default(),
 
             #[cfg(not(feature = "no_module"))]
@@ -786,14 +1125,23 @@ impl Engine {
             #[cfg(not(feature = "no_module"))]
             #[cfg(any(feature = "no_std", target_arch = "wasm32",))]
             module_resolver: None,
+            #[cfg(not(feature = "no_module"))]
+            module_filter: None,
+            catch_filter: None,
 
             type_names: Default::default(),
+            #[cfg(feature = "serde")]
+            #[cfg(not(feature = "no_object"))]
+            custom_types_serde: Default::default(),
             disabled_symbols: Default::default(),
             custom_keywords: Default::default(),
+            operator_precedence: Default::default(),
             custom_syntax: Default::default(),
 
             // variable resolver
             resolve_var: None,
+            write_var: None,
+            large_copy: None,
 
             // default print/debug implementations
             print: Box::new(default_print),
@@ -801,6 +1149,7 @@ impl Engine {
 
             // progress callback
             progress: None,
+            debugger: None,
 
             // optimization level
             optimization_level: if cfg!(feature = "no_optimize") {
@@ -824,9 +1173,29 @@ impl Engine {
                 max_array_size: 0,
                 #[cfg(not(feature = "no_object"))]
                 max_map_size: 0,
+                max_variables: 0,
+                max_total_data_size: 0,
             },
 
             disable_doc_comments: false,
+
+            #[cfg(not(feature = "no_module"))]
+            strict_module_aliases: false,
+
+            #[cfg(not(feature = "no_object"))]
+            allow_compile_expression: false,
+            #[cfg(not(feature = "no_object"))]
+            allow_render: true,
+
+            strict_variables: false,
+            strict_fn_pointers: false,
+            #[cfg(not(feature = "no_float"))]
+            int_to_float_promotion: false,
+            retain_source: false,
+            compilation_cache: None,
+
+            #[cfg(not(feature = "no_module"))]
+            module_fn_private_by_default: false,
         };
 
         engine.register_global_module(StandardPackage::new().as_shared_module());
@@ -843,21 +1212,32 @@ impl Engine {
 
             global_namespace: Default::default(),
             global_modules: Default::default(),
+            global_constants: Default::default(),
             global_sub_modules: Default::default(),
 
             #[cfg(not(feature = "no_module"))]
             module_resolver: Box::new(crate::module::resolvers::DummyModuleResolver::new()),
+            #[cfg(not(feature = "no_module"))]
+            module_filter: None,
+            catch_filter: None,
 
             type_names: Default::default(),
+            #[cfg(feature = "serde")]
+            #[cfg(not(feature = "no_object"))]
+            custom_types_serde: Default::default(),
             disabled_symbols: Default::default(),
             custom_keywords: Default::default(),
+            operator_precedence: Default::default(),
             custom_syntax: Default::default(),
 
             resolve_var: None,
+            write_var: None,
+            large_copy: None,
 
-            print: Box::new(|_| {}),
+            print: Box::new(|_, _, _| {}),
             debug: Box::new(|_, _, _| {}),
             progress: None,
+            debugger: None,
 
             optimization_level: if cfg!(feature = "no_optimize") {
                 OptimizationLevel::None
@@ -880,9 +1260,29 @@ impl Engine {
                 max_array_size: 0,
                 #[cfg(not(feature = "no_object"))]
                 max_map_size: 0,
+                max_variables: 0,
+                max_total_data_size: 0,
             },
 
             disable_doc_comments: false,
+
+            #[cfg(not(feature = "no_module"))]
+            strict_module_aliases: false,
+
+            #[cfg(not(feature = "no_object"))]
+            allow_compile_expression: false,
+            #[cfg(not(feature = "no_object"))]
+            allow_render: false,
+
+            strict_variables: false,
+            strict_fn_pointers: false,
+            #[cfg(not(feature = "no_float"))]
+            int_to_float_promotion: false,
+            retain_source: false,
+            compilation_cache: None,
+
+            #[cfg(not(feature = "no_module"))]
+            module_fn_private_by_default: false,
         }
     }
 
@@ -978,25 +1378,37 @@ impl Engine {
             scope.len() - index
         } else {
             // Find the variable in the scope
-            scope
-                .get_index(name)
-                .ok_or_else(|| EvalAltResult::ErrorVariableNotFound(name.to_string(), *pos))?
-                .0
+            match scope.get_index(name) {
+                Some((index, _)) => index,
+                // Find the variable in the global constants
+                None => {
+                    return match self.global_constants.iter().find_map(|m| m.get_var(name)) {
+                        Some(mut value) => {
+                            value.set_access_mode(AccessMode::ReadOnly);
+                            Ok((value.into(), *pos))
+                        }
+                        None => EvalAltResult::ErrorVariableNotFound(name.to_string(), *pos).into(),
+                    }
+                }
+            }
         };
 
         let val = scope.get_mut_by_index(index);
 
-        // Check for data race - probably not necessary because the only place it should conflict is in a method call
-        //                       when the object variable is also used as a parameter.
-        // if cfg!(not(feature = "no_closure")) && val.is_locked() {
-        //     return EvalAltResult::ErrorDataRace(name.into(), *pos).into();
-        // }
+        // Check for data race - a shared variable is locked while a method is called on it,
+        //                       so it must not be accessed again via another path (e.g. a closure).
+        if cfg!(not(feature = "no_closure")) && val.is_locked() {
+            return EvalAltResult::ErrorDataRace(name.to_string(), *pos).into();
+        }
 
         Ok((val.into(), *pos))
     }
 
     /// Chain-evaluate a dot/index chain.
     /// [`Position`] in [`EvalAltResult`] is [`None`][Position::None] and must be set afterwards.
+    ///
+    /// If `optional` is `true` (i.e. `?.` or `?[`), the rest of the chain is skipped, evaluating to `()`,
+    /// when `target` or the value accessed from it is `()`.
     #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
     fn eval_dot_index_chain_helper(
         &self,
@@ -1008,8 +1420,9 @@ impl Engine {
         rhs: &Expr,
         idx_values: &mut StaticVec<IndexChainValue>,
         chain_type: ChainType,
+        optional: bool,
         level: usize,
-        new_val: Option<(Dynamic, Position)>,
+        new_val: Option<NewValue>,
     ) -> Result<(Dynamic, bool), Box<EvalAltResult>> {
         if chain_type == ChainType::None {
             unreachable!("should not be ChainType::None");
@@ -1017,10 +1430,10 @@ impl Engine {
 
         let is_ref = target.is_ref();
 
-        let next_chain = match rhs {
-            Expr::Index(_, _) => ChainType::Index,
-            Expr::Dot(_, _) => ChainType::Dot,
-            _ => ChainType::None,
+        let (next_chain, next_optional) = match rhs {
+            Expr::Index(_, optional, _) => (ChainType::Index, *optional),
+            Expr::Dot(_, optional, _) => (ChainType::Dot, *optional),
+            _ => (ChainType::None, false),
         };
 
         // Pop the last index value
@@ -1028,14 +1441,42 @@ impl Engine {
 
         let target_val = target.as_mut();
 
+        // ()?.xxx or ()?[xxx]
+        if optional && target_val.is::<()>() {
+            return Ok((Dynamic::UNIT, false));
+        }
+
         match chain_type {
             #[cfg(not(feature = "no_index"))]
             ChainType::Index => {
                 let pos = rhs.position();
 
+                // xxx[idx1, idx2, ...] - multiple indices can only be handled by indexer functions
+                let idx_val = match idx_val {
+                    IndexChainValue::FnCallArgs(idx_vals) => {
+                        return self.eval_multi_index(
+                            mods,
+                            state,
+                            lib,
+                            this_ptr,
+                            target_val,
+                            rhs,
+                            idx_vals,
+                            idx_values,
+                            next_chain,
+                            optional,
+                            next_optional,
+                            is_ref,
+                            level,
+                            new_val,
+                        );
+                    }
+                    idx_val => idx_val,
+                };
+
                 match rhs {
                     // xxx[idx].expr... | xxx[idx][expr]...
-                    Expr::Dot(x, x_pos) | Expr::Index(x, x_pos) => {
+                    Expr::Dot(x, _, x_pos) | Expr::Index(x, _, x_pos) => {
                         let idx_pos = x.lhs.position();
                         let idx_val = idx_val.as_value();
                         let obj_ptr = &mut self.get_indexed_mut(
@@ -1043,9 +1484,23 @@ impl Engine {
                             level,
                         )?;
 
+                        // xxx?[idx] is () - skip the rest of the chain
+                        if optional && obj_ptr.as_ref().is::<()>() {
+                            return Ok((Dynamic::UNIT, false));
+                        }
+
                         self.eval_dot_index_chain_helper(
-                            mods, state, lib, this_ptr, obj_ptr, &x.rhs, idx_values, next_chain,
-                            level, new_val,
+                            mods,
+                            state,
+                            lib,
+                            this_ptr,
+                            obj_ptr,
+                            &x.rhs,
+                            idx_values,
+                            next_chain,
+                            next_optional,
+                            level,
+                            new_val,
                         )
                         .map_err(|err| err.fill_position(*x_pos))
                     }
@@ -1054,33 +1509,99 @@ impl Engine {
                         let idx_val = idx_val.as_value();
                         let mut idx_val2 = idx_val.clone();
 
+                        // An index setter registered for arrays or object maps takes precedence
+                        let overridden = Self::is_indexer_overridable(target_val)
+                            && self.has_native_override(
+                                mods,
+                                FN_IDX_SET,
+                                [
+                                    target_val.type_id(),
+                                    idx_val.type_id(),
+                                    new_val.as_ref().unwrap().0 .0.type_id(),
+                                ]
+                                .iter()
+                                .cloned(),
+                            );
+
                         // `call_setter` is introduced to bypass double mutable borrowing of target
-                        let _call_setter = match self.get_indexed_mut(
-                            mods, state, lib, target_val, idx_val, pos, true, is_ref, false, level,
-                        ) {
-                            // Indexed value is a reference - update directly
-                            Ok(ref mut obj_ptr) => {
-                                let (new_val, new_val_pos) = new_val.unwrap();
-                                obj_ptr.set_value(new_val, new_val_pos)?;
-                                None
+                        let _call_setter = if overridden {
+                            Some(new_val.unwrap())
+                        } else {
+                            match self.get_indexed_mut(
+                                mods, state, lib, target_val, idx_val, pos, true, is_ref, false,
+                                level,
+                            ) {
+                                // Indexed value is a reference - update directly
+                                Ok(ref mut obj_ptr) => {
+                                    self.eval_op_assignment(
+                                        mods,
+                                        state,
+                                        lib,
+                                        obj_ptr,
+                                        new_val.unwrap(),
+                                        level,
+                                    )?;
+                                    None
+                                }
+                                Err(err) => match *err {
+                                    // No index getter - try to call an index setter
+                                    #[cfg(not(feature = "no_index"))]
+                                    EvalAltResult::ErrorIndexingType(_, _) => {
+                                        Some(new_val.unwrap())
+                                    }
+                                    // Any other error - return
+                                    err => return Err(Box::new(err)),
+                                },
                             }
-                            Err(err) => match *err {
-                                // No index getter - try to call an index setter
-                                #[cfg(not(feature = "no_index"))]
-                                EvalAltResult::ErrorIndexingType(_, _) => Some(new_val.unwrap()),
-                                // Any other error - return
-                                err => return Err(Box::new(err)),
-                            },
                         };
 
                         #[cfg(not(feature = "no_index"))]
-                        if let Some(mut new_val) = _call_setter {
+                        if let Some(((mut new_val, new_val_pos), op_info)) = _call_setter {
+                            // xxx[rhs] op= new_val - get the current value via the index getter
+                            if !op_info.0.is_empty() {
+                                let mut val = self
+                                    .get_indexed_mut(
+                                        mods,
+                                        state,
+                                        lib,
+                                        target_val,
+                                        idx_val2.clone(),
+                                        pos,
+                                        false,
+                                        is_ref,
+                                        true,
+                                        level,
+                                    )?
+                                    .take_or_clone()
+                                    .into();
+                                self.eval_op_assignment(
+                                    mods,
+                                    state,
+                                    lib,
+                                    &mut val,
+                                    ((new_val, new_val_pos), op_info),
+                                    level,
+                                )?;
+                                new_val = val.take_or_clone();
+                            }
+
                             let val_type_name = target_val.type_name();
-                            let args = &mut [target_val, &mut idx_val2, &mut new_val.0];
+                            let args = &mut [target_val, &mut idx_val2, &mut new_val];
 
                             self.exec_fn_call(
-                                mods, state, lib, FN_IDX_SET, None, args, is_ref, true, false,
-                                new_val.1, None, None, level,
+                                mods,
+                                state,
+                                lib,
+                                FN_IDX_SET,
+                                None,
+                                args,
+                                is_ref,
+                                true,
+                                false,
+                                new_val_pos,
+                                None,
+                                None,
+                                level,
                             )
                             .map_err(|err| match *err {
                                 EvalAltResult::ErrorFunctionNotFound(fn_sig, _)
@@ -1121,10 +1642,21 @@ impl Engine {
                         } = x.as_ref();
                         let def_value = def_value.as_ref();
                         let args = idx_val.as_fn_call_args();
-                        self.make_method_call(
+                        let (result, updated) = self.make_method_call(
                             mods, state, lib, name, *hash, target, args, def_value, false, *pos,
                             level,
-                        )
+                        )?;
+
+                        // {temp}.fn_name(...) returning () - evaluate to the modified temporary
+                        if result.is::<()>()
+                            && updated
+                            && target.is_value()
+                            && is_fluent_receiver(target)
+                        {
+                            Ok((crate::stdlib::mem::take(target.as_mut()), updated))
+                        } else {
+                            Ok((result, updated))
+                        }
                     }
                     // xxx.fn_name(...) = ???
                     Expr::FnCall(_, _) if new_val.is_some() => {
@@ -1134,34 +1666,86 @@ impl Engine {
                     Expr::FnCall(_, _) => {
                         unreachable!("function call in dot chain should not be namespace-qualified")
                     }
-                    // {xxx:map}.id = ???
-                    Expr::Property(x) if target_val.is::<Map>() && new_val.is_some() => {
+                    // {xxx:map}.id = ??? - unless there is a registered property setter
+                    Expr::Property(x)
+                        if target_val.is::<Map>()
+                            && new_val.is_some()
+                            && !self.has_native_override(
+                                mods,
+                                &x.1,
+                                once(TypeId::of::<Map>())
+                                    .chain(once(new_val.as_ref().unwrap().0 .0.type_id())),
+                            ) =>
+                    {
                         let Ident { name, pos } = &x.2;
                         let index = name.clone().into();
                         let mut val = self.get_indexed_mut(
                             mods, state, lib, target_val, index, *pos, true, is_ref, false, level,
                         )?;
 
-                        let (new_val, new_val_pos) = new_val.unwrap();
-                        val.set_value(new_val, new_val_pos)?;
+                        self.eval_op_assignment(
+                            mods,
+                            state,
+                            lib,
+                            &mut val,
+                            new_val.unwrap(),
+                            level,
+                        )?;
 
                         Ok((Default::default(), true))
                     }
                     // {xxx:map}.id
-                    Expr::Property(x) if target_val.is::<Map>() => {
-                        let Ident { name, pos } = &x.2;
-                        let index = name.clone().into();
-                        let val = self.get_indexed_mut(
-                            mods, state, lib, target_val, index, *pos, false, is_ref, false, level,
-                        )?;
-
-                        Ok((val.take_or_clone(), false))
+                    Expr::Property(x) if target_val.is::<Map>() && new_val.is_none() => {
+                        let (getter, _, Ident { name, pos }) = x.as_ref();
+                        self.read_map_property(
+                            mods, state, lib, target_val, getter, name, is_ref, *pos, level,
+                        )
+                        .map(|v| (v, false))
                     }
                     // xxx.id = ???
                     Expr::Property(x) if new_val.is_some() => {
-                        let (_, setter, Ident { pos, .. }) = x.as_ref();
-                        let mut new_val = new_val;
-                        let mut args = [target_val, &mut new_val.as_mut().unwrap().0];
+                        let (getter, setter, Ident { pos, .. }) = x.as_ref();
+                        let ((mut new_val, new_val_pos), op_info) = new_val.unwrap();
+
+                        // xxx.id op= ??? - get the current value via the getter
+                        if !op_info.0.is_empty() {
+                            #[cfg(not(feature = "no_object"))]
+                            let val = if target_val.is::<Map>() {
+                                let name = &x.2.name;
+                                Some(self.read_map_property(
+                                    mods, state, lib, target_val, getter, name, is_ref, *pos, level,
+                                )?)
+                            } else {
+                                None
+                            };
+                            #[cfg(feature = "no_object")]
+                            let val = None;
+
+                            let val = match val {
+                                Some(val) => val,
+                                None => {
+                                    let mut args = [&mut *target_val];
+                                    self.exec_fn_call(
+                                        mods, state, lib, getter, None, &mut args, is_ref, true,
+                                        false, *pos, None, None, level,
+                                    )
+                                    .map_err(|err| err.fill_position(*pos))?
+                                    .0
+                                }
+                            };
+                            let mut val = val.into();
+                            self.eval_op_assignment(
+                                mods,
+                                state,
+                                lib,
+                                &mut val,
+                                ((new_val, new_val_pos), op_info),
+                                level,
+                            )?;
+                            new_val = val.take_or_clone();
+                        }
+
+                        let mut args = [target_val, &mut new_val];
                         self.exec_fn_call(
                             mods, state, lib, setter, None, &mut args, is_ref, true, false, *pos,
                             None, None, level,
@@ -1181,15 +1765,32 @@ impl Engine {
                         .map_err(|err| err.fill_position(*pos))
                     }
                     // {xxx:map}.sub_lhs[expr] | {xxx:map}.sub_lhs.expr
-                    Expr::Index(x, x_pos) | Expr::Dot(x, x_pos) if target_val.is::<Map>() => {
+                    Expr::Index(x, _, x_pos) | Expr::Dot(x, _, x_pos) if target_val.is::<Map>() => {
+                        let mut setter = None;
+                        let mut getter_val = Dynamic::UNIT;
+
                         let mut val = match &x.lhs {
                             Expr::Property(p) => {
-                                let Ident { name, pos } = &p.2;
-                                let index = name.clone().into();
-                                self.get_indexed_mut(
-                                    mods, state, lib, target_val, index, *pos, false, is_ref, true,
-                                    level,
-                                )?
+                                let (getter, prop_setter, Ident { name, pos }) = p.as_ref();
+
+                                // A registered property getter takes precedence
+                                match self.call_map_getter(
+                                    mods, state, lib, target_val, getter, is_ref, *pos, level,
+                                )? {
+                                    // Keep the value to feed it back via the setter
+                                    Some(val) => {
+                                        setter = Some((prop_setter, *pos));
+                                        getter_val = val;
+                                        (&mut getter_val).into()
+                                    }
+                                    None => {
+                                        let index = name.clone().into();
+                                        self.get_indexed_mut(
+                                            mods, state, lib, target_val, index, *pos, false,
+                                            is_ref, true, level,
+                                        )?
+                                    }
+                                }
                             }
                             // {xxx:map}.fn_name(arg_expr_list)[expr] | {xxx:map}.fn_name(arg_expr_list).expr
                             Expr::FnCall(x, pos) if x.namespace.is_none() => {
@@ -1215,14 +1816,50 @@ impl Engine {
                             expr => unreachable!("invalid dot expression: {:?}", expr),
                         };
 
-                        self.eval_dot_index_chain_helper(
-                            mods, state, lib, this_ptr, &mut val, &x.rhs, idx_values, next_chain,
-                            level, new_val,
-                        )
-                        .map_err(|err| err.fill_position(*x_pos))
+                        // {xxx:map}?.sub_lhs is () - skip the rest of the chain
+                        if optional && val.as_ref().is::<()>() {
+                            return Ok((Dynamic::UNIT, false));
+                        }
+
+                        let (result, may_be_changed) = self
+                            .eval_dot_index_chain_helper(
+                                mods,
+                                state,
+                                lib,
+                                this_ptr,
+                                &mut val,
+                                &x.rhs,
+                                idx_values,
+                                next_chain,
+                                next_optional,
+                                level,
+                                new_val,
+                            )
+                            .map_err(|err| err.fill_position(*x_pos))?;
+
+                        // Feed a value from a registered property getter back via the setter
+                        match setter {
+                            Some((setter, pos)) if may_be_changed => {
+                                drop(val);
+                                let mut val = getter_val;
+                                let args = &mut [target.as_mut(), &mut val];
+                                self.exec_fn_call(
+                                    mods, state, lib, setter, None, args, is_ref, true, false, pos,
+                                    None, None, level,
+                                )
+                                .or_else(|err| match *err {
+                                    // If there is no setter, the property is read-only
+                                    EvalAltResult::ErrorDotExpr(_, _) => Ok((Dynamic::UNIT, false)),
+                                    _ => Err(err.fill_position(*x_pos)),
+                                })?;
+                            }
+                            _ => (),
+                        }
+
+                        Ok((result, may_be_changed))
                     }
                     // xxx.sub_lhs[expr] | xxx.sub_lhs.expr
-                    Expr::Index(x, x_pos) | Expr::Dot(x, x_pos) => {
+                    Expr::Index(x, _, x_pos) | Expr::Dot(x, _, x_pos) => {
                         match &x.lhs {
                             // xxx.prop[expr] | xxx.prop.expr
                             Expr::Property(p) => {
@@ -1236,6 +1873,11 @@ impl Engine {
                                     )
                                     .map_err(|err| err.fill_position(*pos))?;
 
+                                // xxx?.prop is () - skip the rest of the chain
+                                if optional && val.is::<()>() {
+                                    return Ok((Dynamic::UNIT, false));
+                                }
+
                                 let val = &mut val;
 
                                 let (result, may_be_changed) = self
@@ -1248,6 +1890,7 @@ impl Engine {
                                         &x.rhs,
                                         idx_values,
                                         next_chain,
+                                        next_optional,
                                         level,
                                         new_val,
                                     )
@@ -1284,16 +1927,55 @@ impl Engine {
                                 } = f.as_ref();
                                 let def_value = def_value.as_ref();
                                 let args = idx_val.as_fn_call_args();
-                                let (mut val, _) = self.make_method_call(
+                                let (mut val, updated) = self.make_method_call(
                                     mods, state, lib, name, *hash, target, args, def_value, false,
                                     *pos, level,
                                 )?;
+
+                                // xxx?.fn_name(...) is () - skip the rest of the chain
+                                if optional && val.is::<()>() {
+                                    return Ok((Dynamic::UNIT, false));
+                                }
+
+                                // xxx.fn_name(...) returning () - continue with the modified receiver
+                                if val.is::<()>()
+                                    && updated
+                                    && !next_optional
+                                    && is_fluent_receiver(target)
+                                {
+                                    return self
+                                        .eval_dot_index_chain_helper(
+                                            mods,
+                                            state,
+                                            lib,
+                                            this_ptr,
+                                            target,
+                                            &x.rhs,
+                                            idx_values,
+                                            next_chain,
+                                            next_optional,
+                                            level,
+                                            new_val,
+                                        )
+                                        .map(|(v, changed)| (v, changed || updated))
+                                        .map_err(|err| err.fill_position(*pos));
+                                }
+
                                 let val = &mut val;
                                 let target = &mut val.into();
 
                                 self.eval_dot_index_chain_helper(
-                                    mods, state, lib, this_ptr, target, &x.rhs, idx_values,
-                                    next_chain, level, new_val,
+                                    mods,
+                                    state,
+                                    lib,
+                                    this_ptr,
+                                    target,
+                                    &x.rhs,
+                                    idx_values,
+                                    next_chain,
+                                    next_optional,
+                                    level,
+                                    new_val,
                                 )
                                 .map_err(|err| err.fill_position(*pos))
                             }
@@ -1314,35 +1996,206 @@ impl Engine {
         }
     }
 
-    /// Evaluate a dot/index chain.
-    #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
-    fn eval_dot_index_chain(
+    /// Chain-evaluate a dot/index chain where the index at this level has multiple indices,
+    /// which can only be passed to indexer functions.
+    /// [`Position`] in [`EvalAltResult`] is [`None`][Position::None] and must be set afterwards.
+    #[cfg(not(feature = "no_index"))]
+    fn eval_multi_index(
         &self,
-        scope: &mut Scope,
         mods: &mut Imports,
         state: &mut State,
         lib: &[&Module],
         this_ptr: &mut Option<&mut Dynamic>,
-        expr: &Expr,
+        target_val: &mut Dynamic,
+        rhs: &Expr,
+        idx_vals: StaticVec<Dynamic>,
+        idx_values: &mut StaticVec<IndexChainValue>,
+        next_chain: ChainType,
+        optional: bool,
+        next_optional: bool,
+        is_ref: bool,
         level: usize,
-        new_val: Option<(Dynamic, Position)>,
-    ) -> Result<Dynamic, Box<EvalAltResult>> {
-        let (crate::ast::BinaryExpr { lhs, rhs }, chain_type, op_pos) = match expr {
-            Expr::Index(x, pos) => (x.as_ref(), ChainType::Index, *pos),
-            Expr::Dot(x, pos) => (x.as_ref(), ChainType::Dot, *pos),
-            _ => unreachable!("index or dot chain expected, but gets {:?}", expr),
-        };
-
-        let idx_values = &mut Default::default();
+        new_val: Option<NewValue>,
+    ) -> Result<(Dynamic, bool), Box<EvalAltResult>> {
+        let pos = rhs.position();
+
+        match rhs {
+            // xxx[idx1, idx2, ...].expr... | xxx[idx1, idx2, ...][expr]...
+            Expr::Dot(x, _, x_pos) | Expr::Index(x, _, x_pos) => {
+                let idx_pos = x.lhs.position();
+                let val = self.call_indexer(
+                    mods, state, lib, target_val, idx_vals, None, idx_pos, is_ref, level,
+                )?;
 
-        self.eval_indexed_chain(
-            scope, mods, state, lib, this_ptr, rhs, chain_type, idx_values, 0, level,
-        )?;
+                // xxx?[idx1, idx2, ...] is () - skip the rest of the chain
+                if optional && val.is::<()>() {
+                    return Ok((Dynamic::UNIT, false));
+                }
 
-        match lhs {
-            // id.??? or id[???]
-            Expr::Variable(x) => {
-                let Ident {
+                self.eval_dot_index_chain_helper(
+                    mods,
+                    state,
+                    lib,
+                    this_ptr,
+                    &mut val.into(),
+                    &x.rhs,
+                    idx_values,
+                    next_chain,
+                    next_optional,
+                    level,
+                    new_val,
+                )
+                .map_err(|err| err.fill_position(*x_pos))
+            }
+            // xxx[idx1, idx2, ...] op= new_val
+            _ if new_val.is_some() => {
+                let ((mut new_val, new_val_pos), op_info) = new_val.unwrap();
+
+                // xxx[idx1, idx2, ...] op= new_val - get the current value via the index getter
+                if !op_info.0.is_empty() {
+                    let mut val = self
+                        .call_indexer(
+                            mods,
+                            state,
+                            lib,
+                            target_val,
+                            idx_vals.clone(),
+                            None,
+                            pos,
+                            is_ref,
+                            level,
+                        )?
+                        .into();
+                    self.eval_op_assignment(
+                        mods,
+                        state,
+                        lib,
+                        &mut val,
+                        ((new_val, new_val_pos), op_info),
+                        level,
+                    )?;
+                    new_val = val.take_or_clone();
+                }
+
+                self.call_indexer(
+                    mods,
+                    state,
+                    lib,
+                    target_val,
+                    idx_vals,
+                    Some(new_val),
+                    new_val_pos,
+                    is_ref,
+                    level,
+                )?;
+
+                Ok((Dynamic::UNIT, true))
+            }
+            // xxx[idx1, idx2, ...]
+            _ => self
+                .call_indexer(
+                    mods, state, lib, target_val, idx_vals, None, pos, is_ref, level,
+                )
+                .map(|v| (v, false)),
+        }
+    }
+
+    /// Call the index getter (or setter, if `new_val` is provided) of a type with multiple indices.
+    /// [`Position`] in [`EvalAltResult`] may be None and should be set afterwards.
+    #[cfg(not(feature = "no_index"))]
+    fn call_indexer(
+        &self,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        target: &mut Dynamic,
+        mut idx_vals: StaticVec<Dynamic>,
+        mut new_val: Option<Dynamic>,
+        pos: Position,
+        is_ref: bool,
+        level: usize,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        self.inc_operations(state, Position::NONE)?;
+
+        let type_name = target.type_name();
+
+        // Built-in indexing of arrays, object maps and strings takes only one index
+        let multi_index_err = || {
+            EvalAltResult::ErrorMismatchDataType(
+                "".into(),
+                format!(
+                    "{} cannot be indexed with {} indices",
+                    self.map_type_name(type_name),
+                    idx_vals.len()
+                ),
+                Position::NONE,
+            )
+            .into()
+        };
+
+        match target {
+            #[cfg(not(feature = "no_object"))]
+            Dynamic(Union::Map(_, _)) => return multi_index_err(),
+            Dynamic(Union::Array(_, _)) | Dynamic(Union::Str(_, _)) => return multi_index_err(),
+            _ => (),
+        }
+
+        let fn_name = if new_val.is_some() {
+            FN_IDX_SET
+        } else {
+            FN_IDX_GET
+        };
+        let mut args: StaticVec<_> = crate::stdlib::iter::once(target)
+            .chain(idx_vals.iter_mut())
+            .chain(new_val.iter_mut())
+            .collect();
+
+        self.exec_fn_call(
+            mods, state, lib, fn_name, None, &mut args, is_ref, true, false, pos, None, None, level,
+        )
+        .map(|(v, _)| v)
+        .map_err(|err| match *err {
+            EvalAltResult::ErrorFunctionNotFound(fn_sig, _)
+                if fn_sig.ends_with(']') || fn_sig.ends_with("]=") =>
+            {
+                Box::new(EvalAltResult::ErrorIndexingType(
+                    self.map_type_name(type_name).into(),
+                    Position::NONE,
+                ))
+            }
+            _ => err,
+        })
+    }
+
+    /// Evaluate a dot/index chain.
+    #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+    fn eval_dot_index_chain(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        expr: &Expr,
+        level: usize,
+        new_val: Option<NewValue>,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let (crate::ast::BinaryExpr { lhs, rhs }, chain_type, optional, op_pos) = match expr {
+            Expr::Index(x, optional, pos) => (x.as_ref(), ChainType::Index, *optional, *pos),
+            Expr::Dot(x, optional, pos) => (x.as_ref(), ChainType::Dot, *optional, *pos),
+            _ => unreachable!("index or dot chain expected, but gets {:?}", expr),
+        };
+
+        let idx_values = &mut Default::default();
+
+        self.eval_indexed_chain(
+            scope, mods, state, lib, this_ptr, rhs, chain_type, idx_values, 0, level,
+        )?;
+
+        match lhs {
+            // id.??? or id[???]
+            Expr::Variable(x) => {
+                let Ident {
                     name: var_name,
                     pos: var_pos,
                 } = &x.2;
@@ -1360,8 +2213,8 @@ impl Engine {
 
                 let obj_ptr = &mut target.into();
                 self.eval_dot_index_chain_helper(
-                    mods, state, lib, &mut None, obj_ptr, rhs, idx_values, chain_type, level,
-                    new_val,
+                    mods, state, lib, &mut None, obj_ptr, rhs, idx_values, chain_type, optional,
+                    level, new_val,
                 )
                 .map(|(v, _)| v)
                 .map_err(|err| err.fill_position(op_pos))
@@ -1373,8 +2226,8 @@ impl Engine {
                 let val = self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?;
                 let obj_ptr = &mut val.into();
                 self.eval_dot_index_chain_helper(
-                    mods, state, lib, this_ptr, obj_ptr, rhs, idx_values, chain_type, level,
-                    new_val,
+                    mods, state, lib, this_ptr, obj_ptr, rhs, idx_values, chain_type, optional,
+                    level, new_val,
                 )
                 .map(|(v, _)| v)
                 .map_err(|err| err.fill_position(op_pos))
@@ -1421,7 +2274,7 @@ impl Engine {
             }
             Expr::Property(_) => unreachable!("unexpected Expr::Property for indexing"),
 
-            Expr::Index(x, _) | Expr::Dot(x, _) => {
+            Expr::Index(x, _, _) | Expr::Dot(x, _, _) => {
                 let crate::ast::BinaryExpr { lhs, rhs, .. } = x.as_ref();
 
                 // Evaluate in left-to-right order
@@ -1444,6 +2297,13 @@ impl Engine {
                     Expr::FnCall(_, _) if parent_chain_type == ChainType::Dot => {
                         unreachable!("function call in dot chain should not be namespace-qualified")
                     }
+                    Expr::IndexArgs(x, _) => x
+                        .iter()
+                        .map(|idx_expr| {
+                            self.eval_expr(scope, mods, state, lib, this_ptr, idx_expr, level)
+                        })
+                        .collect::<Result<StaticVec<Dynamic>, _>>()?
+                        .into(),
                     _ => self
                         .eval_expr(scope, mods, state, lib, this_ptr, lhs, level)?
                         .into(),
@@ -1451,8 +2311,8 @@ impl Engine {
 
                 // Push in reverse order
                 let chain_type = match expr {
-                    Expr::Index(_, _) => ChainType::Index,
-                    Expr::Dot(_, _) => ChainType::Dot,
+                    Expr::Index(_, _, _) => ChainType::Index,
+                    Expr::Dot(_, _, _) => ChainType::Dot,
                     _ => unreachable!("index or dot chain expected, but gets {:?}", expr),
                 };
                 self.eval_indexed_chain(
@@ -1462,6 +2322,17 @@ impl Engine {
                 idx_values.push(lhs_val);
             }
 
+            Expr::IndexArgs(x, _) => {
+                let idx_vals = x
+                    .iter()
+                    .map(|idx_expr| {
+                        self.eval_expr(scope, mods, state, lib, this_ptr, idx_expr, level)
+                    })
+                    .collect::<Result<StaticVec<_>, _>>()?;
+
+                idx_values.push(idx_vals.into());
+            }
+
             _ => idx_values.push(
                 self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
                     .into(),
@@ -1471,6 +2342,136 @@ impl Engine {
         Ok(())
     }
 
+    /// Call a property getter registered for object maps, if any.
+    ///
+    /// Returns [`None`] if there is no such getter or if it returns `()`, in which case the
+    /// property should be read as a key of the object map instead.
+    #[cfg(not(feature = "no_object"))]
+    fn call_map_getter(
+        &self,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        target: &mut Dynamic,
+        getter: &str,
+        is_ref: bool,
+        pos: Position,
+        level: usize,
+    ) -> Result<Option<Dynamic>, Box<EvalAltResult>> {
+        if !self.has_native_override(mods, getter, once(TypeId::of::<Map>())) {
+            return Ok(None);
+        }
+
+        let (val, _) = self
+            .exec_fn_call(
+                mods,
+                state,
+                lib,
+                getter,
+                None,
+                &mut [target],
+                is_ref,
+                true,
+                false,
+                pos,
+                None,
+                None,
+                level,
+            )
+            .map_err(|err| err.fill_position(pos))?;
+
+        Ok(if val.is::<()>() { None } else { Some(val) })
+    }
+
+    /// Read a property of an object map.
+    ///
+    /// A property getter registered for object maps takes precedence. The property is read as
+    /// a key of the object map if there is no such getter, or if it returns `()`.
+    #[cfg(not(feature = "no_object"))]
+    fn read_map_property(
+        &self,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        target: &mut Dynamic,
+        getter: &str,
+        name: &ImmutableString,
+        is_ref: bool,
+        pos: Position,
+        level: usize,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if let Some(val) =
+            self.call_map_getter(mods, state, lib, target, getter, is_ref, pos, level)?
+        {
+            return Ok(val);
+        }
+
+        let index = name.clone().into();
+        self.get_indexed_mut(
+            mods, state, lib, target, index, pos, false, is_ref, false, level,
+        )
+        .map(Target::take_or_clone)
+    }
+
+    /// Call an index getter registered for arrays or object maps, if any.
+    ///
+    /// Returns [`None`] if there is no such indexer or if it returns `()`, in which case the
+    /// built-in indexing should be used instead.
+    #[cfg(not(feature = "no_index"))]
+    fn call_builtin_indexer_override(
+        &self,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        target: &mut Dynamic,
+        idx: &Dynamic,
+        is_ref: bool,
+        idx_pos: Position,
+        level: usize,
+    ) -> Result<Option<Dynamic>, Box<EvalAltResult>> {
+        if !Self::is_indexer_overridable(target)
+            || !self.has_native_override(
+                mods,
+                FN_IDX_GET,
+                once(target.type_id()).chain(once(idx.type_id())),
+            )
+        {
+            return Ok(None);
+        }
+
+        let mut idx = idx.clone();
+
+        let (val, _) = self.exec_fn_call(
+            mods,
+            state,
+            lib,
+            FN_IDX_GET,
+            None,
+            &mut [target, &mut idx],
+            is_ref,
+            true,
+            false,
+            idx_pos,
+            None,
+            None,
+            level,
+        )?;
+
+        Ok(if val.is::<()>() { None } else { Some(val) })
+    }
+
+    /// Can indexers registered for this type override its built-in indexing?
+    #[cfg(not(feature = "no_index"))]
+    #[inline(always)]
+    fn is_indexer_overridable(target: &Dynamic) -> bool {
+        match target.0 {
+            Union::Array(_, _) | Union::Int(_, _) => true,
+            #[cfg(not(feature = "no_object"))]
+            Union::Map(_, _) => true,
+            _ => false,
+        }
+    }
+
     /// Get the value at the indexed position of a base type.
     /// [`Position`] in [`EvalAltResult`] may be None and should be set afterwards.
     #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
@@ -1489,6 +2490,16 @@ impl Engine {
     ) -> Result<Target<'t>, Box<EvalAltResult>> {
         self.inc_operations(state, Position::NONE)?;
 
+        // An index getter registered for arrays or object maps takes precedence
+        #[cfg(not(feature = "no_index"))]
+        if _indexers && !_create {
+            if let Some(val) = self.call_builtin_indexer_override(
+                _mods, state, _lib, target, &idx, _is_ref, idx_pos, _level,
+            )? {
+                return Ok(val.into());
+            }
+        }
+
         match target {
             #[cfg(not(feature = "no_index"))]
             Dynamic(Union::Array(arr, _)) => {
@@ -1518,7 +2529,7 @@ impl Engine {
                         self.make_type_mismatch_err::<ImmutableString>(err, idx_pos)
                     })?;
 
-                    map.entry(index).or_insert_with(Default::default).into()
+                    map.get_or_insert_with(index, Default::default).into()
                 } else {
                     let index = idx.read_lock::<ImmutableString>().ok_or_else(|| {
                         self.make_type_mismatch_err::<ImmutableString>("", idx_pos)
@@ -1549,6 +2560,18 @@ impl Engine {
                 }
             }
 
+            #[cfg(not(feature = "no_index"))]
+            Dynamic(Union::Int(value, _)) => {
+                // val_int[idx]
+                let index = idx
+                    .as_int()
+                    .map_err(|err| self.make_type_mismatch_err::<crate::INT>(err, idx_pos))?;
+
+                let offset = calc_bit_offset(index).map_err(|err| err.fill_position(idx_pos))?;
+                let bit = (*value & (1 << offset)) != 0;
+                Ok(Target::BitField(target, offset, bit.into()))
+            }
+
             #[cfg(not(feature = "no_index"))]
             _ if _indexers => {
                 let type_name = target.type_name();
@@ -1593,17 +2616,21 @@ impl Engine {
         self.inc_operations(state, rhs.position())?;
 
         let lhs_value = self.eval_expr(scope, mods, state, lib, this_ptr, lhs, level)?;
-        let rhs_value = self.eval_expr(scope, mods, state, lib, this_ptr, rhs, level)?;
 
-        match rhs_value {
+        // Look into the container in place, without cloning it (e.g. an entire object map)
+        let (rhs_target, _) =
+            self.eval_expr_as_target(scope, mods, state, lib, this_ptr, rhs, false, level)?;
+        let rhs_value = rhs_target.as_ref().read_lock::<Dynamic>().unwrap();
+
+        match &*rhs_value {
             #[cfg(not(feature = "no_index"))]
-            Dynamic(Union::Array(mut rhs_value, _)) => {
+            Dynamic(Union::Array(rhs_value, _)) => {
                 // Call the `==` operator to compare each value
                 let def_value = Some(false.into());
                 let def_value = def_value.as_ref();
 
-                for value in rhs_value.iter_mut() {
-                    let args = &mut [&mut lhs_value.clone(), value];
+                for value in rhs_value.iter() {
+                    let args = &mut [&mut lhs_value.clone(), &mut value.clone()];
 
                     // Qualifiers (none) + function name + number of arguments + argument `TypeId`'s.
                     let hash_fn =
@@ -1630,8 +2657,10 @@ impl Engine {
             #[cfg(not(feature = "no_object"))]
             Dynamic(Union::Map(rhs_value, _)) => match lhs_value {
                 // Only allows string or char
-                Dynamic(Union::Str(s, _)) => Ok(rhs_value.contains_key(&s).into()),
-                Dynamic(Union::Char(c, _)) => Ok(rhs_value.contains_key(&c.to_string()).into()),
+                Dynamic(Union::Str(s, _)) => Ok(rhs_value.contains_key(s.as_str()).into()),
+                Dynamic(Union::Char(c, _)) => Ok(rhs_value
+                    .contains_key(c.encode_utf8(&mut [0; 4]) as &str)
+                    .into()),
                 _ => EvalAltResult::ErrorInExpr(lhs.position()).into(),
             },
             Dynamic(Union::Str(rhs_value, _)) => match lhs_value {
@@ -1640,6 +2669,14 @@ impl Engine {
                 Dynamic(Union::Char(c, _)) => Ok(rhs_value.contains(c).into()),
                 _ => EvalAltResult::ErrorInExpr(lhs.position()).into(),
             },
+            rhs_value if rhs_value.is::<CharRange>() => match lhs_value {
+                // Only allows char
+                Dynamic(Union::Char(c, _)) => Ok(self
+                    .lock_value_as::<CharRange>(rhs_value, "in", rhs.position())?
+                    .contains(&c)
+                    .into()),
+                _ => EvalAltResult::ErrorInExpr(lhs.position()).into(),
+            },
             _ => EvalAltResult::ErrorInExpr(rhs.position()).into(),
         }
     }
@@ -1671,10 +2708,13 @@ impl Engine {
             }
             // var[...]
             #[cfg(not(feature = "no_index"))]
-            Expr::Index(x, _) if x.lhs.get_variable_access(false).is_some() => match x.rhs {
+            Expr::Index(x, _, _) if x.lhs.get_variable_access(false).is_some() => match x.rhs {
                 Expr::Property(_) => unreachable!("unexpected Expr::Property in indexing"),
-                // var[...]...
-                Expr::FnCall(_, _) | Expr::Index(_, _) | Expr::Dot(_, _) => self
+                // var[...]... | var[idx1, idx2, ...]
+                Expr::FnCall(_, _)
+                | Expr::Index(_, _, _)
+                | Expr::Dot(_, _, _)
+                | Expr::IndexArgs(_, _) => self
                     .eval_expr(scope, mods, state, lib, this_ptr, expr, level)
                     .map(|v| (v.into(), expr.position())),
                 // var[expr] - point directly to the item
@@ -1704,7 +2744,7 @@ impl Engine {
             },
             // var.prop
             #[cfg(not(feature = "no_object"))]
-            Expr::Dot(x, _) if x.lhs.get_variable_access(false).is_some() => match x.rhs {
+            Expr::Dot(x, _, _) if x.lhs.get_variable_access(false).is_some() => match x.rhs {
                 Expr::Variable(_) => unreachable!(
                     "unexpected Expr::Variable in dot access (should be Expr::Property)"
                 ),
@@ -1782,9 +2822,23 @@ impl Engine {
                 .as_deref()
                 .cloned()
                 .ok_or_else(|| EvalAltResult::ErrorUnboundThis((x.2).pos).into()),
-            Expr::Variable(_) => self
-                .search_namespace(scope, mods, state, lib, this_ptr, expr)
-                .map(|(val, _)| val.take_or_clone()),
+            Expr::Variable(x) => {
+                let (target, pos) =
+                    self.search_namespace(scope, mods, state, lib, this_ptr, expr)?;
+
+                // Report a large array or object map that is about to be copied
+                if let Some((threshold, callback)) = &self.large_copy {
+                    if target.is_ref() {
+                        let (arrays, maps, _) = calc_data_sizes(target.as_ref());
+
+                        if arrays + maps > *threshold {
+                            callback(&(x.2).name, arrays + maps, pos);
+                        }
+                    }
+                }
+
+                Ok(target.take_or_clone())
+            }
 
             // Statement block
             Expr::Stmt(x, _) => {
@@ -1793,13 +2847,13 @@ impl Engine {
 
             // lhs[idx_expr]
             #[cfg(not(feature = "no_index"))]
-            Expr::Index(_, _) => {
+            Expr::Index(_, _, _) => {
                 self.eval_dot_index_chain(scope, mods, state, lib, this_ptr, expr, level, None)
             }
 
             // lhs.dot_rhs
             #[cfg(not(feature = "no_object"))]
-            Expr::Dot(_, _) => {
+            Expr::Dot(_, _, _) => {
                 self.eval_dot_index_chain(scope, mods, state, lib, this_ptr, expr, level, None)
             }
 
@@ -1808,22 +2862,55 @@ impl Engine {
                 let mut arr =
                     Array::with_capacity(crate::stdlib::cmp::max(TYPICAL_ARRAY_SIZE, x.len()));
                 for item in x.as_ref() {
-                    arr.push(self.eval_expr(scope, mods, state, lib, this_ptr, item, level)?);
+                    arr.push(
+                        self.eval_expr(scope, mods, state, lib, this_ptr, item, level)?
+                            .flatten(),
+                    );
                 }
-                Ok(Dynamic(Union::Array(Box::new(arr), AccessMode::ReadWrite)))
+                let arr = Dynamic(Union::Array(Box::new(arr), AccessMode::ReadWrite));
+
+                #[cfg(not(feature = "unchecked"))]
+                self.inc_data_size(state, calc_data_footprint(&arr, false), expr.position())?;
+
+                Ok(arr)
             }
 
             #[cfg(not(feature = "no_object"))]
             Expr::Map(x, _) => {
-                let mut map =
-                    Map::with_capacity(crate::stdlib::cmp::max(TYPICAL_MAP_SIZE, x.len()));
-                for (Ident { name: key, .. }, expr) in x.as_ref() {
+                let mut map = Map::with_capacity(x.len());
+                for (key, expr) in x.as_ref() {
+                    let key = match key {
+                        MapKey::Name(Ident { name, .. }) => name.clone(),
+                        // #{ [expr]: value } - the key must be a string or a character
+                        MapKey::Computed(key_expr) => {
+                            let key = self
+                                .eval_expr(scope, mods, state, lib, this_ptr, key_expr, level)?
+                                .flatten();
+
+                            match key.0 {
+                                Union::Str(s, _) => s,
+                                Union::Char(c, _) => c.to_string().into(),
+                                _ => {
+                                    return Err(self.make_type_mismatch_err::<ImmutableString>(
+                                        key.type_name(),
+                                        key_expr.position(),
+                                    ))
+                                }
+                            }
+                        }
+                    };
                     map.insert(
-                        key.clone(),
-                        self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?,
+                        key,
+                        self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
+                            .flatten(),
                     );
                 }
-                Ok(Dynamic(Union::Map(Box::new(map), AccessMode::ReadWrite)))
+                let map = Dynamic(Union::Map(Box::new(map), AccessMode::ReadWrite));
+
+                #[cfg(not(feature = "unchecked"))]
+                self.inc_data_size(state, calc_data_footprint(&map, false), expr.position())?;
+
+                Ok(map)
             }
 
             // Normal function call
@@ -1892,6 +2979,17 @@ impl Engine {
                 .into())
             }
 
+            Expr::Coalesce(x, _) => {
+                let value = self.eval_expr(scope, mods, state, lib, this_ptr, &x.lhs, level)?;
+
+                // Short-circuit unless the value is ()
+                if value.is::<()>() {
+                    self.eval_expr(scope, mods, state, lib, this_ptr, &x.rhs, level)
+                } else {
+                    Ok(value)
+                }
+            }
+
             Expr::BoolConstant(x, _) => Ok((*x).into()),
             Expr::Unit(_) => Ok(Dynamic::UNIT),
 
@@ -1956,6 +3054,142 @@ impl Engine {
         result
     }
 
+    /// Evaluate an op-assignment (or a normal assignment if the operator is empty) on a [`Target`].
+    pub(crate) fn eval_op_assignment(
+        &self,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        target: &mut Target,
+        new_val: NewValue,
+        level: usize,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let ((mut rhs_val, rhs_pos), (op, op_pos)) = new_val;
+
+        if op.is_empty() {
+            // Normal assignment
+            return target.set_value(rhs_val, rhs_pos);
+        }
+
+        // Measure the target before it is modified in place
+        #[cfg(not(feature = "unchecked"))]
+        let data_size = if self.max_total_data_size() > 0 {
+            Some((
+                calc_data_footprint(target.as_ref(), false),
+                calc_data_footprint(&rhs_val, true),
+            ))
+        } else {
+            None
+        };
+
+        // Op-assignment - in order of precedence:
+        // 1) Native registered overriding function
+        // 2) Built-in implementation
+        // 3) Map to `var = var op rhs`
+
+        // Qualifiers (none) + function name + number of arguments + argument `TypeId`'s.
+        let arg_types = once(target.as_mut().type_id()).chain(once(rhs_val.type_id()));
+        let hash_fn = calc_native_fn_hash(empty(), op, arg_types).unwrap();
+
+        let _in_place = match self
+            .global_namespace
+            .get_fn(hash_fn, false)
+            .or_else(|| {
+                self.global_modules
+                    .iter()
+                    .find_map(|m| m.get_fn(hash_fn, false))
+            })
+            .or_else(|| mods.get_fn(hash_fn))
+        {
+            // op= function registered as method
+            Some(func) if func.is_method() => {
+                let mut lock_guard;
+                let target_inner;
+
+                if cfg!(not(feature = "no_closure")) && target.is_shared() {
+                    lock_guard = target.as_mut().write_lock::<Dynamic>().unwrap();
+                    target_inner = lock_guard.deref_mut();
+                } else {
+                    target_inner = target.as_mut();
+                }
+
+                let args = &mut [target_inner, &mut rhs_val];
+
+                if let Some(ref mut audit) = state.audit {
+                    audit.record_native(op, args.len());
+                }
+
+                // Overriding exact implementation
+                if func.is_plugin_fn() {
+                    func.get_plugin_fn()
+                        .call((self, &state.source, &*mods, lib, op_pos).into(), args)
+                } else {
+                    func.get_native_fn()((self, &state.source, &*mods, lib, op_pos).into(), args)
+                }
+                .map_err(|err| err.fill_position(op_pos))?;
+
+                true
+            }
+            // Built-in op-assignment function
+            _ if run_builtin_op_assignment(op, target.as_mut(), &rhs_val)
+                .map_err(|err| err.fill_position(op_pos))?
+                .is_some() =>
+            {
+                if let Some(ref mut audit) = state.audit {
+                    audit.record_native(op, 2);
+                }
+
+                true
+            }
+            // Not built-in: expand to `var = var op rhs`
+            _ => {
+                let op = &op[..op.len() - 1]; // extract operator without =
+
+                // Clone the LHS value
+                let args = &mut [&mut target.as_mut().clone(), &mut rhs_val];
+
+                // Run function
+                let (value, _) = self.exec_fn_call(
+                    mods, state, lib, op, None, args, false, false, false, op_pos, None, None,
+                    level,
+                )?;
+
+                let value = value.flatten();
+
+                if cfg!(not(feature = "no_closure")) && target.is_shared() {
+                    *target.as_mut().write_lock::<Dynamic>().unwrap() = value;
+                } else {
+                    *target.as_mut() = value;
+                }
+
+                // The new value is already accounted for by the function call
+                false
+            }
+        };
+
+        // Account for the growth of the target modified in place
+        #[cfg(not(feature = "unchecked"))]
+        if let (true, Some((size, rhs_size))) = (_in_place, data_size) {
+            let growth = calc_data_footprint(target.as_ref(), false).saturating_sub(size);
+
+            if growth > 0 {
+                self.inc_data_size(state, crate::stdlib::cmp::max(growth, rhs_size), op_pos)?;
+            }
+        }
+
+        // Write the new character back into the string, or the new bit back into the integer
+        #[cfg(not(feature = "no_index"))]
+        match target {
+            Target::StringChar(_, _, value) | Target::BitField(_, _, value) => {
+                let value = value.clone();
+                target.set_value(value, rhs_pos)?;
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
     /// Evaluate a statement.
     ///
     /// # Safety
@@ -1974,6 +3208,10 @@ impl Engine {
     ) -> Result<Dynamic, Box<EvalAltResult>> {
         self.inc_operations(state, stmt.position())?;
 
+        if let Some(ref debugger) = self.debugger {
+            self.run_debugger(debugger, scope, mods, state, lib, this_ptr, stmt, level)?;
+        }
+
         let result = match stmt {
             // No-op
             Stmt::Noop(_) => Ok(Dynamic::UNIT),
@@ -1984,21 +3222,65 @@ impl Engine {
             // var op= rhs
             Stmt::Assignment(x, op_pos) if x.0.get_variable_access(false).is_some() => {
                 let (lhs_expr, op, rhs_expr) = x.as_ref();
-                let mut rhs_val = self
+                let rhs_val = self
                     .eval_expr(scope, mods, state, lib, this_ptr, rhs_expr, level)?
                     .flatten();
-                let (mut lhs_ptr, pos) =
-                    self.search_namespace(scope, mods, state, lib, this_ptr, lhs_expr)?;
-
-                if !lhs_ptr.is_ref() {
-                    return EvalAltResult::ErrorAssignmentToConstant(
-                        lhs_expr.get_variable_access(false).unwrap().to_string(),
-                        pos,
-                    )
-                    .into();
-                }
 
-                self.inc_operations(state, pos)?;
+                // Check the variable assignment callback, if any
+                let (rhs_val, op) = match (&self.write_var, lhs_expr.get_variable_access(true)) {
+                    (Some(write_var), Some(name)) if name != KEYWORD_THIS => {
+                        let new_val = if op.is_empty() {
+                            rhs_val
+                        } else {
+                            // Calculate the new value of `var op= rhs` on a copy
+                            let (lhs_ptr, _) =
+                                self.search_namespace(scope, mods, state, lib, this_ptr, lhs_expr)?;
+                            let mut target: Target = lhs_ptr.as_ref().flatten_clone().into();
+                            self.eval_op_assignment(
+                                mods,
+                                state,
+                                lib,
+                                &mut target,
+                                ((rhs_val, rhs_expr.position()), (op.as_ref(), *op_pos)),
+                                level,
+                            )?;
+                            target.take_or_clone()
+                        };
+
+                        let context = EvalContext {
+                            engine: self,
+                            scope,
+                            mods,
+                            state,
+                            lib,
+                            this_ptr,
+                            level,
+                        };
+
+                        if write_var(name, &new_val, &context)
+                            .map_err(|err| err.fill_position(lhs_expr.position()))?
+                        {
+                            return Ok(Dynamic::UNIT);
+                        }
+
+                        // Not handled - assign the new value as normal
+                        (new_val, "")
+                    }
+                    _ => (rhs_val, op.as_ref()),
+                };
+
+                let (mut lhs_ptr, pos) =
+                    self.search_namespace(scope, mods, state, lib, this_ptr, lhs_expr)?;
+
+                if !lhs_ptr.is_ref() {
+                    return EvalAltResult::ErrorAssignmentToConstant(
+                        lhs_expr.get_variable_access(false).unwrap().to_string(),
+                        pos,
+                    )
+                    .into();
+                }
+
+                self.inc_operations(state, pos)?;
 
                 if lhs_ptr.as_ref().is_read_only() {
                     // Assignment to constant variable
@@ -2015,75 +3297,14 @@ impl Engine {
                     }
                     Ok(Dynamic::UNIT)
                 } else {
-                    // Op-assignment - in order of precedence:
-                    // 1) Native registered overriding function
-                    // 2) Built-in implementation
-                    // 3) Map to `var = var op rhs`
-
-                    // Qualifiers (none) + function name + number of arguments + argument `TypeId`'s.
-                    let arg_types = once(lhs_ptr.as_mut().type_id()).chain(once(rhs_val.type_id()));
-                    let hash_fn = calc_native_fn_hash(empty(), op, arg_types).unwrap();
-
-                    match self
-                        .global_namespace
-                        .get_fn(hash_fn, false)
-                        .or_else(|| {
-                            self.global_modules
-                                .iter()
-                                .find_map(|m| m.get_fn(hash_fn, false))
-                        })
-                        .or_else(|| mods.get_fn(hash_fn))
-                    {
-                        // op= function registered as method
-                        Some(func) if func.is_method() => {
-                            let mut lock_guard;
-                            let lhs_ptr_inner;
-
-                            if cfg!(not(feature = "no_closure")) && lhs_ptr.is_shared() {
-                                lock_guard = lhs_ptr.as_mut().write_lock::<Dynamic>().unwrap();
-                                lhs_ptr_inner = lock_guard.deref_mut();
-                            } else {
-                                lhs_ptr_inner = lhs_ptr.as_mut();
-                            }
-
-                            let args = &mut [lhs_ptr_inner, &mut rhs_val];
-
-                            // Overriding exact implementation
-                            if func.is_plugin_fn() {
-                                func.get_plugin_fn()
-                                    .call((self, &state.source, &*mods, lib).into(), args)?;
-                            } else {
-                                func.get_native_fn()(
-                                    (self, &state.source, &*mods, lib).into(),
-                                    args,
-                                )?;
-                            }
-                        }
-                        // Built-in op-assignment function
-                        _ if run_builtin_op_assignment(op, lhs_ptr.as_mut(), &rhs_val)?
-                            .is_some() => {}
-                        // Not built-in: expand to `var = var op rhs`
-                        _ => {
-                            let op = &op[..op.len() - 1]; // extract operator without =
-
-                            // Clone the LHS value
-                            let args = &mut [&mut lhs_ptr.as_mut().clone(), &mut rhs_val];
-
-                            // Run function
-                            let (value, _) = self.exec_fn_call(
-                                mods, state, lib, op, None, args, false, false, false, *op_pos,
-                                None, None, level,
-                            )?;
-
-                            let value = value.flatten();
-
-                            if cfg!(not(feature = "no_closure")) && lhs_ptr.is_shared() {
-                                *lhs_ptr.as_mut().write_lock::<Dynamic>().unwrap() = value;
-                            } else {
-                                *lhs_ptr.as_mut() = value;
-                            }
-                        }
-                    }
+                    self.eval_op_assignment(
+                        mods,
+                        state,
+                        lib,
+                        &mut lhs_ptr,
+                        ((rhs_val, rhs_expr.position()), (op, *op_pos)),
+                        level,
+                    )?;
                     Ok(Dynamic::UNIT)
                 }
             }
@@ -2091,29 +3312,10 @@ impl Engine {
             // lhs op= rhs
             Stmt::Assignment(x, op_pos) => {
                 let (lhs_expr, op, rhs_expr) = x.as_ref();
-                let mut rhs_val =
-                    self.eval_expr(scope, mods, state, lib, this_ptr, rhs_expr, level)?;
-
-                let _new_val = if op.is_empty() {
-                    // Normal assignment
-                    Some((rhs_val, rhs_expr.position()))
-                } else {
-                    // Op-assignment - always map to `lhs = lhs op rhs`
-                    let op = &op[..op.len() - 1]; // extract operator without =
-                    let args = &mut [
-                        &mut self.eval_expr(scope, mods, state, lib, this_ptr, lhs_expr, level)?,
-                        &mut rhs_val,
-                    ];
-
-                    let result = self
-                        .exec_fn_call(
-                            mods, state, lib, op, None, args, false, false, false, *op_pos, None,
-                            None, level,
-                        )
-                        .map(|(v, _)| v)?;
-
-                    Some((result, rhs_expr.position()))
-                };
+                let rhs_val = self
+                    .eval_expr(scope, mods, state, lib, this_ptr, rhs_expr, level)?
+                    .flatten();
+                let _new_val = Some(((rhs_val, rhs_expr.position()), (op.as_ref(), *op_pos)));
 
                 // Must be either `var[index] op= val` or `var.prop op= val`
                 match lhs_expr {
@@ -2123,7 +3325,7 @@ impl Engine {
                     }
                     // idx_lhs[idx_expr] op= rhs
                     #[cfg(not(feature = "no_index"))]
-                    Expr::Index(_, _) => {
+                    Expr::Index(_, _, _) => {
                         self.eval_dot_index_chain(
                             scope, mods, state, lib, this_ptr, lhs_expr, level, _new_val,
                         )?;
@@ -2131,7 +3333,7 @@ impl Engine {
                     }
                     // dot_lhs.dot_rhs op= rhs
                     #[cfg(not(feature = "no_object"))]
-                    Expr::Dot(_, _) => {
+                    Expr::Dot(_, _, _) => {
                         self.eval_dot_index_chain(
                             scope, mods, state, lib, this_ptr, lhs_expr, level, _new_val,
                         )?;
@@ -2165,18 +3367,28 @@ impl Engine {
 
             // Switch statement
             Stmt::Switch(match_expr, x, _) => {
-                let (table, def_stmt) = x.as_ref();
-
-                let hasher = &mut get_hasher();
-                self.eval_expr_as_target(
-                    scope, mods, state, lib, this_ptr, match_expr, false, level,
-                )?
-                .0
-                .as_ref()
-                .hash(hasher);
-                let hash = hasher.finish();
-
-                if let Some(stmt) = table.get(&hash) {
+                let (table, def_stmt, ranges) = x.as_ref();
+
+                let stmt = {
+                    let (target, _) = self.eval_expr_as_target(
+                        scope, mods, state, lib, this_ptr, match_expr, false, level,
+                    )?;
+                    let value = target.as_ref();
+
+                    let hasher = &mut get_hasher();
+                    value.hash(hasher);
+                    let hash = hasher.finish();
+
+                    // Ranges are only checked after a hash miss, in order
+                    table.get(&hash).or_else(|| {
+                        ranges
+                            .iter()
+                            .find(|(range, _)| range.contains(value))
+                            .map(|(_, stmt)| stmt)
+                    })
+                };
+
+                if let Some(stmt) = stmt {
                     self.eval_stmt(scope, mods, state, lib, this_ptr, stmt, level)
                 } else if let Some(def_stmt) = def_stmt {
                     self.eval_stmt(scope, mods, state, lib, this_ptr, def_stmt, level)
@@ -2186,7 +3398,9 @@ impl Engine {
             }
 
             // While loop
-            Stmt::While(expr, body, _) => loop {
+            Stmt::While(expr, x, _) => loop {
+                let (body, label) = x.as_ref();
+
                 match self
                     .eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
                     .as_bool()
@@ -2195,8 +3409,13 @@ impl Engine {
                         match self.eval_stmt(scope, mods, state, lib, this_ptr, body, level) {
                             Ok(_) => (),
                             Err(err) => match *err {
-                                EvalAltResult::LoopBreak(false, _) => (),
-                                EvalAltResult::LoopBreak(true, _) => return Ok(Dynamic::UNIT),
+                                EvalAltResult::LoopBreak(false, target, _, _)
+                                    if is_loop_target(&target, label) => {}
+                                EvalAltResult::LoopBreak(true, target, value, _)
+                                    if is_loop_target(&target, label) =>
+                                {
+                                    return Ok(value)
+                                }
                                 _ => return Err(err),
                             },
                         }
@@ -2209,12 +3428,22 @@ impl Engine {
             },
 
             // Do loop
-            Stmt::Do(body, expr, is_while, _) => loop {
+            Stmt::Do(x, expr, is_while, _) => loop {
+                let (body, label) = x.as_ref();
+
                 match self.eval_stmt(scope, mods, state, lib, this_ptr, body, level) {
                     Ok(_) => (),
                     Err(err) => match *err {
-                        EvalAltResult::LoopBreak(false, _) => continue,
-                        EvalAltResult::LoopBreak(true, _) => return Ok(Dynamic::UNIT),
+                        EvalAltResult::LoopBreak(false, target, _, _)
+                            if is_loop_target(&target, label) =>
+                        {
+                            continue
+                        }
+                        EvalAltResult::LoopBreak(true, target, value, _)
+                            if is_loop_target(&target, label) =>
+                        {
+                            return Ok(value)
+                        }
                         _ => return Err(err),
                     },
                 }
@@ -2233,23 +3462,91 @@ impl Engine {
             },
 
             // For loop
-            Stmt::For(expr, x, _) => {
-                let (name, stmt) = x.as_ref();
-                let iter_obj = self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?;
-                let iter_type = iter_obj.type_id();
-
-                let func = self
-                    .global_namespace
-                    .get_iter(iter_type)
-                    .or_else(|| {
-                        self.global_modules
-                            .iter()
-                            .find_map(|m| m.get_iter(iter_type))
-                    })
-                    .or_else(|| mods.get_iter(iter_type));
+            Stmt::For(expr, x, pos) => {
+                let (counter, name, stmt, label) = x.as_ref();
+
+                let find_iter = |mods: &Imports, iter_type| {
+                    self.global_namespace
+                        .get_iter(iter_type)
+                        .or_else(|| {
+                            self.global_modules
+                                .iter()
+                                .find_map(|m| m.get_iter(iter_type))
+                        })
+                        .or_else(|| mods.get_iter(iter_type))
+                };
+
+                // Iterate a variable by reference, without cloning it, if its type iterator allows it
+                let by_ref = match expr {
+                    Expr::Variable(_) => {
+                        let (mut target, _) =
+                            self.search_namespace(scope, mods, state, lib, this_ptr, expr)?;
+
+                        match find_iter(mods, target.as_ref().type_id()) {
+                            Some(TypeIterator::Ref(func)) => Some(func(target.as_mut())),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+
+                let pairs: Option<
+                    Box<dyn Iterator<Item = (Dynamic, Result<Dynamic, Box<EvalAltResult>>)>>,
+                > = if let Some(iter) = by_ref {
+                    Some(Box::new(
+                        iter.enumerate().map(|(i, v)| ((i as crate::INT).into(), v)),
+                    ))
+                } else {
+                    let iter_obj =
+                        self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?;
+
+                    match iter_obj {
+                        // Iterating an object map with two loop variables yields (key, value) pairs
+                        #[cfg(not(feature = "no_object"))]
+                        obj if counter.is_some() && obj.is::<Map>() => Some(Box::new(
+                            obj.cast::<Map>()
+                                .into_iter()
+                                .map(|(k, v)| (k.into(), Ok(v))),
+                        )),
+                        // Otherwise the counter is the position of each item
+                        mut obj => match find_iter(mods, obj.type_id()) {
+                            Some(TypeIterator::Fn(func)) => Some(Box::new(
+                                func(obj)
+                                    .enumerate()
+                                    .map(|(i, v)| ((i as crate::INT).into(), Ok(v))),
+                            )),
+                            Some(TypeIterator::Ref(func)) => Some(Box::new(
+                                func(&mut obj)
+                                    .enumerate()
+                                    .map(|(i, v)| ((i as crate::INT).into(), v)),
+                            )),
+                            None => None,
+                        },
+                    }
+                };
+
+                if let Some(pairs) = pairs {
+                    // Guard against too many variables
+                    #[cfg(not(feature = "unchecked"))]
+                    self.ensure_scope_within_limit(
+                        scope,
+                        if counter.is_some() { 2 } else { 1 },
+                        *pos,
+                    )?;
+
+                    let prev_scope_len = scope.len();
+
+                    // Add the loop variables
+                    let counter_index = counter.as_ref().map(|counter| {
+                        let var_name: Cow<'_, str> = if state.is_global() {
+                            counter.clone().into()
+                        } else {
+                            unsafe_cast_var_name_to_lifetime(counter).into()
+                        };
+                        scope.push(var_name, ());
+                        scope.len() - 1
+                    });
 
-                if let Some(func) = func {
-                    // Add the loop variable
                     let var_name: Cow<'_, str> = if state.is_global() {
                         name.clone().into()
                     } else {
@@ -2259,87 +3556,88 @@ impl Engine {
                     let index = scope.len() - 1;
                     state.scope_level += 1;
 
-                    for iter_value in func(iter_obj) {
-                        let loop_var = scope.get_mut_by_index(index);
-                        let value = iter_value.flatten();
+                    let mut result = Dynamic::UNIT;
 
-                        if cfg!(not(feature = "no_closure")) && loop_var.is_shared() {
-                            *loop_var.write_lock().unwrap() = value;
-                        } else {
-                            *loop_var = value;
+                    for (iter_counter, iter_value) in pairs {
+                        let iter_value = iter_value.map_err(|err| err.fill_position(*pos))?;
+
+                        if let Some(counter_index) = counter_index {
+                            set_loop_var(scope.get_mut_by_index(counter_index), iter_counter);
                         }
+                        set_loop_var(scope.get_mut_by_index(index), iter_value.flatten());
 
                         self.inc_operations(state, stmt.position())?;
 
                         match self.eval_stmt(scope, mods, state, lib, this_ptr, stmt, level) {
                             Ok(_) => (),
                             Err(err) => match *err {
-                                EvalAltResult::LoopBreak(false, _) => (),
-                                EvalAltResult::LoopBreak(true, _) => break,
+                                EvalAltResult::LoopBreak(false, target, _, _)
+                                    if is_loop_target(&target, label) => {}
+                                EvalAltResult::LoopBreak(true, target, value, _)
+                                    if is_loop_target(&target, label) =>
+                                {
+                                    result = value;
+                                    break;
+                                }
                                 _ => return Err(err),
                             },
                         }
                     }
 
                     state.scope_level -= 1;
-                    scope.rewind(scope.len() - 1);
-                    Ok(Dynamic::UNIT)
+                    scope.rewind(prev_scope_len);
+                    Ok(result)
                 } else {
                     EvalAltResult::ErrorFor(expr.position()).into()
                 }
             }
 
             // Continue statement
-            Stmt::Continue(pos) => EvalAltResult::LoopBreak(false, *pos).into(),
+            Stmt::Continue(label, pos) => {
+                EvalAltResult::LoopBreak(false, label.clone(), Dynamic::UNIT, *pos).into()
+            }
+
+            // Break statement with value
+            Stmt::Break(label, Some(expr), pos) => EvalAltResult::LoopBreak(
+                true,
+                label.clone(),
+                self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?,
+                *pos,
+            )
+            .into(),
 
             // Break statement
-            Stmt::Break(pos) => EvalAltResult::LoopBreak(true, *pos).into(),
+            Stmt::Break(label, None, pos) => {
+                EvalAltResult::LoopBreak(true, label.clone(), Dynamic::UNIT, *pos).into()
+            }
 
             // Try/Catch statement
             Stmt::TryCatch(x, _, _) => {
-                let (try_body, err_var, catch_body) = x.as_ref();
+                let (try_body, clauses) = x.as_ref();
 
-                let result = self
-                    .eval_stmt(scope, mods, state, lib, this_ptr, try_body, level)
-                    .map(|_| ().into());
+                let result = self.eval_stmt(scope, mods, state, lib, this_ptr, try_body, level);
 
                 match result {
                     Ok(_) => result,
-                    Err(err) if !err.is_catchable() => Err(err),
-                    Err(mut err) => {
-                        let value = match *err {
-                            EvalAltResult::ErrorRuntime(ref x, _) => x.clone(),
-                            _ => {
-                                err.set_position(Position::NONE);
-                                err.to_string().into()
-                            }
-                        };
-
-                        let orig_scope_len = scope.len();
-                        state.scope_level += 1;
-
-                        if let Some(Ident { name, .. }) = err_var {
-                            scope.push(unsafe_cast_var_name_to_lifetime(&name), value);
-                        }
-
-                        let result =
-                            self.eval_stmt(scope, mods, state, lib, this_ptr, catch_body, level);
-
-                        state.scope_level -= 1;
-                        scope.rewind(orig_scope_len);
-
-                        match result {
-                            Ok(_) => Ok(Dynamic::UNIT),
-                            Err(result_err) => match *result_err {
-                                // Re-throw exception
-                                EvalAltResult::ErrorRuntime(Dynamic(Union::Unit(_, _)), pos) => {
-                                    err.set_position(pos);
-                                    Err(err)
-                                }
-                                _ => Err(result_err),
-                            },
-                        }
+                    // `break`, `continue` and `return` are passed straight-through
+                    Err(err)
+                        if matches!(
+                            *err,
+                            EvalAltResult::LoopBreak(_, _, _, _) | EvalAltResult::Return(_, _)
+                        ) =>
+                    {
+                        Err(err)
                     }
+                    Err(err)
+                        if !self
+                            .catch_filter
+                            .as_ref()
+                            .map_or_else(|| err.is_catchable(), |filter| filter(&err)) =>
+                    {
+                        Err(err)
+                    }
+                    Err(err) => self
+                        .eval_catch_clauses(scope, mods, state, lib, this_ptr, clauses, err, level),
                 }
             }
 
@@ -2394,6 +3692,11 @@ impl Engine {
                 } else {
                     (unsafe_cast_var_name_to_lifetime(&var_def.name).into(), None)
                 };
+
+                // Guard against too many variables
+                #[cfg(not(feature = "unchecked"))]
+                self.ensure_scope_within_limit(scope, 1, var_def.pos)?;
+
                 scope.push_dynamic_value(var_name, entry_type, val);
 
                 #[cfg(not(feature = "no_module"))]
@@ -2403,30 +3706,89 @@ impl Engine {
                 Ok(Dynamic::UNIT)
             }
 
+            // Let/const statement with a destructuring pattern
+            #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+            Stmt::LetPattern(x, entry_type, export, _pos) => {
+                let (pattern, expr) = x.as_ref();
+                self.eval_let_pattern(
+                    scope,
+                    mods,
+                    state,
+                    lib,
+                    this_ptr,
+                    pattern,
+                    expr,
+                    *entry_type,
+                    *export,
+                    *_pos,
+                    level,
+                )
+            }
+
             // Import statement
             #[cfg(not(feature = "no_module"))]
-            Stmt::Import(expr, alias, _pos) => {
+            Stmt::Import(expr, alias, pos) => {
                 // Guard against too many modules
                 #[cfg(not(feature = "unchecked"))]
-                if state.modules >= self.max_modules() {
-                    return EvalAltResult::ErrorTooManyModules(*_pos).into();
+                if state.modules
+                    >= state
+                        .options
+                        .max_modules
+                        .unwrap_or_else(|| self.max_modules())
+                {
+                    return EvalAltResult::ErrorTooManyModules(*pos).into();
                 }
 
                 if let Some(path) = self
                     .eval_expr(scope, mods, state, lib, this_ptr, &expr, level)?
                     .try_cast::<ImmutableString>()
                 {
-                    let module = self.module_resolver.resolve(self, &path, expr.position())?;
+                    // Make sure that the module is allowed
+                    if let Some(filter) = self.module_filter.as_ref() {
+                        if !filter(&path) {
+                            return EvalAltResult::ErrorModuleDenied(path.to_string(), *pos).into();
+                        }
+                    }
 
-                    if let Some(name_def) = alias {
-                        if !module.is_indexed() {
+                    // Reuse the module if the same path is already imported during this evaluation
+                    let module = if let Some(module) = mods.get_resolved(&path) {
+                        module
+                    } else {
+                        // Evaluating a module is one level deeper, like a function call,
+                        // so modules importing each other cannot overflow the stack
+                        #[cfg(not(feature = "no_function"))]
+                        #[cfg(not(feature = "unchecked"))]
+                        if level
+                            >= state
+                                .options
+                                .max_call_levels
+                                .unwrap_or_else(|| self.max_call_levels())
+                        {
+                            return EvalAltResult::ErrorStackOverflow(*pos).into();
+                        }
+
+                        let module = self.module_resolver.resolve_raw(
+                            self,
+                            &mut crate::module_resolvers::ResolveContext::new(state, level + 1),
+                            &path,
+                            expr.position(),
+                        )?;
+
+                        let module = if !module.is_indexed() {
                             // Index the module (making a clone copy if necessary) if it is not indexed
                             let mut module = crate::fn_native::shared_take_or_clone(module);
                             module.build_index();
-                            mods.push(name_def.name.clone(), module);
+                            module.into()
                         } else {
-                            mods.push(name_def.name.clone(), module);
-                        }
+                            module
+                        };
+
+                        mods.set_resolved(path, module.clone());
+                        module
+                    };
+
+                    if let Some(name_def) = alias {
+                        mods.push(name_def.name.clone(), module);
                         // When imports list is modified, clear the functions lookup cache
                         state.functions_cache.clear();
                     }
@@ -2447,10 +3809,20 @@ impl Engine {
                     if let Some(index) = scope.get_index(name).map(|(i, _)| i) {
                         let alias = rename.as_ref().map(|x| &x.name).unwrap_or_else(|| name);
                         scope.add_entry_alias(index, alias.clone());
-                    } else {
-                        return EvalAltResult::ErrorVariableNotFound(name.to_string(), *id_pos)
-                            .into();
+                        continue;
+                    }
+
+                    // Functions are marked as exported when parsing
+                    #[cfg(not(feature = "no_function"))]
+                    if rename.is_none()
+                        && lib
+                            .iter()
+                            .any(|m| m.iter_script_fn().any(|(_, _, n, _, _)| n == name.as_str()))
+                    {
+                        continue;
                     }
+
+                    return EvalAltResult::ErrorVariableNotFound(name.to_string(), *id_pos).into();
                 }
                 Ok(Dynamic::UNIT)
             }
@@ -2473,6 +3845,244 @@ impl Engine {
         self.check_data_size(result, stmt.position())
     }
 
+    /// Evaluate the `catch` clauses of a `try` statement for a caught exception, returning the
+    /// result of the first clause whose guard matches, or the exception itself if none does.
+    fn eval_catch_clauses(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        clauses: &[CatchClause],
+        mut err: Box<EvalAltResult>,
+        level: usize,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let err_pos = err.position();
+
+        let value = match *err {
+            EvalAltResult::ErrorRuntime(ref x, _) => x.clone(),
+            _ => {
+                err.set_position(Position::NONE);
+                err.to_string().into()
+            }
+        };
+
+        for (err_var, pos_var, guard, catch_body) in clauses.iter() {
+            let orig_scope_len = scope.len();
+            state.scope_level += 1;
+
+            if let Some(Ident { name, pos: _pos }) = err_var {
+                // Guard against too many variables
+                #[cfg(not(feature = "unchecked"))]
+                self.ensure_scope_within_limit(scope, 1, *_pos)?;
+
+                scope.push(unsafe_cast_var_name_to_lifetime(&name), value.clone());
+            }
+
+            // The position variable holds where the exception was raised
+            #[cfg(not(feature = "no_object"))]
+            if let Some(Ident { name, pos: _pos }) = pos_var {
+                #[cfg(not(feature = "unchecked"))]
+                self.ensure_scope_within_limit(scope, 1, *_pos)?;
+
+                let mut map = Map::new();
+                map.insert(
+                    "line".into(),
+                    err_pos.line().map_or(Dynamic::UNIT, |v| (v as INT).into()),
+                );
+                map.insert(
+                    "position".into(),
+                    err_pos
+                        .position()
+                        .map_or(Dynamic::UNIT, |v| (v as INT).into()),
+                );
+                map.insert(
+                    "source".into(),
+                    state
+                        .source
+                        .as_ref()
+                        .map_or(Dynamic::UNIT, |s| s.clone().into()),
+                );
+                scope.push(unsafe_cast_var_name_to_lifetime(&name), map);
+            }
+            #[cfg(feature = "no_object")]
+            let _ = pos_var;
+
+            // Skip the catch clause if its guard does not match
+            let matched = match guard {
+                Some(expr) => self
+                    .eval_expr(scope, mods, state, lib, this_ptr, expr, level)
+                    .and_then(|v| {
+                        v.as_bool().map_err(|err| {
+                            self.make_type_mismatch_err::<bool>(err, expr.position())
+                        })
+                    }),
+                None => Ok(true),
+            };
+
+            let result = match matched {
+                Ok(true) => {
+                    Some(self.eval_stmt(scope, mods, state, lib, this_ptr, catch_body, level))
+                }
+                Ok(false) => None,
+                Err(guard_err) => Some(Err(guard_err)),
+            };
+
+            state.scope_level -= 1;
+            scope.rewind(orig_scope_len);
+
+            let result = match result {
+                Some(result) => result,
+                None => continue,
+            };
+
+            return match result {
+                Ok(_) => result,
+                Err(result_err) => match *result_err {
+                    // Re-throw exception
+                    EvalAltResult::ErrorRuntime(Dynamic(Union::Unit(_, _)), pos) => {
+                        err.set_position(pos);
+                        Err(err)
+                    }
+                    _ => Err(result_err),
+                },
+            };
+        }
+
+        // No catch clause matches - propagate the exception
+        err.set_position(err_pos);
+        Err(err)
+    }
+
+    /// Evaluate a `let`/`const` statement with a destructuring pattern.
+    #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+    fn eval_let_pattern(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        pattern: &Pattern,
+        expr: &Expr,
+        entry_type: AccessMode,
+        export: bool,
+        _pos: Position,
+        level: usize,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let val = self
+            .eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
+            .flatten();
+        let typ = self.map_type_name(val.type_name()).to_string();
+
+        let values: StaticVec<(&Ident, Dynamic)> = match pattern {
+            #[cfg(not(feature = "no_index"))]
+            Pattern::Array(vars, rest) => {
+                let mut arr = val.try_cast::<Array>().ok_or_else(|| {
+                    EvalAltResult::ErrorMismatchDataType(
+                        self.map_type_name(type_name::<Array>()).into(),
+                        typ,
+                        expr.position(),
+                    )
+                })?;
+
+                if arr.len() < vars.len() {
+                    return EvalAltResult::ErrorDestructure(arr.len(), vars.len(), expr.position())
+                        .into();
+                }
+
+                let rest_values = arr.split_off(vars.len());
+                let mut values: StaticVec<_> = vars.iter().zip(arr).collect();
+                if let Some(rest) = rest {
+                    values.push((rest, rest_values.into()));
+                }
+                values
+            }
+            #[cfg(not(feature = "no_object"))]
+            Pattern::Map(vars, rest) => {
+                let mut map = val.try_cast::<Map>().ok_or_else(|| {
+                    EvalAltResult::ErrorMismatchDataType(
+                        self.map_type_name(type_name::<Map>()).into(),
+                        typ,
+                        expr.position(),
+                    )
+                })?;
+
+                // Missing properties are ()
+                let mut values: StaticVec<_> = vars
+                    .iter()
+                    .map(|var_def| {
+                        let value = map.remove(var_def.name.as_str()).unwrap_or_default();
+                        (var_def, value)
+                    })
+                    .collect();
+                if let Some(rest) = rest {
+                    values.push((rest, map.into()));
+                }
+                values
+            }
+        };
+
+        // Guard against too many variables
+        #[cfg(not(feature = "unchecked"))]
+        self.ensure_scope_within_limit(scope, values.len(), _pos)?;
+
+        for (var_def, value) in values {
+            let (var_name, _alias): (Cow<'_, str>, _) = if state.is_global() {
+                (
+                    var_def.name.to_string().into(),
+                    if export {
+                        Some(var_def.name.clone())
+                    } else {
+                        None
+                    },
+                )
+            } else if export {
+                unreachable!("exported variable not on global level");
+            } else {
+                (unsafe_cast_var_name_to_lifetime(&var_def.name).into(), None)
+            };
+            scope.push_dynamic_value(var_name, entry_type, value);
+
+            #[cfg(not(feature = "no_module"))]
+            if let Some(alias) = _alias {
+                scope.add_entry_alias(scope.len() - 1, alias);
+            }
+        }
+        Ok(Dynamic::UNIT)
+    }
+
+    /// Recursively copy a value, severing all shared values, within the data size limits.
+    pub(crate) fn deep_copy_value(
+        &self,
+        target: &Target,
+        pos: Position,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let (value, container) = match target {
+            #[cfg(not(feature = "no_closure"))]
+            #[cfg(not(feature = "no_object"))]
+            Target::LockGuard((guard, orig)) => (&**guard, Some(orig)),
+            _ => (target.as_ref(), None),
+        };
+
+        #[cfg(not(feature = "unchecked"))]
+        #[cfg(not(feature = "no_index"))]
+        let max_array_size = self.max_array_size();
+        #[cfg(any(feature = "unchecked", feature = "no_index"))]
+        let max_array_size = 0;
+
+        #[cfg(not(feature = "unchecked"))]
+        #[cfg(not(feature = "no_object"))]
+        let max_map_size = self.max_map_size();
+        #[cfg(any(feature = "unchecked", feature = "no_object"))]
+        let max_map_size = 0;
+
+        value
+            .deep_copy_with_limits(container, max_array_size, max_map_size)
+            .map_err(|err| err.fill_position(pos))
+    }
+
     /// Check a result to ensure that the data size is within allowable limit.
     /// [`Position`] in [`EvalAltResult`] may be None and should be set afterwards.
     #[cfg(feature = "unchecked")]
@@ -2509,58 +4119,6 @@ impl Engine {
             return result;
         }
 
-        // Recursively calculate the size of a value (especially `Array` and `Map`)
-        fn calc_size(value: &Dynamic) -> (usize, usize, usize) {
-            match value {
-                #[cfg(not(feature = "no_index"))]
-                Dynamic(Union::Array(arr, _)) => {
-                    let mut arrays = 0;
-                    let mut maps = 0;
-
-                    arr.iter().for_each(|value| match value {
-                        Dynamic(Union::Array(_, _)) => {
-                            let (a, m, _) = calc_size(value);
-                            arrays += a;
-                            maps += m;
-                        }
-                        #[cfg(not(feature = "no_object"))]
-                        Dynamic(Union::Map(_, _)) => {
-                            let (a, m, _) = calc_size(value);
-                            arrays += a;
-                            maps += m;
-                        }
-                        _ => arrays += 1,
-                    });
-
-                    (arrays, maps, 0)
-                }
-                #[cfg(not(feature = "no_object"))]
-                Dynamic(Union::Map(map, _)) => {
-                    let mut arrays = 0;
-                    let mut maps = 0;
-
-                    map.values().for_each(|value| match value {
-                        #[cfg(not(feature = "no_index"))]
-                        Dynamic(Union::Array(_, _)) => {
-                            let (a, m, _) = calc_size(value);
-                            arrays += a;
-                            maps += m;
-                        }
-                        Dynamic(Union::Map(_, _)) => {
-                            let (a, m, _) = calc_size(value);
-                            arrays += a;
-                            maps += m;
-                        }
-                        _ => maps += 1,
-                    });
-
-                    (arrays, maps, 0)
-                }
-                Dynamic(Union::Str(s, _)) => (0, 0, s.len()),
-                _ => (0, 0, 0),
-            }
-        }
-
         match result {
             // Simply return all errors
             Err(_) => return result,
@@ -2576,7 +4134,7 @@ impl Engine {
             Ok(_) => return result,
         };
 
-        let (_arr, _map, s) = calc_size(result.as_ref().unwrap());
+        let (_arr, _map, s) = calc_data_sizes(result.as_ref().unwrap());
 
         if s > self.max_string_size() {
             return EvalAltResult::ErrorDataTooLarge("Length of string".to_string(), pos).into();
@@ -2595,6 +4153,49 @@ impl Engine {
         result
     }
 
+    /// Check if the number of variables in the [`Scope`] stays within limit
+    /// after adding a number of new variables.
+    #[cfg(not(feature = "unchecked"))]
+    #[inline(always)]
+    pub(crate) fn ensure_scope_within_limit(
+        &self,
+        scope: &Scope,
+        new_vars: usize,
+        pos: Position,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let max = self.max_variables();
+
+        if max > 0 && scope.len() + new_vars > max {
+            EvalAltResult::ErrorTooManyVariables(pos).into()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Add to the estimated total size of data created during this evaluation,
+    /// checking that it stays within limit.
+    #[cfg(not(feature = "unchecked"))]
+    pub(crate) fn inc_data_size(
+        &self,
+        state: &mut State,
+        size: usize,
+        pos: Position,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let max_total_data_size = self.max_total_data_size();
+
+        if max_total_data_size == 0 || size == 0 {
+            return Ok(());
+        }
+
+        state.data_size = state.data_size.saturating_add(size);
+
+        if state.data_size > max_total_data_size {
+            return EvalAltResult::ErrorDataTooLarge("Total size of data".to_string(), pos).into();
+        }
+
+        Ok(())
+    }
+
     /// Check if the number of operations stay within limit.
     pub(crate) fn inc_operations(
         &self,
@@ -2605,12 +4206,24 @@ impl Engine {
 
         #[cfg(not(feature = "unchecked"))]
         // Guard against too many operations
-        if self.max_operations() > 0 && state.operations > self.max_operations() {
-            return EvalAltResult::ErrorTooManyOperations(pos).into();
+        {
+            let max_operations = state
+                .options
+                .max_operations
+                .unwrap_or_else(|| self.max_operations());
+
+            if max_operations > 0 && state.operations > max_operations {
+                return EvalAltResult::ErrorTooManyOperations(pos).into();
+            }
         }
 
         // Report progress - only in steps
-        if let Some(progress) = &self.progress {
+        let progress = match state.options.progress {
+            Some(ref progress) => Some(&**progress),
+            None => self.progress.as_ref(),
+        };
+
+        if let Some(progress) = progress {
             if let Some(token) = progress(state.operations) {
                 // Terminate script if progress returns a termination token
                 return EvalAltResult::ErrorTerminated(token, pos).into();
@@ -2620,6 +4233,55 @@ impl Engine {
         Ok(())
     }
 
+    /// Call the debugger before running a statement, if it should break there.
+    #[inline]
+    fn run_debugger(
+        &self,
+        debugger: &OnDebuggerCallback,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        stmt: &Stmt,
+        level: usize,
+    ) -> Result<(), Box<EvalAltResult>> {
+        // Blocks are not stepped on, only the statements inside
+        if matches!(stmt, Stmt::Noop(_) | Stmt::Block(_, _)) {
+            return Ok(());
+        }
+
+        if !state
+            .debugger
+            .get_or_insert_with(Default::default)
+            .is_break()
+        {
+            return Ok(());
+        }
+
+        let pos = stmt.position();
+
+        let context = EvalContext {
+            engine: self,
+            scope,
+            mods,
+            state,
+            lib,
+            this_ptr,
+            level,
+        };
+        let command = debugger(&context, pos);
+
+        if let Some(ref mut state) = state.debugger {
+            state.apply(command);
+        }
+
+        match command {
+            DebuggerCommand::Abort => EvalAltResult::ErrorTerminated(Dynamic::UNIT, pos).into(),
+            _ => Ok(()),
+        }
+    }
+
     /// Map a type_name into a pretty-print name
     #[inline(always)]
     pub(crate) fn map_type_name<'a>(&'a self, name: &'a str) -> &'a str {
@@ -2629,6 +4291,37 @@ impl Engine {
             .unwrap_or_else(|| map_std_type_name(name))
     }
 
+    /// Lock a value as a specific type for an operation that has already checked its type.
+    ///
+    /// Returns an error naming the operation and the actual type of the value, instead of
+    /// panicking, if the value is currently locked or cannot be accessed as that type.
+    #[inline(always)]
+    pub(crate) fn lock_value_as<'a, T: Variant + Clone>(
+        &self,
+        value: &'a Dynamic,
+        op: &str,
+        pos: Position,
+    ) -> Result<DynamicReadLock<'a, T>, Box<EvalAltResult>> {
+        if value.is_locked() {
+            return EvalAltResult::ErrorDataRace(op.into(), pos).into();
+        }
+
+        value.read_lock::<T>().ok_or_else(|| {
+            EvalAltResult::ErrorInFunctionCall(
+                op.into(),
+                String::new(),
+                EvalAltResult::ErrorMismatchDataType(
+                    self.map_type_name(type_name::<T>()).into(),
+                    self.map_type_name(value.type_name()).into(),
+                    pos,
+                )
+                .into(),
+                pos,
+            )
+            .into()
+        })
+    }
+
     /// Make a `Box<`[`EvalAltResult<ErrorMismatchDataType>`][EvalAltResult::ErrorMismatchDataType]`>`.
     #[inline(always)]
     pub(crate) fn make_type_mismatch_err<T>(&self, typ: &str, pos: Position) -> Box<EvalAltResult> {
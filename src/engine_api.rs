@@ -9,23 +9,34 @@ use crate::stdlib::{
     boxed::Box,
     format,
     hash::{Hash, Hasher},
+    mem,
     string::String,
     vec::Vec,
 };
 use crate::utils::get_hasher;
 use crate::{
-    scope::Scope, Dynamic, Engine, EvalAltResult, FnAccess, FnNamespace, Module, NativeCallContext,
-    ParseError, Position, Shared, AST,
+    scope::Scope, CallAudit, CapturedOutput, DebuggerCommand, Dynamic, Engine, EvalAltResult,
+    EvalMetrics, EvalOptions, FnAccess, FnNamespace, Module, NativeCallContext, ParseError,
+    Position, Shared, AST,
 };
 
-#[cfg(not(feature = "no_index"))]
-use crate::Array;
+#[cfg(not(feature = "no_module"))]
+#[cfg(feature = "async")]
+use crate::ImmutableString;
 
 #[cfg(not(feature = "no_object"))]
 use crate::Map;
 
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(target_arch = "wasm32"))]
+use crate::stdlib::time::Instant;
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+
 /// Calculate a unique hash for a script.
-fn calc_hash_for_scripts<'a>(scripts: impl IntoIterator<Item = &'a &'a str>) -> u64 {
+pub(crate) fn calc_hash_for_scripts<'a>(scripts: impl IntoIterator<Item = &'a &'a str>) -> u64 {
     let s = &mut get_hasher();
     scripts.into_iter().for_each(|&script| script.hash(s));
     s.finish()
@@ -158,6 +169,100 @@ impl Engine {
         self.type_names.insert(type_name::<T>().into(), name.into());
         self
     }
+    /// Register serialization support for a custom type with the [`Engine`].
+    ///
+    /// Values of the type can then be converted via [`Engine::from_dynamic`] and
+    /// [`Engine::to_dynamic`], even when nested inside arrays and object maps.
+    /// Each value is serialized as an object map with two properties: `$type` holding the name
+    /// of the type (the name registered via [`register_type_with_name`][Engine::register_type_with_name],
+    /// if any) and `$value` holding the serialized value itself.
+    ///
+    /// Exported under the `serde` feature only.  Not available under `no_object`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Dynamic, INT};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    /// struct Point {
+    ///     x: INT,
+    ///     y: INT,
+    /// }
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Tagged {
+    ///     #[serde(rename = "$type")]
+    ///     type_name: String,
+    ///     #[serde(rename = "$value")]
+    ///     value: Point,
+    /// }
+    ///
+    /// let mut engine = Engine::new();
+    /// engine
+    ///     .register_type_with_name::<Point>("Point")
+    ///     .register_type_serde::<Point>();
+    ///
+    /// // Serialize the value, tagged with its type name
+    /// let data: Tagged = engine.from_dynamic(&Dynamic::from(Point { x: 1, y: 2 }))?;
+    /// assert_eq!(data.type_name, "Point");
+    ///
+    /// // Restore the value from the serialized data
+    /// let value = engine.to_dynamic(&data)?;
+    /// assert_eq!(value.cast::<Point>(), Point { x: 1, y: 2 });
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "serde")]
+    #[cfg(not(feature = "no_object"))]
+    #[inline(always)]
+    pub fn register_type_serde<T>(&mut self) -> &mut Self
+    where
+        T: Variant + Clone + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.custom_types_serde.insert(
+            TypeId::of::<T>(),
+            crate::serde_impl::custom::CustomTypeSerde::new::<T>(),
+        );
+        self
+    }
+    /// Deserialize a [`Dynamic`] value into a Rust type that implements [`serde::Deserialize`].
+    ///
+    /// Same as [`rhai::serde::from_dynamic`][crate::serde::from_dynamic], except that values of
+    /// custom types registered via [`register_type_serde`][Engine::register_type_serde]
+    /// (including those nested inside arrays and object maps) are serialized as object maps
+    /// tagged with their type names.
+    ///
+    /// Exported under the `serde` feature only.  Not available under `no_object`.
+    #[cfg(feature = "serde")]
+    #[cfg(not(feature = "no_object"))]
+    #[inline]
+    pub fn from_dynamic<T: serde::de::DeserializeOwned>(
+        &self,
+        value: &Dynamic,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let value = crate::serde_impl::custom::encode_custom_types(self, value)?;
+        crate::serde_impl::de::from_dynamic(&value)
+    }
+    /// Serialize a Rust type that implements [`serde::Serialize`] into a [`Dynamic`].
+    ///
+    /// Same as [`rhai::serde::to_dynamic`][crate::serde::to_dynamic], except that object maps
+    /// tagged with the type names of custom types registered via
+    /// [`register_type_serde`][Engine::register_type_serde] (including those nested inside arrays
+    /// and object maps) are restored into values of those types.
+    ///
+    /// An object map tagged with an unknown type name is an error.
+    ///
+    /// Exported under the `serde` feature only.  Not available under `no_object`.
+    #[cfg(feature = "serde")]
+    #[cfg(not(feature = "no_object"))]
+    #[inline]
+    pub fn to_dynamic<T: serde::Serialize>(&self, value: T) -> Result<Dynamic, Box<EvalAltResult>> {
+        let value = crate::serde_impl::ser::to_dynamic(value)?;
+        crate::serde_impl::custom::decode_custom_types(self, value)
+    }
     /// Register an type iterator for an iterable type with the [`Engine`].
     /// This is an advanced feature.
     #[inline(always)]
@@ -169,6 +274,48 @@ impl Engine {
         self.global_namespace.set_iterable::<T>();
         self
     }
+    /// Register a type iterator for a custom type with the [`Engine`], which iterates
+    /// the value by mutable reference instead of consuming it.
+    ///
+    /// When the `for` loop iterates a variable, the collection is not cloned.
+    /// Items are pulled lazily from the iterator, one per loop iteration.
+    /// An error yielded by the iterator stops the loop and is raised at the position of the `for` statement.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, Dynamic, RegisterFn, INT};
+    ///
+    /// #[derive(Clone)]
+    /// struct Inventory { count: INT }
+    ///
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// let mut engine = Engine::new();
+    ///
+    /// engine
+    ///     .register_fn("new_inventory", || Inventory { count: 3 })
+    ///     .register_iterator_fn::<Inventory, _>(|inv| {
+    ///         Box::new((1..=inv.count).map(|n| Ok(Dynamic::from(n * 10))))
+    ///     });
+    ///
+    /// assert_eq!(
+    ///     engine.eval::<INT>("let inv = new_inventory(); let sum = 0; for x in inv { sum += x; } sum")?,
+    ///     60
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn register_iterator_fn<T, F>(&mut self, func: F) -> &mut Self
+    where
+        T: Variant + Clone,
+        F: Fn(&mut T) -> Box<dyn Iterator<Item = Result<Dynamic, Box<EvalAltResult>>>>
+            + SendSync
+            + 'static,
+    {
+        self.global_namespace.set_iterator_fn::<T>(func);
+        self
+    }
     /// Register a getter function for a member of a registered type with the [`Engine`].
     ///
     /// The function signature must start with `&mut self` and not `&self`.
@@ -410,10 +557,13 @@ impl Engine {
     ///
     /// The function signature must start with `&mut self` and not `&self`.
     ///
+    /// An index getter registered for [`Array`][crate::Array] or [`Map`][crate::Map] takes precedence
+    /// over the built-in indexing, which is used instead when the index getter returns `()`.
+    ///
     /// # Panics
     ///
-    /// Panics if the type is [`Array`], [`Map`], [`String`], [`ImmutableString`][crate::ImmutableString] or `&str`.
-    /// Indexers for arrays, object maps and strings cannot be registered.
+    /// Panics if the type is [`String`], [`ImmutableString`][crate::ImmutableString] or `&str`.
+    /// Indexers for strings cannot be registered.
     ///
     /// # Example
     ///
@@ -453,13 +603,6 @@ impl Engine {
         &mut self,
         get_fn: impl Fn(&mut T, X) -> U + SendSync + 'static,
     ) -> &mut Self {
-        if TypeId::of::<T>() == TypeId::of::<Array>() {
-            panic!("Cannot register indexer for arrays.");
-        }
-        #[cfg(not(feature = "no_object"))]
-        if TypeId::of::<T>() == TypeId::of::<Map>() {
-            panic!("Cannot register indexer for object maps.");
-        }
         if TypeId::of::<T>() == TypeId::of::<String>()
             || TypeId::of::<T>() == TypeId::of::<&str>()
             || TypeId::of::<T>() == TypeId::of::<crate::ImmutableString>()
@@ -474,10 +617,13 @@ impl Engine {
     ///
     /// The function signature must start with `&mut self` and not `&self`.
     ///
+    /// An index getter registered for [`Array`][crate::Array] or [`Map`][crate::Map] takes precedence
+    /// over the built-in indexing, which is used instead when the index getter returns `()`.
+    ///
     /// # Panics
     ///
-    /// Panics if the type is [`Array`], [`Map`], [`String`], [`ImmutableString`][crate::ImmutableString] or `&str`.
-    /// Indexers for arrays, object maps and strings cannot be registered.
+    /// Panics if the type is [`String`], [`ImmutableString`][crate::ImmutableString] or `&str`.
+    /// Indexers for strings cannot be registered.
     ///
     /// # Example
     ///
@@ -519,13 +665,6 @@ impl Engine {
         &mut self,
         get_fn: impl Fn(&mut T, X) -> Result<Dynamic, Box<EvalAltResult>> + SendSync + 'static,
     ) -> &mut Self {
-        if TypeId::of::<T>() == TypeId::of::<Array>() {
-            panic!("Cannot register indexer for arrays.");
-        }
-        #[cfg(not(feature = "no_object"))]
-        if TypeId::of::<T>() == TypeId::of::<Map>() {
-            panic!("Cannot register indexer for object maps.");
-        }
         if TypeId::of::<T>() == TypeId::of::<String>()
             || TypeId::of::<T>() == TypeId::of::<&str>()
             || TypeId::of::<T>() == TypeId::of::<crate::ImmutableString>()
@@ -538,10 +677,13 @@ impl Engine {
     }
     /// Register an index setter for a custom type with the [`Engine`].
     ///
+    /// An index setter registered for [`Array`][crate::Array] or [`Map`][crate::Map] takes precedence
+    /// over the built-in indexing.
+    ///
     /// # Panics
     ///
-    /// Panics if the type is [`Array`], [`Map`], [`String`], [`ImmutableString`][crate::ImmutableString] or `&str`.
-    /// Indexers for arrays, object maps and strings cannot be registered.
+    /// Panics if the type is [`String`], [`ImmutableString`][crate::ImmutableString] or `&str`.
+    /// Indexers for strings cannot be registered.
     ///
     /// # Example
     ///
@@ -583,13 +725,6 @@ impl Engine {
         &mut self,
         set_fn: impl Fn(&mut T, X, U) + SendSync + 'static,
     ) -> &mut Self {
-        if TypeId::of::<T>() == TypeId::of::<Array>() {
-            panic!("Cannot register indexer for arrays.");
-        }
-        #[cfg(not(feature = "no_object"))]
-        if TypeId::of::<T>() == TypeId::of::<Map>() {
-            panic!("Cannot register indexer for object maps.");
-        }
         if TypeId::of::<T>() == TypeId::of::<String>()
             || TypeId::of::<T>() == TypeId::of::<&str>()
             || TypeId::of::<T>() == TypeId::of::<crate::ImmutableString>()
@@ -602,10 +737,13 @@ impl Engine {
     }
     /// Register an index setter for a custom type with the [`Engine`].
     ///
+    /// An index setter registered for [`Array`][crate::Array] or [`Map`][crate::Map] takes precedence
+    /// over the built-in indexing.
+    ///
     /// # Panics
     ///
-    /// Panics if the type is [`Array`], [`Map`], [`String`], [`ImmutableString`][crate::ImmutableString] or `&str`.
-    /// Indexers for arrays, object maps and strings cannot be registered.
+    /// Panics if the type is [`String`], [`ImmutableString`][crate::ImmutableString] or `&str`.
+    /// Indexers for strings cannot be registered.
     ///
     /// # Example
     ///
@@ -654,13 +792,6 @@ impl Engine {
         &mut self,
         set_fn: impl Fn(&mut T, X, U) -> Result<(), Box<EvalAltResult>> + SendSync + 'static,
     ) -> &mut Self {
-        if TypeId::of::<T>() == TypeId::of::<Array>() {
-            panic!("Cannot register indexer for arrays.");
-        }
-        #[cfg(not(feature = "no_object"))]
-        if TypeId::of::<T>() == TypeId::of::<Map>() {
-            panic!("Cannot register indexer for object maps.");
-        }
         if TypeId::of::<T>() == TypeId::of::<String>()
             || TypeId::of::<T>() == TypeId::of::<&str>()
             || TypeId::of::<T>() == TypeId::of::<crate::ImmutableString>()
@@ -677,8 +808,8 @@ impl Engine {
     ///
     /// # Panics
     ///
-    /// Panics if the type is [`Array`], [`Map`], [`String`], [`ImmutableString`][crate::ImmutableString] or `&str`.
-    /// Indexers for arrays, object maps and strings cannot be registered.
+    /// Panics if the type is [`String`], [`ImmutableString`][crate::ImmutableString] or `&str`.
+    /// Indexers for strings cannot be registered.
     ///
     /// # Example
     ///
@@ -723,6 +854,193 @@ impl Engine {
         self.register_indexer_get(getter)
             .register_indexer_set(setter)
     }
+    /// Register a two-dimensional index getter for a custom type with the [`Engine`].
+    ///
+    /// The function signature must start with `&mut self` and not `&self`.
+    ///
+    /// The indexer is called with both indices, as in `a[x, y]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type is [`String`], [`ImmutableString`][crate::ImmutableString] or `&str`.
+    /// Indexers for strings cannot be registered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[derive(Clone)]
+    /// struct TestGrid {
+    ///     cells: Vec<Vec<i64>>
+    /// }
+    ///
+    /// impl TestGrid {
+    ///     fn new() -> Self { Self { cells: vec![vec![1, 2, 3], vec![4, 5, 6]] } }
+    ///     // Even a getter must start with `&mut self` and not `&self`.
+    ///     fn get_cell(&mut self, x: i64, y: i64) -> i64 { self.cells[y as usize][x as usize] }
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, RegisterFn};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// // Register the custom type.
+    /// # #[cfg(not(feature = "no_object"))]
+    /// engine.register_type::<TestGrid>();
+    ///
+    /// engine.register_fn("new_grid", TestGrid::new);
+    ///
+    /// // Register an indexer.
+    /// engine.register_indexer_get2(TestGrid::get_cell);
+    ///
+    /// assert_eq!(engine.eval::<i64>("let g = new_grid(); g[2, 1]")?, 6);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_index"))]
+    #[inline(always)]
+    pub fn register_indexer_get2<
+        T: Variant + Clone,
+        X1: Variant + Clone,
+        X2: Variant + Clone,
+        U: Variant + Clone,
+    >(
+        &mut self,
+        get_fn: impl Fn(&mut T, X1, X2) -> U + SendSync + 'static,
+    ) -> &mut Self {
+        if TypeId::of::<T>() == TypeId::of::<String>()
+            || TypeId::of::<T>() == TypeId::of::<&str>()
+            || TypeId::of::<T>() == TypeId::of::<crate::ImmutableString>()
+        {
+            panic!("Cannot register indexer for strings.");
+        }
+
+        use crate::{engine::FN_IDX_GET, RegisterFn};
+        self.register_fn(FN_IDX_GET, get_fn)
+    }
+    /// Register a two-dimensional index setter for a custom type with the [`Engine`].
+    ///
+    /// The indexer is called with both indices, as in `a[x, y] = value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type is [`String`], [`ImmutableString`][crate::ImmutableString] or `&str`.
+    /// Indexers for strings cannot be registered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[derive(Clone)]
+    /// struct TestGrid {
+    ///     cells: Vec<Vec<i64>>
+    /// }
+    ///
+    /// impl TestGrid {
+    ///     fn new() -> Self { Self { cells: vec![vec![1, 2, 3], vec![4, 5, 6]] } }
+    ///     fn set_cell(&mut self, x: i64, y: i64, value: i64) {
+    ///         self.cells[y as usize][x as usize] = value;
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, RegisterFn};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// // Register the custom type.
+    /// # #[cfg(not(feature = "no_object"))]
+    /// engine.register_type::<TestGrid>();
+    ///
+    /// engine.register_fn("new_grid", TestGrid::new);
+    ///
+    /// // Register an indexer.
+    /// engine.register_indexer_set2(TestGrid::set_cell);
+    ///
+    /// assert_eq!(
+    ///     engine.eval::<TestGrid>("let g = new_grid(); g[2, 1] = 42; g")?.cells[1][2],
+    ///     42
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_index"))]
+    #[inline(always)]
+    pub fn register_indexer_set2<
+        T: Variant + Clone,
+        X1: Variant + Clone,
+        X2: Variant + Clone,
+        U: Variant + Clone,
+    >(
+        &mut self,
+        set_fn: impl Fn(&mut T, X1, X2, U) + SendSync + 'static,
+    ) -> &mut Self {
+        if TypeId::of::<T>() == TypeId::of::<String>()
+            || TypeId::of::<T>() == TypeId::of::<&str>()
+            || TypeId::of::<T>() == TypeId::of::<crate::ImmutableString>()
+        {
+            panic!("Cannot register indexer for strings.");
+        }
+
+        use crate::{engine::FN_IDX_SET, RegisterFn};
+        self.register_fn(FN_IDX_SET, set_fn)
+    }
+    /// Short-hand for register both two-dimensional index getter and setter functions for a custom type with the [`Engine`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type is [`String`], [`ImmutableString`][crate::ImmutableString] or `&str`.
+    /// Indexers for strings cannot be registered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[derive(Clone)]
+    /// struct TestGrid {
+    ///     cells: Vec<Vec<i64>>
+    /// }
+    ///
+    /// impl TestGrid {
+    ///     fn new() -> Self { Self { cells: vec![vec![1, 2, 3], vec![4, 5, 6]] } }
+    ///     // Even a getter must start with `&mut self` and not `&self`.
+    ///     fn get_cell(&mut self, x: i64, y: i64) -> i64 { self.cells[y as usize][x as usize] }
+    ///     fn set_cell(&mut self, x: i64, y: i64, value: i64) {
+    ///         self.cells[y as usize][x as usize] = value;
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, RegisterFn};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// // Register the custom type.
+    /// # #[cfg(not(feature = "no_object"))]
+    /// engine.register_type::<TestGrid>();
+    ///
+    /// engine.register_fn("new_grid", TestGrid::new);
+    ///
+    /// // Register an indexer.
+    /// engine.register_indexer_get_set2(TestGrid::get_cell, TestGrid::set_cell);
+    ///
+    /// assert_eq!(engine.eval::<i64>("let g = new_grid(); g[2, 1] += 42; g[2, 1]")?, 48);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_index"))]
+    #[inline(always)]
+    pub fn register_indexer_get_set2<
+        T: Variant + Clone,
+        X1: Variant + Clone,
+        X2: Variant + Clone,
+        U: Variant + Clone,
+    >(
+        &mut self,
+        getter: impl Fn(&mut T, X1, X2) -> U + SendSync + 'static,
+        setter: impl Fn(&mut T, X1, X2, U) + SendSync + 'static,
+    ) -> &mut Self {
+        self.register_indexer_get2(getter)
+            .register_indexer_set2(setter)
+    }
     /// Register a shared [`Module`] into the global namespace of [`Engine`].
     ///
     /// All functions and type iterators are automatically available to scripts without namespace
@@ -738,6 +1056,43 @@ impl Engine {
         self.global_modules.insert(0, module);
         self
     }
+    /// Register a shared [`Module`] whose variables are available to all scripts, without
+    /// namespace qualifications, as read-only global constants.
+    ///
+    /// Like [`register_global_module`][Engine::register_global_module], all functions and type
+    /// iterators in the module are also available to scripts without namespace qualifications.
+    ///
+    /// A global constant is only searched when no variable with the same name is in scope, so it
+    /// can be shadowed by a variable declared via `let`.  Assigning to a global constant is an
+    /// [`ErrorAssignmentToConstant`][EvalAltResult::ErrorAssignmentToConstant].
+    ///
+    /// When searching for constants, modules registered later are preferred.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Module};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// let mut module = Module::new();
+    /// module.set_var("MAX_PLAYERS", 4_i64);
+    ///
+    /// engine.register_static_constants(module.into());
+    ///
+    /// assert_eq!(engine.eval::<i64>("MAX_PLAYERS * 10")?, 40);
+    /// assert_eq!(engine.eval::<i64>("let MAX_PLAYERS = 2; MAX_PLAYERS")?, 2);
+    /// assert!(engine.eval::<()>("MAX_PLAYERS = 8").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn register_static_constants(&mut self, module: Shared<Module>) -> &mut Self {
+        // Insert the module into the front
+        self.global_constants.insert(0, module.clone());
+        self.register_global_module(module)
+    }
     /// Register a shared [`Module`] into the global namespace of [`Engine`].
     ///
     /// ## Deprecated
@@ -962,17 +1317,146 @@ impl Engine {
     ) -> Result<AST, ParseError> {
         self.compile_with_scope_and_optimization_level(scope, scripts, self.optimization_level)
     }
-    /// Join a list of strings and compile into an [`AST`] using own scope at a specific optimization level.
+    /// Compile a string into an [`AST`], adding the script-defined functions in it to an
+    /// existing [`AST`] which accumulates the functions across many scripts.
+    ///
+    /// The returned [`AST`] contains only the statements of the new script, but can call all
+    /// the functions accumulated so far.  This is useful for a REPL, where functions defined
+    /// on one line must still be available on the next.
+    ///
+    /// New functions overwrite accumulated functions with the same name and number of parameters.
+    /// The statements of the accumulating [`AST`] are left untouched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::{Engine, Scope, AST};
+    ///
+    /// let engine = Engine::new();
+    /// let mut scope = Scope::new();
+    ///
+    /// // Accumulates the functions defined so far
+    /// let mut lib = AST::default();
+    ///
+    /// let ast = engine.compile_into(&mut lib, "fn f(x) { x * 2 }")?;
+    /// engine.consume_ast_with_scope(&mut scope, &ast)?;
+    ///
+    /// // 'f' is still available
+    /// let ast = engine.compile_into(&mut lib, "let y = f(21); y")?;
+    /// assert_eq!(engine.eval_ast_with_scope::<i64>(&mut scope, &ast)?, 42);
+    ///
+    /// // Redefine 'f'
+    /// engine.compile_into(&mut lib, "fn f(x) { x + 1 }")?;
+    ///
+    /// let ast = engine.compile_into(&mut lib, "f(y)")?;
+    /// assert_eq!(engine.eval_ast_with_scope::<i64>(&mut scope, &ast)?, 43);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
     #[inline(always)]
-    pub(crate) fn compile_with_scope_and_optimization_level(
-        &self,
-        scope: &Scope,
-        scripts: &[&str],
-        optimization_level: OptimizationLevel,
+    pub fn compile_into(&self, ast: &mut AST, script: &str) -> Result<AST, ParseError> {
+        self.compile_into_with_scope(&Default::default(), ast, script)
+    }
+    /// Compile a string into an [`AST`] using own scope, adding the script-defined functions in it
+    /// to an existing [`AST`] which accumulates the functions across many scripts.
+    ///
+    /// See [`compile_into`][Engine::compile_into] for details.
+    ///
+    /// The scope is useful for passing constants into the script for optimization
+    /// when using [`OptimizationLevel::Full`].
+    #[inline]
+    pub fn compile_into_with_scope(
+        &self,
+        scope: &Scope,
+        ast: &mut AST,
+        script: &str,
+    ) -> Result<AST, ParseError> {
+        let mut new_ast = self.compile_with_scope(scope, script)?;
+        ast.absorb_functions(&mut new_ast);
+        Ok(new_ast)
+    }
+    /// Compile a string into an [`AST`] that can use `this` at global level, to be evaluated
+    /// via [`eval_ast_with_this`][Engine::eval_ast_with_this].
+    ///
+    /// Normally, `this` can only be used in functions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_object"))]
+    /// # {
+    /// use rhai::{Dynamic, Engine, Map};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile_with_this("this.hp -= 10; this.hp")?;
+    ///
+    /// let mut entity = Map::new();
+    /// entity.insert("hp".into(), Dynamic::from(100_i64));
+    /// let mut entity: Dynamic = entity.into();
+    ///
+    /// assert_eq!(engine.eval_ast_with_this::<i64>(&mut entity, &ast)?, 90);
+    /// assert_eq!(engine.eval_ast_with_this::<i64>(&mut entity, &ast)?, 80);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn compile_with_this(&self, script: &str) -> Result<AST, ParseError> {
+        let scripts = [script];
+        let hash = calc_hash_for_scripts(&scripts);
+        let stream = self.lex(&scripts);
+        let options = EvalOptions {
+            bind_this: true,
+            ..Default::default()
+        };
+        let mut ast = self.parse_with_options(
+            hash,
+            &mut stream.peekable(),
+            &Default::default(),
+            self.optimization_level,
+            &options,
+        )?;
+        if self.retain_source {
+            ast.set_source_text(Some(script));
+        }
+        Ok(ast)
+    }
+    /// Join a list of strings and compile into an [`AST`] using own scope at a specific optimization level.
+    #[inline(always)]
+    pub(crate) fn compile_with_scope_and_optimization_level(
+        &self,
+        scope: &Scope,
+        scripts: &[&str],
+        optimization_level: OptimizationLevel,
+    ) -> Result<AST, ParseError> {
+        if self.compilation_cache.is_some() {
+            self.compile_shared(scope, scripts, optimization_level)
+                .map(|ast| (*ast).clone())
+        } else {
+            self.compile_uncached(scope, scripts, optimization_level)
+        }
+    }
+    /// Join a list of strings and compile into an [`AST`] without using the compilation cache.
+    #[inline(always)]
+    pub(crate) fn compile_uncached(
+        &self,
+        scope: &Scope,
+        scripts: &[&str],
+        optimization_level: OptimizationLevel,
     ) -> Result<AST, ParseError> {
         let hash = calc_hash_for_scripts(scripts);
         let stream = self.lex(scripts);
-        self.parse(hash, &mut stream.peekable(), scope, optimization_level)
+        let mut ast = self.parse(hash, &mut stream.peekable(), scope, optimization_level)?;
+        if self.retain_source {
+            ast.set_source_text(Some(scripts.concat()));
+        }
+        Ok(ast)
     }
     /// Read the contents of a file into a string.
     #[cfg(not(feature = "no_std"))]
@@ -1148,6 +1632,7 @@ impl Engine {
             &mut stream.peekable(),
             &scope,
             OptimizationLevel::None,
+            &Default::default(),
         )?;
 
         // Handle null - map to ()
@@ -1187,6 +1672,12 @@ impl Engine {
     /// The scope is useful for passing constants into the script for optimization
     /// when using [`OptimizationLevel::Full`].
     ///
+    /// Only the _constants_ in the scope are used, and only during optimization.
+    /// Variables are always looked up by name when the [`AST`] is evaluated, so the same [`AST`]
+    /// can be evaluated with any scope, regardless of the order of its variables or of the
+    /// other variables it contains.  A variable missing from that scope fails evaluation with
+    /// [`EvalAltResult::ErrorVariableNotFound`][crate::EvalAltResult::ErrorVariableNotFound].
+    ///
     /// # Example
     ///
     /// ```
@@ -1231,7 +1722,44 @@ impl Engine {
         let stream = self.lex(&scripts);
 
         let mut peekable = stream.peekable();
-        self.parse_global_expr(hash, &mut peekable, scope, self.optimization_level)
+        let mut ast = self.parse_global_expr(
+            hash,
+            &mut peekable,
+            scope,
+            self.optimization_level,
+            &Default::default(),
+        )?;
+        if self.retain_source {
+            ast.set_source_text(Some(script));
+        }
+        Ok(ast)
+    }
+    /// Compile a string containing an expression into an [`AST`] using own scope,
+    /// with [options][EvalOptions] allowing constructs that are otherwise rejected in expressions.
+    ///
+    /// See [`eval_expression_with_options`][Engine::eval_expression_with_options] for details.
+    #[inline]
+    pub fn compile_expression_with_options(
+        &self,
+        scope: &Scope,
+        script: &str,
+        options: &EvalOptions,
+    ) -> Result<AST, ParseError> {
+        let scripts = [script];
+        let hash = calc_hash_for_scripts(&scripts);
+        let stream = self.lex(&scripts);
+
+        let mut ast = self.parse_global_expr(
+            hash,
+            &mut stream.peekable(),
+            scope,
+            self.optimization_level,
+            options,
+        )?;
+        if self.retain_source {
+            ast.set_source_text(Some(script));
+        }
+        Ok(ast)
     }
     /// Evaluate a script file.
     ///
@@ -1332,12 +1860,93 @@ impl Engine {
         scope: &mut Scope,
         script: &str,
     ) -> Result<T, Box<EvalAltResult>> {
-        let ast = self.compile_with_scope_and_optimization_level(
+        let ast = self.compile_shared(scope, &[script], self.optimization_level)?;
+        self.eval_ast_with_scope(scope, &ast)
+    }
+    /// Evaluate a string with own scope, overriding [`Engine`] settings for this evaluation only.
+    ///
+    /// The [`Engine`] itself is not modified.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "unchecked"))]
+    /// # {
+    /// use rhai::{Engine, EvalAltResult, EvalOptions, Scope};
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.set_max_operations(100);
+    ///
+    /// // Trusted scripts may run for longer
+    /// let mut options = EvalOptions::new();
+    /// options.set_max_operations(0);
+    ///
+    /// let script = "let x = 0; for i in range(0, 1000) { x += i; } x";
+    ///
+    /// assert_eq!(engine.eval_with_options::<i64>(&mut Scope::new(), script, &options)?, 499500);
+    ///
+    /// assert!(matches!(
+    ///     *engine.eval::<i64>(script).expect_err("should error"),
+    ///     EvalAltResult::ErrorTooManyOperations(_)
+    /// ));
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn eval_with_options<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        script: &str,
+        options: &EvalOptions,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let scripts = [script];
+        let hash = calc_hash_for_scripts(&scripts);
+        let stream = self.lex(&scripts);
+        let ast = self.parse_with_options(
+            hash,
+            &mut stream.peekable(),
             scope,
-            &[script],
             self.optimization_level,
+            options,
         )?;
-        self.eval_ast_with_scope(scope, &ast)
+        self.eval_ast_with_options(scope, &ast, options)
+    }
+    /// Evaluate a string, with `this` bound at global level to a value.
+    ///
+    /// Normally, `this` can only be used in functions.
+    /// Changes made to `this` by the script are made to the value itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_object"))]
+    /// # {
+    /// use rhai::{Dynamic, Engine, Map};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let mut entity = Map::new();
+    /// entity.insert("hp".into(), Dynamic::from(100_i64));
+    /// let mut entity: Dynamic = entity.into();
+    ///
+    /// engine.eval_with_this::<()>(&mut entity, "let damage = 42; this.hp -= damage;")?;
+    ///
+    /// assert_eq!(entity.cast::<Map>()["hp"].as_int().unwrap(), 58);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn eval_with_this<T: Variant + Clone>(
+        &self,
+        this: &mut Dynamic,
+        script: &str,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let ast = self.compile_with_this(script)?;
+        self.eval_ast_with_this(this, &ast)
     }
     /// Evaluate a string containing an expression.
     ///
@@ -1389,11 +1998,75 @@ impl Engine {
         let stream = self.lex(&scripts);
 
         // No need to optimize a lone expression
-        let ast =
-            self.parse_global_expr(hash, &mut stream.peekable(), scope, OptimizationLevel::None)?;
+        let ast = self.parse_global_expr(
+            hash,
+            &mut stream.peekable(),
+            scope,
+            OptimizationLevel::None,
+            &Default::default(),
+        )?;
 
         self.eval_ast_with_scope(scope, &ast)
     }
+    /// Evaluate a string containing an expression with own scope, with [options][EvalOptions]
+    /// allowing constructs that are otherwise rejected in expressions, and overriding [`Engine`]
+    /// settings for this evaluation only.
+    ///
+    /// By default, only pure expressions are allowed.  `if` expressions, `switch` expressions and
+    /// statement blocks can be enabled individually via
+    /// [`EvalOptions::set_allow_if_expression`], [`EvalOptions::set_allow_switch_expression`]
+    /// and [`EvalOptions::set_allow_statement_expression`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, EvalOptions, Scope};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let mut scope = Scope::new();
+    /// scope.push("x", 42_i64);
+    ///
+    /// let script = "if x > 0 { x } else { -x }";
+    ///
+    /// // If-expressions are not allowed by default
+    /// assert!(engine.eval_expression_with_scope::<i64>(&mut scope, script).is_err());
+    ///
+    /// let mut options = EvalOptions::new();
+    /// options.set_allow_if_expression(true);
+    ///
+    /// assert_eq!(engine.eval_expression_with_options::<i64>(&mut scope, script, &options)?, 42);
+    ///
+    /// // Statements are still not allowed
+    /// assert!(engine
+    ///     .eval_expression_with_options::<i64>(&mut scope, "if x > 0 { let y = x; y } else { 0 }", &options)
+    ///     .is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn eval_expression_with_options<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        script: &str,
+        options: &EvalOptions,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let scripts = [script];
+        let hash = calc_hash_for_scripts(&scripts);
+        let stream = self.lex(&scripts);
+
+        // No need to optimize a lone expression
+        let ast = self.parse_global_expr(
+            hash,
+            &mut stream.peekable(),
+            scope,
+            OptimizationLevel::None,
+            options,
+        )?;
+
+        self.eval_ast_with_options(scope, &ast, options)
+    }
     /// Evaluate an [`AST`].
     ///
     /// # Example
@@ -1450,10 +2123,69 @@ impl Engine {
         &self,
         scope: &mut Scope,
         ast: &AST,
+    ) -> Result<T, Box<EvalAltResult>> {
+        self.eval_ast_with_options(scope, ast, &Default::default())
+    }
+    /// Evaluate an [`AST`] with own scope, overriding [`Engine`] settings for this evaluation only.
+    ///
+    /// The [`Engine`] itself is not modified.
+    #[inline]
+    pub fn eval_ast_with_options<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        ast: &AST,
+        options: &EvalOptions,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let mods = &mut (&self.global_sub_modules).into();
+        self.eval_ast_with_imports(scope, mods, ast, &mut None, options)
+    }
+    /// Evaluate an [`AST`] compiled via [`compile_with_this`][Engine::compile_with_this],
+    /// with `this` bound at global level to a value.
+    ///
+    /// Changes made to `this` by the script are made to the value itself.
+    ///
+    /// Using `this` outside of functions in an [`AST`] evaluated without a bound value raises
+    /// [`EvalAltResult::ErrorUnboundThis`].
+    #[inline]
+    pub fn eval_ast_with_this<T: Variant + Clone>(
+        &self,
+        this: &mut Dynamic,
+        ast: &AST,
     ) -> Result<T, Box<EvalAltResult>> {
         let mods = &mut (&self.global_sub_modules).into();
+        self.eval_ast_with_imports(
+            &mut Default::default(),
+            mods,
+            ast,
+            &mut Some(this),
+            &Default::default(),
+        )
+    }
+    /// Evaluate an [`AST`] with own scope and a prepared list of [imports][Imports].
+    #[inline]
+    fn eval_ast_with_imports<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        ast: &AST,
+        this_ptr: &mut Option<&mut Dynamic>,
+        options: &EvalOptions,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let state = &mut State {
+            source: ast.clone_source(),
+            options: options.clone(),
+            ..Default::default()
+        };
 
-        let result = self.eval_ast_with_scope_raw(scope, mods, ast, 0)?;
+        let result = self.eval_statements_raw(
+            scope,
+            mods,
+            state,
+            ast.statements(),
+            &[ast.lib()],
+            this_ptr,
+            0,
+        )?;
 
         let typ = self.map_type_name(result.type_name());
 
@@ -1466,56 +2198,373 @@ impl Engine {
             .into()
         });
     }
-    /// Evaluate an [`AST`] with own scope.
-    #[inline(always)]
-    pub(crate) fn eval_ast_with_scope_raw<'a>(
-        &self,
-        scope: &mut Scope,
-        mods: &mut Imports,
-        ast: &'a AST,
-        level: usize,
-    ) -> Result<Dynamic, Box<EvalAltResult>> {
-        let state = &mut State {
-            source: ast.clone_source(),
-            ..Default::default()
-        };
-        self.eval_statements_raw(scope, mods, state, ast.statements(), &[ast.lib()], level)
-    }
-    /// Evaluate a file, but throw away the result and only return error (if any).
-    /// Useful for when you don't need the result, but still need to keep track of possible errors.
+    /// Compile a script file into an [`AST`], for evaluation via
+    /// [`eval_ast_with_scope_async`][Engine::eval_ast_with_scope_async].
+    ///
+    /// Compiling a script does not resolve the modules it imports, so this never calls the
+    /// [module resolver][crate::ModuleResolver].  Notice that the script file itself is read
+    /// from disk in a blocking manner, just like [`compile_file`][Engine::compile_file].
+    ///
+    /// When the [`AST`] is evaluated, only modules imported via constant paths
+    /// (e.g. `import "hello" as h;`) are resolved asynchronously.
+    ///
+    /// Exported under the `async` feature.
     #[cfg(not(feature = "no_std"))]
     #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(feature = "async")]
     #[inline(always)]
-    pub fn consume_file(
+    pub async fn compile_file_async(
         &self,
         path: crate::stdlib::path::PathBuf,
-    ) -> Result<(), Box<EvalAltResult>> {
-        Self::read_file(path).and_then(|contents| self.consume(&contents))
+    ) -> Result<AST, Box<EvalAltResult>> {
+        self.compile_file(path)
     }
-    /// Evaluate a file with own scope, but throw away the result and only return error (if any).
-    /// Useful for when you don't need the result, but still need to keep track of possible errors.
-    #[cfg(not(feature = "no_std"))]
-    #[cfg(not(target_arch = "wasm32"))]
+    /// Evaluate a string, resolving all imported modules asynchronously.
+    ///
+    /// Modules imported via constant paths (e.g. `import "hello" as h;`) are resolved up-front via
+    /// [`ModuleResolver::resolve_async`][crate::ModuleResolver::resolve_async] and awaited,
+    /// before the script is evaluated.  Modules imported via paths computed at run-time fall back
+    /// to the blocking [`ModuleResolver::resolve`][crate::ModuleResolver::resolve].
+    ///
+    /// Notice that errors in resolving a module are returned even when the `import` statement is
+    /// never run.  Evaluation of the script itself is not asynchronous.
+    ///
+    /// Not available under `no_module`.  Exported under the `async` feature.
+    #[cfg(not(feature = "no_module"))]
+    #[cfg(feature = "async")]
     #[inline(always)]
-    pub fn consume_file_with_scope(
+    pub async fn eval_async<T: Variant + Clone>(
         &self,
-        scope: &mut Scope,
-        path: crate::stdlib::path::PathBuf,
-    ) -> Result<(), Box<EvalAltResult>> {
-        Self::read_file(path).and_then(|contents| self.consume_with_scope(scope, &contents))
-    }
-    /// Evaluate a string, but throw away the result and only return error (if any).
-    /// Useful for when you don't need the result, but still need to keep track of possible errors.
-    #[inline(always)]
-    pub fn consume(&self, script: &str) -> Result<(), Box<EvalAltResult>> {
-        self.consume_with_scope(&mut Default::default(), script)
+        script: &str,
+    ) -> Result<T, Box<EvalAltResult>> {
+        self.eval_with_scope_async(&mut Default::default(), script)
+            .await
     }
-    /// Evaluate a string with own scope, but throw away the result and only return error (if any).
-    /// Useful for when you don't need the result, but still need to keep track of possible errors.
+    /// Evaluate a string with own scope, resolving all imported modules asynchronously.
+    ///
+    /// See [`eval_async`][Engine::eval_async] for details.
+    ///
+    /// Not available under `no_module`.  Exported under the `async` feature.
+    #[cfg(not(feature = "no_module"))]
+    #[cfg(feature = "async")]
     #[inline]
-    pub fn consume_with_scope(
+    pub async fn eval_with_scope_async<T: Variant + Clone>(
         &self,
-        scope: &mut Scope,
+        scope: &mut Scope<'_>,
+        script: &str,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let ast = self.compile_shared(scope, &[script], self.optimization_level)?;
+        self.eval_ast_with_scope_async(scope, &ast).await
+    }
+    /// Evaluate an [`AST`] with own scope, resolving all imported modules asynchronously.
+    ///
+    /// See [`eval_async`][Engine::eval_async] for details.
+    ///
+    /// Not available under `no_module`.  Exported under the `async` feature.
+    #[cfg(not(feature = "no_module"))]
+    #[cfg(feature = "async")]
+    pub async fn eval_ast_with_scope_async<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope<'_>,
+        ast: &AST,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let mut mods: Imports = (&self.global_sub_modules).into();
+
+        for (path, pos) in Self::collect_import_paths(ast) {
+            if mods.get_resolved(&path).is_some() {
+                continue;
+            }
+            // Denied modules are reported when the `import` statement is run
+            if let Some(filter) = self.module_filter.as_ref() {
+                if !filter(&path) {
+                    continue;
+                }
+            }
+
+            let module = self.module_resolver.resolve_async(self, &path, pos).await?;
+
+            let module = if !module.is_indexed() {
+                // Index the module (making a clone copy if necessary) if it is not indexed
+                let mut module = crate::fn_native::shared_take_or_clone(module);
+                module.build_index();
+                module.into()
+            } else {
+                module
+            };
+
+            mods.set_resolved(path, module);
+        }
+
+        self.eval_ast_with_imports(scope, &mut mods, ast, &mut None, &Default::default())
+    }
+    /// Collect all the constant module paths imported by an [`AST`], including those imported
+    /// within functions.
+    #[cfg(not(feature = "no_module"))]
+    #[cfg(feature = "async")]
+    fn collect_import_paths(ast: &AST) -> Vec<(ImmutableString, Position)> {
+        use crate::ast::{Expr, Stmt};
+        use crate::optimize::{walk_stmt, Node};
+
+        let mut statements = ast.statements().to_vec();
+
+        #[cfg(not(feature = "no_function"))]
+        statements.extend(
+            ast.lib()
+                .iter_script_fn()
+                .map(|(_, _, _, _, f)| f.body.clone()),
+        );
+
+        let mut paths = Vec::new();
+
+        statements.iter_mut().for_each(|stmt| {
+            walk_stmt(stmt, &mut |node| {
+                if let Node::Stmt(Stmt::Import(Expr::StringConstant(path, pos), _, _)) = node {
+                    paths.push((path.clone(), *pos));
+                }
+            })
+        });
+
+        paths
+    }
+    /// Evaluate a string with own scope, capturing all the output of `print` and `debug`.
+    ///
+    /// The output is recorded in a buffer local to this evaluation, so evaluations running at the
+    /// same time (e.g. under the `sync` feature) never mix up their output.
+    ///
+    /// Callbacks registered via [`on_print`][Engine::on_print] and [`on_debug`][Engine::on_debug]
+    /// are still called, _after_ each piece of output is recorded.  Notice that the default
+    /// callbacks print to stdout; register callbacks doing nothing to silence them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Scope, INT};
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.on_print(|_| {});
+    ///
+    /// let mut scope = Scope::new();
+    /// scope.push("x", 40 as INT);
+    ///
+    /// let (result, output) =
+    ///     engine.eval_with_captured_output(&mut scope, "print(x); print(x + 1); x + 2")?;
+    ///
+    /// assert_eq!(result.cast::<INT>(), 42);
+    ///
+    /// let text: Vec<_> = output.iter().map(|entry| entry.text.as_str()).collect();
+    /// assert_eq!(text, ["40", "41"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn eval_with_captured_output(
+        &self,
+        scope: &mut Scope,
+        script: &str,
+    ) -> Result<(Dynamic, CapturedOutput), Box<EvalAltResult>> {
+        let ast = self.compile_shared(scope, &[script], self.optimization_level)?;
+        self.eval_ast_with_captured_output(scope, &ast)
+    }
+    /// Evaluate an [`AST`] with own scope, capturing all the output of `print` and `debug`.
+    ///
+    /// See [`eval_with_captured_output`][Engine::eval_with_captured_output] for details.
+    #[inline]
+    pub fn eval_ast_with_captured_output(
+        &self,
+        scope: &mut Scope,
+        ast: &AST,
+    ) -> Result<(Dynamic, CapturedOutput), Box<EvalAltResult>> {
+        let mods = &mut (&self.global_sub_modules).into();
+        let state = &mut State {
+            source: ast.clone_source(),
+            output: Some(Default::default()),
+            ..Default::default()
+        };
+
+        let result = self.eval_statements_raw(
+            scope,
+            mods,
+            state,
+            ast.statements(),
+            &[ast.lib()],
+            &mut None,
+            0,
+        )?;
+
+        Ok((result, state.output.take().unwrap_or_default()))
+    }
+    /// Evaluate a string with own scope, recording the functions called in a [`CallAudit`].
+    ///
+    /// The calls are added to those already in the [`CallAudit`], and are recorded even when the
+    /// evaluation fails.  Without a [`CallAudit`], no calls are recorded.
+    ///
+    /// See [`CallAudit`] for details and an example.
+    #[inline]
+    pub fn eval_with_call_audit<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        script: &str,
+        audit: &mut CallAudit,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let ast = self.compile_shared(scope, &[script], self.optimization_level)?;
+        self.eval_ast_with_call_audit(scope, &ast, audit)
+    }
+    /// Evaluate an [`AST`] with own scope, recording the functions called in a [`CallAudit`].
+    ///
+    /// See [`eval_with_call_audit`][Engine::eval_with_call_audit] for details.
+    #[inline]
+    pub fn eval_ast_with_call_audit<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        ast: &AST,
+        audit: &mut CallAudit,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let mods = &mut (&self.global_sub_modules).into();
+        let state = &mut State {
+            source: ast.clone_source(),
+            audit: Some(mem::take(audit)),
+            ..Default::default()
+        };
+
+        let result = self.eval_statements_raw(
+            scope,
+            mods,
+            state,
+            ast.statements(),
+            &[ast.lib()],
+            &mut None,
+            0,
+        );
+
+        *audit = state.audit.take().unwrap_or_default();
+
+        let result = result?;
+        let typ = self.map_type_name(result.type_name());
+
+        result.try_cast::<T>().ok_or_else(|| {
+            EvalAltResult::ErrorMismatchOutputType(
+                self.map_type_name(type_name::<T>()).into(),
+                typ.into(),
+                Position::NONE,
+            )
+            .into()
+        })
+    }
+    /// Evaluate a string with own scope, recording the work done in an [`EvalMetrics`].
+    ///
+    /// The metrics are recorded even when the evaluation fails, but not when the script fails to
+    /// parse.
+    ///
+    /// See [`EvalMetrics`] for details and an example.
+    #[inline]
+    pub fn eval_with_metrics<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        script: &str,
+        metrics: &mut EvalMetrics,
+    ) -> Result<T, Box<EvalAltResult>> {
+        #[cfg(not(feature = "no_std"))]
+        let start = Instant::now();
+
+        let ast = self.compile_shared(scope, &[script], self.optimization_level)?;
+
+        #[cfg(not(feature = "no_std"))]
+        let parse_time = Some(start.elapsed());
+        #[cfg(feature = "no_std")]
+        let parse_time = None;
+
+        let result = self.eval_ast_with_metrics(scope, &ast, metrics);
+        metrics.parse_time = parse_time;
+        result
+    }
+    /// Evaluate an [`AST`] with own scope, recording the work done in an [`EvalMetrics`].
+    ///
+    /// See [`eval_with_metrics`][Engine::eval_with_metrics] for details.
+    #[inline]
+    pub fn eval_ast_with_metrics<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        ast: &AST,
+        metrics: &mut EvalMetrics,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let mods = &mut (&self.global_sub_modules).into();
+        let state = &mut State {
+            source: ast.clone_source(),
+            ..Default::default()
+        };
+
+        #[cfg(not(feature = "no_std"))]
+        let start = Instant::now();
+
+        let result = self.eval_statements_raw(
+            scope,
+            mods,
+            state,
+            ast.statements(),
+            &[ast.lib()],
+            &mut None,
+            0,
+        );
+
+        *metrics = EvalMetrics {
+            operations: state.operations,
+            max_call_depth: state.max_call_level,
+            modules: state.modules,
+            parse_time: None,
+            #[cfg(not(feature = "no_std"))]
+            eval_time: Some(start.elapsed()),
+            #[cfg(feature = "no_std")]
+            eval_time: None,
+        };
+
+        let result = result?;
+        let typ = self.map_type_name(result.type_name());
+
+        result.try_cast::<T>().ok_or_else(|| {
+            EvalAltResult::ErrorMismatchOutputType(
+                self.map_type_name(type_name::<T>()).into(),
+                typ.into(),
+                Position::NONE,
+            )
+            .into()
+        })
+    }
+    /// Evaluate a file, but throw away the result and only return error (if any).
+    /// Useful for when you don't need the result, but still need to keep track of possible errors.
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(target_arch = "wasm32"))]
+    #[inline(always)]
+    pub fn consume_file(
+        &self,
+        path: crate::stdlib::path::PathBuf,
+    ) -> Result<(), Box<EvalAltResult>> {
+        Self::read_file(path).and_then(|contents| self.consume(&contents))
+    }
+    /// Evaluate a file with own scope, but throw away the result and only return error (if any).
+    /// Useful for when you don't need the result, but still need to keep track of possible errors.
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(target_arch = "wasm32"))]
+    #[inline(always)]
+    pub fn consume_file_with_scope(
+        &self,
+        scope: &mut Scope,
+        path: crate::stdlib::path::PathBuf,
+    ) -> Result<(), Box<EvalAltResult>> {
+        Self::read_file(path).and_then(|contents| self.consume_with_scope(scope, &contents))
+    }
+    /// Evaluate a string, but throw away the result and only return error (if any).
+    /// Useful for when you don't need the result, but still need to keep track of possible errors.
+    #[inline(always)]
+    pub fn consume(&self, script: &str) -> Result<(), Box<EvalAltResult>> {
+        self.consume_with_scope(&mut Default::default(), script)
+    }
+    /// Evaluate a string with own scope, but throw away the result and only return error (if any).
+    /// Useful for when you don't need the result, but still need to keep track of possible errors.
+    #[inline]
+    pub fn consume_with_scope(
+        &self,
+        scope: &mut Scope,
         script: &str,
     ) -> Result<(), Box<EvalAltResult>> {
         let scripts = [script];
@@ -1543,7 +2592,15 @@ impl Engine {
             source: ast.clone_source(),
             ..Default::default()
         };
-        self.eval_statements_raw(scope, mods, state, ast.statements(), &[ast.lib()], 0)?;
+        self.eval_statements_raw(
+            scope,
+            mods,
+            state,
+            ast.statements(),
+            &[ast.lib()],
+            &mut None,
+            0,
+        )?;
         Ok(())
     }
     /// Call a script function defined in an [`AST`] with multiple arguments.
@@ -1583,19 +2640,101 @@ impl Engine {
     /// # }
     /// ```
     #[cfg(not(feature = "no_function"))]
-    #[inline]
+    #[inline(always)]
     pub fn call_fn<T: Variant + Clone>(
         &self,
         scope: &mut Scope,
         ast: &AST,
         name: &str,
         args: impl crate::fn_args::FuncArgs,
+    ) -> Result<T, Box<EvalAltResult>> {
+        self.call_fn_with_options(scope, ast, name, args, &Default::default())
+    }
+    /// Call a script function defined in an [`AST`] with multiple arguments,
+    /// overriding [`Engine`] settings for this call only.
+    /// Arguments are passed as a tuple.
+    ///
+    /// The [`Engine`] itself is not modified.
+    #[cfg(not(feature = "no_function"))]
+    #[inline]
+    pub fn call_fn_with_options<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        ast: &AST,
+        name: &str,
+        args: impl crate::fn_args::FuncArgs,
+        options: &EvalOptions,
+    ) -> Result<T, Box<EvalAltResult>> {
+        self.call_fn_typed(scope, ast, name, &mut None, args, options)
+    }
+    /// Call a script function defined in an [`AST`] with multiple arguments,
+    /// binding the `this` pointer to a value.
+    /// Arguments are passed as a tuple.
+    ///
+    /// This mirrors calling the function as a method in scripts (i.e. `value.name(args)`).
+    /// Any change made to `this` by the function is reflected in the value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::{Dynamic, Engine, Scope, INT};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile(r"
+    ///     fn grow(x) { this += x; this * 2 }
+    /// ")?;
+    ///
+    /// let mut value = Dynamic::from(1 as INT);
+    ///
+    /// let result: INT = engine.call_fn_with_this(&mut Scope::new(), &mut value, &ast, "grow", ( 20 as INT, ))?;
+    ///
+    /// assert_eq!(result, 42);
+    /// assert_eq!(value.cast::<INT>(), 21);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub fn call_fn_with_this<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        this_ptr: &mut Dynamic,
+        ast: &AST,
+        name: &str,
+        args: impl crate::fn_args::FuncArgs,
+    ) -> Result<T, Box<EvalAltResult>> {
+        self.call_fn_typed(
+            scope,
+            ast,
+            name,
+            &mut Some(this_ptr),
+            args,
+            &Default::default(),
+        )
+    }
+    /// Call a script function defined in an [`AST`] with multiple arguments,
+    /// and cast the result to the required type.
+    #[cfg(not(feature = "no_function"))]
+    #[inline]
+    fn call_fn_typed<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        ast: &AST,
+        name: &str,
+        this_ptr: &mut Option<&mut Dynamic>,
+        args: impl crate::fn_args::FuncArgs,
+        options: &EvalOptions,
     ) -> Result<T, Box<EvalAltResult>> {
         let mut arg_values = args.into_vec();
         let mut args: crate::StaticVec<_> = arg_values.as_mut().iter_mut().collect();
 
         let result =
-            self.call_fn_dynamic_raw(scope, &[ast.lib()], name, &mut None, args.as_mut())?;
+            self.call_fn_dynamic_raw(scope, &[ast.lib()], name, this_ptr, args.as_mut(), options)?;
 
         let typ = self.map_type_name(result.type_name());
 
@@ -1669,7 +2808,14 @@ impl Engine {
     ) -> Result<Dynamic, Box<EvalAltResult>> {
         let mut args: crate::StaticVec<_> = arg_values.as_mut().iter_mut().collect();
 
-        self.call_fn_dynamic_raw(scope, &[lib.as_ref()], name, &mut this_ptr, args.as_mut())
+        self.call_fn_dynamic_raw(
+            scope,
+            &[lib.as_ref()],
+            name,
+            &mut this_ptr,
+            args.as_mut(),
+            &Default::default(),
+        )
     }
     /// Call a script function defined in an [`AST`] with multiple [`Dynamic`] arguments.
     ///
@@ -1688,13 +2834,26 @@ impl Engine {
         name: &str,
         this_ptr: &mut Option<&mut Dynamic>,
         args: &mut FnCallArgs,
+        options: &EvalOptions,
     ) -> Result<Dynamic, Box<EvalAltResult>> {
         let fn_def = lib
             .iter()
             .find_map(|&m| m.get_script_fn(name, args.len(), true))
+            .or_else(|| self.find_global_script_fn(name, args.len()));
+
+        #[cfg(not(feature = "no_index"))]
+        let fn_def = fn_def.or_else(|| {
+            lib.iter()
+                .find_map(|&m| m.get_variadic_script_fn(name, args.len(), true))
+        });
+
+        let fn_def = fn_def
             .ok_or_else(|| EvalAltResult::ErrorFunctionNotFound(name.into(), Position::NONE))?;
 
-        let mut state = Default::default();
+        let mut state = State {
+            options: options.clone(),
+            ..Default::default()
+        };
         let mut mods = (&self.global_sub_modules).into();
 
         // Check for data race.
@@ -1714,6 +2873,33 @@ impl Engine {
             0,
         )
     }
+    /// Find a public script-defined function in the global modules registered into the [`Engine`],
+    /// or in a static module if the name is qualified with its namespace (e.g. `foo::bar::baz`).
+    #[cfg(not(feature = "no_function"))]
+    fn find_global_script_fn(
+        &self,
+        name: &str,
+        num_params: usize,
+    ) -> Option<&crate::ast::ScriptFnDef> {
+        let separator = crate::token::Token::DoubleColon.syntax();
+        let mut path = name.rsplitn(2, separator.as_ref());
+        let fn_name = path.next()?;
+
+        match path.next() {
+            None => self
+                .global_modules
+                .iter()
+                .find_map(|m| m.get_script_fn(fn_name, num_params, true)),
+            Some(path) => {
+                let mut modules = path.split(separator.as_ref());
+                let root = self.global_sub_modules.get(modules.next()?)?.as_ref();
+
+                modules
+                    .try_fold(root, |m, name| m.get_sub_module(name))?
+                    .get_script_fn(fn_name, num_params, true)
+            }
+        }
+    }
     /// Optimize the [`AST`] with constants defined in an external Scope.
     /// An optimized copy of the [`AST`] is returned while the original [`AST`] is consumed.
     ///
@@ -1747,7 +2933,10 @@ impl Engine {
         let lib = Default::default();
 
         let stmt = crate::stdlib::mem::take(ast.statements_mut());
-        crate::optimize::optimize_into_ast(self, scope, stmt, lib, optimization_level)
+        let mut optimized =
+            crate::optimize::optimize_into_ast(self, scope, stmt, lib, optimization_level);
+        optimized.set_source_text(ast.clone_source_text());
+        optimized
     }
     /// Generate a list of all registered functions.
     ///
@@ -1816,6 +3005,108 @@ impl Engine {
         self.resolve_var = Some(Box::new(callback));
         self
     }
+    /// Provide a callback that will be invoked before each assignment to a variable,
+    /// including op-assignments (e.g. `x += 1`), with the new value of the variable.
+    ///
+    /// Use this together with [`on_var`][Engine::on_var] to provide variables that can be
+    /// both read and written by scripts, without keeping them in a [`Scope`].
+    ///
+    /// ## Return Value of Callback
+    ///
+    /// Return `Ok(true)` if the assignment is handled.  
+    /// Return `Ok(false)` to continue with normal variable assignment.
+    ///
+    /// ## Errors in Callback
+    ///
+    /// Return `Err(...)` if there is an error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, INT};
+    /// use std::sync::{Arc, RwLock};
+    ///
+    /// let health = Arc::new(RwLock::new(100 as INT));
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// // Read the variable from the host.
+    /// let value = health.clone();
+    /// engine.on_var(move |name, _, _| match name {
+    ///     "HEALTH" => Ok(Some((*value.read().unwrap()).into())),
+    ///     _ => Ok(None),
+    /// });
+    ///
+    /// // Write the variable back to the host.
+    /// let value = health.clone();
+    /// engine.on_var_write(move |name, new_value, _| match name {
+    ///     "HEALTH" => {
+    ///         *value.write().unwrap() = new_value.as_int()?;
+    ///         Ok(true)
+    ///     }
+    ///     _ => Ok(false),
+    /// });
+    ///
+    /// engine.consume("HEALTH -= 30; HEALTH *= 2;")?;
+    ///
+    /// assert_eq!(*health.read().unwrap(), 140);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn on_var_write(
+        &mut self,
+        callback: impl Fn(&str, &Dynamic, &EvalContext) -> Result<bool, Box<EvalAltResult>>
+            + SendSync
+            + 'static,
+    ) -> &mut Self {
+        self.write_var = Some(Box::new(callback));
+        self
+    }
+    /// Provide a callback that will be invoked whenever reading a variable implicitly copies an
+    /// array or object map holding more than `threshold` items (counting all nested items), with
+    /// the name of the variable, the number of items and the position of the variable.
+    ///
+    /// Arrays and object maps have value semantics, so assigning one to another variable or
+    /// passing it to a function copies it. Use this to find accidental large copies in scripts.
+    ///
+    /// Method calls (e.g. `x.len()`) take the variable by reference and are never reported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_index"))]
+    /// # {
+    /// use rhai::Engine;
+    /// use std::sync::{Arc, RwLock};
+    ///
+    /// let copies = Arc::new(RwLock::new(Vec::new()));
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// let log = copies.clone();
+    /// engine.on_large_copy(100, move |name, size, _| {
+    ///     log.write().unwrap().push(format!("{}: {}", name, size));
+    /// });
+    ///
+    /// engine.consume("let x = []; pad(x, 1000, 0); let y = x; len(x);")?;
+    ///
+    /// assert_eq!(*copies.read().unwrap(), vec!["x: 1000"]);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn on_large_copy(
+        &mut self,
+        threshold: usize,
+        callback: impl Fn(&str, usize, Position) + SendSync + 'static,
+    ) -> &mut Self {
+        self.large_copy = Some((threshold, Box::new(callback)));
+        self
+    }
     /// Register a callback for script evaluation progress.
     ///
     /// # Example
@@ -1858,6 +3149,61 @@ impl Engine {
         self.progress = Some(Box::new(callback));
         self
     }
+    /// Register a callback to debug scripts, called before running each statement.
+    ///
+    /// The callback receives the evaluation context, with the current [`Scope`] and the names of the
+    /// script-defined functions being called (see [`EvalContext::call_stack`]),
+    /// as well as the [position][Position] of the statement.
+    /// It returns a [`DebuggerCommand`] deciding when it is called next,
+    /// or aborting the evaluation.
+    ///
+    /// The debugger starts stepping into each evaluation.  It is also called for statements in
+    /// script-defined functions and in module scripts that are loaded via `import`; the latter run
+    /// with their own call stack and start stepping anew.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// # use std::sync::RwLock;
+    /// # use std::sync::Arc;
+    /// use rhai::{DebuggerCommand, Engine};
+    ///
+    /// let result = Arc::new(RwLock::new(Vec::new()));
+    /// let logger = result.clone();
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.on_debugger(move |context, pos| {
+    ///     logger.write().unwrap().push(format!(
+    ///         "{} {:?} {}",
+    ///         pos.line().unwrap(),
+    ///         context.call_stack(),
+    ///         context.scope().len()
+    ///     ));
+    ///     DebuggerCommand::StepInto
+    /// });
+    ///
+    /// engine.consume("fn foo(x) {\n  x + 1\n}\nlet x = 41;\nfoo(x);")?;
+    ///
+    /// assert_eq!(
+    ///     *result.read().unwrap(),
+    ///     ["4 [] 0", "5 [] 1", "2 [\"foo\"] 1"]
+    /// );
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn on_debugger(
+        &mut self,
+        callback: impl Fn(&EvalContext, Position) -> DebuggerCommand + SendSync + 'static,
+    ) -> &mut Self {
+        self.debugger = Some(Box::new(callback));
+        self
+    }
     /// Override default action of `print` (print to stdout using [`println!`])
     ///
     /// # Example
@@ -1884,6 +3230,43 @@ impl Engine {
     /// ```
     #[inline(always)]
     pub fn on_print(&mut self, callback: impl Fn(&str) + SendSync + 'static) -> &mut Self {
+        self.print = Box::new(move |s, _, _| callback(s));
+        self
+    }
+    /// Override default action of `print` (print to stdout using [`println!`]) with a callback
+    /// that also receives the source (if any) and the position of the `print` call.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # use std::sync::RwLock;
+    /// # use std::sync::Arc;
+    /// use rhai::Engine;
+    ///
+    /// let result = Arc::new(RwLock::new(String::from("")));
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// // Override action of 'print' function
+    /// let logger = result.clone();
+    /// engine.on_print_with_position(move |s, src, pos| logger.write().unwrap().push_str(
+    ///                     &format!("{} @ {:?} > {}", src.unwrap_or("unknown"), pos, s)
+    ///                ));
+    ///
+    /// let mut ast = engine.compile(r#"let x = "hello"; print(x);"#)?;
+    /// ast.set_source(Some("world"));
+    /// engine.consume_ast(&ast)?;
+    ///
+    /// assert_eq!(*result.read().unwrap(), "world @ 1:18 > hello");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn on_print_with_position(
+        &mut self,
+        callback: impl Fn(&str, Option<&str>, Position) + SendSync + 'static,
+    ) -> &mut Self {
         self.print = Box::new(callback);
         self
     }
@@ -1,8 +1,9 @@
 //! Configuration settings for [`Engine`].
 
+use crate::engine::CustomKeyword;
 use crate::stdlib::{format, num::NonZeroU8, string::String};
 use crate::token::Token;
-use crate::Engine;
+use crate::{Associativity, Engine};
 
 #[cfg(not(feature = "no_module"))]
 use crate::stdlib::boxed::Box;
@@ -163,6 +164,225 @@ impl Engine {
     pub fn max_map_size(&self) -> usize {
         self.limits.max_map_size
     }
+    /// Set the maximum estimated total number of bytes of data (i.e. [strings][crate::ImmutableString],
+    /// [arrays][crate::Array] and [object maps][crate::Map]) created during an evaluation (0 for unlimited).
+    ///
+    /// The estimate only ever grows during an evaluation, even when data is dropped.
+    #[cfg(not(feature = "unchecked"))]
+    #[inline(always)]
+    pub fn set_max_total_data_size(&mut self, max_size: usize) -> &mut Self {
+        self.limits.max_total_data_size = if max_size == usize::MAX { 0 } else { max_size };
+        self
+    }
+    /// The maximum estimated total number of bytes of data created during an evaluation (0 for unlimited).
+    #[cfg(not(feature = "unchecked"))]
+    #[inline(always)]
+    pub fn max_total_data_size(&self) -> usize {
+        self.limits.max_total_data_size
+    }
+    /// Set the maximum number of variables declared at the same time (0 for unlimited).
+    ///
+    /// This is checked both when parsing a script (against the variables declared in the global
+    /// level or within each function) and when running it (against all the variables in the [`Scope`][crate::Scope]).
+    #[cfg(not(feature = "unchecked"))]
+    #[inline(always)]
+    pub fn set_max_variables(&mut self, max_variables: usize) -> &mut Self {
+        self.limits.max_variables = if max_variables == usize::MAX {
+            0
+        } else {
+            max_variables
+        };
+        self
+    }
+    /// The maximum number of variables declared at the same time (0 for unlimited).
+    #[cfg(not(feature = "unchecked"))]
+    #[inline(always)]
+    pub fn max_variables(&self) -> usize {
+        self.limits.max_variables
+    }
+    /// Enable/disable strict module aliases.
+    ///
+    /// When enabled, declaring a variable with the same name as a module alias in scope,
+    /// or importing a module under the name of a variable in scope, is a parse error.
+    ///
+    /// Not available under the `no_module` feature.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn set_strict_module_aliases(&mut self, enable: bool) -> &mut Self {
+        self.strict_module_aliases = enable;
+        self
+    }
+    /// Are strict module aliases enabled?
+    ///
+    /// Not available under the `no_module` feature.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn strict_module_aliases(&self) -> bool {
+        self.strict_module_aliases
+    }
+    /// Enable/disable private-by-default functions in module scripts.
+    ///
+    /// When enabled, a function defined in a script loaded as a [module][crate::Module] is only
+    /// visible to importers when it is defined via `export fn` or its name is listed in an
+    /// `export` statement.  All other functions stay private to the module, but functions in the
+    /// same module can still call each other.
+    ///
+    /// Not available under the `no_module` feature.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn set_module_fn_private_by_default(&mut self, enable: bool) -> &mut Self {
+        self.module_fn_private_by_default = enable;
+        self
+    }
+    /// Are functions in module scripts private unless explicitly exported?
+    ///
+    /// Not available under the `no_module` feature.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn module_fn_private_by_default(&self) -> bool {
+        self.module_fn_private_by_default
+    }
+    /// Enable/disable the `compile_expression` and `compile_check` functions in scripts.
+    ///
+    /// When enabled, scripts can compile an expression (e.g. a user-entered formula) via
+    /// `compile_expression`, and evaluate it repeatedly against an object map of variables
+    /// via its `eval` method, without enabling `eval` on arbitrary scripts.
+    ///
+    /// Scripts can also check a script for syntax errors, without running it, via `compile_check`.
+    ///
+    /// Not available under the `no_object` feature.
+    #[cfg(not(feature = "no_object"))]
+    #[inline(always)]
+    pub fn set_allow_compile_expression(&mut self, enable: bool) -> &mut Self {
+        self.allow_compile_expression = enable;
+        self
+    }
+    /// Are the `compile_expression` and `compile_check` functions enabled in scripts?
+    ///
+    /// Not available under the `no_object` feature.
+    #[cfg(not(feature = "no_object"))]
+    #[inline(always)]
+    pub fn allow_compile_expression(&self) -> bool {
+        self.allow_compile_expression
+    }
+    /// Enable/disable the `render` function in scripts.
+    ///
+    /// `render` evaluates each placeholder of a template as an expression, so it is enabled by
+    /// [`Engine::new`] but disabled by [`Engine::new_raw`].
+    ///
+    /// Not available under the `no_object` feature.
+    #[cfg(not(feature = "no_object"))]
+    #[inline(always)]
+    pub fn set_allow_render(&mut self, enable: bool) -> &mut Self {
+        self.allow_render = enable;
+        self
+    }
+    /// Is the `render` function enabled in scripts?
+    ///
+    /// Not available under the `no_object` feature.
+    #[cfg(not(feature = "no_object"))]
+    #[inline(always)]
+    pub fn allow_render(&self) -> bool {
+        self.allow_render
+    }
+    /// Enable/disable strict variables.
+    ///
+    /// When enabled, declaring a variable with the same name as another variable in the same
+    /// block, or using a variable that is not declared, is a parse error.
+    ///
+    /// A variable is declared if it is defined in an enclosing block, is a function parameter,
+    /// is captured by a closure from an enclosing scope, or exists in the [`Scope`][crate::Scope]
+    /// passed when compiling the script.
+    #[inline(always)]
+    pub fn set_strict_variables(&mut self, enable: bool) -> &mut Self {
+        self.strict_variables = enable;
+        self
+    }
+    /// Are strict variables enabled?
+    #[inline(always)]
+    pub fn strict_variables(&self) -> bool {
+        self.strict_variables
+    }
+    /// Enable/disable strict function pointers.
+    ///
+    /// When enabled, creating a function pointer via `Fn` to a function that does not exist
+    /// (with any number of parameters) is an error, instead of calling it later.
+    ///
+    /// A function exists if it is defined in the script, registered with the [`Engine`],
+    /// part of a package or in the global namespace of an imported module.
+    #[inline(always)]
+    pub fn set_strict_fn_pointers(&mut self, enable: bool) -> &mut Self {
+        self.strict_fn_pointers = enable;
+        self
+    }
+    /// Are strict function pointers enabled?
+    #[inline(always)]
+    pub fn strict_fn_pointers(&self) -> bool {
+        self.strict_fn_pointers
+    }
+    /// Enable/disable implicit promotion of `INT` arguments to `FLOAT` when calling native Rust
+    /// functions.
+    ///
+    /// When enabled and no native function matches the types of the arguments exactly, a function
+    /// taking `FLOAT` in place of some `INT` arguments is called instead, with those arguments
+    /// converted.  Functions requiring the fewest promoted arguments are preferred; between them,
+    /// arguments further to the left are promoted first.  `FLOAT` arguments are never converted
+    /// to `INT`.
+    ///
+    /// This is off by default.
+    ///
+    /// Not available under `no_float`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, RegisterFn, FLOAT};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_fn("half", |x: FLOAT| x / 2.0);
+    ///
+    /// assert!(engine.eval::<FLOAT>("half(5)").is_err());
+    ///
+    /// engine.set_int_to_float_promotion(true);
+    ///
+    /// assert_eq!(engine.eval::<FLOAT>("half(5)")?, 2.5);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_float"))]
+    #[inline(always)]
+    pub fn set_int_to_float_promotion(&mut self, enable: bool) -> &mut Self {
+        self.int_to_float_promotion = enable;
+        self
+    }
+    /// Is implicit promotion of `INT` arguments to `FLOAT` enabled?
+    ///
+    /// Not available under `no_float`.
+    #[cfg(not(feature = "no_float"))]
+    #[inline(always)]
+    pub fn int_to_float_promotion(&self) -> bool {
+        self.int_to_float_promotion
+    }
+    /// Enable/disable keeping the original script text in compiled [`AST`][crate::AST]'s.
+    ///
+    /// When enabled, [`compile`][Engine::compile] and friends store the script text in the
+    /// [`AST`][crate::AST], and module resolvers keep the text of the modules they load,
+    /// so that [`EvalAltResult::excerpt`][crate::EvalAltResult::excerpt] can show the
+    /// offending line of an error.
+    ///
+    /// This is off by default to save memory.
+    #[inline(always)]
+    pub fn set_retain_source(&mut self, enable: bool) -> &mut Self {
+        self.retain_source = enable;
+        self
+    }
+    /// Is the original script text kept in compiled [`AST`][crate::AST]'s?
+    #[inline(always)]
+    pub fn retain_source(&self) -> bool {
+        self.retain_source
+    }
     /// Set the module resolution service used by the [`Engine`].
     ///
     /// Not available under the `no_module` feature.
@@ -175,6 +395,80 @@ impl Engine {
         self.module_resolver = Box::new(resolver);
         self
     }
+    /// Set a filter that decides whether a module may be imported.
+    ///
+    /// The filter is called with the module path of every `import` statement before the
+    /// module resolution service is consulted.  Returning `false` denies the import,
+    /// which fails with [`ErrorModuleDenied`][crate::EvalAltResult::ErrorModuleDenied].
+    ///
+    /// Not available under the `no_module` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, EvalAltResult};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// // Only allow modules under 'lib/'
+    /// engine.set_module_filter(|path| path.starts_with("lib/") && !path.contains(".."));
+    ///
+    /// let err = engine.consume(r#"import "secrets" as s;"#).expect_err("should error");
+    ///
+    /// assert!(matches!(*err, EvalAltResult::ErrorModuleDenied(path, _) if path == "secrets"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn set_module_filter(
+        &mut self,
+        filter: impl Fn(&str) -> bool + crate::fn_native::SendSync + 'static,
+    ) -> &mut Self {
+        self.module_filter = Some(Box::new(filter));
+        self
+    }
+    /// Set a filter that decides whether an error can be caught by `try` ... `catch`.
+    ///
+    /// By default, only errors for which [`EvalAltResult::is_catchable`][crate::EvalAltResult::is_catchable]
+    /// returns `true` are caught.  In particular, terminations (e.g. via
+    /// [`on_progress`][Engine::on_progress]), violations of limits (e.g. the maximum number of
+    /// operations) and system errors always propagate out of the script, so a script cannot
+    /// get around them by wrapping its code in `try` blocks.
+    ///
+    /// The filter is called with every error raised inside a `try` block (except for `break`,
+    /// `continue` and `return`), and replaces this default.  Returning `false` propagates the error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, EvalAltResult};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// // Do not catch errors raised by functions not being found
+    /// engine.set_catch_filter(|err| {
+    ///     err.is_catchable() && !matches!(err, EvalAltResult::ErrorFunctionNotFound(_, _))
+    /// });
+    ///
+    /// assert_eq!(engine.eval::<i64>(r#"try { throw 42; } catch { 0 }"#)?, 0);
+    ///
+    /// let err = engine.eval::<i64>("try { foo() } catch { 0 }").expect_err("should error");
+    ///
+    /// assert!(matches!(*err, EvalAltResult::ErrorFunctionNotFound(_, _)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn set_catch_filter(
+        &mut self,
+        filter: impl Fn(&crate::EvalAltResult) -> bool + crate::fn_native::SendSync + 'static,
+    ) -> &mut Self {
+        self.catch_filter = Some(Box::new(filter));
+        self
+    }
     /// Disable a particular keyword or operator in the language.
     ///
     /// # Examples
@@ -213,7 +507,49 @@ impl Engine {
     /// ```
     #[inline(always)]
     pub fn disable_symbol(&mut self, symbol: &str) -> &mut Self {
-        self.disabled_symbols.insert(symbol.into());
+        self.disabled_symbols.insert(symbol.into(), None);
+        self
+    }
+    /// Disable a particular keyword or operator in the language, with a message explaining why.
+    ///
+    /// Using the disabled symbol raises [`ParseErrorType::DisabledSymbol`][crate::ParseErrorType::DisabledSymbol]
+    /// carrying the symbol and the message, instead of the generic error raised for symbols
+    /// disabled via [`disable_symbol`][Engine::disable_symbol].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), rhai::ParseError> {
+    /// use rhai::{Engine, ParseErrorType};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.disable_symbol_with_message("while", "use 'for' loops with a range instead");
+    ///
+    /// let err = engine.compile("while true {}").expect_err("should error");
+    ///
+    /// assert_eq!(
+    ///     *err.0,
+    ///     ParseErrorType::DisabledSymbol(
+    ///         "while".into(),
+    ///         "use 'for' loops with a range instead".into()
+    ///     )
+    /// );
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "'while' is disabled: use 'for' loops with a range instead (line 1, position 1)"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn disable_symbol_with_message(
+        &mut self,
+        symbol: &str,
+        message: impl Into<String>,
+    ) -> &mut Self {
+        self.disabled_symbols
+            .insert(symbol.into(), Some(message.into()));
         self
     }
     /// Register a custom operator with a precedence into the language.
@@ -244,10 +580,48 @@ impl Engine {
     /// # Ok(())
     /// # }
     /// ```
+    #[inline(always)]
     pub fn register_custom_operator(
         &mut self,
         keyword: &str,
         precedence: u8,
+    ) -> Result<&mut Self, String> {
+        self.register_custom_operator_with_associativity(keyword, precedence, Associativity::Left)
+    }
+    /// Register a custom operator with a precedence and an [associativity][Associativity]
+    /// into the language.
+    ///
+    /// The operator must be a valid identifier (i.e. it cannot be a symbol).
+    ///
+    /// The precedence cannot be zero.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Associativity, Engine, RegisterFn};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// // Register a right-associative custom operator called 'pow'
+    /// // with a precedence of 190 (same as '~').
+    /// engine
+    ///     .register_custom_operator_with_associativity("pow", 190, Associativity::Right)
+    ///     .unwrap();
+    ///
+    /// // Register a binary function named 'pow'
+    /// engine.register_fn("pow", |x: i64, y: i64| x.pow(y as u32));
+    ///
+    /// // 2 pow (3 pow 2)
+    /// assert_eq!(engine.eval_expression::<i64>("2 pow 3 pow 2")?, 512);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_custom_operator_with_associativity(
+        &mut self,
+        keyword: &str,
+        precedence: u8,
+        associativity: Associativity,
     ) -> Result<&mut Self, String> {
         let precedence = NonZeroU8::new(precedence);
 
@@ -261,18 +635,18 @@ impl Engine {
             // Active standard keywords cannot be made custom
             // Disabled keywords are OK
             Some(token) if token.is_keyword() => {
-                if !self.disabled_symbols.contains(token.syntax().as_ref()) {
+                if !self.disabled_symbols.contains_key(token.syntax().as_ref()) {
                     return Err(format!("'{}' is a reserved keyword", keyword).into());
                 }
             }
             // Active standard operators cannot be made custom
             Some(token) if token.is_operator() => {
-                if !self.disabled_symbols.contains(token.syntax().as_ref()) {
+                if !self.disabled_symbols.contains_key(token.syntax().as_ref()) {
                     return Err(format!("'{}' is a reserved operator", keyword).into());
                 }
             }
             // Active standard symbols cannot be made custom
-            Some(token) if !self.disabled_symbols.contains(token.syntax().as_ref()) => {
+            Some(token) if !self.disabled_symbols.contains_key(token.syntax().as_ref()) => {
                 return Err(format!("'{}' is a reserved symbol", keyword).into())
             }
             // Disabled symbols are OK
@@ -280,7 +654,70 @@ impl Engine {
         }
 
         // Add to custom keywords
-        self.custom_keywords.insert(keyword.into(), precedence);
+        self.custom_keywords.insert(
+            keyword.into(),
+            CustomKeyword {
+                precedence,
+                associativity,
+            },
+        );
+
+        Ok(self)
+    }
+    /// Override the precedence of a built-in binary operator.
+    ///
+    /// This only affects how scripts are parsed by this [`Engine`], not what the operator does.
+    ///
+    /// Assignment operators (e.g. `=`, `+=`) and the property access operators `.` and `?.`
+    /// cannot be changed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use std::num::NonZeroU8;
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// // Make '+' bind more tightly than '*' (default 180).
+    /// engine.set_operator_precedence("+", NonZeroU8::new(200).unwrap()).unwrap();
+    ///
+    /// assert_eq!(engine.eval_expression::<i64>("1 + 2 * 3")?, 9);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_operator_precedence(
+        &mut self,
+        operator: &str,
+        precedence: NonZeroU8,
+    ) -> Result<&mut Self, String> {
+        match Token::lookup_from_syntax(operator) {
+            // Assignments are not expressions
+            Some(Token::Equals)
+            | Some(Token::PlusAssign)
+            | Some(Token::MinusAssign)
+            | Some(Token::MultiplyAssign)
+            | Some(Token::DivideAssign)
+            | Some(Token::LeftShiftAssign)
+            | Some(Token::RightShiftAssign)
+            | Some(Token::ModuloAssign)
+            | Some(Token::PowerOfAssign)
+            | Some(Token::AndAssign)
+            | Some(Token::OrAssign)
+            | Some(Token::XOrAssign) => {
+                return Err(format!("'{}' is an assignment operator", operator))
+            }
+            // Property access is not a binary operator
+            Some(Token::Period) | Some(Token::Elvis) => {
+                return Err(format!("'{}' is not a binary operator", operator))
+            }
+            // Binary operators are OK
+            Some(token) if token.precedence() > 0 => (),
+            _ => return Err(format!("'{}' is not a binary operator", operator)),
+        }
+
+        self.operator_precedence.insert(operator.into(), precedence);
 
         Ok(self)
     }
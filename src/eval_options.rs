@@ -0,0 +1,183 @@
+//! Module defining per-evaluation overrides of [`Engine`][crate::Engine] settings.
+
+use crate::fn_native::{OnProgressCallback, SendSync};
+use crate::stdlib::{boxed::Box, fmt};
+use crate::{Dynamic, Shared};
+
+/// Options for a single evaluation, overriding the settings of the [`Engine`][crate::Engine].
+///
+/// Settings that are not overridden take the values set in the [`Engine`][crate::Engine].
+/// The [`Engine`][crate::Engine] itself is never modified, so evaluations running with different
+/// options do not interfere with each other.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// # #[cfg(not(feature = "unchecked"))]
+/// # {
+/// use rhai::{Engine, EvalOptions, Scope};
+///
+/// let engine = Engine::new();
+///
+/// let mut options = EvalOptions::new();
+/// options.set_max_operations(100);
+///
+/// let result = engine.eval_with_options::<()>(
+///     &mut Scope::new(),
+///     "for x in range(0, 1000) {}",
+///     &options,
+/// );
+///
+/// assert!(result.is_err());
+///
+/// // The engine's own settings are not affected
+/// assert_eq!(engine.max_operations(), 0);
+/// # }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct EvalOptions {
+    /// Maximum number of operations allowed to run (0 = unlimited).
+    #[cfg(not(feature = "unchecked"))]
+    pub(crate) max_operations: Option<u64>,
+    /// Maximum levels of call-stack.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) max_call_levels: Option<usize>,
+    /// Maximum number of [modules][crate::Module] allowed to load.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) max_modules: Option<usize>,
+    /// Disallow variables and module aliases with the same name in the same scope?
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) strict_module_aliases: Option<bool>,
+    /// Callback closure for progress reporting.
+    pub(crate) progress: Option<Shared<OnProgressCallback>>,
+    /// Allow if-expressions in expression-only evaluations?
+    pub(crate) allow_if_expression: bool,
+    /// Allow switch expressions in expression-only evaluations?
+    pub(crate) allow_switch_expression: bool,
+    /// Allow statement blocks in expression-only evaluations?
+    pub(crate) allow_statement_expression: bool,
+    /// Is `this` bound at global level?
+    ///
+    /// Only set by the `Engine::xxx_with_this` methods.
+    pub(crate) bind_this: bool,
+}
+
+impl fmt::Debug for EvalOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("EvalOptions");
+
+        #[cfg(not(feature = "unchecked"))]
+        d.field("max_operations", &self.max_operations);
+        #[cfg(not(feature = "unchecked"))]
+        #[cfg(not(feature = "no_function"))]
+        d.field("max_call_levels", &self.max_call_levels);
+        #[cfg(not(feature = "unchecked"))]
+        #[cfg(not(feature = "no_module"))]
+        d.field("max_modules", &self.max_modules);
+        #[cfg(not(feature = "no_module"))]
+        d.field("strict_module_aliases", &self.strict_module_aliases);
+
+        d.field("progress", &self.progress.is_some())
+            .field("allow_if_expression", &self.allow_if_expression)
+            .field("allow_switch_expression", &self.allow_switch_expression)
+            .field(
+                "allow_statement_expression",
+                &self.allow_statement_expression,
+            )
+            .field("bind_this", &self.bind_this)
+            .finish()
+    }
+}
+
+impl EvalOptions {
+    /// Create a new [`EvalOptions`] that overrides no settings.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Override the maximum number of operations allowed for the evaluation (0 for unlimited).
+    #[cfg(not(feature = "unchecked"))]
+    #[inline(always)]
+    pub fn set_max_operations(&mut self, operations: u64) -> &mut Self {
+        self.max_operations = Some(if operations == u64::MAX {
+            0
+        } else {
+            operations
+        });
+        self
+    }
+    /// Override the maximum levels of function calls allowed for the evaluation.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub fn set_max_call_levels(&mut self, levels: usize) -> &mut Self {
+        self.max_call_levels = Some(levels);
+        self
+    }
+    /// Override the maximum number of imported [modules][crate::Module] allowed for the evaluation.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn set_max_modules(&mut self, modules: usize) -> &mut Self {
+        self.max_modules = Some(modules);
+        self
+    }
+    /// Override whether strict module aliases are enabled when compiling the script.
+    ///
+    /// Only affects evaluations that compile a script, e.g. [`Engine::eval_with_options`][crate::Engine::eval_with_options].
+    ///
+    /// Not available under the `no_module` feature.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn set_strict_module_aliases(&mut self, enable: bool) -> &mut Self {
+        self.strict_module_aliases = Some(enable);
+        self
+    }
+    /// Allow `if` expressions (e.g. `if x > 0 { 1 } else { 2 }`) in expression-only evaluations,
+    /// e.g. [`Engine::eval_expression_with_options`][crate::Engine::eval_expression_with_options].
+    /// Not allowed by default.
+    ///
+    /// Unless [statement blocks][EvalOptions::set_allow_statement_expression] are also allowed,
+    /// the branches can only contain expressions, so `let` statements and loops are still rejected.
+    #[inline(always)]
+    pub fn set_allow_if_expression(&mut self, enable: bool) -> &mut Self {
+        self.allow_if_expression = enable;
+        self
+    }
+    /// Allow `switch` expressions in expression-only evaluations,
+    /// e.g. [`Engine::eval_expression_with_options`][crate::Engine::eval_expression_with_options].
+    /// Not allowed by default.
+    ///
+    /// Unless [statement blocks][EvalOptions::set_allow_statement_expression] are also allowed,
+    /// the cases can only contain expressions, so `let` statements and loops are still rejected.
+    #[inline(always)]
+    pub fn set_allow_switch_expression(&mut self, enable: bool) -> &mut Self {
+        self.allow_switch_expression = enable;
+        self
+    }
+    /// Allow statement blocks (e.g. `{ let x = 40; x + 2 }`) in expression-only evaluations,
+    /// e.g. [`Engine::eval_expression_with_options`][crate::Engine::eval_expression_with_options].
+    /// Not allowed by default.
+    ///
+    /// Statement blocks can contain any statements, including `let` statements and loops.
+    #[inline(always)]
+    pub fn set_allow_statement_expression(&mut self, enable: bool) -> &mut Self {
+        self.allow_statement_expression = enable;
+        self
+    }
+    /// Register a callback for progress reporting during the evaluation,
+    /// used instead of the one registered via [`Engine::on_progress`][crate::Engine::on_progress].
+    #[inline(always)]
+    pub fn on_progress(
+        &mut self,
+        callback: impl Fn(u64) -> Option<Dynamic> + SendSync + 'static,
+    ) -> &mut Self {
+        self.progress = Some(Shared::new(Box::new(callback)));
+        self
+    }
+}
@@ -20,17 +20,21 @@ use crate::stdlib::{
     string::ToString,
     vec::Vec,
 };
+use crate::token::is_keyword_function;
 use crate::utils::combine_hashes;
 use crate::{
     calc_native_fn_hash, calc_script_fn_hash, Dynamic, Engine, EvalAltResult, FnPtr,
-    ImmutableString, Module, ParseErrorType, Position, Scope, StaticVec, INT,
+    ImmutableString, Module, OutputKind, ParseErrorType, Position, Scope, StaticVec, INT,
 };
 
 #[cfg(not(feature = "no_float"))]
 use crate::FLOAT;
 
+#[cfg(not(feature = "no_function"))]
+use crate::engine::MAX_BACKTRACE_DEPTH;
+
 #[cfg(not(feature = "no_object"))]
-use crate::Map;
+use crate::{CompiledExpr, Map, ParseError};
 
 #[cfg(feature = "no_std")]
 #[cfg(not(feature = "no_float"))]
@@ -58,6 +62,32 @@ fn extract_prop_from_setter(_fn_name: &str) -> Option<&str> {
     }
 }
 
+/// Convert a [`Position`][Position] within the part of a template starting at `offset`
+/// into a position within the whole template.
+///
+/// If there is no position, the position of `offset` is returned.
+#[cfg(not(feature = "no_object"))]
+fn template_position(template: &str, offset: usize, pos: Position) -> Position {
+    let before = &template[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before[before.rfind('\n').map_or(0, |n| n + 1)..]
+        .chars()
+        .count()
+        + 1;
+
+    let (line, column) = match (pos.line(), pos.position()) {
+        (Some(1), Some(col)) => (line, column + col - 1),
+        (Some(l), Some(col)) => (line + l - 1, col),
+        (Some(l), None) => (line + l - 1, 0),
+        (None, _) => (line, column),
+    };
+
+    Position::new(
+        line.min(u16::MAX as usize) as u16,
+        column.min(u16::MAX as usize) as u16,
+    )
+}
+
 /// A type that temporarily stores a mutable reference to a `Dynamic`,
 /// replacing it with a cloned copy.
 #[derive(Debug, Default)]
@@ -126,6 +156,19 @@ impl Drop for ArgBackup<'_> {
     }
 }
 
+/// Find the index of the first argument that is a locked shared value,
+/// skipping the first argument if it is passed by reference.
+#[inline(always)]
+fn find_locked_arg(args: &FnCallArgs, is_ref: bool) -> Option<usize> {
+    let skip = if is_ref { 1 } else { 0 };
+
+    args.iter()
+        .enumerate()
+        .skip(skip)
+        .find(|(_, a)| a.is_locked())
+        .map(|(n, _)| n)
+}
+
 #[inline(always)]
 pub fn ensure_no_data_race(
     fn_name: &str,
@@ -133,16 +176,9 @@ pub fn ensure_no_data_race(
     is_ref: bool,
 ) -> Result<(), Box<EvalAltResult>> {
     if cfg!(not(feature = "no_closure")) {
-        let skip = if is_ref { 1 } else { 0 };
-
-        if let Some((n, _)) = args
-            .iter()
-            .skip(skip)
-            .enumerate()
-            .find(|(_, a)| a.is_locked())
-        {
+        if let Some(n) = find_locked_arg(args, is_ref) {
             return EvalAltResult::ErrorDataRace(
-                format!("argument #{} of function '{}'", n + 1 + skip, fn_name),
+                format!("argument #{} of function '{}'", n + 1, fn_name),
                 Position::NONE,
             )
             .into();
@@ -152,6 +188,36 @@ pub fn ensure_no_data_race(
     Ok(())
 }
 
+/// Wrap an error raised inside a called function into an
+/// [`ErrorInFunctionCall`][EvalAltResult::ErrorInFunctionCall] frame.
+///
+/// To keep deep recursion from building up an unbounded backtrace, once there are
+/// [`MAX_BACKTRACE_DEPTH`] frames the outermost one is dropped before adding the new frame,
+/// so the backtrace keeps the outermost call together with the innermost calls.
+#[cfg(not(feature = "no_function"))]
+fn make_fn_call_frame(
+    fn_name: &str,
+    source: &str,
+    mut err: Box<EvalAltResult>,
+    pos: Position,
+) -> Box<EvalAltResult> {
+    let mut depth = 0;
+    let mut e = Some(err.as_ref());
+
+    while let Some(EvalAltResult::ErrorInFunctionCall(_, _, inner, _)) = e {
+        depth += 1;
+        e = Some(inner.as_ref());
+    }
+
+    if depth >= MAX_BACKTRACE_DEPTH {
+        if let EvalAltResult::ErrorInFunctionCall(_, _, inner, _) = *err {
+            err = inner;
+        }
+    }
+
+    EvalAltResult::ErrorInFunctionCall(fn_name.into(), source.into(), err, pos).into()
+}
+
 impl Engine {
     /// Call a native Rust function registered with the [`Engine`].
     ///
@@ -203,6 +269,28 @@ impl Engine {
         if let Some(func) = func {
             assert!(func.is_native());
 
+            if let Some(ref mut audit) = state.audit {
+                audit.record_native(fn_name, args.len());
+            }
+
+            // Measure the first argument before it is modified in place, and all other arguments
+            #[cfg(not(feature = "unchecked"))]
+            let data_size = if self.max_total_data_size() > 0 {
+                let size = if is_ref && !func.is_pure() {
+                    crate::engine::calc_data_footprint(args[0], false)
+                } else {
+                    0
+                };
+                let args_size = args
+                    .iter()
+                    .skip(1)
+                    .map(|arg| crate::engine::calc_data_footprint(arg, true))
+                    .sum::<usize>();
+                Some((size, args_size))
+            } else {
+                None
+            };
+
             // Calling pure function but the first argument is a reference?
             let mut backup: ArgBackup = Default::default();
             backup.change_first_arg_to_copy(is_ref && func.is_pure(), args);
@@ -210,15 +298,33 @@ impl Engine {
             // Run external function
             let result = if func.is_plugin_fn() {
                 func.get_plugin_fn()
-                    .call((self, &state.source, mods, lib).into(), args)
+                    .call((self, &state.source, mods, lib, pos).into(), args)
             } else {
-                func.get_native_fn()((self, &state.source, mods, lib).into(), args)
+                func.get_native_fn()((self, &state.source, mods, lib, pos).into(), args)
             };
 
             // Restore the original reference
             backup.restore_first_arg(args);
 
-            let result = result?;
+            let result = result.map_err(|err| err.fill_position(pos))?;
+
+            // Account for the new value returned, plus the growth of the first argument
+            // modified in place
+            #[cfg(not(feature = "unchecked"))]
+            if let Some((size, args_size)) = data_size {
+                let growth = if is_ref && !func.is_pure() {
+                    crate::engine::calc_data_footprint(args[0], false).saturating_sub(size)
+                } else {
+                    0
+                };
+                let growth = if growth > 0 {
+                    crate::stdlib::cmp::max(growth, args_size)
+                } else {
+                    0
+                };
+                let size = growth + crate::engine::calc_data_footprint(&result, true);
+                self.inc_data_size(state, size, pos)?;
+            }
 
             // See if the function match print/debug (which requires special processing)
             return Ok(match fn_name {
@@ -230,7 +336,12 @@ impl Engine {
                             pos,
                         )
                     })?;
-                    ((self.print)(text).into(), false)
+                    if let Some(ref mut output) = state.output {
+                        output.push(OutputKind::Print, text, pos, state.source.clone());
+                    }
+                    let source = state.source.as_ref().map(|s| s.as_str());
+                    (self.print)(text, source, pos);
+                    (Dynamic::UNIT, false)
                 }
                 KEYWORD_DEBUG => {
                     let text = result.as_str().map_err(|typ| {
@@ -240,8 +351,12 @@ impl Engine {
                             pos,
                         )
                     })?;
+                    if let Some(ref mut output) = state.output {
+                        output.push(OutputKind::Debug, text, pos, state.source.clone());
+                    }
                     let source = state.source.as_ref().map(|s| s.as_str());
-                    ((self.debug)(text, source, pos).into(), false)
+                    (self.debug)(text, source, pos);
+                    (Dynamic::UNIT, false)
                 }
                 _ => (result, func.is_method()),
             });
@@ -249,12 +364,54 @@ impl Engine {
 
         // See if it is built in.
         if args.len() == 2 {
-            match run_builtin_binary_op(fn_name, args[0], args[1])? {
-                Some(v) => return Ok((v, false)),
+            match run_builtin_binary_op(fn_name, args[0], args[1])
+                .map_err(|err| err.fill_position(pos))?
+            {
+                Some(v) => {
+                    if let Some(ref mut audit) = state.audit {
+                        audit.record_native(fn_name, 2);
+                    }
+                    #[cfg(not(feature = "unchecked"))]
+                    self.inc_data_size(state, crate::engine::calc_data_footprint(&v, true), pos)?;
+                    return Ok((v, false));
+                }
                 None => (),
             }
         }
 
+        // See if there is a function taking FLOAT in place of some INT arguments
+        #[cfg(not(feature = "no_float"))]
+        if self.int_to_float_promotion {
+            if let Some((hash_fn, promoted)) =
+                self.find_fn_with_float_promotion(mods, fn_name, args, pub_only)
+            {
+                let mut values: StaticVec<Dynamic> = promoted
+                    .iter()
+                    .map(|&index| (args[index].as_int().unwrap() as FLOAT).into())
+                    .collect();
+                let mut values = values.iter_mut();
+
+                let mut args: StaticVec<_> = args
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(index, arg)| {
+                        if promoted.contains(&index) {
+                            values.next().unwrap()
+                        } else {
+                            &mut **arg
+                        }
+                    })
+                    .collect();
+
+                // A promoted first argument is a temporary value
+                let is_ref = is_ref && promoted[0] != 0;
+
+                return self.call_native_fn(
+                    mods, state, lib, fn_name, hash_fn, &mut args, is_ref, pub_only, pos, def_val,
+                );
+            }
+        }
+
         // Return default value (if any)
         if let Some(val) = def_val {
             return Ok((val.clone(), false));
@@ -291,12 +448,16 @@ impl Engine {
 
         // index getter function not found?
         #[cfg(not(feature = "no_index"))]
-        if fn_name == crate::engine::FN_IDX_GET && args.len() == 2 {
+        if fn_name == crate::engine::FN_IDX_GET && args.len() >= 2 {
             return EvalAltResult::ErrorFunctionNotFound(
                 format!(
                     "{} [{}]",
                     self.map_type_name(args[0].type_name()),
-                    self.map_type_name(args[1].type_name()),
+                    args[1..]
+                        .iter()
+                        .map(|idx| self.map_type_name(idx.type_name()))
+                        .collect::<Vec<_>>()
+                        .join(", "),
                 ),
                 pos,
             )
@@ -310,7 +471,11 @@ impl Engine {
                 format!(
                     "{} [{}]=",
                     self.map_type_name(args[0].type_name()),
-                    self.map_type_name(args[1].type_name()),
+                    args[1..args.len() - 1]
+                        .iter()
+                        .map(|idx| self.map_type_name(idx.type_name()))
+                        .collect::<Vec<_>>()
+                        .join(", "),
                 ),
                 pos,
             )
@@ -358,26 +523,72 @@ impl Engine {
     ) -> Result<Dynamic, Box<EvalAltResult>> {
         self.inc_operations(state, pos)?;
 
+        state.max_call_level = state.max_call_level.max(level);
+
         // Check for stack overflow
         #[cfg(not(feature = "no_function"))]
         #[cfg(not(feature = "unchecked"))]
-        if level > self.max_call_levels() {
+        if level
+            > state
+                .options
+                .max_call_levels
+                .unwrap_or_else(|| self.max_call_levels())
+        {
             return Err(Box::new(EvalAltResult::ErrorStackOverflow(pos)));
         }
 
+        // Guard against too many variables
+        #[cfg(not(feature = "unchecked"))]
+        self.ensure_scope_within_limit(scope, fn_def.params.len(), pos)?;
+
         let orig_scope_level = state.scope_level;
         state.scope_level += 1;
 
         let prev_scope_len = scope.len();
         let prev_mods_len = mods.len();
 
+        let num_fixed = fn_def.num_fixed_params();
+        let (args, _rest) = if args.len() > num_fixed {
+            args.split_at_mut(num_fixed)
+        } else {
+            (args, &mut [][..])
+        };
+
+        // Default values of the parameters not passed as arguments
+        let num_defaults = fn_def.defaults.len();
+        let defaults = fn_def
+            .defaults
+            .iter()
+            .skip((num_defaults + args.len()).saturating_sub(num_fixed));
+
+        // Remaining arguments collected into an array by the rest parameter
+        #[cfg(not(feature = "no_index"))]
+        let rest = if fn_def.variadic {
+            Some(
+                _rest
+                    .iter_mut()
+                    .map(|v| mem::take(*v))
+                    .collect::<crate::Array>()
+                    .into(),
+            )
+        } else {
+            None
+        };
+        #[cfg(feature = "no_index")]
+        let rest: Option<Dynamic> = None;
+
         // Put arguments into scope as variables
         // Actually consume the arguments instead of cloning them
         scope.extend(
             fn_def
                 .params
                 .iter()
-                .zip(args.iter_mut().map(|v| mem::take(*v)))
+                .zip(
+                    args.iter_mut()
+                        .map(|v| mem::take(*v))
+                        .chain(defaults.cloned())
+                        .chain(rest),
+                )
                 .map(|(name, value)| {
                     let var_name: crate::stdlib::borrow::Cow<'_, str> =
                         crate::r#unsafe::unsafe_cast_var_name_to_lifetime(name).into();
@@ -405,6 +616,18 @@ impl Engine {
             mods.extend(fn_def.mods.iter_raw().map(|(n, m)| (n.clone(), m.clone())));
         }
 
+        if let Some(ref mut audit) = state.audit {
+            audit.record_script(&fn_def.name, fn_def.params.len());
+        }
+
+        if self.debugger.is_some() {
+            state
+                .debugger
+                .get_or_insert_with(Default::default)
+                .call_stack
+                .push(fn_def.name.clone());
+        }
+
         // Evaluate the function
         let stmt = &fn_def.body;
 
@@ -413,18 +636,13 @@ impl Engine {
             .or_else(|err| match *err {
                 // Convert return statement to return value
                 EvalAltResult::Return(x, _) => Ok(x),
-                EvalAltResult::ErrorInFunctionCall(name, err, _) => {
-                    EvalAltResult::ErrorInFunctionCall(
-                        format!("{} > {}", fn_def.name, name),
-                        err,
-                        pos,
-                    )
-                    .into()
-                }
                 // System errors are passed straight-through
-                err if err.is_system_exception() => Err(Box::new(err)),
-                // Other errors are wrapped in `ErrorInFunctionCall`
-                _ => EvalAltResult::ErrorInFunctionCall(fn_def.name.to_string(), err, pos).into(),
+                ref e if e.is_system_exception() => Err(err),
+                // Other errors are wrapped in `ErrorInFunctionCall`, adding a frame to the backtrace
+                _ => {
+                    let source = state.source.as_ref().map_or("", |s| s.as_str());
+                    Err(make_fn_call_frame(&fn_def.name, source, err, pos))
+                }
             });
 
         // Remove all local variables
@@ -432,9 +650,91 @@ impl Engine {
         mods.truncate(prev_mods_len);
         state.scope_level = orig_scope_level;
 
+        if let Some(ref mut debugger) = state.debugger {
+            debugger.call_stack.pop();
+        }
+
         result
     }
 
+    // Is there a function with a particular name, taking any number of parameters?
+    pub(crate) fn has_fn_with_name(&self, mods: &Imports, lib: &[&Module], name: &str) -> bool {
+        is_keyword_function(name)
+            || lib.iter().any(|m| m.contains_fn_with_name(name, false))
+            || self.global_namespace.contains_fn_with_name(name, false)
+            || self
+                .global_modules
+                .iter()
+                .any(|m| m.contains_fn_with_name(name, false))
+            || mods
+                .iter_raw()
+                .any(|(_, m)| m.contains_fn_with_name(name, true))
+    }
+
+    /// Find a native function taking `FLOAT` in place of some `INT` arguments.
+    ///
+    /// Functions requiring fewer arguments to be promoted are preferred; between them, arguments
+    /// further to the left are promoted first.
+    ///
+    /// Returns the hash of the function and the (non-empty) list of positions of the arguments to promote.
+    #[cfg(not(feature = "no_float"))]
+    fn find_fn_with_float_promotion(
+        &self,
+        mods: &Imports,
+        fn_name: &str,
+        args: &FnCallArgs,
+        pub_only: bool,
+    ) -> Option<(NonZeroU64, StaticVec<usize>)> {
+        let ints: StaticVec<usize> = args
+            .iter()
+            .enumerate()
+            .filter(|(_, arg)| arg.is::<INT>())
+            .map(|(index, _)| index)
+            .collect();
+
+        let num_ints = ints.len();
+
+        for count in 1..=num_ints {
+            // Indices into `ints`, enumerated as combinations in lexicographic order
+            let mut picks: StaticVec<usize> = (0..count).collect();
+
+            loop {
+                let promoted: StaticVec<usize> = picks.iter().map(|&p| ints[p]).collect();
+
+                let arg_types = args.iter().enumerate().map(|(index, arg)| {
+                    if promoted.contains(&index) {
+                        TypeId::of::<FLOAT>()
+                    } else {
+                        arg.type_id()
+                    }
+                });
+
+                if let Some(hash_fn) = calc_native_fn_hash(empty(), fn_name, arg_types) {
+                    if self.global_namespace.contains_fn(hash_fn, pub_only)
+                        || self
+                            .global_modules
+                            .iter()
+                            .any(|m| m.contains_fn(hash_fn, false))
+                        || mods.contains_fn(hash_fn)
+                    {
+                        return Some((hash_fn, promoted));
+                    }
+                }
+
+                // Advance to the next combination
+                match (0..count).rev().find(|&i| picks[i] < num_ints - count + i) {
+                    Some(i) => {
+                        picks[i] += 1;
+                        (i + 1..count).for_each(|j| picks[j] = picks[j - 1] + 1);
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        None
+    }
+
     // Has a system function an override?
     #[inline(always)]
     pub(crate) fn has_override_by_name_and_arguments(
@@ -476,6 +776,32 @@ impl Engine {
             || hash_fn.map(|hash| mods.map(|m| m.contains_fn(hash)).unwrap_or(false)).unwrap_or(false)
     }
 
+    // Is there a native Rust function that overrides the built-in behavior of a standard type
+    // (e.g. a property getter or indexer registered for object maps)?
+    //
+    // Only functions registered by the host count - functions in the standard library
+    // (e.g. `keys` or `len` for object maps) never override the built-in behavior.
+    #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+    pub(crate) fn has_native_override(
+        &self,
+        mods: &Imports,
+        fn_name: &str,
+        arg_types: impl Iterator<Item = TypeId>,
+    ) -> bool {
+        let hash_fn = match calc_native_fn_hash(empty(), fn_name, arg_types) {
+            Some(hash) => hash,
+            None => return false,
+        };
+
+        self.global_namespace.contains_fn(hash_fn, false)
+            || self
+                .global_modules
+                .iter()
+                .filter(|m| !m.is_standard())
+                .any(|m| m.contains_fn(hash_fn, false))
+            || mods.contains_fn(hash_fn)
+    }
+
     /// Perform an actual function call, native Rust or scripted, taking care of special functions.
     ///
     /// ## WARNING
@@ -501,7 +827,29 @@ impl Engine {
     ) -> Result<(Dynamic, bool), Box<EvalAltResult>> {
         // Check for data race.
         if cfg!(not(feature = "no_closure")) {
-            ensure_no_data_race(fn_name, args, is_ref)?;
+            if let Some(n) = find_locked_arg(args, is_ref) {
+                // Name the parameter (which, for a closure, may be a captured variable)
+                // if a script-defined function is called
+                #[cfg(not(feature = "no_function"))]
+                let param = hash_script
+                    .and_then(|hash| lib.iter().find_map(|&m| m.get_fn(hash, pub_only)))
+                    .filter(|f| f.is_script())
+                    .and_then(|f| {
+                        let index = if _is_method { n.checked_sub(1)? } else { n };
+                        f.get_fn_def().params.get(index).cloned()
+                    });
+                #[cfg(feature = "no_function")]
+                let param: Option<ImmutableString> = None;
+
+                return EvalAltResult::ErrorDataRace(
+                    param.map_or_else(
+                        || format!("argument #{} of function '{}'", n + 1, fn_name),
+                        |name| name.to_string(),
+                    ),
+                    Position::NONE,
+                )
+                .into();
+            }
         }
 
         // Qualifiers (none) + function name + number of arguments + argument `TypeId`'s.
@@ -537,6 +885,22 @@ impl Engine {
                 .into()
             }
 
+            // render - placeholders are evaluated within the calling script
+            #[cfg(not(feature = "no_object"))]
+            crate::engine::FN_RENDER
+                if self.allow_render
+                    && args.len() == 2
+                    && args[0].is::<ImmutableString>()
+                    && args[1].is::<Map>()
+                    && !self.has_override(Some(mods), lib, hash_fn, hash_script, pub_only) =>
+            {
+                let template = args[0].clone().cast::<ImmutableString>();
+                let vars = args[1].clone().cast::<Map>();
+
+                self.render_template(mods, state, lib, &template, vars, pos, _level + 1)
+                    .map(|v| (v, false))
+            }
+
             // Script-like function found
             #[cfg(not(feature = "no_function"))]
             _ if hash_script.is_some()
@@ -545,7 +909,7 @@ impl Engine {
                 let hash_script = hash_script.unwrap();
 
                 // Get function
-                let (func, mut source) = lib
+                let (func, source) = lib
                     .iter()
                     .find_map(|&m| {
                         m.get_fn(hash_script, pub_only)
@@ -562,72 +926,20 @@ impl Engine {
                     .unwrap();
 
                 if func.is_script() {
-                    let func = func.get_fn_def();
-
-                    let scope: &mut Scope = &mut Default::default();
-
-                    // Move captured variables into scope
-                    #[cfg(not(feature = "no_closure"))]
-                    if let Some(captured) = _capture_scope {
-                        if !func.externals.is_empty() {
-                            captured
-                                .into_iter()
-                                .filter(|(name, _, _)| func.externals.iter().any(|ex| ex == name))
-                                .for_each(|(name, value, _)| {
-                                    // Consume the scope values.
-                                    scope.push_dynamic(name, value);
-                                });
-                        }
-                    }
-
-                    let result = if _is_method {
-                        // Method call of script function - map first argument to `this`
-                        let (first, rest) = args.split_first_mut().unwrap();
-
-                        mem::swap(&mut state.source, &mut source);
-
-                        let level = _level + 1;
-
-                        let result = self.call_script_fn(
-                            scope,
-                            mods,
-                            state,
-                            lib,
-                            &mut Some(*first),
-                            func,
-                            rest,
-                            pos,
-                            level,
-                        );
-
-                        // Restore the original source
-                        state.source = source;
-
-                        result?
-                    } else {
-                        // Normal call of script function
-                        // The first argument is a reference?
-                        let mut backup: ArgBackup = Default::default();
-                        backup.change_first_arg_to_copy(is_ref, args);
-
-                        mem::swap(&mut state.source, &mut source);
-
-                        let level = _level + 1;
-
-                        let result = self.call_script_fn(
-                            scope, mods, state, lib, &mut None, func, args, pos, level,
-                        );
-
-                        // Restore the original source
-                        state.source = source;
-
-                        // Restore the original reference
-                        backup.restore_first_arg(args);
-
-                        result?
-                    };
-
-                    Ok((result, false))
+                    self.exec_script_fn_call(
+                        mods,
+                        state,
+                        lib,
+                        func.get_fn_def(),
+                        source,
+                        args,
+                        is_ref,
+                        _is_method,
+                        pos,
+                        _capture_scope,
+                        _level,
+                    )
+                    .map(|result| (result, false))
                 } else {
                     // If it is a native function, redirect it
                     self.call_native_fn(
@@ -646,22 +958,136 @@ impl Engine {
             }
 
             // Normal native function call
-            _ => self.call_native_fn(
+            _ => {
+                // Variadic script function, when no function takes exactly these arguments
+                #[cfg(not(feature = "no_function"))]
+                #[cfg(not(feature = "no_index"))]
+                if hash_script.is_some()
+                    && !self.has_override(Some(mods), lib, hash_fn, None, pub_only)
+                {
+                    let num_args = if _is_method {
+                        args.len() - 1
+                    } else {
+                        args.len()
+                    };
+
+                    if let Some((func, source)) = lib.iter().find_map(|&m| {
+                        m.get_variadic_script_fn(fn_name, num_args, pub_only)
+                            .map(|f| (f, m.id_raw().clone()))
+                    }) {
+                        return self
+                            .exec_script_fn_call(
+                                mods,
+                                state,
+                                lib,
+                                func,
+                                source,
+                                args,
+                                is_ref,
+                                _is_method,
+                                pos,
+                                _capture_scope,
+                                _level,
+                            )
+                            .map(|result| (result, false));
+                    }
+                }
+
+                self.call_native_fn(
+                    mods,
+                    state,
+                    lib,
+                    fn_name,
+                    hash_fn.unwrap(),
+                    args,
+                    is_ref,
+                    pub_only,
+                    pos,
+                    def_val,
+                )
+            }
+        }
+    }
+
+    /// Call a script-defined function found for a function call, mapping the first argument to
+    /// `this` for a method-style call.
+    #[cfg(not(feature = "no_function"))]
+    fn exec_script_fn_call(
+        &self,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        func: &crate::ast::ScriptFnDef,
+        mut source: Option<ImmutableString>,
+        args: &mut FnCallArgs,
+        is_ref: bool,
+        is_method: bool,
+        pos: Position,
+        _capture_scope: Option<Scope>,
+        level: usize,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let scope: &mut Scope = &mut Default::default();
+
+        // Move captured variables into scope
+        #[cfg(not(feature = "no_closure"))]
+        if let Some(captured) = _capture_scope {
+            if !func.externals.is_empty() {
+                captured
+                    .into_iter()
+                    .filter(|(name, _, _)| func.externals.iter().any(|ex| ex == name))
+                    .for_each(|(name, value, _)| {
+                        // Consume the scope values.
+                        scope.push_dynamic(name, value);
+                    });
+            }
+        }
+
+        let level = level + 1;
+
+        if is_method {
+            // Method call of script function - map first argument to `this`
+            let (first, rest) = args.split_first_mut().unwrap();
+
+            mem::swap(&mut state.source, &mut source);
+
+            let result = self.call_script_fn(
+                scope,
                 mods,
                 state,
                 lib,
-                fn_name,
-                hash_fn.unwrap(),
-                args,
-                is_ref,
-                pub_only,
+                &mut Some(*first),
+                func,
+                rest,
                 pos,
-                def_val,
-            ),
+                level,
+            );
+
+            // Restore the original source
+            state.source = source;
+
+            result
+        } else {
+            // Normal call of script function
+            // The first argument is a reference?
+            let mut backup: ArgBackup = Default::default();
+            backup.change_first_arg_to_copy(is_ref, args);
+
+            mem::swap(&mut state.source, &mut source);
+
+            let result =
+                self.call_script_fn(scope, mods, state, lib, &mut None, func, args, pos, level);
+
+            // Restore the original source
+            state.source = source;
+
+            // Restore the original reference
+            backup.restore_first_arg(args);
+
+            result
         }
     }
 
-    /// Evaluate a list of statements with an empty state and no `this` pointer.
+    /// Evaluate a list of statements with an optional `this` pointer.
     /// This is commonly used to evaluate a list of statements in an [`AST`] or a script function body.
     #[inline]
     pub(crate) fn eval_statements_raw<'a>(
@@ -671,16 +1097,17 @@ impl Engine {
         state: &mut State,
         statements: impl IntoIterator<Item = &'a Stmt>,
         lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
         level: usize,
     ) -> Result<Dynamic, Box<EvalAltResult>> {
         statements
             .into_iter()
             .try_fold(().into(), |_, stmt| {
-                self.eval_stmt(scope, mods, state, lib, &mut None, stmt, level)
+                self.eval_stmt(scope, mods, state, lib, this_ptr, stmt, level)
             })
             .or_else(|err| match *err {
                 EvalAltResult::Return(out, _) => Ok(out),
-                EvalAltResult::LoopBreak(_, _) => {
+                EvalAltResult::LoopBreak(_, _, _, _) => {
                     unreachable!("no outer loop scope to break out of")
                 }
                 _ => Err(err),
@@ -722,16 +1149,204 @@ impl Engine {
         let mut new_state = State {
             source: state.source.clone(),
             operations: state.operations,
+            options: state.options.clone(),
             ..Default::default()
         };
 
-        let result =
-            self.eval_statements_raw(scope, mods, &mut new_state, ast.statements(), lib, level);
+        let result = self.eval_statements_raw(
+            scope,
+            mods,
+            &mut new_state,
+            ast.statements(),
+            lib,
+            &mut None,
+            level,
+        );
 
         state.operations = new_state.operations;
         result
     }
 
+    /// Evaluate a [compiled expression][CompiledExpr] in a new scope,
+    /// with the properties of an object map as variables.
+    #[cfg(not(feature = "no_object"))]
+    fn eval_compiled_expr(
+        &self,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        expr: &CompiledExpr,
+        vars: Map,
+        level: usize,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let mut scope = Scope::new();
+        vars.into_iter().for_each(|(name, value)| {
+            scope.push_dynamic(name.to_string(), value);
+        });
+
+        // Operations within the expression count towards the limits of the calling script
+        let mut new_state = State {
+            source: state.source.clone(),
+            operations: state.operations,
+            options: state.options.clone(),
+            ..Default::default()
+        };
+
+        let result = self.eval_statements_raw(
+            &mut scope,
+            mods,
+            &mut new_state,
+            expr.ast().statements(),
+            lib,
+            &mut None,
+            level,
+        );
+
+        state.operations = new_state.operations;
+        result
+    }
+
+    /// Render a template, replacing each `{{ expr }}` placeholder with the value of the expression,
+    /// evaluated with the properties of an object map as variables.
+    ///
+    /// `\{{` is a literal `{{`.
+    ///
+    /// Placeholders are evaluated one call level deeper, under the limits of the calling script.
+    #[cfg(not(feature = "no_object"))]
+    fn render_template(
+        &self,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        template: &str,
+        vars: Map,
+        pos: Position,
+        level: usize,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        // Check for stack overflow - a placeholder may itself render a template
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(not(feature = "unchecked"))]
+        if level
+            > state
+                .options
+                .max_call_levels
+                .unwrap_or_else(|| self.max_call_levels())
+        {
+            return EvalAltResult::ErrorStackOverflow(pos).into();
+        }
+
+        let mut scope = Scope::new();
+        vars.into_iter().for_each(|(name, value)| {
+            scope.push_dynamic(name.to_string(), value);
+        });
+
+        // Errors within the template are reported with their positions in the template
+        let wrap_err = |mut err: Box<EvalAltResult>, offset: usize| {
+            let err_pos = template_position(template, offset, err.position());
+            err.set_position(err_pos);
+
+            Box::new(EvalAltResult::ErrorInFunctionCall(
+                crate::engine::FN_RENDER.to_string(),
+                String::new(),
+                err,
+                pos,
+            ))
+        };
+
+        let mut result = String::with_capacity(template.len());
+        let mut last = 0;
+
+        while let Some(index) = template[last..].find("{{").map(|index| last + index) {
+            if template[last..index].ends_with('\\') {
+                result.push_str(&template[last..index - 1]);
+                result.push_str("{{");
+                last = index + 2;
+                continue;
+            }
+
+            result.push_str(&template[last..index]);
+
+            let start = index + 2;
+
+            let end = match template[start..].find("}}") {
+                Some(len) => start + len,
+                None => {
+                    let err = EvalAltResult::ErrorParsing(
+                        ParseErrorType::MissingToken(
+                            "}}".into(),
+                            "to close the placeholder".into(),
+                        ),
+                        Position::NONE,
+                    );
+                    return Err(wrap_err(err.into(), index));
+                }
+            };
+
+            self.inc_operations(state, pos)?;
+
+            let ast = self
+                .compile_expression(&template[start..end])
+                .map_err(|err| wrap_err(err.into(), start))?;
+
+            // Placeholders run within the state of the calling script, so that they count
+            // towards its limits and show up in its audit
+            let orig_scope_level = state.scope_level;
+            state.scope_level += 1;
+
+            let value = self.eval_statements_raw(
+                &mut scope,
+                mods,
+                state,
+                ast.statements(),
+                lib,
+                &mut None,
+                level,
+            );
+
+            state.scope_level = orig_scope_level;
+            let mut value = value.map_err(|err| wrap_err(err, start))?;
+
+            if let Ok(s) = value.as_str() {
+                result.push_str(s);
+            } else if let Ok(ch) = value.as_char() {
+                result.push(ch);
+            } else {
+                let hash_script = calc_script_fn_hash(empty(), "to_string", 0);
+
+                match self.exec_fn_call(
+                    mods,
+                    state,
+                    lib,
+                    "to_string",
+                    hash_script,
+                    &mut [&mut value],
+                    true,
+                    true,
+                    false,
+                    pos,
+                    None,
+                    None,
+                    level,
+                ) {
+                    Ok((s, _)) if s.is::<ImmutableString>() => result.push_str(s.as_str().unwrap()),
+                    _ => result.push_str(self.map_type_name(value.type_name())),
+                }
+            }
+
+            #[cfg(not(feature = "unchecked"))]
+            if self.max_string_size() > 0 && result.len() > self.max_string_size() {
+                return EvalAltResult::ErrorDataTooLarge("Length of string".to_string(), pos)
+                    .into();
+            }
+
+            last = end + 2;
+        }
+
+        result.push_str(&template[last..]);
+
+        Ok(result.into())
+    }
+
     /// Call a dot method.
     #[cfg(not(feature = "no_object"))]
     pub(crate) fn make_method_call(
@@ -750,13 +1365,18 @@ impl Engine {
     ) -> Result<(Dynamic, bool), Box<EvalAltResult>> {
         let is_ref = target.is_ref();
 
+        // Compare with the argument of same_instance() before the target is borrowed
+        let is_same_instance = fn_name == crate::engine::KEYWORD_SAME_INSTANCE
+            && call_args.len() == 1
+            && target.ptr_eq(&call_args[0]);
+
         // Get a reference to the mutation target Dynamic
         let obj = target.as_mut();
         let mut fn_name = fn_name;
 
         let (result, updated) = if fn_name == KEYWORD_FN_PTR_CALL && obj.is::<FnPtr>() {
             // FnPtr call
-            let fn_ptr = obj.read_lock::<FnPtr>().unwrap();
+            let fn_ptr = self.lock_value_as::<FnPtr>(obj, fn_name, pos)?;
             // Redirect function name
             let fn_name = fn_ptr.fn_name();
             let args_len = call_args.len() + fn_ptr.curry().len();
@@ -801,7 +1421,7 @@ impl Engine {
             )
         } else if fn_name == KEYWORD_FN_PTR_CURRY && obj.is::<FnPtr>() {
             // Curry call
-            let fn_ptr = obj.read_lock::<FnPtr>().unwrap();
+            let fn_ptr = self.lock_value_as::<FnPtr>(obj, fn_name, pos)?;
             Ok((
                 FnPtr::new_unchecked(
                     fn_ptr.get_fn_name().clone(),
@@ -815,6 +1435,19 @@ impl Engine {
                 .into(),
                 false,
             ))
+        } else if fn_name == KEYWORD_EVAL && call_args.len() == 1 && obj.is::<CompiledExpr>() {
+            // Evaluate compiled expression
+            let expr = self
+                .lock_value_as::<CompiledExpr>(obj, fn_name, pos)?
+                .clone();
+            let vars = call_args.remove(0);
+            let typ = self.map_type_name(vars.type_name());
+            let vars = vars
+                .try_cast::<Map>()
+                .ok_or_else(|| self.make_type_mismatch_err::<Map>(typ, pos))?;
+            self.inc_operations(state, pos)?;
+            self.eval_compiled_expr(mods, state, lib, &expr, vars, level + 1)
+                .map(|v| (v, false))
         } else if {
             #[cfg(not(feature = "no_closure"))]
             {
@@ -825,15 +1458,52 @@ impl Engine {
         } {
             // is_shared call
             Ok((target.is_shared().into(), false))
+        } else if fn_name == crate::engine::KEYWORD_SAME_INSTANCE
+            && call_args.len() == 1
+            && !self.has_override_by_name_and_arguments(
+                Some(mods),
+                lib,
+                fn_name,
+                [
+                    obj.type_id(),
+                    // The argument may be the same shared value as the locked object
+                    if is_same_instance {
+                        obj.type_id()
+                    } else {
+                        call_args[0].type_id()
+                    },
+                ],
+                pub_only,
+            )
+        {
+            // same_instance call
+            Ok((is_same_instance.into(), false))
+        } else if fn_name == crate::engine::KEYWORD_DEEP_COPY
+            && call_args.is_empty()
+            && !self.has_override_by_name_and_arguments(
+                Some(mods),
+                lib,
+                fn_name,
+                [obj.type_id()],
+                pub_only,
+            )
+        {
+            // deep_copy call
+            Ok((self.deep_copy_value(target, pos)?, false))
         } else {
             let _redirected;
             let mut hash = hash_script;
 
             // Check if it is a map method call in OOP style
+            // (unless a method with the same name is registered for object maps)
             #[cfg(not(feature = "no_object"))]
             if let Some(map) = obj.read_lock::<Map>() {
                 if let Some(val) = map.get(fn_name) {
-                    if let Some(fn_ptr) = val.read_lock::<FnPtr>() {
+                    if let Some(fn_ptr) = val.read_lock::<FnPtr>().filter(|_| {
+                        let arg_types = call_args.iter().map(|v| v.type_id());
+                        let arg_types = once(TypeId::of::<Map>()).chain(arg_types);
+                        !self.has_native_override(mods, fn_name, arg_types)
+                    }) {
                         // Remap the function name
                         _redirected = fn_ptr.get_fn_name().clone();
                         fn_name = &_redirected;
@@ -908,7 +1578,15 @@ impl Engine {
                         self.make_type_mismatch_err::<ImmutableString>(typ, args_expr[0].position())
                     })
                     .and_then(|s| FnPtr::try_from(s))
-                    .map(Into::<Dynamic>::into)
+                    .and_then(|f| {
+                        if self.strict_fn_pointers && !self.has_fn_with_name(mods, lib, f.fn_name())
+                        {
+                            EvalAltResult::ErrorFunctionNotFound(f.fn_name().into(), Position::NONE)
+                                .into()
+                        } else {
+                            Ok(f.into())
+                        }
+                    })
                     .map_err(|err| err.fill_position(args_expr[0].position()));
             }
         }
@@ -947,6 +1625,94 @@ impl Engine {
             return Ok(value.is_shared().into());
         }
 
+        // Handle same_instance()
+        if fn_name == crate::engine::KEYWORD_SAME_INSTANCE && args_expr.len() == 2 {
+            let mut value1 =
+                self.eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)?;
+            let mut value2 =
+                self.eval_expr(scope, mods, state, lib, this_ptr, &args_expr[1], level)?;
+
+            if !self.has_override_by_name_and_arguments(
+                Some(mods),
+                lib,
+                fn_name,
+                [value1.type_id(), value2.type_id()],
+                pub_only,
+            ) {
+                return Ok(value1.ptr_eq(&value2).into());
+            }
+
+            // Call the overriding function with the values already evaluated
+            return self
+                .exec_fn_call(
+                    mods,
+                    state,
+                    lib,
+                    fn_name,
+                    hash_script,
+                    &mut [&mut value1, &mut value2],
+                    false,
+                    false,
+                    pub_only,
+                    pos,
+                    None,
+                    def_val,
+                    level,
+                )
+                .map(|(v, _)| v);
+        }
+
+        // Handle deep_copy()
+        if fn_name == crate::engine::KEYWORD_DEEP_COPY && args_expr.len() == 1 {
+            let expr = &args_expr[0];
+
+            if let Expr::Variable(_) = expr {
+                // Copy a variable in place, so it is not cloned first
+                let (target, _) = self.search_namespace(scope, mods, state, lib, this_ptr, expr)?;
+
+                if !self.has_override_by_name_and_arguments(
+                    Some(mods),
+                    lib,
+                    fn_name,
+                    [target.as_ref().type_id()],
+                    pub_only,
+                ) {
+                    return self.deep_copy_value(&target, pos);
+                }
+            } else {
+                let mut value = self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?;
+
+                if !self.has_override_by_name_and_arguments(
+                    Some(mods),
+                    lib,
+                    fn_name,
+                    [value.type_id()],
+                    pub_only,
+                ) {
+                    return self.deep_copy_value(&value.into(), pos);
+                }
+
+                // Call the overriding function with the value already evaluated
+                return self
+                    .exec_fn_call(
+                        mods,
+                        state,
+                        lib,
+                        fn_name,
+                        hash_script,
+                        &mut [&mut value],
+                        false,
+                        false,
+                        pub_only,
+                        pos,
+                        None,
+                        def_val,
+                        level,
+                    )
+                    .map(|(v, _)| v);
+            }
+        }
+
         // Handle call() - Redirect function call
         let redirected;
         let mut args_expr = args_expr.as_ref();
@@ -991,7 +1757,12 @@ impl Engine {
                 let var_name = var_name.as_str().map_err(|err| {
                     self.make_type_mismatch_err::<ImmutableString>(err, args_expr[0].position())
                 })?;
-                return Ok(scope.contains(var_name).into());
+                let is_def = scope.contains(var_name)
+                    || self
+                        .global_constants
+                        .iter()
+                        .any(|m| m.contains_var(var_name));
+                return Ok(is_def.into());
             }
         }
 
@@ -1021,6 +1792,50 @@ impl Engine {
             }
         }
 
+        // Handle compile_expression()
+        #[cfg(not(feature = "no_object"))]
+        if self.allow_compile_expression
+            && name == crate::engine::FN_COMPILE_EXPRESSION
+            && args_expr.len() == 1
+        {
+            let hash_fn = calc_native_fn_hash(empty(), name, once(TypeId::of::<ImmutableString>()));
+
+            if !self.has_override(Some(mods), lib, hash_fn, hash_script, pub_only) {
+                let script =
+                    self.eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)?;
+                let script = script.as_str().map_err(|typ| {
+                    self.make_type_mismatch_err::<ImmutableString>(typ, args_expr[0].position())
+                })?;
+                let pos = args_expr[0].position();
+
+                self.inc_operations(state, pos)?;
+
+                return self.compile_expression_value(name, script, pos);
+            }
+        }
+
+        // Handle compile_check()
+        #[cfg(not(feature = "no_object"))]
+        if self.allow_compile_expression
+            && name == crate::engine::FN_COMPILE_CHECK
+            && args_expr.len() == 1
+        {
+            let hash_fn = calc_native_fn_hash(empty(), name, once(TypeId::of::<ImmutableString>()));
+
+            if !self.has_override(Some(mods), lib, hash_fn, hash_script, pub_only) {
+                let script =
+                    self.eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)?;
+                let script = script.as_str().map_err(|typ| {
+                    self.make_type_mismatch_err::<ImmutableString>(typ, args_expr[0].position())
+                })?;
+                let pos = args_expr[0].position();
+
+                self.inc_operations(state, pos)?;
+
+                return Ok(self.check_script(script).into());
+            }
+        }
+
         // Normal function call - except for Fn, curry, call and eval (handled above)
         let mut arg_values: StaticVec<_>;
         let mut args: StaticVec<_>;
@@ -1095,6 +1910,59 @@ impl Engine {
         .map(|(v, _)| v)
     }
 
+    /// Compile an expression for `compile_expression` into a [`CompiledExpr`] value.
+    #[cfg(not(feature = "no_object"))]
+    fn compile_expression_value(
+        &self,
+        fn_name: &str,
+        script: &str,
+        pos: Position,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        // Compile errors are catchable by the script
+        let ast = self.compile_expression(script).map_err(|err| {
+            EvalAltResult::ErrorInFunctionCall(fn_name.to_string(), String::new(), err.into(), pos)
+        })?;
+
+        Ok(Dynamic::from(CompiledExpr::new(ast)))
+    }
+
+    /// Parse a script for `compile_check`, returning a map with the `ok` flag and, on failure,
+    /// the details of the parse error.
+    #[cfg(not(feature = "no_object"))]
+    fn check_script(&self, script: &str) -> Map {
+        // Only parse the script - no optimization, so nothing is evaluated
+        let result = self.compile_with_scope_and_optimization_level(
+            &Default::default(),
+            &[script],
+            OptimizationLevel::None,
+        );
+
+        let mut map = Map::new();
+
+        match result {
+            Ok(_) => {
+                map.insert("ok".into(), true.into());
+            }
+            Err(ParseError(err, err_pos)) => {
+                map.insert("ok".into(), false.into());
+                map.insert("error".into(), err.to_string().into());
+                map.insert("error_type".into(), err.name().into());
+                map.insert(
+                    "line".into(),
+                    err_pos.line().map_or(Dynamic::UNIT, |v| (v as INT).into()),
+                );
+                map.insert(
+                    "position".into(),
+                    err_pos
+                        .position()
+                        .map_or(Dynamic::UNIT, |v| (v as INT).into()),
+                );
+            }
+        }
+
+        map
+    }
+
     /// Call a namespace-qualified function in normal function-call style.
     pub(crate) fn make_qualified_function_call(
         &self,
@@ -1190,6 +2058,12 @@ impl Engine {
             r => r,
         };
 
+        if let (Some(audit), Some(f)) = (state.audit.as_mut(), func) {
+            if f.is_native() {
+                audit.record_native(&format!("{}{}", namespace, fn_name), args.len());
+            }
+        }
+
         match func {
             #[cfg(not(feature = "no_function"))]
             Some(f) if f.is_script() => {
@@ -1215,12 +2089,28 @@ impl Engine {
 
                 state.source = source;
 
-                result
+                // Name the frame in the backtrace with the namespace
+                result.map_err(|err| match *err {
+                    EvalAltResult::ErrorInFunctionCall(name, src, err, pos) => {
+                        EvalAltResult::ErrorInFunctionCall(
+                            format!("{}{}", namespace, name),
+                            src,
+                            err,
+                            pos,
+                        )
+                        .into()
+                    }
+                    err => err.into(),
+                })
             }
             Some(f) if f.is_plugin_fn() => f
                 .get_plugin_fn()
                 .clone()
-                .call((self, module.id_raw(), &*mods, lib).into(), args.as_mut()),
+                .call(
+                    (self, module.id_raw(), &*mods, lib, pos).into(),
+                    args.as_mut(),
+                )
+                .map_err(|err| err.fill_position(pos)),
             Some(f) if f.is_native() => {
                 if !f.is_method() {
                     // Clone first argument
@@ -1231,7 +2121,11 @@ impl Engine {
                     }
                 }
 
-                f.get_native_fn()((self, module.id_raw(), &*mods, lib).into(), args.as_mut())
+                f.get_native_fn()(
+                    (self, module.id_raw(), &*mods, lib, pos).into(),
+                    args.as_mut(),
+                )
+                .map_err(|err| err.fill_position(pos))
             }
             Some(f) => unreachable!("unknown function type: {:?}", f),
             None if def_val.is_some() => Ok(def_val.unwrap().clone()),
@@ -13,8 +13,8 @@ use crate::stdlib::{
 };
 use crate::token::is_valid_identifier;
 use crate::{
-    calc_script_fn_hash, Dynamic, Engine, EvalAltResult, EvalContext, ImmutableString, Module,
-    Position, StaticVec,
+    calc_script_fn_hash, DebuggerCommand, Dynamic, Engine, EvalAltResult, EvalContext,
+    ImmutableString, Module, Position, StaticVec,
 };
 
 #[cfg(not(feature = "sync"))]
@@ -44,11 +44,9 @@ pub type Shared<T> = Rc<T>;
 pub type Shared<T> = Arc<T>;
 
 /// Synchronized shared object.
-#[cfg(not(feature = "no_closure"))]
 #[cfg(not(feature = "sync"))]
 pub type Locked<T> = crate::stdlib::cell::RefCell<T>;
 /// Synchronized shared object.
-#[cfg(not(feature = "no_closure"))]
 #[cfg(feature = "sync")]
 pub type Locked<T> = crate::stdlib::sync::RwLock<T>;
 
@@ -59,6 +57,36 @@ pub struct NativeCallContext<'e, 's, 'a, 'm, 'pm: 'm> {
     source: Option<&'s str>,
     pub(crate) mods: Option<&'a Imports>,
     pub(crate) lib: &'m [&'pm Module],
+    pos: Position,
+}
+
+impl<'e, 's, 'a, 'm, 'pm: 'm, M: AsRef<[&'pm Module]> + ?Sized>
+    From<(
+        &'e Engine,
+        &'s Option<ImmutableString>,
+        &'a Imports,
+        &'m M,
+        Position,
+    )> for NativeCallContext<'e, 's, 'a, 'm, 'pm>
+{
+    #[inline(always)]
+    fn from(
+        value: (
+            &'e Engine,
+            &'s Option<ImmutableString>,
+            &'a Imports,
+            &'m M,
+            Position,
+        ),
+    ) -> Self {
+        Self {
+            engine: value.0,
+            source: value.1.as_ref().map(|s| s.as_str()),
+            mods: Some(value.2),
+            lib: value.3.as_ref(),
+            pos: value.4,
+        }
+    }
 }
 
 impl<'e, 's, 'a, 'm, 'pm: 'm, M: AsRef<[&'pm Module]> + ?Sized>
@@ -72,6 +100,7 @@ impl<'e, 's, 'a, 'm, 'pm: 'm, M: AsRef<[&'pm Module]> + ?Sized>
             source: value.1.as_ref().map(|s| s.as_str()),
             mods: Some(value.2),
             lib: value.3.as_ref(),
+            pos: Position::NONE,
         }
     }
 }
@@ -86,6 +115,7 @@ impl<'e, 'm, 'pm: 'm, M: AsRef<[&'pm Module]> + ?Sized> From<(&'e Engine, &'m M)
             source: None,
             mods: None,
             lib: value.1.as_ref(),
+            pos: Position::NONE,
         }
     }
 }
@@ -99,6 +129,7 @@ impl<'e, 's, 'a, 'm, 'pm> NativeCallContext<'e, 's, 'a, 'm, 'pm> {
             source: None,
             mods: None,
             lib: lib.as_ref(),
+            pos: Position::NONE,
         }
     }
     /// _(INTERNALS)_ Create a new [`NativeCallContext`].
@@ -117,6 +148,7 @@ impl<'e, 's, 'a, 'm, 'pm> NativeCallContext<'e, 's, 'a, 'm, 'pm> {
             source: source.as_ref().map(|s| s.as_str()),
             mods: Some(imports),
             lib: lib.as_ref(),
+            pos: Position::NONE,
         }
     }
     /// The current [`Engine`].
@@ -124,11 +156,20 @@ impl<'e, 's, 'a, 'm, 'pm> NativeCallContext<'e, 's, 'a, 'm, 'pm> {
     pub fn engine(&self) -> &'e Engine {
         self.engine
     }
-    /// The current source.
+    /// The current source, i.e. the source of the [`AST`][crate::AST] (set via
+    /// [`AST::set_source`][crate::AST::set_source]) or the module containing the function being called.
     #[inline(always)]
     pub fn source<'z: 's>(&'z self) -> Option<&'s str> {
         self.source
     }
+    /// [Position][`Position`] of the function call in the script.
+    ///
+    /// Errors returned by the function without a position (i.e. with [`Position::NONE`])
+    /// are automatically given this position.
+    #[inline(always)]
+    pub fn position(&self) -> Position {
+        self.pos
+    }
     /// _(INTERNALS)_ The current set of modules imported via `import` statements.
     /// Available under the `internals` feature only.
     #[cfg(feature = "internals")]
@@ -372,6 +413,25 @@ pub type FnAny =
 /// A standard function that gets an iterator from a type.
 pub type IteratorFn = fn(Dynamic) -> Box<dyn Iterator<Item = Dynamic>>;
 
+/// A function that gets an iterator from a mutable reference to a value, yielding items lazily.
+#[cfg(not(feature = "sync"))]
+pub type IteratorRefFn =
+    dyn Fn(&mut Dynamic) -> Box<dyn Iterator<Item = Result<Dynamic, Box<EvalAltResult>>>>;
+/// A function that gets an iterator from a mutable reference to a value, yielding items lazily.
+#[cfg(feature = "sync")]
+pub type IteratorRefFn = dyn Fn(&mut Dynamic) -> Box<dyn Iterator<Item = Result<Dynamic, Box<EvalAltResult>>>>
+    + Send
+    + Sync;
+
+/// A type iterator registered into a [`Module`].
+#[derive(Clone)]
+pub enum TypeIterator {
+    /// An iterator function consuming the value.
+    Fn(IteratorFn),
+    /// An iterator function taking a mutable reference to the value.
+    Ref(Shared<IteratorRefFn>),
+}
+
 #[cfg(not(feature = "sync"))]
 pub type FnPlugin = dyn PluginFunction;
 #[cfg(feature = "sync")]
@@ -384,12 +444,20 @@ pub type OnProgressCallback = Box<dyn Fn(u64) -> Option<Dynamic> + 'static>;
 #[cfg(feature = "sync")]
 pub type OnProgressCallback = Box<dyn Fn(u64) -> Option<Dynamic> + Send + Sync + 'static>;
 
+/// A standard callback function for debugging scripts.
+#[cfg(not(feature = "sync"))]
+pub type OnDebuggerCallback = Box<dyn Fn(&EvalContext, Position) -> DebuggerCommand + 'static>;
+/// A standard callback function for debugging scripts.
+#[cfg(feature = "sync")]
+pub type OnDebuggerCallback =
+    Box<dyn Fn(&EvalContext, Position) -> DebuggerCommand + Send + Sync + 'static>;
+
 /// A standard callback function for printing.
 #[cfg(not(feature = "sync"))]
-pub type OnPrintCallback = Box<dyn Fn(&str) + 'static>;
+pub type OnPrintCallback = Box<dyn Fn(&str, Option<&str>, Position) + 'static>;
 /// A standard callback function for printing.
 #[cfg(feature = "sync")]
-pub type OnPrintCallback = Box<dyn Fn(&str) + Send + Sync + 'static>;
+pub type OnPrintCallback = Box<dyn Fn(&str, Option<&str>, Position) + Send + Sync + 'static>;
 
 /// A standard callback function for debugging.
 #[cfg(not(feature = "sync"))]
@@ -398,6 +466,22 @@ pub type OnDebugCallback = Box<dyn Fn(&str, Option<&str>, Position) + 'static>;
 #[cfg(feature = "sync")]
 pub type OnDebugCallback = Box<dyn Fn(&str, Option<&str>, Position) + Send + Sync + 'static>;
 
+/// A standard callback function for filtering module imports.
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "sync"))]
+pub type OnImportCallback = Box<dyn Fn(&str) -> bool + 'static>;
+/// A standard callback function for filtering module imports.
+#[cfg(not(feature = "no_module"))]
+#[cfg(feature = "sync")]
+pub type OnImportCallback = Box<dyn Fn(&str) -> bool + Send + Sync + 'static>;
+
+/// A standard callback function for filtering the errors caught by `try` ... `catch`.
+#[cfg(not(feature = "sync"))]
+pub type OnCatchCallback = Box<dyn Fn(&EvalAltResult) -> bool + 'static>;
+/// A standard callback function for filtering the errors caught by `try` ... `catch`.
+#[cfg(feature = "sync")]
+pub type OnCatchCallback = Box<dyn Fn(&EvalAltResult) -> bool + Send + Sync + 'static>;
+
 /// A standard callback function for variable access.
 #[cfg(not(feature = "sync"))]
 pub type OnVarCallback =
@@ -411,6 +495,26 @@ pub type OnVarCallback = Box<
         + 'static,
 >;
 
+/// A standard callback function for variable assignment.
+#[cfg(not(feature = "sync"))]
+pub type OnVarWriteCallback =
+    Box<dyn Fn(&str, &Dynamic, &EvalContext) -> Result<bool, Box<EvalAltResult>> + 'static>;
+/// A standard callback function for variable assignment.
+#[cfg(feature = "sync")]
+pub type OnVarWriteCallback = Box<
+    dyn Fn(&str, &Dynamic, &EvalContext) -> Result<bool, Box<EvalAltResult>>
+        + Send
+        + Sync
+        + 'static,
+>;
+
+/// A standard callback function for reporting large implicit copies of variables.
+#[cfg(not(feature = "sync"))]
+pub type OnLargeCopyCallback = Box<dyn Fn(&str, usize, Position) + 'static>;
+/// A standard callback function for reporting large implicit copies of variables.
+#[cfg(feature = "sync")]
+pub type OnLargeCopyCallback = Box<dyn Fn(&str, usize, Position) + Send + Sync + 'static>;
+
 /// A type encapsulating a function callable by Rhai.
 #[derive(Clone)]
 pub enum CallableFunction {
@@ -40,6 +40,32 @@ pub trait RegisterFn<FN, ARGS, RET> {
     /// # }
     /// ```
     fn register_fn(&mut self, name: &str, f: FN) -> &mut Self;
+
+    /// Register a custom pure function with the [`Engine`].
+    ///
+    /// A pure function always returns the same result for the same arguments and has no side effects.
+    /// When optimizing a script (at [`OptimizationLevel::Simple`][crate::OptimizationLevel::Simple] or above),
+    /// calls to it with only constant arguments are evaluated and replaced with the result.
+    /// A call that returns an error is left alone.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, RegisterFn};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_fn_pure("double", |x: i64| x * 2);
+    ///
+    /// // 'double(21)' is evaluated during compilation
+    /// let ast = engine.compile("double(21)")?;
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn register_fn_pure(&mut self, name: &str, f: FN) -> &mut Self;
 }
 
 /// Trait to register fallible custom functions returning [`Result`]`<`[`Dynamic`]`, `[`Box`]`<`[`EvalAltResult`]`>>` with the [`Engine`].
@@ -70,6 +96,11 @@ pub trait RegisterResultFn<FN, ARGS> {
     ///         .expect_err("expecting division by zero error!");
     /// ```
     fn register_result_fn(&mut self, name: &str, f: FN) -> &mut Self;
+
+    /// Register a custom fallible pure function with the [`Engine`].
+    ///
+    /// See [`register_fn_pure`][RegisterFn::register_fn_pure] for details.
+    fn register_result_fn_pure(&mut self, name: &str, f: FN) -> &mut Self;
 }
 
 // These types are used to build a unique _marker_ tuple type for each combination
@@ -193,6 +224,16 @@ macro_rules! def_register {
                 );
                 self
             }
+
+            #[inline]
+            fn register_fn_pure(&mut self, name: &str, f: FN) -> &mut Self {
+                let hash_fn = self.global_namespace.set_fn(name, FnNamespace::Global, FnAccess::Public, None,
+                    &[$(map_type_id::<$par>()),*],
+                    CallableFunction::$abi(make_func!(f : map_dynamic ; $($par => $let => $clone => $arg),*))
+                );
+                self.global_namespace.update_fn_foldable(hash_fn, true);
+                self
+            }
         }
 
         impl<
@@ -208,6 +249,16 @@ macro_rules! def_register {
                 );
                 self
             }
+
+            #[inline]
+            fn register_result_fn_pure(&mut self, name: &str, f: FN) -> &mut Self {
+                let hash_fn = self.global_namespace.set_fn(name, FnNamespace::Global, FnAccess::Public, None,
+                    &[$(map_type_id::<$par>()),*],
+                    CallableFunction::$abi(make_func!(f : map_result ; $($par => $let => $clone => $arg),*))
+                );
+                self.global_namespace.update_fn_foldable(hash_fn, true);
+                self
+            }
         }
 
         //def_register!(imp_pop $($par => $mark => $param),*);
@@ -62,17 +62,27 @@
 extern crate alloc;
 
 mod ast;
+mod audit;
+#[cfg(feature = "binary_ast")]
+mod binary_ast;
+mod compile_cache;
+mod debugger;
 mod dynamic;
 mod engine;
 mod engine_api;
 mod engine_settings;
+mod eval_options;
 mod fn_args;
 mod fn_call;
 mod fn_func;
 mod fn_native;
 mod fn_register;
+mod metrics;
 mod module;
+mod numeric;
+mod object_map;
 mod optimize;
+mod output;
 pub mod packages;
 mod parse_error;
 mod parser;
@@ -84,6 +94,7 @@ mod serde_impl;
 mod stdlib;
 mod syntax;
 mod token;
+mod typed_array;
 mod r#unsafe;
 mod utils;
 
@@ -119,17 +130,23 @@ pub type FLOAT = f64;
 #[cfg(feature = "f32_float")]
 pub type FLOAT = f32;
 
-pub use ast::{FnAccess, ScriptFnMetadata, AST};
+pub use ast::{FnAccess, FnCallSite, FnCalls, AST};
+pub use audit::CallAudit;
+pub use debugger::DebuggerCommand;
 pub use dynamic::Dynamic;
 pub use engine::{Engine, EvalContext};
+pub use eval_options::EvalOptions;
 pub use fn_native::{FnPtr, NativeCallContext, Shared};
 pub use fn_register::{RegisterFn, RegisterResultFn};
+pub use metrics::EvalMetrics;
 pub use module::{FnNamespace, Module};
+pub use numeric::NumericOps;
+pub use output::{CapturedOutput, OutputEntry, OutputKind};
 pub use parse_error::{LexError, ParseError, ParseErrorType};
-pub use result::EvalAltResult;
+pub use result::{EvalAltResult, FrameInfo};
 pub use scope::Scope;
-pub use syntax::Expression;
-pub use token::Position;
+pub use syntax::{Expression, ParseContext};
+pub use token::{Associativity, Position, Span, TokenKind};
 pub use utils::ImmutableString;
 
 #[cfg(not(feature = "no_closure"))]
@@ -146,17 +163,26 @@ pub use rhai_codegen::*;
 #[cfg(not(feature = "no_function"))]
 pub use fn_func::Func;
 
+#[cfg(not(feature = "no_function"))]
+pub use ast::ScriptFnMetadata;
+
+/// Inclusive range of characters, e.g. `'a'..='z'` in scripts.
+pub type CharRange = stdlib::ops::RangeInclusive<char>;
+
 /// Variable-sized array of [`Dynamic`] values.
 ///
 /// Not available under the `no_index` feature.
 #[cfg(not(feature = "no_index"))]
 pub type Array = stdlib::vec::Vec<Dynamic>;
 
-/// Hash map of [`Dynamic`] values with [`ImmutableString`] keys.
-///
-/// Not available under the `no_object` feature.
+#[cfg(not(feature = "no_index"))]
+pub use typed_array::TypedArray;
+
+#[cfg(not(feature = "no_object"))]
+pub use object_map::Map;
+
 #[cfg(not(feature = "no_object"))]
-pub type Map = stdlib::collections::HashMap<ImmutableString, Dynamic>;
+pub use ast::CompiledExpr;
 
 #[cfg(not(feature = "no_module"))]
 pub use module::ModuleResolver;
@@ -165,6 +191,9 @@ pub use module::ModuleResolver;
 #[cfg(not(feature = "no_module"))]
 pub use crate::module::resolvers as module_resolvers;
 
+#[cfg(feature = "binary_ast")]
+pub use binary_ast::{BinaryAstError, BINARY_AST_VERSION};
+
 /// _(SERDE)_ Serialization and deserialization support for [`serde`](https://crates.io/crates/serde).
 /// Exported under the `serde` feature.
 #[cfg(feature = "serde")]
@@ -179,16 +208,31 @@ pub use optimize::OptimizationLevel;
 // Expose internal data structures.
 #[cfg(feature = "internals")]
 #[deprecated = "this type is volatile and may change"]
-pub use token::{get_next_token, parse_string_literal, InputStream, Token, TokenizeState};
+pub use token::{
+    get_next_token, parse_raw_string_literal, parse_string_literal, InputStream, Token,
+    TokenizeState,
+};
+
+#[cfg(feature = "internals")]
+#[deprecated = "this type is volatile and may change"]
+pub use ast::{
+    BinaryExpr, CatchClause, CustomExpr, Expr, FnCallExpr, Ident, MapKey, ReturnType, ScriptFnDef,
+    Stmt, SwitchCases, SwitchRange,
+};
 
 #[cfg(feature = "internals")]
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
 #[deprecated = "this type is volatile and may change"]
-pub use ast::{BinaryExpr, CustomExpr, Expr, FnCallExpr, Ident, ReturnType, ScriptFnDef, Stmt};
+pub use ast::Pattern;
 
 #[cfg(feature = "internals")]
 #[deprecated = "this type is volatile and may change"]
 pub use engine::{Imports, State as EvalState};
 
+#[cfg(feature = "internals")]
+#[deprecated = "this type is volatile and may change"]
+pub use debugger::Debugger;
+
 #[cfg(feature = "internals")]
 #[cfg(not(feature = "unchecked"))]
 pub use engine::Limits;
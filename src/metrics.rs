@@ -0,0 +1,62 @@
+//! Module defining the metrics of the work done during an evaluation.
+
+use crate::stdlib::time::Duration;
+
+/// Metrics of the work done by a script during an evaluation.
+///
+/// Filled by [`Engine::eval_with_metrics`][crate::Engine::eval_with_metrics] and
+/// [`Engine::eval_ast_with_metrics`][crate::Engine::eval_ast_with_metrics], even when the
+/// evaluation fails.
+///
+/// The number of operations is the same count that is checked against
+/// [`max_operations`][crate::Engine::max_operations] and reported to the
+/// [progress callback][crate::Engine::on_progress].
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use rhai::{Engine, EvalMetrics, Scope, INT};
+///
+/// let engine = Engine::new();
+///
+/// let mut metrics = EvalMetrics::new();
+///
+/// let result: INT = engine.eval_with_metrics(
+///     &mut Scope::new(),
+///     "let x = 0; for i in range(0, 10) { x += i; } x",
+///     &mut metrics,
+/// )?;
+///
+/// assert_eq!(result, 45);
+/// assert!(metrics.operations > 10);
+/// assert_eq!(metrics.max_call_depth, 0);
+/// assert_eq!(metrics.modules, 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct EvalMetrics {
+    /// Number of operations performed.
+    pub operations: u64,
+    /// Deepest nesting level of script-defined function calls, zero if no function is called.
+    pub max_call_depth: usize,
+    /// Number of modules loaded via `import` statements.
+    pub modules: usize,
+    /// Time taken to parse the script.
+    ///
+    /// Always [`None`] when evaluating a pre-compiled [`AST`][crate::AST], or under `no_std`.
+    pub parse_time: Option<Duration>,
+    /// Time taken to evaluate the script.
+    ///
+    /// Always [`None`] under `no_std`.
+    pub eval_time: Option<Duration>,
+}
+
+impl EvalMetrics {
+    /// Create a new [`EvalMetrics`] with all counters set to zero.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
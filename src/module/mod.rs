@@ -2,7 +2,9 @@
 
 use crate::ast::{FnAccess, Ident};
 use crate::dynamic::Variant;
-use crate::fn_native::{shared_take_or_clone, CallableFunction, FnCallArgs, IteratorFn, SendSync};
+use crate::fn_native::{
+    shared_take_or_clone, CallableFunction, FnCallArgs, IteratorFn, SendSync, TypeIterator,
+};
 use crate::fn_register::by_value as cast_arg;
 use crate::stdlib::{
     any::TypeId,
@@ -25,13 +27,6 @@ use crate::{
 #[cfg(not(feature = "no_function"))]
 use crate::ast::ScriptFnDef;
 
-#[cfg(not(feature = "no_index"))]
-use crate::Array;
-
-#[cfg(not(feature = "no_index"))]
-#[cfg(not(feature = "no_object"))]
-use crate::Map;
-
 /// A type representing the namespace of a function.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum FnNamespace {
@@ -83,9 +78,20 @@ pub struct FuncInfo {
     pub param_types: StaticVec<TypeId>,
     /// Parameter names (if available).
     pub param_names: StaticVec<ImmutableString>,
+    /// Can calls to this function with constant arguments be evaluated during optimization?
+    pub foldable: bool,
 }
 
 impl FuncInfo {
+    /// Is this a script-defined function set under a smaller number of arguments than its
+    /// parameters, i.e. called with default parameter values?
+    #[inline(always)]
+    pub(crate) fn is_default_params_alias(&self) -> bool {
+        #[cfg(not(feature = "no_function"))]
+        return self.func.is_script() && self.params < self.func.get_fn_def().num_fixed_params();
+        #[cfg(feature = "no_function")]
+        return false;
+    }
     /// Generate a signature of the function.
     pub fn gen_signature(&self) -> String {
         let mut sig = format!("{}(", self.name);
@@ -143,11 +149,13 @@ pub struct Module {
     /// including those in sub-modules.
     all_functions: HashMap<NonZeroU64, CallableFunction, StraightHasherBuilder>,
     /// Iterator functions, keyed by the type producing the iterator.
-    type_iterators: HashMap<TypeId, IteratorFn>,
+    type_iterators: HashMap<TypeId, TypeIterator>,
     /// Flattened collection of iterator functions, including those in sub-modules.
-    all_type_iterators: HashMap<TypeId, IteratorFn>,
+    all_type_iterators: HashMap<TypeId, TypeIterator>,
     /// Is the module indexed?
     indexed: bool,
+    /// Is the module part of the standard library?
+    standard: bool,
 }
 
 impl Default for Module {
@@ -162,6 +170,7 @@ impl Default for Module {
             type_iterators: Default::default(),
             all_type_iterators: Default::default(),
             indexed: false,
+            standard: false,
         }
     }
 }
@@ -314,11 +323,24 @@ impl Module {
         self.indexed
     }
 
+    /// Is the [`Module`] part of the standard library (i.e. a built-in package)?
+    #[inline(always)]
+    pub(crate) fn is_standard(&self) -> bool {
+        self.standard
+    }
+
+    /// Mark the [`Module`] as part of the standard library.
+    #[inline(always)]
+    pub(crate) fn set_standard(&mut self, standard: bool) -> &mut Self {
+        self.standard = standard;
+        self
+    }
+
     /// Generate signatures for all the functions in the module.
     pub fn gen_fn_signatures<'a>(&'a self) -> impl Iterator<Item = String> + 'a {
         self.functions
             .values()
-            .filter(|FuncInfo { access, .. }| !access.is_private())
+            .filter(|f| !f.access.is_private() && !f.is_default_params_alias())
             .map(FuncInfo::gen_signature)
     }
 
@@ -411,30 +433,52 @@ impl Module {
     /// Set a script-defined function into the module.
     ///
     /// If there is an existing function of the same name and number of arguments, it is replaced.
+    ///
+    /// A function with default parameter values is also set under each smaller number of
+    /// arguments it can be called with, unless another function already takes exactly that
+    /// number of arguments.  A variadic function is set under the number of parameters before
+    /// its rest parameter.
     #[cfg(not(feature = "no_function"))]
     #[inline]
     pub(crate) fn set_script_fn(&mut self, fn_def: impl Into<Shared<ScriptFnDef>>) -> NonZeroU64 {
-        let fn_def = fn_def.into();
+        let fn_def: Shared<ScriptFnDef> = fn_def.into();
 
-        // None + function name + number of arguments.
-        let num_params = fn_def.params.len();
-        let hash_script = crate::calc_script_fn_hash(empty(), &fn_def.name, num_params).unwrap();
         let mut param_names: StaticVec<_> = fn_def.params.iter().cloned().collect();
         param_names.push("Dynamic".into());
-        self.functions.insert(
-            hash_script,
-            FuncInfo {
-                name: fn_def.name.to_string(),
-                namespace: FnNamespace::Internal,
-                access: fn_def.access,
-                params: num_params,
-                param_types: Default::default(),
-                param_names,
-                func: fn_def.into(),
-            },
-        );
+
+        let mut hash_script = None;
+
+        // Set the full number of arguments last, replacing any existing function
+        for num_params in fn_def.min_params()..=fn_def.num_fixed_params() {
+            // None + function name + number of arguments.
+            let hash = crate::calc_script_fn_hash(empty(), &fn_def.name, num_params).unwrap();
+
+            if num_params < fn_def.num_fixed_params() {
+                match self.functions.get(&hash) {
+                    Some(f) if !f.is_default_params_alias() => continue,
+                    _ => (),
+                }
+            }
+
+            self.functions.insert(
+                hash,
+                FuncInfo {
+                    name: fn_def.name.to_string(),
+                    namespace: FnNamespace::Internal,
+                    access: fn_def.access,
+                    params: num_params,
+                    param_types: Default::default(),
+                    param_names: param_names.clone(),
+                    func: fn_def.clone().into(),
+                    foldable: false,
+                },
+            );
+
+            hash_script = Some(hash);
+        }
+
         self.indexed = false;
-        hash_script
+        hash_script.unwrap()
     }
 
     /// Get a script-defined function in the module based on name and number of parameters.
@@ -463,6 +507,32 @@ impl Module {
             .map(|FuncInfo { func, .. }| func.get_fn_def())
     }
 
+    /// Get a variadic script-defined function in the module that can be called with a number of
+    /// arguments.
+    ///
+    /// If more than one function matches, the one with the most parameters before its rest
+    /// parameter is returned.
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_index"))]
+    #[inline]
+    pub(crate) fn get_variadic_script_fn(
+        &self,
+        name: &str,
+        num_args: usize,
+        public_only: bool,
+    ) -> Option<&ScriptFnDef> {
+        self.functions
+            .values()
+            .filter(|f| {
+                f.func.is_script()
+                    && (!public_only || f.access == FnAccess::Public)
+                    && f.name == name
+            })
+            .map(|f| f.func.get_fn_def())
+            .filter(|f| f.variadic && f.num_fixed_params() <= num_args)
+            .max_by_key(|f| f.num_fixed_params())
+    }
+
     /// Get a mutable reference to the underlying [`HashMap`] of sub-modules.
     ///
     /// ## Warning
@@ -567,6 +637,16 @@ impl Module {
         }
     }
 
+    /// Does a function with a particular name, taking any number of parameters, exist in the module?
+    ///
+    /// If `global_only` is `true`, only functions in the global namespace are considered.
+    #[inline]
+    pub(crate) fn contains_fn_with_name(&self, name: &str, global_only: bool) -> bool {
+        self.functions
+            .values()
+            .any(|f| f.name == name && (!global_only || f.namespace.is_global()))
+    }
+
     /// Update the metadata (parameter names/types and return type) of a registered function.
     ///
     /// The [`NonZeroU64`] hash is calculated either by the function [`crate::calc_native_fn_hash`] or
@@ -603,6 +683,28 @@ impl Module {
         self
     }
 
+    /// Update whether calls to a registered Rust function with constant arguments can be evaluated
+    /// during optimization, replacing each call with its result.
+    ///
+    /// Only mark a function as foldable when it always returns the same result for the same
+    /// arguments and has no side effects.
+    ///
+    /// The [`NonZeroU64`] hash is calculated by the function [`crate::calc_native_fn_hash`].
+    pub fn update_fn_foldable(&mut self, hash_fn: NonZeroU64, foldable: bool) -> &mut Self {
+        if let Some(f) = self.functions.get_mut(&hash_fn) {
+            f.foldable = foldable;
+        }
+        self
+    }
+
+    /// Can calls to a registered Rust function with constant arguments be evaluated during optimization?
+    ///
+    /// Returns [`None`] if the function does not exist.
+    #[inline(always)]
+    pub(crate) fn is_fn_foldable(&self, hash_fn: NonZeroU64) -> Option<bool> {
+        self.functions.get(&hash_fn).map(|f| f.foldable)
+    }
+
     /// Set a Rust function into the module, returning a hash key.
     ///
     /// If there is an existing Rust function of the same hash, it is replaced.
@@ -650,6 +752,7 @@ impl Module {
                     Default::default()
                 },
                 func: func.into(),
+                foldable: false,
             },
         );
 
@@ -1023,8 +1126,8 @@ impl Module {
     ///
     /// # Panics
     ///
-    /// Panics if the type is [`Array`] or [`Map`].
-    /// Indexers for arrays, object maps and strings cannot be registered.
+    /// Panics if the type is [`String`], [`ImmutableString`] or `&str`.
+    /// Indexers for strings cannot be registered.
     ///
     /// # Function Metadata
     ///
@@ -1047,13 +1150,6 @@ impl Module {
         &mut self,
         func: impl Fn(&mut A, B) -> Result<T, Box<EvalAltResult>> + SendSync + 'static,
     ) -> NonZeroU64 {
-        if TypeId::of::<A>() == TypeId::of::<Array>() {
-            panic!("Cannot register indexer for arrays.");
-        }
-        #[cfg(not(feature = "no_object"))]
-        if TypeId::of::<A>() == TypeId::of::<Map>() {
-            panic!("Cannot register indexer for object maps.");
-        }
         if TypeId::of::<A>() == TypeId::of::<String>()
             || TypeId::of::<A>() == TypeId::of::<&str>()
             || TypeId::of::<A>() == TypeId::of::<ImmutableString>()
@@ -1173,8 +1269,8 @@ impl Module {
     ///
     /// # Panics
     ///
-    /// Panics if the type is [`Array`] or [`Map`].
-    /// Indexers for arrays, object maps and strings cannot be registered.
+    /// Panics if the type is [`String`], [`ImmutableString`] or `&str`.
+    /// Indexers for strings cannot be registered.
     ///
     /// # Function Metadata
     ///
@@ -1198,13 +1294,6 @@ impl Module {
         &mut self,
         func: impl Fn(&mut A, B, C) -> Result<(), Box<EvalAltResult>> + SendSync + 'static,
     ) -> NonZeroU64 {
-        if TypeId::of::<A>() == TypeId::of::<Array>() {
-            panic!("Cannot register indexer for arrays.");
-        }
-        #[cfg(not(feature = "no_object"))]
-        if TypeId::of::<A>() == TypeId::of::<Map>() {
-            panic!("Cannot register indexer for object maps.");
-        }
         if TypeId::of::<A>() == TypeId::of::<String>()
             || TypeId::of::<A>() == TypeId::of::<&str>()
             || TypeId::of::<A>() == TypeId::of::<ImmutableString>()
@@ -1238,8 +1327,8 @@ impl Module {
     ///
     /// # Panics
     ///
-    /// Panics if the type is [`Array`] or [`Map`].
-    /// Indexers for arrays, object maps and strings cannot be registered.
+    /// Panics if the type is [`String`], [`ImmutableString`] or `&str`.
+    /// Indexers for strings cannot be registered.
     ///
     /// # Function Metadata
     ///
@@ -1482,8 +1571,8 @@ impl Module {
         other.functions.iter().for_each(|(&k, v)| {
             self.functions.entry(k).or_insert_with(|| v.clone());
         });
-        other.type_iterators.iter().for_each(|(&k, &v)| {
-            self.type_iterators.entry(k).or_insert(v);
+        other.type_iterators.iter().for_each(|(&k, v)| {
+            self.type_iterators.entry(k).or_insert_with(|| v.clone());
         });
         self.all_functions.clear();
         self.all_variables.clear();
@@ -1544,7 +1633,8 @@ impl Module {
                 .map(|(&k, v)| (k, v.clone())),
         );
 
-        self.type_iterators.extend(other.type_iterators.iter());
+        self.type_iterators
+            .extend(other.type_iterators.iter().map(|(&k, v)| (k, v.clone())));
         self.all_functions.clear();
         self.all_variables.clear();
         self.all_type_iterators.clear();
@@ -1611,7 +1701,9 @@ impl Module {
     #[cfg(not(feature = "no_function"))]
     #[inline(always)]
     pub(crate) fn iter_fn(&self) -> impl Iterator<Item = &FuncInfo> {
-        self.functions.values()
+        self.functions
+            .values()
+            .filter(|f| !f.is_default_params_alias())
     }
 
     /// Get an iterator over all script-defined functions in the module.
@@ -1627,24 +1719,27 @@ impl Module {
     pub(crate) fn iter_script_fn<'a>(
         &'a self,
     ) -> impl Iterator<Item = (FnNamespace, FnAccess, &str, usize, &ScriptFnDef)> + 'a {
-        self.functions.values().filter(|f| f.func.is_script()).map(
-            |FuncInfo {
-                 namespace,
-                 access,
-                 name,
-                 params,
-                 func,
-                 ..
-             }| {
-                (
-                    *namespace,
-                    *access,
-                    name.as_str(),
-                    *params,
-                    func.get_fn_def(),
-                )
-            },
-        )
+        self.functions
+            .values()
+            .filter(|f| f.func.is_script() && !f.is_default_params_alias())
+            .map(
+                |FuncInfo {
+                     namespace,
+                     access,
+                     name,
+                     params,
+                     func,
+                     ..
+                 }| {
+                    (
+                        *namespace,
+                        *access,
+                        name.as_str(),
+                        *params,
+                        func.get_fn_def(),
+                    )
+                },
+            )
     }
 
     /// Get an iterator over all script-defined functions in the module.
@@ -1660,15 +1755,18 @@ impl Module {
     pub fn iter_script_fn_info(
         &self,
     ) -> impl Iterator<Item = (FnNamespace, FnAccess, &str, usize)> {
-        self.functions.values().filter(|f| f.func.is_script()).map(
-            |FuncInfo {
-                 name,
-                 namespace,
-                 access,
-                 params,
-                 ..
-             }| (*namespace, *access, name.as_str(), *params),
-        )
+        self.functions
+            .values()
+            .filter(|f| f.func.is_script() && !f.is_default_params_alias())
+            .map(
+                |FuncInfo {
+                     name,
+                     namespace,
+                     access,
+                     params,
+                     ..
+                 }| (*namespace, *access, name.as_str(), *params),
+            )
     }
 
     /// Get an iterator over all script-defined functions in the module.
@@ -1689,6 +1787,35 @@ impl Module {
         self.iter_script_fn()
     }
 
+    /// Rewrite every script-defined function in the module.
+    ///
+    /// A function definition shared by multiple entries (e.g. aliases for default parameter
+    /// values) is rewritten only once.
+    #[cfg(feature = "internals")]
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) fn transform_script_fn(&mut self, mut transform: impl FnMut(&mut ScriptFnDef)) {
+        let mut done: Vec<(Shared<ScriptFnDef>, Shared<ScriptFnDef>)> = Default::default();
+
+        self.functions.values_mut().for_each(|f| {
+            if let CallableFunction::Script(ref mut fn_def) = f.func {
+                match done.iter().find(|(old, _)| Shared::ptr_eq(old, fn_def)) {
+                    Some((_, new_def)) => *fn_def = new_def.clone(),
+                    None => {
+                        let mut new_def = fn_def.as_ref().clone();
+                        transform(&mut new_def);
+                        let old = crate::stdlib::mem::replace(fn_def, new_def.into());
+                        done.push((old, fn_def.clone()));
+                    }
+                }
+            }
+        });
+
+        if self.indexed {
+            self.indexed = false;
+            self.build_index();
+        }
+    }
+
     /// Create a new module by evaluating an [`AST`][crate::AST].
     ///
     /// The entire [`AST`][crate::AST] is encapsulated into each function, allowing functions
@@ -1711,16 +1838,66 @@ impl Module {
     /// # }
     /// ```
     #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
     pub fn eval_ast_as_new(
+        scope: crate::Scope,
+        ast: &crate::AST,
+        engine: &crate::Engine,
+    ) -> Result<Self, Box<EvalAltResult>> {
+        Self::eval_ast_as_new_with_state(scope, ast, engine, &mut Default::default(), 0)
+    }
+    /// Create a new module by evaluating an [`AST`][crate::AST], as part of the evaluation
+    /// importing the module.
+    ///
+    /// Used by [module resolvers][crate::ModuleResolver] in
+    /// [`resolve_raw`][crate::ModuleResolver::resolve_raw].  Operations and levels of function
+    /// calls within the module count towards the limits of the evaluation.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn eval_ast_as_new_raw(
+        scope: crate::Scope,
+        ast: &crate::AST,
+        engine: &crate::Engine,
+        ctx: &mut crate::module_resolvers::ResolveContext,
+    ) -> Result<Self, Box<EvalAltResult>> {
+        Self::eval_ast_as_new_with_state(scope, ast, engine, ctx.state, ctx.level)
+    }
+    /// Create a new module by evaluating an [`AST`][crate::AST] with the limits of an evaluation.
+    #[cfg(not(feature = "no_module"))]
+    fn eval_ast_as_new_with_state(
         mut scope: crate::Scope,
         ast: &crate::AST,
         engine: &crate::Engine,
+        state: &mut crate::engine::State,
+        level: usize,
     ) -> Result<Self, Box<EvalAltResult>> {
         let mut mods: crate::engine::Imports = (&engine.global_sub_modules).into();
         let orig_mods_len = mods.len();
 
+        // Operations and imported modules within the module count towards the limits of the evaluation
+        let mut new_state = crate::engine::State {
+            source: ast.clone_source(),
+            operations: state.operations,
+            modules: state.modules,
+            options: state.options.clone(),
+            ..Default::default()
+        };
+
         // Run the script
-        engine.eval_ast_with_scope_raw(&mut scope, &mut mods, &ast, 0)?;
+        let result = engine.eval_statements_raw(
+            &mut scope,
+            &mut mods,
+            &mut new_state,
+            ast.statements(),
+            &[ast.lib()],
+            &mut None,
+            level,
+        );
+
+        state.operations = new_state.operations;
+        state.modules = new_state.modules;
+        state.max_call_level = state.max_call_level.max(new_state.max_call_level);
+        result?;
 
         // Create new module
         let mut module = Module::new();
@@ -1745,12 +1922,18 @@ impl Module {
         });
 
         // Non-private functions defined become module functions
+        // (only those explicitly exported if functions are private by default)
         #[cfg(not(feature = "no_function"))]
         {
             ast.lib()
                 .functions
                 .values()
-                .filter(|FuncInfo { access, func, .. }| !access.is_private() && func.is_script())
+                .filter(|f| {
+                    !f.access.is_private()
+                        && f.func.is_script()
+                        && !f.is_default_params_alias()
+                        && (!engine.module_fn_private_by_default || f.func.get_fn_def().exported)
+                })
                 .for_each(|FuncInfo { func, .. }| {
                     // Encapsulate AST environment
                     let mut func = func.get_fn_def().clone();
@@ -1777,7 +1960,7 @@ impl Module {
             qualifiers: &mut Vec<&'a str>,
             variables: &mut HashMap<NonZeroU64, Dynamic, StraightHasherBuilder>,
             functions: &mut HashMap<NonZeroU64, CallableFunction, StraightHasherBuilder>,
-            type_iterators: &mut HashMap<TypeId, IteratorFn>,
+            type_iterators: &mut HashMap<TypeId, TypeIterator>,
         ) {
             module.modules.iter().for_each(|(name, m)| {
                 // Index all the sub-modules first.
@@ -1889,7 +2072,26 @@ impl Module {
 
     /// Set a type iterator into the module.
     pub fn set_iter(&mut self, typ: TypeId, func: IteratorFn) -> &mut Self {
-        self.type_iterators.insert(typ, func);
+        self.type_iterators.insert(typ, TypeIterator::Fn(func));
+        self.indexed = false;
+        self
+    }
+
+    /// Set a type iterator into the module that iterates a value by mutable reference,
+    /// without consuming (or cloning) it.
+    ///
+    /// The iterator returned is evaluated lazily, one item per loop iteration.
+    /// An error yielded by the iterator stops the loop and is propagated.
+    pub fn set_iterator_fn<T: Variant + Clone>(
+        &mut self,
+        func: impl Fn(&mut T) -> Box<dyn Iterator<Item = Result<Dynamic, Box<EvalAltResult>>>>
+            + SendSync
+            + 'static,
+    ) -> &mut Self {
+        let f: Shared<crate::fn_native::IteratorRefFn> =
+            Shared::new(move |obj: &mut Dynamic| func(&mut *obj.write_lock::<T>().unwrap()));
+        self.type_iterators
+            .insert(TypeId::of::<T>(), TypeIterator::Ref(f));
         self.indexed = false;
         self
     }
@@ -1917,12 +2119,12 @@ impl Module {
     }
 
     /// Get the specified type iterator.
-    pub(crate) fn get_qualified_iter(&self, id: TypeId) -> Option<IteratorFn> {
+    pub(crate) fn get_qualified_iter(&self, id: TypeId) -> Option<TypeIterator> {
         self.all_type_iterators.get(&id).cloned()
     }
 
     /// Get the specified type iterator.
-    pub(crate) fn get_iter(&self, id: TypeId) -> Option<IteratorFn> {
+    pub(crate) fn get_iter(&self, id: TypeId) -> Option<TypeIterator> {
         self.type_iterators.get(&id).cloned()
     }
 }
@@ -1939,7 +2141,7 @@ impl Module {
 ///
 /// This type is volatile and may change.
 #[derive(Clone, Eq, PartialEq, Default, Hash)]
-pub struct NamespaceRef(Option<NonZeroUsize>, StaticVec<Ident>);
+pub struct NamespaceRef(Option<NonZeroUsize>, StaticVec<Ident>, Option<Position>);
 
 impl fmt::Debug for NamespaceRef {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -1978,7 +2180,7 @@ impl fmt::Display for NamespaceRef {
 
 impl From<StaticVec<Ident>> for NamespaceRef {
     fn from(modules: StaticVec<Ident>) -> Self {
-        Self(None, modules)
+        Self(None, modules, None)
     }
 }
 
@@ -2017,6 +2219,17 @@ impl NamespaceRef {
     pub(crate) fn set_index(&mut self, index: Option<NonZeroUsize>) {
         self.0 = index
     }
+    /// Get the [position][Position] of the variable in scope, if any, with the same name as the
+    /// root of this namespace.
+    pub(crate) fn shadowing_var(&self) -> Option<Position> {
+        self.2
+    }
+    /// Set the [position][Position] of the variable in scope, if any, with the same name as the
+    /// root of this namespace.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) fn set_shadowing_var(&mut self, pos: Option<Position>) {
+        self.2 = pos
+    }
 }
 
 /// Re-export module resolver trait.
@@ -1,3 +1,4 @@
+use super::ResolveContext;
 use crate::stdlib::{boxed::Box, ops::AddAssign, vec::Vec};
 use crate::{Engine, EvalAltResult, Module, ModuleResolver, Position, Shared};
 
@@ -113,9 +114,24 @@ impl ModuleResolver for ModuleResolversCollection {
         engine: &Engine,
         path: &str,
         pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        self.resolve_raw(
+            engine,
+            &mut ResolveContext::new(&mut Default::default(), 0),
+            path,
+            pos,
+        )
+    }
+
+    fn resolve_raw(
+        &self,
+        engine: &Engine,
+        ctx: &mut ResolveContext,
+        path: &str,
+        pos: Position,
     ) -> Result<Shared<Module>, Box<EvalAltResult>> {
         for resolver in self.0.iter() {
-            match resolver.resolve(engine, path, pos) {
+            match resolver.resolve_raw(engine, ctx, path, pos) {
                 Ok(module) => return Ok(module),
                 Err(err) => match *err {
                     EvalAltResult::ErrorModuleNotFound(_, _) => continue,
@@ -127,6 +143,12 @@ impl ModuleResolver for ModuleResolversCollection {
 
         EvalAltResult::ErrorModuleNotFound(path.into(), pos).into()
     }
+
+    fn source_text(&self, path: &str) -> Option<Shared<str>> {
+        self.0
+            .iter()
+            .find_map(|resolver| resolver.source_text(path))
+    }
 }
 
 impl<M: ModuleResolver + 'static> AddAssign<M> for ModuleResolversCollection {
@@ -1,7 +1,9 @@
+use super::ResolveContext;
 use crate::stdlib::{
     boxed::Box,
     collections::HashMap,
-    io::Error as IoError,
+    fs::{symlink_metadata, File, Metadata},
+    io::{Error as IoError, ErrorKind, Read},
     path::{Path, PathBuf},
     string::String,
 };
@@ -17,6 +19,14 @@ use crate::{Engine, EvalAltResult, Module, ModuleResolver, Position, Shared};
 /// to the base directory. The script file is then forced to be in a specified extension
 /// (default `.rhai`).
 ///
+/// Symbolic links within the module path are followed by default (as long as the script file
+/// stays within the base directory).  Use [`set_follow_symlinks`][FileModuleResolver::set_follow_symlinks]
+/// to refuse them instead.
+///
+/// Module paths that escape the base directory (e.g. via `..` components, absolute paths or
+/// symbolic links pointing outside) are refused.  Use
+/// [`set_confine_to_base_path`][FileModuleResolver::set_confine_to_base_path] to allow them.
+///
 /// # Function Namespace
 ///
 /// When a function within a script file module is loaded, all functions in the _global_ namespace
@@ -41,11 +51,18 @@ use crate::{Engine, EvalAltResult, Module, ModuleResolver, Position, Shared};
 pub struct FileModuleResolver {
     base_path: PathBuf,
     extension: String,
+    follow_symlinks: bool,
+    confine_to_base_path: bool,
 
     #[cfg(not(feature = "sync"))]
     cache: crate::stdlib::cell::RefCell<HashMap<PathBuf, Shared<Module>>>,
     #[cfg(feature = "sync")]
     cache: crate::stdlib::sync::RwLock<HashMap<PathBuf, Shared<Module>>>,
+
+    #[cfg(not(feature = "sync"))]
+    sources: crate::stdlib::cell::RefCell<HashMap<PathBuf, Shared<str>>>,
+    #[cfg(feature = "sync")]
+    sources: crate::stdlib::sync::RwLock<HashMap<PathBuf, Shared<str>>>,
 }
 
 impl Default for FileModuleResolver {
@@ -101,7 +118,10 @@ impl FileModuleResolver {
         Self {
             base_path: path.into(),
             extension: extension.into(),
+            follow_symlinks: true,
+            confine_to_base_path: true,
             cache: Default::default(),
+            sources: Default::default(),
         }
     }
 
@@ -150,6 +170,128 @@ impl FileModuleResolver {
         self
     }
 
+    /// Are symbolic links within module paths followed?
+    #[inline(always)]
+    pub fn follow_symlinks(&self) -> bool {
+        self.follow_symlinks
+    }
+
+    /// Enable/disable following symbolic links within module paths.
+    ///
+    /// When disabled, a module path is refused if any of its components, relative to the
+    /// base path, is a symbolic link.  The base path itself may still contain symbolic links.
+    ///
+    /// Enabled by default.
+    #[inline(always)]
+    pub fn set_follow_symlinks(&mut self, enable: bool) -> &mut Self {
+        self.follow_symlinks = enable;
+        self
+    }
+
+    /// Are script files confined to the base path?
+    #[inline(always)]
+    pub fn confine_to_base_path(&self) -> bool {
+        self.confine_to_base_path
+    }
+
+    /// Enable/disable confining script files to the base path.
+    ///
+    /// When enabled, a module path is refused if its script file, after resolving all symbolic
+    /// links and `..` components, is not within the base path (or the current directory if
+    /// no base path is set).  Disable it to allow scripts to import any script file,
+    /// e.g. via absolute paths.
+    ///
+    /// Enabled by default.
+    #[inline(always)]
+    pub fn set_confine_to_base_path(&mut self, enable: bool) -> &mut Self {
+        self.confine_to_base_path = enable;
+        self
+    }
+
+    /// Construct the script file path of a module path.
+    fn get_file_path(&self, path: &str) -> PathBuf {
+        let mut file_path = self.base_path.clone();
+        file_path.push(path);
+        file_path.set_extension(&self.extension); // Force extension
+        file_path
+    }
+
+    /// Find the first component of a module path, relative to the base path, that is a
+    /// symbolic link.
+    fn find_symlink(&self, path: &str) -> Option<PathBuf> {
+        let mut relative_path = PathBuf::from(path);
+        relative_path.set_extension(&self.extension);
+
+        let mut file_path = self.base_path.clone();
+        let mut component_path = PathBuf::new();
+
+        relative_path.components().find_map(|component| {
+            file_path.push(component);
+            component_path.push(component);
+
+            match symlink_metadata(&file_path) {
+                Ok(metadata) if metadata.file_type().is_symlink() => Some(component_path.clone()),
+                _ => None,
+            }
+        })
+    }
+
+    /// Is a script file path within the base path, after resolving all symbolic links
+    /// and `..` components?
+    ///
+    /// For a script file that does not exist, its parent directory is checked instead.
+    /// A script file whose parent directory does not exist either is considered within the
+    /// base path, so that loading it fails with the usual error.
+    fn is_within_base_path(&self, file_path: &Path) -> bool {
+        let file_path = match file_path.canonicalize() {
+            Ok(path) => path,
+            Err(_) => match (file_path.parent(), file_path.file_name()) {
+                (Some(parent), Some(file_name)) => match parent.canonicalize() {
+                    Ok(parent) => parent.join(file_name),
+                    Err(_) => return true,
+                },
+                _ => return true,
+            },
+        };
+
+        let base_path = if self.base_path.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            self.base_path.as_path()
+        };
+
+        base_path
+            .canonicalize()
+            .map(|base_path| file_path.starts_with(base_path))
+            .unwrap_or(false)
+    }
+
+    /// Is an opened script file the one found by looking up its module path, after all the
+    /// checks on the path have passed?
+    ///
+    /// The checks look up the script file by path, so it may be swapped for another file
+    /// (e.g. a symbolic link pointing outside the base path) just before it is opened.
+    /// Looking it up again after opening it, and comparing the file found with the opened
+    /// file, makes sure that the checked file is the one read.
+    fn is_checked_file(&self, path: &str, file_path: &Path, opened: &Metadata) -> bool {
+        if !self.follow_symlinks && self.find_symlink(path).is_some() {
+            return false;
+        }
+
+        let checked_path = if self.confine_to_base_path {
+            match file_path.canonicalize() {
+                Ok(path) if self.is_within_base_path(&path) => path,
+                _ => return false,
+            }
+        } else {
+            file_path.to_path_buf()
+        };
+
+        symlink_metadata(&checked_path)
+            .map(|checked| is_same_file(&checked, opened))
+            .unwrap_or(false)
+    }
+
     /// Empty the internal cache.
     #[inline(always)]
     pub fn clear_cache(&mut self) {
@@ -157,11 +299,21 @@ impl FileModuleResolver {
         self.cache.borrow_mut().clear();
         #[cfg(feature = "sync")]
         self.cache.write().unwrap().clear();
+
+        #[cfg(not(feature = "sync"))]
+        self.sources.borrow_mut().clear();
+        #[cfg(feature = "sync")]
+        self.sources.write().unwrap().clear();
     }
 
     /// Empty the internal cache.
     #[inline(always)]
     pub fn clear_cache_for_path(&mut self, path: impl AsRef<Path>) -> Option<Shared<Module>> {
+        #[cfg(not(feature = "sync"))]
+        self.sources.borrow_mut().remove(path.as_ref());
+        #[cfg(feature = "sync")]
+        self.sources.write().unwrap().remove(path.as_ref());
+
         #[cfg(not(feature = "sync"))]
         return self
             .cache
@@ -178,17 +330,47 @@ impl FileModuleResolver {
     }
 }
 
+/// Are two [`Metadata`] of the same file?
+#[cfg(unix)]
+fn is_same_file(a: &Metadata, b: &Metadata) -> bool {
+    use crate::stdlib::os::unix::fs::MetadataExt;
+
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+/// Are two [`Metadata`] of the same file?
+///
+/// There is no stable file identity outside Unix, so this compares the file types,
+/// lengths and modification times instead.
+#[cfg(not(unix))]
+fn is_same_file(a: &Metadata, b: &Metadata) -> bool {
+    a.file_type() == b.file_type() && a.len() == b.len() && a.modified().ok() == b.modified().ok()
+}
+
 impl ModuleResolver for FileModuleResolver {
     fn resolve(
         &self,
         engine: &Engine,
         path: &str,
         pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        self.resolve_raw(
+            engine,
+            &mut ResolveContext::new(&mut Default::default(), 0),
+            path,
+            pos,
+        )
+    }
+
+    fn resolve_raw(
+        &self,
+        engine: &Engine,
+        ctx: &mut ResolveContext,
+        path: &str,
+        pos: Position,
     ) -> Result<Shared<Module>, Box<EvalAltResult>> {
         // Construct the script file path
-        let mut file_path = self.base_path.clone();
-        file_path.push(path);
-        file_path.set_extension(&self.extension); // Force extension
+        let file_path = self.get_file_path(path);
 
         let scope = Default::default();
 
@@ -209,17 +391,104 @@ impl ModuleResolver for FileModuleResolver {
         };
 
         if module_ref.is_none() {
-            // Load the script file and compile it
-            let ast = engine
-                .compile_file(file_path.clone())
-                .map_err(|err| match *err {
-                    EvalAltResult::ErrorSystem(_, err) if err.is::<IoError>() => {
-                        Box::new(EvalAltResult::ErrorModuleNotFound(path.to_string(), pos))
-                    }
-                    _ => Box::new(EvalAltResult::ErrorInModule(path.to_string(), err, pos)),
-                })?;
-
-            let mut m = Module::eval_ast_as_new(scope, &ast, engine).map_err(|err| {
+            // Make sure that the module path contains no symbolic links, if so required
+            if !self.follow_symlinks {
+                if let Some(link) = self.find_symlink(path) {
+                    return EvalAltResult::ErrorInModule(
+                        path.to_string(),
+                        Box::new(EvalAltResult::ErrorSystem(
+                            format!("Cannot follow symbolic link '{}'", link.display()),
+                            Box::new(IoError::new(
+                                ErrorKind::PermissionDenied,
+                                "following symbolic links is disabled",
+                            )),
+                        )),
+                        pos,
+                    )
+                    .into();
+                }
+            }
+
+            // Make sure that the script file does not escape the base directory, if so required
+            if self.confine_to_base_path && !self.is_within_base_path(&file_path) {
+                return EvalAltResult::ErrorInModule(
+                    path.to_string(),
+                    Box::new(EvalAltResult::ErrorSystem(
+                        format!("Module path '{}' escapes module root", path),
+                        Box::new(IoError::new(
+                            ErrorKind::PermissionDenied,
+                            "path escapes module root",
+                        )),
+                    )),
+                    pos,
+                )
+                .into();
+            }
+
+            // Open the script file only once and read it via the same handle,
+            // so it cannot be swapped for another file in between
+            let not_found = |_| EvalAltResult::ErrorModuleNotFound(path.to_string(), pos);
+
+            let mut file = File::open(&file_path).map_err(not_found)?;
+
+            let metadata = file.metadata().map_err(not_found)?;
+
+            // Only read regular files (e.g. not a named pipe, which may block forever)
+            if !metadata.is_file() {
+                return EvalAltResult::ErrorModuleNotFound(path.to_string(), pos).into();
+            }
+
+            // Make sure that the opened file is the one that passed the checks above
+            if (!self.follow_symlinks || self.confine_to_base_path)
+                && !self.is_checked_file(path, &file_path, &metadata)
+            {
+                return EvalAltResult::ErrorInModule(
+                    path.to_string(),
+                    Box::new(EvalAltResult::ErrorSystem(
+                        format!("Script file '{}' changed while being loaded", path),
+                        Box::new(IoError::new(
+                            ErrorKind::PermissionDenied,
+                            "script file changed while being loaded",
+                        )),
+                    )),
+                    pos,
+                )
+                .into();
+            }
+
+            let mut contents = String::new();
+            file.read_to_string(&mut contents).map_err(|err| {
+                EvalAltResult::ErrorInModule(
+                    path.to_string(),
+                    Box::new(EvalAltResult::ErrorSystem(
+                        format!("Cannot read script file '{}'", file_path.to_string_lossy()),
+                        err.into(),
+                    )),
+                    pos,
+                )
+            })?;
+
+            // Compile it
+            let ast = engine.compile(&contents).map_err(|err| {
+                Box::new(EvalAltResult::ErrorInModule(
+                    path.to_string(),
+                    err.into(),
+                    pos,
+                ))
+            })?;
+
+            // Keep the script text, if retained, before running it so runtime errors can refer to it
+            if let Some(text) = ast.clone_source_text() {
+                #[cfg(not(feature = "sync"))]
+                self.sources.borrow_mut().insert(file_path.clone(), text);
+                #[cfg(feature = "sync")]
+                self.sources
+                    .write()
+                    .unwrap()
+                    .insert(file_path.clone(), text);
+            }
+
+            let mut m = Module::eval_ast_as_new_raw(scope, &ast, engine, ctx).map_err(|err| {
                 Box::new(EvalAltResult::ErrorInModule(path.to_string(), err, pos))
             })?;
 
@@ -238,4 +507,13 @@ impl ModuleResolver for FileModuleResolver {
 
         Ok(module_ref.unwrap())
     }
+
+    fn source_text(&self, path: &str) -> Option<Shared<str>> {
+        let file_path = self.get_file_path(path);
+
+        #[cfg(not(feature = "sync"))]
+        return self.sources.borrow().get(&file_path).cloned();
+        #[cfg(feature = "sync")]
+        return self.sources.read().unwrap().get(&file_path).cloned();
+    }
 }
@@ -1,7 +1,11 @@
+use crate::engine::State;
 use crate::fn_native::SendSync;
 use crate::stdlib::boxed::Box;
 use crate::{Engine, EvalAltResult, Module, Position, Shared};
 
+#[cfg(feature = "async")]
+use crate::stdlib::{future::Future, pin::Pin};
+
 mod dummy;
 pub use dummy::DummyModuleResolver;
 
@@ -19,6 +23,47 @@ pub use file::FileModuleResolver;
 mod stat;
 pub use stat::StaticModuleResolver;
 
+/// A boxed [`Future`][std::future::Future] resolving to a [module][Module].
+///
+/// Returned by [`ModuleResolver::resolve_async`].  Exported under the `async` feature.
+#[cfg(feature = "async")]
+#[cfg(not(feature = "sync"))]
+pub type ModuleFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Shared<Module>, Box<EvalAltResult>>> + 'a>>;
+
+/// A boxed [`Future`][std::future::Future] resolving to a [module][Module].
+///
+/// Returned by [`ModuleResolver::resolve_async`].  Exported under the `async` feature.
+#[cfg(feature = "async")]
+#[cfg(feature = "sync")]
+pub type ModuleFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Shared<Module>, Box<EvalAltResult>>> + Send + 'a>>;
+
+/// Context of the evaluation importing a module, passed to [`ModuleResolver::resolve_raw`].
+///
+/// It is opaque, and can only be passed on to
+/// [`Module::eval_ast_as_new_raw`][crate::Module::eval_ast_as_new_raw].
+#[derive(Debug)]
+pub struct ResolveContext<'s> {
+    /// State of the evaluation.
+    pub(crate) state: &'s mut State,
+    /// Level of function calls of the module script.
+    pub(crate) level: usize,
+}
+
+impl<'s> ResolveContext<'s> {
+    /// Create a new [`ResolveContext`].
+    #[inline(always)]
+    pub(crate) fn new(state: &'s mut State, level: usize) -> Self {
+        Self { state, level }
+    }
+    /// Level of function calls the module script is evaluated at.
+    #[inline(always)]
+    pub fn level(&self) -> usize {
+        self.level
+    }
+}
+
 /// Trait that encapsulates a module resolution service.
 pub trait ModuleResolver: SendSync {
     /// Resolve a module based on a path string.
@@ -28,4 +73,49 @@ pub trait ModuleResolver: SendSync {
         path: &str,
         pos: Position,
     ) -> Result<Shared<Module>, Box<EvalAltResult>>;
+
+    /// Resolve a module based on a path string, as part of an evaluation.
+    ///
+    /// Resolvers that run scripts should evaluate them via
+    /// [`Module::eval_ast_as_new_raw`][crate::Module::eval_ast_as_new_raw], passing on the
+    /// [context][ResolveContext], so that operations and levels of function calls within the
+    /// module count towards the limits of the evaluation.
+    /// The default implementation simply calls [`resolve`][ModuleResolver::resolve].
+    #[inline(always)]
+    fn resolve_raw(
+        &self,
+        engine: &Engine,
+        _ctx: &mut ResolveContext,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        self.resolve(engine, path, pos)
+    }
+
+    /// Resolve a module based on a path string, without blocking.
+    ///
+    /// Used by the asynchronous evaluation API, e.g. [`Engine::eval_async`][crate::Engine::eval_async].
+    /// The default implementation simply calls [`resolve`][ModuleResolver::resolve].
+    ///
+    /// Exported under the `async` feature.
+    #[cfg(feature = "async")]
+    #[inline(always)]
+    fn resolve_async<'a>(
+        &'a self,
+        engine: &'a Engine,
+        path: &'a str,
+        pos: Position,
+    ) -> ModuleFuture<'a> {
+        Box::pin(async move { self.resolve(engine, path, pos) })
+    }
+
+    /// Get the original script text of a module previously resolved from a path string, if any.
+    ///
+    /// Only available for script modules compiled with
+    /// [`Engine::set_retain_source`][crate::Engine::set_retain_source] turned on.
+    /// The default implementation returns [`None`].
+    #[inline(always)]
+    fn source_text(&self, _path: &str) -> Option<Shared<str>> {
+        None
+    }
 }
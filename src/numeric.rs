@@ -0,0 +1,517 @@
+//! Module implementing the registration of custom numeric types with the [`Engine`].
+
+use crate::dynamic::Variant;
+use crate::fn_native::{CallableFunction, FnCallArgs, SendSync};
+use crate::packages::arithmetic::make_err;
+use crate::stdlib::{
+    any::{type_name, TypeId},
+    boxed::Box,
+    cmp::Ordering,
+    fmt, format,
+    string::{String, ToString},
+};
+use crate::{
+    Dynamic, Engine, EvalAltResult, FnAccess, FnNamespace, ImmutableString, NativeCallContext,
+    Shared, INT,
+};
+
+/// A binary arithmetic operation, returning [`None`] on failure.
+#[cfg(not(feature = "sync"))]
+type FnBinaryOp<T> = dyn Fn(T, T) -> Option<T>;
+/// A binary arithmetic operation, returning [`None`] on failure.
+#[cfg(feature = "sync")]
+type FnBinaryOp<T> = dyn Fn(T, T) -> Option<T> + Send + Sync;
+
+/// A unary arithmetic operation, returning [`None`] on failure.
+#[cfg(not(feature = "sync"))]
+type FnUnaryOp<T> = dyn Fn(T) -> Option<T>;
+/// A unary arithmetic operation, returning [`None`] on failure.
+#[cfg(feature = "sync")]
+type FnUnaryOp<T> = dyn Fn(T) -> Option<T> + Send + Sync;
+
+/// A comparison function.
+#[cfg(not(feature = "sync"))]
+type FnCompare<T> = dyn Fn(&T, &T) -> Ordering;
+/// A comparison function.
+#[cfg(feature = "sync")]
+type FnCompare<T> = dyn Fn(&T, &T) -> Ordering + Send + Sync;
+
+/// A function converting a value into text.
+#[cfg(not(feature = "sync"))]
+type FnToString<T> = dyn Fn(&T) -> String;
+/// A function converting a value into text.
+#[cfg(feature = "sync")]
+type FnToString<T> = dyn Fn(&T) -> String + Send + Sync;
+
+/// A function converting an integer into a value.
+#[cfg(not(feature = "sync"))]
+type FnFromInt<T> = dyn Fn(INT) -> T;
+/// A function converting an integer into a value.
+#[cfg(feature = "sync")]
+type FnFromInt<T> = dyn Fn(INT) -> T + Send + Sync;
+
+/// Operations of a custom numeric type, for use with [`Engine::register_numeric_type`].
+///
+/// All operations are optional.
+/// Functions depending on an operation that is not provided are simply not registered.
+///
+/// | Operation     | Functions registered                                                               |
+/// | ------------- | ---------------------------------------------------------------------------------- |
+/// | `add`         | `+`                                                                                |
+/// | `sub`         | `-`                                                                                |
+/// | `mul`         | `*`                                                                                |
+/// | `div`         | `/`                                                                                |
+/// | `rem`         | `%`                                                                                |
+/// | `neg`         | unary `-` and `+`                                                                  |
+/// | `cmp`         | `==`, `!=`, `<`, `<=`, `>`, `>=`, `min`, `max`, `clamp`                            |
+/// | `neg` + `cmp` + `from_int` | `abs`                                                                 |
+/// | `cmp` + `from_int`         | `sign`                                                                |
+/// | `to_string`   | `to_string`, `to_debug`, `print`, `debug`, `+` with a string                       |
+/// | `from_int`    | versions of all binary operators above taking an `INT` on either side              |
+///
+/// With `cmp`, the array functions `sort` (without a comparer), `min` and `max`
+/// also work on arrays of the type, because they use the `<` operator.
+pub struct NumericOps<T: Variant + Clone> {
+    add: Option<Shared<FnBinaryOp<T>>>,
+    sub: Option<Shared<FnBinaryOp<T>>>,
+    mul: Option<Shared<FnBinaryOp<T>>>,
+    div: Option<Shared<FnBinaryOp<T>>>,
+    rem: Option<Shared<FnBinaryOp<T>>>,
+    neg: Option<Shared<FnUnaryOp<T>>>,
+    cmp: Option<Shared<FnCompare<T>>>,
+    to_string: Option<Shared<FnToString<T>>>,
+    from_int: Option<Shared<FnFromInt<T>>>,
+}
+
+impl<T: Variant + Clone> Default for NumericOps<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            add: None,
+            sub: None,
+            mul: None,
+            div: None,
+            rem: None,
+            neg: None,
+            cmp: None,
+            to_string: None,
+            from_int: None,
+        }
+    }
+}
+
+impl<T: Variant + Clone> fmt::Debug for NumericOps<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NumericOps")
+            .field("type", &type_name::<T>())
+            .field("add", &self.add.is_some())
+            .field("sub", &self.sub.is_some())
+            .field("mul", &self.mul.is_some())
+            .field("div", &self.div.is_some())
+            .field("rem", &self.rem.is_some())
+            .field("neg", &self.neg.is_some())
+            .field("cmp", &self.cmp.is_some())
+            .field("to_string", &self.to_string.is_some())
+            .field("from_int", &self.from_int.is_some())
+            .finish()
+    }
+}
+
+impl<T: Variant + Clone> NumericOps<T> {
+    /// Create a new [`NumericOps`] with no operations.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Set the addition operation, returning [`None`] on overflow.
+    #[inline(always)]
+    pub fn with_add(mut self, func: impl Fn(T, T) -> Option<T> + SendSync + 'static) -> Self {
+        self.add = Some(Shared::new(func));
+        self
+    }
+    /// Set the subtraction operation, returning [`None`] on overflow.
+    #[inline(always)]
+    pub fn with_sub(mut self, func: impl Fn(T, T) -> Option<T> + SendSync + 'static) -> Self {
+        self.sub = Some(Shared::new(func));
+        self
+    }
+    /// Set the multiplication operation, returning [`None`] on overflow.
+    #[inline(always)]
+    pub fn with_mul(mut self, func: impl Fn(T, T) -> Option<T> + SendSync + 'static) -> Self {
+        self.mul = Some(Shared::new(func));
+        self
+    }
+    /// Set the division operation, returning [`None`] on division by zero or overflow.
+    #[inline(always)]
+    pub fn with_div(mut self, func: impl Fn(T, T) -> Option<T> + SendSync + 'static) -> Self {
+        self.div = Some(Shared::new(func));
+        self
+    }
+    /// Set the remainder operation, returning [`None`] on division by zero or overflow.
+    #[inline(always)]
+    pub fn with_rem(mut self, func: impl Fn(T, T) -> Option<T> + SendSync + 'static) -> Self {
+        self.rem = Some(Shared::new(func));
+        self
+    }
+    /// Set the negation operation, returning [`None`] on overflow.
+    #[inline(always)]
+    pub fn with_neg(mut self, func: impl Fn(T) -> Option<T> + SendSync + 'static) -> Self {
+        self.neg = Some(Shared::new(func));
+        self
+    }
+    /// Set the comparison function, which must be a total order.
+    #[inline(always)]
+    pub fn with_cmp(mut self, func: impl Fn(&T, &T) -> Ordering + SendSync + 'static) -> Self {
+        self.cmp = Some(Shared::new(func));
+        self
+    }
+    /// Set the function converting a value into text.
+    #[inline(always)]
+    pub fn with_to_string(mut self, func: impl Fn(&T) -> String + SendSync + 'static) -> Self {
+        self.to_string = Some(Shared::new(func));
+        self
+    }
+    /// Set the function converting an `INT` into a value.
+    #[inline(always)]
+    pub fn with_from_int(mut self, func: impl Fn(INT) -> T + SendSync + 'static) -> Self {
+        self.from_int = Some(Shared::new(func));
+        self
+    }
+}
+
+impl Engine {
+    /// Register a custom numeric type with the [`Engine`], in one call registering
+    /// the operators and functions provided for `INT` that can be built from the operations
+    /// supplied in a [`NumericOps`].
+    ///
+    /// Register a friendly name for the type via [`register_type_with_name`][Engine::register_type_with_name]
+    /// _before_ calling this method so that it shows up in the functions metadata.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, NumericOps, RegisterFn};
+    ///
+    /// // Fixed-point number with two decimal places
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    /// struct Money(i64);
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine
+    ///     .register_fn("money", |cents: i64| Money(cents))
+    ///     .register_numeric_type(
+    ///         NumericOps::new()
+    ///             .with_add(|x: Money, y: Money| x.0.checked_add(y.0).map(Money))
+    ///             .with_sub(|x: Money, y: Money| x.0.checked_sub(y.0).map(Money))
+    ///             .with_neg(|x: Money| x.0.checked_neg().map(Money))
+    ///             .with_cmp(Money::cmp)
+    ///             .with_to_string(|x: &Money| format!("{}.{:02}", x.0 / 100, x.0.abs() % 100))
+    ///             .with_from_int(|x| Money(x * 100)),
+    ///     );
+    ///
+    /// assert_eq!(
+    ///     engine.eval::<String>("let x = money(150) - 3; to_string(abs(x))")?,
+    ///     "1.50"
+    /// );
+    /// assert_eq!(engine.eval::<Money>("max(money(5), money(3))")?, Money(5));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_numeric_type<T: Variant + Clone>(&mut self, ops: NumericOps<T>) -> &mut Self {
+        let name = self.map_type_name(type_name::<T>()).to_string();
+        let name = name.as_str();
+        let from_int = ops.from_int.clone();
+        let show = ops.to_string.clone();
+
+        // Arithmetic operators
+        let arithmetic = [
+            ("+", ops.add, "Addition overflow"),
+            ("-", ops.sub, "Subtraction overflow"),
+            ("*", ops.mul, "Multiplication overflow"),
+            ("/", ops.div, "Division by zero or overflow"),
+            ("%", ops.rem, "Modulo division by zero or overflow"),
+        ];
+
+        for (op, func, err) in arithmetic.iter() {
+            if let Some(func) = func {
+                let (op, err, func) = (*op, *err, func.clone());
+                let show = show.clone();
+
+                self.register_numeric_binary_fn(op, name, name, from_int.as_ref(), move |x, y| {
+                    func(x.clone(), y.clone())
+                        .map(Dynamic::from)
+                        .ok_or_else(|| {
+                            make_err(format!(
+                                "{}: {} {} {}",
+                                err,
+                                show_value(show.as_ref(), &x),
+                                op,
+                                show_value(show.as_ref(), &y)
+                            ))
+                        })
+                });
+            }
+        }
+
+        // Unary operators
+        if let Some(neg) = ops.neg.clone() {
+            let show = show.clone();
+
+            self.register_numeric_fn(
+                "-",
+                &[TypeId::of::<T>()],
+                &[&format!("x: {}", name), name],
+                move |_, args| {
+                    let x = args[0].clone().cast::<T>();
+
+                    neg(x.clone()).map(Dynamic::from).ok_or_else(|| {
+                        make_err(format!(
+                            "Negation overflow: -{}",
+                            show_value(show.as_ref(), &x)
+                        ))
+                    })
+                },
+            );
+            self.register_numeric_fn(
+                "+",
+                &[TypeId::of::<T>()],
+                &[&format!("x: {}", name), name],
+                |_, args| Ok(args[0].clone()),
+            );
+        }
+
+        // Comparison operators
+        if let Some(cmp) = ops.cmp.clone() {
+            let comparisons = [
+                ("==", Ordering::is_eq as fn(Ordering) -> bool),
+                ("!=", Ordering::is_ne),
+                ("<", Ordering::is_lt),
+                ("<=", Ordering::is_le),
+                (">", Ordering::is_gt),
+                (">=", Ordering::is_ge),
+            ];
+
+            for &(op, test) in comparisons.iter() {
+                let cmp = cmp.clone();
+
+                self.register_numeric_binary_fn(
+                    op,
+                    name,
+                    "bool",
+                    from_int.as_ref(),
+                    move |x, y| Ok(test(cmp(&x, &y)).into()),
+                );
+            }
+
+            let min = cmp.clone();
+            self.register_numeric_binary_fn("min", name, name, None, move |x, y| {
+                Ok(Dynamic::from(if min(&y, &x) == Ordering::Less {
+                    y
+                } else {
+                    x
+                }))
+            });
+
+            let max = cmp.clone();
+            self.register_numeric_binary_fn("max", name, name, None, move |x, y| {
+                Ok(Dynamic::from(if max(&y, &x) == Ordering::Greater {
+                    y
+                } else {
+                    x
+                }))
+            });
+
+            let (clamp, show_clamp) = (cmp.clone(), show.clone());
+            self.register_numeric_fn(
+                "clamp",
+                &[TypeId::of::<T>(), TypeId::of::<T>(), TypeId::of::<T>()],
+                &[
+                    &format!("x: {}", name),
+                    &format!("min: {}", name),
+                    &format!("max: {}", name),
+                    name,
+                ],
+                move |_, args| {
+                    let x = args[0].clone().cast::<T>();
+                    let min = args[1].clone().cast::<T>();
+                    let max = args[2].clone().cast::<T>();
+
+                    if clamp(&min, &max) == Ordering::Greater {
+                        Err(make_err(format!(
+                            "Invalid range for clamp: {} > {}",
+                            show_value(show_clamp.as_ref(), &min),
+                            show_value(show_clamp.as_ref(), &max)
+                        )))
+                    } else if clamp(&x, &min) == Ordering::Less {
+                        Ok(Dynamic::from(min))
+                    } else if clamp(&x, &max) == Ordering::Greater {
+                        Ok(Dynamic::from(max))
+                    } else {
+                        Ok(Dynamic::from(x))
+                    }
+                },
+            );
+
+            // Functions that need a zero value
+            if let Some(from_int) = from_int.clone() {
+                let zero = from_int(0);
+
+                if let Some(neg) = ops.neg.clone() {
+                    let cmp = cmp.clone();
+                    let zero = zero.clone();
+                    let show = show.clone();
+
+                    self.register_numeric_fn(
+                        "abs",
+                        &[TypeId::of::<T>()],
+                        &[&format!("x: {}", name), name],
+                        move |_, args| {
+                            let x = args[0].clone().cast::<T>();
+
+                            if cmp(&x, &zero) != Ordering::Less {
+                                return Ok(Dynamic::from(x));
+                            }
+
+                            neg(x.clone()).map(Dynamic::from).ok_or_else(|| {
+                                make_err(format!(
+                                    "Negation overflow: -{}",
+                                    show_value(show.as_ref(), &x)
+                                ))
+                            })
+                        },
+                    );
+                }
+
+                self.register_numeric_fn(
+                    "sign",
+                    &[TypeId::of::<T>()],
+                    &[&format!("x: {}", name), "INT"],
+                    move |_, args| {
+                        let x = args[0].clone().cast::<T>();
+
+                        Ok(match cmp(&x, &zero) {
+                            Ordering::Less => -1 as INT,
+                            Ordering::Equal => 0,
+                            Ordering::Greater => 1,
+                        }
+                        .into())
+                    },
+                );
+            }
+        }
+
+        // Conversion to text
+        if let Some(to_string) = ops.to_string {
+            for &fn_name in ["to_string", "to_debug", "print", "debug"].iter() {
+                let to_string = to_string.clone();
+
+                self.register_numeric_fn(
+                    fn_name,
+                    &[TypeId::of::<T>()],
+                    &[&format!("x: {}", name), "String"],
+                    move |_, args| {
+                        let x = args[0].read_lock::<T>().unwrap();
+                        Ok(ImmutableString::from(to_string(&x)).into())
+                    },
+                );
+            }
+
+            let append = to_string.clone();
+            self.register_numeric_fn(
+                "+",
+                &[TypeId::of::<ImmutableString>(), TypeId::of::<T>()],
+                &["x: &str", &format!("y: {}", name), "String"],
+                move |_, args| {
+                    let x = args[0].read_lock::<ImmutableString>().unwrap();
+                    let y = args[1].read_lock::<T>().unwrap();
+                    Ok(format!("{}{}", x.as_str(), append(&y)).into())
+                },
+            );
+
+            let prepend = to_string;
+            self.register_numeric_fn(
+                "+",
+                &[TypeId::of::<T>(), TypeId::of::<ImmutableString>()],
+                &[&format!("x: {}", name), "y: &str", "String"],
+                move |_, args| {
+                    let x = args[0].read_lock::<T>().unwrap();
+                    let y = args[1].read_lock::<ImmutableString>().unwrap();
+                    Ok(format!("{}{}", prepend(&x), y.as_str()).into())
+                },
+            );
+        }
+
+        self
+    }
+
+    /// Register a function of a custom numeric type, together with its metadata.
+    fn register_numeric_fn(
+        &mut self,
+        fn_name: &str,
+        arg_types: &[TypeId],
+        arg_names: &[&str],
+        func: impl Fn(NativeCallContext, &mut FnCallArgs) -> Result<Dynamic, Box<EvalAltResult>>
+            + SendSync
+            + 'static,
+    ) {
+        self.global_namespace.set_fn(
+            fn_name,
+            FnNamespace::Global,
+            FnAccess::Public,
+            Some(arg_names),
+            arg_types,
+            CallableFunction::from_method(Box::new(func)),
+        );
+    }
+
+    /// Register a binary function of a custom numeric type.
+    ///
+    /// If `from_int` is provided, versions taking an `INT` as either argument are also registered.
+    fn register_numeric_binary_fn<T: Variant + Clone>(
+        &mut self,
+        fn_name: &str,
+        type_name: &str,
+        return_type: &str,
+        from_int: Option<&Shared<FnFromInt<T>>>,
+        func: impl Fn(T, T) -> Result<Dynamic, Box<EvalAltResult>> + Clone + SendSync + 'static,
+    ) {
+        let x_type = format!("x: {}", type_name);
+        let y_type = format!("y: {}", type_name);
+
+        let f = func.clone();
+        self.register_numeric_fn(
+            fn_name,
+            &[TypeId::of::<T>(), TypeId::of::<T>()],
+            &[&x_type, &y_type, return_type],
+            move |_, args| f(args[0].clone().cast::<T>(), args[1].clone().cast::<T>()),
+        );
+
+        if let Some(from_int) = from_int {
+            let (f, from) = (func.clone(), from_int.clone());
+            self.register_numeric_fn(
+                fn_name,
+                &[TypeId::of::<T>(), TypeId::of::<INT>()],
+                &[&x_type, "y: INT", return_type],
+                move |_, args| f(args[0].clone().cast::<T>(), from(args[1].as_int().unwrap())),
+            );
+
+            let (f, from) = (func, from_int.clone());
+            self.register_numeric_fn(
+                fn_name,
+                &[TypeId::of::<INT>(), TypeId::of::<T>()],
+                &["x: INT", &y_type, return_type],
+                move |_, args| f(from(args[0].as_int().unwrap()), args[1].clone().cast::<T>()),
+            );
+        }
+    }
+}
+
+/// Show a value of a custom numeric type in an error message.
+fn show_value<T: Variant + Clone>(to_string: Option<&Shared<FnToString<T>>>, value: &T) -> String {
+    match to_string {
+        Some(func) => func(value),
+        None => type_name::<T>().to_string(),
+    }
+}
@@ -0,0 +1,386 @@
+//! Module defining the object map type, [`Map`].
+
+#![cfg(not(feature = "no_object"))]
+
+use crate::stdlib::{
+    borrow::Borrow,
+    collections::{hash_map, HashMap},
+    fmt,
+    hash::Hash,
+    iter::{FromIterator, IntoIterator},
+    mem,
+    ops::Index,
+    slice,
+};
+use crate::{Dynamic, ImmutableString, StaticVec};
+
+/// Internal representation of a [`Map`].
+#[derive(Clone)]
+enum Repr {
+    /// Small map held as a list of properties in insertion order, searched linearly.
+    Inline(StaticVec<(ImmutableString, Dynamic)>),
+    /// Hash map, used once the number of properties grows past [`Map::INLINE_SIZE`].
+    Hashed(HashMap<ImmutableString, Dynamic>),
+}
+
+/// Hash map of [`Dynamic`] values with [`ImmutableString`] keys.
+///
+/// Object maps are typically tiny (points, pairs, small records), so a map holding up to
+/// [`Map::INLINE_SIZE`] properties is kept inline as a list searched linearly, avoiding both
+/// the allocation and the hashing of a full hash map.
+/// The map is transparently upgraded to a hash map when it grows past that size.
+///
+/// # Iteration order
+///
+/// A map in the inline form iterates its properties in insertion order.
+/// Once upgraded, iteration order is unspecified, as with any hash map.
+/// An upgraded map stays a hash map until [`clear`][Map::clear] is called.
+///
+/// Not available under the `no_object` feature.
+///
+/// # Example
+///
+/// ```
+/// use rhai::{Dynamic, Map, INT};
+///
+/// let mut map = Map::new();
+///
+/// map.insert("x".into(), Dynamic::from(1 as INT));
+/// map.insert("y".into(), Dynamic::from(2 as INT));
+///
+/// assert_eq!(map.len(), 2);
+/// assert_eq!(map.get("y").unwrap().as_int().unwrap(), 2);
+///
+/// let keys: Vec<_> = map.keys().map(|k| k.as_str()).collect();
+/// assert_eq!(keys, ["x", "y"]);
+/// ```
+#[derive(Clone)]
+pub struct Map(Repr);
+
+impl Default for Map {
+    #[inline(always)]
+    fn default() -> Self {
+        Self(Repr::Inline(Default::default()))
+    }
+}
+
+impl fmt::Debug for Map {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl Map {
+    /// Maximum number of properties held in the inline form of a [`Map`].
+    pub const INLINE_SIZE: usize = 4;
+
+    /// Create a new, empty [`Map`].
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Create a new, empty [`Map`] able to hold at least `capacity` properties.
+    ///
+    /// A capacity larger than [`Map::INLINE_SIZE`] creates a hash map straight away.
+    #[inline(always)]
+    pub fn with_capacity(capacity: usize) -> Self {
+        if capacity > Self::INLINE_SIZE {
+            Self(Repr::Hashed(HashMap::with_capacity(capacity)))
+        } else {
+            Default::default()
+        }
+    }
+    /// Is this [`Map`] held in the inline form?
+    #[inline(always)]
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, Repr::Inline(_))
+    }
+    /// Number of properties in the [`Map`].
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Repr::Inline(list) => list.len(),
+            Repr::Hashed(map) => map.len(),
+        }
+    }
+    /// Is the [`Map`] empty?
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Remove all properties, returning the [`Map`] to the inline form.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.0 = Repr::Inline(Default::default());
+    }
+    /// Does the [`Map`] contain a property with the specified name?
+    #[inline(always)]
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        ImmutableString: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+    /// Get a reference to the value of a property.
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&Dynamic>
+    where
+        ImmutableString: Borrow<Q>,
+    {
+        match &self.0 {
+            Repr::Inline(list) => list.iter().find(|(k, _)| k.borrow() == key).map(|(_, v)| v),
+            Repr::Hashed(map) => map.get(key),
+        }
+    }
+    /// Get a mutable reference to the value of a property.
+    pub fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut Dynamic>
+    where
+        ImmutableString: Borrow<Q>,
+    {
+        match &mut self.0 {
+            Repr::Inline(list) => list
+                .iter_mut()
+                .find(|(k, _)| k.borrow() == key)
+                .map(|(_, v)| v),
+            Repr::Hashed(map) => map.get_mut(key),
+        }
+    }
+    /// Set the value of a property, returning the previous value, if any.
+    ///
+    /// Adding a property to a full inline [`Map`] upgrades it to a hash map.
+    pub fn insert(&mut self, key: ImmutableString, value: Dynamic) -> Option<Dynamic> {
+        let list = match &mut self.0 {
+            Repr::Hashed(map) => return map.insert(key, value),
+            Repr::Inline(list) => list,
+        };
+
+        if let Some((_, v)) = list.iter_mut().find(|(k, _)| *k == key) {
+            return Some(mem::replace(v, value));
+        }
+
+        if list.len() < Self::INLINE_SIZE {
+            list.push((key, value));
+        } else {
+            let mut map = HashMap::with_capacity(Self::INLINE_SIZE * 2);
+            map.extend(list.drain(..));
+            map.insert(key, value);
+            self.0 = Repr::Hashed(map);
+        }
+
+        None
+    }
+    /// Get a mutable reference to the value of a property,
+    /// first adding the property with the value returned by `default` if it does not exist.
+    pub fn get_or_insert_with(
+        &mut self,
+        key: ImmutableString,
+        default: impl FnOnce() -> Dynamic,
+    ) -> &mut Dynamic {
+        if !self.contains_key(&key) {
+            self.insert(key.clone(), default());
+        }
+        self.get_mut(&key).unwrap()
+    }
+    /// Remove a property, returning its value, if any.
+    ///
+    /// The inline form keeps the insertion order of the remaining properties.
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<Dynamic>
+    where
+        ImmutableString: Borrow<Q>,
+    {
+        match &mut self.0 {
+            Repr::Inline(list) => list
+                .iter()
+                .position(|(k, _)| k.borrow() == key)
+                .map(|index| list.remove(index).1),
+            Repr::Hashed(map) => map.remove(key),
+        }
+    }
+    /// Get an iterator over the properties of the [`Map`].
+    #[inline(always)]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(match &self.0 {
+            Repr::Inline(list) => IterRepr::Inline(list.iter()),
+            Repr::Hashed(map) => IterRepr::Hashed(map.iter()),
+        })
+    }
+    /// Get an iterator over the properties of the [`Map`], with mutable references to the values.
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut(match &mut self.0 {
+            Repr::Inline(list) => IterMutRepr::Inline(list.iter_mut()),
+            Repr::Hashed(map) => IterMutRepr::Hashed(map.iter_mut()),
+        })
+    }
+    /// Get an iterator over the property names of the [`Map`].
+    #[inline(always)]
+    pub fn keys(&self) -> impl Iterator<Item = &ImmutableString> {
+        self.iter().map(|(k, _)| k)
+    }
+    /// Get an iterator over the property values of the [`Map`].
+    #[inline(always)]
+    pub fn values(&self) -> impl Iterator<Item = &Dynamic> {
+        self.iter().map(|(_, v)| v)
+    }
+}
+
+impl<Q: Hash + Eq + ?Sized> Index<&Q> for Map
+where
+    ImmutableString: Borrow<Q>,
+{
+    type Output = Dynamic;
+
+    /// Get a reference to the value of a property.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the property does not exist.
+    #[inline(always)]
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get(key).expect("property not found")
+    }
+}
+
+impl From<HashMap<ImmutableString, Dynamic>> for Map {
+    #[inline(always)]
+    fn from(map: HashMap<ImmutableString, Dynamic>) -> Self {
+        if map.len() > Self::INLINE_SIZE {
+            Self(Repr::Hashed(map))
+        } else {
+            map.into_iter().collect()
+        }
+    }
+}
+
+impl Extend<(ImmutableString, Dynamic)> for Map {
+    #[inline(always)]
+    fn extend<T: IntoIterator<Item = (ImmutableString, Dynamic)>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|(k, v)| {
+            self.insert(k, v);
+        });
+    }
+}
+
+impl FromIterator<(ImmutableString, Dynamic)> for Map {
+    #[inline(always)]
+    fn from_iter<T: IntoIterator<Item = (ImmutableString, Dynamic)>>(iter: T) -> Self {
+        let iter = iter.into_iter();
+        let mut map = Self::with_capacity(iter.size_hint().0);
+        map.extend(iter);
+        map
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (ImmutableString, Dynamic);
+    type IntoIter = IntoIter;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(match self.0 {
+            Repr::Inline(list) => IntoIterRepr::Inline(list.into_iter()),
+            Repr::Hashed(map) => IntoIterRepr::Hashed(map.into_iter()),
+        })
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a ImmutableString, &'a Dynamic);
+    type IntoIter = Iter<'a>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Map {
+    type Item = (&'a ImmutableString, &'a mut Dynamic);
+    type IntoIter = IterMut<'a>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Iterator over the properties of a [`Map`].
+pub struct Iter<'a>(IterRepr<'a>);
+
+enum IterRepr<'a> {
+    Inline(slice::Iter<'a, (ImmutableString, Dynamic)>),
+    Hashed(hash_map::Iter<'a, ImmutableString, Dynamic>),
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a ImmutableString, &'a Dynamic);
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IterRepr::Inline(iter) => iter.next().map(|(k, v)| (k, v)),
+            IterRepr::Hashed(iter) => iter.next(),
+        }
+    }
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            IterRepr::Inline(iter) => iter.size_hint(),
+            IterRepr::Hashed(iter) => iter.size_hint(),
+        }
+    }
+}
+
+/// Iterator over the properties of a [`Map`], with mutable references to the values.
+pub struct IterMut<'a>(IterMutRepr<'a>);
+
+enum IterMutRepr<'a> {
+    Inline(slice::IterMut<'a, (ImmutableString, Dynamic)>),
+    Hashed(hash_map::IterMut<'a, ImmutableString, Dynamic>),
+}
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a ImmutableString, &'a mut Dynamic);
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IterMutRepr::Inline(iter) => iter.next().map(|(k, v)| (&*k, v)),
+            IterMutRepr::Hashed(iter) => iter.next(),
+        }
+    }
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            IterMutRepr::Inline(iter) => iter.size_hint(),
+            IterMutRepr::Hashed(iter) => iter.size_hint(),
+        }
+    }
+}
+
+/// Owning iterator over the properties of a [`Map`].
+pub struct IntoIter(IntoIterRepr);
+
+enum IntoIterRepr {
+    Inline(smallvec::IntoIter<[(ImmutableString, Dynamic); 4]>),
+    Hashed(hash_map::IntoIter<ImmutableString, Dynamic>),
+}
+
+impl Iterator for IntoIter {
+    type Item = (ImmutableString, Dynamic);
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IntoIterRepr::Inline(iter) => iter.next(),
+            IntoIterRepr::Hashed(iter) => iter.next(),
+        }
+    }
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            IntoIterRepr::Inline(iter) => iter.size_hint(),
+            IntoIterRepr::Hashed(iter) => iter.size_hint(),
+        }
+    }
+}
@@ -1,8 +1,11 @@
 //! Module implementing the [`AST`] optimizer.
 
-use crate::ast::{Expr, ScriptFnDef, Stmt};
+use crate::ast::{Expr, MapKey, ScriptFnDef, Stmt};
 use crate::dynamic::AccessMode;
-use crate::engine::{Imports, KEYWORD_DEBUG, KEYWORD_EVAL, KEYWORD_PRINT, KEYWORD_TYPE_OF};
+use crate::engine::{
+    Imports, KEYWORD_DEBUG, KEYWORD_EVAL, KEYWORD_FN_PTR_CALL, KEYWORD_IS_DEF_VAR, KEYWORD_PRINT,
+    KEYWORD_TYPE_OF,
+};
 use crate::fn_call::run_builtin_binary_op;
 use crate::parser::map_dynamic_to_expr;
 use crate::stdlib::{
@@ -10,13 +13,16 @@ use crate::stdlib::{
     hash::{Hash, Hasher},
     iter::empty,
     mem,
+    num::NonZeroU64,
     string::{String, ToString},
     vec,
     vec::Vec,
 };
 use crate::token::is_valid_identifier;
 use crate::utils::get_hasher;
-use crate::{calc_native_fn_hash, Dynamic, Engine, Module, Position, Scope, StaticVec, AST};
+use crate::{
+    calc_native_fn_hash, CharRange, Dynamic, Engine, Module, Position, Scope, StaticVec, AST,
+};
 
 /// Level of optimization performed.
 ///
@@ -60,6 +66,8 @@ struct State<'a> {
     changed: bool,
     /// Collection of constants to use for eager function evaluations.
     variables: Vec<(String, AccessMode, Expr)>,
+    /// [`Scope`] holding the constants and variables defined outside the script.
+    scope: &'a Scope<'a>,
     /// An [`Engine`] instance for eager function evaluation.
     engine: &'a Engine,
     /// Collection of sub-modules.
@@ -73,10 +81,16 @@ struct State<'a> {
 impl<'a> State<'a> {
     /// Create a new State.
     #[inline(always)]
-    pub fn new(engine: &'a Engine, lib: &'a [&'a Module], level: OptimizationLevel) -> Self {
+    pub fn new(
+        engine: &'a Engine,
+        scope: &'a Scope<'a>,
+        lib: &'a [&'a Module],
+        level: OptimizationLevel,
+    ) -> Self {
         Self {
             changed: false,
             variables: vec![],
+            scope,
             engine,
             mods: (&engine.global_sub_modules).into(),
             lib,
@@ -108,23 +122,48 @@ impl<'a> State<'a> {
     pub fn push_var(&mut self, name: &str, access: AccessMode, value: Expr) {
         self.variables.push((name.into(), access, value))
     }
-    /// Look up a constant from the list.
+    /// Look up a constant from the list, then from the [`Scope`].
     #[inline]
-    pub fn find_constant(&self, name: &str) -> Option<&Expr> {
+    pub fn find_constant(&self, name: &str) -> Option<Expr> {
         for (n, access, expr) in self.variables.iter().rev() {
             if n == name {
                 return if access.is_read_only() {
-                    Some(expr)
+                    Some(expr.clone())
                 } else {
                     None
                 };
             }
         }
 
-        None
+        // Look up the scope by name (which is indexed for large scopes) instead of copying it
+        let value = self.scope.get_dynamic_ref(name)?;
+
+        if value.is_read_only() {
+            map_dynamic_to_expr(value.flatten_clone(), Position::NONE)
+                .or_else(|| Some(Expr::Unit(Position::NONE)))
+        } else {
+            None
+        }
     }
 }
 
+/// Is a registered function marked as foldable?
+fn is_foldable_fn(state: &State, hash_fn: NonZeroU64) -> bool {
+    // Search registered functions, then packages, the same way they are called
+    state
+        .engine
+        .global_namespace
+        .is_fn_foldable(hash_fn)
+        .or_else(|| {
+            state
+                .engine
+                .global_modules
+                .iter()
+                .find_map(|m| m.is_fn_foldable(hash_fn))
+        })
+        .unwrap_or(false)
+}
+
 /// Call a registered function
 fn call_fn_with_constant_arguments(
     state: &State,
@@ -152,37 +191,289 @@ fn call_fn_with_constant_arguments(
         .map(|(v, _)| v)
 }
 
-/// Optimize a block of [statements][Stmt].
-fn optimize_stmt_block(
-    mut statements: Vec<Stmt>,
-    pos: Position,
-    state: &mut State,
-    preserve_result: bool,
-    count_promote_as_dirty: bool,
-) -> Stmt {
-    let orig_len = statements.len(); // Original number of statements in the block, for change detection
-    let orig_constants_len = state.variables.len(); // Original number of constants in the state, for restore later
+/// A node visited when walking [statements][Stmt] and [expressions][Expr].
+pub(crate) enum Node<'a> {
+    Stmt(&'a mut Stmt),
+    Expr(&'a mut Expr),
+}
+
+/// Walk a [statement][Stmt] depth-first, calling `f` on it and on all its nested statements and
+/// expressions.
+pub(crate) fn walk_stmt(stmt: &mut Stmt, f: &mut impl FnMut(Node)) {
+    f(Node::Stmt(stmt));
+
+    match stmt {
+        Stmt::Noop(_) | Stmt::Continue(_, _) => (),
+        Stmt::If(condition, x, _) => {
+            walk_expr(condition, f);
+            walk_stmt(&mut x.0, f);
+            if let Some(stmt) = x.1.as_mut() {
+                walk_stmt(stmt, f);
+            }
+        }
+        Stmt::Switch(expr, x, _) => {
+            walk_expr(expr, f);
+            for stmt in x.0.values_mut() {
+                walk_stmt(stmt, f);
+            }
+            if let Some(stmt) = x.1.as_mut() {
+                walk_stmt(stmt, f);
+            }
+            for (_, stmt) in x.2.iter_mut() {
+                walk_stmt(stmt, f);
+            }
+        }
+        Stmt::While(condition, x, _) | Stmt::Do(x, condition, _, _) => {
+            walk_expr(condition, f);
+            walk_stmt(&mut x.0, f);
+        }
+        Stmt::For(iterable, x, _) => {
+            walk_expr(iterable, f);
+            walk_stmt(&mut x.2, f);
+        }
+        Stmt::Let(_, expr, _, _)
+        | Stmt::Const(_, expr, _, _)
+        | Stmt::Break(_, expr, _)
+        | Stmt::Return(_, expr, _) => {
+            if let Some(expr) = expr {
+                walk_expr(expr, f);
+            }
+        }
+        #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+        Stmt::LetPattern(x, _, _, _) => walk_expr(&mut x.1, f),
+        Stmt::Assignment(x, _) => {
+            walk_expr(&mut x.0, f);
+            walk_expr(&mut x.2, f);
+        }
+        Stmt::Block(statements, _) => {
+            for stmt in statements.iter_mut() {
+                walk_stmt(stmt, f);
+            }
+        }
+        Stmt::TryCatch(x, _, _) => {
+            walk_stmt(&mut x.0, f);
+            for (_, _, guard, block) in x.1.iter_mut() {
+                if let Some(guard) = guard {
+                    walk_expr(guard, f);
+                }
+                walk_stmt(block, f);
+            }
+        }
+        Stmt::Expr(expr) => walk_expr(expr, f),
+        #[cfg(not(feature = "no_module"))]
+        Stmt::Import(expr, _, _) => walk_expr(expr, f),
+        #[cfg(not(feature = "no_module"))]
+        Stmt::Export(_, _) => (),
+        #[cfg(not(feature = "no_closure"))]
+        Stmt::Share(_) => (),
+    }
+}
+
+/// Walk an [expression][Expr] depth-first, calling `f` on it and on all its nested statements and
+/// expressions.
+fn walk_expr(expr: &mut Expr, f: &mut impl FnMut(Node)) {
+    f(Node::Expr(expr));
+
+    match expr {
+        Expr::Array(x, _) | Expr::IndexArgs(x, _) => {
+            for expr in x.iter_mut() {
+                walk_expr(expr, f);
+            }
+        }
+        Expr::Map(x, _) => {
+            for (key, expr) in x.iter_mut() {
+                if let MapKey::Computed(key) = key {
+                    walk_expr(key, f);
+                }
+                walk_expr(expr, f);
+            }
+        }
+        Expr::Stmt(x, _) => {
+            for stmt in x.iter_mut() {
+                walk_stmt(stmt, f);
+            }
+        }
+        Expr::FnCall(x, _) => {
+            for expr in x.args.iter_mut() {
+                walk_expr(expr, f);
+            }
+        }
+        Expr::Dot(x, _, _)
+        | Expr::Index(x, _, _)
+        | Expr::In(x, _)
+        | Expr::And(x, _)
+        | Expr::Or(x, _)
+        | Expr::Coalesce(x, _) => {
+            walk_expr(&mut x.lhs, f);
+            walk_expr(&mut x.rhs, f);
+        }
+        Expr::Custom(x, _) => {
+            for expr in x.keywords.iter_mut() {
+                walk_expr(expr, f);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// How a variable is used in a list of [statements][Stmt].
+#[derive(Debug, Clone, Copy, Default)]
+struct VarUsage {
+    /// Is the variable read?
+    read: bool,
+    /// Can the variable be modified, captured by a closure, or accessed dynamically?
+    modified: bool,
+}
 
-    // Optimize each statement in the block
-    statements.iter_mut().for_each(|stmt| match stmt {
+/// Find out how a variable is used in a list of [statements][Stmt].
+///
+/// This is conservative: any usage that may change the variable is treated as a modification,
+/// including method calls and indexing on the variable, passing it as the first argument to a
+/// function or the second argument to `call` (which may take it by `&mut`), capturing it in a closure, and any call to `eval`,
+/// `is_def_var` or custom syntax (which may access variables by name).
+fn var_usage(statements: &mut [Stmt], name: &str) -> VarUsage {
+    let is_var =
+        |expr: &Expr| matches!(expr, Expr::Variable(x) if x.1.is_none() && x.2.name == name);
+    let mut usage = VarUsage::default();
+
+    for stmt in statements.iter_mut() {
+        walk_stmt(stmt, &mut |node| match node {
+            Node::Stmt(Stmt::Assignment(x, _)) if is_var(&x.0) => usage.modified = true,
+            #[cfg(not(feature = "no_closure"))]
+            Node::Stmt(Stmt::Share(x)) if x.name == name => usage.modified = true,
+            #[cfg(not(feature = "no_module"))]
+            Node::Stmt(Stmt::Export(list, _)) if list.iter().any(|(x, _)| x.name == name) => {
+                usage.read = true
+            }
+            Node::Expr(expr) if is_var(expr) => usage.read = true,
+            Node::Expr(Expr::Dot(x, _, _)) | Node::Expr(Expr::Index(x, _, _)) if is_var(&x.lhs) => {
+                usage.modified = true
+            }
+            Node::Expr(Expr::FnCall(x, _))
+                if x.name == KEYWORD_EVAL
+                    || x.name == KEYWORD_IS_DEF_VAR
+                    || (is_valid_identifier(x.name.chars())
+                        && x.args.first().is_some_and(is_var))
+                    || (x.name == KEYWORD_FN_PTR_CALL && x.args.get(1).is_some_and(is_var)) =>
+            {
+                usage.modified = true
+            }
+            Node::Expr(Expr::Custom(_, _)) => usage.modified = true,
+            _ => (),
+        });
+    }
+
+    usage
+}
+
+/// Clear the cached offsets of all variables in a [statement][Stmt], so that they are searched
+/// in the scope by name instead.
+fn clear_var_indices(stmt: &mut Stmt) {
+    walk_stmt(stmt, &mut |node| {
+        if let Node::Expr(Expr::Variable(x)) = node {
+            x.0 = None;
+        }
+    });
+}
+
+/// Add the variables defined by a [statement][Stmt] into the state.
+///
+/// A variable defined by `let` with a constant value is added as a constant if it is not modified
+/// in the rest of the block, so that its value can be propagated.
+fn push_block_vars(stmt: &Stmt, rest: &mut [Stmt], state: &mut State) {
+    match stmt {
         // Add constant literals into the state
         Stmt::Const(var_def, Some(expr), _, _) if expr.is_constant() => {
-            state.push_var(&var_def.name, AccessMode::ReadOnly, mem::take(expr));
+            state.push_var(&var_def.name, AccessMode::ReadOnly, expr.clone());
         }
         Stmt::Const(var_def, None, _, _) => {
             state.push_var(&var_def.name, AccessMode::ReadOnly, Expr::Unit(var_def.pos));
         }
-        // Add variables into the state
-        Stmt::Let(var_def, _, _, _) => {
+        // Add variables never modified after being set to constant values into the state
+        Stmt::Let(var_def, Some(expr), _, _)
+            if expr.is_constant() && !var_usage(rest, &var_def.name).modified =>
+        {
+            state.push_var(&var_def.name, AccessMode::ReadOnly, expr.clone());
+        }
+        // Values only known at runtime cannot be propagated,
+        // but they still shadow any constant with the same name
+        Stmt::Const(var_def, _, _, _) | Stmt::Let(var_def, _, _, _) => {
             state.push_var(
                 &var_def.name,
                 AccessMode::ReadWrite,
                 Expr::Unit(var_def.pos),
             );
         }
-        // Optimize the statement
-        _ => optimize_stmt(stmt, state, preserve_result),
-    });
+        // Add destructured variables into the state
+        #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+        Stmt::LetPattern(x, _, _, _) => x.0.iter_vars().for_each(|var_def| {
+            state.push_var(
+                &var_def.name,
+                AccessMode::ReadWrite,
+                Expr::Unit(var_def.pos),
+            )
+        }),
+        _ => (),
+    }
+}
+
+/// Does a [statement][Stmt] always diverge, i.e. `return`, `throw`, `break` or `continue`
+/// on every path, so that all statements following it are unreachable?
+fn diverges(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::Return(_, _, _) | Stmt::Break(_, _, _) | Stmt::Continue(_, _) => true,
+        Stmt::If(_, x, _) => diverges(&x.0) && x.1.as_ref().is_some_and(diverges),
+        Stmt::Switch(_, x, _) => {
+            x.1.as_ref().is_some_and(diverges)
+                && x.0.values().all(diverges)
+                && x.2.iter().all(|(_, stmt)| diverges(stmt))
+        }
+        Stmt::Block(statements, _) => statements.iter().any(diverges),
+        Stmt::Expr(Expr::Stmt(statements, _)) => statements.iter().any(diverges),
+        _ => false,
+    }
+}
+
+/// Optimize a block of [statements][Stmt].
+fn optimize_stmt_block(
+    mut statements: Vec<Stmt>,
+    pos: Position,
+    state: &mut State,
+    preserve_result: bool,
+    count_promote_as_dirty: bool,
+) -> Stmt {
+    let orig_len = statements.len(); // Original number of statements in the block, for change detection
+    let orig_constants_len = state.variables.len(); // Original number of constants in the state, for restore later
+
+    // Optimize each statement in the block, adding the variables it defines into the state
+    for i in 0..statements.len() {
+        let (head, rest) = statements.split_at_mut(i + 1);
+        optimize_stmt(&mut head[i], state, preserve_result);
+        push_block_vars(&head[i], rest, state);
+    }
+
+    // Remove all `let` statements with constant values that are no longer used
+    let mut i = 0;
+
+    while i < statements.len() {
+        let (head, rest) = statements.split_at_mut(i + 1);
+
+        let dead = match &head[i] {
+            Stmt::Let(var_def, Some(expr), false, _) if expr.is_constant() => {
+                let usage = var_usage(rest, &var_def.name);
+                !usage.read && !usage.modified
+            }
+            _ => false,
+        };
+
+        if dead {
+            statements.remove(i);
+            // Variables are now at different offsets in the scope, so search for them by name
+            statements[i..].iter_mut().for_each(clear_var_indices);
+        } else {
+            i += 1;
+        }
+    }
 
     // Remove all raw expression statements that are pure except for the very last statement
     let last_stmt = if preserve_result {
@@ -221,29 +512,22 @@ fn optimize_stmt_block(
             statements.push(Stmt::Noop(pos))
         }
 
-        // Optimize all the statements again
-        let num_statements = statements.len();
-        statements
-            .iter_mut()
-            .enumerate()
-            .for_each(|(i, stmt)| optimize_stmt(stmt, state, i == num_statements));
-    }
-
-    // Remove everything following the the first return/throw
-    let mut dead_code = false;
+        // Optimize all the statements again, adding back the variables in order
+        state.restore_var(orig_constants_len);
 
-    statements.retain(|stmt| {
-        if dead_code {
-            return false;
-        }
+        let num_statements = statements.len();
 
-        match stmt {
-            Stmt::Return(_, _, _) | Stmt::Break(_) => dead_code = true,
-            _ => (),
+        for i in 0..num_statements {
+            let (head, rest) = statements.split_at_mut(i + 1);
+            optimize_stmt(&mut head[i], state, i == num_statements - 1);
+            push_block_vars(&head[i], rest, state);
         }
+    }
 
-        true
-    });
+    // Remove everything following the first statement that always diverges
+    if let Some(index) = statements.iter().position(diverges) {
+        statements.truncate(index + 1);
+    }
 
     // Change detection
     if orig_len != statements.len() {
@@ -263,6 +547,9 @@ fn optimize_stmt_block(
         [x] if matches!(x, Stmt::Let(_, _, _, _)) => Stmt::Block(statements, pos),
         // Only one const statement - leave it alone
         [x] if matches!(x, Stmt::Const(_, _, _, _)) => Stmt::Block(statements, pos),
+        // Only one let/const statement with a destructuring pattern - leave it alone
+        #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+        [x] if matches!(x, Stmt::LetPattern(_, _, _, _)) => Stmt::Block(statements, pos),
         // Only one import statement - leave it alone
         #[cfg(not(feature = "no_module"))]
         [x] if matches!(x, Stmt::Import(_, _, _)) => Stmt::Block(statements, pos),
@@ -290,7 +577,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut State, preserve_result: bool) {
         },
 
         // if false { if_block } -> Noop
-        Stmt::If(Expr::BoolConstant(false, pos), x, _) if x.1.is_none() => {
+        Stmt::If(Expr::BoolConstant(false, _), x, pos) if x.1.is_none() => {
             state.set_dirty();
             *stmt = Stmt::Noop(*pos);
         }
@@ -300,10 +587,10 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut State, preserve_result: bool) {
             optimize_stmt(stmt, state, true);
         }
         // if expr { Noop }
-        Stmt::If(condition, x, _) if x.1.is_none() && matches!(x.0, Stmt::Noop(_)) => {
+        Stmt::If(condition, x, pos) if x.1.is_none() && matches!(x.0, Stmt::Noop(_)) => {
             state.set_dirty();
 
-            let pos = condition.position();
+            let pos = *pos;
             let mut expr = mem::take(condition);
             optimize_expr(&mut expr, state);
 
@@ -346,7 +633,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut State, preserve_result: bool) {
             }
         }
 
-        // switch const { ... }
+        // switch const { ... } -> { matched_stmt }
         Stmt::Switch(expr, x, pos) if expr.is_constant() => {
             let value = expr.get_constant_value().unwrap();
             let hasher = &mut get_hasher();
@@ -355,23 +642,30 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut State, preserve_result: bool) {
 
             state.set_dirty();
 
-            let table = &mut x.0;
+            let pos = *pos;
 
-            if let Some(stmt) = table.get_mut(&hash) {
-                optimize_stmt(stmt, state, true);
-                *expr = Expr::Stmt(Box::new(vec![mem::take(stmt)].into()), *pos);
-            } else if let Some(def_stmt) = x.1.as_mut() {
-                optimize_stmt(def_stmt, state, true);
-                *expr = Expr::Stmt(Box::new(vec![mem::take(def_stmt)].into()), *pos);
-            } else {
-                *expr = Expr::Unit(*pos);
-            }
+            let matched = match x.0.get_mut(&hash) {
+                Some(stmt) => Some(mem::take(stmt)),
+                // Ranges are only checked after a hash miss, in order
+                None => match x.2.iter_mut().find(|(range, _)| range.contains(&value)) {
+                    Some((_, stmt)) => Some(mem::take(stmt)),
+                    None => x.1.as_mut().map(mem::take),
+                },
+            };
+
+            *stmt = match matched {
+                Some(matched) => Stmt::Block(vec![matched], pos),
+                None => Stmt::Noop(pos),
+            };
+            optimize_stmt(stmt, state, preserve_result);
         }
         // switch
         Stmt::Switch(expr, x, _) => {
             optimize_expr(expr, state);
             x.0.values_mut()
                 .for_each(|stmt| optimize_stmt(stmt, state, true));
+            x.2.iter_mut()
+                .for_each(|(_, stmt)| optimize_stmt(stmt, state, true));
             if let Some(def_stmt) = x.1.as_mut() {
                 optimize_stmt(def_stmt, state, true);
 
@@ -383,18 +677,19 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut State, preserve_result: bool) {
         }
 
         // while false { block } -> Noop
-        Stmt::While(Expr::BoolConstant(false, pos), _, _) => {
+        Stmt::While(Expr::BoolConstant(false, _), _, pos) => {
             state.set_dirty();
             *stmt = Stmt::Noop(*pos)
         }
         // while expr { block }
-        Stmt::While(condition, block, _) => {
-            optimize_stmt(block, state, false);
+        Stmt::While(condition, x, pos) => {
+            optimize_stmt(&mut x.0, state, false);
             optimize_expr(condition, state);
 
-            match **block {
+            match x.0 {
                 // while expr { break; } -> { expr; }
-                Stmt::Break(pos) => {
+                Stmt::Break(None, None, _) => {
+                    let pos = *pos;
                     // Only a single break statement - turn into running the guard expression once
                     state.set_dirty();
                     let mut statements = Vec::new();
@@ -408,26 +703,53 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut State, preserve_result: bool) {
             }
         }
         // do { block } while false | do { block } until true -> { block }
-        Stmt::Do(block, Expr::BoolConstant(true, _), false, _)
-        | Stmt::Do(block, Expr::BoolConstant(false, _), true, _) => {
-            state.set_dirty();
-            optimize_stmt(block.as_mut(), state, false);
-            *stmt = mem::take(block.as_mut());
+        Stmt::Do(x, Expr::BoolConstant(true, _), false, _)
+        | Stmt::Do(x, Expr::BoolConstant(false, _), true, _)
+            if x.1.is_none() =>
+        {
+            optimize_stmt(&mut x.0, state, false);
+
+            // The block must not break out of or continue the loop
+            let mut has_loop_control = false;
+            walk_stmt(&mut x.0, &mut |node| match node {
+                Node::Stmt(Stmt::Break(_, _, _)) | Node::Stmt(Stmt::Continue(_, _)) => {
+                    has_loop_control = true
+                }
+                _ => (),
+            });
+
+            if !has_loop_control {
+                state.set_dirty();
+                *stmt = mem::take(&mut x.0);
+            }
         }
         // do { block } while|until expr
-        Stmt::Do(block, condition, _, _) => {
-            optimize_stmt(block.as_mut(), state, false);
+        Stmt::Do(x, condition, _, _) => {
+            optimize_stmt(&mut x.0, state, false);
             optimize_expr(condition, state);
         }
         // for id in expr { block }
-        Stmt::For(iterable, x, _) => {
+        Stmt::For(iterable, x, pos) => {
             optimize_expr(iterable, state);
-            optimize_stmt(&mut x.1, state, false);
+
+            // The loop variables shadow any constant with the same name
+            let orig_constants_len = state.variables.len();
+            if let Some(counter) = x.0.as_ref() {
+                state.push_var(counter, AccessMode::ReadWrite, Expr::Unit(*pos));
+            }
+            state.push_var(&x.1, AccessMode::ReadWrite, Expr::Unit(*pos));
+            optimize_stmt(&mut x.2, state, false);
+            state.restore_var(orig_constants_len);
         }
         // let id = expr;
         Stmt::Let(_, Some(expr), _, _) => optimize_expr(expr, state),
+        // const id = expr;
+        Stmt::Const(_, Some(expr), _, _) => optimize_expr(expr, state),
         // let id;
         Stmt::Let(_, None, _, _) => (),
+        // let pattern = expr;
+        #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+        Stmt::LetPattern(x, _, _, _) => optimize_expr(&mut x.1, state),
         // import expr as var;
         #[cfg(not(feature = "no_module"))]
         Stmt::Import(expr, _, _) => optimize_expr(expr, state),
@@ -436,22 +758,37 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut State, preserve_result: bool) {
             *stmt = optimize_stmt_block(mem::take(statements), *pos, state, preserve_result, true);
         }
         // try { block } catch ( var ) { block }
-        Stmt::TryCatch(x, _, _) if x.0.is_pure() => {
+        Stmt::TryCatch(x, pos, _) if x.0.is_pure() => {
             // If try block is pure, there will never be any exceptions
             state.set_dirty();
-            let pos = x.0.position();
+            let pos = *pos;
             optimize_stmt(&mut x.0, state, preserve_result);
-            let mut statements = match mem::take(&mut x.0) {
+            let statements = match mem::take(&mut x.0) {
                 Stmt::Block(statements, _) => statements,
                 stmt => vec![stmt],
             };
-            statements.push(Stmt::Noop(pos));
             *stmt = Stmt::Block(statements, pos);
         }
-        // try { block } catch ( var ) { block }
+        // try { block } catch ( var [, pos_var] [if guard] ) { block } ...
         Stmt::TryCatch(x, _, _) => {
-            optimize_stmt(&mut x.0, state, false);
-            optimize_stmt(&mut x.2, state, false);
+            optimize_stmt(&mut x.0, state, preserve_result);
+            x.1.iter_mut()
+                .for_each(|(var_def, pos_var_def, guard, block)| {
+                    // The error variables shadow any constants with the same names
+                    let orig_constants_len = state.variables.len();
+                    for var_def in var_def.iter().chain(pos_var_def.iter()) {
+                        state.push_var(
+                            &var_def.name,
+                            AccessMode::ReadWrite,
+                            Expr::Unit(var_def.pos),
+                        );
+                    }
+                    if let Some(guard) = guard {
+                        optimize_expr(guard, state);
+                    }
+                    optimize_stmt(block, state, preserve_result);
+                    state.restore_var(orig_constants_len);
+                });
         }
         // {}
         Stmt::Expr(Expr::Stmt(x, pos)) if x.is_empty() => {
@@ -467,12 +804,27 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut State, preserve_result: bool) {
         Stmt::Expr(expr) => optimize_expr(expr, state),
         // return expr;
         Stmt::Return(_, Some(ref mut expr), _) => optimize_expr(expr, state),
+        // break expr;
+        Stmt::Break(_, Some(ref mut expr), _) => optimize_expr(expr, state),
 
         // All other statements - skip
         _ => (),
     }
 }
 
+/// Give a folded [expression][Expr] the [position][Position] of the original expression it replaces.
+///
+/// Constants and variables take the position of the original expression, so that errors involving
+/// their values are reported at the same place with or without optimization.
+/// All other expressions keep their own positions, which are used by errors raised within them.
+#[inline(always)]
+fn take_position(mut expr: Expr, pos: Position) -> Expr {
+    if expr.is_constant() || matches!(expr, Expr::Variable(_)) {
+        expr.set_position(pos);
+    }
+    expr
+}
+
 /// Optimize an [expression][Expr].
 fn optimize_expr(expr: &mut Expr, state: &mut State) {
     // These keywords are handled specially
@@ -492,22 +844,22 @@ fn optimize_expr(expr: &mut Expr, state: &mut State) {
             // { stmt, .. }
             Stmt::Block(statements, _) => *x = Box::new(statements.into()),
             // { expr }
-            Stmt::Expr(inner) => { state.set_dirty(); *expr = inner; }
+            Stmt::Expr(inner) => { state.set_dirty(); *expr = take_position(inner, *pos); }
             // { stmt }
             stmt => x.push(stmt),
         }
 
         // lhs.rhs
         #[cfg(not(feature = "no_object"))]
-        Expr::Dot(x, _) => match (&mut x.lhs, &mut x.rhs) {
+        Expr::Dot(x, _, _) => match (&mut x.lhs, &mut x.rhs) {
             // map.string
-            (Expr::Map(m, pos), Expr::Property(p)) if m.iter().all(|(_, x)| x.is_pure()) => {
+            (Expr::Map(m, pos), Expr::Property(p)) if m.iter().all(|(k, x)| k.name().is_some() && x.is_pure()) => {
                 let prop = &p.2.name;
                 // Map literal where everything is pure - promote the indexed item.
                 // All other items can be thrown away.
                 state.set_dirty();
-                *expr = mem::take(m).into_iter().find(|(x, _)| &x.name == prop)
-                            .map(|(_, mut expr)| { expr.set_position(*pos); expr })
+                *expr = mem::take(m).into_iter().find(|(x, _)| x.name() == Some(prop))
+                            .map(|(_, expr)| take_position(expr, *pos))
                             .unwrap_or_else(|| Expr::Unit(*pos));
             }
             // var.rhs
@@ -518,7 +870,7 @@ fn optimize_expr(expr: &mut Expr, state: &mut State) {
 
         // lhs[rhs]
         #[cfg(not(feature = "no_index"))]
-        Expr::Index(x, _) => match (&mut x.lhs, &mut x.rhs) {
+        Expr::Index(x, _, _) => match (&mut x.lhs, &mut x.rhs) {
             // array[int]
             (Expr::Array(a, pos), Expr::IntegerConstant(i, _))
                 if *i >= 0 && (*i as usize) < a.len() && a.iter().all(Expr::is_pure) =>
@@ -526,17 +878,15 @@ fn optimize_expr(expr: &mut Expr, state: &mut State) {
                 // Array literal where everything is pure - promote the indexed item.
                 // All other items can be thrown away.
                 state.set_dirty();
-                let mut result = a.remove(*i as usize);
-                result.set_position(*pos);
-                *expr = result;
+                *expr = take_position(a.remove(*i as usize), *pos);
             }
             // map[string]
-            (Expr::Map(m, pos), Expr::StringConstant(s, _)) if m.iter().all(|(_, x)| x.is_pure()) => {
+            (Expr::Map(m, pos), Expr::StringConstant(s, _)) if m.iter().all(|(k, x)| k.name().is_some() && x.is_pure()) => {
                 // Map literal where everything is pure - promote the indexed item.
                 // All other items can be thrown away.
                 state.set_dirty();
-                *expr = mem::take(m).into_iter().find(|(x, _)| x.name == *s)
-                            .map(|(_, mut expr)| { expr.set_position(*pos); expr })
+                *expr = mem::take(m).into_iter().find(|(x, _)| x.name() == Some(s))
+                            .map(|(_, expr)| take_position(expr, *pos))
                             .unwrap_or_else(|| Expr::Unit(*pos));
             }
             // string[int]
@@ -550,6 +900,9 @@ fn optimize_expr(expr: &mut Expr, state: &mut State) {
             // lhs[rhs]
             (lhs, rhs) => { optimize_expr(lhs, state); optimize_expr(rhs, state); }
         },
+        // lhs[idx1, idx2, ..]
+        #[cfg(not(feature = "no_index"))]
+        Expr::IndexArgs(x, _) => x.iter_mut().for_each(|expr| optimize_expr(expr, state)),
         // [ constant .. ]
         #[cfg(not(feature = "no_index"))]
         Expr::Array(_, _) if expr.is_constant() => {
@@ -567,7 +920,12 @@ fn optimize_expr(expr: &mut Expr, state: &mut State) {
         }
         // #{ key:value, .. }
         #[cfg(not(feature = "no_object"))]
-        Expr::Map(x, _) => x.iter_mut().for_each(|(_, expr)| optimize_expr(expr, state)),
+        Expr::Map(x, _) => x.iter_mut().for_each(|(key, expr)| {
+            if let MapKey::Computed(key) = key {
+                optimize_expr(key, state);
+            }
+            optimize_expr(expr, state);
+        }),
         // lhs in rhs
         Expr::In(x, _) => match (&mut x.lhs, &mut x.rhs) {
             // "xxx" in "xxxxx"
@@ -581,15 +939,20 @@ fn optimize_expr(expr: &mut Expr, state: &mut State) {
                 *expr = Expr::BoolConstant(b.contains(*a), *pos);
             }
             // "xxx" in #{...}
-            (Expr::StringConstant(a, pos), Expr::Map(b, _)) => {
+            (Expr::StringConstant(a, pos), Expr::Map(b, _)) if b.iter().all(|(x, _)| x.name().is_some()) => {
                 state.set_dirty();
-                *expr = Expr::BoolConstant(b.iter().find(|(x, _)| x.name == *a).is_some(), *pos);
+                *expr = Expr::BoolConstant(b.iter().find(|(x, _)| x.name() == Some(a)).is_some(), *pos);
             }
             // 'x' in #{...}
-            (Expr::CharConstant(a, pos), Expr::Map(b, _)) => {
+            (Expr::CharConstant(a, pos), Expr::Map(b, _)) if b.iter().all(|(x, _)| x.name().is_some()) => {
                 state.set_dirty();
                 let ch = a.to_string();
-                *expr = Expr::BoolConstant(b.iter().find(|(x, _)| x.name == &ch).is_some(), *pos);
+                *expr = Expr::BoolConstant(b.iter().any(|(x, _)| matches!(x.name(), Some(x) if *x == ch)), *pos);
+            }
+            // 'x' in 'a'..='z'
+            (Expr::CharConstant(a, pos), Expr::DynamicConstant(b, _)) if b.is::<CharRange>() => {
+                state.set_dirty();
+                *expr = Expr::BoolConstant(b.downcast_ref::<CharRange>().unwrap().contains(a), *pos);
             }
             // lhs in rhs
             (lhs, rhs) => { optimize_expr(lhs, state); optimize_expr(rhs, state); }
@@ -638,9 +1001,25 @@ fn optimize_expr(expr: &mut Expr, state: &mut State) {
             // lhs || rhs
             (lhs, rhs) => { optimize_expr(lhs, state); optimize_expr(rhs, state); }
         },
+        // lhs ?? rhs
+        Expr::Coalesce(x, _) => match x.lhs.get_constant_value() {
+            // () ?? rhs -> rhs
+            Some(value) if value.is::<()>() => {
+                state.set_dirty();
+                optimize_expr(&mut x.rhs, state);
+                *expr = mem::take(&mut x.rhs);
+            }
+            // constant ?? rhs -> constant
+            Some(_) => {
+                state.set_dirty();
+                *expr = mem::take(&mut x.lhs);
+            }
+            // lhs ?? rhs
+            None => { optimize_expr(&mut x.lhs, state); optimize_expr(&mut x.rhs, state); }
+        },
 
         // Do not call some special keywords
-        Expr::FnCall(x, _) if DONT_EVAL_KEYWORDS.contains(&x.name.as_ref()) => {
+        Expr::FnCall(x, _) if DONT_EVAL_KEYWORDS.contains(&x.name.as_str()) => {
             x.args.iter_mut().for_each(|a| optimize_expr(a, state));
         }
 
@@ -670,6 +1049,38 @@ fn optimize_expr(expr: &mut Expr, state: &mut State) {
             x.args.iter_mut().for_each(|a| optimize_expr(a, state));
         }
 
+        // Call registered pure functions
+        Expr::FnCall(x, pos)
+                if x.namespace.is_none() // Non-qualified
+                && state.optimization_level == OptimizationLevel::Simple // simple optimizations
+                && x.args.iter().all(Expr::is_constant) // all arguments are constants
+                && is_valid_identifier(x.name.chars()) // not an operator
+        => {
+            // First search for script-defined functions (can override registered functions)
+            #[cfg(not(feature = "no_function"))]
+            let has_script_fn = state.lib.iter().any(|&m| m.get_script_fn(x.name.as_ref(), x.args.len(), false).is_some());
+            #[cfg(feature = "no_function")]
+            let has_script_fn = false;
+
+            if !has_script_fn {
+                let mut arg_values: StaticVec<_> = x.args.iter().map(|e| e.get_constant_value().unwrap()).collect();
+                let hash_fn = calc_native_fn_hash(empty(), x.name.as_ref(), arg_values.iter().map(|a| a.type_id()));
+
+                // Errors abort folding and are raised when the script is run instead
+                if let Some(result) = hash_fn
+                                        .filter(|&hash| is_foldable_fn(state, hash))
+                                        .and_then(|_| call_fn_with_constant_arguments(&state, x.name.as_ref(), arg_values.as_mut()))
+                                        .and_then(|result| map_dynamic_to_expr(result, *pos))
+                {
+                    state.set_dirty();
+                    *expr = result;
+                    return;
+                }
+            }
+
+            x.args.iter_mut().for_each(|a| optimize_expr(a, state));
+        }
+
         // Eagerly call functions
         Expr::FnCall(x, pos)
                 if x.namespace.is_none() // Non-qualified
@@ -718,13 +1129,14 @@ fn optimize_expr(expr: &mut Expr, state: &mut State) {
         Expr::FnCall(x, _) => x.args.iter_mut().for_each(|a| optimize_expr(a, state)),
 
         // constant-name
-        Expr::Variable(x) if x.1.is_none() && state.find_constant(&x.2.name).is_some() => {
-            state.set_dirty();
+        Expr::Variable(x) if x.1.is_none() => {
+            if let Some(mut result) = state.find_constant(&x.2.name) {
+                state.set_dirty();
 
-            // Replace constant with value
-            let mut result = state.find_constant(&x.2.name).unwrap().clone();
-            result.set_position(x.2.pos);
-            *expr = result;
+                // Replace constant with value
+                result.set_position(x.2.pos);
+                *expr = result;
+            }
         }
 
         // Custom syntax
@@ -749,19 +1161,8 @@ fn optimize_top_level(
         return statements;
     }
 
-    // Set up the state
-    let mut state = State::new(engine, lib, level);
-
-    // Add constants and variables from the scope
-    scope.iter().for_each(|(name, constant, value)| {
-        if !constant {
-            state.push_var(name, AccessMode::ReadWrite, Expr::Unit(Position::NONE));
-        } else if let Some(val) = map_dynamic_to_expr(value, Position::NONE) {
-            state.push_var(name, AccessMode::ReadOnly, val);
-        } else {
-            state.push_var(name, AccessMode::ReadOnly, Expr::Unit(Position::NONE));
-        }
-    });
+    // Set up the state - constants and variables from the scope are looked up directly
+    let mut state = State::new(engine, scope, lib, level);
 
     let orig_constants_len = state.variables.len();
 
@@ -772,7 +1173,10 @@ fn optimize_top_level(
 
         let num_statements = statements.len();
 
-        statements.iter_mut().enumerate().for_each(|(i, stmt)| {
+        for i in 0..num_statements {
+            let (head, rest) = statements.split_at_mut(i + 1);
+            let stmt = &mut head[i];
+
             match stmt {
                 Stmt::Const(var_def, expr, _, _) if expr.is_some() => {
                     // Load constants
@@ -781,6 +1185,14 @@ fn optimize_top_level(
 
                     if value_expr.is_constant() {
                         state.push_var(&var_def.name, AccessMode::ReadOnly, value_expr.clone());
+                    } else {
+                        // Values only known at runtime cannot be propagated,
+                        // but they still shadow any constant with the same name
+                        state.push_var(
+                            &var_def.name,
+                            AccessMode::ReadWrite,
+                            Expr::Unit(var_def.pos),
+                        );
                     }
 
                     // Keep it in the global scope
@@ -792,12 +1204,15 @@ fn optimize_top_level(
                 Stmt::Const(var_def, None, _, _) => {
                     state.push_var(&var_def.name, AccessMode::ReadOnly, Expr::Unit(var_def.pos));
                 }
-                Stmt::Let(var_def, _, _, _) => {
-                    state.push_var(
-                        &var_def.name,
-                        AccessMode::ReadWrite,
-                        Expr::Unit(var_def.pos),
-                    );
+                // Variables are kept in the global scope, but constant values are still propagated
+                Stmt::Let(_, _, _, _) => {
+                    optimize_stmt(stmt, &mut state, false);
+                    push_block_vars(stmt, rest, &mut state);
+                }
+                #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+                Stmt::LetPattern(_, _, _, _) => {
+                    optimize_stmt(stmt, &mut state, false);
+                    push_block_vars(stmt, rest, &mut state);
                 }
                 _ => {
                     // Keep all variable declarations at this level
@@ -811,13 +1226,18 @@ fn optimize_top_level(
                     optimize_stmt(stmt, &mut state, keep);
                 }
             }
-        });
+        }
 
         if !state.is_dirty() {
             break;
         }
     }
 
+    // Remove everything following the first statement that always diverges
+    if let Some(index) = statements.iter().position(diverges) {
+        statements.truncate(index + 1);
+    }
+
     // Eliminate code that is pure but always keep the last statement
     let last_stmt = statements.pop();
 
@@ -862,8 +1282,12 @@ pub fn optimize_into_ast(
                 .map(|fn_def| ScriptFnDef {
                     name: fn_def.name.clone(),
                     access: fn_def.access,
+                    #[cfg(not(feature = "no_module"))]
+                    exported: fn_def.exported,
                     body: Default::default(),
                     params: fn_def.params.clone(),
+                    defaults: fn_def.defaults.clone(),
+                    variadic: fn_def.variadic,
                     #[cfg(not(feature = "no_closure"))]
                     externals: fn_def.externals.clone(),
                     lib: None,
@@ -931,3 +1355,108 @@ pub fn optimize_into_ast(
         lib,
     )
 }
+
+#[cfg(test)]
+#[cfg(not(feature = "no_optimize"))]
+mod tests {
+    use super::{walk_stmt, Node};
+    use crate::{Engine, OptimizationLevel};
+
+    /// Count the number of statements and expressions in a script after optimization.
+    #[allow(deprecated)]
+    fn count_nodes(engine: &mut Engine, level: OptimizationLevel, script: &str) -> usize {
+        engine.set_optimization_level(level);
+
+        let mut statements = engine.compile(script).unwrap().statements().to_vec();
+        let mut count = 0;
+
+        statements
+            .iter_mut()
+            .for_each(|stmt| walk_stmt(stmt, &mut |_: Node| count += 1));
+
+        count
+    }
+
+    /// Count the number of statements in a script after optimization.
+    #[allow(deprecated)]
+    fn count_stmts(engine: &mut Engine, level: OptimizationLevel, script: &str) -> usize {
+        engine.set_optimization_level(level);
+
+        let mut statements = engine.compile(script).unwrap().statements().to_vec();
+        let mut count = 0;
+
+        statements.iter_mut().for_each(|stmt| {
+            walk_stmt(stmt, &mut |node| {
+                if let Node::Stmt(_) = node {
+                    count += 1
+                }
+            })
+        });
+
+        count
+    }
+
+    #[test]
+    fn check_propagate_let() {
+        let engine = &mut Engine::new();
+
+        // Constants are propagated and the dead bindings removed
+        let script = "{ let x = 2; let y = x * 3; y + 1 }";
+        assert_eq!(count_nodes(engine, OptimizationLevel::None, script), 11);
+        assert_eq!(count_nodes(engine, OptimizationLevel::Simple, script), 2);
+
+        // Only the unmodified variable is propagated
+        let script = "{ let x = 2; let y = x * 3; y += 1; y }";
+        assert_eq!(count_nodes(engine, OptimizationLevel::None, script), 12);
+        assert_eq!(count_nodes(engine, OptimizationLevel::Simple, script), 8);
+
+        // Variables at global level are kept
+        let script = "let x = 2; let y = x * 3; y + 1";
+        assert_eq!(count_nodes(engine, OptimizationLevel::None, script), 10);
+        assert_eq!(count_nodes(engine, OptimizationLevel::Simple, script), 6);
+    }
+
+    #[test]
+    fn check_dead_code() {
+        let engine = &mut Engine::new();
+
+        let scripts = [
+            ("foo(); return 1; foo(); foo()", 4, 2),
+            (
+                "{ let x = foo(); if x > 1 { return 1; } else { throw 2; } x += 1; foo(x); }",
+                9,
+                5,
+            ),
+            (
+                "loop { let x = foo(); if x { continue; } else { break; } x = 1; foo(x); }",
+                10,
+                6,
+            ),
+            (
+                "{ switch foo() { 1 => return 1, _ => throw 2 } foo(); }",
+                5,
+                3,
+            ),
+            (
+                "loop { while false { foo(); } if false { foo(); } foo(); break; foo(); }",
+                11,
+                4,
+            ),
+        ];
+
+        for &(script, before, after) in &scripts {
+            assert_eq!(
+                count_stmts(engine, OptimizationLevel::None, script),
+                before,
+                "{}",
+                script
+            );
+            assert_eq!(
+                count_stmts(engine, OptimizationLevel::Simple, script),
+                after,
+                "{}",
+                script
+            );
+        }
+    }
+}
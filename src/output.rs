@@ -0,0 +1,130 @@
+//! Module defining the output of `print` and `debug` captured during an evaluation.
+
+use crate::stdlib::{slice, vec, vec::Vec};
+use crate::{ImmutableString, Position};
+
+/// Kind of output produced by a script.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum OutputKind {
+    /// Output of the `print` function.
+    Print,
+    /// Output of the `debug` function.
+    Debug,
+}
+
+/// A single piece of output produced by a script via `print` or `debug`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct OutputEntry {
+    /// Whether the output is produced by `print` or `debug`.
+    pub kind: OutputKind,
+    /// Text of the output.
+    pub text: ImmutableString,
+    /// Position of the `print` or `debug` call.
+    pub position: Position,
+    /// Source of the script producing the output, if any.
+    pub source: Option<ImmutableString>,
+}
+
+/// Output of `print` and `debug` captured during an evaluation, in the order it is produced.
+///
+/// Returned by [`Engine::eval_with_captured_output`][crate::Engine::eval_with_captured_output]
+/// and [`Engine::eval_ast_with_captured_output`][crate::Engine::eval_ast_with_captured_output].
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use rhai::{Engine, OutputKind, Scope, INT};
+///
+/// let mut engine = Engine::new();
+///
+/// // Silence the default output to stdout
+/// engine.on_print(|_| {}).on_debug(|_, _, _| {});
+///
+/// let (result, output) = engine.eval_with_captured_output(
+///     &mut Scope::new(),
+///     r#"print("hello"); debug(42); 0"#,
+/// )?;
+///
+/// assert_eq!(result.cast::<INT>(), 0);
+/// assert_eq!(output.len(), 2);
+/// assert_eq!(output.entries()[0].kind, OutputKind::Print);
+/// assert_eq!(output.entries()[0].text, "hello");
+/// assert_eq!(output.entries()[1].kind, OutputKind::Debug);
+/// assert_eq!(output.entries()[1].text, "42");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct CapturedOutput(Vec<OutputEntry>);
+
+impl CapturedOutput {
+    /// Create a new, empty [`CapturedOutput`].
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Record a new entry.
+    #[inline(always)]
+    pub(crate) fn push(
+        &mut self,
+        kind: OutputKind,
+        text: impl Into<ImmutableString>,
+        position: Position,
+        source: Option<ImmutableString>,
+    ) {
+        self.0.push(OutputEntry {
+            kind,
+            text: text.into(),
+            position,
+            source,
+        });
+    }
+    /// Number of entries captured.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    /// Is no output captured?
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Get all the entries captured, in order.
+    #[inline(always)]
+    pub fn entries(&self) -> &[OutputEntry] {
+        &self.0
+    }
+    /// Get an iterator over all the entries captured, in order.
+    #[inline(always)]
+    pub fn iter(&self) -> slice::Iter<'_, OutputEntry> {
+        self.0.iter()
+    }
+}
+
+impl IntoIterator for CapturedOutput {
+    type Item = OutputEntry;
+    type IntoIter = vec::IntoIter<OutputEntry>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a CapturedOutput {
+    type Item = &'a OutputEntry;
+    type IntoIter = slice::Iter<'a, OutputEntry>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl From<CapturedOutput> for Vec<OutputEntry> {
+    #[inline(always)]
+    fn from(output: CapturedOutput) -> Self {
+        output.0
+    }
+}
@@ -1,7 +1,8 @@
 #![allow(non_snake_case)]
 
+use crate::engine::{calc_bit_offset, INT_BITS};
 use crate::plugin::*;
-use crate::stdlib::{format, string::String};
+use crate::stdlib::{format, ops::Range, string::String};
 use crate::{def_package, EvalAltResult, Position, INT};
 
 #[cfg(not(feature = "no_float"))]
@@ -169,6 +170,60 @@ macro_rules! gen_signed_functions {
     }
 }
 
+macro_rules! gen_safe_division_functions {
+    ($root:ident => $($arg_type:ident),+) => {
+        pub mod $root { $(pub mod $arg_type {
+            use super::super::*;
+
+            #[export_module]
+            pub mod functions {
+                pub fn checked_div(x: $arg_type, y: $arg_type) -> Dynamic {
+                    x.checked_div(y).map_or(Dynamic::UNIT, Dynamic::from)
+                }
+                pub fn checked_mod(x: $arg_type, y: $arg_type) -> Dynamic {
+                    x.checked_rem(y).map_or(Dynamic::UNIT, Dynamic::from)
+                }
+                pub fn div_or(x: $arg_type, y: $arg_type, default: $arg_type) -> $arg_type {
+                    x.checked_div(y).unwrap_or(default)
+                }
+                pub fn mod_or(x: $arg_type, y: $arg_type, default: $arg_type) -> $arg_type {
+                    x.checked_rem(y).unwrap_or(default)
+                }
+            }
+        })* }
+    }
+}
+
+macro_rules! gen_min_max_functions {
+    ($root:ident => $($arg_type:ident),+) => {
+        pub mod $root { $(pub mod $arg_type {
+            use super::super::*;
+
+            #[export_module]
+            pub mod functions {
+                pub fn min(x: $arg_type, y: $arg_type) -> $arg_type {
+                    if y < x { y } else { x }
+                }
+                pub fn max(x: $arg_type, y: $arg_type) -> $arg_type {
+                    if y > x { y } else { x }
+                }
+                #[rhai_fn(return_raw)]
+                pub fn clamp(x: $arg_type, min: $arg_type, max: $arg_type) -> Result<Dynamic, Box<EvalAltResult>> {
+                    if min > max {
+                        Err(make_err(format!("Invalid range for clamp: {} > {}", min, max)))
+                    } else if x < min {
+                        Ok(Dynamic::from(min))
+                    } else if x > max {
+                        Ok(Dynamic::from(max))
+                    } else {
+                        Ok(Dynamic::from(x))
+                    }
+                }
+            }
+        })* }
+    }
+}
+
 macro_rules! reg_functions {
     ($mod_name:ident += $root:ident ; $($arg_type:ident),+ ) => { $(
         combine_with_exported_module!($mod_name, "arithmetic", $root::$arg_type::functions);
@@ -177,17 +232,27 @@ macro_rules! reg_functions {
 
 def_package!(crate:ArithmeticPackage:"Basic arithmetic", lib, {
     reg_functions!(lib += signed_basic; INT);
+    reg_functions!(lib += safe_basic; INT);
+    reg_functions!(lib += min_max_basic; INT);
+
+    // Bit-field functions for integers
+    combine_with_exported_module!(lib, "bit_field", bit_field_functions);
+    lib.set_iterator::<BitRange>();
 
     #[cfg(not(feature = "only_i32"))]
     #[cfg(not(feature = "only_i64"))]
     {
         reg_functions!(lib += arith_numbers; i8, u8, i16, u16, i32, u32, u64);
         reg_functions!(lib += signed_numbers; i8, i16, i32);
+        reg_functions!(lib += safe_numbers; i8, u8, i16, u16, i32, u32, u64);
+        reg_functions!(lib += min_max_numbers; i8, u8, i16, u16, i32, u32, u64);
 
         #[cfg(not(target_arch = "wasm32"))]
         {
             reg_functions!(lib += arith_num_128; i128, u128);
             reg_functions!(lib += signed_num_128; i128);
+            reg_functions!(lib += safe_num_128; i128, u128);
+            reg_functions!(lib += min_max_num_128; i128, u128);
         }
     }
 
@@ -221,6 +286,91 @@ gen_signed_functions!(signed_numbers => i8, i16, i32);
 #[cfg(not(target_arch = "wasm32"))]
 gen_signed_functions!(signed_num_128 => i128);
 
+gen_safe_division_functions!(safe_basic => INT);
+
+#[cfg(not(feature = "only_i32"))]
+#[cfg(not(feature = "only_i64"))]
+gen_safe_division_functions!(safe_numbers => i8, u8, i16, u16, i32, u32, u64);
+
+#[cfg(not(feature = "only_i32"))]
+#[cfg(not(feature = "only_i64"))]
+#[cfg(not(target_arch = "wasm32"))]
+gen_safe_division_functions!(safe_num_128 => i128, u128);
+
+gen_min_max_functions!(min_max_basic => INT);
+
+#[cfg(not(feature = "only_i32"))]
+#[cfg(not(feature = "only_i64"))]
+gen_min_max_functions!(min_max_numbers => i8, u8, i16, u16, i32, u32, u64);
+
+#[cfg(not(feature = "only_i32"))]
+#[cfg(not(feature = "only_i64"))]
+#[cfg(not(target_arch = "wasm32"))]
+gen_min_max_functions!(min_max_num_128 => i128, u128);
+
+/// An iterator over a range of bits inside an [`INT`], yielding each bit as a `bool`.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct BitRange(INT, usize, usize);
+
+impl Iterator for BitRange {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.1 < self.2 {
+            let bit = (self.0 & (1 << self.1)) != 0;
+            self.1 += 1;
+            Some(bit)
+        } else {
+            None
+        }
+    }
+}
+
+#[export_module]
+mod bit_field_functions {
+    #[rhai_fn(return_raw)]
+    pub fn get_bit(value: INT, index: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        let offset = calc_bit_offset(index)?;
+        Ok(((value & (1 << offset)) != 0).into())
+    }
+    #[rhai_fn(return_raw)]
+    pub fn set_bit(
+        value: &mut INT,
+        index: INT,
+        new_bit: bool,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let offset = calc_bit_offset(index)?;
+        let mask = 1 << offset;
+
+        if new_bit {
+            *value |= mask;
+        } else {
+            *value &= !mask;
+        }
+
+        Ok(Dynamic::UNIT)
+    }
+    #[rhai_fn(name = "bits")]
+    pub fn bits_all(value: INT) -> BitRange {
+        BitRange(value, 0, INT_BITS)
+    }
+    #[rhai_fn(name = "bits", return_raw)]
+    pub fn bits_range(value: INT, range: Range<INT>) -> Result<Dynamic, Box<EvalAltResult>> {
+        let bits = INT_BITS as INT;
+
+        for &index in [range.start, range.end].iter() {
+            if index < 0 || index > bits {
+                return EvalAltResult::ErrorBitFieldBounds(INT_BITS, index, Position::NONE).into();
+            }
+        }
+
+        let from = range.start as usize;
+        let to = range.end.max(range.start) as usize;
+
+        Ok(Dynamic::from(BitRange(value, from, to)))
+    }
+}
+
 #[cfg(not(feature = "no_float"))]
 #[export_module]
 mod f32_functions {
@@ -251,6 +401,34 @@ mod f32_functions {
             Ok(Dynamic::from(x.powf(y)))
         }
     }
+    pub fn checked_div(x: f32, y: f32) -> Dynamic {
+        if y == 0.0 {
+            Dynamic::UNIT
+        } else {
+            Dynamic::from(x / y)
+        }
+    }
+    pub fn checked_mod(x: f32, y: f32) -> Dynamic {
+        if y == 0.0 {
+            Dynamic::UNIT
+        } else {
+            Dynamic::from(x % y)
+        }
+    }
+    pub fn div_or(x: f32, y: f32, default: f32) -> f32 {
+        if y == 0.0 {
+            default
+        } else {
+            x / y
+        }
+    }
+    pub fn mod_or(x: f32, y: f32, default: f32) -> f32 {
+        if y == 0.0 {
+            default
+        } else {
+            x % y
+        }
+    }
     #[rhai_fn(name = "-")]
     pub fn neg(x: f32) -> f32 {
         -x
@@ -262,6 +440,27 @@ mod f32_functions {
     pub fn abs(x: f32) -> f32 {
         x.abs()
     }
+    pub fn min(x: f32, y: f32) -> f32 {
+        x.min(y)
+    }
+    pub fn max(x: f32, y: f32) -> f32 {
+        x.max(y)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn clamp(x: f32, min: f32, max: f32) -> Result<Dynamic, Box<EvalAltResult>> {
+        if min > max {
+            Err(make_err(format!(
+                "Invalid range for clamp: {} > {}",
+                min, max
+            )))
+        } else if x < min {
+            Ok(Dynamic::from(min))
+        } else if x > max {
+            Ok(Dynamic::from(max))
+        } else {
+            Ok(Dynamic::from(x))
+        }
+    }
     pub fn sign(x: f32) -> INT {
         if x == 0.0 {
             0
@@ -314,6 +513,34 @@ mod f64_functions {
             Ok(Dynamic::from(x.powf(y)))
         }
     }
+    pub fn checked_div(x: f64, y: f64) -> Dynamic {
+        if y == 0.0 {
+            Dynamic::UNIT
+        } else {
+            Dynamic::from(x / y)
+        }
+    }
+    pub fn checked_mod(x: f64, y: f64) -> Dynamic {
+        if y == 0.0 {
+            Dynamic::UNIT
+        } else {
+            Dynamic::from(x % y)
+        }
+    }
+    pub fn div_or(x: f64, y: f64, default: f64) -> f64 {
+        if y == 0.0 {
+            default
+        } else {
+            x / y
+        }
+    }
+    pub fn mod_or(x: f64, y: f64, default: f64) -> f64 {
+        if y == 0.0 {
+            default
+        } else {
+            x % y
+        }
+    }
     #[rhai_fn(name = "-")]
     pub fn neg(x: f64) -> f64 {
         -x
@@ -325,6 +552,27 @@ mod f64_functions {
     pub fn abs(x: f64) -> f64 {
         x.abs()
     }
+    pub fn min(x: f64, y: f64) -> f64 {
+        x.min(y)
+    }
+    pub fn max(x: f64, y: f64) -> f64 {
+        x.max(y)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn clamp(x: f64, min: f64, max: f64) -> Result<Dynamic, Box<EvalAltResult>> {
+        if min > max {
+            Err(make_err(format!(
+                "Invalid range for clamp: {} > {}",
+                min, max
+            )))
+        } else if x < min {
+            Ok(Dynamic::from(min))
+        } else if x > max {
+            Ok(Dynamic::from(max))
+        } else {
+            Ok(Dynamic::from(x))
+        }
+    }
     pub fn sign(x: f64) -> INT {
         if x == 0.0 {
             0
@@ -1,14 +1,19 @@
 #![cfg(not(feature = "no_index"))]
 #![allow(non_snake_case)]
 
-use crate::engine::{OP_EQUALS, TYPICAL_ARRAY_SIZE};
+use crate::dynamic::{map_std_type_name, Variant};
+use crate::engine::{OP_EQUALS, OP_LESS_THAN, TYPICAL_ARRAY_SIZE};
+use crate::packages::arithmetic::make_err;
 use crate::plugin::*;
-use crate::stdlib::{any::TypeId, boxed::Box, cmp::max, cmp::Ordering, string::ToString};
+use crate::stdlib::{any::TypeId, boxed::Box, cmp::max, format, mem, string::ToString};
 use crate::{
     def_package, Array, Dynamic, EvalAltResult, FnPtr, ImmutableString, NativeCallContext,
-    Position, INT,
+    Position, TypedArray, INT,
 };
 
+#[cfg(not(feature = "no_float"))]
+use crate::FLOAT;
+
 #[cfg(not(feature = "no_object"))]
 use crate::Map;
 
@@ -52,6 +57,22 @@ macro_rules! gen_array_functions {
 
                     Ok(Dynamic::UNIT)
                 }
+
+                #[rhai_fn(name = "push", name = "+=", return_raw)]
+                pub fn push_typed(list: &mut TypedArray, item: $arg_type) -> Result<Dynamic, Box<EvalAltResult>> {
+                    list.push(Dynamic::from(item))?;
+                    Ok(Dynamic::UNIT)
+                }
+
+                #[rhai_fn(name = "insert", return_raw)]
+                pub fn insert_typed(list: &mut TypedArray, position: INT, item: $arg_type) -> Result<Dynamic, Box<EvalAltResult>> {
+                    typed_array_insert(list, position, Dynamic::from(item))
+                }
+
+                #[rhai_fn(name = "pad", return_raw)]
+                pub fn pad_typed(_ctx: NativeCallContext, list: &mut TypedArray, len: INT, item: $arg_type) -> Result<Dynamic, Box<EvalAltResult>> {
+                    typed_array_pad(_ctx, list, len, Dynamic::from(item))
+                }
             }
         })* }
     }
@@ -60,6 +81,7 @@ macro_rules! gen_array_functions {
 macro_rules! reg_functions {
     ($mod_name:ident += $root:ident ; $($arg_type:ident),+) => { $(
         combine_with_exported_module!($mod_name, "array_functions", $root::$arg_type::functions);
+        $mod_name.set_indexer_set_fn(typed_array_set::<$arg_type>);
     )* }
 }
 
@@ -84,8 +106,12 @@ def_package!(crate:BasicArrayPackage:"Basic array utilities.", lib, {
     // Merge in the module at the end to override `+=` for arrays
     combine_with_exported_module!(lib, "array", array_functions);
 
-    // Register array iterator
+    // Typed arrays validate every element added to them
+    combine_with_exported_module!(lib, "typed_array", typed_array_functions);
+
+    // Register array iterators
     lib.set_iterable::<Array>();
+    lib.set_iterable::<TypedArray>();
 });
 
 #[export_module]
@@ -103,6 +129,62 @@ mod array_functions {
         x.extend(y);
         x
     }
+    #[rhai_fn(name = "*", return_raw)]
+    pub fn repeat(
+        _ctx: NativeCallContext,
+        list: &mut Array,
+        count: INT,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if count < 0 {
+            return EvalAltResult::ErrorArithmetic(
+                format!("Array repeated a negative number of times: {}", count),
+                Position::NONE,
+            )
+            .into();
+        }
+
+        if count == 0 || list.is_empty() {
+            return Ok(Array::new().into());
+        }
+
+        use crate::stdlib::convert::TryFrom;
+
+        let len = match usize::try_from(count)
+            .ok()
+            .and_then(|count| list.len().checked_mul(count))
+        {
+            Some(len) => len,
+            None => {
+                return EvalAltResult::ErrorDataTooLarge(
+                    "Size of array".to_string(),
+                    Position::NONE,
+                )
+                .into()
+            }
+        };
+
+        // Check if array will be over max size limit before allocating it
+        #[cfg(not(feature = "unchecked"))]
+        {
+            let max = _ctx.engine().max_array_size();
+
+            if max > 0 && len > max {
+                return EvalAltResult::ErrorDataTooLarge(
+                    format!("Size of array (limit {})", max),
+                    Position::NONE,
+                )
+                .into();
+            }
+        }
+
+        let mut array = Array::with_capacity(len);
+
+        for _ in 0..count {
+            array.extend_from_slice(list);
+        }
+
+        Ok(array.into())
+    }
     pub fn pop(list: &mut Array) -> Dynamic {
         list.pop().unwrap_or_else(|| ().into())
     }
@@ -214,6 +296,7 @@ mod array_functions {
                     .map_err(|err| {
                         Box::new(EvalAltResult::ErrorInFunctionCall(
                             "map".to_string(),
+                            String::new(),
                             err,
                             Position::NONE,
                         ))
@@ -245,6 +328,7 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "filter".to_string(),
+                        String::new(),
                         err,
                         Position::NONE,
                     ))
@@ -278,6 +362,7 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "index_of".to_string(),
+                        String::new(),
                         err,
                         Position::NONE,
                     ))
@@ -311,6 +396,7 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "some".to_string(),
+                        String::new(),
                         err,
                         Position::NONE,
                     ))
@@ -344,6 +430,7 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "all".to_string(),
+                        String::new(),
                         err,
                         Position::NONE,
                     ))
@@ -358,6 +445,40 @@ mod array_functions {
         Ok(true.into())
     }
     #[rhai_fn(return_raw)]
+    pub fn for_each(
+        ctx: NativeCallContext,
+        list: &mut Array,
+        action: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        for (i, item) in list.iter().enumerate() {
+            let result = action
+                .call_dynamic(ctx, None, [item.clone()])
+                .or_else(|err| match *err {
+                    EvalAltResult::ErrorFunctionNotFound(fn_sig, _)
+                        if fn_sig.starts_with(action.fn_name()) =>
+                    {
+                        action.call_dynamic(ctx, None, [item.clone(), (i as INT).into()])
+                    }
+                    _ => Err(err),
+                })
+                .map_err(|err| {
+                    Box::new(EvalAltResult::ErrorInFunctionCall(
+                        "for_each".to_string(),
+                        String::new(),
+                        err,
+                        Position::NONE,
+                    ))
+                })?;
+
+            // Only an explicit `false` stops the iteration
+            if let Ok(false) = result.as_bool() {
+                return Ok(false.into());
+            }
+        }
+
+        Ok(true.into())
+    }
+    #[rhai_fn(return_raw)]
     pub fn reduce(
         ctx: NativeCallContext,
         list: &mut Array,
@@ -379,6 +500,7 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "reduce".to_string(),
+                        String::new(),
                         err,
                         Position::NONE,
                     ))
@@ -397,6 +519,7 @@ mod array_functions {
         let mut result = initial.call_dynamic(ctx, None, []).map_err(|err| {
             Box::new(EvalAltResult::ErrorInFunctionCall(
                 "reduce".to_string(),
+                String::new(),
                 err,
                 Position::NONE,
             ))
@@ -416,6 +539,7 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "reduce".to_string(),
+                        String::new(),
                         err,
                         Position::NONE,
                     ))
@@ -446,6 +570,7 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "reduce_rev".to_string(),
+                        String::new(),
                         err,
                         Position::NONE,
                     ))
@@ -464,6 +589,7 @@ mod array_functions {
         let mut result = initial.call_dynamic(ctx, None, []).map_err(|err| {
             Box::new(EvalAltResult::ErrorInFunctionCall(
                 "reduce_rev".to_string(),
+                String::new(),
                 err,
                 Position::NONE,
             ))
@@ -483,6 +609,7 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "reduce_rev".to_string(),
+                        String::new(),
                         err,
                         Position::NONE,
                     ))
@@ -491,39 +618,209 @@ mod array_functions {
 
         Ok(result)
     }
-    #[rhai_fn(return_raw)]
+    #[rhai_fn(name = "sort", name = "sort_by", return_raw)]
     pub fn sort(
         ctx: NativeCallContext,
         list: &mut Array,
         comparer: FnPtr,
     ) -> Result<Dynamic, Box<EvalAltResult>> {
-        list.sort_by(|x, y| {
-            comparer
+        #[cfg(not(feature = "unchecked"))]
+        let max_operations = ctx.engine().max_operations();
+        #[cfg(not(feature = "unchecked"))]
+        let mut operations: u64 = 0;
+
+        sort_stable(list, |x, y| {
+            // Each call to the comparer runs at least one operation, so the total number
+            // of comparisons is bounded by the operations limit.
+            #[cfg(not(feature = "unchecked"))]
+            {
+                operations += 1;
+
+                if max_operations > 0 && operations > max_operations {
+                    return EvalAltResult::ErrorTooManyOperations(Position::NONE).into();
+                }
+            }
+
+            let result = comparer
                 .call_dynamic(ctx, None, [x.clone(), y.clone()])
-                .ok()
-                .and_then(|v| v.as_int().ok())
-                .map(|v| {
-                    if v > 0 {
-                        Ordering::Greater
-                    } else if v < 0 {
-                        Ordering::Less
-                    } else {
-                        Ordering::Equal
-                    }
-                })
-                .unwrap_or_else(|| {
-                    let x_type_id = x.type_id();
-                    let y_type_id = y.type_id();
-
-                    if x_type_id > y_type_id {
-                        Ordering::Greater
-                    } else if x_type_id < y_type_id {
-                        Ordering::Less
-                    } else {
-                        Ordering::Equal
-                    }
-                })
-        });
+                .map_err(|err| {
+                    Box::new(EvalAltResult::ErrorInFunctionCall(
+                        "sort".to_string(),
+                        String::new(),
+                        err,
+                        Position::NONE,
+                    ))
+                })?;
+
+            if let Ok(v) = result.as_int() {
+                Ok(v < 0)
+            } else if let Ok(v) = result.as_bool() {
+                Ok(v)
+            } else {
+                Err(Box::new(EvalAltResult::ErrorInFunctionCall(
+                    "sort".to_string(),
+                    String::new(),
+                    EvalAltResult::ErrorMismatchOutputType(
+                        "INT or bool".to_string(),
+                        result.type_name().to_string(),
+                        Position::NONE,
+                    )
+                    .into(),
+                    Position::NONE,
+                )))
+            }
+        })?;
+
+        Ok(Dynamic::UNIT)
+    }
+    #[rhai_fn(name = "sort", return_raw)]
+    pub fn sort_default(
+        ctx: NativeCallContext,
+        list: &mut Array,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if let Some(type_id) = item_type(list)? {
+            sort_stable(list, |x, y| is_less_than(ctx, type_id, x, y))?;
+        }
+
+        Ok(Dynamic::UNIT)
+    }
+    #[rhai_fn(name = "min", return_raw)]
+    pub fn min_item(
+        ctx: NativeCallContext,
+        list: &mut Array,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        min_max_item(ctx, list, false)
+    }
+    #[rhai_fn(name = "max", return_raw)]
+    pub fn max_item(
+        ctx: NativeCallContext,
+        list: &mut Array,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        min_max_item(ctx, list, true)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn sum(list: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        sum_items(list)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn mean(list: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        mean_or(list, Dynamic::UNIT)
+    }
+    #[rhai_fn(name = "mean", return_raw)]
+    pub fn mean_or_int(list: &mut Array, default: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        mean_or(list, default.into())
+    }
+    #[cfg(not(feature = "no_float"))]
+    pub mod float_functions {
+        #[rhai_fn(name = "mean", return_raw)]
+        pub fn mean_or_float(
+            list: &mut Array,
+            default: FLOAT,
+        ) -> Result<Dynamic, Box<EvalAltResult>> {
+            super::mean_or(list, default.into())
+        }
+    }
+    #[rhai_fn(return_raw)]
+    pub fn zip(
+        _ctx: NativeCallContext,
+        list: &mut Array,
+        other: Array,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        ensure_array_size(_ctx, list.len().min(other.len()))?;
+
+        Ok(list
+            .iter()
+            .cloned()
+            .zip(other)
+            .map(|(x, y)| vec![x, y].into())
+            .collect::<Array>()
+            .into())
+    }
+    #[rhai_fn(name = "zip", return_raw)]
+    pub fn zip_with(
+        ctx: NativeCallContext,
+        list: &mut Array,
+        other: Array,
+        combiner: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        ensure_array_size(ctx, list.len().min(other.len()))?;
+
+        let mut array = Array::with_capacity(list.len().min(other.len()));
+
+        for (x, y) in list.iter().cloned().zip(other) {
+            array.push(combiner.call_dynamic(ctx, None, [x, y]).map_err(|err| {
+                Box::new(EvalAltResult::ErrorInFunctionCall(
+                    "zip".to_string(),
+                    String::new(),
+                    err,
+                    Position::NONE,
+                ))
+            })?);
+        }
+
+        Ok(array.into())
+    }
+    #[rhai_fn(return_raw)]
+    pub fn flatten(
+        ctx: NativeCallContext,
+        list: &mut Array,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        flatten_with_depth(ctx, list, 1)
+    }
+    #[rhai_fn(name = "flatten", return_raw)]
+    pub fn flatten_with_depth(
+        _ctx: NativeCallContext,
+        list: &mut Array,
+        depth: INT,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let mut array = Array::with_capacity(max(TYPICAL_ARRAY_SIZE, list.len()));
+        let depth = if depth > 0 {
+            Some(depth as usize)
+        } else {
+            None
+        };
+
+        flatten_into(_ctx, &mut array, list, depth)?;
+
+        Ok(array.into())
+    }
+    #[rhai_fn(return_raw)]
+    pub fn dedup(ctx: NativeCallContext, list: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        let def_value = Some(false.into());
+
+        dedup_with(list, |x, y| {
+            ctx.call_fn_dynamic_raw(
+                OP_EQUALS,
+                true,
+                false,
+                &mut [x, &mut y.clone()],
+                def_value.as_ref(),
+            )
+            .map(|v| v.as_bool().unwrap_or(false))
+        })?;
+
+        Ok(Dynamic::UNIT)
+    }
+    #[rhai_fn(name = "dedup", return_raw)]
+    pub fn dedup_by(
+        ctx: NativeCallContext,
+        list: &mut Array,
+        comparer: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        dedup_with(list, |x, y| {
+            Ok(comparer
+                .call_dynamic(ctx, None, [x.clone(), y.clone()])
+                .map_err(|err| {
+                    Box::new(EvalAltResult::ErrorInFunctionCall(
+                        "dedup".to_string(),
+                        String::new(),
+                        err,
+                        Position::NONE,
+                    ))
+                })?
+                .as_bool()
+                .unwrap_or(false))
+        })?;
 
         Ok(Dynamic::UNIT)
     }
@@ -553,6 +850,7 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "drain".to_string(),
+                        String::new(),
                         err,
                         Position::NONE,
                     ))
@@ -612,6 +910,7 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "retain".to_string(),
+                        String::new(),
                         err,
                         Position::NONE,
                     ))
@@ -701,3 +1000,510 @@ gen_array_functions!(float => f32, f64);
 
 #[cfg(not(feature = "no_object"))]
 gen_array_functions!(map => Map);
+
+/// Stable merge sort of an array, where `is_less(x, y)` returns `true` if `x` sorts before `y`.
+///
+/// Unlike the standard library sort, this never panics on an inconsistent comparison function,
+/// and stops at the first error returned by it.
+fn sort_stable(
+    list: &mut Array,
+    mut is_less: impl FnMut(&Dynamic, &Dynamic) -> Result<bool, Box<EvalAltResult>>,
+) -> Result<(), Box<EvalAltResult>> {
+    fn merge_sort(
+        mut items: Array,
+        is_less: &mut impl FnMut(&Dynamic, &Dynamic) -> Result<bool, Box<EvalAltResult>>,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if items.len() <= 1 {
+            return Ok(items);
+        }
+
+        let right = items.split_off(items.len() / 2);
+        let left = merge_sort(items, is_less)?;
+        let right = merge_sort(right, is_less)?;
+
+        let mut merged = Array::with_capacity(left.len() + right.len());
+        let mut left = left.into_iter().peekable();
+        let mut right = right.into_iter().peekable();
+
+        while let (Some(x), Some(y)) = (left.peek(), right.peek()) {
+            // Only take from the right when strictly less, to keep the sort stable
+            if is_less(y, x)? {
+                merged.extend(right.next());
+            } else {
+                merged.extend(left.next());
+            }
+        }
+
+        merged.extend(left);
+        merged.extend(right);
+        Ok(merged)
+    }
+
+    *list = merge_sort(mem::take(list), &mut is_less)?;
+    Ok(())
+}
+
+fn is_less<T: Variant + Clone + PartialOrd>(x: &Dynamic, y: &Dynamic) -> bool {
+    match (x.read_lock::<T>(), y.read_lock::<T>()) {
+        (Some(x), Some(y)) => *x < *y,
+        _ => false,
+    }
+}
+
+/// Get the type of the items of an array, or [`None`] if the array is empty.
+///
+/// All items must be of the same type.
+fn item_type(list: &Array) -> Result<Option<TypeId>, Box<EvalAltResult>> {
+    let type_id = match list.first() {
+        Some(item) => item.type_id(),
+        None => return Ok(None),
+    };
+
+    if let Some((index, item)) = list
+        .iter()
+        .enumerate()
+        .find(|(_, item)| item.type_id() != type_id)
+    {
+        return EvalAltResult::ErrorArrayElementType(
+            map_std_type_name(list[0].type_name()).to_string(),
+            map_std_type_name(item.type_name()).to_string(),
+            index,
+            Position::NONE,
+        )
+        .into();
+    }
+
+    Ok(Some(type_id))
+}
+
+/// Is `x` less than `y`, both items being of the type `type_id`?
+///
+/// `INT`, `FLOAT`, strings, characters and `bool` are compared directly.
+/// Other types are compared via the `<` operator, if one is registered for them.
+fn is_less_than(
+    ctx: NativeCallContext,
+    type_id: TypeId,
+    x: &Dynamic,
+    y: &Dynamic,
+) -> Result<bool, Box<EvalAltResult>> {
+    if type_id == TypeId::of::<INT>() {
+        return Ok(is_less::<INT>(x, y));
+    } else if type_id == TypeId::of::<ImmutableString>() {
+        return Ok(is_less::<ImmutableString>(x, y));
+    } else if type_id == TypeId::of::<char>() {
+        return Ok(is_less::<char>(x, y));
+    } else if type_id == TypeId::of::<bool>() {
+        return Ok(is_less::<bool>(x, y));
+    }
+
+    #[cfg(not(feature = "no_float"))]
+    if type_id == TypeId::of::<FLOAT>() {
+        return Ok(is_less::<FLOAT>(x, y));
+    }
+
+    let (mut x, mut y) = (x.clone(), y.clone());
+
+    match ctx.call_fn_dynamic_raw(OP_LESS_THAN, true, false, &mut [&mut x, &mut y], None) {
+        Ok(result) => result.as_bool().map_err(|typ| {
+            EvalAltResult::ErrorMismatchOutputType(
+                "bool".to_string(),
+                typ.to_string(),
+                Position::NONE,
+            )
+            .into()
+        }),
+        Err(err) => match *err {
+            EvalAltResult::ErrorFunctionNotFound(ref fn_sig, _)
+                if fn_sig.starts_with(OP_LESS_THAN) =>
+            {
+                EvalAltResult::ErrorMismatchDataType(
+                    "array of INT, FLOAT, string, char, bool or a type with '<'".to_string(),
+                    format!("array of {}", map_std_type_name(x.type_name())),
+                    Position::NONE,
+                )
+                .into()
+            }
+            _ => Err(err),
+        },
+    }
+}
+
+/// Find the smallest (or largest if `largest` is `true`) item of an array,
+/// or `()` if the array is empty.
+///
+/// The first of several equal items is returned.
+fn min_max_item(
+    ctx: NativeCallContext,
+    list: &Array,
+    largest: bool,
+) -> Result<Dynamic, Box<EvalAltResult>> {
+    let type_id = match item_type(list)? {
+        Some(type_id) => type_id,
+        None => return Ok(Dynamic::UNIT),
+    };
+
+    let mut result = &list[0];
+
+    for item in list.iter().skip(1) {
+        let better = if largest {
+            is_less_than(ctx, type_id, result, item)?
+        } else {
+            is_less_than(ctx, type_id, item, result)?
+        };
+
+        if better {
+            result = item;
+        }
+    }
+
+    Ok(result.clone())
+}
+
+/// Add up the items of an array of `INT` and/or `FLOAT`.
+///
+/// The sum is an `INT` if all items are `INT`, otherwise it is a `FLOAT`.
+fn sum_items(list: &Array) -> Result<Dynamic, Box<EvalAltResult>> {
+    let mut total: INT = 0;
+    #[cfg(not(feature = "no_float"))]
+    let mut float_total: Option<FLOAT> = None;
+
+    for (index, item) in list.iter().enumerate() {
+        if let Ok(x) = item.as_int() {
+            #[cfg(not(feature = "no_float"))]
+            if let Some(ref mut total) = float_total {
+                *total += x as FLOAT;
+                continue;
+            }
+
+            total = if cfg!(not(feature = "unchecked")) {
+                total
+                    .checked_add(x)
+                    .ok_or_else(|| make_err(format!("Addition overflow: {} + {}", total, x)))?
+            } else {
+                total + x
+            };
+            continue;
+        }
+
+        #[cfg(not(feature = "no_float"))]
+        if let Ok(x) = item.as_float() {
+            float_total = Some(float_total.unwrap_or(total as FLOAT) + x);
+            continue;
+        }
+
+        return EvalAltResult::ErrorArrayElementType(
+            "INT or FLOAT".to_string(),
+            map_std_type_name(item.type_name()).to_string(),
+            index,
+            Position::NONE,
+        )
+        .into();
+    }
+
+    #[cfg(not(feature = "no_float"))]
+    if let Some(total) = float_total {
+        return Ok(total.into());
+    }
+
+    Ok(total.into())
+}
+
+/// Average of the items of an array of `INT` and/or `FLOAT`, or `default` if the array is empty.
+fn mean_or(list: &Array, default: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+    if list.is_empty() {
+        return Ok(default);
+    }
+
+    let total = sum_items(list)?;
+
+    #[cfg(not(feature = "no_float"))]
+    if let Ok(total) = total.as_float() {
+        return Ok((total / list.len() as FLOAT).into());
+    }
+
+    Ok((total.as_int().unwrap() / list.len() as INT).into())
+}
+
+/// Copy the items of an array into `array`, flattening nested arrays up to `depth` levels
+/// (all levels if [`None`]).
+fn flatten_into(
+    _ctx: NativeCallContext,
+    array: &mut Array,
+    list: &[Dynamic],
+    depth: Option<usize>,
+) -> Result<(), Box<EvalAltResult>> {
+    for item in list {
+        match (item.read_lock::<Array>(), depth) {
+            (Some(inner), None) => flatten_into(_ctx, array, &inner, None)?,
+            (Some(inner), Some(n)) if n > 0 => flatten_into(_ctx, array, &inner, Some(n - 1))?,
+            _ => {
+                ensure_array_size(_ctx, array.len() + 1)?;
+                array.push(item.clone());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove consecutive items for which `is_dup(previous, item)` returns `true`.
+///
+/// The array is left untouched if `is_dup` returns an error.
+fn dedup_with(
+    list: &mut Array,
+    mut is_dup: impl FnMut(&mut Dynamic, &Dynamic) -> Result<bool, Box<EvalAltResult>>,
+) -> Result<(), Box<EvalAltResult>> {
+    let mut array = Array::with_capacity(list.len());
+
+    for item in list.iter() {
+        let is_dup = match array.last_mut() {
+            Some(prev) => is_dup(prev, item)?,
+            None => false,
+        };
+
+        if !is_dup {
+            array.push(item.clone());
+        }
+    }
+
+    *list = array;
+    Ok(())
+}
+
+/// Make sure that an array with `len` items does not exceed `max_array_size`.
+#[inline(always)]
+fn ensure_array_size(_ctx: NativeCallContext, _len: usize) -> Result<(), Box<EvalAltResult>> {
+    #[cfg(not(feature = "unchecked"))]
+    if _ctx.engine().max_array_size() > 0 && _len > _ctx.engine().max_array_size() {
+        return EvalAltResult::ErrorDataTooLarge("Size of array".to_string(), Position::NONE)
+            .into();
+    }
+
+    Ok(())
+}
+
+fn typed_array_set<T: Variant + Clone>(
+    list: &mut TypedArray,
+    index: INT,
+    item: T,
+) -> Result<(), Box<EvalAltResult>> {
+    if index >= 0 && (index as usize) < list.len() {
+        list.set(index as usize, Dynamic::from(item))
+    } else {
+        EvalAltResult::ErrorArrayBounds(list.len(), index, Position::NONE).into()
+    }
+}
+
+fn typed_array_insert(
+    list: &mut TypedArray,
+    position: INT,
+    item: Dynamic,
+) -> Result<Dynamic, Box<EvalAltResult>> {
+    let position = if position <= 0 {
+        0
+    } else if (position as usize) >= list.len() {
+        list.len()
+    } else {
+        position as usize
+    };
+
+    list.insert(position, item)?;
+    Ok(Dynamic::UNIT)
+}
+
+fn typed_array_pad(
+    _ctx: NativeCallContext,
+    list: &mut TypedArray,
+    len: INT,
+    item: Dynamic,
+) -> Result<Dynamic, Box<EvalAltResult>> {
+    // Check if array will be over max size limit
+    #[cfg(not(feature = "unchecked"))]
+    if _ctx.engine().max_array_size() > 0
+        && len > 0
+        && (len as usize) > _ctx.engine().max_array_size()
+    {
+        return EvalAltResult::ErrorDataTooLarge("Size of array".to_string(), Position::NONE)
+            .into();
+    }
+
+    if len > 0 && len as usize > list.len() {
+        list.check_element(list.len(), &item)?;
+        list.as_array_mut().resize(len as usize, item);
+    }
+
+    Ok(Dynamic::UNIT)
+}
+
+#[export_module]
+mod typed_array_functions {
+    #[rhai_fn(return_raw)]
+    pub fn typed_array(type_name: &str) -> Result<Dynamic, Box<EvalAltResult>> {
+        TypedArray::new_by_name(type_name)
+            .map(Dynamic::from)
+            .ok_or_else(|| format!("Unknown array element type: {}", type_name).into())
+    }
+    #[rhai_fn(name = "typed_array", return_raw)]
+    pub fn typed_array_from(type_name: &str, list: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        let mut typed = typed_array(type_name)?.cast::<TypedArray>();
+        typed.append(list)?;
+        Ok(Dynamic::from(typed))
+    }
+    #[rhai_fn(get = "type")]
+    pub fn element_type(list: &mut TypedArray) -> ImmutableString {
+        list.element_type_name().into()
+    }
+    pub fn to_array(list: &mut TypedArray) -> Array {
+        list.as_array().clone()
+    }
+    #[rhai_fn(name = "len", get = "len")]
+    pub fn len(list: &mut TypedArray) -> INT {
+        list.len() as INT
+    }
+    #[rhai_fn(index_get, return_raw)]
+    pub fn get(list: &mut TypedArray, index: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if index >= 0 && (index as usize) < list.len() {
+            Ok(list.as_array()[index as usize].clone())
+        } else {
+            EvalAltResult::ErrorArrayBounds(list.len(), index, Position::NONE).into()
+        }
+    }
+    #[rhai_fn(name = "append", name = "+=", return_raw)]
+    pub fn append(list: &mut TypedArray, y: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        list.append(y)?;
+        Ok(Dynamic::UNIT)
+    }
+    #[rhai_fn(name = "+", return_raw)]
+    pub fn concat(mut list: TypedArray, y: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        list.append(y)?;
+        Ok(Dynamic::from(list))
+    }
+    #[rhai_fn(return_raw)]
+    pub fn map(
+        ctx: NativeCallContext,
+        list: &mut TypedArray,
+        mapper: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        // The mapped values may be of any type, so the result is a normal array
+        array_functions::map(ctx, list.as_array_mut(), mapper)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn filter(
+        ctx: NativeCallContext,
+        list: &mut TypedArray,
+        filter: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let array = array_functions::filter(ctx, list.as_array_mut(), filter)?.cast::<Array>();
+        Ok(Dynamic::from(list.with_array_unchecked(array)))
+    }
+    #[rhai_fn(return_raw)]
+    pub fn index_of(
+        ctx: NativeCallContext,
+        list: &mut TypedArray,
+        filter: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        array_functions::index_of(ctx, list.as_array_mut(), filter)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn some(
+        ctx: NativeCallContext,
+        list: &mut TypedArray,
+        filter: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        array_functions::some(ctx, list.as_array_mut(), filter)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn all(
+        ctx: NativeCallContext,
+        list: &mut TypedArray,
+        filter: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        array_functions::all(ctx, list.as_array_mut(), filter)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn for_each(
+        ctx: NativeCallContext,
+        list: &mut TypedArray,
+        action: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        array_functions::for_each(ctx, list.as_array_mut(), action)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn reduce(
+        ctx: NativeCallContext,
+        list: &mut TypedArray,
+        reducer: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        array_functions::reduce(ctx, list.as_array_mut(), reducer)
+    }
+    #[rhai_fn(name = "reduce", return_raw)]
+    pub fn reduce_with_initial(
+        ctx: NativeCallContext,
+        list: &mut TypedArray,
+        reducer: FnPtr,
+        initial: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        array_functions::reduce_with_initial(ctx, list.as_array_mut(), reducer, initial)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn reduce_rev(
+        ctx: NativeCallContext,
+        list: &mut TypedArray,
+        reducer: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        array_functions::reduce_rev(ctx, list.as_array_mut(), reducer)
+    }
+    #[rhai_fn(name = "reduce_rev", return_raw)]
+    pub fn reduce_rev_with_initial(
+        ctx: NativeCallContext,
+        list: &mut TypedArray,
+        reducer: FnPtr,
+        initial: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        array_functions::reduce_rev_with_initial(ctx, list.as_array_mut(), reducer, initial)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn splice(
+        list: &mut TypedArray,
+        start: INT,
+        len: INT,
+        replace: Array,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let start = if start < 0 {
+            0
+        } else if start as usize >= list.len() {
+            list.len()
+        } else {
+            start as usize
+        };
+
+        let len = if len < 0 {
+            0
+        } else if len as usize > list.len() - start {
+            list.len() - start
+        } else {
+            len as usize
+        };
+
+        list.splice(start, len, replace)?;
+        Ok(Dynamic::UNIT)
+    }
+    pub fn pop(list: &mut TypedArray) -> Dynamic {
+        list.as_array_mut().pop().unwrap_or_else(|| ().into())
+    }
+    pub fn shift(list: &mut TypedArray) -> Dynamic {
+        array_functions::shift(list.as_array_mut())
+    }
+    pub fn remove(list: &mut TypedArray, len: INT) -> Dynamic {
+        array_functions::remove(list.as_array_mut(), len)
+    }
+    pub fn clear(list: &mut TypedArray) {
+        list.as_array_mut().clear();
+    }
+    pub fn truncate(list: &mut TypedArray, len: INT) {
+        array_functions::truncate(list.as_array_mut(), len);
+    }
+    pub fn reverse(list: &mut TypedArray) {
+        list.as_array_mut().reverse();
+    }
+}
@@ -1,5 +1,5 @@
 use crate::plugin::*;
-use crate::{def_package, FnPtr, ImmutableString, NativeCallContext};
+use crate::{def_package, FnPtr, ImmutableString, NativeCallContext, INT};
 
 #[cfg(not(feature = "no_function"))]
 #[cfg(not(feature = "no_index"))]
@@ -16,10 +16,14 @@ mod fn_ptr_functions {
     pub fn name(f: &mut FnPtr) -> ImmutableString {
         f.get_fn_name().clone()
     }
+    #[rhai_fn(name = "curry_count", get = "curry_count")]
+    pub fn curry_count(f: &mut FnPtr) -> INT {
+        f.curry().len() as INT
+    }
 
     #[cfg(not(feature = "no_function"))]
     pub mod functions {
-        use crate::{calc_script_fn_hash, stdlib::iter::empty, INT};
+        use crate::{calc_script_fn_hash, stdlib::iter::empty};
 
         #[rhai_fn(name = "is_anonymous", get = "is_anonymous")]
         pub fn is_anonymous(f: &mut FnPtr) -> bool {
@@ -1,20 +1,63 @@
 #![cfg(not(feature = "no_object"))]
+#![allow(non_snake_case)]
 
 use crate::engine::OP_EQUALS;
 use crate::plugin::*;
-use crate::{def_package, Dynamic, ImmutableString, Map, INT};
+use crate::stdlib::any::type_name;
+use crate::{def_package, Dynamic, FnPtr, ImmutableString, Map, Position, INT};
 
 #[cfg(not(feature = "no_index"))]
 use crate::Array;
 
+pub type Unit = ();
+
+macro_rules! gen_map_functions {
+    ($root:ident => $($arg_type:ident),+ ) => {
+        pub mod $root { $( pub mod $arg_type {
+            use super::super::*;
+
+            #[export_module]
+            pub mod functions {
+                #[rhai_fn(return_raw)]
+                pub fn get_or_insert(_ctx: NativeCallContext, map: &mut Map, key: ImmutableString, default: $arg_type) -> Result<Dynamic, Box<EvalAltResult>> {
+                    map_get_or_insert(_ctx, map, key, Dynamic::from(default))
+                }
+            }
+        })* }
+    }
+}
+
+macro_rules! reg_functions {
+    ($mod_name:ident += $root:ident ; $($arg_type:ident),+) => { $(
+        combine_with_exported_module!($mod_name, "map_functions", $root::$arg_type::functions);
+    )* }
+}
+
 def_package!(crate:BasicMapPackage:"Basic object map utilities.", lib, {
+    reg_functions!(lib += basic; INT, bool, char, ImmutableString, FnPtr, Map, Unit);
+
+    #[cfg(not(feature = "only_i32"))]
+    #[cfg(not(feature = "only_i64"))]
+    {
+        reg_functions!(lib += numbers; i8, u8, i16, u16, i32, i64, u32, u64);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        reg_functions!(lib += num_128; i128, u128);
+    }
+
+    #[cfg(not(feature = "no_float"))]
+    reg_functions!(lib += float; f32, f64);
+
+    #[cfg(not(feature = "no_index"))]
+    reg_functions!(lib += array; Array);
+
     combine_with_exported_module!(lib, "map", map_functions);
 });
 
 #[export_module]
 mod map_functions {
-    pub fn has(map: &mut Map, prop: ImmutableString) -> bool {
-        map.contains_key(&prop)
+    pub fn has(map: &mut Map, prop: &str) -> bool {
+        map.contains_key(prop)
     }
     pub fn len(map: &mut Map) -> INT {
         map.len() as INT
@@ -22,8 +65,8 @@ mod map_functions {
     pub fn clear(map: &mut Map) {
         map.clear();
     }
-    pub fn remove(x: &mut Map, name: ImmutableString) -> Dynamic {
-        x.remove(&name).unwrap_or_else(|| ().into())
+    pub fn remove(x: &mut Map, name: &str) -> Dynamic {
+        x.remove(name).unwrap_or_else(|| ().into())
     }
     #[rhai_fn(name = "mixin", name = "+=")]
     pub fn mixin(map1: &mut Map, map2: Map) {
@@ -40,9 +83,74 @@ mod map_functions {
     }
     pub fn fill_with(map1: &mut Map, map2: Map) {
         map2.into_iter().for_each(|(key, value)| {
-            map1.entry(key).or_insert(value);
+            if !map1.contains_key(&key) {
+                map1.insert(key, value);
+            }
         });
     }
+    #[rhai_fn(return_raw)]
+    pub fn update(
+        ctx: NativeCallContext,
+        map: &mut Map,
+        key: ImmutableString,
+        updater: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let value = match map.get(&key) {
+            Some(value) => value.clone(),
+            None => {
+                ensure_room_for_key(ctx, map)?;
+                Dynamic::UNIT
+            }
+        };
+
+        let value = updater.call_dynamic(ctx, None, [value]).map_err(|err| {
+            Box::new(EvalAltResult::ErrorInFunctionCall(
+                "update".to_string(),
+                String::new(),
+                err,
+                Position::NONE,
+            ))
+        })?;
+
+        map.insert(key, value);
+
+        Ok(Dynamic::UNIT)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn increment(
+        ctx: NativeCallContext,
+        map: &mut Map,
+        key: ImmutableString,
+        delta: INT,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let value = match map.get(&key) {
+            Some(value) => value.as_int().map_err(|typ| {
+                EvalAltResult::ErrorMismatchDataType(
+                    ctx.engine().map_type_name(type_name::<INT>()).into(),
+                    ctx.engine().map_type_name(typ).into(),
+                    Position::NONE,
+                )
+            })?,
+            None => {
+                ensure_room_for_key(ctx, map)?;
+                0
+            }
+        };
+
+        #[cfg(not(feature = "unchecked"))]
+        let value = value.checked_add(delta).ok_or_else(|| {
+            EvalAltResult::ErrorArithmetic(
+                format!("Addition overflow: {} + {}", value, delta),
+                Position::NONE,
+            )
+        })?;
+        #[cfg(feature = "unchecked")]
+        let value = value + delta;
+
+        map.insert(key, value.into());
+
+        Ok(value.into())
+    }
     #[rhai_fn(name = "==", return_raw)]
     pub fn equals(
         ctx: NativeCallContext,
@@ -93,3 +201,47 @@ mod map_functions {
         }
     }
 }
+
+gen_map_functions!(basic => INT, bool, char, ImmutableString, FnPtr, Map, Unit);
+
+#[cfg(not(feature = "only_i32"))]
+#[cfg(not(feature = "only_i64"))]
+gen_map_functions!(numbers => i8, u8, i16, u16, i32, i64, u32, u64);
+
+#[cfg(not(feature = "only_i32"))]
+#[cfg(not(feature = "only_i64"))]
+#[cfg(not(target_arch = "wasm32"))]
+gen_map_functions!(num_128 => i128, u128);
+
+#[cfg(not(feature = "no_float"))]
+gen_map_functions!(float => f32, f64);
+
+#[cfg(not(feature = "no_index"))]
+gen_map_functions!(array => Array);
+
+fn map_get_or_insert(
+    _ctx: NativeCallContext,
+    map: &mut Map,
+    key: ImmutableString,
+    default: Dynamic,
+) -> Result<Dynamic, Box<EvalAltResult>> {
+    if let Some(value) = map.get(&key) {
+        return Ok(value.clone());
+    }
+
+    ensure_room_for_key(_ctx, map)?;
+
+    Ok(map.get_or_insert_with(key, || default).clone())
+}
+
+/// Make sure that a new property can be added to an object map without exceeding `max_map_size`.
+#[inline(always)]
+fn ensure_room_for_key(_ctx: NativeCallContext, _map: &Map) -> Result<(), Box<EvalAltResult>> {
+    #[cfg(not(feature = "unchecked"))]
+    if _ctx.engine().max_map_size() > 0 && _map.len() >= _ctx.engine().max_map_size() {
+        return EvalAltResult::ErrorDataTooLarge("Size of object map".to_string(), Position::NONE)
+            .into();
+    }
+
+    Ok(())
+}
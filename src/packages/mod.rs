@@ -14,6 +14,7 @@ mod pkg_std;
 mod string_basic;
 mod string_more;
 mod time_basic;
+mod time_more;
 
 pub use arithmetic::ArithmeticPackage;
 #[cfg(not(feature = "no_index"))]
@@ -30,6 +31,8 @@ pub use string_basic::BasicStringPackage;
 pub use string_more::MoreStringPackage;
 #[cfg(not(feature = "no_std"))]
 pub use time_basic::BasicTimePackage;
+#[cfg(not(feature = "no_std"))]
+pub use time_more::MoreTimePackage;
 
 /// Trait that all packages must implement.
 pub trait Package {
@@ -74,7 +77,17 @@ pub trait Package {
 /// The above defines a package named 'MyPackage' with a single function named 'my_add'.
 #[macro_export]
 macro_rules! def_package {
+    // Packages defined in this crate make up the standard library
+    (crate : $package:ident : $comment:expr , $lib:ident , $block:stmt) => {
+        $crate::def_package!(@package crate : $package : $comment, $lib, {
+            $lib.set_standard(true);
+            $block
+        });
+    };
     ($root:ident : $package:ident : $comment:expr , $lib:ident , $block:stmt) => {
+        $crate::def_package!(@package $root : $package : $comment, $lib, $block);
+    };
+    (@package $root:ident : $package:ident : $comment:expr , $lib:ident , $block:stmt) => {
         #[doc=$comment]
         pub struct $package($root::Shared<$root::Module>);
 
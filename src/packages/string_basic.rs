@@ -7,7 +7,7 @@ use crate::stdlib::{
     format,
     string::ToString,
 };
-use crate::{def_package, FnPtr, ImmutableString, INT};
+use crate::{def_package, CharRange, FnPtr, ImmutableString, INT};
 
 #[cfg(not(feature = "no_index"))]
 use crate::Array;
@@ -137,6 +137,10 @@ mod print_debug_functions {
     pub fn debug_fn_ptr(f: &mut FnPtr) -> ImmutableString {
         to_string(f)
     }
+    #[rhai_fn(name = "print", name = "to_string", name = "to_debug", name = "debug")]
+    pub fn format_char_range(range: &mut CharRange) -> ImmutableString {
+        to_debug(range)
+    }
 
     #[cfg(not(feature = "no_index"))]
     pub mod array_functions {
@@ -159,6 +163,13 @@ mod print_debug_functions {
             result.push_str("]");
             result.into()
         }
+        #[rhai_fn(name = "print", name = "to_string", name = "to_debug", name = "debug")]
+        pub fn format_typed_array(
+            ctx: NativeCallContext,
+            arr: &mut crate::TypedArray,
+        ) -> ImmutableString {
+            format_array(ctx, arr.as_array_mut())
+        }
     }
     #[cfg(not(feature = "no_object"))]
     pub mod map_functions {
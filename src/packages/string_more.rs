@@ -4,7 +4,7 @@ use crate::plugin::*;
 use crate::stdlib::{
     any::TypeId, boxed::Box, format, mem, string::String, string::ToString, vec::Vec,
 };
-use crate::{def_package, Dynamic, FnPtr, ImmutableString, StaticVec, INT};
+use crate::{def_package, CharRange, Dynamic, FnPtr, ImmutableString, StaticVec, INT};
 
 macro_rules! gen_concat_functions {
     ($root:ident => $($arg_type:ident),+ ) => {
@@ -49,6 +49,7 @@ def_package!(crate:MoreStringPackage:"Additional string utilities, including str
     reg_functions!(lib += float; f32, f64);
 
     combine_with_exported_module!(lib, "string", string_functions);
+    combine_with_exported_module!(lib, "char", char_functions);
 
     // Register string iterator
     lib.set_iter(
@@ -239,7 +240,82 @@ mod string_functions {
     pub fn replace_char(s: &mut ImmutableString, find: char, sub: char) {
         *s = s.replace(&find.to_string(), &sub.to_string()).into();
     }
+    #[rhai_fn(name = "replace", return_raw)]
+    pub fn replace_with_fn(
+        ctx: NativeCallContext,
+        s: &mut ImmutableString,
+        find: ImmutableString,
+        replacer: FnPtr,
+    ) -> Result<Dynamic, Box<crate::EvalAltResult>> {
+        replace_matches(ctx, s, find.as_str(), replacer)
+    }
+    #[rhai_fn(name = "replace", return_raw)]
+    pub fn replace_char_with_fn(
+        ctx: NativeCallContext,
+        s: &mut ImmutableString,
+        find: char,
+        replacer: FnPtr,
+    ) -> Result<Dynamic, Box<crate::EvalAltResult>> {
+        replace_matches(ctx, s, find.encode_utf8(&mut [0; 4]), replacer)
+    }
 
+    #[rhai_fn(name = "*", return_raw)]
+    pub fn repeat(
+        _ctx: NativeCallContext,
+        s: &str,
+        count: INT,
+    ) -> Result<Dynamic, Box<crate::EvalAltResult>> {
+        if count < 0 {
+            return crate::EvalAltResult::ErrorArithmetic(
+                format!("String repeated a negative number of times: {}", count),
+                crate::Position::NONE,
+            )
+            .into();
+        }
+
+        if count == 0 || s.is_empty() {
+            return Ok(ImmutableString::default().into());
+        }
+
+        use crate::stdlib::convert::TryFrom;
+
+        let len = match usize::try_from(count)
+            .ok()
+            .and_then(|count| s.len().checked_mul(count))
+            .filter(|&len| len <= isize::MAX as usize)
+        {
+            Some(len) => len,
+            None => {
+                return crate::EvalAltResult::ErrorArithmetic(
+                    format!("String repeated too many times: {}", count),
+                    crate::Position::NONE,
+                )
+                .into()
+            }
+        };
+
+        // Check if string will be over max size limit before allocating it
+        #[cfg(not(feature = "unchecked"))]
+        {
+            let max = _ctx.engine().max_string_size();
+
+            if max > 0 && len > max {
+                return crate::EvalAltResult::ErrorDataTooLarge(
+                    format!("Length of string (limit {})", max),
+                    crate::Position::NONE,
+                )
+                .into();
+            }
+        }
+
+        let mut result = String::with_capacity(len);
+
+        for _ in 0..count {
+            result.push_str(s);
+        }
+
+        Ok(result.into())
+    }
     #[rhai_fn(return_raw)]
     pub fn pad(
         _ctx: NativeCallContext,
@@ -351,6 +427,51 @@ mod string_functions {
         pub fn split_char(s: &str, delimiter: char) -> Array {
             s.split(delimiter).map(Into::<Dynamic>::into).collect()
         }
+        #[rhai_fn(name = "split", return_raw)]
+        pub fn split_max(
+            s: &str,
+            delimiter: ImmutableString,
+            max_parts: INT,
+        ) -> Result<Dynamic, Box<crate::EvalAltResult>> {
+            if delimiter.is_empty() {
+                return empty_pattern_error();
+            }
+
+            let max_parts = if max_parts < 1 { 1 } else { max_parts as usize };
+
+            Ok(s.splitn(max_parts, delimiter.as_str())
+                .map(Into::<Dynamic>::into)
+                .collect::<Array>()
+                .into())
+        }
+        #[rhai_fn(name = "split")]
+        pub fn split_char_max(s: &str, delimiter: char, max_parts: INT) -> Array {
+            let max_parts = if max_parts < 1 { 1 } else { max_parts as usize };
+
+            s.splitn(max_parts, delimiter)
+                .map(Into::<Dynamic>::into)
+                .collect()
+        }
+        pub fn to_chars(s: &str) -> Array {
+            s.chars().map(Into::<Dynamic>::into).collect()
+        }
+        #[rhai_fn(return_raw)]
+        pub fn from_chars(chars: Array) -> Result<Dynamic, Box<crate::EvalAltResult>> {
+            chars
+                .iter()
+                .map(|ch| {
+                    ch.as_char().map_err(|typ| {
+                        crate::EvalAltResult::ErrorMismatchDataType(
+                            "char".into(),
+                            typ.into(),
+                            crate::Position::NONE,
+                        )
+                        .into()
+                    })
+                })
+                .collect::<Result<String, _>>()
+                .map(Into::<Dynamic>::into)
+        }
     }
 
     #[cfg(not(feature = "no_object"))]
@@ -367,3 +488,100 @@ mod string_functions {
         }
     }
 }
+
+#[export_module]
+mod char_functions {
+    pub fn is_alpha(ch: char) -> bool {
+        ch.is_alphabetic()
+    }
+    pub fn is_digit(ch: char) -> bool {
+        ch.is_ascii_digit()
+    }
+    pub fn is_whitespace(ch: char) -> bool {
+        ch.is_whitespace()
+    }
+    pub fn to_lower(ch: char) -> char {
+        let mut chars = ch.to_lowercase();
+        match (chars.next(), chars.next()) {
+            (Some(lower), None) => lower,
+            // Keep characters that do not map to a single character
+            _ => ch,
+        }
+    }
+    pub fn to_upper(ch: char) -> char {
+        let mut chars = ch.to_uppercase();
+        match (chars.next(), chars.next()) {
+            (Some(upper), None) => upper,
+            // Keep characters that do not map to a single character
+            _ => ch,
+        }
+    }
+
+    pub fn contains(range: &mut CharRange, ch: char) -> bool {
+        range.contains(&ch)
+    }
+}
+
+/// Error for an empty pattern, which would otherwise match everywhere.
+fn empty_pattern_error<T>() -> Result<T, Box<crate::EvalAltResult>> {
+    crate::EvalAltResult::ErrorMismatchDataType(
+        "non-empty string".into(),
+        "empty string".into(),
+        crate::Position::NONE,
+    )
+    .into()
+}
+
+/// Replace all matches of `find` in a string with the results of calling a function pointer,
+/// which is passed the matched text.
+fn replace_matches(
+    ctx: NativeCallContext,
+    s: &mut ImmutableString,
+    find: &str,
+    replacer: FnPtr,
+) -> Result<Dynamic, Box<crate::EvalAltResult>> {
+    if find.is_empty() {
+        return empty_pattern_error();
+    }
+
+    let mut result = String::with_capacity(s.len());
+    let mut last = 0;
+
+    for (index, matched) in s.match_indices(find) {
+        result.push_str(&s[last..index]);
+
+        let sub = replacer
+            .call_dynamic(ctx, None, [matched.into()])
+            .and_then(|sub| {
+                if let Ok(sub) = sub.as_str() {
+                    result.push_str(sub);
+                } else if let Ok(ch) = sub.as_char() {
+                    result.push(ch);
+                } else {
+                    return crate::EvalAltResult::ErrorMismatchOutputType(
+                        "string".into(),
+                        ctx.engine().map_type_name(sub.type_name()).into(),
+                        crate::Position::NONE,
+                    )
+                    .into();
+                }
+                Ok(())
+            });
+
+        sub.map_err(|err| {
+            Box::new(crate::EvalAltResult::ErrorInFunctionCall(
+                "replace".to_string(),
+                String::new(),
+                err,
+                crate::Position::NONE,
+            ))
+        })?;
+
+        last = index + matched.len();
+    }
+
+    result.push_str(&s[last..]);
+    *s = result.into();
+
+    Ok(Dynamic::UNIT)
+}
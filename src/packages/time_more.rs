@@ -0,0 +1,95 @@
+#![cfg(not(feature = "no_std"))]
+
+use super::time_basic::BasicTimePackage;
+use crate::plugin::*;
+use crate::stdlib::{format, string::String};
+use crate::{def_package, INT};
+
+#[cfg(not(feature = "no_float"))]
+use crate::FLOAT;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::stdlib::{thread, time::Duration};
+
+#[cfg(not(feature = "no_float"))]
+#[cfg(not(target_arch = "wasm32"))]
+use super::{arithmetic::make_err as make_arithmetic_err, math_basic::MAX_INT};
+#[cfg(not(feature = "no_float"))]
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{stdlib::boxed::Box, Dynamic, EvalAltResult};
+
+def_package!(crate:MoreTimePackage:"Additional timing utilities, including sleeping and formatting durations.", lib, {
+    BasicTimePackage::init(lib);
+
+    combine_with_exported_module!(lib, "time", time_functions);
+});
+
+#[export_module]
+mod time_functions {
+    /// Format a number of seconds as `HH:MM:SS`.
+    pub fn format_duration(seconds: INT) -> String {
+        let sign = if seconds < 0 { "-" } else { "" };
+        let seconds = (seconds as i128).abs();
+
+        format!(
+            "{}{:02}:{:02}:{:02}",
+            sign,
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub mod sleep_functions {
+        /// Block the current thread for a number of seconds.
+        pub fn sleep(seconds: INT) {
+            if seconds > 0 {
+                thread::sleep(Duration::from_secs(seconds as u64));
+            }
+        }
+    }
+
+    #[cfg(not(feature = "no_float"))]
+    pub mod float_functions {
+        /// Format a number of seconds as `HH:MM:SS.mmm`.
+        #[rhai_fn(name = "format_duration")]
+        pub fn format_duration_float(seconds: FLOAT) -> String {
+            if !seconds.is_finite() {
+                return format!("{}", seconds);
+            }
+
+            let sign = if seconds < 0.0 { "-" } else { "" };
+            let millis = (seconds.abs() * 1000.0).round() as u128;
+            let seconds = millis / 1000;
+
+            format!(
+                "{}{:02}:{:02}:{:02}.{:03}",
+                sign,
+                seconds / 3600,
+                seconds / 60 % 60,
+                seconds % 60,
+                millis % 1000
+            )
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        pub mod sleep_functions {
+            /// Block the current thread for a number of seconds.
+            #[rhai_fn(name = "sleep", return_raw)]
+            pub fn sleep_float(seconds: FLOAT) -> Result<Dynamic, Box<EvalAltResult>> {
+                if seconds > (MAX_INT as FLOAT) {
+                    Err(make_arithmetic_err(format!(
+                        "Integer overflow for sleep: {}",
+                        seconds
+                    )))
+                } else {
+                    if seconds > 0.0 {
+                        thread::sleep(Duration::from_millis((seconds * 1000.0) as u64));
+                    }
+                    Ok(Dynamic::UNIT)
+                }
+            }
+        }
+    }
+}
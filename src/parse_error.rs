@@ -104,24 +104,41 @@ pub enum ParseErrorType {
     ///
     /// Never appears under the `no_object` and `no_index` features combination.
     MalformedInExpr(String),
-    /// A capturing  has syntax error. Wrapped value is the error description (if any).
-    ///
-    /// Never appears under the `no_closure` feature.
-    MalformedCapture(String),
+    /// A function call that cannot capture the scope is called with `!`.
+    /// Wrapped values are the kind of function call and the function name.
+    CaptureNotSupported(String, String),
     /// A map definition has duplicated property names. Wrapped value is the property name.
     ///
     /// Never appears under the `no_object` feature.
     DuplicatedProperty(String),
     /// A switch case is duplicated.
     DuplicatedSwitchCase,
+    /// A destructuring pattern has duplicated variable names. Wrapped value is the variable name.
+    ///
+    /// Never appears under the `no_index` and `no_object` features combination.
+    DuplicatedVariable(String),
     /// Missing a property name for custom types and maps.
     ///
     /// Never appears under the `no_object` feature.
     PropertyExpected,
     /// Missing a variable name after the `let`, `const`, `for` or `catch` keywords.
     VariableExpected,
+    /// A variable is declared more than once in the same block.
+    /// Wrapped value is the variable name.
+    ///
+    /// Only raised when strict variables are enabled.
+    VariableExists(String),
+    /// A variable is used but not declared.
+    /// Wrapped value is the variable name.
+    ///
+    /// Only raised when strict variables are enabled.
+    VariableUndefined(String),
     /// An identifier is a reserved keyword.
     Reserved(String),
+    /// A keyword or operator is disabled via
+    /// [`Engine::disable_symbol_with_message`][crate::Engine::disable_symbol_with_message].
+    /// Wrapped values are the symbol and the message explaining why it is disabled.
+    DisabledSymbol(String, String),
     /// Missing an expression. Wrapped value is the expression type.
     ExprExpected(String),
     /// Defining a doc-comment in an appropriate place (e.g. not at global level).
@@ -145,6 +162,16 @@ pub enum ParseErrorType {
     ///
     /// Never appears under the `no_function` feature.
     FnDuplicatedParam(String, String),
+    /// A function parameter without a default value follows parameters with default values.
+    /// Wrapped values are the function name and parameter name.
+    ///
+    /// Never appears under the `no_function` feature.
+    FnMissingDefault(String, String),
+    /// A rest parameter (e.g. `...args`) is not the last parameter of a function definition.
+    /// Wrapped values are the function name and parameter name.
+    ///
+    /// Never appears under the `no_function` feature.
+    FnMisplacedRestParam(String, String),
     /// A function definition is missing the body. Wrapped value is the function name.
     ///
     /// Never appears under the `no_function` feature.
@@ -153,6 +180,10 @@ pub enum ParseErrorType {
     ///
     /// Never appears under the `no_module` feature.
     WrongExport,
+    /// Import statement not at the start of a statement (e.g. used as an expression).
+    ///
+    /// Never appears under the `no_module` feature.
+    WrongImport,
     /// Assignment to an a constant variable. Wrapped value is the constant variable name.
     AssignmentToConstant(String),
     /// Assignment to an inappropriate LHS (left-hand-side) expression.
@@ -166,8 +197,20 @@ pub enum ParseErrorType {
     ///
     /// Never appears under the `unchecked` feature.
     LiteralTooLarge(String, usize),
+    /// Too many variables declared at the same time. Wrapped value is the maximum number of variables.
+    ///
+    /// Never appears under the `unchecked` feature.
+    TooManyVariables(usize),
     /// Break statement not inside a loop.
     LoopBreak,
+    /// A `break` or `continue` statement refers to a label that is not on an enclosing loop.
+    /// Wrapped value is the label.
+    UndefinedLabel(String),
+    /// A variable and a module alias in scope share the same name.
+    /// Wrapped value is the name.
+    ///
+    /// Only raised when strict module aliases are enabled.
+    ModuleAliasConflict(String),
 }
 
 impl ParseErrorType {
@@ -176,6 +219,50 @@ impl ParseErrorType {
     pub(crate) fn into_err(self, pos: Position) -> ParseError {
         ParseError(Box::new(self), pos)
     }
+    /// Get the name of this error type, which is the same as its variant name
+    /// (e.g. `"MissingToken"`).
+    ///
+    /// The names are stable and can be relied upon, e.g. for displaying diagnostics.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UnexpectedEOF => "UnexpectedEOF",
+            Self::BadInput(_) => "BadInput",
+            Self::UnknownOperator(_) => "UnknownOperator",
+            Self::MissingToken(_, _) => "MissingToken",
+            Self::MalformedCallExpr(_) => "MalformedCallExpr",
+            Self::MalformedIndexExpr(_) => "MalformedIndexExpr",
+            Self::MalformedInExpr(_) => "MalformedInExpr",
+            Self::CaptureNotSupported(_, _) => "CaptureNotSupported",
+            Self::DuplicatedProperty(_) => "DuplicatedProperty",
+            Self::DuplicatedSwitchCase => "DuplicatedSwitchCase",
+            Self::DuplicatedVariable(_) => "DuplicatedVariable",
+            Self::PropertyExpected => "PropertyExpected",
+            Self::VariableExpected => "VariableExpected",
+            Self::VariableExists(_) => "VariableExists",
+            Self::VariableUndefined(_) => "VariableUndefined",
+            Self::Reserved(_) => "Reserved",
+            Self::DisabledSymbol(_, _) => "DisabledSymbol",
+            Self::ExprExpected(_) => "ExprExpected",
+            Self::WrongDocComment => "WrongDocComment",
+            Self::WrongFnDefinition => "WrongFnDefinition",
+            Self::FnMissingName => "FnMissingName",
+            Self::FnMissingParams(_) => "FnMissingParams",
+            Self::FnDuplicatedParam(_, _) => "FnDuplicatedParam",
+            Self::FnMissingDefault(_, _) => "FnMissingDefault",
+            Self::FnMisplacedRestParam(_, _) => "FnMisplacedRestParam",
+            Self::FnMissingBody(_) => "FnMissingBody",
+            Self::WrongExport => "WrongExport",
+            Self::WrongImport => "WrongImport",
+            Self::AssignmentToConstant(_) => "AssignmentToConstant",
+            Self::AssignmentToInvalidLHS(_) => "AssignmentToInvalidLHS",
+            Self::ExprTooDeep => "ExprTooDeep",
+            Self::LiteralTooLarge(_, _) => "LiteralTooLarge",
+            Self::TooManyVariables(_) => "TooManyVariables",
+            Self::LoopBreak => "LoopBreak",
+            Self::UndefinedLabel(_) => "UndefinedLabel",
+            Self::ModuleAliasConflict(_) => "ModuleAliasConflict",
+        }
+    }
 
     pub(crate) fn desc(&self) -> &str {
         match self {
@@ -186,25 +273,35 @@ impl ParseErrorType {
             Self::MalformedCallExpr(_) => "Invalid expression in function call arguments",
             Self::MalformedIndexExpr(_) => "Invalid index in indexing expression",
             Self::MalformedInExpr(_) => "Invalid 'in' expression",
-            Self::MalformedCapture(_) => "Invalid capturing",
+            Self::CaptureNotSupported(_, _) => "Capturing the scope is not supported for this function call",
             Self::DuplicatedProperty(_) => "Duplicated property in object map literal",
             Self::DuplicatedSwitchCase => "Duplicated switch case",
+            Self::DuplicatedVariable(_) => "Duplicated variable in destructuring pattern",
             Self::PropertyExpected => "Expecting name of a property",
             Self::VariableExpected => "Expecting name of a variable",
+            Self::VariableExists(_) => "Variable is already declared in the same block",
+            Self::VariableUndefined(_) => "Undefined variable",
             Self::Reserved(_) => "Invalid use of reserved keyword",
+            Self::DisabledSymbol(_, _) => "Invalid use of disabled symbol",
             Self::ExprExpected(_) => "Expecting an expression",
             Self::FnMissingName => "Expecting function name in function declaration",
             Self::FnMissingParams(_) => "Expecting parameters in function declaration",
             Self::FnDuplicatedParam(_,_) => "Duplicated parameters in function declaration",
+            Self::FnMissingDefault(_,_) => "Expecting default values for all parameters after the first default value in function declaration",
+            Self::FnMisplacedRestParam(_,_) => "The rest parameter must be the last parameter in function declaration",
             Self::FnMissingBody(_) => "Expecting body statement block for function declaration",
             Self::WrongDocComment => "Doc-comment must be followed immediately by a function definition",
             Self::WrongFnDefinition => "Function definitions must be at global level and cannot be inside a block or another function",
             Self::WrongExport => "Export statement can only appear at global level",
+            Self::WrongImport => "Import statement can only appear at the start of a statement, not inside an expression",
             Self::AssignmentToConstant(_) => "Cannot assign to a constant value",
             Self::AssignmentToInvalidLHS(_) => "Expression cannot be assigned to",
             Self::ExprTooDeep => "Expression exceeds maximum complexity",
             Self::LiteralTooLarge(_, _) => "Literal exceeds maximum limit",
-            Self::LoopBreak => "Break statement should only be used inside a loop"
+            Self::TooManyVariables(_) => "Too many variables declared",
+            Self::LoopBreak => "Break statement should only be used inside a loop",
+            Self::UndefinedLabel(_) => "Undefined loop label",
+            Self::ModuleAliasConflict(_) => "Variable and module alias share the same name",
         }
     }
 }
@@ -217,14 +314,23 @@ impl fmt::Display for ParseErrorType {
             Self::MalformedCallExpr(s) => f.write_str(if s.is_empty() { self.desc() } else { s }),
             Self::UnknownOperator(s) => write!(f, "{}: '{}'", self.desc(), s),
 
-            Self::MalformedIndexExpr(s) | Self::MalformedInExpr(s) | Self::MalformedCapture(s) => {
+            Self::MalformedIndexExpr(s) | Self::MalformedInExpr(s) => {
                 f.write_str(if s.is_empty() { self.desc() } else { s })
             }
 
+            Self::CaptureNotSupported(kind, s) => write!(
+                f,
+                "Capturing the scope is not supported for {}. Only plain function calls such as '{}!(...)' can capture the scope",
+                kind, s
+            ),
+
             Self::DuplicatedProperty(s) => {
                 write!(f, "Duplicated property '{}' for object map literal", s)
             }
             Self::DuplicatedSwitchCase => f.write_str(self.desc()),
+            Self::DuplicatedVariable(s) => {
+                write!(f, "Duplicated variable '{}' in destructuring pattern", s)
+            }
 
             Self::ExprExpected(s) => write!(f, "Expecting {} expression", s),
 
@@ -241,6 +347,28 @@ impl fmt::Display for ParseErrorType {
                 write!(f, "Duplicated parameter '{}' for function '{}'", arg, s)
             }
 
+            Self::FnMissingDefault(s, arg) if s.is_empty() => write!(
+                f,
+                "Expecting a default value for parameter '{}' of anonymous function",
+                arg
+            ),
+            Self::FnMissingDefault(s, arg) => write!(
+                f,
+                "Expecting a default value for parameter '{}' of function '{}'",
+                arg, s
+            ),
+
+            Self::FnMisplacedRestParam(s, arg) if s.is_empty() => write!(
+                f,
+                "Rest parameter '...{}' must be the last parameter of anonymous function",
+                arg
+            ),
+            Self::FnMisplacedRestParam(s, arg) => write!(
+                f,
+                "Rest parameter '...{}' must be the last parameter of function '{}'",
+                arg, s
+            ),
+
             Self::MissingToken(token, s) => write!(f, "Expecting '{}' {}", token, s),
 
             Self::AssignmentToConstant(s) if s.is_empty() => f.write_str(self.desc()),
@@ -253,7 +381,23 @@ impl fmt::Display for ParseErrorType {
                 write!(f, "{} exceeds the maximum limit ({})", typ, max)
             }
 
+            Self::TooManyVariables(max) => {
+                write!(f, "Number of variables exceeds the maximum limit ({})", max)
+            }
+
             Self::Reserved(s) => write!(f, "'{}' is a reserved keyword", s),
+            Self::DisabledSymbol(s, msg) => write!(f, "'{}' is disabled: {}", s, msg),
+
+            Self::UndefinedLabel(s) => write!(f, "Undefined loop label '{}'", s),
+
+            Self::VariableExists(s) => {
+                write!(f, "Variable '{}' is already declared in the same block", s)
+            }
+            Self::VariableUndefined(s) => write!(f, "Undefined variable '{}'", s),
+
+            Self::ModuleAliasConflict(s) => {
+                write!(f, "'{}' is both a variable and a module alias", s)
+            }
 
             _ => f.write_str(self.desc()),
         }
@@ -1,15 +1,18 @@
 //! Main module defining the lexer and parser.
 
-use crate::ast::{BinaryExpr, CustomExpr, Expr, FnCallExpr, Ident, ReturnType, ScriptFnDef, Stmt};
+use crate::ast::{
+    BinaryExpr, CustomExpr, Expr, FnCallExpr, Ident, ReturnType, ScriptFnDef, Stmt, SwitchRange,
+};
 use crate::dynamic::{AccessMode, Union};
-use crate::engine::KEYWORD_THIS;
+use crate::engine::{KEYWORD_FN_PTR_CALL, KEYWORD_THIS};
 use crate::module::NamespaceRef;
 use crate::optimize::optimize_into_ast;
 use crate::optimize::OptimizationLevel;
+use crate::scope::SCOPE_INDEX_THRESHOLD;
 use crate::stdlib::{
     borrow::Cow,
     boxed::Box,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     format,
     hash::{Hash, Hasher},
     iter::empty,
@@ -18,12 +21,14 @@ use crate::stdlib::{
     vec,
     vec::Vec,
 };
-use crate::syntax::{CustomSyntax, MARKER_BLOCK, MARKER_EXPR, MARKER_IDENT};
+use crate::syntax::{
+    CustomSyntax, ParseContext, MARKER_BLOCK, MARKER_EXPR, MARKER_EXPR_LIST, MARKER_IDENT,
+};
 use crate::token::{is_keyword_function, is_valid_identifier, Token, TokenStream};
 use crate::utils::{get_hasher, StraightHasherBuilder};
 use crate::{
-    calc_script_fn_hash, Dynamic, Engine, ImmutableString, LexError, ParseError, ParseErrorType,
-    Position, Scope, StaticVec, AST,
+    calc_script_fn_hash, Associativity, CharRange, Dynamic, Engine, EvalOptions, ImmutableString,
+    LexError, ParseError, ParseErrorType, Position, Scope, StaticVec, AST, INT,
 };
 
 #[cfg(not(feature = "no_float"))]
@@ -32,6 +37,12 @@ use crate::FLOAT;
 #[cfg(not(feature = "no_function"))]
 use crate::FnAccess;
 
+#[cfg(not(feature = "no_object"))]
+use crate::ast::MapKey;
+
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+use crate::ast::Pattern;
+
 type PERR = ParseErrorType;
 
 type FunctionsLib = HashMap<NonZeroU64, ScriptFnDef, StraightHasherBuilder>;
@@ -46,7 +57,10 @@ struct ParseState<'e> {
     /// Interned strings.
     strings: HashMap<String, ImmutableString>,
     /// Encapsulates a local stack with variable names to simulate an actual runtime scope.
-    stack: Vec<(ImmutableString, AccessMode)>,
+    stack: Vec<(ImmutableString, AccessMode, Position)>,
+    /// Indices into the local variables stack of each variable name, newest last.
+    /// Only built when the stack grows large, empty otherwise.
+    stack_index: HashMap<ImmutableString, StaticVec<usize>>,
     /// Size of the local variables stack upon entry of the current block scope.
     entry_stack_len: usize,
     /// Tracks a list of external variables (variables that are not explicitly declared in the scope).
@@ -61,6 +75,18 @@ struct ParseState<'e> {
     /// Encapsulates a local stack with imported [module][crate::Module] names.
     #[cfg(not(feature = "no_module"))]
     modules: StaticVec<ImmutableString>,
+    /// Disallow variables and module aliases with the same name in the same scope?
+    #[cfg(not(feature = "no_module"))]
+    strict_module_aliases: bool,
+    /// Encapsulates a local stack with the labels of the enclosing loops.
+    labels: StaticVec<ImmutableString>,
+    /// Disallow redeclared and undeclared variables?
+    strict_variables: bool,
+    /// Names of the variables in the [`Scope`] passed to the parser.
+    scope_vars: HashSet<ImmutableString>,
+    /// Is this the state of a closure, which may capture variables from the enclosing scope?
+    #[cfg(not(feature = "no_closure"))]
+    is_closure: bool,
     /// Maximum levels of expression nesting.
     #[cfg(not(feature = "unchecked"))]
     max_expr_depth: usize,
@@ -95,10 +121,86 @@ impl<'e> ParseState<'e> {
             allow_capture: true,
             strings: HashMap::with_capacity(64),
             stack: Vec::with_capacity(16),
+            stack_index: Default::default(),
             entry_stack_len: 0,
             #[cfg(not(feature = "no_module"))]
             modules: Default::default(),
+            #[cfg(not(feature = "no_module"))]
+            strict_module_aliases: engine.strict_module_aliases,
+            labels: Default::default(),
+            strict_variables: engine.strict_variables,
+            scope_vars: Default::default(),
+            #[cfg(not(feature = "no_closure"))]
+            is_closure: false,
+        }
+    }
+
+    /// Add (push) a variable to the local variables stack, keeping its index up-to-date.
+    ///
+    /// Returns an error if the maximum number of variables is exceeded.
+    fn push_var(
+        &mut self,
+        name: ImmutableString,
+        access: AccessMode,
+        pos: Position,
+    ) -> Result<(), ParseError> {
+        #[cfg(not(feature = "unchecked"))]
+        {
+            let max = self.engine.max_variables();
+
+            if max > 0 && self.stack.len() >= max {
+                return Err(PERR::TooManyVariables(max).into_err(pos));
+            }
+        }
+
+        if !self.stack_index.is_empty() {
+            self.stack_index
+                .entry(name.clone())
+                .or_default()
+                .push(self.stack.len());
+        }
+
+        self.stack.push((name, access, pos));
+
+        if self.stack_index.is_empty() && self.stack.len() > SCOPE_INDEX_THRESHOLD {
+            let index = &mut self.stack_index;
+
+            self.stack.iter().enumerate().for_each(|(i, (n, _, _))| {
+                index.entry(n.clone()).or_default().push(i);
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Truncate (rewind) the local variables stack to a previous size, keeping its index up-to-date.
+    fn rewind_stack(&mut self, len: usize) {
+        if len > SCOPE_INDEX_THRESHOLD && !self.stack_index.is_empty() {
+            let index = &mut self.stack_index;
+
+            self.stack.iter().skip(len).for_each(|(n, _, _)| {
+                if let Some(list) = index.get_mut(n.as_str()) {
+                    list.pop();
+                    if list.is_empty() {
+                        index.remove(n.as_str());
+                    }
+                }
+            });
+        } else if len < self.stack.len() {
+            self.stack_index.clear();
         }
+
+        self.stack.truncate(len);
+    }
+
+    /// Find the index into the local variables stack of the newest variable with a name,
+    /// via the index.
+    #[inline(always)]
+    fn find_indexed_var(&self, name: &str) -> Option<usize> {
+        self.stack_index
+            .get(name)
+            .and_then(|list| list.last())
+            .cloned()
     }
 
     /// Find explicitly declared variable by name in the [`ParseState`], searching in reverse order.
@@ -113,21 +215,37 @@ impl<'e> ParseState<'e> {
     fn access_var(&mut self, name: &str, _pos: Position) -> Option<NonZeroUsize> {
         let mut barrier = false;
 
-        let index = self
-            .stack
-            .iter()
-            .rev()
-            .enumerate()
-            .find(|(_, (n, _))| {
-                if n.is_empty() {
-                    // Do not go beyond empty variable names
-                    barrier = true;
-                    false
-                } else {
-                    *n == name
-                }
-            })
-            .and_then(|(i, _)| NonZeroUsize::new(i + 1));
+        let index = if self.stack_index.is_empty() {
+            self.stack
+                .iter()
+                .rev()
+                .enumerate()
+                .find(|(_, (n, _, _))| {
+                    if n.is_empty() {
+                        // Do not go beyond empty variable names
+                        barrier = true;
+                        false
+                    } else {
+                        *n == name
+                    }
+                })
+                .and_then(|(i, _)| NonZeroUsize::new(i + 1))
+        } else {
+            let newest = if name.is_empty() {
+                None
+            } else {
+                self.find_indexed_var(name)
+            };
+
+            // A barrier is hit if there is an empty variable name after the variable
+            barrier = match (self.find_indexed_var(""), newest) {
+                (Some(empty), Some(index)) => empty > index,
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+
+            newest.and_then(|index| NonZeroUsize::new(self.stack.len() - index))
+        };
 
         #[cfg(not(feature = "no_closure"))]
         if self.allow_capture {
@@ -145,6 +263,67 @@ impl<'e> ParseState<'e> {
         }
     }
 
+    /// Make sure that a variable not found by `access_var` is declared,
+    /// when strict variables are enabled.
+    ///
+    /// A variable is considered declared if it is in the [`Scope`] passed to the parser,
+    /// if it is a global constant registered with the [`Engine`],
+    /// if it may be defined by custom syntax beyond a barrier,
+    /// or if it may be captured by a closure from the enclosing scope.
+    fn ensure_var_declared(&self, name: &str, pos: Position) -> Result<(), ParseError> {
+        if !self.strict_variables
+            || self.engine.resolve_var.is_some()
+            || self.engine.write_var.is_some()
+        {
+            return Ok(());
+        }
+
+        #[cfg(not(feature = "no_closure"))]
+        if self.is_closure {
+            return Ok(());
+        }
+
+        let in_stack = if self.stack_index.is_empty() {
+            self.stack
+                .iter()
+                .any(|(n, _, _)| n.is_empty() || *n == name)
+        } else {
+            self.stack_index.contains_key("") || self.stack_index.contains_key(name)
+        };
+
+        if in_stack
+            || self.scope_vars.contains(name)
+            || self
+                .engine
+                .global_constants
+                .iter()
+                .any(|m| m.contains_var(name))
+        {
+            Ok(())
+        } else {
+            Err(PERR::VariableUndefined(name.to_string()).into_err(pos))
+        }
+    }
+
+    /// Make sure that a variable is not already declared in the current block,
+    /// when strict variables are enabled.
+    fn ensure_not_declared_in_block(&self, name: &str, pos: Position) -> Result<(), ParseError> {
+        let declared = if self.stack_index.is_empty() {
+            self.stack[self.entry_stack_len..]
+                .iter()
+                .any(|(n, _, _)| n.as_str() == name)
+        } else {
+            self.find_indexed_var(name)
+                .is_some_and(|index| index >= self.entry_stack_len)
+        };
+
+        if self.strict_variables && declared {
+            Err(PERR::VariableExists(name.to_string()).into_err(pos))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Find a module by name in the [`ParseState`], searching in reverse.
     ///
     /// Returns the offset to be deducted from `Stack::len`,
@@ -166,6 +345,49 @@ impl<'e> ParseState<'e> {
             .and_then(|(i, _)| NonZeroUsize::new(i + 1))
     }
 
+    /// Find the position where a variable is declared in the [`ParseState`], searching in reverse.
+    ///
+    /// Unlike `access_var`, this does not stop at barriers nor capture external variables.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    fn find_var_pos(&self, name: &str) -> Option<Position> {
+        if !self.stack_index.is_empty() {
+            return self.find_indexed_var(name).map(|index| self.stack[index].2);
+        }
+
+        self.stack
+            .iter()
+            .rev()
+            .find(|(n, _, _)| *n == name)
+            .map(|(_, _, pos)| *pos)
+    }
+
+    /// Resolve the root of a [namespace][NamespaceRef] to a module alias in the [`ParseState`].
+    ///
+    /// If no module alias is found but a variable of the same name is in scope,
+    /// its position is recorded so that a failed resolution can point to it.
+    #[cfg(not(feature = "no_module"))]
+    fn resolve_namespace(&self, namespace: &mut NamespaceRef) {
+        let index = self.find_module(&namespace[0].name);
+
+        if index.is_none() {
+            namespace.set_shadowing_var(self.find_var_pos(&namespace[0].name));
+        }
+
+        namespace.set_index(index);
+    }
+
+    /// Under strict module aliases, make sure that a new variable does not shadow a module alias.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    fn ensure_not_module_alias(&self, name: &str, pos: Position) -> Result<(), ParseError> {
+        if self.strict_module_aliases && self.find_module(name).is_some() {
+            Err(PERR::ModuleAliasConflict(name.to_string()).into_err(pos))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Get an interned string, creating one if it is not yet interned.
     pub fn get_interned_string(
         &mut self,
@@ -182,6 +404,16 @@ impl<'e> ParseState<'e> {
             self.strings.get(text.as_ref()).unwrap().clone()
         }
     }
+
+    /// Get the error for an invalid use of a reserved keyword or symbol,
+    /// which is [`PERR::DisabledSymbol`] if the symbol has been disabled with a message.
+    #[inline]
+    pub fn symbol_error(&self, symbol: String, err: impl FnOnce(String) -> PERR) -> PERR {
+        match self.engine.disabled_symbols.get(&symbol) {
+            Some(Some(msg)) => PERR::DisabledSymbol(symbol, msg.clone()),
+            _ => err(symbol),
+        }
+    }
 }
 
 /// A type that encapsulates all the settings for a particular parsing function.
@@ -193,14 +425,22 @@ struct ParseSettings {
     is_global: bool,
     /// Is the construct being parsed located at function definition level?
     is_function_scope: bool,
+    /// Is `this` bound at global level?
+    is_this_bound: bool,
     /// Is the current position inside a loop?
     is_breakable: bool,
+    /// Is the construct being parsed the right-hand side of a property access?
+    is_property: bool,
     /// Is anonymous function allowed?
     allow_anonymous_fn: bool,
     /// Is if-expression allowed?
     allow_if_expr: bool,
     /// Is switch expression allowed?
     allow_switch_expr: bool,
+    /// Is try-catch expression allowed?
+    allow_try_expr: bool,
+    /// Is loop expression allowed?
+    allow_loop_expr: bool,
     /// Is statement-expression allowed?
     allow_stmt_expr: bool,
     /// Current expression nesting level.
@@ -306,6 +546,29 @@ fn parse_paren_expr(
     }
 }
 
+/// Parse a call to the [function pointer][crate::FnPtr] value of an expression, i.e. `expr(args)`,
+/// which is turned into `call(expr, args)`.
+fn parse_fn_ptr_call(
+    input: &mut TokenStream,
+    state: &mut ParseState,
+    lib: &mut FunctionsLib,
+    fn_ptr_expr: Expr,
+    mut settings: ParseSettings,
+) -> Result<Expr, ParseError> {
+    settings.pos = fn_ptr_expr.position();
+
+    let fn_name = state.get_interned_string(KEYWORD_FN_PTR_CALL);
+
+    match parse_fn_call(input, state, lib, fn_name, false, None, settings)? {
+        Expr::FnCall(mut x, pos) => {
+            x.args.insert(0, fn_ptr_expr);
+            x.hash_script = calc_script_fn_hash(empty(), &x.name, x.args.len());
+            Ok(Expr::FnCall(x, pos))
+        }
+        expr => unreachable!("expecting Expr::FnCall, but gets {:?}", expr),
+    }
+}
+
 /// Parse a function call.
 fn parse_fn_call(
     input: &mut TokenStream,
@@ -340,7 +603,7 @@ fn parse_fn_call(
 
             let mut hash_script = if let Some(ref mut modules) = namespace {
                 #[cfg(not(feature = "no_module"))]
-                modules.set_index(state.find_module(&modules[0].name));
+                state.resolve_namespace(modules);
 
                 // Rust functions are indexed in two steps:
                 // 1) Calculate a hash in a similar manner to script-defined functions,
@@ -362,7 +625,7 @@ fn parse_fn_call(
 
             return Ok(Expr::FnCall(
                 Box::new(FnCallExpr {
-                    name: id.to_string().into(),
+                    name: id,
                     capture,
                     namespace,
                     hash_script,
@@ -392,7 +655,7 @@ fn parse_fn_call(
 
                 let mut hash_script = if let Some(modules) = namespace.as_mut() {
                     #[cfg(not(feature = "no_module"))]
-                    modules.set_index(state.find_module(&modules[0].name));
+                    state.resolve_namespace(modules);
 
                     // Rust functions are indexed in two steps:
                     // 1) Calculate a hash in a similar manner to script-defined functions,
@@ -414,7 +677,7 @@ fn parse_fn_call(
 
                 return Ok(Expr::FnCall(
                     Box::new(FnCallExpr {
-                        name: id.to_string().into(),
+                        name: id,
                         capture,
                         namespace,
                         hash_script,
@@ -458,6 +721,7 @@ fn parse_index_chain(
     state: &mut ParseState,
     lib: &mut FunctionsLib,
     lhs: Expr,
+    optional: bool,
     mut settings: ParseSettings,
 ) -> Result<Expr, ParseError> {
     #[cfg(not(feature = "unchecked"))]
@@ -465,10 +729,40 @@ fn parse_index_chain(
 
     let idx_expr = parse_expr(input, state, lib, settings.level_up())?;
 
+    // lhs[idx1, idx2, ...] - multiple indices are passed to the indexer function
+    let idx_expr = match input.peek().unwrap() {
+        (Token::Comma, _) => {
+            let idx_pos = idx_expr.position();
+            let mut indices: StaticVec<_> = Default::default();
+            indices.push(idx_expr);
+
+            while match_token(input, Token::Comma).0 {
+                indices.push(parse_expr(input, state, lib, settings.level_up())?);
+            }
+
+            Expr::IndexArgs(Box::new(indices), idx_pos)
+        }
+        _ => idx_expr,
+    };
+
     // Check type of indexing - must be integer or string
     match &idx_expr {
-        // lhs[int]
-        Expr::IntegerConstant(x, pos) if *x < 0 => {
+        // [...][int] - negative indices are allowed only if an index getter is registered for arrays
+        // (other expressions may evaluate to integers, whose bits can be indexed from the MSB)
+        Expr::IntegerConstant(x, pos)
+            if *x < 0
+                && matches!(lhs, Expr::Array(_, _) | Expr::StringConstant(_, _))
+                && !state.engine.has_override_by_name_and_arguments(
+                    None,
+                    &[],
+                    crate::engine::FN_IDX_GET,
+                    [
+                        crate::stdlib::any::TypeId::of::<crate::Array>(),
+                        crate::stdlib::any::TypeId::of::<crate::INT>(),
+                    ],
+                    false,
+                ) =>
+        {
             return Err(PERR::MalformedIndexExpr(format!(
                 "Array access expects non-negative index: {} < 0",
                 *x
@@ -488,7 +782,7 @@ fn parse_index_chain(
             #[cfg(not(feature = "no_float"))]
             Expr::FloatConstant(_, _) => {
                 return Err(PERR::MalformedIndexExpr(
-                    "Only arrays, object maps and strings can be indexed".into(),
+                    "Only arrays, object maps, strings and integers can be indexed".into(),
                 )
                 .into_err(lhs.position()))
             }
@@ -500,7 +794,7 @@ fn parse_index_chain(
             | Expr::BoolConstant(_, _)
             | Expr::Unit(_) => {
                 return Err(PERR::MalformedIndexExpr(
-                    "Only arrays, object maps and strings can be indexed".into(),
+                    "Only arrays, object maps, strings and integers can be indexed".into(),
                 )
                 .into_err(lhs.position()))
             }
@@ -590,21 +884,30 @@ fn parse_index_chain(
             // Any more indexing following?
             match input.peek().unwrap() {
                 // If another indexing level, right-bind it
-                (Token::LeftBracket, _) => {
+                (Token::LeftBracket, _) | (Token::QuestionBracket, _) => {
                     let prev_pos = settings.pos;
-                    settings.pos = eat_token(input, Token::LeftBracket);
+                    let (token, pos) = input.next().unwrap();
+                    settings.pos = pos;
                     // Recursively parse the indexing chain, right-binding each
-                    let idx_expr =
-                        parse_index_chain(input, state, lib, idx_expr, settings.level_up())?;
+                    let idx_expr = parse_index_chain(
+                        input,
+                        state,
+                        lib,
+                        idx_expr,
+                        token == Token::QuestionBracket,
+                        settings.level_up(),
+                    )?;
                     // Indexing binds to right
                     Ok(Expr::Index(
                         Box::new(BinaryExpr { lhs, rhs: idx_expr }),
+                        optional,
                         prev_pos,
                     ))
                 }
                 // Otherwise terminate the indexing chain
                 _ => Ok(Expr::Index(
                     Box::new(BinaryExpr { lhs, rhs: idx_expr }),
+                    optional,
                     settings.pos,
                 )),
             }
@@ -702,7 +1005,7 @@ fn parse_map_literal(
     // #{ ...
     settings.pos = eat_token(input, Token::MapStart);
 
-    let mut map: StaticVec<(Ident, Expr)> = Default::default();
+    let mut map: StaticVec<(MapKey, Expr)> = Default::default();
 
     loop {
         const MISSING_RBRACE: &str = "to end this object map literal";
@@ -721,15 +1024,40 @@ fn parse_map_literal(
             _ => (),
         }
 
-        let (name, pos) = match input.next().unwrap() {
-            (Token::Identifier(s), pos) | (Token::StringConstant(s), pos) => {
-                if map.iter().any(|(p, _)| p.name == &s) {
-                    return Err(PERR::DuplicatedProperty(s).into_err(pos));
+        let (key, is_ident) = match input.next().unwrap() {
+            (Token::Identifier(s), pos) | (Token::StringConstant(s), pos)
+                if map.iter().any(|(p, _)| p.name().is_some_and(|p| *p == s)) =>
+            {
+                return Err(PERR::DuplicatedProperty(s).into_err(pos));
+            }
+            (Token::Identifier(s), pos) => {
+                let name = state.get_interned_string(s);
+                (MapKey::Name(Ident { name, pos }), true)
+            }
+            (Token::StringConstant(s), pos) => {
+                let name = state.get_interned_string(s);
+                (MapKey::Name(Ident { name, pos }), false)
+            }
+            // [expr] - computed property name
+            (Token::LeftBracket, _) => {
+                let key = parse_expr(input, state, lib, settings.level_up())?;
+
+                match input.next().unwrap() {
+                    (Token::RightBracket, _) => (),
+                    (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                    (_, pos) => {
+                        return Err(PERR::MissingToken(
+                            Token::RightBracket.into(),
+                            "to end this computed property name".into(),
+                        )
+                        .into_err(pos))
+                    }
                 }
-                (s, pos)
+
+                (MapKey::Computed(key), false)
             }
             (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
-                return Err(PERR::Reserved(s).into_err(pos));
+                return Err(state.symbol_error(s, PERR::Reserved).into_err(pos));
             }
             (Token::LexError(err), pos) => return Err(err.into_err(pos)),
             (_, pos) if map.is_empty() => {
@@ -747,20 +1075,32 @@ fn parse_map_literal(
             (_, pos) => return Err(PERR::PropertyExpected.into_err(pos)),
         };
 
-        match input.next().unwrap() {
-            (Token::Colon, _) => (),
-            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
-            (_, pos) => {
-                return Err(PERR::MissingToken(
-                    Token::Colon.into(),
-                    format!(
-                        "to follow the property '{}' in this object map literal",
-                        name
-                    ),
-                )
-                .into_err(pos))
-            }
-        };
+        // #{ name, ... } is short for #{ name: name, ... }
+        let is_shorthand =
+            is_ident && matches!(input.peek().unwrap().0, Token::Comma | Token::RightBrace);
+
+        if !is_shorthand {
+            match input.next().unwrap() {
+                (Token::Colon, _) => (),
+                (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                (_, pos) => {
+                    return Err(PERR::MissingToken(
+                        Token::Colon.into(),
+                        match key.name() {
+                            Some(name) => format!(
+                                "to follow the property '{}' in this object map literal",
+                                name
+                            ),
+                            None => {
+                                "to follow the computed property name in this object map literal"
+                                    .into()
+                            }
+                        },
+                    )
+                    .into_err(pos))
+                }
+            };
+        }
 
         #[cfg(not(feature = "unchecked"))]
         if state.engine.max_map_size() > 0 && map.len() >= state.engine.max_map_size() {
@@ -771,9 +1111,20 @@ fn parse_map_literal(
             .into_err(input.peek().unwrap().1));
         }
 
-        let expr = parse_expr(input, state, lib, settings.level_up())?;
-        let name = state.get_interned_string(name);
-        map.push((Ident { name, pos }, expr));
+        let expr = match key {
+            MapKey::Name(ref var_name_def) if is_shorthand => {
+                let index = state.access_var(&var_name_def.name, var_name_def.pos);
+
+                if index.is_none() {
+                    state.ensure_var_declared(&var_name_def.name, var_name_def.pos)?;
+                }
+
+                Expr::Variable(Box::new((index, None, var_name_def.clone())))
+            }
+            _ => parse_expr(input, state, lib, settings.level_up())?,
+        };
+
+        map.push((key, expr));
 
         match input.peek().unwrap() {
             (Token::Comma, _) => {
@@ -828,6 +1179,7 @@ fn parse_switch(
     }
 
     let mut table = HashMap::new();
+    let mut ranges = StaticVec::new();
     let mut def_stmt = None;
 
     loop {
@@ -852,17 +1204,36 @@ fn parse_switch(
             _ => Some(parse_expr(input, state, lib, settings.level_up())?),
         };
 
+        let mut range = None;
+
+        // x..y, x..=y
+        let expr = match (expr, input.peek().unwrap()) {
+            (Some(start), (Token::DoublePeriod, _)) | (Some(start), (Token::InclusiveRange, _)) => {
+                let inclusive = input.next().unwrap().0 == Token::InclusiveRange;
+                let end = parse_expr(input, state, lib, settings.level_up())?;
+                range = Some(parse_int_range(&start, &end, inclusive)?);
+                None
+            }
+            (expr, _) => expr,
+        };
+
         let hash = if let Some(expr) = expr {
             if let Some(value) = expr.get_constant_value() {
-                let hasher = &mut get_hasher();
-                value.hash(hasher);
-                let hash = hasher.finish();
+                if value.is::<CharRange>() {
+                    // Ranges cannot be hashed - they are matched in order after a hash miss
+                    range = Some(SwitchRange::Char(value.cast::<CharRange>()));
+                    None
+                } else {
+                    let hasher = &mut get_hasher();
+                    value.hash(hasher);
+                    let hash = hasher.finish();
 
-                if table.contains_key(&hash) {
-                    return Err(PERR::DuplicatedSwitchCase.into_err(expr.position()));
-                }
+                    if table.contains_key(&hash) {
+                        return Err(PERR::DuplicatedSwitchCase.into_err(expr.position()));
+                    }
 
-                Some(hash)
+                    Some(hash)
+                }
             } else {
                 return Err(PERR::ExprExpected("a literal".to_string()).into_err(expr.position()));
             }
@@ -889,6 +1260,9 @@ fn parse_switch(
         def_stmt = if let Some(hash) = hash {
             table.insert(hash, stmt);
             None
+        } else if let Some(range) = range {
+            ranges.push((range, stmt));
+            None
         } else {
             Some(stmt)
         };
@@ -921,11 +1295,75 @@ fn parse_switch(
 
     Ok(Stmt::Switch(
         item,
-        Box::new((final_table, def_stmt)),
+        Box::new((final_table, def_stmt, ranges)),
         settings.pos,
     ))
 }
 
+/// Make an integer range case of a `switch` statement out of its bounds.
+fn parse_int_range(start: &Expr, end: &Expr, inclusive: bool) -> Result<SwitchRange, ParseError> {
+    let bound = |expr: &Expr| match expr.get_constant_value() {
+        Some(value) if value.is::<INT>() => Ok(value.cast::<INT>()),
+        #[cfg(not(feature = "no_float"))]
+        Some(value) if value.is::<FLOAT>() => Err(LexError::ImproperSymbol(
+            "..".into(),
+            "Floating-point ranges are not supported in switch cases".into(),
+        )
+        .into_err(expr.position())),
+        Some(_) => Err(PERR::ExprExpected("an integer".into()).into_err(expr.position())),
+        None => Err(PERR::ExprExpected("a constant range bound".into()).into_err(expr.position())),
+    };
+
+    let start = bound(start)?;
+    let end_pos = end.position();
+    let end = bound(end)?;
+
+    Ok(SwitchRange::Int(if inclusive {
+        start..=end
+    } else {
+        // x..y - stop at the number before y
+        match end.checked_sub(1) {
+            Some(end) => start..=end,
+            // x..INT::MIN - nothing to match
+            None => {
+                return Err(LexError::ImproperSymbol(
+                    "..".into(),
+                    "This range is empty and never matches".into(),
+                )
+                .into_err(end_pos))
+            }
+        }
+    }))
+}
+
+/// Parse a character range literal after its first character: `..` 'y' or `..=` 'y'.
+fn parse_char_range(
+    input: &mut TokenStream,
+    start: char,
+    pos: Position,
+) -> Result<Expr, ParseError> {
+    let inclusive = input.next().unwrap().0 == Token::InclusiveRange;
+
+    let end = match input.next().unwrap() {
+        (Token::CharConstant(c), _) => c,
+        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+        (_, pos) => return Err(PERR::ExprExpected("a character".into()).into_err(pos)),
+    };
+
+    let range: CharRange = if inclusive {
+        start..=end
+    } else {
+        // 'x'..'y' - stop at the character before 'y'
+        match end {
+            '\0' => '\u{1}'..='\0',
+            '\u{E000}' => start..='\u{D7FF}',
+            _ => start..=char::from_u32(end as u32 - 1).unwrap(),
+        }
+    };
+
+    Ok(Expr::DynamicConstant(Box::new(Dynamic::from(range)), pos))
+}
+
 /// Parse a primary expression.
 fn parse_primary(
     input: &mut TokenStream,
@@ -939,6 +1377,10 @@ fn parse_primary(
     let (token, token_pos) = input.peek().unwrap();
     settings.pos = *token_pos;
 
+    // Only the postfix operators of a property are affected, not any nested expression
+    let is_property = settings.is_property;
+    settings.is_property = false;
+
     let mut root_expr = match token {
         Token::EOF => return Err(PERR::UnexpectedEOF.into_err(settings.pos)),
 
@@ -948,7 +1390,13 @@ fn parse_primary(
         | Token::True
         | Token::False => match input.next().unwrap().0 {
             Token::IntegerConstant(x) => Expr::IntegerConstant(x, settings.pos),
-            Token::CharConstant(c) => Expr::CharConstant(c, settings.pos),
+            Token::CharConstant(c) => match input.peek().unwrap() {
+                // 'x'..'y', 'x'..='y'
+                (Token::DoublePeriod, _) | (Token::InclusiveRange, _) => {
+                    parse_char_range(input, c, settings.pos)?
+                }
+                _ => Expr::CharConstant(c, settings.pos),
+            },
             Token::StringConstant(s) => {
                 Expr::StringConstant(state.get_interned_string(s), settings.pos)
             }
@@ -971,7 +1419,16 @@ fn parse_primary(
             }
         }
         // ( - grouped expression
-        Token::LeftParen => parse_paren_expr(input, state, lib, settings.level_up())?,
+        Token::LeftParen => {
+            let expr = parse_paren_expr(input, state, lib, settings.level_up())?;
+
+            // (expr)(args) - call the function pointer value of the expression
+            if match_token(input, Token::LeftParen).0 {
+                parse_fn_ptr_call(input, state, lib, expr, settings.level_up())?
+            } else {
+                expr
+            }
+        }
 
         // If statement is allowed to act as expressions
         Token::If if settings.allow_if_expr => Expr::Stmt(
@@ -983,6 +1440,64 @@ fn parse_primary(
             Box::new(vec![parse_switch(input, state, lib, settings.level_up())?].into()),
             settings.pos,
         ),
+        // Try-catch statement is allowed to act as expressions
+        Token::Try if settings.allow_try_expr => Expr::Stmt(
+            Box::new(vec![parse_try_catch(input, state, lib, settings.level_up())?].into()),
+            settings.pos,
+        ),
+        // Loops are allowed to act as expressions
+        Token::While | Token::Loop if settings.allow_loop_expr => Expr::Stmt(
+            Box::new(
+                vec![parse_while_loop(
+                    input,
+                    state,
+                    lib,
+                    None,
+                    settings.level_up(),
+                )?]
+                .into(),
+            ),
+            settings.pos,
+        ),
+        Token::Do if settings.allow_loop_expr => Expr::Stmt(
+            Box::new(vec![parse_do(input, state, lib, None, settings.level_up())?].into()),
+            settings.pos,
+        ),
+        Token::For if settings.allow_loop_expr => Expr::Stmt(
+            Box::new(vec![parse_for(input, state, lib, None, settings.level_up())?].into()),
+            settings.pos,
+        ),
+        Token::Label(_) if settings.allow_loop_expr => Expr::Stmt(
+            Box::new(vec![parse_labeled_loop(input, state, lib, settings.level_up())?].into()),
+            settings.pos,
+        ),
+        // import - not an expression
+        #[cfg(not(feature = "no_module"))]
+        Token::Import => return Err(PERR::WrongImport.into_err(settings.pos)),
+
+        // Statements that are not allowed to act as expressions in this context
+        Token::LeftBrace
+        | Token::If
+        | Token::Switch
+        | Token::Try
+        | Token::While
+        | Token::Loop
+        | Token::Do
+        | Token::For
+        | Token::Label(_) => {
+            let kind = match token {
+                Token::LeftBrace => "statement-expressions",
+                Token::If => "if-expressions",
+                Token::Switch => "switch-expressions",
+                Token::Try => "try-expressions",
+                _ => "loop-expressions",
+            };
+            return Err(LexError::ImproperSymbol(
+                token.syntax().to_string(),
+                format!("{} are disabled in this context", kind),
+            )
+            .into_err(settings.pos));
+        }
         // | ...
         #[cfg(not(feature = "no_function"))]
         Token::Pipe | Token::Or if settings.allow_anonymous_fn => {
@@ -995,13 +1510,27 @@ fn parse_primary(
                 state.max_function_expr_depth,
             );
 
+            #[cfg(not(feature = "no_module"))]
+            {
+                new_state.strict_module_aliases = state.strict_module_aliases;
+            }
+            new_state.strict_variables = state.strict_variables;
+            #[cfg(not(feature = "no_closure"))]
+            {
+                new_state.is_closure = true;
+            }
+
             let settings = ParseSettings {
                 allow_if_expr: true,
                 allow_switch_expr: true,
+                allow_try_expr: true,
+                allow_loop_expr: true,
                 allow_stmt_expr: true,
                 allow_anonymous_fn: true,
+                is_property: false,
                 is_global: false,
                 is_function_scope: true,
+                is_this_bound: false,
                 is_breakable: false,
                 level: 0,
                 pos: settings.pos,
@@ -1010,13 +1539,15 @@ fn parse_primary(
             let (expr, func) = parse_anon_fn(input, &mut new_state, lib, settings)?;
 
             #[cfg(not(feature = "no_closure"))]
-            new_state.externals.iter().for_each(|(closure, pos)| {
-                state.access_var(closure, *pos);
-            });
+            for (closure, pos) in new_state.externals.iter() {
+                if state.access_var(closure, *pos).is_none() {
+                    state.ensure_var_declared(closure, *pos)?;
+                }
+            }
 
             lib.insert(
                 // Qualifiers (none) + function name + number of arguments.
-                calc_script_fn_hash(empty(), &func.name, func.params.len()).unwrap(),
+                calc_script_fn_hash(empty(), &func.name, func.num_fixed_params()).unwrap(),
                 func,
             );
 
@@ -1069,6 +1600,11 @@ fn parse_primary(
                 // Normal variable access
                 _ => {
                     let index = state.access_var(&s, settings.pos);
+
+                    if index.is_none() && !is_property {
+                        state.ensure_var_declared(&s, settings.pos)?;
+                    }
+
                     let var_name_def = Ident {
                         name: state.get_interned_string(s),
                         pos: settings.pos,
@@ -1089,7 +1625,7 @@ fn parse_primary(
                 // Function call is allowed to have reserved keyword
                 Token::LeftParen | Token::Bang => {
                     if s == KEYWORD_THIS {
-                        return Err(PERR::Reserved(s).into_err(settings.pos));
+                        return Err(state.symbol_error(s, PERR::Reserved).into_err(settings.pos));
                     } else if is_keyword_function(&s) {
                         let var_name_def = Ident {
                             name: state.get_interned_string(s),
@@ -1097,12 +1633,12 @@ fn parse_primary(
                         };
                         Expr::Variable(Box::new((None, None, var_name_def)))
                     } else {
-                        return Err(PERR::Reserved(s).into_err(settings.pos));
+                        return Err(state.symbol_error(s, PERR::Reserved).into_err(settings.pos));
                     }
                 }
                 // Access to `this` as a variable is OK
                 _ if s == KEYWORD_THIS => {
-                    if !settings.is_function_scope {
+                    if !settings.is_function_scope && !settings.is_this_bound {
                         let msg = format!("'{}' can only be used in functions", s);
                         return Err(LexError::ImproperSymbol(s, msg).into_err(settings.pos));
                     } else {
@@ -1114,10 +1650,12 @@ fn parse_primary(
                     }
                 }
                 _ if is_valid_identifier(s.chars()) => {
-                    return Err(PERR::Reserved(s).into_err(settings.pos));
+                    return Err(state.symbol_error(s, PERR::Reserved).into_err(settings.pos));
                 }
                 _ => {
-                    return Err(LexError::UnexpectedInput(s).into_err(settings.pos));
+                    return Err(state
+                        .symbol_error(s, |s| LexError::UnexpectedInput(s).into())
+                        .into_err(settings.pos));
                 }
             }
         }
@@ -1146,6 +1684,12 @@ fn parse_primary(
             break;
         }
 
+        // A property followed by ( is a method call, which is handled by the property access
+        if is_property && *tail_token == Token::LeftParen && !matches!(root_expr, Expr::Variable(_))
+        {
+            break;
+        }
+
         let (tail_token, tail_pos) = input.next().unwrap();
         settings.pos = tail_pos;
 
@@ -1155,9 +1699,9 @@ fn parse_primary(
                 return Err(if !match_token(input, Token::LeftParen).0 {
                     LexError::UnexpectedInput(Token::Bang.syntax().to_string()).into_err(tail_pos)
                 } else {
-                    LexError::ImproperSymbol(
-                        "!".to_string(),
-                        "'!' cannot be used to call module functions".to_string(),
+                    PERR::CaptureNotSupported(
+                        "namespace-qualified function calls".into(),
+                        x.2.name.to_string(),
                     )
                     .into_err(tail_pos)
                 });
@@ -1185,6 +1729,10 @@ fn parse_primary(
                 let ns = namespace.map(|(_, ns)| ns);
                 parse_fn_call(input, state, lib, name, false, ns, settings.level_up())?
             }
+            // expr(args) - call the function pointer value of the expression
+            (expr, Token::LeftParen) => {
+                parse_fn_ptr_call(input, state, lib, expr, settings.level_up())?
+            }
             // module access
             (Expr::Variable(x), Token::DoubleColon) => match input.next().unwrap() {
                 (Token::Identifier(id2), pos2) => {
@@ -1206,26 +1754,30 @@ fn parse_primary(
                     Expr::Variable(Box::new((index, namespace, var_name_def)))
                 }
                 (Token::Reserved(id2), pos2) if is_valid_identifier(id2.chars()) => {
-                    return Err(PERR::Reserved(id2).into_err(pos2));
+                    return Err(state.symbol_error(id2, PERR::Reserved).into_err(pos2));
                 }
                 (_, pos2) => return Err(PERR::VariableExpected.into_err(pos2)),
             },
             // Indexing
             #[cfg(not(feature = "no_index"))]
-            (expr, Token::LeftBracket) => {
-                parse_index_chain(input, state, lib, expr, settings.level_up())?
+            (expr, token @ Token::LeftBracket) | (expr, token @ Token::QuestionBracket) => {
+                let optional = token == Token::QuestionBracket;
+                parse_index_chain(input, state, lib, expr, optional, settings.level_up())?
             }
             // Property access
             #[cfg(not(feature = "no_object"))]
-            (expr, Token::Period) => {
+            (expr, token @ Token::Period) | (expr, token @ Token::Elvis) => {
                 // prevents capturing of the object properties as vars: xxx.<var>
                 #[cfg(not(feature = "no_closure"))]
                 if let (Token::Identifier(_), _) = input.peek().unwrap() {
                     state.allow_capture = false;
                 }
 
-                let rhs = parse_primary(input, state, lib, settings.level_up())?;
-                make_dot_expr(state, expr, rhs, tail_pos)?
+                let mut rhs_settings = settings.level_up();
+                rhs_settings.is_property = true;
+
+                let rhs = parse_primary(input, state, lib, rhs_settings)?;
+                make_dot_expr(state, expr, rhs, token == Token::Elvis, tail_pos)?
             }
             // Unknown postfix operator
             (expr, token) => unreachable!(
@@ -1239,7 +1791,7 @@ fn parse_primary(
     // Cache the hash key for namespace-qualified variables
     match &mut root_expr {
         Expr::Variable(x) if x.1.is_some() => Some(x),
-        Expr::Index(x, _) | Expr::Dot(x, _) => match &mut x.lhs {
+        Expr::Index(x, _, _) | Expr::Dot(x, _, _) => match &mut x.lhs {
             Expr::Variable(x) if x.1.is_some() => Some(x),
             _ => None,
         },
@@ -1252,7 +1804,7 @@ fn parse_primary(
                 calc_script_fn_hash(namespace.iter().map(|v| v.name.as_str()), name, 0).unwrap();
 
             #[cfg(not(feature = "no_module"))]
-            namespace.set_index(state.find_module(&namespace[0].name));
+            state.resolve_namespace(namespace);
         }
         _ => unreachable!("expecting namespace-qualified variable access"),
     });
@@ -1304,7 +1856,7 @@ fn parse_unary(
 
                     Ok(Expr::FnCall(
                         Box::new(FnCallExpr {
-                            name: op.into(),
+                            name: state.get_interned_string(op),
                             args,
                             ..Default::default()
                         }),
@@ -1330,7 +1882,7 @@ fn parse_unary(
 
                     Ok(Expr::FnCall(
                         Box::new(FnCallExpr {
-                            name: op.into(),
+                            name: state.get_interned_string(op),
                             args,
                             ..Default::default()
                         }),
@@ -1350,7 +1902,7 @@ fn parse_unary(
 
             Ok(Expr::FnCall(
                 Box::new(FnCallExpr {
-                    name: op.into(),
+                    name: state.get_interned_string(op),
                     args,
                     def_value: Some(false.into()), // NOT operator, when operating on invalid operand, defaults to false
                     ..Default::default()
@@ -1375,13 +1927,15 @@ fn make_assignment_stmt<'a>(
 ) -> Result<Stmt, ParseError> {
     fn check_lvalue(expr: &Expr, parent_is_dot: bool) -> Position {
         match expr {
-            Expr::Index(x, _) | Expr::Dot(x, _) if parent_is_dot => match x.lhs {
-                Expr::Property(_) => check_lvalue(&x.rhs, matches!(expr, Expr::Dot(_, _))),
+            // xxx?.??? = rhs, xxx?[???] = rhs
+            Expr::Index(_, true, pos) | Expr::Dot(_, true, pos) => *pos,
+            Expr::Index(x, _, _) | Expr::Dot(x, _, _) if parent_is_dot => match x.lhs {
+                Expr::Property(_) => check_lvalue(&x.rhs, matches!(expr, Expr::Dot(_, _, _))),
                 ref e => e.position(),
             },
-            Expr::Index(x, _) | Expr::Dot(x, _) => match x.lhs {
+            Expr::Index(x, _, _) | Expr::Dot(x, _, _) => match x.lhs {
                 Expr::Property(_) => unreachable!("unexpected Expr::Property in indexing"),
-                _ => check_lvalue(&x.rhs, matches!(expr, Expr::Dot(_, _))),
+                _ => check_lvalue(&x.rhs, matches!(expr, Expr::Dot(_, _, _))),
             },
             Expr::Property(_) if parent_is_dot => Position::NONE,
             Expr::Property(_) => unreachable!("unexpected Expr::Property in indexing"),
@@ -1414,9 +1968,13 @@ fn make_assignment_stmt<'a>(
                 }
             }
         }
+        // xxx?[???]... = rhs, xxx?.prop... = rhs
+        Expr::Index(_, true, pos) | Expr::Dot(_, true, pos) => {
+            Err(PERR::AssignmentToInvalidLHS("".to_string()).into_err(*pos))
+        }
         // xxx[???]... = rhs, xxx.prop... = rhs
-        Expr::Index(x, _) | Expr::Dot(x, _) => {
-            match check_lvalue(&x.rhs, matches!(lhs, Expr::Dot(_, _))) {
+        Expr::Index(x, _, _) | Expr::Dot(x, _, _) => {
+            match check_lvalue(&x.rhs, matches!(lhs, Expr::Dot(_, _, _))) {
                 Position::NONE => match &x.lhs {
                     // var[???] (non-indexed) = rhs, var.??? (non-indexed) = rhs
                     Expr::Variable(x) if x.0.is_none() => Ok(Stmt::Assignment(
@@ -1499,14 +2057,15 @@ fn make_dot_expr(
     state: &mut ParseState,
     lhs: Expr,
     rhs: Expr,
+    optional: bool,
     op_pos: Position,
 ) -> Result<Expr, ParseError> {
     Ok(match (lhs, rhs) {
         // idx_lhs[idx_expr].rhs
         // Attach dot chain to the bottom level of indexing chain
-        (Expr::Index(mut x, pos), rhs) => {
-            x.rhs = make_dot_expr(state, x.rhs, rhs, op_pos)?;
-            Expr::Index(x, pos)
+        (Expr::Index(mut x, idx_optional, pos), rhs) => {
+            x.rhs = make_dot_expr(state, x.rhs, rhs, optional, op_pos)?;
+            Expr::Index(x, idx_optional, pos)
         }
         // lhs.id
         (lhs, Expr::Variable(x)) if x.1.is_none() => {
@@ -1515,7 +2074,7 @@ fn make_dot_expr(
             let setter = state.get_interned_string(crate::engine::make_setter(&ident.name));
             let rhs = Expr::Property(Box::new((getter, setter, ident)));
 
-            Expr::Dot(Box::new(BinaryExpr { lhs, rhs }), op_pos)
+            Expr::Dot(Box::new(BinaryExpr { lhs, rhs }), optional, op_pos)
         }
         // lhs.module::id - syntax error
         (_, Expr::Variable(x)) if x.1.is_some() => {
@@ -1523,35 +2082,37 @@ fn make_dot_expr(
         }
         // lhs.prop
         (lhs, prop @ Expr::Property(_)) => {
-            Expr::Dot(Box::new(BinaryExpr { lhs, rhs: prop }), op_pos)
+            Expr::Dot(Box::new(BinaryExpr { lhs, rhs: prop }), optional, op_pos)
         }
         // lhs.dot_lhs.dot_rhs
-        (lhs, Expr::Dot(x, pos)) => {
+        (lhs, Expr::Dot(x, dot_optional, pos)) => {
             let rhs = Expr::Dot(
                 Box::new(BinaryExpr {
                     lhs: x.lhs.into_property(state),
                     rhs: x.rhs,
                 }),
+                dot_optional,
                 pos,
             );
-            Expr::Dot(Box::new(BinaryExpr { lhs, rhs }), op_pos)
+            Expr::Dot(Box::new(BinaryExpr { lhs, rhs }), optional, op_pos)
         }
         // lhs.idx_lhs[idx_rhs]
-        (lhs, Expr::Index(x, pos)) => {
+        (lhs, Expr::Index(x, idx_optional, pos)) => {
             let rhs = Expr::Index(
                 Box::new(BinaryExpr {
                     lhs: x.lhs.into_property(state),
                     rhs: x.rhs,
                 }),
+                idx_optional,
                 pos,
             );
-            Expr::Dot(Box::new(BinaryExpr { lhs, rhs }), op_pos)
+            Expr::Dot(Box::new(BinaryExpr { lhs, rhs }), optional, op_pos)
         }
         // lhs.Fn() or lhs.eval()
         (_, Expr::FnCall(x, pos))
             if x.args.len() == 0
                 && [crate::engine::KEYWORD_FN_PTR, crate::engine::KEYWORD_EVAL]
-                    .contains(&x.name.as_ref()) =>
+                    .contains(&x.name.as_str()) =>
         {
             return Err(LexError::ImproperSymbol(
                 x.name.to_string(),
@@ -1564,14 +2125,13 @@ fn make_dot_expr(
         }
         // lhs.func!(...)
         (_, Expr::FnCall(x, pos)) if x.capture => {
-            return Err(PERR::MalformedCapture(
-                "method-call style does not support capturing".into(),
-            )
-            .into_err(pos));
+            return Err(
+                PERR::CaptureNotSupported("method calls".into(), x.name.to_string()).into_err(pos),
+            );
         }
         // lhs.func(...)
         (lhs, func @ Expr::FnCall(_, _)) => {
-            Expr::Dot(Box::new(BinaryExpr { lhs, rhs: func }), op_pos)
+            Expr::Dot(Box::new(BinaryExpr { lhs, rhs: func }), optional, op_pos)
         }
         // lhs.rhs
         (_, rhs) => return Err(PERR::PropertyExpected.into_err(rhs.position())),
@@ -1711,8 +2271,24 @@ fn make_in_expr(lhs: Expr, rhs: Expr, op_pos: Position) -> Result<Expr, ParseErr
     Ok(Expr::In(Box::new(BinaryExpr { lhs, rhs }), op_pos))
 }
 
-/// Parse a binary expression.
-fn parse_binary_op(
+/// Get the precedence of a built-in operator, taking into account any override set via
+/// [`Engine::set_operator_precedence`].
+#[inline]
+fn get_operator_precedence(engine: &Engine, op: &Token) -> u8 {
+    let precedence = op.precedence();
+
+    if precedence == 0 || engine.operator_precedence.is_empty() {
+        return precedence;
+    }
+
+    engine
+        .operator_precedence
+        .get(op.syntax().as_ref())
+        .map_or(precedence, |p| p.get())
+}
+
+/// Parse a binary expression.
+fn parse_binary_op(
     input: &mut TokenStream,
     state: &mut ParseState,
     lib: &mut FunctionsLib,
@@ -1730,25 +2306,32 @@ fn parse_binary_op(
     loop {
         let (current_op, current_pos) = input.peek().unwrap();
         let precedence = match current_op {
-            Token::Custom(c) => {
-                if state
-                    .engine
-                    .custom_keywords
-                    .get(c)
-                    .map(Option::is_some)
-                    .unwrap_or(false)
-                {
-                    state.engine.custom_keywords.get(c).unwrap().unwrap().get()
-                } else {
-                    return Err(PERR::Reserved(c.clone()).into_err(*current_pos));
+            Token::Custom(c) => match state
+                .engine
+                .custom_keywords
+                .get(c)
+                .and_then(|k| k.precedence)
+            {
+                Some(precedence) => precedence.get(),
+                None => {
+                    return Err(state
+                        .symbol_error(c.clone(), PERR::Reserved)
+                        .into_err(*current_pos))
                 }
-            }
+            },
             Token::Reserved(c) if !is_valid_identifier(c.chars()) => {
-                return Err(PERR::UnknownOperator(c.into()).into_err(*current_pos))
+                return Err(state
+                    .symbol_error(c.into(), PERR::UnknownOperator)
+                    .into_err(*current_pos))
+            }
+            _ => get_operator_precedence(state.engine, current_op),
+        };
+        let bind_right = match current_op {
+            Token::Custom(c) => {
+                state.engine.custom_keywords.get(c).unwrap().associativity == Associativity::Right
             }
-            _ => current_op.precedence(),
+            _ => current_op.is_bind_right(),
         };
-        let bind_right = current_op.is_bind_right();
 
         // Bind left to the parent lhs expression if precedence is higher
         // If same precedence, then check if the operator binds right
@@ -1758,27 +2341,37 @@ fn parse_binary_op(
 
         let (op_token, pos) = input.next().unwrap();
 
+        // cond ? expr : expr
+        if op_token == Token::QuestionMark {
+            settings = settings.level_up();
+            settings.pos = pos;
+            root = parse_ternary(input, state, lib, precedence, root, settings)?;
+            continue;
+        }
+
         let rhs = parse_unary(input, state, lib, settings)?;
 
         let (next_op, next_pos) = input.peek().unwrap();
         let next_precedence = match next_op {
-            Token::Custom(c) => {
-                if state
-                    .engine
-                    .custom_keywords
-                    .get(c)
-                    .map(Option::is_some)
-                    .unwrap_or(false)
-                {
-                    state.engine.custom_keywords.get(c).unwrap().unwrap().get()
-                } else {
-                    return Err(PERR::Reserved(c.clone()).into_err(*next_pos));
+            Token::Custom(c) => match state
+                .engine
+                .custom_keywords
+                .get(c)
+                .and_then(|k| k.precedence)
+            {
+                Some(precedence) => precedence.get(),
+                None => {
+                    return Err(state
+                        .symbol_error(c.clone(), PERR::Reserved)
+                        .into_err(*next_pos))
                 }
-            }
+            },
             Token::Reserved(c) if !is_valid_identifier(c.chars()) => {
-                return Err(PERR::UnknownOperator(c.into()).into_err(*next_pos))
+                return Err(state
+                    .symbol_error(c.into(), PERR::UnknownOperator)
+                    .into_err(*next_pos))
             }
-            _ => next_op.precedence(),
+            _ => get_operator_precedence(state.engine, next_op),
         };
 
         // Bind to right if the next operator has higher precedence
@@ -1800,7 +2393,7 @@ fn parse_binary_op(
         let op = op_token.syntax();
 
         let op_base = FnCallExpr {
-            name: op,
+            name: state.get_interned_string(op.as_ref()),
             capture: false,
             ..Default::default()
         };
@@ -1868,19 +2461,46 @@ fn parse_binary_op(
                     pos,
                 )
             }
+            Token::DoubleQuestion => {
+                let rhs = args.pop().unwrap();
+                let current_lhs = args.pop().unwrap();
+                Expr::Coalesce(
+                    Box::new(BinaryExpr {
+                        lhs: current_lhs,
+                        rhs,
+                    }),
+                    pos,
+                )
+            }
             Token::In => {
                 let rhs = args.pop().unwrap();
                 let current_lhs = args.pop().unwrap();
                 make_in_expr(current_lhs, rhs, pos)?
             }
+            // lhs !in rhs - same as !(lhs in rhs)
+            Token::NotIn => {
+                let rhs = args.pop().unwrap();
+                let current_lhs = args.pop().unwrap();
+                let mut args = StaticVec::new();
+                args.push(make_in_expr(current_lhs, rhs, pos)?);
+
+                Expr::FnCall(
+                    Box::new(FnCallExpr {
+                        name: state.get_interned_string("!"),
+                        args,
+                        def_value: Some(false.into()), // NOT operator, when operating on invalid operand, defaults to false
+                        ..Default::default()
+                    }),
+                    pos,
+                )
+            }
 
             Token::Custom(s)
                 if state
                     .engine
                     .custom_keywords
                     .get(&s)
-                    .map(Option::is_some)
-                    .unwrap_or(false) =>
+                    .map_or(false, |k| k.precedence.is_some()) =>
             {
                 let hash_script = if is_valid_identifier(s.chars()) {
                     // Accept non-native functions for custom operators
@@ -1924,15 +2544,14 @@ fn parse_custom_syntax(
             // Add enough empty variable names to the stack.
             // Empty variable names act as a barrier so earlier variables will not be matched.
             // Variable searches stop at the first empty variable name.
-            state.stack.resize(
-                state.stack.len() + delta as usize,
-                ("".into(), AccessMode::ReadWrite),
-            );
+            let empty: ImmutableString = "".into();
+
+            for _ in 0..delta {
+                state.push_var(empty.clone(), AccessMode::ReadWrite, pos)?;
+            }
         }
-        delta if delta < 0 && state.stack.len() <= delta.abs() as usize => state.stack.clear(),
-        delta if delta < 0 => state
-            .stack
-            .truncate(state.stack.len() - delta.abs() as usize),
+        delta if delta < 0 && state.stack.len() <= delta.unsigned_abs() => state.rewind_stack(0),
+        delta if delta < 0 => state.rewind_stack(state.stack.len() - delta.unsigned_abs()),
         _ => (),
     }
 
@@ -1946,8 +2565,15 @@ fn parse_custom_syntax(
         settings.pos = *fwd_pos;
         let settings = settings.level_up();
 
-        let required_token = if let Some(seg) = parse_func(&segments, fwd_token.syntax().as_ref())
-            .map_err(|err| err.0.into_err(settings.pos))?
+        let context = ParseContext {
+            stack: &state.stack,
+            is_function_scope: settings.is_function_scope,
+            is_breakable: settings.is_breakable,
+        };
+
+        let required_token = if let Some(seg) =
+            parse_func(&segments, fwd_token.syntax().as_ref(), &context)
+                .map_err(|err| err.0.into_err(settings.pos))?
         {
             seg
         } else {
@@ -1964,7 +2590,7 @@ fn parse_custom_syntax(
                     keywords.push(Expr::Variable(Box::new((None, None, var_name_def))));
                 }
                 (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
-                    return Err(PERR::Reserved(s).into_err(pos));
+                    return Err(state.symbol_error(s, PERR::Reserved).into_err(pos));
                 }
                 (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
             },
@@ -1983,6 +2609,52 @@ fn parse_custom_syntax(
                 }
                 stmt => unreachable!("expecting Stmt::Block, but gets {:?}", stmt),
             },
+            s if s.starts_with(MARKER_EXPR_LIST) => {
+                let separator = &s[MARKER_EXPR_LIST.len()..];
+
+                if separator.is_empty() {
+                    return Err(LexError::ImproperSymbol(
+                        s.to_string(),
+                        format!("Missing separator for '{}' in custom syntax", s),
+                    )
+                    .into_err(settings.pos));
+                }
+
+                // The number of items is stored before the items themselves
+                let count_index = keywords.len();
+                keywords.push(Expr::IntegerConstant(0, settings.pos));
+                tokens.push(required_token.clone());
+
+                let mut count: INT = 0;
+
+                // An empty list is immediately followed by a closing symbol
+                let is_empty = matches!(
+                    input.peek().unwrap().0,
+                    Token::RightParen
+                        | Token::RightBracket
+                        | Token::RightBrace
+                        | Token::SemiColon
+                        | Token::EOF
+                );
+
+                if !is_empty {
+                    loop {
+                        keywords.push(parse_expr(input, state, lib, settings)?);
+                        tokens.push(state.get_interned_string(MARKER_EXPR));
+                        count += 1;
+
+                        if input.peek().unwrap().0.syntax().as_ref() != separator {
+                            break;
+                        }
+
+                        input.next().unwrap();
+                        tokens.push(state.get_interned_string(separator));
+                    }
+                }
+
+                keywords[count_index] = Expr::IntegerConstant(count, settings.pos);
+                segments.push(required_token.clone());
+            }
             s => match input.next().unwrap() {
                 (Token::LexError(err), pos) => return Err(err.into_err(pos)),
                 (t, _) if t.syntax().as_ref() == s => {
@@ -2010,6 +2682,62 @@ fn parse_custom_syntax(
     ))
 }
 
+/// Parse the branches of a conditional expression (i.e. `cond ? expr : expr`).
+///
+/// The expression is turned into an `if` expression.
+fn parse_ternary(
+    input: &mut TokenStream,
+    state: &mut ParseState,
+    lib: &mut FunctionsLib,
+    precedence: u8,
+    condition: Expr,
+    settings: ParseSettings,
+) -> Result<Expr, ParseError> {
+    #[cfg(not(feature = "unchecked"))]
+    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+
+    // cond ? then_expr
+    let then_expr = parse_expr(input, state, lib, settings.level_up())?;
+
+    // cond ? then_expr : ...
+    match input.next().unwrap() {
+        (Token::Colon, _) => (),
+        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+        (_, pos) => {
+            return Err(PERR::MissingToken(
+                Token::Colon.into(),
+                "to separate the branches of this conditional expression".into(),
+            )
+            .into_err(pos))
+        }
+    }
+
+    // cond ? then_expr : else_expr - binds to the right
+    let else_expr = parse_unary(input, state, lib, settings.level_up())?;
+    let else_expr = parse_binary_op(
+        input,
+        state,
+        lib,
+        precedence,
+        else_expr,
+        settings.level_up(),
+    )?;
+
+    let then_pos = then_expr.position();
+    let else_pos = else_expr.position();
+
+    let stmt = Stmt::If(
+        condition,
+        Box::new((
+            Stmt::Block(vec![Stmt::Expr(then_expr)], then_pos),
+            Some(Stmt::Block(vec![Stmt::Expr(else_expr)], else_pos)),
+        )),
+        settings.pos,
+    );
+
+    Ok(Expr::Stmt(Box::new(vec![stmt].into()), settings.pos))
+}
+
 /// Parse an expression.
 fn parse_expr(
     input: &mut TokenStream,
@@ -2127,11 +2855,91 @@ fn parse_if(
     ))
 }
 
+/// Parse a loop with a label.
+fn parse_labeled_loop(
+    input: &mut TokenStream,
+    state: &mut ParseState,
+    lib: &mut FunctionsLib,
+    settings: ParseSettings,
+) -> Result<Stmt, ParseError> {
+    #[cfg(not(feature = "unchecked"))]
+    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+
+    // 'label ...
+    let label = match input.next().unwrap() {
+        (Token::Label(s), _) => state.get_interned_string(s),
+        (t, _) => unreachable!("expecting Token::Label, but gets {:?}", t),
+    };
+
+    // 'label: ...
+    match input.next().unwrap() {
+        (Token::Colon, _) => (),
+        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+        (_, pos) => {
+            return Err(
+                PERR::MissingToken(Token::Colon.into(), "after the loop label".into())
+                    .into_err(pos),
+            )
+        }
+    }
+
+    // 'label: loop ...
+    match input.peek().unwrap() {
+        (Token::While, _) | (Token::Loop, _) => {
+            parse_while_loop(input, state, lib, Some(label), settings.level_up())
+        }
+        (Token::Do, _) => parse_do(input, state, lib, Some(label), settings.level_up()),
+        (Token::For, _) => parse_for(input, state, lib, Some(label), settings.level_up()),
+        (_, pos) => Err(PERR::ExprExpected("loop".into()).into_err(*pos)),
+    }
+}
+
+/// Parse the optional label after `break` or `continue`, which must be that of an enclosing loop.
+fn parse_loop_target(
+    input: &mut TokenStream,
+    state: &mut ParseState,
+) -> Result<Option<ImmutableString>, ParseError> {
+    match input.peek().unwrap() {
+        (Token::Label(_), _) => match input.next().unwrap() {
+            (Token::Label(s), pos) => {
+                if !state.labels.iter().any(|label| label == &s) {
+                    return Err(PERR::UndefinedLabel(s).into_err(pos));
+                }
+                Ok(Some(state.get_interned_string(s)))
+            }
+            (t, _) => unreachable!("expecting Token::Label, but gets {:?}", t),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// Parse the body of a loop, making its label (if any) available to `break` and `continue`.
+fn parse_loop_body(
+    input: &mut TokenStream,
+    state: &mut ParseState,
+    lib: &mut FunctionsLib,
+    label: &Option<ImmutableString>,
+    settings: ParseSettings,
+) -> Result<Stmt, ParseError> {
+    let prev_labels_len = state.labels.len();
+
+    if let Some(label) = label {
+        state.labels.push(label.clone());
+    }
+
+    let body = parse_block(input, state, lib, settings)?;
+
+    state.labels.truncate(prev_labels_len);
+
+    Ok(body)
+}
+
 /// Parse a while loop.
 fn parse_while_loop(
     input: &mut TokenStream,
     state: &mut ParseState,
     lib: &mut FunctionsLib,
+    label: Option<ImmutableString>,
     mut settings: ParseSettings,
 ) -> Result<Stmt, ParseError> {
     #[cfg(not(feature = "unchecked"))]
@@ -2150,9 +2958,9 @@ fn parse_while_loop(
 
     ensure_not_assignment(input)?;
     settings.is_breakable = true;
-    let body = Box::new(parse_block(input, state, lib, settings.level_up())?);
+    let body = parse_loop_body(input, state, lib, &label, settings.level_up())?;
 
-    Ok(Stmt::While(guard, body, settings.pos))
+    Ok(Stmt::While(guard, Box::new((body, label)), settings.pos))
 }
 
 /// Parse a do loop.
@@ -2160,6 +2968,7 @@ fn parse_do(
     input: &mut TokenStream,
     state: &mut ParseState,
     lib: &mut FunctionsLib,
+    label: Option<ImmutableString>,
     mut settings: ParseSettings,
 ) -> Result<Stmt, ParseError> {
     #[cfg(not(feature = "unchecked"))]
@@ -2170,7 +2979,7 @@ fn parse_do(
 
     // do { body } [while|until] guard
     settings.is_breakable = true;
-    let body = Box::new(parse_block(input, state, lib, settings.level_up())?);
+    let body = parse_loop_body(input, state, lib, &label, settings.level_up())?;
 
     let is_while = match input.next().unwrap() {
         (Token::While, _) => true,
@@ -2188,7 +2997,12 @@ fn parse_do(
     let guard = parse_expr(input, state, lib, settings.level_up())?;
     ensure_not_assignment(input)?;
 
-    Ok(Stmt::Do(body, guard, is_while, settings.pos))
+    Ok(Stmt::Do(
+        Box::new((body, label)),
+        guard,
+        is_while,
+        settings.pos,
+    ))
 }
 
 /// Parse a for loop.
@@ -2196,6 +3010,7 @@ fn parse_for(
     input: &mut TokenStream,
     state: &mut ParseState,
     lib: &mut FunctionsLib,
+    label: Option<ImmutableString>,
     mut settings: ParseSettings,
 ) -> Result<Stmt, ParseError> {
     #[cfg(not(feature = "unchecked"))]
@@ -2204,20 +3019,55 @@ fn parse_for(
     // for ...
     settings.pos = eat_token(input, Token::For);
 
-    // for name ...
-    let name = match input.next().unwrap() {
-        // Variable name
-        (Token::Identifier(s), _) => s,
-        // Reserved keyword
-        (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
-            return Err(PERR::Reserved(s).into_err(pos));
+    // for name ... | for (counter, name) ...
+    let (counter, (name, name_pos)) = if match_token(input, Token::LeftParen).0 {
+        // for (counter ...
+        let (counter, counter_pos) = parse_for_var(input, state)?;
+
+        // for (counter, ...
+        match input.next().unwrap() {
+            (Token::Comma, _) => (),
+            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+            (_, pos) => {
+                return Err(PERR::MissingToken(
+                    Token::Comma.into(),
+                    "after the counter variable".into(),
+                )
+                .into_err(pos))
+            }
         }
-        // Bad identifier
-        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
-        // Not a variable name
-        (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
+
+        // for (counter, name ...
+        let (name, name_pos) = parse_for_var(input, state)?;
+
+        if name == counter {
+            return Err(PERR::FnDuplicatedParam("".to_string(), name).into_err(name_pos));
+        }
+
+        // for (counter, name) ...
+        match input.next().unwrap() {
+            (Token::RightParen, _) => (),
+            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+            (_, pos) => {
+                return Err(PERR::MissingToken(
+                    Token::RightParen.into(),
+                    "to close the list of iteration variables".into(),
+                )
+                .into_err(pos))
+            }
+        }
+
+        #[cfg(not(feature = "no_module"))]
+        state.ensure_not_module_alias(&counter, counter_pos)?;
+
+        (Some((counter, counter_pos)), (name, name_pos))
+    } else {
+        (None, parse_for_var(input, state)?)
     };
 
+    #[cfg(not(feature = "no_module"))]
+    state.ensure_not_module_alias(&name, name_pos)?;
+
     // for name in ...
     match input.next().unwrap() {
         (Token::In, _) => (),
@@ -2234,16 +3084,49 @@ fn parse_for(
     ensure_not_statement_expr(input, "a boolean")?;
     let expr = parse_expr(input, state, lib, settings.level_up())?;
 
-    let loop_var = state.get_interned_string(name.clone());
     let prev_stack_len = state.stack.len();
-    state.stack.push((loop_var, AccessMode::ReadWrite));
+
+    let counter = match counter {
+        Some((counter, counter_pos)) => {
+            let counter_var = state.get_interned_string(counter.clone());
+            state.push_var(counter_var, AccessMode::ReadWrite, counter_pos)?;
+            Some(counter)
+        }
+        None => None,
+    };
+
+    let loop_var = state.get_interned_string(name.clone());
+    state.push_var(loop_var, AccessMode::ReadWrite, name_pos)?;
 
     settings.is_breakable = true;
-    let body = parse_block(input, state, lib, settings.level_up())?;
+    let body = parse_loop_body(input, state, lib, &label, settings.level_up())?;
+
+    state.rewind_stack(prev_stack_len);
 
-    state.stack.truncate(prev_stack_len);
+    Ok(Stmt::For(
+        expr,
+        Box::new((counter, name, body, label)),
+        settings.pos,
+    ))
+}
 
-    Ok(Stmt::For(expr, Box::new((name, body)), settings.pos))
+/// Parse the name of an iteration variable in a `for` statement.
+fn parse_for_var(
+    input: &mut TokenStream,
+    state: &ParseState,
+) -> Result<(String, Position), ParseError> {
+    match input.next().unwrap() {
+        // Variable name
+        (Token::Identifier(s), pos) => Ok((s, pos)),
+        // Reserved keyword
+        (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
+            Err(state.symbol_error(s, PERR::Reserved).into_err(pos))
+        }
+        // Bad identifier
+        (Token::LexError(err), pos) => Err(err.into_err(pos)),
+        // Not a variable name
+        (_, pos) => Err(PERR::VariableExpected.into_err(pos)),
+    }
 }
 
 /// Parse a variable definition statement.
@@ -2261,16 +3144,32 @@ fn parse_let(
     // let/const... (specified in `var_type`)
     settings.pos = input.next().unwrap().1;
 
+    // let [ ... ] = expr
+    #[cfg(not(feature = "no_index"))]
+    if input.peek().unwrap().0 == Token::LeftBracket {
+        return parse_let_pattern(input, state, lib, var_type, export, settings);
+    }
+    // let #{ ... } = expr
+    #[cfg(not(feature = "no_object"))]
+    if input.peek().unwrap().0 == Token::MapStart {
+        return parse_let_pattern(input, state, lib, var_type, export, settings);
+    }
+
     // let name ...
     let (name, pos) = match input.next().unwrap() {
         (Token::Identifier(s), pos) => (s, pos),
         (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
-            return Err(PERR::Reserved(s).into_err(pos));
+            return Err(state.symbol_error(s, PERR::Reserved).into_err(pos));
         }
         (Token::LexError(err), pos) => return Err(err.into_err(pos)),
         (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
     };
 
+    #[cfg(not(feature = "no_module"))]
+    state.ensure_not_module_alias(&name, pos)?;
+
+    state.ensure_not_declared_in_block(&name, pos)?;
+
     // let name = ...
     let expr = if match_token(input, Token::Equals).0 {
         // let name = expr
@@ -2283,20 +3182,146 @@ fn parse_let(
         // let name = expr
         AccessMode::ReadWrite => {
             let name = state.get_interned_string(name);
-            state.stack.push((name.clone(), AccessMode::ReadWrite));
+            state.push_var(name.clone(), AccessMode::ReadWrite, pos)?;
             let var_def = Ident { name, pos };
             Ok(Stmt::Let(Box::new(var_def), expr, export, settings.pos))
         }
         // const name = { expr:constant }
         AccessMode::ReadOnly => {
             let name = state.get_interned_string(name);
-            state.stack.push((name.clone(), AccessMode::ReadOnly));
+            state.push_var(name.clone(), AccessMode::ReadOnly, pos)?;
             let var_def = Ident { name, pos };
             Ok(Stmt::Const(Box::new(var_def), expr, export, settings.pos))
         }
     }
 }
 
+/// Parse a destructuring pattern in a variable definition statement.
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+fn parse_let_pattern(
+    input: &mut TokenStream,
+    state: &mut ParseState,
+    lib: &mut FunctionsLib,
+    var_type: AccessMode,
+    export: bool,
+    settings: ParseSettings,
+) -> Result<Stmt, ParseError> {
+    // [ ... or #{ ...
+    let (end_token, missing_end) = match input.next().unwrap().0 {
+        Token::LeftBracket => (Token::RightBracket, "to end this array pattern"),
+        Token::MapStart => (Token::RightBrace, "to end this object map pattern"),
+        t => unreachable!("expecting '[' or '#{{', but gets {:?}", t),
+    };
+
+    let mut vars: StaticVec<Ident> = Default::default();
+    let mut rest: Option<Ident> = None;
+
+    loop {
+        match input.peek().unwrap() {
+            (t, _) if *t == end_token => {
+                input.next().unwrap();
+                break;
+            }
+            (Token::EOF, pos) => {
+                return Err(PERR::MissingToken(end_token.into(), missing_end.into()).into_err(*pos))
+            }
+            _ => (),
+        }
+
+        // ..rest
+        let is_rest = match input.peek().unwrap() {
+            (Token::DoublePeriod, _) => {
+                eat_token(input, Token::DoublePeriod);
+                true
+            }
+            _ => false,
+        };
+
+        let (name, pos) = match input.next().unwrap() {
+            (Token::Identifier(s), pos) => (s, pos),
+            (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
+                return Err(state.symbol_error(s, PERR::Reserved).into_err(pos));
+            }
+            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+            (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
+        };
+
+        if vars.iter().chain(rest.iter()).any(|v| v.name == name) {
+            return Err(PERR::DuplicatedVariable(name).into_err(pos));
+        }
+
+        #[cfg(not(feature = "no_module"))]
+        state.ensure_not_module_alias(&name, pos)?;
+
+        state.ensure_not_declared_in_block(&name, pos)?;
+
+        let var_def = Ident {
+            name: state.get_interned_string(name),
+            pos,
+        };
+
+        if is_rest {
+            rest = Some(var_def);
+        } else {
+            vars.push(var_def);
+        }
+
+        match input.peek().unwrap() {
+            // The rest variable must be the last one
+            (t, _) if rest.is_some() && *t != end_token => {
+                return Err(PERR::MissingToken(end_token.into(), missing_end.into())
+                    .into_err(input.peek().unwrap().1))
+            }
+            (Token::Comma, _) => {
+                eat_token(input, Token::Comma);
+            }
+            (t, _) if *t == end_token => (),
+            (Token::LexError(err), pos) => return Err(err.clone().into_err(*pos)),
+            (_, pos) => {
+                return Err(PERR::MissingToken(
+                    Token::Comma.into(),
+                    "to separate the variables of this pattern".into(),
+                )
+                .into_err(*pos))
+            }
+        }
+    }
+
+    // let pattern = expr
+    match input.next().unwrap() {
+        (Token::Equals, _) => (),
+        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+        (_, pos) => {
+            return Err(PERR::MissingToken(
+                Token::Equals.into(),
+                "to assign the value to destructure".into(),
+            )
+            .into_err(pos))
+        }
+    }
+
+    let expr = parse_expr(input, state, lib, settings.level_up())?;
+
+    let pattern = match end_token {
+        #[cfg(not(feature = "no_index"))]
+        Token::RightBracket => Pattern::Array(vars, rest),
+        #[cfg(not(feature = "no_object"))]
+        Token::RightBrace => Pattern::Map(vars, rest),
+        t => unreachable!("expecting ']' or '}}', but gets {:?}", t),
+    };
+
+    for var_def in pattern.iter_vars() {
+        state.push_var(var_def.name.clone(), var_type, var_def.pos)?;
+    }
+
+    Ok(Stmt::LetPattern(
+        Box::new((pattern, expr)),
+        var_type,
+        export,
+        settings.pos,
+    ))
+}
+
 /// Parse an import statement.
 #[cfg(not(feature = "no_module"))]
 fn parse_import(
@@ -2323,12 +3348,16 @@ fn parse_import(
     let (name, name_pos) = match input.next().unwrap() {
         (Token::Identifier(s), pos) => (s, pos),
         (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
-            return Err(PERR::Reserved(s).into_err(pos));
+            return Err(state.symbol_error(s, PERR::Reserved).into_err(pos));
         }
         (Token::LexError(err), pos) => return Err(err.into_err(pos)),
         (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
     };
 
+    if state.strict_module_aliases && state.find_var_pos(&name).is_some() {
+        return Err(PERR::ModuleAliasConflict(name).into_err(name_pos));
+    }
+
     let name = state.get_interned_string(name);
     state.modules.push(name.clone());
 
@@ -2349,13 +3378,37 @@ fn parse_export(
     state: &mut ParseState,
     lib: &mut FunctionsLib,
     mut settings: ParseSettings,
+    _comments: Vec<String>,
 ) -> Result<Stmt, ParseError> {
     #[cfg(not(feature = "unchecked"))]
     settings.ensure_level_within_max_limit(state.max_expr_depth)?;
 
     settings.pos = eat_token(input, Token::Export);
 
+    // Doc-comments can only precede `export fn`
+    #[cfg(not(feature = "no_function"))]
+    if !_comments.is_empty() && !matches!(input.peek().unwrap().0, Token::Fn) {
+        return Err(PERR::WrongDocComment.into_err(settings.pos));
+    }
+
     match input.peek().unwrap() {
+        // export fn ...
+        #[cfg(not(feature = "no_function"))]
+        (Token::Fn, pos) => {
+            let pos = *pos;
+            eat_token(input, Token::Fn);
+
+            let mut func = parse_global_fn(input, state, lib, FnAccess::Public, pos, _comments)?;
+            func.exported = true;
+
+            lib.insert(
+                // Qualifiers (none) + function name + number of arguments.
+                calc_script_fn_hash(empty(), &func.name, func.num_fixed_params()).unwrap(),
+                func,
+            );
+
+            return Ok(Stmt::Noop(settings.pos));
+        }
         (Token::Let, pos) => {
             let pos = *pos;
             let mut stmt = parse_let(input, state, lib, AccessMode::ReadWrite, true, settings)?;
@@ -2377,7 +3430,7 @@ fn parse_export(
         let (id, id_pos) = match input.next().unwrap() {
             (Token::Identifier(s), pos) => (s.clone(), pos),
             (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
-                return Err(PERR::Reserved(s).into_err(pos));
+                return Err(state.symbol_error(s, PERR::Reserved).into_err(pos));
             }
             (Token::LexError(err), pos) => return Err(err.into_err(pos)),
             (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
@@ -2390,7 +3443,7 @@ fn parse_export(
                     pos,
                 }),
                 (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
-                    return Err(PERR::Reserved(s).into_err(pos));
+                    return Err(state.symbol_error(s, PERR::Reserved).into_err(pos));
                 }
                 (Token::LexError(err), pos) => return Err(err.into_err(pos)),
                 (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
@@ -2457,6 +3510,15 @@ fn parse_block(
     let prev_mods_len = state.modules.len();
 
     while !match_token(input, Token::RightBrace).0 {
+        // { ... <EOF>
+        if let (Token::EOF, pos) = input.peek().unwrap() {
+            return Err(PERR::MissingToken(
+                Token::RightBrace.into(),
+                "to terminate this block".into(),
+            )
+            .into_err(*pos));
+        }
+
         // Parse statements inside the block
         settings.is_global = false;
 
@@ -2499,7 +3561,7 @@ fn parse_block(
         }
     }
 
-    state.stack.truncate(state.entry_stack_len);
+    state.rewind_stack(state.entry_stack_len);
     state.entry_stack_len = prev_entry_stack_len;
 
     #[cfg(not(feature = "no_module"))]
@@ -2560,6 +3622,8 @@ fn parse_stmt(
 
                     match input.peek().unwrap() {
                         (Token::Fn, _) | (Token::Private, _) => break,
+                        #[cfg(not(feature = "no_module"))]
+                        (Token::Export, _) => break,
                         (Token::Comment(_), _) => (),
                         _ => return Err(PERR::WrongDocComment.into_err(comments_pos)),
                     }
@@ -2578,9 +3642,18 @@ fn parse_stmt(
     #[cfg(not(feature = "unchecked"))]
     settings.ensure_level_within_max_limit(state.max_expr_depth)?;
 
+    // Without statement-expressions, blocks (e.g. bodies of if-expressions) hold only expressions
+    if !settings.allow_stmt_expr && *token != Token::LeftBrace {
+        let expr = parse_expr(input, state, lib, settings.level_up())?;
+        return Ok(Stmt::Expr(expr));
+    }
+
     match token {
         // ; - empty statement
-        Token::SemiColon => Ok(Stmt::Noop(settings.pos)),
+        Token::SemiColon => {
+            eat_token(input, Token::SemiColon);
+            Ok(Stmt::Noop(settings.pos))
+        }
 
         // { - statements block
         Token::LeftBrace => Ok(parse_block(input, state, lib, settings.level_up())?),
@@ -2600,32 +3673,11 @@ fn parse_stmt(
 
             match input.next().unwrap() {
                 (Token::Fn, pos) => {
-                    let mut new_state = ParseState::new(
-                        state.engine,
-                        state.script_hash,
-                        #[cfg(not(feature = "unchecked"))]
-                        state.max_function_expr_depth,
-                        #[cfg(not(feature = "unchecked"))]
-                        state.max_function_expr_depth,
-                    );
-
-                    let settings = ParseSettings {
-                        allow_if_expr: true,
-                        allow_switch_expr: true,
-                        allow_stmt_expr: true,
-                        allow_anonymous_fn: true,
-                        is_global: false,
-                        is_function_scope: true,
-                        is_breakable: false,
-                        level: 0,
-                        pos: pos,
-                    };
-
-                    let func = parse_fn(input, &mut new_state, lib, access, settings, _comments)?;
+                    let func = parse_global_fn(input, state, lib, access, pos, _comments)?;
 
                     lib.insert(
                         // Qualifiers (none) + function name + number of arguments.
-                        calc_script_fn_hash(empty(), &func.name, func.params.len()).unwrap(),
+                        calc_script_fn_hash(empty(), &func.name, func.num_fixed_params()).unwrap(),
                         func,
                     );
 
@@ -2642,17 +3694,34 @@ fn parse_stmt(
 
         Token::If => parse_if(input, state, lib, settings.level_up()),
         Token::Switch => parse_switch(input, state, lib, settings.level_up()),
-        Token::While | Token::Loop => parse_while_loop(input, state, lib, settings.level_up()),
-        Token::Do => parse_do(input, state, lib, settings.level_up()),
-        Token::For => parse_for(input, state, lib, settings.level_up()),
+        Token::While | Token::Loop => {
+            parse_while_loop(input, state, lib, None, settings.level_up())
+        }
+        Token::Do => parse_do(input, state, lib, None, settings.level_up()),
+        Token::For => parse_for(input, state, lib, None, settings.level_up()),
+        Token::Label(_) => parse_labeled_loop(input, state, lib, settings.level_up()),
 
         Token::Continue if settings.is_breakable => {
             let pos = eat_token(input, Token::Continue);
-            Ok(Stmt::Continue(pos))
+            let label = parse_loop_target(input, state)?;
+            Ok(Stmt::Continue(label, pos))
         }
         Token::Break if settings.is_breakable => {
             let pos = eat_token(input, Token::Break);
-            Ok(Stmt::Break(pos))
+            let label = parse_loop_target(input, state)?;
+
+            match input.peek().unwrap() {
+                // `break` at <EOF>, `break;`, `break }` or `break,`
+                (Token::EOF, _)
+                | (Token::SemiColon, _)
+                | (Token::RightBrace, _)
+                | (Token::Comma, _) => Ok(Stmt::Break(label, None, pos)),
+                // `break` with value
+                (_, _) => {
+                    let expr = parse_expr(input, state, lib, settings.level_up())?;
+                    Ok(Stmt::Break(label, Some(expr), pos))
+                }
+            }
         }
         Token::Continue | Token::Break => Err(PERR::LoopBreak.into_err(settings.pos)),
 
@@ -2702,12 +3771,28 @@ fn parse_stmt(
         Token::Export if !settings.is_global => Err(PERR::WrongExport.into_err(settings.pos)),
 
         #[cfg(not(feature = "no_module"))]
-        Token::Export => parse_export(input, state, lib, settings.level_up()),
+        Token::Export => parse_export(input, state, lib, settings.level_up(), _comments),
 
         _ => parse_expr_stmt(input, state, lib, settings.level_up()),
     }
 }
 
+/// Parse an error variable of a `catch` clause.
+fn parse_catch_var(input: &mut TokenStream, state: &mut ParseState) -> Result<Ident, ParseError> {
+    let id = match input.next().unwrap() {
+        (Token::Identifier(s), pos) => Ident {
+            name: state.get_interned_string(s),
+            pos,
+        },
+        (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
+    };
+
+    #[cfg(not(feature = "no_module"))]
+    state.ensure_not_module_alias(&id.name, id.pos)?;
+
+    Ok(id)
+}
+
 /// Parse a try/catch statement.
 fn parse_try_catch(
     input: &mut TokenStream,
@@ -2734,41 +3819,118 @@ fn parse_try_catch(
         );
     }
 
-    // try { body } catch (
-    let var_def = if match_token(input, Token::LeftParen).0 {
-        let id = match input.next().unwrap() {
-            (Token::Identifier(s), pos) => Ident {
-                name: state.get_interned_string(s),
-                pos,
-            },
-            (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
+    let mut clauses = StaticVec::new();
+
+    loop {
+        // try { body } catch (
+        let var_def = if match_token(input, Token::LeftParen).0 {
+            Some(parse_catch_var(input, state)?)
+        } else {
+            None
+        };
+
+        // try { body } catch ( var, pos_var )
+        #[cfg(not(feature = "no_object"))]
+        let pos_var_def = if var_def.is_some() && match_token(input, Token::Comma).0 {
+            Some(parse_catch_var(input, state)?)
+        } else {
+            None
         };
+        #[cfg(feature = "no_object")]
+        let pos_var_def = None;
 
-        let (matched, pos) = match_token(input, Token::RightParen);
+        // try { body } catch ( var ) { catch_block }
+        let prev_stack_len = state.stack.len();
 
-        if !matched {
-            return Err(PERR::MissingToken(
-                Token::RightParen.into(),
-                "to enclose the catch variable".into(),
-            )
-            .into_err(pos));
+        for Ident { name, pos } in var_def.iter().chain(pos_var_def.iter()) {
+            state.push_var(name.clone(), AccessMode::ReadWrite, *pos)?;
         }
 
-        Some(id)
-    } else {
-        None
-    };
+        // try { body } catch ( var if guard )
+        let guard = if var_def.is_some() && match_token(input, Token::If).0 {
+            Some(parse_expr(input, state, lib, settings.level_up())?)
+        } else {
+            None
+        };
+
+        if var_def.is_some() {
+            let (matched, pos) = match_token(input, Token::RightParen);
+
+            if !matched {
+                return Err(PERR::MissingToken(
+                    Token::RightParen.into(),
+                    "to enclose the catch variable".into(),
+                )
+                .into_err(pos));
+            }
+        }
+
+        let catch_body = parse_block(input, state, lib, settings.level_up())?;
 
-    // try { body } catch ( var ) { catch_block }
-    let catch_body = parse_block(input, state, lib, settings.level_up())?;
+        // The catch variable is only visible inside the catch clause
+        state.rewind_stack(prev_stack_len);
+
+        let catch_all = guard.is_none();
+
+        clauses.push((var_def, pos_var_def, guard, catch_body));
+
+        // A clause without guard catches everything, so it must be the last one
+        if catch_all || !match_token(input, Token::Catch).0 {
+            break;
+        }
+    }
 
     Ok(Stmt::TryCatch(
-        Box::new((body, var_def, catch_body)),
+        Box::new((body, clauses)),
         settings.pos,
         catch_pos,
     ))
 }
 
+/// Parse a function definition at global level, after the `fn` keyword.
+#[cfg(not(feature = "no_function"))]
+fn parse_global_fn(
+    input: &mut TokenStream,
+    state: &mut ParseState,
+    lib: &mut FunctionsLib,
+    access: FnAccess,
+    pos: Position,
+    comments: Vec<String>,
+) -> Result<ScriptFnDef, ParseError> {
+    let mut new_state = ParseState::new(
+        state.engine,
+        state.script_hash,
+        #[cfg(not(feature = "unchecked"))]
+        state.max_function_expr_depth,
+        #[cfg(not(feature = "unchecked"))]
+        state.max_function_expr_depth,
+    );
+
+    #[cfg(not(feature = "no_module"))]
+    {
+        new_state.strict_module_aliases = state.strict_module_aliases;
+    }
+    new_state.strict_variables = state.strict_variables;
+
+    let settings = ParseSettings {
+        allow_if_expr: true,
+        allow_switch_expr: true,
+        allow_try_expr: true,
+        allow_loop_expr: true,
+        allow_stmt_expr: true,
+        allow_anonymous_fn: true,
+        is_property: false,
+        is_global: false,
+        is_function_scope: true,
+        is_this_bound: false,
+        is_breakable: false,
+        level: 0,
+        pos,
+    };
+
+    parse_fn(input, &mut new_state, lib, access, settings, comments)
+}
+
 /// Parse a function definition.
 #[cfg(not(feature = "no_function"))]
 fn parse_fn(
@@ -2787,7 +3949,7 @@ fn parse_fn(
     let name = token
         .into_function_name_for_override()
         .map_err(|t| match t {
-            Token::Reserved(s) => PERR::Reserved(s).into_err(pos),
+            Token::Reserved(s) => state.symbol_error(s, PERR::Reserved).into_err(pos),
             _ => PERR::FnMissingName.into_err(pos),
         })?;
 
@@ -2796,20 +3958,37 @@ fn parse_fn(
         (_, pos) => return Err(PERR::FnMissingParams(name).into_err(*pos)),
     };
 
-    let mut params: StaticVec<_> = Default::default();
+    let mut params: StaticVec<(ImmutableString, Position)> = Default::default();
+    let mut defaults: StaticVec<_> = Default::default();
+    let mut variadic = false;
 
     if !match_token(input, Token::RightParen).0 {
         let sep_err = format!("to separate the parameters of function '{}'", name);
 
         loop {
+            let is_rest = parse_rest_marker(input);
+
             match input.next().unwrap() {
-                (Token::RightParen, _) => break,
+                (Token::RightParen, _) if !is_rest => break,
                 (Token::Identifier(s), pos) => {
+                    if variadic {
+                        let (rest, rest_pos) = params.last().unwrap();
+                        return Err(
+                            PERR::FnMisplacedRestParam(name, rest.to_string()).into_err(*rest_pos)
+                        );
+                    }
                     if params.iter().any(|(p, _)| p == &s) {
                         return Err(PERR::FnDuplicatedParam(name, s).into_err(pos));
                     }
+                    if is_rest {
+                        variadic = true;
+                    } else if let Some(value) = parse_param_default(input, state, lib, settings)? {
+                        defaults.push(value);
+                    } else if !defaults.is_empty() {
+                        return Err(PERR::FnMissingDefault(name, s).into_err(pos));
+                    }
                     let s = state.get_interned_string(s);
-                    state.stack.push((s.clone(), AccessMode::ReadWrite));
+                    state.push_var(s.clone(), AccessMode::ReadWrite, pos)?;
                     params.push((s, pos))
                 }
                 (Token::LexError(err), pos) => return Err(err.into_err(pos)),
@@ -2856,7 +4035,11 @@ fn parse_fn(
     Ok(ScriptFnDef {
         name: name.into(),
         access,
+        #[cfg(not(feature = "no_module"))]
+        exported: false,
         params,
+        defaults,
+        variadic,
         #[cfg(not(feature = "no_closure"))]
         externals,
         body,
@@ -2867,9 +4050,50 @@ fn parse_fn(
     })
 }
 
+/// Parse the `...` marker of a rest parameter, if any.
+#[cfg(not(feature = "no_function"))]
+fn parse_rest_marker(input: &mut TokenStream) -> bool {
+    match input.peek().unwrap() {
+        // Rest parameters collect the remaining arguments into an array
+        #[cfg(not(feature = "no_index"))]
+        (Token::TriplePeriod, _) => {
+            eat_token(input, Token::TriplePeriod);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Parse the default value of a function parameter, if any: `=` followed by a constant.
+#[cfg(not(feature = "no_function"))]
+fn parse_param_default(
+    input: &mut TokenStream,
+    state: &mut ParseState,
+    lib: &mut FunctionsLib,
+    settings: ParseSettings,
+) -> Result<Option<Dynamic>, ParseError> {
+    if !match_token(input, Token::Equals).0 {
+        return Ok(None);
+    }
+
+    // Only a unary expression, so that the '|' ending the parameters of a closure is not taken
+    // as an operator
+    let expr = parse_unary(input, state, lib, settings.level_up())?;
+
+    match expr.get_constant_value() {
+        Some(value) => Ok(Some(value)),
+        None => Err(PERR::ExprExpected("a constant".into()).into_err(expr.position())),
+    }
+}
+
 /// Creates a curried expression from a list of external variables
 #[cfg(not(feature = "no_function"))]
-fn make_curry_from_externals(fn_expr: Expr, externals: StaticVec<Ident>, pos: Position) -> Expr {
+fn make_curry_from_externals(
+    state: &mut ParseState,
+    fn_expr: Expr,
+    externals: StaticVec<Ident>,
+    pos: Position,
+) -> Expr {
     if externals.is_empty() {
         return fn_expr;
     }
@@ -2895,7 +4119,7 @@ fn make_curry_from_externals(fn_expr: Expr, externals: StaticVec<Ident>, pos: Po
 
     let expr = Expr::FnCall(
         Box::new(FnCallExpr {
-            name: curry_func.into(),
+            name: state.get_interned_string(curry_func),
             hash_script,
             args,
             ..Default::default()
@@ -2931,19 +4155,40 @@ fn parse_anon_fn(
     #[cfg(not(feature = "unchecked"))]
     settings.ensure_level_within_max_limit(state.max_expr_depth)?;
 
-    let mut params: StaticVec<_> = Default::default();
+    let mut params: StaticVec<(ImmutableString, Position)> = Default::default();
+    let mut defaults: StaticVec<_> = Default::default();
+    let mut variadic = false;
 
     if input.next().unwrap().0 != Token::Or {
         if !match_token(input, Token::Pipe).0 {
             loop {
+                let is_rest = parse_rest_marker(input);
+
                 match input.next().unwrap() {
-                    (Token::Pipe, _) => break,
+                    (Token::Pipe, _) if !is_rest => break,
                     (Token::Identifier(s), pos) => {
+                        if variadic {
+                            let (rest, rest_pos) = params.last().unwrap();
+                            return Err(PERR::FnMisplacedRestParam(
+                                "".to_string(),
+                                rest.to_string(),
+                            )
+                            .into_err(*rest_pos));
+                        }
                         if params.iter().any(|(p, _)| p == &s) {
                             return Err(PERR::FnDuplicatedParam("".to_string(), s).into_err(pos));
                         }
+                        if is_rest {
+                            variadic = true;
+                        } else if let Some(value) =
+                            parse_param_default(input, state, lib, settings)?
+                        {
+                            defaults.push(value);
+                        } else if !defaults.is_empty() {
+                            return Err(PERR::FnMissingDefault("".to_string(), s).into_err(pos));
+                        }
                         let s = state.get_interned_string(s);
-                        state.stack.push((s.clone(), AccessMode::ReadWrite));
+                        state.push_var(s.clone(), AccessMode::ReadWrite, pos)?;
                         params.push((s, pos))
                     }
                     (Token::LexError(err), pos) => return Err(err.into_err(pos)),
@@ -3016,7 +4261,11 @@ fn parse_anon_fn(
     let script = ScriptFnDef {
         name: fn_name.clone(),
         access: FnAccess::Public,
+        #[cfg(not(feature = "no_module"))]
+        exported: false,
         params,
+        defaults,
+        variadic,
         #[cfg(not(feature = "no_closure"))]
         externals: Default::default(),
         body,
@@ -3029,7 +4278,7 @@ fn parse_anon_fn(
     let expr = Expr::FnPointer(fn_name, settings.pos);
 
     let expr = if cfg!(not(feature = "no_closure")) {
-        make_curry_from_externals(expr, externals, settings.pos)
+        make_curry_from_externals(state, expr, externals, settings.pos)
     } else {
         expr
     };
@@ -3044,6 +4293,7 @@ impl Engine {
         input: &mut TokenStream,
         scope: &Scope,
         optimization_level: OptimizationLevel,
+        options: &EvalOptions,
     ) -> Result<AST, ParseError> {
         let mut functions = Default::default();
         let mut state = ParseState::new(
@@ -3055,14 +4305,21 @@ impl Engine {
             #[cfg(not(feature = "no_function"))]
             self.max_function_expr_depth(),
         );
+        if state.strict_variables {
+            state.scope_vars = scope.iter_raw().map(|(n, _, _)| n.into()).collect();
+        }
 
         let settings = ParseSettings {
-            allow_if_expr: false,
-            allow_switch_expr: false,
-            allow_stmt_expr: false,
+            allow_if_expr: options.allow_if_expression,
+            allow_switch_expr: options.allow_switch_expression,
+            allow_try_expr: false,
+            allow_loop_expr: false,
+            allow_stmt_expr: options.allow_statement_expression,
             allow_anonymous_fn: false,
+            is_property: false,
             is_global: true,
             is_function_scope: false,
+            is_this_bound: options.bind_this,
             is_breakable: false,
             level: 0,
             pos: Position::NONE,
@@ -3092,6 +4349,8 @@ impl Engine {
         &self,
         script_hash: u64,
         input: &mut TokenStream,
+        scope: &Scope,
+        options: &EvalOptions,
     ) -> Result<(Vec<Stmt>, Vec<ScriptFnDef>), ParseError> {
         let mut statements = Vec::with_capacity(16);
         let mut functions = HashMap::with_capacity_and_hasher(16, StraightHasherBuilder);
@@ -3104,15 +4363,27 @@ impl Engine {
             #[cfg(not(feature = "no_function"))]
             self.max_function_expr_depth(),
         );
+        if state.strict_variables {
+            state.scope_vars = scope.iter_raw().map(|(n, _, _)| n.into()).collect();
+        }
+
+        #[cfg(not(feature = "no_module"))]
+        if let Some(strict) = options.strict_module_aliases {
+            state.strict_module_aliases = strict;
+        }
 
         while !input.peek().unwrap().0.is_eof() {
             let settings = ParseSettings {
                 allow_if_expr: true,
                 allow_switch_expr: true,
+                allow_try_expr: true,
+                allow_loop_expr: true,
                 allow_stmt_expr: true,
                 allow_anonymous_fn: true,
+                is_property: false,
                 is_global: true,
                 is_function_scope: false,
+                is_this_bound: options.bind_this,
                 is_breakable: false,
                 level: 0,
                 pos: Position::NONE,
@@ -3153,6 +4424,22 @@ impl Engine {
             }
         }
 
+        // Functions listed in `export` statements (without renaming) are exported
+        #[cfg(not(feature = "no_module"))]
+        #[cfg(not(feature = "no_function"))]
+        statements.iter().for_each(|stmt| {
+            if let Stmt::Export(list, _) = stmt {
+                list.iter().filter(|(_, rename)| rename.is_none()).for_each(
+                    |(Ident { name, .. }, _)| {
+                        functions
+                            .values_mut()
+                            .filter(|f| f.name == *name)
+                            .for_each(|f| f.exported = true);
+                    },
+                );
+            }
+        });
+
         Ok((statements, functions.into_iter().map(|(_, v)| v).collect()))
     }
 
@@ -3165,7 +4452,25 @@ impl Engine {
         scope: &Scope,
         optimization_level: OptimizationLevel,
     ) -> Result<AST, ParseError> {
-        let (statements, lib) = self.parse_global_level(script_hash, input)?;
+        self.parse_with_options(
+            script_hash,
+            input,
+            scope,
+            optimization_level,
+            &Default::default(),
+        )
+    }
+
+    /// Run the parser on an input stream with [options][EvalOptions], returning an AST.
+    pub(crate) fn parse_with_options(
+        &self,
+        script_hash: u64,
+        input: &mut TokenStream,
+        scope: &Scope,
+        optimization_level: OptimizationLevel,
+        options: &EvalOptions,
+    ) -> Result<AST, ParseError> {
+        let (statements, lib) = self.parse_global_level(script_hash, input, scope, options)?;
 
         Ok(
             // Optimize AST
@@ -3207,7 +4512,12 @@ pub fn map_dynamic_to_expr(value: Dynamic, pos: Position) -> Option<Expr> {
         Union::Map(map, _) => {
             let items: Vec<_> = map
                 .into_iter()
-                .map(|(name, value)| (Ident { name, pos }, map_dynamic_to_expr(value, pos)))
+                .map(|(name, value)| {
+                    (
+                        MapKey::Name(Ident { name, pos }),
+                        map_dynamic_to_expr(value, pos),
+                    )
+                })
                 .collect();
 
             if items.iter().all(|(_, expr)| expr.is_some()) {
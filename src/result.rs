@@ -5,13 +5,20 @@ use crate::stdlib::{
     error::Error,
     fmt,
     string::{String, ToString},
+    vec::Vec,
 };
-use crate::{Dynamic, ImmutableString, ParseErrorType, Position, INT};
+use crate::{Dynamic, Engine, ImmutableString, ParseErrorType, Position, AST, INT};
 
 /// Evaluation result.
 ///
 /// All wrapped [`Position`] values represent the location in the script where the error occurs.
 ///
+/// This type is marked `#[non_exhaustive]` and new variants may be added in future versions.
+/// Instead of matching on all the variants, use the accessor methods that are stable across versions:
+/// [`position`][EvalAltResult::position], [`set_position`][EvalAltResult::set_position],
+/// [`kind_name`][EvalAltResult::kind_name], [`source_error`][EvalAltResult::source_error]
+/// and [`walk`][EvalAltResult::walk].
+///
 /// # Thread Safety
 ///
 /// Currently, [`EvalAltResult`] is neither [`Send`] nor [`Sync`].
@@ -34,10 +41,20 @@ pub enum EvalAltResult {
     /// Call to an unknown function. Wrapped value is the function signature.
     ErrorFunctionNotFound(String, Position),
     /// An error has occurred inside a called function.
-    /// Wrapped values are the function name and the interior error.
-    ErrorInFunctionCall(String, Box<EvalAltResult>, Position),
+    /// Wrapped values are the function name, the source of the function (empty if none)
+    /// and the interior error.
+    ///
+    /// Errors raised inside nested function calls are wrapped once per call, so the chain of
+    /// [`ErrorInFunctionCall`][EvalAltResult::ErrorInFunctionCall] forms a backtrace
+    /// (see [`backtrace`][EvalAltResult::backtrace]).
+    ErrorInFunctionCall(String, String, Box<EvalAltResult>, Position),
     /// Usage of an unknown [module][crate::Module]. Wrapped value is the [module][crate::Module] name.
     ErrorModuleNotFound(String, Position),
+    /// Importing a [module][crate::Module] that is not allowed. Wrapped value is the [module][crate::Module] path.
+    ErrorModuleDenied(String, Position),
+    /// Usage of an unknown [module][crate::Module] that has the same name as a variable in scope.
+    /// Wrapped values are the [module][crate::Module] name and the position of the variable declaration.
+    ErrorModuleShadowed(String, Position, Position),
     /// An error has occurred while loading a [module][crate::Module].
     /// Wrapped value are the [module][crate::Module] name and the interior error.
     ErrorInModule(String, Box<EvalAltResult>, Position),
@@ -52,9 +69,18 @@ pub enum EvalAltResult {
     /// Array access out-of-bounds.
     /// Wrapped values are the current number of elements in the array and the index number.
     ErrorArrayBounds(usize, INT, Position),
+    /// Element in a typed array is not of the required type.
+    /// Wrapped values are the element type required, the type of the offending element and its index.
+    ErrorArrayElementType(String, String, usize, Position),
+    /// Array has too few elements to destructure.
+    /// Wrapped values are the number of elements in the array and the number of variables in the pattern.
+    ErrorDestructure(usize, usize, Position),
     /// String indexing out-of-bounds.
     /// Wrapped values are the current number of characters in the string and the index number.
     ErrorStringBounds(usize, INT, Position),
+    /// Bit-field indexing out-of-bounds.
+    /// Wrapped values are the number of bits in the bit-field and the index number.
+    ErrorBitFieldBounds(usize, INT, Position),
     /// Trying to index into a type that is not an array, an object map, or a string, and has no
     /// indexer function defined. Wrapped value is the type name.
     ErrorIndexingType(String, Position),
@@ -74,6 +100,8 @@ pub enum EvalAltResult {
     ErrorTooManyOperations(Position),
     /// [Modules][crate::Module] over maximum limit.
     ErrorTooManyModules(Position),
+    /// Variables in the [`Scope`][crate::Scope] over maximum limit.
+    ErrorTooManyVariables(Position),
     /// Call stack over maximum limit.
     ErrorStackOverflow(Position),
     /// Data value over maximum size limit. Wrapped value is the type name.
@@ -86,7 +114,10 @@ pub enum EvalAltResult {
     /// Breaking out of loops - not an error if within a loop.
     /// The wrapped value, if true, means breaking clean out of the loop (i.e. a `break` statement).
     /// The wrapped value, if false, means breaking the current context (i.e. a `continue` statement).
-    LoopBreak(bool, Position),
+    /// The second wrapped value is the label of the loop to break out of (or [`None`] for the
+    /// innermost loop).
+    /// The third wrapped value is the value of the loop when breaking out of it (`()` if none).
+    LoopBreak(bool, Option<ImmutableString>, Dynamic, Position),
     /// Not an error: Value returned from a script via the `return` keyword.
     /// Wrapped value is the result value.
     Return(Dynamic, Position),
@@ -98,7 +129,7 @@ impl EvalAltResult {
             #[allow(deprecated)]
             Self::ErrorSystem(_, s) => s.description(),
             Self::ErrorParsing(p, _) => p.desc(),
-            Self::ErrorInFunctionCall(_, _, _) => "Error in called function",
+            Self::ErrorInFunctionCall(_, _, _, _) => "Error in called function",
             Self::ErrorInModule(_, _, _) => "Error in module",
             Self::ErrorFunctionNotFound(_, _) => "Function not found",
             Self::ErrorUnboundThis(_) => "'this' is not bound",
@@ -111,14 +142,20 @@ impl EvalAltResult {
             }
             Self::ErrorArrayBounds(0, _, _) => "Empty array has nothing to access",
             Self::ErrorArrayBounds(_, _, _) => "Array index out of bounds",
+            Self::ErrorArrayElementType(_, _, _, _) => "Array element type is incorrect",
+            Self::ErrorDestructure(_, _, _) => "Not enough elements in array to destructure",
             Self::ErrorStringBounds(_, index, _) if *index < 0 => {
                 "Indexing a string expects a non-negative index"
             }
             Self::ErrorStringBounds(0, _, _) => "Empty string has nothing to index",
             Self::ErrorStringBounds(_, _, _) => "String index out of bounds",
+            Self::ErrorBitFieldBounds(_, _, _) => "Bit-field index out of bounds",
             Self::ErrorFor(_) => "For loop expects an array, object map, or range",
             Self::ErrorVariableNotFound(_, _) => "Variable not found",
-            Self::ErrorModuleNotFound(_, _) => "Module not found",
+            Self::ErrorModuleNotFound(_, _) | Self::ErrorModuleShadowed(_, _, _) => {
+                "Module not found"
+            }
+            Self::ErrorModuleDenied(_, _) => "Importing this module is not allowed",
             Self::ErrorDataRace(_, _) => "Data race detected when accessing variable",
             Self::ErrorAssignmentToConstant(_, _) => "Cannot assign to a constant",
             Self::ErrorMismatchOutputType(_, _, _) => "Output type is incorrect",
@@ -127,12 +164,13 @@ impl EvalAltResult {
             Self::ErrorArithmetic(_, _) => "Arithmetic error",
             Self::ErrorTooManyOperations(_) => "Too many operations",
             Self::ErrorTooManyModules(_) => "Too many modules imported",
+            Self::ErrorTooManyVariables(_) => "Too many variables declared",
             Self::ErrorStackOverflow(_) => "Stack overflow",
             Self::ErrorDataTooLarge(_, _) => "Data size exceeds maximum limit",
             Self::ErrorTerminated(_,_) => "Script terminated.",
             Self::ErrorRuntime(_, _) => "Runtime error",
-            Self::LoopBreak(true, _) => "Break statement not inside a loop",
-            Self::LoopBreak(false, _) => "Continue statement not inside a loop",
+            Self::LoopBreak(true, _, _, _) => "Break statement not inside a loop",
+            Self::LoopBreak(false, _, _, _) => "Continue statement not inside a loop",
             Self::Return(_, _) => "[Not Error] Function returns value",
         }
     }
@@ -140,6 +178,64 @@ impl EvalAltResult {
 
 impl Error for EvalAltResult {}
 
+/// A function call frame in the backtrace of an [`EvalAltResult`].
+///
+/// Returned by [`EvalAltResult::backtrace`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct FrameInfo<'a> {
+    /// Name of the function called, including any namespace.
+    ///
+    /// Closures have anonymous names (starting with `anon$`).
+    pub name: &'a str,
+    /// Source of the function called, if any.
+    pub source: Option<&'a str>,
+    /// Position of the call.
+    pub position: Position,
+}
+
+impl<'a> FrameInfo<'a> {
+    /// Create a [`FrameInfo`] from the wrapped values of an
+    /// [`ErrorInFunctionCall`][EvalAltResult::ErrorInFunctionCall].
+    #[inline(always)]
+    fn new(name: &'a str, source: &'a str, position: Position) -> Self {
+        Self {
+            name,
+            source: if source.is_empty() {
+                None
+            } else {
+                Some(source)
+            },
+            position,
+        }
+    }
+}
+
+impl fmt::Display for FrameInfo<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(not(feature = "no_function"))]
+        let is_closure = crate::engine::is_anonymous_fn(self.name);
+        #[cfg(feature = "no_function")]
+        let is_closure = false;
+
+        if is_closure {
+            write!(f, "closure '{}'", self.name)?;
+        } else {
+            write!(f, "function '{}'", self.name)?;
+        }
+
+        if let Some(source) = self.source {
+            write!(f, " from '{}'", source)?;
+        }
+
+        // Do not write any position if None
+        if !self.position.is_none() {
+            write!(f, " ({})", self.position)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl fmt::Display for EvalAltResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let desc = self.desc();
@@ -151,12 +247,23 @@ impl fmt::Display for EvalAltResult {
 
             Self::ErrorParsing(p, _) => write!(f, "Syntax error: {}", p)?,
 
-            #[cfg(not(feature = "no_function"))]
-            Self::ErrorInFunctionCall(s, err, _) if crate::engine::is_anonymous_fn(s) => {
-                write!(f, "Error in call to closure: {}", err)?
-            }
-            Self::ErrorInFunctionCall(s, err, _) => {
-                write!(f, "Error in call to function '{}': {}", s, err)?
+            // Show the innermost error followed by the backtrace, innermost call first
+            Self::ErrorInFunctionCall(_, _, _, _) => {
+                let mut err = self;
+                let mut frames = Vec::new();
+
+                while let Self::ErrorInFunctionCall(name, source, inner, pos) = err {
+                    frames.push(FrameInfo::new(name, source, *pos));
+                    err = inner;
+                }
+
+                write!(f, "{}", err)?;
+
+                for frame in frames.iter().rev() {
+                    write!(f, "\n    in call to {}", frame)?;
+                }
+
+                return Ok(());
             }
             Self::ErrorInModule(s, err, _) if s.is_empty() => {
                 write!(f, "Error in module: {}", err)?
@@ -166,7 +273,14 @@ impl fmt::Display for EvalAltResult {
             Self::ErrorFunctionNotFound(s, _)
             | Self::ErrorVariableNotFound(s, _)
             | Self::ErrorDataRace(s, _)
-            | Self::ErrorModuleNotFound(s, _) => write!(f, "{}: '{}'", desc, s)?,
+            | Self::ErrorModuleNotFound(s, _)
+            | Self::ErrorModuleDenied(s, _) => write!(f, "{}: '{}'", desc, s)?,
+
+            Self::ErrorModuleShadowed(s, var_pos, _) => write!(
+                f,
+                "{}: '{}' is a variable ({}), not a module alias",
+                desc, s, var_pos
+            )?,
 
             Self::ErrorDotExpr(s, _) if !s.is_empty() => write!(f, "{}", s)?,
 
@@ -177,6 +291,7 @@ impl fmt::Display for EvalAltResult {
             | Self::ErrorDotExpr(_, _)
             | Self::ErrorTooManyOperations(_)
             | Self::ErrorTooManyModules(_)
+            | Self::ErrorTooManyVariables(_)
             | Self::ErrorStackOverflow(_)
             | Self::ErrorTerminated(_, _) => f.write_str(desc)?,
 
@@ -202,9 +317,22 @@ impl fmt::Display for EvalAltResult {
             Self::ErrorMismatchDataType(s, r, _) => {
                 write!(f, "Data type is incorrect: {} (expecting {})", r, s)?
             }
+            Self::ErrorArrayElementType(s, r, index, _) => {
+                write!(f, "{}: {} at index {} (expecting {})", desc, r, index, s)?
+            }
+            Self::ErrorDestructure(1, expected, _) => write!(
+                f,
+                "{}: only one element in the array (expecting {})",
+                desc, expected
+            )?,
+            Self::ErrorDestructure(len, expected, _) => write!(
+                f,
+                "{}: only {} elements in the array (expecting {})",
+                desc, len, expected
+            )?,
             Self::ErrorArithmetic(s, _) => f.write_str(s)?,
 
-            Self::LoopBreak(_, _) => f.write_str(desc)?,
+            Self::LoopBreak(_, _, _, _) => f.write_str(desc)?,
             Self::Return(_, _) => f.write_str(desc)?,
 
             Self::ErrorArrayBounds(_, index, _) if *index < 0 => {
@@ -235,6 +363,11 @@ impl fmt::Display for EvalAltResult {
                 "String index {} is out of bounds: only {} characters in the string",
                 index, max
             )?,
+            Self::ErrorBitFieldBounds(max, index, _) => write!(
+                f,
+                "Bit-field index {} is out of bounds: only {} bits in the bit-field",
+                index, max
+            )?,
             Self::ErrorDataTooLarge(typ, _) => write!(f, "{} exceeds maximum limit", typ)?,
         }
 
@@ -275,17 +408,27 @@ impl EvalAltResult {
             Self::ErrorSystem(_, _) => false,
             Self::ErrorParsing(_, _) => false,
 
+            // Wrapped errors cannot be caught if the interior error is a termination or
+            // a violation of limits, so that wrapping cannot be used to get around them
+            Self::ErrorInFunctionCall(_, _, err, _) | Self::ErrorInModule(_, err, _) => {
+                err.is_catchable()
+                    || matches!(**err, Self::ErrorSystem(_, _) | Self::ErrorParsing(_, _))
+            }
+
             Self::ErrorFunctionNotFound(_, _)
-            | Self::ErrorInFunctionCall(_, _, _)
-            | Self::ErrorInModule(_, _, _)
             | Self::ErrorUnboundThis(_)
             | Self::ErrorMismatchDataType(_, _, _)
             | Self::ErrorArrayBounds(_, _, _)
+            | Self::ErrorArrayElementType(_, _, _, _)
+            | Self::ErrorDestructure(_, _, _)
             | Self::ErrorStringBounds(_, _, _)
+            | Self::ErrorBitFieldBounds(_, _, _)
             | Self::ErrorIndexingType(_, _)
             | Self::ErrorFor(_)
             | Self::ErrorVariableNotFound(_, _)
             | Self::ErrorModuleNotFound(_, _)
+            | Self::ErrorModuleShadowed(_, _, _)
+            | Self::ErrorModuleDenied(_, _)
             | Self::ErrorDataRace(_, _)
             | Self::ErrorAssignmentToConstant(_, _)
             | Self::ErrorMismatchOutputType(_, _, _)
@@ -296,11 +439,14 @@ impl EvalAltResult {
 
             Self::ErrorTooManyOperations(_)
             | Self::ErrorTooManyModules(_)
+            | Self::ErrorTooManyVariables(_)
             | Self::ErrorStackOverflow(_)
             | Self::ErrorDataTooLarge(_, _)
             | Self::ErrorTerminated(_, _) => false,
 
-            Self::LoopBreak(_, _) => panic!("EvalAltResult::LoopBreak should not occur naturally"),
+            Self::LoopBreak(_, _, _, _) => {
+                panic!("EvalAltResult::LoopBreak should not occur naturally")
+            }
             Self::Return(_, _) => panic!("EvalAltResult::Return should not occur naturally"),
         }
     }
@@ -316,34 +462,48 @@ impl EvalAltResult {
 
             Self::ErrorTooManyOperations(_)
             | Self::ErrorTooManyModules(_)
+            | Self::ErrorTooManyVariables(_)
             | Self::ErrorStackOverflow(_)
             | Self::ErrorDataTooLarge(_, _) => true,
 
             Self::ErrorTerminated(_, _) => true,
 
-            Self::LoopBreak(_, _) => panic!("EvalAltResult::LoopBreak should not occur naturally"),
+            Self::ErrorInFunctionCall(_, _, _, _) | Self::ErrorInModule(_, _, _) => {
+                !self.is_catchable()
+            }
+
+            Self::LoopBreak(_, _, _, _) => {
+                panic!("EvalAltResult::LoopBreak should not occur naturally")
+            }
             Self::Return(_, _) => panic!("EvalAltResult::Return should not occur naturally"),
 
             _ => false,
         }
     }
     /// Get the [position][Position] of this error.
+    ///
+    /// [`ErrorSystem`][EvalAltResult::ErrorSystem] has no position and always returns [`Position::NONE`].
     pub fn position(&self) -> Position {
         match self {
             Self::ErrorSystem(_, _) => Position::NONE,
 
             Self::ErrorParsing(_, pos)
             | Self::ErrorFunctionNotFound(_, pos)
-            | Self::ErrorInFunctionCall(_, _, pos)
+            | Self::ErrorInFunctionCall(_, _, _, pos)
             | Self::ErrorInModule(_, _, pos)
             | Self::ErrorUnboundThis(pos)
             | Self::ErrorMismatchDataType(_, _, pos)
             | Self::ErrorArrayBounds(_, _, pos)
+            | Self::ErrorArrayElementType(_, _, _, pos)
+            | Self::ErrorDestructure(_, _, pos)
             | Self::ErrorStringBounds(_, _, pos)
+            | Self::ErrorBitFieldBounds(_, _, pos)
             | Self::ErrorIndexingType(_, pos)
             | Self::ErrorFor(pos)
             | Self::ErrorVariableNotFound(_, pos)
             | Self::ErrorModuleNotFound(_, pos)
+            | Self::ErrorModuleShadowed(_, _, pos)
+            | Self::ErrorModuleDenied(_, pos)
             | Self::ErrorDataRace(_, pos)
             | Self::ErrorAssignmentToConstant(_, pos)
             | Self::ErrorMismatchOutputType(_, _, pos)
@@ -352,11 +512,12 @@ impl EvalAltResult {
             | Self::ErrorArithmetic(_, pos)
             | Self::ErrorTooManyOperations(pos)
             | Self::ErrorTooManyModules(pos)
+            | Self::ErrorTooManyVariables(pos)
             | Self::ErrorStackOverflow(pos)
             | Self::ErrorDataTooLarge(_, pos)
             | Self::ErrorTerminated(_, pos)
             | Self::ErrorRuntime(_, pos)
-            | Self::LoopBreak(_, pos)
+            | Self::LoopBreak(_, _, _, pos)
             | Self::Return(_, pos) => *pos,
         }
     }
@@ -367,16 +528,21 @@ impl EvalAltResult {
 
             Self::ErrorParsing(_, pos)
             | Self::ErrorFunctionNotFound(_, pos)
-            | Self::ErrorInFunctionCall(_, _, pos)
+            | Self::ErrorInFunctionCall(_, _, _, pos)
             | Self::ErrorInModule(_, _, pos)
             | Self::ErrorUnboundThis(pos)
             | Self::ErrorMismatchDataType(_, _, pos)
             | Self::ErrorArrayBounds(_, _, pos)
+            | Self::ErrorArrayElementType(_, _, _, pos)
+            | Self::ErrorDestructure(_, _, pos)
             | Self::ErrorStringBounds(_, _, pos)
+            | Self::ErrorBitFieldBounds(_, _, pos)
             | Self::ErrorIndexingType(_, pos)
             | Self::ErrorFor(pos)
             | Self::ErrorVariableNotFound(_, pos)
             | Self::ErrorModuleNotFound(_, pos)
+            | Self::ErrorModuleShadowed(_, _, pos)
+            | Self::ErrorModuleDenied(_, pos)
             | Self::ErrorDataRace(_, pos)
             | Self::ErrorAssignmentToConstant(_, pos)
             | Self::ErrorMismatchOutputType(_, _, pos)
@@ -385,14 +551,220 @@ impl EvalAltResult {
             | Self::ErrorArithmetic(_, pos)
             | Self::ErrorTooManyOperations(pos)
             | Self::ErrorTooManyModules(pos)
+            | Self::ErrorTooManyVariables(pos)
             | Self::ErrorStackOverflow(pos)
             | Self::ErrorDataTooLarge(_, pos)
             | Self::ErrorTerminated(_, pos)
             | Self::ErrorRuntime(_, pos)
-            | Self::LoopBreak(_, pos)
+            | Self::LoopBreak(_, _, _, pos)
             | Self::Return(_, pos) => *pos = new_position,
         }
     }
+    /// Get the name of the kind of this error, which is the same as the name of its variant
+    /// (e.g. `"ErrorRuntime"` for [`ErrorRuntime`][EvalAltResult::ErrorRuntime]).
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Self::ErrorSystem(_, _) => "ErrorSystem",
+            Self::ErrorParsing(_, _) => "ErrorParsing",
+            Self::ErrorVariableNotFound(_, _) => "ErrorVariableNotFound",
+            Self::ErrorFunctionNotFound(_, _) => "ErrorFunctionNotFound",
+            Self::ErrorInFunctionCall(_, _, _, _) => "ErrorInFunctionCall",
+            Self::ErrorModuleNotFound(_, _) => "ErrorModuleNotFound",
+            Self::ErrorModuleDenied(_, _) => "ErrorModuleDenied",
+            Self::ErrorModuleShadowed(_, _, _) => "ErrorModuleShadowed",
+            Self::ErrorInModule(_, _, _) => "ErrorInModule",
+            Self::ErrorUnboundThis(_) => "ErrorUnboundThis",
+            Self::ErrorMismatchDataType(_, _, _) => "ErrorMismatchDataType",
+            Self::ErrorMismatchOutputType(_, _, _) => "ErrorMismatchOutputType",
+            Self::ErrorArrayBounds(_, _, _) => "ErrorArrayBounds",
+            Self::ErrorArrayElementType(_, _, _, _) => "ErrorArrayElementType",
+            Self::ErrorDestructure(_, _, _) => "ErrorDestructure",
+            Self::ErrorStringBounds(_, _, _) => "ErrorStringBounds",
+            Self::ErrorBitFieldBounds(_, _, _) => "ErrorBitFieldBounds",
+            Self::ErrorIndexingType(_, _) => "ErrorIndexingType",
+            Self::ErrorInExpr(_) => "ErrorInExpr",
+            Self::ErrorFor(_) => "ErrorFor",
+            Self::ErrorDataRace(_, _) => "ErrorDataRace",
+            Self::ErrorAssignmentToConstant(_, _) => "ErrorAssignmentToConstant",
+            Self::ErrorDotExpr(_, _) => "ErrorDotExpr",
+            Self::ErrorArithmetic(_, _) => "ErrorArithmetic",
+            Self::ErrorTooManyOperations(_) => "ErrorTooManyOperations",
+            Self::ErrorTooManyModules(_) => "ErrorTooManyModules",
+            Self::ErrorTooManyVariables(_) => "ErrorTooManyVariables",
+            Self::ErrorStackOverflow(_) => "ErrorStackOverflow",
+            Self::ErrorDataTooLarge(_, _) => "ErrorDataTooLarge",
+            Self::ErrorTerminated(_, _) => "ErrorTerminated",
+            Self::ErrorRuntime(_, _) => "ErrorRuntime",
+            Self::LoopBreak(_, _, _, _) => "LoopBreak",
+            Self::Return(_, _) => "Return",
+        }
+    }
+    /// Get the error wrapped inside this error, if any.
+    ///
+    /// Only [`ErrorInFunctionCall`][EvalAltResult::ErrorInFunctionCall] and
+    /// [`ErrorInModule`][EvalAltResult::ErrorInModule] wrap another error.
+    pub fn source_error(&self) -> Option<&EvalAltResult> {
+        match self {
+            Self::ErrorInFunctionCall(_, _, err, _) | Self::ErrorInModule(_, err, _) => Some(err),
+            _ => None,
+        }
+    }
+    /// Get the function call frames of the backtrace of this error, innermost call first.
+    ///
+    /// There is one frame for each [`ErrorInFunctionCall`][EvalAltResult::ErrorInFunctionCall]
+    /// wrapped around the innermost error.  Only the outermost call and the innermost calls are
+    /// kept when the functions are nested very deeply (e.g. in a deep recursion).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::{Engine, Position};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let err = engine
+    ///     .eval::<()>("fn foo() { throw \"oops\"; }\nfn bar() { foo() }\nbar()")
+    ///     .expect_err("should error");
+    ///
+    /// let frames: Vec<_> = err
+    ///     .backtrace()
+    ///     .iter()
+    ///     .map(|frame| (frame.name, frame.position))
+    ///     .collect();
+    ///
+    /// assert_eq!(frames, [("foo", Position::new(2, 12)), ("bar", Position::new(3, 1))]);
+    /// # }
+    /// ```
+    pub fn backtrace(&self) -> Vec<FrameInfo<'_>> {
+        let mut frames = Vec::new();
+        let mut err = Some(self);
+
+        while let Some(e) = err {
+            if let Self::ErrorInFunctionCall(name, source, _, pos) = e {
+                frames.push(FrameInfo::new(name, source, *pos));
+            }
+            err = e.source_error();
+        }
+
+        frames.reverse();
+        frames
+    }
+    /// Visit this error and all the errors wrapped inside it, from the outermost to the innermost.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let err = engine
+    ///     .eval::<()>(r#"fn foo() { throw "oops"; } foo()"#)
+    ///     .expect_err("should error");
+    ///
+    /// let mut kinds = Vec::new();
+    /// err.walk(|err| kinds.push(err.kind_name()));
+    ///
+    /// assert_eq!(kinds, ["ErrorInFunctionCall", "ErrorRuntime"]);
+    /// # }
+    /// ```
+    pub fn walk(&self, mut f: impl FnMut(&EvalAltResult)) {
+        let mut err = Some(self);
+
+        while let Some(e) = err {
+            f(e);
+            err = e.source_error();
+        }
+    }
+    /// Get an excerpt of the script text where this error occurs: the offending line,
+    /// followed by a line with `^` under the error position.
+    ///
+    /// The whole [span][crate::Span] of the text at the error position (see
+    /// [`Engine::span_at`]) is underlined, up to the end of the offending line.
+    ///
+    /// The text is taken from the [`AST`] that was evaluated, or, for errors inside a module
+    /// script, from the module resolver of the [`Engine`].  Either is only available when
+    /// [`Engine::set_retain_source`] is turned on before compiling.
+    ///
+    /// Returns [`None`] if the error has no position or the text is not available.
+    ///
+    /// # Limitation
+    ///
+    /// An error inside a function defined in a module, when called from another script after the
+    /// module is imported, carries a position within the module script; this cannot be told apart
+    /// from a position in the calling script, so the excerpt may point to the wrong text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, INT};
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.set_retain_source(true);
+    ///
+    /// let ast = engine.compile("let x = 40;\nx + foo(2)").unwrap();
+    /// let err = engine.eval_ast::<INT>(&ast).expect_err("should error");
+    ///
+    /// assert_eq!(err.excerpt(&engine, &ast).unwrap(), "x + foo(2)\n    ^^^^^^");
+    /// ```
+    pub fn excerpt(&self, engine: &Engine, ast: &AST) -> Option<String> {
+        #[cfg(not(feature = "no_module"))]
+        let mut text = ast.clone_source_text();
+        #[cfg(feature = "no_module")]
+        let text = ast.clone_source_text();
+        let mut found = None;
+        let mut err = Some(self);
+
+        // Find the innermost error with a position, tracking the script it refers to
+        while let Some(e) = err {
+            if !e.position().is_none() {
+                found = Some((text.clone(), e.position()));
+            }
+
+            #[cfg(not(feature = "no_module"))]
+            if let Self::ErrorInModule(path, _, _) = e {
+                text = engine.module_resolver.source_text(path);
+            }
+
+            err = e.source_error();
+        }
+
+        let (text, pos) = found?;
+        let text = text?;
+        let line = text
+            .split('\n')
+            .nth(pos.line()? - 1)?
+            .trim_end_matches('\r')
+            .to_string();
+
+        // Positions count characters, so keep tabs and turn every other character into
+        // a single space to line up the caret
+        let start = pos.position().unwrap_or(1);
+        let indent: String = line
+            .chars()
+            .take(start - 1)
+            .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        // Underline up to the end of the span, or the end of the line if the span goes beyond
+        let end = engine.span_at(&text, pos).end();
+        let end = match (end.line(), end.position()) {
+            (Some(end_line), Some(end)) if end_line == pos.line()? => end.max(start),
+            (Some(end_line), _) if end_line > pos.line()? => line.chars().count().max(start),
+            _ => start,
+        };
+
+        Some(format!(
+            "{}\n{}{}",
+            line,
+            indent,
+            "^".repeat(end - start + 1)
+        ))
+    }
     /// Consume the current [`EvalAltResult`] and return a new one with the specified [`Position`]
     /// if the current position is [`Position::None`].
     #[inline(always)]
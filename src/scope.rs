@@ -1,9 +1,19 @@
 //! Module that defines the [`Scope`] type representing a function call-stack scope.
 
 use crate::dynamic::{AccessMode, Variant};
-use crate::stdlib::{borrow::Cow, boxed::Box, iter, vec::Vec};
+use crate::stdlib::{
+    borrow::Cow,
+    boxed::Box,
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    iter,
+    vec::Vec,
+};
 use crate::{Dynamic, ImmutableString, StaticVec};
 
+/// Number of entries in a [`Scope`] beyond which variable names are indexed for faster lookup.
+pub(crate) const SCOPE_INDEX_THRESHOLD: usize = 32;
+
 /// Type containing information about the current scope.
 /// Useful for keeping state between [`Engine`][crate::Engine] evaluation runs.
 ///
@@ -46,12 +56,19 @@ use crate::{Dynamic, ImmutableString, StaticVec};
 // Since [`Dynamic`] is reasonably small, packing it tightly improves cache locality when variables are accessed.
 //
 // The alias is `Box`'ed because it occurs infrequently.
-#[derive(Debug, Clone, Hash)]
+//
+// Looking up a variable by name is a reverse linear scan, which is fast for the usual handful of
+// variables.  Once a [`Scope`] grows beyond `SCOPE_INDEX_THRESHOLD` entries, a hash index from each
+// name to the indices of all its entries (newest last) is built and kept up-to-date on push and
+// rewind, so that large scopes (e.g. thousands of host variables) are looked up in constant time.
+#[derive(Debug, Clone)]
 pub struct Scope<'a> {
     /// Current value of the entry.
     values: Vec<Dynamic>,
     /// (Name, aliases) of the entry.
     names: Vec<(Cow<'a, str>, Box<StaticVec<ImmutableString>>)>,
+    /// Indices of the entries of each name, newest last.  [`None`] if not indexed.
+    index: Option<HashMap<Cow<'a, str>, StaticVec<usize>>>,
 }
 
 impl Default for Scope<'_> {
@@ -60,10 +77,19 @@ impl Default for Scope<'_> {
         Self {
             values: Vec::with_capacity(16),
             names: Vec::with_capacity(16),
+            index: None,
         }
     }
 }
 
+impl Hash for Scope<'_> {
+    #[inline(always)]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.values.hash(state);
+        self.names.hash(state);
+    }
+}
+
 impl<'a> Scope<'a> {
     /// Create a new [`Scope`].
     ///
@@ -104,6 +130,7 @@ impl<'a> Scope<'a> {
     pub fn clear(&mut self) -> &mut Self {
         self.names.clear();
         self.values.clear();
+        self.index = None;
         self
     }
     /// Get the number of entries inside the [`Scope`].
@@ -230,11 +257,41 @@ impl<'a> Scope<'a> {
         access: AccessMode,
         mut value: Dynamic,
     ) -> &mut Self {
-        self.names.push((name.into(), Box::new(Default::default())));
         value.set_access_mode(access);
-        self.values.push(value.into());
+        self.push_entry(name.into(), Box::default(), value);
         self
     }
+    /// Add (push) a new entry, keeping the index of names up-to-date.
+    #[inline]
+    fn push_entry(
+        &mut self,
+        name: Cow<'a, str>,
+        aliases: Box<StaticVec<ImmutableString>>,
+        value: Dynamic,
+    ) {
+        let index = self.names.len();
+
+        if let Some(ref mut map) = self.index {
+            map.entry(name.clone()).or_default().push(index);
+        }
+
+        self.names.push((name, aliases));
+        self.values.push(value);
+
+        if self.index.is_none() && self.names.len() > SCOPE_INDEX_THRESHOLD {
+            self.build_index();
+        }
+    }
+    /// Build the index of names from all the entries.
+    fn build_index(&mut self) {
+        let mut index: HashMap<_, StaticVec<_>> = HashMap::with_capacity(self.names.len());
+
+        self.names.iter().enumerate().for_each(|(i, (name, _))| {
+            index.entry(name.clone()).or_default().push(i);
+        });
+
+        self.index = Some(index);
+    }
     /// Truncate (rewind) the [`Scope`] to a previous size.
     ///
     /// # Example
@@ -261,8 +318,24 @@ impl<'a> Scope<'a> {
     /// assert_eq!(my_scope.len(), 0);
     /// assert!(my_scope.is_empty());
     /// ```
-    #[inline(always)]
+    #[inline]
     pub fn rewind(&mut self, size: usize) -> &mut Self {
+        match self.index {
+            Some(ref mut index) if size > SCOPE_INDEX_THRESHOLD => {
+                // Remove the indices of the discarded entries
+                self.names.iter().skip(size).for_each(|(name, _)| {
+                    if let Some(list) = index.get_mut(name.as_ref()) {
+                        list.pop();
+                        if list.is_empty() {
+                            index.remove(name.as_ref());
+                        }
+                    }
+                });
+            }
+            Some(_) if size < self.names.len() => self.index = None,
+            _ => (),
+        }
+
         self.names.truncate(size);
         self.values.truncate(size);
         self
@@ -282,25 +355,22 @@ impl<'a> Scope<'a> {
     /// ```
     #[inline(always)]
     pub fn contains(&self, name: &str) -> bool {
-        self.names
-            .iter()
-            .rev() // Always search a Scope in reverse order
-            .any(|(key, _)| name == key.as_ref())
+        self.find_index(name).is_some()
+    }
+    /// Find the index of an entry in the [`Scope`], starting from the last.
+    #[inline]
+    fn find_index(&self, name: &str) -> Option<usize> {
+        if let Some(ref index) = self.index {
+            return index.get(name).and_then(|list| list.last()).cloned();
+        }
+
+        self.names.iter().rposition(|(key, _)| name == key.as_ref()) // Always search a Scope in reverse order
     }
     /// Find an entry in the [`Scope`], starting from the last.
     #[inline(always)]
     pub(crate) fn get_index(&self, name: &str) -> Option<(usize, AccessMode)> {
-        self.names
-            .iter()
-            .enumerate()
-            .rev() // Always search a Scope in reverse order
-            .find_map(|(index, (key, _))| {
-                if name == key.as_ref() {
-                    Some((index, self.values[index].access_mode()))
-                } else {
-                    None
-                }
-            })
+        self.find_index(name)
+            .map(|index| (index, self.values[index].access_mode()))
     }
     /// Get the value of an entry in the [`Scope`], starting from the last.
     ///
@@ -316,12 +386,35 @@ impl<'a> Scope<'a> {
     /// ```
     #[inline(always)]
     pub fn get_value<T: Variant + Clone>(&self, name: &str) -> Option<T> {
-        self.names
-            .iter()
-            .enumerate()
-            .rev()
-            .find(|(_, (key, _))| name == key.as_ref())
-            .and_then(|(index, _)| self.values[index].flatten_clone().try_cast())
+        self.find_index(name)
+            .and_then(|index| self.values[index].flatten_clone().try_cast())
+    }
+    /// Get a reference to the value of an entry in the [`Scope`], starting from the last.
+    ///
+    /// Returns [`None`] if the entry is not found, if its value is not of type `T`,
+    /// or if its value is shared (e.g. captured by a closure).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Scope;
+    ///
+    /// let mut my_scope = Scope::new();
+    ///
+    /// my_scope.push("x", "hello".to_string());
+    /// assert_eq!(my_scope.get_value_ref::<String>("x").unwrap(), "hello");
+    /// assert!(my_scope.get_value_ref::<i64>("x").is_none());
+    /// ```
+    #[inline(always)]
+    pub fn get_value_ref<T: Variant + Clone>(&self, name: &str) -> Option<&T> {
+        self.get_dynamic_ref(name)
+            .and_then(|value| value.downcast_ref())
+    }
+    /// Get a reference to the value of an entry in the [`Scope`], starting from the last.
+    /// Shared values are not expanded.
+    #[inline(always)]
+    pub(crate) fn get_dynamic_ref(&self, name: &str) -> Option<&Dynamic> {
+        self.find_index(name).map(|index| &self.values[index])
     }
     /// Update the value of the named entry in the [`Scope`].
     ///
@@ -358,6 +451,96 @@ impl<'a> Scope<'a> {
         }
         self
     }
+    /// Update the value of the named entry in the [`Scope`], or add a new entry if none is found.
+    ///
+    /// Search starts backwards from the last, and only the first entry matching the specified name is updated.
+    /// The new value may be of a different type than the old value.
+    ///
+    /// Unlike [`set_value`][Scope::set_value], the name can be an owned [`String`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when trying to update the value of a constant.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Scope;
+    ///
+    /// let mut my_scope = Scope::new();
+    ///
+    /// my_scope.set_or_push("x", 42_i64);
+    /// assert_eq!(my_scope.get_value::<i64>("x").unwrap(), 42);
+    ///
+    /// my_scope.set_or_push(format!("{}", "x"), "hello".to_string());
+    /// assert_eq!(my_scope.get_value::<String>("x").unwrap(), "hello");
+    /// assert_eq!(my_scope.len(), 1);
+    /// ```
+    #[inline]
+    pub fn set_or_push(
+        &mut self,
+        name: impl Into<Cow<'a, str>>,
+        value: impl Variant + Clone,
+    ) -> &mut Self {
+        let name = name.into();
+
+        match self.get_index(&name) {
+            None => {
+                self.push(name, value);
+            }
+            Some((_, AccessMode::ReadOnly)) => panic!("variable {} is constant", name),
+            Some((index, AccessMode::ReadWrite)) => {
+                *self.get_mut_by_index(index) = Dynamic::from(value);
+            }
+        }
+        self
+    }
+    /// Remove the named entry from the [`Scope`], returning its value.
+    ///
+    /// Search starts backwards from the last, and only the first entry matching the specified name is removed,
+    /// so an older entry of the same name, if any, becomes visible again.
+    /// Constants can also be removed.
+    ///
+    /// Variables in the [`Scope`] are always looked up by name when evaluating an [`AST`][crate::AST],
+    /// so removing an entry never confuses the variables of later entries.  However, constants in
+    /// the [`Scope`] may have been propagated into an [`AST`][crate::AST] optimized with it;
+    /// compile the script again after removing a constant.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Scope;
+    ///
+    /// let mut my_scope = Scope::new();
+    ///
+    /// my_scope.push("x", 42_i64);
+    /// my_scope.push("y", 123_i64);
+    /// my_scope.push("x", 0_i64);
+    ///
+    /// assert_eq!(my_scope.remove("x").unwrap().cast::<i64>(), 0);
+    /// assert_eq!(my_scope.get_value::<i64>("x").unwrap(), 42);
+    /// assert_eq!(my_scope.len(), 2);
+    ///
+    /// assert!(my_scope.remove("z").is_none());
+    /// ```
+    #[inline]
+    pub fn remove(&mut self, name: &str) -> Option<Dynamic> {
+        let index = self.find_index(name)?;
+
+        self.names.remove(index);
+        let value = self.values.remove(index);
+
+        // The indices of all later entries are shifted
+        if self.index.is_some() {
+            if self.names.len() > SCOPE_INDEX_THRESHOLD {
+                self.build_index();
+            } else {
+                self.index = None;
+            }
+        }
+
+        Some(value)
+    }
     /// Get a mutable reference to an entry in the [`Scope`].
     ///
     /// If the entry by the specified name is not found, of if it is read-only,
@@ -415,9 +598,8 @@ impl<'a> Scope<'a> {
             .enumerate()
             .rev()
             .for_each(|(index, (name, alias))| {
-                if !entries.names.iter().any(|(key, _)| key == name) {
-                    entries.names.push((name.clone(), alias.clone()));
-                    entries.values.push(self.values[index].clone());
+                if !entries.contains(name) {
+                    entries.push_entry(name.clone(), alias.clone(), self.values[index].clone());
                 }
             });
 
@@ -464,8 +646,27 @@ impl<'a> Scope<'a> {
         self.iter_raw()
             .map(|(name, constant, value)| (name, constant, value.flatten_clone()))
     }
-    /// Get an iterator to entries in the [`Scope`].
+    /// Get an iterator to entries in the [`Scope`], yielding the name, whether it is a constant,
+    /// and a reference to the value of each entry.
     /// Shared values are not expanded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Scope;
+    ///
+    /// let mut my_scope = Scope::new();
+    ///
+    /// my_scope.push("x", 42_i64);
+    /// my_scope.push_constant("foo", "hello".to_string());
+    ///
+    /// let snapshot: Vec<_> = my_scope
+    ///     .iter_raw()
+    ///     .map(|(name, constant, value)| format!("{}{} = {}", if constant { "const " } else { "" }, name, value))
+    ///     .collect();
+    ///
+    /// assert_eq!(snapshot, ["x = 42", "const foo = hello"]);
+    /// ```
     #[inline(always)]
     pub fn iter_raw<'x: 'a>(&'x self) -> impl Iterator<Item = (&'a str, bool, &'x Dynamic)> + 'x {
         self.names
@@ -479,8 +680,51 @@ impl<'a, K: Into<Cow<'a, str>>> iter::Extend<(K, Dynamic)> for Scope<'a> {
     #[inline(always)]
     fn extend<T: IntoIterator<Item = (K, Dynamic)>>(&mut self, iter: T) {
         iter.into_iter().for_each(|(name, value)| {
-            self.names.push((name.into(), Box::new(Default::default())));
-            self.values.push(value);
+            self.push_entry(name.into(), Box::default(), value);
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Scope, SCOPE_INDEX_THRESHOLD};
+    use crate::INT;
+
+    /// This test is to make sure large scopes are looked up via the index instead of a linear scan.
+    #[test]
+    fn check_scope_index() {
+        let mut scope = Scope::new();
+
+        (0..SCOPE_INDEX_THRESHOLD).for_each(|i| {
+            scope.push(format!("v{}", i), i as INT);
+        });
+        assert!(scope.index.is_none());
+
+        scope.push("x", 1 as INT);
+        scope.push("v1", 42 as INT);
+        assert!(scope.index.is_some());
+
+        // Shadowed names resolve to the newest entry
+        assert_eq!(scope.find_index("v1"), Some(SCOPE_INDEX_THRESHOLD + 1));
+        assert_eq!(scope.get_value::<INT>("v1").unwrap(), 42);
+
+        // Point the index elsewhere - a lookup that scans the entries would not notice
+        scope.index.as_mut().unwrap().get_mut("x").unwrap()[0] = 2;
+        assert_eq!(scope.find_index("x"), Some(2));
+        assert_eq!(scope.get_value::<INT>("x").unwrap(), 2);
+
+        // Rewinding past the threshold keeps the index up-to-date
+        scope.index.as_mut().unwrap().get_mut("x").unwrap()[0] = SCOPE_INDEX_THRESHOLD;
+        scope.push("y", 0 as INT);
+        scope.rewind(SCOPE_INDEX_THRESHOLD + 1);
+        assert!(scope.index.is_some());
+        assert_eq!(scope.find_index("v1"), Some(1));
+        assert!(scope.find_index("y").is_none());
+
+        // Rewinding below the threshold drops the index
+        scope.rewind(SCOPE_INDEX_THRESHOLD);
+        assert!(scope.index.is_none());
+        assert!(!scope.contains("x"));
+        assert_eq!(scope.get_value::<INT>("v1").unwrap(), 1);
+    }
+}
@@ -0,0 +1,127 @@
+//! Implement serialization support of custom types registered with an [`Engine`][crate::Engine].
+
+use super::de::from_dynamic;
+use super::ser::to_dynamic;
+use crate::dynamic::{Union, Variant};
+use crate::stdlib::{any::type_name, boxed::Box, format, string::ToString};
+use crate::{Dynamic, Engine, EvalAltResult, ImmutableString, Map, Position};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Name of the property holding the type name of a serialized custom type.
+pub const TYPE_TAG: &str = "$type";
+
+/// Name of the property holding the value of a serialized custom type.
+pub const VALUE_TAG: &str = "$value";
+
+/// Serialization hooks of a custom type.
+#[derive(Debug, Clone, Copy)]
+pub struct CustomTypeSerde {
+    /// Name of the Rust type.
+    type_name: &'static str,
+    /// Convert a value of the type into a [`Dynamic`] holding only serde data.
+    serialize: fn(&Dynamic) -> Result<Dynamic, Box<EvalAltResult>>,
+    /// Convert a [`Dynamic`] holding only serde data back into a value of the type.
+    deserialize: fn(&Dynamic) -> Result<Dynamic, Box<EvalAltResult>>,
+}
+
+impl CustomTypeSerde {
+    /// Create the serialization hooks of a custom type.
+    pub fn new<T: Variant + Clone + Serialize + DeserializeOwned>() -> Self {
+        Self {
+            type_name: type_name::<T>(),
+            serialize: |value| to_dynamic(value.downcast_ref::<T>().unwrap()),
+            deserialize: |value| from_dynamic::<T>(value).map(Dynamic::from),
+        }
+    }
+}
+
+/// Replace all values of custom types with serialization hooks inside a [`Dynamic`] (including
+/// inside arrays and object maps) with object maps tagged with the type names.
+pub fn encode_custom_types(
+    engine: &Engine,
+    value: &Dynamic,
+) -> Result<Dynamic, Box<EvalAltResult>> {
+    let mut value = value.flatten_clone();
+
+    match value.0 {
+        #[cfg(not(feature = "no_index"))]
+        Union::Array(ref mut arr, _) => {
+            for item in arr.iter_mut() {
+                *item = encode_custom_types(engine, item)?;
+            }
+        }
+        Union::Map(ref mut map, _) => {
+            for (_, item) in map.iter_mut() {
+                *item = encode_custom_types(engine, item)?;
+            }
+        }
+        Union::Variant(_, _) => {
+            if let Some(hooks) = engine.custom_types_serde.get(&value.type_id()) {
+                let mut map = Map::new();
+                map.insert(
+                    TYPE_TAG.into(),
+                    engine.map_type_name(hooks.type_name).into(),
+                );
+                map.insert(VALUE_TAG.into(), (hooks.serialize)(&value)?);
+                return Ok(map.into());
+            }
+        }
+        _ => (),
+    }
+
+    Ok(value)
+}
+
+/// Restore all object maps tagged with the type names of custom types inside a [`Dynamic`]
+/// (including inside arrays and object maps) into values of those types.
+pub fn decode_custom_types(
+    engine: &Engine,
+    mut value: Dynamic,
+) -> Result<Dynamic, Box<EvalAltResult>> {
+    match value.0 {
+        #[cfg(not(feature = "no_index"))]
+        Union::Array(ref mut arr, _) => {
+            for item in arr.iter_mut() {
+                *item = decode_custom_types(engine, crate::stdlib::mem::take(item))?;
+            }
+        }
+        Union::Map(ref mut map, _) if map.len() == 2 && map.contains_key(TYPE_TAG) => {
+            let tag = map
+                .get(TYPE_TAG)
+                .and_then(|tag| tag.read_lock::<ImmutableString>())
+                .map(|tag| tag.to_string())
+                .unwrap_or_default();
+
+            let hooks = engine
+                .custom_types_serde
+                .values()
+                .find(|hooks| engine.map_type_name(hooks.type_name) == tag)
+                .ok_or_else(|| {
+                    EvalAltResult::ErrorMismatchDataType(
+                        "".into(),
+                        format!("unknown custom type '{}' in serialized data", tag),
+                        Position::NONE,
+                    )
+                })?;
+
+            return match map.get(VALUE_TAG) {
+                Some(data) => (hooks.deserialize)(data),
+                None => EvalAltResult::ErrorMismatchDataType(
+                    "".into(),
+                    format!("missing '{}' for custom type '{}'", VALUE_TAG, tag),
+                    Position::NONE,
+                )
+                .into(),
+            };
+        }
+        Union::Map(ref mut map, _) => {
+            for (_, item) in map.iter_mut() {
+                *item = decode_custom_types(engine, crate::stdlib::mem::take(item))?;
+            }
+        }
+        _ => (),
+    }
+
+    Ok(value)
+}
@@ -2,7 +2,7 @@
 
 use super::str::ImmutableStringDeserializer;
 use crate::dynamic::Union;
-use crate::stdlib::{any::type_name, boxed::Box, fmt, string::ToString};
+use crate::stdlib::{any::type_name, boxed::Box, fmt, format, string::ToString};
 use crate::{Dynamic, EvalAltResult, ImmutableString, LexError, Position};
 use serde::de::{
     DeserializeSeed, Deserializer, Error, IntoDeserializer, MapAccess, SeqAccess, Visitor,
@@ -159,7 +159,15 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
             Union::Variant(value, _) if value.is::<u64>() => self.deserialize_u64(visitor),
             Union::Variant(value, _) if value.is::<u128>() => self.deserialize_u128(visitor),
 
-            Union::Variant(_, _) => self.type_error(),
+            Union::Variant(value, _) => EvalAltResult::ErrorMismatchDataType(
+                "".into(),
+                format!(
+                    "custom type '{}' cannot be serialized (register it via `Engine::register_type_serde`)",
+                    value.type_name()
+                ),
+                Position::NONE,
+            )
+            .into(),
 
             #[cfg(not(feature = "no_closure"))]
             Union::Shared(_, _) => self.type_error(),
@@ -160,6 +160,7 @@ impl From<&crate::module::FuncInfo> for FnMetadata {
     }
 }
 
+#[cfg(not(feature = "no_function"))]
 impl From<crate::ScriptFnMetadata<'_>> for FnMetadata {
     fn from(info: crate::ScriptFnMetadata) -> Self {
         Self {
@@ -244,6 +245,7 @@ impl Engine {
             .map(|f| f.into())
             .for_each(|info| global.functions.push(info));
 
+        #[cfg(not(feature = "no_function"))]
         if let Some(ast) = ast {
             ast.iter_functions()
                 .map(|f| f.into())
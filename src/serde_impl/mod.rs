@@ -4,5 +4,8 @@ pub mod de;
 pub mod ser;
 mod str;
 
+#[cfg(not(feature = "no_object"))]
+pub mod custom;
+
 #[cfg(feature = "metadata")]
 pub mod metadata;
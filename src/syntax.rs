@@ -1,6 +1,7 @@
 //! Module implementing custom syntax for [`Engine`].
 
 use crate::ast::Expr;
+use crate::dynamic::{AccessMode, Variant};
 use crate::engine::EvalContext;
 use crate::fn_native::SendSync;
 use crate::stdlib::{boxed::Box, format, string::ToString};
@@ -13,6 +14,8 @@ use crate::{
 pub const MARKER_EXPR: &str = "$expr$";
 pub const MARKER_BLOCK: &str = "$block$";
 pub const MARKER_IDENT: &str = "$ident$";
+/// Prefix of a marker for a list of expressions, followed by the separator symbol (e.g. `$expr$*,`).
+pub const MARKER_EXPR_LIST: &str = "$expr$*";
 
 /// A general expression evaluation trait object.
 #[cfg(not(feature = "sync"))]
@@ -26,11 +29,62 @@ pub type FnCustomSyntaxEval =
 /// A general expression parsing trait object.
 #[cfg(not(feature = "sync"))]
 pub type FnCustomSyntaxParse =
-    dyn Fn(&[ImmutableString], &str) -> Result<Option<ImmutableString>, ParseError>;
+    dyn Fn(&[ImmutableString], &str, &ParseContext) -> Result<Option<ImmutableString>, ParseError>;
 /// A general expression parsing trait object.
 #[cfg(feature = "sync")]
-pub type FnCustomSyntaxParse =
-    dyn Fn(&[ImmutableString], &str) -> Result<Option<ImmutableString>, ParseError> + Send + Sync;
+pub type FnCustomSyntaxParse = dyn Fn(&[ImmutableString], &str, &ParseContext) -> Result<Option<ImmutableString>, ParseError>
+    + Send
+    + Sync;
+
+/// Context of the parser, passed to a custom syntax parsing function registered via
+/// [`Engine::register_custom_syntax_with_context_raw`].
+#[derive(Debug, Clone, Copy)]
+pub struct ParseContext<'a> {
+    pub(crate) stack: &'a [(ImmutableString, AccessMode, Position)],
+    pub(crate) is_function_scope: bool,
+    pub(crate) is_breakable: bool,
+}
+
+impl<'a> ParseContext<'a> {
+    /// Get an iterator over the names of all variables declared so far, innermost first.
+    ///
+    /// Only variables declared within the script are included, not those in a [`Scope`][crate::Scope]
+    /// that the script is compiled with.
+    #[inline(always)]
+    pub fn iter_vars(&self) -> impl Iterator<Item = &'a str> {
+        self.stack
+            .iter()
+            .rev()
+            .map(|(name, _, _)| name.as_str())
+            .filter(|name| !name.is_empty())
+    }
+    /// Has a variable with a particular name been declared?
+    #[inline(always)]
+    pub fn contains_var(&self, name: &str) -> bool {
+        self.iter_vars().any(|v| v == name)
+    }
+    /// Is the variable with a particular name declared as a constant?
+    ///
+    /// Returns [`None`] if the variable has not been declared.
+    #[inline(always)]
+    pub fn is_constant(&self, name: &str) -> Option<bool> {
+        self.stack
+            .iter()
+            .rev()
+            .find(|(n, _, _)| n.as_str() == name)
+            .map(|(_, access, _)| access.is_read_only())
+    }
+    /// Is the custom syntax being parsed inside a function?
+    #[inline(always)]
+    pub fn is_function_scope(&self) -> bool {
+        self.is_function_scope
+    }
+    /// Is the custom syntax being parsed inside a loop?
+    #[inline(always)]
+    pub fn is_in_loop(&self) -> bool {
+        self.is_breakable
+    }
+}
 
 /// An expression sub-tree in an [`AST`][crate::AST].
 #[derive(Debug, Clone)]
@@ -49,6 +103,12 @@ impl Expression<'_> {
     pub fn get_variable_name(&self) -> Option<&str> {
         self.0.get_variable_access(true)
     }
+    /// If this expression is a literal constant (e.g. the number of items captured by a
+    /// `$expr$*` list in a custom syntax) of a particular type, return it.  Otherwise [`None`].
+    #[inline(always)]
+    pub fn get_literal_value<T: Variant>(&self) -> Option<T> {
+        self.0.get_constant_value()?.try_cast()
+    }
     /// Get the expression.
     #[inline(always)]
     pub(crate) fn expr(&self) -> &Expr {
@@ -99,6 +159,10 @@ impl Engine {
     /// Register a custom syntax with the [`Engine`].
     ///
     /// * `keywords` holds a slice of strings that define the custom syntax.  
+    ///   A `$expr$*` marker followed by a separator symbol (e.g. `$expr$*,`) matches a list of
+    ///   zero or more expressions separated by that symbol.  It provides, in `inputs`, the number
+    ///   of items (an [`INT`][crate::INT] literal, see [`Expression::get_literal_value`]),
+    ///   followed by the items themselves.
     /// * `new_vars` is the number of new variables declared by this custom syntax, or the number of variables removed (if negative).
     /// * `func` is the implementation function.
     pub fn register_custom_syntax<S: AsRef<str> + Into<ImmutableString>>(
@@ -126,14 +190,33 @@ impl Engine {
             let seg = match s {
                 // Markers not in first position
                 MARKER_IDENT | MARKER_EXPR | MARKER_BLOCK if !segments.is_empty() => s.into(),
+                // List of expressions with a separator symbol not in first position
+                s if !segments.is_empty() && s.starts_with(MARKER_EXPR_LIST) => {
+                    let separator = &s[MARKER_EXPR_LIST.len()..];
+
+                    match Token::lookup_from_syntax(separator) {
+                        Some(t) if !t.is_keyword() && !t.is_reserved() => s.into(),
+                        _ => {
+                            return Err(LexError::ImproperSymbol(
+                                s.to_string(),
+                                format!(
+                                    "Improper separator for custom syntax at position #{}: '{}'",
+                                    segments.len() + 1,
+                                    s
+                                ),
+                            )
+                            .into_err(Position::NONE));
+                        }
+                    }
+                }
                 // Standard or reserved keyword/symbol not in first position
                 s if !segments.is_empty() && token.is_some() => {
                     // Make it a custom keyword/symbol if it is disabled or reserved
-                    if (self.disabled_symbols.contains(s)
+                    if (self.disabled_symbols.contains_key(s)
                         || matches!(token, Some(Token::Reserved(_))))
                         && !self.custom_keywords.contains_key(s)
                     {
-                        self.custom_keywords.insert(s.into(), None);
+                        self.custom_keywords.insert(s.into(), Default::default());
                     }
                     s.into()
                 }
@@ -158,11 +241,11 @@ impl Engine {
                 // Identifier in first position
                 s if segments.is_empty() && is_valid_identifier(s.chars()) => {
                     // Make it a custom keyword/symbol if it is disabled or reserved
-                    if (self.disabled_symbols.contains(s)
+                    if (self.disabled_symbols.contains_key(s)
                         || matches!(token, Some(Token::Reserved(_))))
                         && !self.custom_keywords.contains_key(s)
                     {
-                        self.custom_keywords.insert(s.into(), None);
+                        self.custom_keywords.insert(s.into(), Default::default());
                     }
                     s.into()
                 }
@@ -192,10 +275,10 @@ impl Engine {
         // The first keyword is the discriminator
         let key = segments[0].clone();
 
-        self.register_custom_syntax_raw(
+        self.register_custom_syntax_with_context_raw(
             key,
             // Construct the parsing function
-            move |stream, _| {
+            move |stream, _, _| {
                 if stream.len() >= segments.len() {
                     Ok(None)
                 } else {
@@ -230,6 +313,45 @@ impl Engine {
         func: impl Fn(&mut EvalContext, &[Expression]) -> Result<Dynamic, Box<EvalAltResult>>
             + SendSync
             + 'static,
+    ) -> &mut Self {
+        self.register_custom_syntax_with_context_raw(
+            key,
+            move |stream, look_ahead, _| parse(stream, look_ahead),
+            new_vars,
+            func,
+        )
+    }
+    /// Register a custom syntax with the [`Engine`], with a parsing function that also receives
+    /// the [context][ParseContext] of the parser.
+    ///
+    /// ## WARNING - Low Level API
+    ///
+    /// This function is very low level.
+    ///
+    /// Same as [`Engine::register_custom_syntax_raw`], except that the parsing function can inspect
+    /// the variables declared so far, e.g. to reject an `$ident$` that does not refer to an
+    /// existing variable.
+    ///
+    /// * `new_vars` is the number of new variables declared by this custom syntax, or the number of variables removed (if negative).
+    /// * `parse` is the parsing function.
+    /// * `func` is the implementation function.
+    ///
+    /// All custom keywords must be manually registered via [`Engine::register_custom_operator`].
+    /// Otherwise, custom keywords won't be recognized.
+    pub fn register_custom_syntax_with_context_raw(
+        &mut self,
+        key: impl Into<ImmutableString>,
+        parse: impl Fn(
+                &[ImmutableString],
+                &str,
+                &ParseContext,
+            ) -> Result<Option<ImmutableString>, ParseError>
+            + SendSync
+            + 'static,
+        new_vars: isize,
+        func: impl Fn(&mut EvalContext, &[Expression]) -> Result<Dynamic, Box<EvalAltResult>>
+            + SendSync
+            + 'static,
     ) -> &mut Self {
         let syntax = CustomSyntax {
             parse: Box::new(parse),
@@ -146,6 +146,106 @@ impl fmt::Debug for Position {
     }
 }
 
+/// A span of text in the input script, from the [position][Position] of its first character to
+/// the [position][Position] of its last character.
+///
+/// Spans are not kept in the [`AST`][crate::AST] (which only records the start [position][Position]
+/// of each node, in order to keep its footprint small), but are calculated from the script text.
+/// See [`Engine::tokenize`] and [`Engine::span_at`].
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub struct Span {
+    /// Position of the first character.
+    start: Position,
+    /// Position of the last character.
+    end: Position,
+}
+
+impl Span {
+    /// A [`Span`] representing no text.
+    pub const NONE: Self = Self {
+        start: Position::NONE,
+        end: Position::NONE,
+    };
+
+    /// Create a new [`Span`] from the [position][Position] of its first character to the
+    /// [position][Position] of its last character.
+    ///
+    /// If `end` is [`Position::NONE`] or before `start`, the [`Span`] covers only `start`.
+    #[inline]
+    pub fn new(start: Position, end: Position) -> Self {
+        if start.is_none() {
+            Self::NONE
+        } else if end.is_none() || end < start {
+            Self { start, end: start }
+        } else {
+            Self { start, end }
+        }
+    }
+    /// Get the [position][Position] of the first character.
+    #[inline(always)]
+    pub fn start(self) -> Position {
+        self.start
+    }
+    /// Get the [position][Position] of the last character.
+    #[inline(always)]
+    pub fn end(self) -> Position {
+        self.end
+    }
+    /// Is there no [`Span`]?
+    #[inline(always)]
+    pub fn is_none(self) -> bool {
+        self.start.is_none()
+    }
+    /// Create a [`Span`] covering both this [`Span`] and another.
+    #[inline]
+    pub fn merge(self, other: Self) -> Self {
+        if self.is_none() {
+            other
+        } else if other.is_none() {
+            self
+        } else {
+            Self {
+                start: self.start.min(other.start),
+                end: self.end.max(other.end),
+            }
+        }
+    }
+}
+
+impl From<Position> for Span {
+    #[inline(always)]
+    fn from(pos: Position) -> Self {
+        Self::new(pos, pos)
+    }
+}
+
+impl fmt::Display for Span {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_none() {
+            write!(f, "none")
+        } else {
+            write!(f, "{} to {}", self.start, self.end)
+        }
+    }
+}
+
+/// Associativity of a binary operator, deciding how operators of the same precedence are grouped.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Associativity {
+    /// `a op b op c` is `(a op b) op c`.
+    Left,
+    /// `a op b op c` is `a op (b op c)`.
+    Right,
+}
+
+impl Default for Associativity {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::Left
+    }
+}
+
 /// _(INTERNALS)_ A Rhai language token.
 /// Exported under the `internals` feature only.
 ///
@@ -167,6 +267,8 @@ pub enum Token {
     CharConstant(char),
     /// A string constant.
     StringConstant(String),
+    /// A loop label, i.e. `'`label.
+    Label(String),
     /// `{`
     LeftBrace,
     /// `}`
@@ -207,12 +309,26 @@ pub enum Token {
     DoubleColon,
     /// `=>`
     DoubleArrow,
+    /// `?`
+    QuestionMark,
+    /// `?.`
+    Elvis,
+    /// `?[`
+    QuestionBracket,
+    /// `??`
+    DoubleQuestion,
     /// `_`
     Underscore,
     /// `,`
     Comma,
     /// `.`
     Period,
+    /// `..`
+    DoublePeriod,
+    /// `...`
+    TriplePeriod,
+    /// `..=`
+    InclusiveRange,
     /// `#{`
     MapStart,
     /// `=`
@@ -243,6 +359,8 @@ pub enum Token {
     For,
     /// `in`
     In,
+    /// `!in`
+    NotIn,
     /// `<`
     LessThan,
     /// `>`
@@ -349,6 +467,7 @@ impl Token {
             FloatConstant(f) => f.to_string().into(),
             StringConstant(_) => "string".into(),
             CharConstant(c) => c.to_string().into(),
+            Label(s) => format!("'{}", s).into(),
             Identifier(s) => s.clone().into(),
             Reserved(s) => s.clone().into(),
             Custom(s) => s.clone().into(),
@@ -372,9 +491,16 @@ impl Token {
                 Colon => ":",
                 DoubleColon => "::",
                 DoubleArrow => "=>",
+                QuestionMark => "?",
+                Elvis => "?.",
+                QuestionBracket => "?[",
+                DoubleQuestion => "??",
                 Underscore => "_",
                 Comma => ",",
                 Period => ".",
+                DoublePeriod => "..",
+                TriplePeriod => "...",
+                InclusiveRange => "..=",
                 MapStart => "#{",
                 Equals => "=",
                 True => "true",
@@ -390,6 +516,7 @@ impl Token {
                 Loop => "loop",
                 For => "for",
                 In => "in",
+                NotIn => "!in",
                 LessThan => "<",
                 GreaterThan => ">",
                 Bang => "!",
@@ -461,9 +588,16 @@ impl Token {
             ":" => Colon,
             "::" => DoubleColon,
             "=>" => DoubleArrow,
+            "?" => QuestionMark,
+            "?." => Elvis,
+            "?[" => QuestionBracket,
+            "??" => DoubleQuestion,
             "_" => Underscore,
             "," => Comma,
             "." => Period,
+            ".." => DoublePeriod,
+            "..." => TriplePeriod,
+            "..=" => InclusiveRange,
             "#{" => MapStart,
             "=" => Equals,
             "true" => True,
@@ -479,6 +613,7 @@ impl Token {
             "loop" => Loop,
             "for" => For,
             "in" => In,
+            "!in" => NotIn,
             "<" => LessThan,
             ">" => GreaterThan,
             "!" => Bang,
@@ -567,6 +702,7 @@ impl Token {
             LeftParen        | // (-expr) - is unary
             // RightParen    | (expr) - expr not unary & is closing
             LeftBracket      | // [-expr] - is unary
+            QuestionBracket  | // ?[-expr] - is unary
             // RightBracket  | [expr] - expr not unary & is closing
             Plus             |
             UnaryPlus        |
@@ -575,7 +711,10 @@ impl Token {
             Multiply         |
             Divide           |
             Comma            |
+            Colon            | // #{ prop: -expr }, cond ? expr : -expr - is unary
             Period           |
+            QuestionMark     |
+            DoubleQuestion   |
             Equals           |
             LessThan         |
             GreaterThan      |
@@ -610,6 +749,7 @@ impl Token {
             Throw            |
             PowerOf          |
             In               |
+            NotIn            |
             PowerOfAssign    => true,
 
             _ => false,
@@ -626,13 +766,17 @@ impl Token {
             | RightShiftAssign | AndAssign | OrAssign | XOrAssign | ModuloAssign
             | PowerOfAssign => 0,
 
+            QuestionMark => 10,
+
             Or | XOr | Pipe => 30,
 
+            DoubleQuestion => 40,
+
             And | Ampersand => 60,
 
             EqualsTo | NotEqualsTo => 90,
 
-            In => 110,
+            In | NotIn => 110,
 
             LessThan | LessThanEqualsTo | GreaterThan | GreaterThanEqualsTo => 130,
 
@@ -644,7 +788,7 @@ impl Token {
 
             LeftShift | RightShift => 210,
 
-            Period => 240,
+            Period | Elvis => 240,
 
             _ => 0,
         }
@@ -661,7 +805,10 @@ impl Token {
             | PowerOfAssign => true,
 
             // Property access binds to the right
-            Period => true,
+            Period | Elvis => true,
+
+            // Conditional expressions bind to the right
+            QuestionMark => true,
 
             _ => false,
         }
@@ -674,11 +821,13 @@ impl Token {
         match self {
             LeftBrace | RightBrace | LeftParen | RightParen | LeftBracket | RightBracket | Plus
             | UnaryPlus | Minus | UnaryMinus | Multiply | Divide | Modulo | PowerOf | LeftShift
-            | RightShift | SemiColon | Colon | DoubleColon | Comma | Period | MapStart | Equals
-            | LessThan | GreaterThan | LessThanEqualsTo | GreaterThanEqualsTo | EqualsTo
-            | NotEqualsTo | Bang | Pipe | Or | XOr | Ampersand | And | PlusAssign | MinusAssign
-            | MultiplyAssign | DivideAssign | LeftShiftAssign | RightShiftAssign | AndAssign
-            | OrAssign | XOrAssign | ModuloAssign | PowerOfAssign => true,
+            | RightShift | SemiColon | Colon | DoubleColon | QuestionMark | Elvis
+            | QuestionBracket | DoubleQuestion | Comma | Period | DoublePeriod | TriplePeriod
+            | InclusiveRange | MapStart | Equals | LessThan | GreaterThan | LessThanEqualsTo
+            | GreaterThanEqualsTo | EqualsTo | NotEqualsTo | Bang | NotIn | Pipe | Or | XOr
+            | Ampersand | And | PlusAssign | MinusAssign | MultiplyAssign | DivideAssign
+            | LeftShiftAssign | RightShiftAssign | AndAssign | OrAssign | XOrAssign
+            | ModuloAssign | PowerOfAssign => true,
 
             _ => false,
         }
@@ -825,6 +974,64 @@ pub fn parse_string_literal(
                 escape.clear();
                 result.push('\r');
             }
+            // \0
+            '0' if !escape.is_empty() => {
+                escape.clear();
+                result.push('\0');
+            }
+            // \u{?...} - variable length hex
+            'u' if !escape.is_empty() && stream.peek_next() == Some('{') => {
+                let mut seq = escape.clone();
+                escape.clear();
+                seq.push('u');
+                seq.push(stream.get_next().unwrap());
+                pos.advance();
+
+                let mut out_val: u32 = 0;
+
+                loop {
+                    let c = stream.get_next().ok_or_else(|| {
+                        (
+                            LERR::MalformedEscapeSequence(seq.iter().cloned().collect()),
+                            *pos,
+                        )
+                    })?;
+
+                    seq.push(c);
+                    pos.advance();
+
+                    if c == '}' {
+                        break;
+                    }
+
+                    // At most 6 hex digits
+                    match c.to_digit(16) {
+                        Some(digit) if seq.len() <= 9 => out_val = out_val * 16 + digit,
+                        _ => {
+                            return Err((
+                                LERR::MalformedEscapeSequence(seq.iter().cloned().collect()),
+                                *pos,
+                            ))
+                        }
+                    }
+                }
+
+                // \u{} - no digits
+                if seq.len() == 4 {
+                    return Err((
+                        LERR::MalformedEscapeSequence(seq.into_iter().collect()),
+                        *pos,
+                    ));
+                }
+
+                // Surrogates and values beyond 0x10FFFF are not valid characters
+                result.push(char::from_u32(out_val).ok_or_else(|| {
+                    (
+                        LERR::MalformedEscapeSequence(seq.into_iter().collect()),
+                        *pos,
+                    )
+                })?);
+            }
             // \x??, \u????, \U????????
             ch @ 'x' | ch @ 'u' | ch @ 'U' if !escape.is_empty() => {
                 let mut seq = escape.clone();
@@ -876,6 +1083,33 @@ pub fn parse_string_literal(
             // Close wrapper
             ch if enclosing_char == ch && escape.is_empty() => break,
 
+            // \{new-line} - line continuation, skipping leading white-space on the next line
+            '\r' | '\n'
+                if !escape.is_empty()
+                    && enclosing_char == '"'
+                    && (next_char == '\n' || stream.peek_next() == Some('\n')) =>
+            {
+                escape.clear();
+
+                if next_char == '\r' {
+                    stream.get_next();
+                    pos.advance();
+                }
+                pos.new_line();
+
+                while let Some(ch) = stream.peek_next() {
+                    if !ch.is_whitespace() {
+                        break;
+                    }
+                    stream.get_next();
+                    pos.advance();
+
+                    if ch == '\n' {
+                        pos.new_line();
+                    }
+                }
+            }
+
             // Unknown escape sequence
             ch if !escape.is_empty() => {
                 escape.push(ch);
@@ -909,6 +1143,63 @@ pub fn parse_string_literal(
     Ok(s)
 }
 
+/// _(INTERNALS)_ Parse a raw string literal (e.g. `r"..."` or `r#"..."#`) after the opening `"`,
+/// where `hashes` is the number of `#` between the `r` and the opening `"`.
+/// Exported under the `internals` feature only.
+///
+/// No escape sequences are processed and new-lines are allowed.
+/// An unterminated raw string is reported at `start`, the position of the opening delimiter.
+///
+/// ## WARNING
+///
+/// This type is volatile and may change.
+pub fn parse_raw_string_literal(
+    stream: &mut impl InputStream,
+    state: &mut TokenizeState,
+    pos: &mut Position,
+    start: Position,
+    hashes: usize,
+) -> Result<String, (LexError, Position)> {
+    let mut result = String::new();
+
+    loop {
+        let next_char = stream.get_next().ok_or((LERR::UnterminatedString, start))?;
+
+        pos.advance();
+
+        match next_char {
+            '"' => {
+                // Count the closing fence
+                let mut closing = 0;
+
+                while closing < hashes && stream.peek_next() == Some('#') {
+                    stream.get_next();
+                    pos.advance();
+                    closing += 1;
+                }
+
+                if closing == hashes {
+                    break;
+                }
+
+                result.push('"');
+                (0..closing).for_each(|_| result.push('#'));
+            }
+            '\n' => {
+                pos.new_line();
+                result.push('\n');
+            }
+            ch => result.push(ch),
+        }
+
+        if state.max_string_size > 0 && result.len() > state.max_string_size {
+            return Err((LexError::StringTooLong(state.max_string_size), *pos));
+        }
+    }
+
+    Ok(result)
+}
+
 /// Consume the next character.
 #[inline(always)]
 fn eat_next(stream: &mut impl InputStream, pos: &mut Position) -> Option<char> {
@@ -1182,6 +1473,55 @@ fn get_next_token_inner(
                 }
             }
 
+            // r" - raw string literal
+            ('r', '"') => {
+                eat_next(stream, pos);
+
+                return parse_raw_string_literal(stream, state, pos, start_pos, 0).map_or_else(
+                    |err| Some((Token::LexError(err.0), err.1)),
+                    |out| Some((Token::StringConstant(out), start_pos)),
+                );
+            }
+            // r#" - raw string literal with fences
+            ('r', '#') => {
+                stream.get_next();
+
+                match stream.peek_next() {
+                    Some('"') | Some('#') => {
+                        pos.advance();
+                        let mut hashes = 1;
+
+                        while stream.peek_next() == Some('#') {
+                            eat_next(stream, pos);
+                            hashes += 1;
+                        }
+
+                        if stream.peek_next() != Some('"') {
+                            return Some((
+                                Token::LexError(LERR::ImproperSymbol(
+                                    format!("r{}", "#".repeat(hashes)),
+                                    "Expecting '\"' to start a raw string".to_string(),
+                                )),
+                                start_pos,
+                            ));
+                        }
+
+                        eat_next(stream, pos);
+
+                        return parse_raw_string_literal(stream, state, pos, start_pos, hashes)
+                            .map_or_else(
+                                |err| Some((Token::LexError(err.0), err.1)),
+                                |out| Some((Token::StringConstant(out), start_pos)),
+                            );
+                    }
+                    // Not a raw string - the identifier 'r' followed by '#'
+                    _ => {
+                        stream.unread('#');
+                        return get_identifier(stream, pos, start_pos, c);
+                    }
+                }
+            }
+
             // letter or underscore ...
             #[cfg(not(feature = "unicode-xid-ident"))]
             ('a'..='z', _) | ('_', _) | ('A'..='Z', _) => {
@@ -1207,6 +1547,41 @@ fn get_next_token_inner(
                     start_pos,
                 ))
             }
+            // 'label - loop label
+            ('\'', ch) if is_id_first_alphabetic(ch) || ch == '_' => {
+                let mut result: StaticVec<char> = Default::default();
+
+                while let Some(next_char) = stream.peek_next() {
+                    match next_char {
+                        x if is_id_continue(x) => {
+                            result.push(x);
+                            eat_next(stream, pos);
+                        }
+                        _ => break,
+                    }
+                }
+
+                // 'x' - character literal
+                if stream.peek_next() == Some('\'') {
+                    eat_next(stream, pos);
+
+                    return Some(if result.len() == 1 {
+                        (Token::CharConstant(result[0]), start_pos)
+                    } else {
+                        let result = result.into_iter().collect();
+                        (Token::LexError(LERR::MalformedChar(result)), start_pos)
+                    });
+                }
+
+                let is_valid_identifier = is_valid_identifier(result.iter().cloned());
+                let label: String = result.into_iter().collect();
+
+                return Some(if is_valid_identifier {
+                    (Token::Label(label), start_pos)
+                } else {
+                    (Token::LexError(LERR::MalformedIdentifier(label)), start_pos)
+                });
+            }
             ('\'', _) => {
                 return Some(parse_string_literal(stream, state, pos, '\'').map_or_else(
                     |err| (Token::LexError(err.0), err.1),
@@ -1361,11 +1736,16 @@ fn get_next_token_inner(
             ('.', '.') => {
                 eat_next(stream, pos);
 
-                if stream.peek_next() == Some('.') {
-                    eat_next(stream, pos);
-                    return Some((Token::Reserved("...".into()), start_pos));
-                } else {
-                    return Some((Token::Reserved("..".into()), start_pos));
+                match stream.peek_next() {
+                    Some('.') => {
+                        eat_next(stream, pos);
+                        return Some((Token::TriplePeriod, start_pos));
+                    }
+                    Some('=') => {
+                        eat_next(stream, pos);
+                        return Some((Token::InclusiveRange, start_pos));
+                    }
+                    _ => return Some((Token::DoublePeriod, start_pos)),
                 }
             }
             ('.', _) => return Some((Token::Period, start_pos)),
@@ -1494,6 +1874,20 @@ fn get_next_token_inner(
             }
             ('~', _) => return Some((Token::PowerOf, start_pos)),
 
+            ('?', '.') => {
+                eat_next(stream, pos);
+                return Some((Token::Elvis, start_pos));
+            }
+            ('?', '[') => {
+                eat_next(stream, pos);
+                return Some((Token::QuestionBracket, start_pos));
+            }
+            ('?', '?') => {
+                eat_next(stream, pos);
+                return Some((Token::DoubleQuestion, start_pos));
+            }
+            ('?', _) => return Some((Token::QuestionMark, start_pos)),
+
             ('@', _) => return Some((Token::Reserved("@".into()), start_pos)),
 
             ('$', _) => return Some((Token::Reserved("$".into()), start_pos)),
@@ -1686,13 +2080,43 @@ pub struct TokenIterator<'a, 'e> {
     stream: MultiInputsStream<'a>,
     /// A processor function that maps a token to another.
     map: Option<fn(Token) -> Token>,
+    /// Is the previous token a `.`?
+    #[cfg(not(feature = "no_object"))]
+    after_period: bool,
+    /// A token read ahead of a `!` that is not followed by `in`.
+    pending: Option<(Token, Span)>,
 }
 
-impl<'a> Iterator for TokenIterator<'a, '_> {
-    type Item = (Token, Position);
+impl TokenIterator<'_, '_> {
+    /// Get the next token from the input stream, together with its [`Span`].
+    #[inline(always)]
+    fn next_token(&mut self) -> Option<(Token, Span)> {
+        // The current position is at the last character of the token
+        get_next_token(&mut self.stream, &mut self.state, &mut self.pos)
+            .map(|(token, start)| (token, Span::new(start, self.pos)))
+    }
+    /// Get the next token, together with its [`Span`].
+    fn next_with_span(&mut self) -> Option<(Token, Span)> {
+        let next = match self.pending.take() {
+            Some(token) => Some(token),
+            None => self.next_token(),
+        };
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let (token, pos) = match get_next_token(&mut self.stream, &mut self.state, &mut self.pos) {
+        // `!` followed by `in` is the `!in` operator
+        let next = match next {
+            Some((Token::Bang, pos)) if !self.engine.disabled_symbols.contains_key("in") => {
+                match self.next_token() {
+                    Some((Token::In, in_pos)) => Some((Token::NotIn, pos.merge(in_pos))),
+                    token => {
+                        self.pending = token;
+                        Some((Token::Bang, pos))
+                    }
+                }
+            }
+            next => next,
+        };
+
+        let (token, pos) = match next {
             // {EOF}
             None => return None,
             // Reserved keyword/symbol
@@ -1729,8 +2153,11 @@ impl<'a> Iterator for TokenIterator<'a, '_> {
                     let msg = format!("'{}' is a reserved symbol", token);
                     Token::LexError(LERR::ImproperSymbol(s, msg))
                 },
+                // `eval` as a method name (for compiled expressions) is allowed even when disabled.
+                #[cfg(not(feature = "no_object"))]
+                (KEYWORD_EVAL, false) if self.after_period => Token::Reserved(s),
                 // Reserved keyword that is not custom and disabled.
-                (token, false) if self.engine.disabled_symbols.contains(token) => {
+                (token, false) if self.engine.disabled_symbols.contains_key(token) => {
                     let msg = format!("reserved symbol '{}' is disabled", token);
                     Token::LexError(LERR::ImproperSymbol(s, msg))
                 },
@@ -1743,7 +2170,7 @@ impl<'a> Iterator for TokenIterator<'a, '_> {
             }
             // Custom standard keyword/symbol - must be disabled
             Some((token, pos)) if self.engine.custom_keywords.contains_key(token.syntax().as_ref()) => {
-                if self.engine.disabled_symbols.contains(token.syntax().as_ref()) {
+                if self.engine.disabled_symbols.contains_key(token.syntax().as_ref()) {
                     // Disabled standard keyword/symbol
                     (Token::Custom(token.syntax().into()), pos)
                 } else {
@@ -1752,13 +2179,18 @@ impl<'a> Iterator for TokenIterator<'a, '_> {
                 }
             }
             // Disabled symbol
-            Some((token, pos)) if self.engine.disabled_symbols.contains(token.syntax().as_ref()) => {
+            Some((token, pos)) if self.engine.disabled_symbols.contains_key(token.syntax().as_ref()) => {
                 (Token::Reserved(token.syntax().into()), pos)
             }
             // Normal symbol
             Some(r) => r,
         };
 
+        #[cfg(not(feature = "no_object"))]
+        {
+            self.after_period = token == Token::Period;
+        }
+
         // Run the mapper, if any
         let token = if let Some(map) = self.map {
             map(token)
@@ -1770,6 +2202,16 @@ impl<'a> Iterator for TokenIterator<'a, '_> {
     }
 }
 
+impl<'a> Iterator for TokenIterator<'a, '_> {
+    type Item = (Token, Position);
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_span()
+            .map(|(token, span)| (token, span.start()))
+    }
+}
+
 impl Engine {
     /// Tokenize an input text stream.
     #[inline(always)]
@@ -1788,6 +2230,139 @@ impl Engine {
     ) -> TokenIterator<'a, 'e> {
         self.lex_raw(input, Some(map))
     }
+    /// Tokenize a script without compiling it, e.g. for syntax highlighting.
+    ///
+    /// Returns an iterator over the [kind][TokenKind], the [span][Span] and the source text of each
+    /// token.  Unlike compiling, comments are included.
+    ///
+    /// Custom keywords and disabled symbols registered with the [`Engine`] are respected.
+    /// Malformed input never causes the iterator to fail; lexing errors (e.g. unterminated strings)
+    /// and symbols that cannot be used (e.g. disabled or reserved symbols) are returned as
+    /// [`TokenKind::Error`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, TokenKind};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let tokens: Vec<_> = engine
+    ///     .tokenize("let x = 42; // the answer")
+    ///     .map(|(kind, _, text)| (kind, text))
+    ///     .collect();
+    ///
+    /// assert_eq!(tokens, [
+    ///     (TokenKind::Keyword, "let"),
+    ///     (TokenKind::Identifier, "x"),
+    ///     (TokenKind::Operator, "="),
+    ///     (TokenKind::Number, "42"),
+    ///     (TokenKind::Operator, ";"),
+    ///     (TokenKind::Comment, "// the answer"),
+    /// ]);
+    /// ```
+    pub fn tokenize<'s>(
+        &'s self,
+        script: &'s str,
+    ) -> impl Iterator<Item = (TokenKind, Span, &'s str)> + 's {
+        let mut stream = self.lex_raw(&[] as &[&str], None);
+        stream.stream.streams.push(script.chars().peekable());
+        stream.state.include_comments = true;
+
+        // Byte offsets of the start of each line
+        let lines: StaticVec<_> = crate::stdlib::iter::once(0)
+            .chain(script.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        crate::stdlib::iter::from_fn(move || stream.next_with_span())
+            .take_while(|(token, _)| *token != Token::EOF)
+            .map(move |(token, span)| {
+                (
+                    TokenKind::of(&token),
+                    span,
+                    source_text(script, &lines, span),
+                )
+            })
+    }
+    /// Get the [span][Span] of the text starting at a [position][Position] in a script,
+    /// e.g. the [position][Position] of an error.
+    ///
+    /// This is the span of the token at that position.  If the token is an opening bracket, or a
+    /// function name followed by its arguments, the span extends to the matching closing bracket.
+    ///
+    /// Returns [`Span::NONE`] if no token starts at that position.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, Position};
+    ///
+    /// let engine = Engine::new();
+    /// let script = "let x = 40;\nx + foo(2, [3])";
+    ///
+    /// let span = engine.span_at(script, Position::new(2, 5));
+    /// assert_eq!(span.start(), Position::new(2, 5));
+    /// assert_eq!(span.end(), Position::new(2, 15));
+    ///
+    /// let span = engine.span_at(script, Position::new(1, 9));
+    /// assert_eq!(span.end(), Position::new(1, 10));
+    /// ```
+    pub fn span_at(&self, script: &str, pos: Position) -> Span {
+        let mut stream = self.lex_raw(&[] as &[&str], None);
+        stream.stream.streams.push(script.chars().peekable());
+
+        let mut tokens = crate::stdlib::iter::from_fn(move || stream.next_with_span())
+            .take_while(|(token, _)| *token != Token::EOF)
+            .skip_while(|(_, span)| span.start() < pos)
+            .peekable();
+
+        let (token, mut span) = match tokens.next() {
+            Some((token, span)) if span.start() == pos => (token, span),
+            _ => return Span::NONE,
+        };
+
+        let is_open = |token: &Token| {
+            matches!(
+                token,
+                Token::LeftParen
+                    | Token::LeftBracket
+                    | Token::QuestionBracket
+                    | Token::LeftBrace
+                    | Token::MapStart
+            )
+        };
+
+        let mut next = match TokenKind::of(&token) {
+            _ if is_open(&token) => Some((token, span)),
+            // A function name followed by its arguments
+            TokenKind::Identifier | TokenKind::Keyword => match tokens.peek() {
+                Some((Token::LeftParen, _)) => tokens.next(),
+                _ => return span,
+            },
+            _ => return span,
+        };
+
+        // Extend the span to the matching closing bracket, if any
+        let mut level = 0_usize;
+
+        while let Some((token, token_span)) = next {
+            span = span.merge(token_span);
+
+            match token {
+                token if is_open(&token) => level += 1,
+                Token::RightParen | Token::RightBracket | Token::RightBrace => level -= 1,
+                _ => (),
+            }
+
+            if level == 0 {
+                break;
+            }
+
+            next = tokens.next();
+        }
+
+        span
+    }
     /// Tokenize an input text stream with an optional mapping function.
     #[inline]
     fn lex_raw<'a, 'e>(
@@ -1815,6 +2390,74 @@ impl Engine {
                 index: 0,
             },
             map,
+            #[cfg(not(feature = "no_object"))]
+            after_period: false,
+            pending: None,
+        }
+    }
+}
+
+/// Get the text in a script covered by a [`Span`].
+fn source_text<'s>(script: &'s str, lines: &[usize], span: Span) -> &'s str {
+    let char_at = |line: Option<usize>, position: Option<usize>| {
+        let start = *lines.get(line?.checked_sub(1)?)?;
+        script[start..]
+            .char_indices()
+            .nth(position?.checked_sub(1)?)
+            .map(|(index, ch)| (start + index, ch))
+    };
+
+    let start = match char_at(span.start().line(), span.start().position()) {
+        Some((index, _)) => index,
+        None => return "",
+    };
+
+    let end = match char_at(span.end().line(), span.end().position()) {
+        Some((index, ch)) if index >= start => index + ch.len_utf8(),
+        _ => return "",
+    };
+
+    &script[start..end]
+}
+
+/// Kind of a token returned by [`Engine::tokenize`], e.g. for syntax highlighting.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum TokenKind {
+    /// A standard or custom keyword (e.g. `let`, `switch`, `true`).
+    Keyword,
+    /// An identifier (e.g. a variable or function name) or a loop label.
+    Identifier,
+    /// An integer or floating-point number.
+    Number,
+    /// A string literal.
+    String,
+    /// A character literal.
+    Char,
+    /// A comment, including doc-comments.
+    Comment,
+    /// An operator or a punctuation symbol (e.g. `+`, `(`, `;`).
+    Operator,
+    /// A lexing error (e.g. an unterminated string) or a symbol that cannot be used
+    /// (e.g. a disabled or reserved symbol).
+    Error,
+}
+
+impl TokenKind {
+    /// Get the kind of a [`Token`].
+    fn of(token: &Token) -> Self {
+        match token {
+            Token::IntegerConstant(_) => Self::Number,
+            #[cfg(not(feature = "no_float"))]
+            Token::FloatConstant(_) => Self::Number,
+            Token::StringConstant(_) => Self::String,
+            Token::CharConstant(_) => Self::Char,
+            Token::Identifier(_) | Token::Label(_) => Self::Identifier,
+            Token::Custom(_) => Self::Keyword,
+            Token::Comment(_) => Self::Comment,
+            Token::LexError(_) | Token::Reserved(_) => Self::Error,
+            token if is_valid_identifier(token.syntax().chars()) => Self::Keyword,
+            _ => Self::Operator,
         }
     }
 }
@@ -0,0 +1,244 @@
+//! Module defining the typed array, an [`Array`] constrained to a single element type.
+
+#![cfg(not(feature = "no_index"))]
+
+use crate::dynamic::{map_std_type_name, Variant};
+use crate::stdlib::{
+    any::{type_name, TypeId},
+    boxed::Box,
+    string::ToString,
+    vec::IntoIter,
+};
+use crate::{Array, Dynamic, EvalAltResult, FnPtr, ImmutableString, Position, INT};
+
+#[cfg(not(feature = "no_float"))]
+use crate::FLOAT;
+
+#[cfg(not(feature = "no_object"))]
+use crate::Map;
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(target_arch = "wasm32"))]
+use crate::stdlib::time::Instant;
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+
+/// An [`Array`] whose elements are all of the same type.
+///
+/// Every mutation that adds an element is validated against the element type, so a
+/// [`TypedArray`] handed back to the host can be used without checking each element again.
+///
+/// Not available under the `no_index` feature.
+///
+/// # Example
+///
+/// ```
+/// use rhai::{Dynamic, TypedArray, INT};
+///
+/// let mut array = TypedArray::new::<INT>();
+///
+/// array.push(Dynamic::from(42 as INT))?;
+/// assert!(array.push(Dynamic::from(true)).is_err());
+///
+/// assert_eq!(array.len(), 1);
+/// assert_eq!(array.element_type_name(), "i64");
+/// # Ok::<(), Box<rhai::EvalAltResult>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct TypedArray {
+    /// [`TypeId`] of the element type.
+    type_id: TypeId,
+    /// Friendly name of the element type.
+    type_name: &'static str,
+    /// The elements.
+    array: Array,
+}
+
+impl TypedArray {
+    /// Create a new, empty [`TypedArray`] holding elements of type `T`.
+    #[inline(always)]
+    pub fn new<T: Variant + Clone>() -> Self {
+        let mut type_id = TypeId::of::<T>();
+
+        // Strings are always stored as ImmutableString
+        if type_id == TypeId::of::<crate::stdlib::string::String>() {
+            type_id = TypeId::of::<ImmutableString>();
+        }
+
+        Self {
+            type_id,
+            type_name: map_std_type_name(type_name::<T>()),
+            array: Default::default(),
+        }
+    }
+    /// Create a new, empty [`TypedArray`] from the name of the element type,
+    /// as returned by the `type_of` function.
+    ///
+    /// Returns [`None`] if the name is not that of a standard type.
+    pub fn new_by_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "()" => Self::new::<()>(),
+            "bool" => Self::new::<bool>(),
+            "char" => Self::new::<char>(),
+            "string" => Self::new::<ImmutableString>(),
+            "array" => Self::new::<Array>(),
+            "Fn" => Self::new::<FnPtr>(),
+            #[cfg(not(feature = "no_object"))]
+            "map" => Self::new::<Map>(),
+            #[cfg(not(feature = "no_std"))]
+            "timestamp" => Self::new::<Instant>(),
+            _ if name == type_name::<INT>() => Self::new::<INT>(),
+            #[cfg(not(feature = "no_float"))]
+            _ if name == type_name::<FLOAT>() => Self::new::<FLOAT>(),
+            _ => return None,
+        })
+    }
+    /// Create a [`TypedArray`] holding elements of type `T` from an [`Array`],
+    /// checking that every element is of type `T`.
+    pub fn from_array<T: Variant + Clone>(array: Array) -> Result<Self, Box<EvalAltResult>> {
+        let mut typed = Self::new::<T>();
+        typed.append(array)?;
+        Ok(typed)
+    }
+    /// Create a [`TypedArray`] with the same element type as this one, holding the elements of an
+    /// [`Array`] which are already known to be of that type.
+    #[inline(always)]
+    pub(crate) fn with_array_unchecked(&self, array: Array) -> Self {
+        Self {
+            type_id: self.type_id,
+            type_name: self.type_name,
+            array,
+        }
+    }
+    /// Are the elements of this [`TypedArray`] of type `T`?
+    #[inline(always)]
+    pub fn is_of<T: Variant + Clone>(&self) -> bool {
+        Self::new::<T>().type_id == self.type_id
+    }
+    /// Get the [`TypeId`] of the element type.
+    #[inline(always)]
+    pub fn element_type_id(&self) -> TypeId {
+        self.type_id
+    }
+    /// Get the name of the element type.
+    #[inline(always)]
+    pub fn element_type_name(&self) -> &'static str {
+        self.type_name
+    }
+    /// Get a reference to the elements as an [`Array`].
+    #[inline(always)]
+    pub fn as_array(&self) -> &Array {
+        &self.array
+    }
+    /// Consume this [`TypedArray`] and return the elements as an untyped [`Array`].
+    #[inline(always)]
+    pub fn into_array(self) -> Array {
+        self.array
+    }
+    /// Get the number of elements.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.array.len()
+    }
+    /// Is this [`TypedArray`] empty?
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.array.is_empty()
+    }
+    /// Check that a value is of the element type, reporting `index` as its position on failure.
+    pub fn check_element(&self, index: usize, value: &Dynamic) -> Result<(), Box<EvalAltResult>> {
+        if value.type_id() == self.type_id {
+            Ok(())
+        } else {
+            EvalAltResult::ErrorArrayElementType(
+                self.type_name.to_string(),
+                map_std_type_name(value.type_name()).to_string(),
+                index,
+                Position::NONE,
+            )
+            .into()
+        }
+    }
+    /// Add a value to the end of this [`TypedArray`].
+    pub fn push(&mut self, value: Dynamic) -> Result<(), Box<EvalAltResult>> {
+        self.check_element(self.array.len(), &value)?;
+        self.array.push(value);
+        Ok(())
+    }
+    /// Insert a value at a position, shifting all elements after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of elements.
+    pub fn insert(&mut self, index: usize, value: Dynamic) -> Result<(), Box<EvalAltResult>> {
+        self.check_element(index, &value)?;
+        self.array.insert(index, value);
+        Ok(())
+    }
+    /// Replace the value at a position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: Dynamic) -> Result<(), Box<EvalAltResult>> {
+        self.check_element(index, &value)?;
+        self.array[index] = value;
+        Ok(())
+    }
+    /// Add all the values of an [`Array`] to the end of this [`TypedArray`].
+    ///
+    /// Nothing is added if any value is not of the element type.
+    pub fn append(&mut self, array: Array) -> Result<(), Box<EvalAltResult>> {
+        let len = self.array.len();
+        array
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, value)| self.check_element(len + i, value))?;
+        self.array.extend(array);
+        Ok(())
+    }
+    /// Replace a range of elements with the values of an [`Array`].
+    ///
+    /// Nothing is changed if any value is not of the element type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn splice(
+        &mut self,
+        start: usize,
+        len: usize,
+        replace: Array,
+    ) -> Result<(), Box<EvalAltResult>> {
+        replace
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, value)| self.check_element(start + i, value))?;
+        self.array.splice(start..start + len, replace);
+        Ok(())
+    }
+    /// Get a mutable reference to the elements as an [`Array`], bypassing validation.
+    #[inline(always)]
+    pub(crate) fn as_array_mut(&mut self) -> &mut Array {
+        &mut self.array
+    }
+}
+
+impl IntoIterator for TypedArray {
+    type Item = Dynamic;
+    type IntoIter = IntoIter<Dynamic>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.array.into_iter()
+    }
+}
+
+impl From<TypedArray> for Array {
+    #[inline(always)]
+    fn from(value: TypedArray) -> Self {
+        value.array
+    }
+}
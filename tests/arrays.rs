@@ -1,5 +1,6 @@
 #![cfg(not(feature = "no_index"))]
-use rhai::{Array, Engine, EvalAltResult, RegisterFn, INT};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, RegisterFn, TypedArray, INT};
+use std::any::type_name;
 
 #[test]
 fn test_arrays() -> Result<(), Box<EvalAltResult>> {
@@ -237,3 +238,498 @@ fn test_arrays_map_reduce() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_typed_arrays() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let int_type = type_name::<INT>();
+
+    assert_eq!(
+        engine.eval::<INT>(&format!(
+            r#"
+                let x = typed_array("{}");
+                push(x, 1);
+                x += 2;
+                insert(x, 0, 3);
+                append(x, [4, 5]);
+                x[1] = 42;
+
+                let sum = 0;
+                for n in x {{ sum += n; }}
+                sum + len(x) + x[0]
+            "#,
+            int_type
+        ))?,
+        64
+    );
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval::<String>(&format!(r#"let x = typed_array("{}"); x.type"#, int_type))?,
+        int_type
+    );
+    assert_eq!(
+        engine.eval::<String>(&format!(r#"type_of(typed_array("{}"))"#, int_type))?,
+        "typed_array"
+    );
+    assert_eq!(
+        engine
+            .eval::<Array>(r#"let x = typed_array("string", ["a", "b"]); to_array(x)"#)?
+            .len(),
+        2
+    );
+
+    assert!(matches!(
+        *engine.eval::<()>(&format!(r#"let x = typed_array("{}", [1, 2]); insert(x, 1, "hello");"#, int_type))
+            .expect_err("should error"),
+        EvalAltResult::ErrorArrayElementType(ref t, ref r, 1, _) if t == int_type && r == "string"
+    ));
+    assert!(matches!(
+        *engine.eval::<()>(r#"let x = typed_array("bool"); push(x, true); x[0] = 42;"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorArrayElementType(ref t, _, 0, _) if t == "bool"
+    ));
+    assert!(matches!(
+        *engine.eval::<()>(&format!(r#"let x = typed_array("{}", [1, 2]); splice(x, 1, 1, [3, 'x']);"#, int_type))
+            .expect_err("should error"),
+        EvalAltResult::ErrorArrayElementType(_, ref r, 2, _) if r == "char"
+    ));
+
+    // Functions taking function pointers read the elements like a normal array
+    #[cfg(not(feature = "no_function"))]
+    {
+        let script = format!(
+            r#"
+                let x = typed_array("{}", [1, 2, 3, 4]);
+                let y = filter(x, |v| v % 2 == 0);
+                push(x, 5);
+                [
+                    type_of(y), len(y), reduce(map(x, |v| v * 10), |sum, v| sum + v, || 0),
+                    index_of(x, |v| v > 2), some(x, |v| v > 4), all(x, |v| v > 0),
+                    reduce_rev(x, |s, v| s + to_string(v), || "")
+                ]
+            "#,
+            int_type
+        );
+        let result = engine.eval::<Array>(&script)?;
+        assert_eq!(result[0].clone().cast::<String>(), "typed_array");
+        assert_eq!(result[1].as_int().unwrap(), 2);
+        assert_eq!(result[2].as_int().unwrap(), 150);
+        assert_eq!(result[3].as_int().unwrap(), 2);
+        assert!(result[4].as_bool().unwrap());
+        assert!(result[5].as_bool().unwrap());
+        assert_eq!(result[6].clone().cast::<String>(), "54321");
+
+        // Filtering keeps the element type
+        #[cfg(not(feature = "no_object"))]
+        assert_eq!(
+            engine.eval::<String>(&format!(
+                r#"let x = typed_array("{}", [1, 2]); filter(x, |v| v > 1).type"#,
+                int_type
+            ))?,
+            int_type
+        );
+    }
+
+    // Concatenating keeps the element type and checks the new elements
+    assert_eq!(
+        engine.eval::<INT>(&format!(
+            r#"let x = typed_array("{}", [1]) + [2, 3]; len(x) + x[2]"#,
+            int_type
+        ))?,
+        6
+    );
+    assert!(matches!(
+        *engine
+            .eval::<()>(&format!(r#"let x = typed_array("{}") + [1, true];"#, int_type))
+            .expect_err("should error"),
+        EvalAltResult::ErrorArrayElementType(_, ref r, 1, _) if r == "bool"
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"typed_array("foo")"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorRuntime(_, _)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_typed_arrays_host() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_fn("make_ints", || TypedArray::new::<INT>());
+
+    let result = engine.eval::<Dynamic>("let x = make_ints(); push(x, 1); push(x, 2); x")?;
+
+    assert!(result.clone().try_cast_typed_array::<bool>().is_none());
+
+    let array = result
+        .try_cast_typed_array::<INT>()
+        .expect("should be a typed array of INT");
+
+    assert_eq!(array.len(), 2);
+    assert_eq!(
+        array
+            .into_iter()
+            .map(|v| v.cast::<INT>())
+            .collect::<Vec<_>>(),
+        [1, 2]
+    );
+
+    let mut array = TypedArray::from_array::<INT>(vec![1.into(), 2.into()])?;
+    assert!(TypedArray::from_array::<INT>(vec![1.into(), true.into()]).is_err());
+    assert!(array.push(Dynamic::from("x")).is_err());
+    assert_eq!(array.as_array().len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_arrays_repeat() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine
+            .eval::<Array>("[1, 2] * 3")?
+            .into_iter()
+            .map(|v| v.cast::<INT>())
+            .collect::<Vec<_>>(),
+        [1, 2, 1, 2, 1, 2]
+    );
+    assert!(engine.eval::<Array>("[0] * 0")?.is_empty());
+    assert_eq!(engine.eval::<INT>("let x = [0] * 8; x[7] = 1; len(x)")?, 8);
+
+    assert!(matches!(
+        *engine.eval::<Array>("[0] * -2").expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(msg, _) if msg.contains("negative")
+    ));
+
+    // Nothing is repeated for an empty array, however large the count
+    assert!(engine.eval::<Array>("[] * 1000000000")?.is_empty());
+
+    #[cfg(not(feature = "unchecked"))]
+    {
+        let mut engine = Engine::new();
+        engine.set_max_array_size(10);
+
+        assert_eq!(engine.eval::<Array>("[1, 2] * 5")?.len(), 10);
+        assert!(matches!(
+            *engine
+                .eval::<Array>("[1, 2] * 1000000000")
+                .expect_err("should error"),
+            EvalAltResult::ErrorDataTooLarge(msg, _) if msg.contains("limit 10")
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_arrays_null_propagation() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<()>("let x = (); x?[0]")?, ());
+    assert_eq!(engine.eval::<()>("let x = (); x?[0][1]")?, ());
+    assert_eq!(engine.eval::<INT>("let x = [1, [2, 3]]; x[1]?[1]")?, 3);
+    assert_eq!(engine.eval::<()>("let x = [1, ()]; x[1]?[0]")?, ());
+    assert_eq!(
+        engine.eval::<INT>("let x = true; let y = x ? [1] : [2]; y[0]")?,
+        1
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let x = [1, 2]; x?[2]")
+            .expect_err("should error"),
+        EvalAltResult::ErrorArrayBounds(_, _, _)
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_arrays_sort() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine
+            .eval::<Array>("let x = [3, 1, 4, 1, 5]; x.sort(); x")?
+            .into_iter()
+            .map(|v| v.cast::<INT>())
+            .collect::<Vec<_>>(),
+        [1, 1, 3, 4, 5]
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"let x = ["b", "c", "a"]; x.sort(); x[0] + x[1] + x[2]"#)?,
+        "abc"
+    );
+    assert_eq!(
+        engine.eval::<char>("let x = ['z', 'x', 'y']; x.sort(); x[2]")?,
+        'z'
+    );
+    assert!(engine.eval::<bool>("let x = [true, false]; x.sort(); x[1]")?);
+    #[cfg(not(feature = "no_float"))]
+    assert_eq!(
+        engine.eval::<rhai::FLOAT>("let x = [2.5, -1.0, 0.5]; x.sort(); x[0]")?,
+        -1.0
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"let x = [1, 2, "hello"]; x.sort()"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorArrayElementType(_, _, 2, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<()>("let x = [[2], [1]]; x.sort()")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        assert_eq!(
+            engine.eval::<INT>("let x = [1, 3, 2]; x.sort(|a, b| b - a); x[0]")?,
+            3
+        );
+        assert_eq!(
+            engine.eval::<INT>("let x = [1, 3, 2]; x.sort_by(|a, b| a > b); x[0]")?,
+            3
+        );
+        assert_eq!(
+            engine.eval::<String>(
+                r#"
+                    let x = [[1, "a"], [0, "b"], [1, "c"], [0, "d"]];
+                    x.sort(|a, b| a[0] < b[0]);
+                    let s = "";
+                    for item in x { s += item[1]; }
+                    s
+                "#
+            )?,
+            "bdac"
+        );
+
+        assert!(matches!(
+            *engine
+                .eval::<()>(r#"let x = [1, 2]; x.sort(|a, b| throw "oops")"#)
+                .expect_err("should error"),
+            EvalAltResult::ErrorInFunctionCall(fn_name, _, err, _)
+                if fn_name == "sort" && err.to_string().contains("oops")
+        ));
+        assert!(matches!(
+            *engine
+                .eval::<()>(r#"let x = [1, 2]; x.sort(|a, b| "oops")"#)
+                .expect_err("should error"),
+            EvalAltResult::ErrorInFunctionCall(fn_name, _, err, _)
+                if fn_name == "sort" && matches!(*err, EvalAltResult::ErrorMismatchOutputType(_, _, _))
+        ));
+
+        #[cfg(not(feature = "unchecked"))]
+        {
+            let mut engine = Engine::new();
+            engine.set_max_operations(50);
+
+            assert!(matches!(
+                *engine
+                    .eval::<()>("let x = []; for i in range(0, 30) { x.push(30 - i); } x.sort(|a, b| a - b)")
+                    .expect_err("should error"),
+                EvalAltResult::ErrorTooManyOperations(_)
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_arrays_zip_flatten_dedup() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let to_ints = |array: Array| {
+        array
+            .into_iter()
+            .map(|v| v.cast::<INT>())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let x = [1, 2, 3];
+                let y = x.zip(["a", 'b']);
+                "" + x.len() + " " + y.len() + " " + y[0][0] + y[0][1] + " " + y[1][0] + y[1][1]
+            "#
+        )?,
+        "3 2 1a 2b"
+    );
+    assert!(engine.eval::<Array>("[1, 2].zip([])")?.is_empty());
+
+    assert_eq!(
+        to_ints(engine.eval::<Array>("let x = [1, 2, 3]; x.flatten()")?),
+        [1, 2, 3]
+    );
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let x = [1, [2, [3, [4]]], "x"];
+                let y = x.flatten();
+                let z = x.flatten(2);
+                "" + x.len() + " " + y.len() + " " + z.len() + " " + type_of(z[3])
+            "#
+        )?,
+        "3 4 5 array"
+    );
+    assert_eq!(
+        to_ints(engine.eval::<Array>("[1, [[2], [3, [[4]]]], [], 5].flatten(0)")?),
+        [1, 2, 3, 4, 5]
+    );
+
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let x = [1, 1, "a", "a", 'a', 2, 2, (), (), 1];
+                x.dedup();
+                let s = "";
+                for v in x { s += v; s += ";"; }
+                s
+            "#
+        )?,
+        "1;a;a;2;;1;"
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        assert_eq!(
+            to_ints(engine.eval::<Array>("[1, 2, 3].zip([10, 20], |x, y| x + y)")?),
+            [11, 22]
+        );
+        assert_eq!(
+            to_ints(engine.eval::<Array>(
+                "let x = [1, 5, 12, 18, 23, 7]; x.dedup(|a, b| a / 10 == b / 10); x"
+            )?),
+            [1, 12, 23, 7]
+        );
+        assert!(matches!(
+            *engine
+                .eval::<()>(r#"let x = [1, 1]; x.dedup(|a, b| throw "oops")"#)
+                .expect_err("should error"),
+            EvalAltResult::ErrorInFunctionCall(fn_name, _, _, _) if fn_name == "dedup"
+        ));
+    }
+
+    #[cfg(not(feature = "unchecked"))]
+    {
+        let mut engine = Engine::new();
+        engine.set_max_array_size(5);
+
+        assert_eq!(
+            engine.eval::<Array>("[1, [2, 3], [4, 5]].flatten()")?.len(),
+            5
+        );
+        assert!(matches!(
+            *engine
+                .eval::<Array>("[1, [2, 3], [4, [5, 6]]].flatten(0)")
+                .expect_err("should error"),
+            EvalAltResult::ErrorDataTooLarge(_, _)
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_arrays_for_each() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    #[cfg(not(feature = "no_closure"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let weighted = 0;
+                let total = 0;
+
+                let done = for_each([1, 2, 3], |v, i| {
+                    weighted += v * (i + 1);
+                    total += v;
+                });
+
+                if !done { throw "not completed"; }
+
+                weighted + total * 1000
+            "#
+        )?,
+        6014
+    );
+
+    #[cfg(not(feature = "no_closure"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let count = 0;
+                let done = for_each([1, 2, 3, 4, 5], |v| {
+                    count += 1;
+                    v != 3
+                });
+                if done { -1 } else { count }
+            "
+        )?,
+        3
+    );
+
+    assert!(engine.eval::<bool>("for_each([1, 2, 3], |v| ())")?);
+    assert!(engine.eval::<bool>("for_each([1, 2, 3], |v| 0)")?);
+    assert!(!engine.eval::<bool>("for_each([1, 2, 3], |v, i| i < 1)")?);
+
+    let err = engine
+        .eval::<bool>(r#"for_each([1, 2, 3], |v, i| if i == 1 { throw "boom at " + v; })"#)
+        .expect_err("should error");
+
+    assert!(
+        matches!(*err, EvalAltResult::ErrorInFunctionCall(ref fn_name, _, _, _) if fn_name == "for_each")
+    );
+    assert!(err.to_string().contains("boom at 2"));
+
+    Ok(())
+}
+
+#[test]
+fn test_arrays_sum_mean() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("sum([1, 2, 3, 4])")?, 10);
+    assert_eq!(engine.eval::<INT>("sum([])")?, 0);
+    assert_eq!(engine.eval::<INT>("mean([1, 2, 3, 4])")?, 2);
+
+    // The mean of an empty array follows 'checked_div' and 'div_or'
+    engine.eval::<()>("mean([])")?;
+    assert_eq!(engine.eval::<INT>("mean([], -1)")?, -1);
+
+    #[cfg(not(feature = "no_float"))]
+    {
+        assert_eq!(engine.eval::<rhai::FLOAT>("sum([1, 2.5, 3])")?, 6.5);
+        assert_eq!(engine.eval::<rhai::FLOAT>("mean([1, 2, 3, 4.0])")?, 2.5);
+        assert_eq!(engine.eval::<rhai::FLOAT>("mean([], 0.0)")?, 0.0);
+    }
+
+    #[cfg(not(feature = "unchecked"))]
+    assert!(matches!(
+        *engine
+            .eval::<INT>(&format!("sum([{}, 1])", INT::MAX))
+            .expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(_, _)
+    ));
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"sum([1, "x"])"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorArrayElementType(_, _, 1, _)
+    ));
+
+    Ok(())
+}
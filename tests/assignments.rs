@@ -16,6 +16,61 @@ fn test_assignments() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_assignments_eval_once() -> Result<(), Box<EvalAltResult>> {
+    use rhai::RegisterFn;
+    use std::sync::{Arc, RwLock};
+
+    let mut engine = Engine::new();
+
+    let count = Arc::new(RwLock::new(0));
+    let counter = count.clone();
+
+    engine.register_fn("next", move || {
+        *counter.write().unwrap() += 1;
+        1 as INT
+    });
+
+    #[cfg(not(feature = "no_optimize"))]
+    engine.set_optimization_level(rhai::OptimizationLevel::None);
+
+    assert_eq!(
+        engine.eval::<INT>("let x = [1, 2, 3]; x[next()] += 40; x[1]")?,
+        42
+    );
+    assert_eq!(*count.read().unwrap(), 1);
+
+    assert_eq!(
+        engine.eval::<INT>("let x = [1, 2, 3]; x[next()] -= 40; x[1]")?,
+        -38
+    );
+    assert_eq!(*count.read().unwrap(), 2);
+
+    assert_eq!(
+        engine.eval::<INT>("let x = [[1, 2], [3, 4]]; x[next()][next()] *= 10; x[1][1]")?,
+        40
+    );
+    assert_eq!(*count.read().unwrap(), 4);
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert_eq!(
+            engine.eval::<INT>("let x = [#{a:1}, #{a:2}]; x[next()].a += 40; x[1].a")?,
+            42
+        );
+        assert_eq!(*count.read().unwrap(), 5);
+
+        assert_eq!(
+            engine.eval::<INT>("let x = #{a:[1, 2]}; x.a[next()] += 40; x.a[1]")?,
+            42
+        );
+        assert_eq!(*count.read().unwrap(), 6);
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_assignments_bad_lhs() -> Result<(), Box<EvalAltResult>> {
     let engine = Engine::new();
@@ -0,0 +1,162 @@
+#![cfg(feature = "binary_ast")]
+
+use rhai::{BinaryAstError, Engine, EvalAltResult, BINARY_AST_VERSION};
+
+#[cfg(not(feature = "no_function"))]
+use rhai::{Scope, INT};
+
+fn load_error(engine: &Engine, bytes: &[u8]) -> BinaryAstError {
+    match *engine.load_compiled_ast(bytes).expect_err("should error") {
+        EvalAltResult::ErrorSystem(_, err) => err
+            .downcast_ref::<BinaryAstError>()
+            .expect("should be BinaryAstError")
+            .clone(),
+        err => panic!("wrong error: {}", err),
+    }
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_binary_ast() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let script = r#"
+        fn greet(name) { "hello, " + name }
+        fn inc(x) { x + 1 }
+
+        let x = 40;
+        let s = greet("world");
+
+        switch s {
+            "hello, world" => x = inc(x),
+            _ => x = 0
+        }
+
+        for (i, v) in range(0, 3) {
+            if i == 2 { x += v - 1; }
+        }
+
+        x
+    "#;
+
+    let bytes = engine.compile_to_bytes(script)?;
+    let ast = engine.load_compiled_ast(&bytes)?;
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+    assert_eq!(
+        engine.call_fn::<String>(&mut Scope::new(), &ast, "greet", ("rhai",))?,
+        "hello, rhai"
+    );
+
+    // Saving the loaded AST again produces the same bytes
+    assert_eq!(ast.to_bytes()?, bytes);
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        let bytes = engine.compile_to_bytes(r#"let m = #{ a: 1, b: "x" }; m.a + m.b.len"#)?;
+        let ast = engine.load_compiled_ast(&bytes)?;
+        assert_eq!(engine.eval_ast::<INT>(&ast)?, 2);
+    }
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        let bytes = engine.compile_to_bytes("const [a, ..rest] = [40, 1, 1]; a + rest.len()")?;
+        let ast = engine.load_compiled_ast(&bytes)?;
+        assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+        assert_eq!(ast.to_bytes()?, bytes);
+    }
+
+    let bytes = engine.compile_to_bytes(
+        "let c = 'q'; let x = switch c { 'a'..='m' => 1, 'n'..='z' => 2 }; if c in 'p'..'r' { x + 40 }",
+    )?;
+    let ast = engine.load_compiled_ast(&bytes)?;
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+    assert_eq!(ast.to_bytes()?, bytes);
+
+    Ok(())
+}
+
+#[test]
+fn test_binary_ast_errors() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let bytes = engine.compile_to_bytes("let x = 42; x")?;
+
+    assert_eq!(load_error(&engine, b""), BinaryAstError::Malformed);
+    assert_eq!(
+        load_error(&engine, b"let x = 42;"),
+        BinaryAstError::Malformed
+    );
+    assert_eq!(
+        load_error(&engine, &bytes[..bytes.len() - 1]),
+        BinaryAstError::Malformed
+    );
+
+    let mut extra = bytes.clone();
+    extra.push(0);
+    assert_eq!(load_error(&engine, &extra), BinaryAstError::Malformed);
+
+    // Format version follows the magic bytes
+    let mut version = bytes.clone();
+    version[4] = BINARY_AST_VERSION as u8 + 1;
+    assert_eq!(
+        load_error(&engine, &version),
+        BinaryAstError::Version(BINARY_AST_VERSION + 1)
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_binary_ast_depth() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let script = format!("x{}", " + x".repeat(200));
+
+    assert!(engine.compile_to_bytes(&script).is_err());
+
+    engine.set_max_expr_depths(
+        0,
+        #[cfg(not(feature = "no_function"))]
+        0,
+    );
+
+    let bytes = engine.compile_to_bytes(&script)?;
+    assert_eq!(load_error(&Engine::new(), &bytes), BinaryAstError::TooDeep);
+
+    // An AST within the limit of the parser is also within the limit when loading
+    let engine = Engine::new();
+    let mut depth = 1;
+
+    while engine
+        .compile(&format!("x{}", " + x".repeat(depth + 1)))
+        .is_ok()
+    {
+        depth += 1;
+    }
+
+    let bytes = engine.compile_to_bytes(&format!("x{}", " + x".repeat(depth)))?;
+    engine.load_compiled_ast(&bytes)?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_float"))]
+#[test]
+fn test_binary_ast_features() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let mut bytes = engine.compile_to_bytes("let x = 42; x")?;
+
+    // Pretend the script was compiled with `no_float`
+    bytes[5] ^= 0b10;
+
+    let err = load_error(&engine, &bytes);
+
+    assert!(matches!(&err, BinaryAstError::Features(compiled, loading)
+        if compiled == &["no_float"] && loading.is_empty()));
+    assert!(err.to_string().contains("compiled with no_float"));
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+#![cfg(not(feature = "no_index"))]
+use rhai::{Engine, EvalAltResult, INT};
+
+#[test]
+fn test_bit_fields() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert!(engine.eval::<bool>("let x = 10; x[1]")?);
+    assert!(!engine.eval::<bool>("let x = 10; x[2]")?);
+    assert!(engine.eval::<bool>("10[3]")?);
+    assert!(engine.eval::<bool>("let x = -1; x[-1]")?);
+    assert!(!engine.eval::<bool>("let x = 1; x[-1]")?);
+
+    assert_eq!(engine.eval::<INT>("let x = 10; x[0] = true; x")?, 11);
+    assert_eq!(engine.eval::<INT>("let x = 10; x[3] = false; x")?, 2);
+    assert_eq!(engine.eval::<INT>("let x = 0; x[-1] = true; x")?, INT::MIN);
+    assert_eq!(engine.eval::<INT>("let x = 10; x[1] = !x[1]; x")?, 8);
+    assert_eq!(engine.eval::<INT>("let x = 10; x[2] |= true; x")?, 14);
+    assert_eq!(
+        engine.eval::<INT>("let x = [1, 2]; x[1][0] = true; x[1]")?,
+        3
+    );
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval::<INT>("let x = #{ flags: 0 }; x.flags[4] = true; x.flags")?,
+        16
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<bool>("let x = 10; x[100]")
+            .expect_err("should error"),
+        EvalAltResult::ErrorBitFieldBounds(_, 100, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<bool>("let x = 10; x[-100]")
+            .expect_err("should error"),
+        EvalAltResult::ErrorBitFieldBounds(_, -100, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let x = 10; x[0] = 1; x")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_bit_fields_functions() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert!(engine.eval::<bool>("let x = 10; get_bit(x, 1)")?);
+    assert!(!engine.eval::<bool>("get_bit(10, -1)")?);
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert_eq!(engine.eval::<INT>("let x = 10; x.set_bit(0, true); x")?, 11);
+        assert_eq!(engine.eval::<INT>("let x = 10; x.set_bit(1, false); x")?, 8);
+    }
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = 0b1011;
+                let count = 0;
+                for bit in bits(x) { if bit { count += 1; } }
+                count
+            "
+        )?,
+        3
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = 0b1011;
+                let count = 0;
+                for bit in bits(x, range(1, 3)) { if bit { count += 1; } }
+                count
+            "
+        )?,
+        1
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<bool>("get_bit(10, 100)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorBitFieldBounds(_, 100, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<()>("for b in bits(10, range(0, 100)) {}")
+            .expect_err("should error"),
+        EvalAltResult::ErrorBitFieldBounds(_, 100, _)
+    ));
+
+    Ok(())
+}
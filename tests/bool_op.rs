@@ -86,3 +86,33 @@ fn test_bool_op_no_short_circuit2() {
         )
         .is_err());
 }
+
+#[test]
+fn test_bool_op_not_in() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert!(engine.eval::<bool>(r#"let y = "hello, world!"; "hey" !in y"#)?);
+    assert!(!engine.eval::<bool>(r#"let y = "hello, world!"; 'w' !in y"#)?);
+    assert!(engine.eval::<bool>(r#""hey" ! in "hello""#)?);
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert!(engine.eval::<bool>("let y = [1, 2, 3]; 42 !in y")?);
+        assert!(!engine.eval::<bool>("let y = [1, 2, 3]; 2 !in y")?);
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert!(engine.eval::<bool>(r#"let y = #{a: 1, b: 2}; "z" !in y"#)?);
+        assert!(!engine.eval::<bool>(r#"let y = #{a: 1, b: 2}; "a" !in y"#)?);
+        assert!(engine.eval::<bool>(r#"let y = #{a: 1, b: 2}; "a" in y && "z" !in y && y.a == 1"#)?);
+        assert!(!engine.eval::<bool>(r#"let y = #{a: 1}; "z" !in y && "a" !in y"#)?);
+    }
+
+    // `!` followed by an identifier starting with `in` is not `!in`
+    assert!(engine.eval::<bool>("let inner = false; !inner")?);
+
+    assert!(engine.compile("42 !in 123").is_err());
+
+    Ok(())
+}
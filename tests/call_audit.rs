@@ -0,0 +1,109 @@
+use rhai::{CallAudit, Engine, EvalAltResult, RegisterFn, Scope, INT};
+
+fn make_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine
+        .register_fn("add", |x: INT, y: INT| x + y)
+        .register_fn("neg", |x: INT| -x);
+
+    engine
+}
+
+#[test]
+fn test_call_audit() -> Result<(), Box<EvalAltResult>> {
+    let engine = make_engine();
+    let script = "let x = add(1, 2); let y = neg(x); add(x, y) * 2";
+
+    let mut audit = CallAudit::new();
+    assert_eq!(
+        engine.eval_with_call_audit::<INT>(&mut Scope::new(), script, &mut audit)?,
+        0
+    );
+
+    let mut calls: Vec<_> = audit.iter().collect();
+    calls.sort();
+    assert_eq!(calls, [("add", 2, 2), ("neg", 1, 1)]);
+    assert_eq!(audit.count("add", 1), 0);
+    assert_eq!(audit.count("*", 2), 0);
+
+    let mut audit = CallAudit::new();
+    audit.set_include_operators(true);
+    engine.eval_with_call_audit::<INT>(&mut Scope::new(), script, &mut audit)?;
+
+    let mut calls: Vec<_> = audit.iter().collect();
+    calls.sort();
+    assert_eq!(calls, [("*", 2, 1), ("add", 2, 2), ("neg", 1, 1)]);
+
+    // Counts accumulate
+    engine.eval_with_call_audit::<INT>(&mut Scope::new(), "let x = 1; x += 2; x", &mut audit)?;
+    assert_eq!(audit.count("add", 2), 2);
+    assert_eq!(audit.count("+=", 2), 1);
+
+    audit.clear();
+    assert!(audit.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_call_audit_errors() {
+    let engine = make_engine();
+    let mut audit = CallAudit::new();
+
+    assert!(engine
+        .eval_with_call_audit::<INT>(&mut Scope::new(), "neg(1); neg(2); throw 42;", &mut audit)
+        .is_err());
+
+    assert_eq!(audit.count("neg", 1), 2);
+
+    assert!(matches!(
+        *engine
+            .eval_with_call_audit::<String>(&mut Scope::new(), "neg(1)", &mut audit)
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchOutputType(_, _, _)
+    ));
+
+    assert_eq!(audit.count("neg", 1), 3);
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_call_audit_script_fns() -> Result<(), Box<EvalAltResult>> {
+    let engine = make_engine();
+    let script = "fn foo(x) { neg(x) } foo(1) + foo(2)";
+
+    let mut audit = CallAudit::new();
+    engine.eval_with_call_audit::<INT>(&mut Scope::new(), script, &mut audit)?;
+    assert_eq!(audit.count("foo", 1), 0);
+    assert_eq!(audit.count("neg", 1), 2);
+
+    let mut audit = CallAudit::new();
+    audit.set_include_script_fns(true);
+    engine.eval_with_call_audit::<INT>(&mut Scope::new(), script, &mut audit)?;
+    assert_eq!(audit.count("foo", 1), 2);
+    assert_eq!(audit.count("neg", 1), 2);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_module"))]
+fn test_call_audit_namespace() -> Result<(), Box<EvalAltResult>> {
+    use rhai::Module;
+
+    let mut engine = make_engine();
+
+    let mut module = Module::new();
+    module.set_fn_1("double", |x: INT| Ok(x * 2));
+    engine.register_static_module("math", module.into());
+
+    let mut audit = CallAudit::new();
+    assert_eq!(
+        engine.eval_with_call_audit::<INT>(&mut Scope::new(), "math::double(21)", &mut audit)?,
+        42
+    );
+    assert_eq!(audit.count("math::double", 1), 1);
+
+    Ok(())
+}
@@ -1,5 +1,8 @@
 #![cfg(not(feature = "no_function"))]
-use rhai::{Engine, EvalAltResult, FnPtr, Func, ParseErrorType, RegisterFn, Scope, INT};
+use rhai::{
+    Dynamic, Engine, EvalAltResult, FnPtr, Func, Module, ParseErrorType, Position, RegisterFn,
+    Scope, INT,
+};
 use std::any::TypeId;
 
 #[test]
@@ -69,6 +72,63 @@ fn test_call_fn() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_call_fn_with_this() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    let mut scope = Scope::new();
+
+    let ast = engine.compile(
+        r"
+            fn grow(x) { this += x; this * 2 }
+            fn answer() { 42 }
+        ",
+    )?;
+
+    let mut value = Dynamic::from(1 as INT);
+
+    let result: INT =
+        engine.call_fn_with_this(&mut scope, &mut value, &ast, "grow", (20 as INT,))?;
+    assert_eq!(result, 42);
+    assert_eq!(value.clone().cast::<INT>(), 21);
+
+    // Binding 'this' to a function not using it is fine
+    let result: INT = engine.call_fn_with_this(&mut scope, &mut value, &ast, "answer", ())?;
+    assert_eq!(result, 42);
+
+    assert!(matches!(
+        *engine
+            .call_fn_with_this::<INT>(&mut scope, &mut value, &ast, "shrink", ())
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(name, _) if name == "shrink"
+    ));
+
+    // Functions in modules registered into the engine
+    #[cfg(not(feature = "no_module"))]
+    {
+        let module_ast = engine.compile(
+            r"
+                fn reset() { let old = this; this = 0; old }
+            ",
+        )?;
+        let module = Module::eval_ast_as_new(Scope::new(), &module_ast, &engine)?;
+        engine.register_static_module("counter", module.into());
+
+        let module_ast = engine.compile("fn double() { this *= 2; }")?;
+        let module = Module::eval_ast_as_new(Scope::new(), &module_ast, &engine)?;
+        engine.register_global_module(module.into());
+
+        engine.call_fn_with_this::<()>(&mut scope, &mut value, &ast, "double", ())?;
+        assert_eq!(value.clone().cast::<INT>(), 42);
+
+        let result: INT =
+            engine.call_fn_with_this(&mut scope, &mut value, &ast, "counter::reset", ())?;
+        assert_eq!(result, 42);
+        assert_eq!(value.clone().cast::<INT>(), 0);
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_call_fn_private() -> Result<(), Box<EvalAltResult>> {
     let engine = Engine::new();
@@ -199,3 +259,39 @@ fn test_anonymous_fn() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_fn_native_context() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    #[allow(deprecated)]
+    engine
+        .register_raw_fn("location", &[], |context, _| {
+            let pos = context.position();
+            Ok(format!(
+                "{}:{}:{}",
+                context.source().unwrap_or("?"),
+                pos.line().unwrap(),
+                pos.position().unwrap()
+            ))
+        })
+        .register_raw_fn::<()>("fail", &[], |_, _| Err("oops".into()));
+
+    let mut ast = engine.compile("\n    location()")?;
+    ast.set_source(Some("script"));
+    assert_eq!(engine.eval_ast::<String>(&ast)?, "script:2:5");
+
+    // Calls inside script functions
+    let mut ast = engine.compile("fn foo() {\n  location()\n}\nfoo()")?;
+    ast.set_source(Some("script"));
+    assert_eq!(engine.eval_ast::<String>(&ast)?, "script:2:3");
+
+    // Errors without a position are given the position of the call
+    let err = engine
+        .eval::<()>("let x = 1;\nfail()")
+        .expect_err("should error");
+    assert!(matches!(*err, EvalAltResult::ErrorRuntime(_, _)));
+    assert_eq!(err.position(), Position::new(2, 1));
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult};
+use rhai::{CharRange, Engine, EvalAltResult, ParseErrorType, INT};
 
 #[test]
 fn test_chars() -> Result<(), Box<EvalAltResult>> {
@@ -23,3 +23,134 @@ fn test_chars() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_char_ranges() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<CharRange>("'a'..='z'")?, 'a'..='z');
+    assert_eq!(engine.eval::<CharRange>("'a'..'z'")?, 'a'..='y');
+    assert_eq!(engine.eval::<String>("type_of('0'..='9')")?, "char_range");
+
+    assert!(engine.eval::<bool>("'x' in 'a'..='z'")?);
+    assert!(!engine.eval::<bool>("let c = 'X'; c in 'a'..='z'")?);
+    assert!(!engine.eval::<bool>("let c = 'z'; c in 'a'..'z'")?);
+    assert!(engine.eval::<bool>("let r = '0'..='9'; contains(r, '5')")?);
+
+    assert!(matches!(
+        *engine.compile("'a'..42").expect_err("should error").0,
+        ParseErrorType::ExprExpected(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_char_functions() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert!(engine.eval::<bool>("is_alpha('x') && is_digit('7') && is_whitespace(' ')")?);
+    assert!(!engine.eval::<bool>("is_alpha('7') || is_digit('x') || is_whitespace('_')")?);
+    assert_eq!(engine.eval::<char>("to_lower('A')")?, 'a');
+    assert_eq!(engine.eval::<char>("to_upper('a')")?, 'A');
+    assert_eq!(engine.eval::<char>("to_upper('ß')")?, 'ß');
+
+    // Classify the characters of a string
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let letters = 0;
+                let digits = 0;
+                let vowels = 0;
+                let others = 0;
+
+                for c in "Hello, World 42!" {
+                    if c in "aeiou" {
+                        vowels += 1;
+                    }
+                    switch to_lower(c) {
+                        'a'..='z' => letters += 1,
+                        '0'..='9' => digits += 1,
+                        _ => others += 1
+                    }
+                }
+
+                letters * 1000 + digits * 100 + vowels * 10 + others
+            "#
+        )?,
+        10234
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_chars_escapes() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<char>(r"'\u{1F600}'")?, '😀');
+    assert_eq!(engine.eval::<char>(r"'\u{41}'")?, 'A');
+    assert_eq!(engine.eval::<char>(r"'\0'")?, '\0');
+    assert_eq!(engine.eval::<String>(r#""smile \u{1F600}!""#)?, "smile 😀!");
+    assert_eq!(engine.eval::<String>(r#""a\0b""#)?, "a\0b");
+
+    use rhai::LexError;
+
+    assert_eq!(
+        *engine.compile(r"'\u{D800}'").expect_err("should error").0,
+        ParseErrorType::BadInput(LexError::MalformedEscapeSequence(r"\u{D800}".to_string()))
+    );
+    assert_eq!(
+        *engine.compile(r"'\uDFFF'").expect_err("should error").0,
+        ParseErrorType::BadInput(LexError::MalformedEscapeSequence(r"\uDFFF".to_string()))
+    );
+    assert!(engine.compile(r"'\u{110000}'").is_err());
+    assert!(engine.compile(r"'\u{1234567}'").is_err());
+    assert!(engine.compile(r"'\u{}'").is_err());
+    assert!(engine.compile(r"'\u{12'").is_err());
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_chars_astral() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let script = r#"let s = "a😀b\u{1F601}c";"#;
+
+    assert_eq!(engine.eval::<INT>(&format!("{} len(s)", script))?, 5);
+    assert_eq!(engine.eval::<char>(&format!("{} s[1]", script))?, '😀');
+    assert_eq!(engine.eval::<char>(&format!("{} s[2]", script))?, 'b');
+    assert_eq!(engine.eval::<char>(&format!("{} s[3]", script))?, '😁');
+    assert_eq!(
+        engine.eval::<String>(&format!("{} s[3] = 'x'; s", script))?,
+        "a😀bxc"
+    );
+    assert_eq!(
+        engine.eval::<INT>(&format!("{} len(to_chars(s))", script))?,
+        5
+    );
+    assert_eq!(
+        engine.eval::<char>(&format!("{} to_chars(s)[3]", script))?,
+        '😁'
+    );
+    assert_eq!(
+        engine.eval::<String>(&format!("{} from_chars(to_chars(s))", script))?,
+        "a😀b😁c"
+    );
+    assert_eq!(
+        engine.eval::<String>("let a = to_chars(\"abc\"); reverse(a); from_chars(a)")?,
+        "cba"
+    );
+    assert_eq!(engine.eval::<String>("from_chars([])")?, "");
+
+    assert!(matches!(
+        *engine
+            .eval::<String>("from_chars(['a', 42])")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+
+    Ok(())
+}
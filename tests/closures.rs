@@ -1,6 +1,7 @@
 #![cfg(not(feature = "no_function"))]
 use rhai::{
-    Engine, EvalAltResult, FnPtr, NativeCallContext, ParseErrorType, RegisterFn, Scope, INT,
+    Dynamic, Engine, EvalAltResult, FnPtr, NativeCallContext, ParseErrorType, Position, RegisterFn,
+    Scope, INT,
 };
 use std::any::TypeId;
 use std::cell::RefCell;
@@ -10,6 +11,9 @@ use std::rc::Rc;
 #[cfg(not(feature = "no_object"))]
 use rhai::Map;
 
+#[cfg(not(feature = "no_index"))]
+use rhai::Array;
+
 #[test]
 fn test_fn_ptr_curry_call() -> Result<(), Box<EvalAltResult>> {
     let mut engine = Engine::new();
@@ -174,7 +178,6 @@ fn test_closures() -> Result<(), Box<EvalAltResult>> {
 #[test]
 #[cfg(not(feature = "no_closure"))]
 #[cfg(not(feature = "no_object"))]
-#[cfg(not(feature = "sync"))]
 fn test_closures_data_race() -> Result<(), Box<EvalAltResult>> {
     let engine = Engine::new();
 
@@ -205,6 +208,68 @@ fn test_closures_data_race() -> Result<(), Box<EvalAltResult>> {
         EvalAltResult::ErrorDataRace(_, _)
     ));
 
+    // Calling a method on a shared value which calls a closure capturing the same value
+    let err = engine
+        .eval::<INT>(
+            r#"
+                fn update(f) { f.call(1); this.v }
+                let x = #{ v: 0 };
+                let f = |v| { x.v = v; 0 };
+                x.update(f)
+            "#,
+        )
+        .expect_err("should error");
+
+    let inner = err.source_error().expect("should be wrapped");
+    assert!(matches!(inner, EvalAltResult::ErrorDataRace(name, _) if name == "x"));
+    assert_eq!(inner.position(), Position::new(2, 33));
+
+    #[cfg(not(feature = "no_index"))]
+    assert!(matches!(
+        *engine
+            .eval::<INT>(
+                r#"
+                    let x = [1, 2, 3];
+                    let f = |v| { x.push(v); v };
+                    x.map(f);
+                    0
+                "#
+            )
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(_, _, err, _)
+            if matches!(*err, EvalAltResult::ErrorDataRace(ref name, _) if name == "x")
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_closure"))]
+#[cfg(feature = "sync")]
+fn test_closures_locked_by_other_thread() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile("abs(x)")?;
+
+    let mut value = Dynamic::from(40 as INT).into_shared();
+    let mut scope = Scope::new();
+    scope.push_dynamic("x", value.clone());
+
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    let handle = std::thread::spawn(move || {
+        let mut guard = value.write_lock::<INT>().unwrap();
+        tx.send(()).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        *guard += 2;
+    });
+
+    // A value locked by another thread is waited on, not reported as a data race
+    rx.recv().unwrap();
+    assert_eq!(engine.eval_ast_with_scope::<INT>(&mut scope, &ast)?, 42);
+
+    handle.join().unwrap();
+
     Ok(())
 }
 
@@ -295,3 +360,102 @@ fn test_closures_external() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[cfg(not(feature = "no_closure"))]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+fn contains_shared(value: &Dynamic) -> bool {
+    if value.is_shared() {
+        return true;
+    }
+    if let Some(array) = value.read_lock::<Array>() {
+        return array.iter().any(contains_shared);
+    }
+    if let Some(map) = value.read_lock::<Map>() {
+        return map.values().any(contains_shared);
+    }
+    if let Some(fn_ptr) = value.read_lock::<FnPtr>() {
+        return fn_ptr.curry().iter().any(contains_shared);
+    }
+    false
+}
+
+#[test]
+#[cfg(not(feature = "no_closure"))]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+fn test_closures_deep_flatten() -> Result<(), Box<EvalAltResult>> {
+    let shared = Dynamic::from(42 as INT).into_shared();
+
+    let mut fn_ptr = FnPtr::new("foo")?;
+    fn_ptr.add_curry(shared.clone());
+
+    let mut map = Map::new();
+    map.insert("a".into(), shared.clone());
+    map.insert("b".into(), Dynamic::from(fn_ptr).into_shared());
+
+    let array: Array = vec![shared.clone(), Dynamic::from(map).into_shared()];
+    let value = Dynamic::from(array).into_shared();
+
+    assert!(contains_shared(&value));
+    assert!(contains_shared(&value.clone().flatten()));
+
+    let value = value.deep_flatten();
+
+    assert!(!contains_shared(&value));
+
+    let array = value.cast::<Array>();
+    assert_eq!(array[0].clone().cast::<INT>(), 42);
+
+    let map = array[1].clone().cast::<Map>();
+    assert_eq!(map["a"].clone().cast::<INT>(), 42);
+    assert_eq!(
+        map["b"].clone().cast::<FnPtr>().curry()[0]
+            .clone()
+            .cast::<INT>(),
+        42
+    );
+
+    // The original shared value is untouched
+    assert!(shared.is_shared());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_closure"))]
+fn test_closures_nested_shared() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    // Deliberately nest one shared value inside another
+    let mut x = Dynamic::from(0 as INT).into_shared();
+    *x.write_lock::<Dynamic>().unwrap() = Dynamic::from(41 as INT).into_shared();
+
+    assert_eq!(x.type_name(), std::any::type_name::<INT>());
+    assert_eq!(*x.read_lock::<INT>().unwrap(), 41);
+    assert!(x.read_lock::<bool>().is_none());
+    assert!(!x.clone().flatten().is_shared());
+    assert!(!x.flatten_clone().is_shared());
+
+    let mut f = Dynamic::from(0 as INT).into_shared();
+    *f.write_lock::<Dynamic>().unwrap() = Dynamic::from(FnPtr::new("foo")?).into_shared();
+
+    let mut scope = Scope::new();
+    scope.push_dynamic("x", x.clone());
+    scope.push_dynamic("f", f);
+
+    let ast = engine.compile(
+        r#"
+            fn foo(n) { n * 2 }
+
+            x += 1;
+            call(f, x) + call(curry(f, x))
+        "#,
+    )?;
+
+    assert_eq!(engine.eval_ast_with_scope::<INT>(&mut scope, &ast)?, 168);
+
+    assert_eq!(x.cast::<INT>(), 42);
+
+    Ok(())
+}
@@ -1,5 +1,8 @@
 use rhai::{Engine, EvalAltResult, INT};
 
+#[cfg(not(feature = "no_function"))]
+use rhai::FnAccess;
+
 #[test]
 fn test_comments() -> Result<(), Box<EvalAltResult>> {
     let engine = Engine::new();
@@ -102,3 +105,54 @@ fn test_comments_doc() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_comments_metadata() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast1 = engine.compile(
+        r"
+            /// Add two numbers.
+            /// Returns the sum.
+            fn add(x, y) { x + y }
+
+            fn no_comments() {}
+        ",
+    )?;
+
+    let ast2 = engine.compile(
+        r"
+            /** Negate a number. */
+            private fn neg(x) { -x }
+        ",
+    )?;
+
+    let ast = ast1.merge(&ast2);
+
+    let mut functions: Vec<_> = ast.iter_functions().collect();
+    functions.sort_by(|a, b| a.name.cmp(b.name));
+
+    assert_eq!(functions.len(), 3);
+
+    assert_eq!(functions[0].name, "add");
+    assert_eq!(functions[0].access, FnAccess::Public);
+    assert_eq!(functions[0].params, ["x", "y"]);
+    assert_eq!(
+        functions[0].comments,
+        ["/// Add two numbers.", "/// Returns the sum."]
+    );
+    assert_eq!(functions[0].to_string(), "add(x, y)");
+
+    assert_eq!(functions[1].name, "neg");
+    assert_eq!(functions[1].access, FnAccess::Private);
+    assert_eq!(functions[1].params, ["x"]);
+    assert_eq!(functions[1].comments, ["/** Negate a number. */"]);
+    assert_eq!(functions[1].to_string(), "private neg(x)");
+
+    assert_eq!(functions[2].name, "no_comments");
+    assert!(functions[2].params.is_empty());
+    assert!(functions[2].comments.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use rhai::{Engine, EvalAltResult, RegisterFn, Scope, INT};
+
+#[test]
+fn test_compile_cache() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    assert_eq!(engine.compilation_cache_capacity(), 0);
+
+    engine.eval::<INT>("40 + 2")?;
+    assert_eq!(engine.compilation_cache_len(), 0);
+
+    engine.set_compilation_cache(2);
+    assert_eq!(engine.compilation_cache_capacity(), 2);
+
+    for _ in 0..5 {
+        assert_eq!(engine.eval::<INT>("40 + 2")?, 42);
+    }
+    assert_eq!(engine.compilation_cache_len(), 1);
+
+    let ast = engine.compile("let x = 40; x + 2")?;
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+    assert_eq!(engine.compilation_cache_len(), 2);
+
+    // Use the first script again so that the second one is evicted
+    assert_eq!(engine.eval::<INT>("40 + 2")?, 42);
+    assert_eq!(engine.eval::<INT>("1 + 1")?, 2);
+    assert_eq!(engine.compilation_cache_len(), 2);
+
+    // Parse errors are not cached
+    assert!(engine.compile("let x = ;").is_err());
+    assert_eq!(engine.compilation_cache_len(), 2);
+
+    engine.clear_compilation_cache();
+    assert_eq!(engine.compilation_cache_len(), 0);
+
+    engine.set_compilation_cache(0);
+    assert_eq!(engine.compilation_cache_capacity(), 0);
+    assert_eq!(engine.eval::<INT>("40 + 2")?, 42);
+
+    Ok(())
+}
+
+#[test]
+fn test_compile_cache_settings() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_compilation_cache(10);
+
+    let script = "let x = 40; x += 2; x";
+
+    assert_eq!(engine.eval::<INT>(script)?, 42);
+
+    // A settings change must not reuse the cached script
+    engine.disable_symbol("+=");
+    assert!(engine.compile(script).is_err());
+
+    let mut engine = Engine::new();
+    engine.set_compilation_cache(10);
+
+    let script = "1 foo 2";
+
+    assert!(engine.compile(script).is_err());
+
+    engine.register_custom_operator("foo", 160).unwrap();
+    engine.register_fn("foo", |x: INT, y: INT| x * 10 + y);
+
+    assert_eq!(engine.eval::<INT>(script)?, 12);
+
+    // Tightening a limit checked by the parser must not reuse the cached script
+    #[cfg(not(feature = "unchecked"))]
+    {
+        let script = "let x = 1; let y = 2; x + y";
+
+        assert_eq!(engine.eval::<INT>(script)?, 3);
+
+        engine.set_max_variables(1);
+        assert!(engine.compile(script).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_compile_cache_scope() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_compilation_cache(10);
+
+    let mut scope = Scope::new();
+    scope.push("x", 40 as INT);
+
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "x + 2")?, 42);
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "x + 2")?, 42);
+    assert_eq!(engine.compilation_cache_len(), 1);
+
+    // Constants may be folded into the script, so it is not cached
+    #[cfg(not(feature = "no_optimize"))]
+    {
+        let mut scope = Scope::new();
+        scope.push_constant("y", 40 as INT);
+
+        assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "y + 2")?, 42);
+        assert_eq!(engine.compilation_cache_len(), 1);
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, ParseErrorType, RegisterFn, Scope, INT};
+use rhai::{Engine, EvalAltResult, Module, ParseErrorType, Position, RegisterFn, Scope, INT};
 
 #[test]
 fn test_constant() -> Result<(), Box<EvalAltResult>> {
@@ -95,3 +95,96 @@ fn test_constant_mut() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[cfg(not(feature = "no_optimize"))]
+#[test]
+fn test_constant_runtime_value() -> Result<(), Box<EvalAltResult>> {
+    use rhai::OptimizationLevel;
+
+    for level in &[
+        OptimizationLevel::None,
+        OptimizationLevel::Simple,
+        OptimizationLevel::Full,
+    ] {
+        let mut engine = Engine::new();
+        engine.set_optimization_level(*level);
+        engine.register_fn("foo", || 40 as INT);
+
+        assert_eq!(engine.eval::<INT>("const x = foo(); x + 2")?, 42);
+        assert_eq!(engine.eval::<INT>("const x = 1; const x = foo(); x")?, 40);
+        assert_eq!(
+            engine.eval::<INT>("const x = 1; { const x = foo(); x }")?,
+            40
+        );
+        assert_eq!(engine.eval::<INT>(r#"{ const x = 42; eval("x") }"#)?, 42);
+        assert_eq!(
+            engine.eval::<INT>(r#"const x = 1; { const x = 40 + 2; eval("x") }"#)?,
+            42
+        );
+
+        assert!(matches!(
+            *engine.compile("const x = foo(); x = 42;").expect_err("should error").0,
+            ParseErrorType::AssignmentToConstant(x) if x == "x"
+        ));
+        assert!(matches!(
+            *engine
+                .consume(r#"const x = foo(); eval("x = 42");"#)
+                .expect_err("should error"),
+            EvalAltResult::ErrorAssignmentToConstant(x, _) if x == "x"
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_constant_static() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let mut module = Module::new();
+    module.set_var("MAX_PLAYERS", 4 as INT);
+    module.set_fn_1("double", |x: INT| Ok(x * 2));
+
+    engine.register_static_constants(module.into());
+
+    assert_eq!(engine.eval::<INT>("MAX_PLAYERS")?, 4);
+    assert_eq!(engine.eval::<INT>("double(MAX_PLAYERS)")?, 8);
+    assert!(engine.eval::<bool>("is_def_var(\"MAX_PLAYERS\")")?);
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(engine.eval::<INT>("fn f() { MAX_PLAYERS + 1 } f()")?, 5);
+
+    // Local variables take precedence
+    assert_eq!(engine.eval::<INT>("let MAX_PLAYERS = 2; MAX_PLAYERS")?, 2);
+    assert_eq!(
+        engine.eval::<INT>("let x = { let MAX_PLAYERS = 2; MAX_PLAYERS }; x + MAX_PLAYERS")?,
+        6
+    );
+
+    assert!(matches!(
+        *engine.consume("let x = 1;\nMAX_PLAYERS = 8;").expect_err("should error"),
+        EvalAltResult::ErrorAssignmentToConstant(ref name, pos)
+            if name == "MAX_PLAYERS" && pos == Position::new(2, 1)
+    ));
+    assert!(matches!(
+        *engine.consume("MAX_PLAYERS += 1;").expect_err("should error"),
+        EvalAltResult::ErrorAssignmentToConstant(ref name, _) if name == "MAX_PLAYERS"
+    ));
+
+    // Constants registered later are preferred
+    let mut module = Module::new();
+    module.set_var("MAX_PLAYERS", 8 as INT);
+    engine.register_static_constants(module.into());
+
+    assert_eq!(engine.eval::<INT>("MAX_PLAYERS")?, 8);
+
+    // Global constants are declared variables
+    engine.set_strict_variables(true);
+    assert_eq!(engine.eval::<INT>("MAX_PLAYERS")?, 8);
+    assert!(matches!(
+        *engine.compile("MIN_PLAYERS").expect_err("should error").0,
+        ParseErrorType::VariableUndefined(ref name) if name == "MIN_PLAYERS"
+    ));
+
+    Ok(())
+}
@@ -5,7 +5,7 @@ use rhai::{Engine, EvalAltResult, ParseErrorType};
 use rhai::Array;
 
 #[cfg(not(feature = "no_object"))]
-use rhai::Map;
+use rhai::{Map, INT};
 
 #[test]
 fn test_max_string_size() -> Result<(), Box<EvalAltResult>> {
@@ -55,6 +55,16 @@ fn test_max_string_size() -> Result<(), Box<EvalAltResult>> {
         EvalAltResult::ErrorDataTooLarge(_, _)
     ));
 
+    assert_eq!(engine.eval::<String>(r#""ab" * 5"#)?, "ababababab");
+
+    // The limit is checked before the string is built
+    assert!(matches!(
+        *engine
+            .eval::<String>(r#""ab" * 1000000000"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(msg, _) if msg.contains("10")
+    ));
+
     engine.set_max_string_size(0);
 
     assert_eq!(
@@ -101,6 +111,16 @@ fn test_max_array_size() -> Result<(), Box<EvalAltResult>> {
         EvalAltResult::ErrorDataTooLarge(_, _)
     ));
 
+    assert_eq!(engine.eval::<Array>("[1, 2] * 5")?.len(), 10);
+
+    // The limit is checked before the array is built
+    assert!(matches!(
+        *engine
+            .eval::<Array>("[1, 2] * 1000000000")
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(msg, _) if msg.contains("10")
+    ));
+
     #[cfg(not(feature = "no_object"))]
     assert!(matches!(
         *engine
@@ -244,6 +264,28 @@ fn test_max_map_size() -> Result<(), Box<EvalAltResult>> {
         EvalAltResult::ErrorDataTooLarge(_, _)
     ));
 
+    assert!(matches!(
+        *engine
+            .eval::<Map>(
+                r#"
+                    let x = #{a:1,b:2,c:3,d:4,e:5,f:6,g:7,h:8,i:9,j:10};
+                    increment(x, "k", 1);
+                    x
+                "#
+            )
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, _)
+    ));
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let x = #{a:1,b:2,c:3,d:4,e:5,f:6,g:7,h:8,i:9,j:10};
+                increment(x, "j", 1)
+            "#
+        )?,
+        11
+    );
+
     engine.set_max_map_size(0);
 
     assert_eq!(
@@ -273,3 +315,55 @@ fn test_max_map_size() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+fn test_max_total_data_size() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_max_array_size(1000);
+    engine.set_max_total_data_size(100_000);
+
+    assert_eq!(engine.max_total_data_size(), 100_000);
+
+    // Many medium arrays, each within the array size limit
+    let err = *engine
+        .eval::<()>(
+            r#"
+                let all = [];
+                for i in range(0, 1000) {
+                    let x = [];
+                    for j in range(0, 100) { push(x, j); }
+                    push(all, x);
+                }
+            "#,
+        )
+        .expect_err("should error");
+
+    assert!(matches!(err, EvalAltResult::ErrorDataTooLarge(ref s, _) if s == "Total size of data"));
+
+    // Many medium literals
+    assert!(matches!(
+        *engine
+            .eval::<()>("let all = []; loop { all += [[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]]; }")
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, _)
+    ));
+
+    // Long strings
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"let s = ""; loop { s += "hello, world!"; }"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, _)
+    ));
+
+    // Small scripts are unaffected, and the total is counted per evaluation
+    for _ in 0..100 {
+        assert_eq!(
+            engine.eval::<rhai::INT>("let x = [1, 2, 3]; push(x, 4); len(x)")?,
+            4
+        );
+    }
+
+    Ok(())
+}
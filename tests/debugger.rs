@@ -0,0 +1,141 @@
+use rhai::{DebuggerCommand, Engine, EvalAltResult, INT};
+use std::sync::{Arc, RwLock};
+
+const SCRIPT: &str = r#"
+fn foo(x) {
+    let y = x + 1;
+    y * 2
+}
+let a = 1;
+let b = foo(a);
+a + b
+"#;
+
+/// Run `SCRIPT`, answering each break with the next command (then `StepInto`),
+/// and record the line and call stack of each break.
+fn run(commands: &[DebuggerCommand]) -> (Result<INT, Box<EvalAltResult>>, Vec<String>) {
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let logger = log.clone();
+    let commands = commands.to_vec();
+
+    let mut engine = Engine::new();
+
+    engine.on_debugger(move |context, pos| {
+        let mut log = logger.write().unwrap();
+        let command = commands
+            .get(log.len())
+            .copied()
+            .unwrap_or(DebuggerCommand::StepInto);
+
+        log.push(format!(
+            "{}:{}",
+            pos.line().unwrap(),
+            context
+                .call_stack()
+                .iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(">")
+        ));
+
+        command
+    });
+
+    let result = engine.eval::<INT>(SCRIPT);
+    let log = log.read().unwrap().clone();
+
+    (result, log)
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_debugger_step() {
+    let (result, log) = run(&[]);
+    assert_eq!(result.unwrap(), 5);
+    assert_eq!(log, ["6:", "7:", "3:foo", "4:foo", "8:"]);
+
+    let (result, log) = run(&[DebuggerCommand::StepInto, DebuggerCommand::StepOver]);
+    assert_eq!(result.unwrap(), 5);
+    assert_eq!(log, ["6:", "7:", "8:"]);
+
+    let (result, log) = run(&[
+        DebuggerCommand::StepInto,
+        DebuggerCommand::StepInto,
+        DebuggerCommand::StepOver,
+        DebuggerCommand::StepOver,
+    ]);
+    assert_eq!(result.unwrap(), 5);
+    assert_eq!(log, ["6:", "7:", "3:foo", "4:foo", "8:"]);
+
+    let (result, log) = run(&[DebuggerCommand::StepInto, DebuggerCommand::Continue]);
+    assert_eq!(result.unwrap(), 5);
+    assert_eq!(log, ["6:", "7:"]);
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_debugger_abort() {
+    let (result, log) = run(&[
+        DebuggerCommand::StepInto,
+        DebuggerCommand::StepInto,
+        DebuggerCommand::Abort,
+    ]);
+    assert_eq!(log, ["6:", "7:", "3:foo"]);
+    assert!(matches!(
+        *result.expect_err("should error"),
+        EvalAltResult::ErrorTerminated(_, pos) if pos.line() == Some(3)
+    ));
+}
+
+#[test]
+fn test_debugger_scope() -> Result<(), Box<EvalAltResult>> {
+    let values = Arc::new(RwLock::new(Vec::new()));
+    let logger = values.clone();
+
+    let mut engine = Engine::new();
+
+    engine.on_debugger(move |context, _| {
+        if let Some(x) = context.scope().get_value::<INT>("x") {
+            logger.write().unwrap().push(x);
+        }
+        DebuggerCommand::StepInto
+    });
+
+    engine.consume("let x = 1; x += 41; x *= 2;")?;
+
+    assert_eq!(*values.read().unwrap(), [1, 42]);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_module"))]
+fn test_debugger_module() -> Result<(), Box<EvalAltResult>> {
+    use rhai::Module;
+
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let logger = log.clone();
+
+    let mut engine = Engine::new();
+
+    engine.on_debugger(move |context, pos| {
+        logger
+            .write()
+            .unwrap()
+            .push((pos.line().unwrap(), context.call_stack().len()));
+        DebuggerCommand::StepInto
+    });
+
+    let ast = engine.compile("fn inc(x) {\n    x + 1\n}\nexport const answer = 42;")?;
+    let module = Module::eval_ast_as_new(Default::default(), &ast, &engine)?;
+    engine.register_static_module("m", module.into());
+
+    assert_eq!(*log.read().unwrap(), [(4, 0)]);
+
+    assert_eq!(engine.eval::<INT>("m::inc(m::answer)")?, 43);
+
+    assert_eq!(*log.read().unwrap(), [(4, 0), (1, 0), (2, 1)]);
+
+    Ok(())
+}
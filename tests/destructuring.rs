@@ -0,0 +1,163 @@
+#![cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+use rhai::{Engine, EvalAltResult, ParseErrorType, Scope, INT};
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_destructuring_array() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>("let [a, b, c] = [1, 2, 3]; a * 100 + b * 10 + c")?,
+        123
+    );
+    assert_eq!(engine.eval::<INT>("let [a, b] = [1, 2, 3]; a + b")?, 3);
+    assert_eq!(engine.eval::<INT>("let [] = [1, 2]; 42")?, 42);
+    assert_eq!(
+        engine.eval::<INT>("let [a, ..rest] = [1, 2, 3]; a + len(rest) * 10")?,
+        21
+    );
+    assert_eq!(
+        engine.eval::<INT>("let [a, b, ..rest] = [1, 2]; a + b + len(rest)")?,
+        3
+    );
+    assert_eq!(engine.eval::<INT>("let [..rest] = [1, 2]; rest[1]")?, 2);
+
+    // The value is evaluated only once, before the variables are declared
+    assert_eq!(
+        engine.eval::<INT>("let a = [1, 2]; let [a, b] = a; a + b")?,
+        3
+    );
+    assert_eq!(engine.eval::<INT>("let x = 42; { let [x] = [1]; } x")?, 42);
+
+    // Variables can be modified
+    assert_eq!(
+        engine.eval::<INT>("let [a, b] = [1, 2]; a += 40; a + b")?,
+        43
+    );
+
+    // Destructuring a value that is not an array
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let [a, b] = 42; a")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+
+    // Too few elements
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let [a, b, c] = [1, 2]; a")
+            .expect_err("should error"),
+        EvalAltResult::ErrorDestructure(2, 3, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let [a, b, ..rest] = [1]; a")
+            .expect_err("should error"),
+        EvalAltResult::ErrorDestructure(1, 2, _)
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_destructuring_map() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>("let #{x, y} = #{x: 1, y: 2, z: 3}; x * 10 + y")?,
+        12
+    );
+    assert!(engine.eval::<bool>("let #{x, y} = #{x: 1}; y == ()")?);
+    assert_eq!(
+        engine.eval::<INT>("let #{x, ..rest} = #{x: 1, y: 2, z: 3}; x + rest.len() * 10")?,
+        21
+    );
+    assert!(engine.eval::<bool>("let #{x, ..rest} = #{x: 1, y: 2}; !(\"x\" in rest)")?);
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let #{x} = 42; x")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_destructuring_const() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("const [a, b] = [1, 2]; a + b")?, 3);
+
+    assert!(matches!(
+        *engine.compile("const [a, b] = [1, 2]; b = 42;").expect_err("should error").0,
+        ParseErrorType::AssignmentToConstant(x) if x == "b"
+    ));
+
+    let mut scope = Scope::new();
+    engine.consume_with_scope(&mut scope, "const [a, ..rest] = [1, 2, 3];")?;
+
+    assert_eq!(scope.len(), 2);
+    assert!(scope.iter().all(|(_, is_constant, _)| is_constant));
+
+    // Constants with values only known at runtime are not propagated
+    assert_eq!(engine.eval::<INT>("const x = 1; let [x] = [42]; x")?, 42);
+
+    Ok(())
+}
+
+#[test]
+fn test_destructuring_errors() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert_eq!(
+            *engine
+                .compile("let [a, b, a] = x;")
+                .expect_err("should error")
+                .0,
+            ParseErrorType::DuplicatedVariable("a".to_string())
+        );
+        assert_eq!(
+            *engine
+                .compile("let [a, ..a] = x;")
+                .expect_err("should error")
+                .0,
+            ParseErrorType::DuplicatedVariable("a".to_string())
+        );
+        assert!(matches!(
+            *engine
+                .compile("let [..rest, a] = x;")
+                .expect_err("should error")
+                .0,
+            ParseErrorType::MissingToken(_, _)
+        ));
+        assert!(matches!(
+            *engine.compile("let [a, b];").expect_err("should error").0,
+            ParseErrorType::MissingToken(_, _)
+        ));
+        assert_eq!(
+            *engine
+                .compile("let [a, 1] = x;")
+                .expect_err("should error")
+                .0,
+            ParseErrorType::VariableExpected
+        );
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        *engine
+            .compile("let #{x, y, x} = m;")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::DuplicatedVariable("x".to_string())
+    );
+
+    Ok(())
+}
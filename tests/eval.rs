@@ -1,5 +1,8 @@
 use rhai::{Engine, EvalAltResult, LexError, ParseErrorType, RegisterFn, Scope, INT};
 
+#[cfg(not(feature = "no_object"))]
+use rhai::Map;
+
 #[test]
 fn test_eval() -> Result<(), Box<EvalAltResult>> {
     let engine = Engine::new();
@@ -129,3 +132,134 @@ fn test_eval_disabled() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_eval_compiled_expr() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    // Disabled by default
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"compile_expression("1 + 2").eval(#{})"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(f, _) if f.starts_with("compile_expression")
+    ));
+
+    engine.set_allow_compile_expression(true);
+    engine.disable_symbol("eval");
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let formula = compile_expression("x * x + y");
+                let r1 = formula.eval(#{ x: 1, y: 2 });
+                let r2 = formula.eval(#{ x: 3, y: 4 });
+                let r3 = formula.eval(#{ x: 5, y: 6 });
+                r1 * 10000 + r2 * 100 + r3
+            "#
+        )?,
+        31331
+    );
+
+    assert_eq!(
+        engine.eval::<String>(r#"type_of(compile_expression("42"))"#)?,
+        "compiled_expr"
+    );
+
+    // Variables of the calling script are not visible
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"let x = 1; compile_expression("x + 1").eval(#{})"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorVariableNotFound(v, _) if v == "x"
+    ));
+
+    // Errors during evaluation are catchable
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let e = compile_expression("x + y");
+                try { e.eval(#{ x: 1 }) } catch { -1 }
+            "#
+        )?,
+        -1
+    );
+
+    // Statements are rejected when compiling the expression
+    for script in &["let x = 1", "x = 1", "fn foo() {}", "import \"foo\" as bar"] {
+        let err = engine
+            .eval::<INT>(&format!("compile_expression({:?}).eval(#{{}})", script))
+            .expect_err("should error");
+
+        match *err {
+            EvalAltResult::ErrorInFunctionCall(f, _, err, _) if f == "compile_expression" => {
+                assert!(matches!(*err, EvalAltResult::ErrorParsing(_, _)))
+            }
+            err => panic!("wrong error: {}", err),
+        }
+    }
+
+    assert_eq!(
+        engine.eval::<INT>(r#"try { compile_expression("let x = 1") } catch { 42 }"#)?,
+        42
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_eval_compile_check() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    // Disabled by default
+    assert!(matches!(
+        *engine
+            .eval::<Map>(r#"compile_check("42")"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(f, _) if f.starts_with("compile_check")
+    ));
+
+    engine.set_allow_compile_expression(true);
+
+    let result = engine.eval::<Map>(r#"compile_check("let x = 40; x + 2")"#)?;
+    assert_eq!(result.len(), 1);
+    assert!(result["ok"].as_bool().unwrap());
+
+    // Missing semicolon
+    let result = engine.eval::<Map>(r#"compile_check("let x = 40\nx + 2")"#)?;
+    assert!(!result["ok"].as_bool().unwrap());
+    assert_eq!(
+        result["error_type"].clone().cast::<String>(),
+        "MissingToken"
+    );
+    assert_eq!(
+        result["error"].clone().cast::<String>(),
+        "Expecting ';' to terminate this statement"
+    );
+    assert_eq!(result["line"].as_int().unwrap(), 2);
+    assert_eq!(result["position"].as_int().unwrap(), 1);
+
+    // Unknown operator
+    let result = engine.eval::<Map>(r#"compile_check("x === 1")"#)?;
+    assert!(!result["ok"].as_bool().unwrap());
+    assert_eq!(result["error_type"].clone().cast::<String>(), "BadInput");
+    assert!(result["error"]
+        .clone()
+        .cast::<String>()
+        .starts_with("'===' is not a valid operator"));
+    assert_eq!(result["line"].as_int().unwrap(), 1);
+    assert_eq!(result["position"].as_int().unwrap(), 3);
+
+    // Unterminated block
+    assert_eq!(
+        engine.eval::<String>(r#"compile_check("if x { 1").error_type"#)?,
+        "MissingToken"
+    );
+
+    // Nothing is evaluated
+    assert!(engine.eval::<bool>(r#"compile_check("no_such_function(42)").ok"#)?);
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, Scope, INT};
+use rhai::{Engine, EvalAltResult, EvalOptions, LexError, ParseErrorType, RegisterFn, Scope, INT};
 
 #[test]
 fn test_expressions() -> Result<(), Box<EvalAltResult>> {
@@ -15,6 +15,10 @@ fn test_expressions() -> Result<(), Box<EvalAltResult>> {
     assert!(engine
         .eval_expression_with_scope::<INT>(&mut scope, "if x > 0 { 42 } else { 123 }")
         .is_err());
+    assert_eq!(
+        engine.eval_expression_with_scope::<INT>(&mut scope, "x > 0 ? 42 : 123")?,
+        42
+    );
 
     assert!(engine.eval_expression::<()>("40 + 2;").is_err());
     assert!(engine.eval_expression::<()>("40 + { 2 }").is_err());
@@ -26,6 +30,93 @@ fn test_expressions() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_expressions_with_options() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let mut scope = Scope::new();
+
+    scope.push("x", 10 as INT);
+
+    let mut options = EvalOptions::new();
+
+    assert!(engine
+        .eval_expression_with_options::<INT>(&mut scope, "if x > 0 { 42 } else { 123 }", &options)
+        .is_err());
+
+    options.set_allow_if_expression(true);
+
+    assert_eq!(
+        engine.eval_expression_with_options::<INT>(
+            &mut scope,
+            "if x > 0 { 42 } else { 123 }",
+            &options
+        )?,
+        42
+    );
+    assert!(engine
+        .eval_expression_with_options::<INT>(&mut scope, "switch x { 10 => 42, _ => 0 }", &options)
+        .is_err());
+    assert!(engine
+        .eval_expression_with_options::<INT>(
+            &mut scope,
+            "if x > 0 { let y = 42; y } else { 0 }",
+            &options
+        )
+        .is_err());
+    assert!(engine
+        .eval_expression_with_options::<INT>(
+            &mut scope,
+            "if x > 0 { while true {} } else { 0 }",
+            &options
+        )
+        .is_err());
+    assert!(engine
+        .eval_expression_with_options::<()>(&mut scope, "if x > 0 { x = 42 }", &options)
+        .is_err());
+    assert!(engine
+        .eval_expression_with_options::<()>(&mut scope, "let y = 42", &options)
+        .is_err());
+
+    options.set_allow_switch_expression(true);
+
+    assert_eq!(
+        engine.eval_expression_with_options::<INT>(
+            &mut scope,
+            "switch x { 10 => 42, _ => 0 }",
+            &options
+        )?,
+        42
+    );
+    assert!(engine
+        .eval_expression_with_options::<INT>(&mut scope, "{ let y = 40; y + 2 }", &options)
+        .is_err());
+
+    options.set_allow_statement_expression(true);
+
+    assert_eq!(
+        engine.eval_expression_with_options::<INT>(
+            &mut scope,
+            "{ let y = 40; y + 2 }",
+            &options
+        )?,
+        42
+    );
+    assert!(engine
+        .eval_expression_with_options::<()>(&mut scope, "let y = 42", &options)
+        .is_err());
+
+    let ast =
+        engine.compile_expression_with_options(&scope, "if x > 0 { x } else { -x }", &options)?;
+    assert_eq!(engine.eval_ast_with_scope::<INT>(&mut scope, &ast)?, 10);
+
+    // The default remains strict
+    assert!(engine
+        .eval_expression_with_scope::<INT>(&mut scope, "if x > 0 { 42 } else { 123 }")
+        .is_err());
+
+    Ok(())
+}
+
 /// This example taken from https://github.com/jonathandturner/rhai/issues/115
 #[test]
 #[cfg(not(feature = "no_object"))]
@@ -75,3 +166,153 @@ fn test_expressions_eval() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_expressions_empty_statements() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("{ 40 }; 42")?, 42);
+    assert_eq!(engine.eval::<INT>("let x = 42;; x")?, 42);
+    assert_eq!(engine.eval::<INT>("let x = { 40 };; { x += 2 }; x")?, 42);
+    engine.eval::<()>(";")?;
+
+    assert!(matches!(
+        *engine.compile("let x = { 42;").expect_err("should error").0,
+        ParseErrorType::MissingToken(t, _) if t == "}"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_expressions_statements_as_values() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_fn("add", |x: INT, y: INT| x + y);
+
+    // Function call arguments
+    assert_eq!(
+        engine.eval::<INT>("let c = true; add(if c { 40 } else { 0 }, 2)")?,
+        42
+    );
+    assert_eq!(
+        engine.eval::<INT>("add(switch 2 { 1 => 0, 2 => 40, _ => 1 }, { let x = 1; x + 1 })")?,
+        42
+    );
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval::<INT>("let x = 40; x.add(if x > 0 { 2 } else { 0 })")?,
+        42
+    );
+
+    // Array and object map literal elements
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            "let a = [if true { 1 } else { 0 }, switch 1 { 1 => 2, _ => 0 }, { 39 }]; a[0] + a[1] + a[2]"
+        )?,
+        42
+    );
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            "let m = #{ a: if false { 0 } else { 1 }, b: switch 1 { 1 => 2, _ => 0 }, c: { 39 } }; m.a + m.b + m.c"
+        )?,
+        42
+    );
+
+    // Return values
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                fn foo(x) { return if x > 0 { x } else { -x }; }
+                fn bar(x) { return switch x { 0 => 42, _ => x }; }
+                foo(-40) + bar(2)
+            "
+        )?,
+        42
+    );
+    assert_eq!(engine.eval::<INT>("return if true { 42 } else { 0 };")?, 42);
+
+    // Not allowed in expressions
+    for (expr, kind) in &[
+        ("add(if true { 1 } else { 2 }, 3)", "if-expressions"),
+        ("1 + switch 1 { _ => 2 }", "switch-expressions"),
+        ("add({ 1 }, 2)", "statement-expressions"),
+        ("loop { break 42; }", "loop-expressions"),
+    ] {
+        match *engine.compile_expression(expr).expect_err("should error").0 {
+            ParseErrorType::BadInput(LexError::ImproperSymbol(_, msg)) => {
+                assert_eq!(msg, format!("{} are disabled in this context", kind))
+            }
+            err => panic!("wrong error: {}", err),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_expressions_reuse_scopes() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let mut scope = Scope::new();
+    scope.push("x", 0 as INT);
+    scope.push("y", 0 as INT);
+
+    let ast = engine.compile_expression_with_scope(&scope, "x * 10 + y")?;
+
+    // Different order
+    let mut scope = Scope::new();
+    scope.push("y", 2 as INT);
+    scope.push("x", 4 as INT);
+    assert_eq!(engine.eval_ast_with_scope::<INT>(&mut scope, &ast)?, 42);
+
+    // Extra variables
+    let mut scope = Scope::new();
+    scope.push("a", true);
+    scope.push("x", 3 as INT);
+    scope.push("y", 12 as INT);
+    scope.push("b", "hello");
+    assert_eq!(engine.eval_ast_with_scope::<INT>(&mut scope, &ast)?, 42);
+
+    // Missing variable
+    let mut scope = Scope::new();
+    scope.push("x", 4 as INT);
+    scope.push("z", 2 as INT);
+    assert!(matches!(
+        *engine.eval_ast_with_scope::<INT>(&mut scope, &ast).expect_err("should error"),
+        EvalAltResult::ErrorVariableNotFound(name, _) if name == "y"
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_optimize"))]
+fn test_expressions_optimize_with_scope() -> Result<(), Box<EvalAltResult>> {
+    use rhai::OptimizationLevel;
+
+    let mut engine = Engine::new();
+
+    let mut scope = Scope::new();
+    scope.push_constant("K", 40 as INT);
+
+    for &level in &[OptimizationLevel::Simple, OptimizationLevel::Full] {
+        engine.set_optimization_level(level);
+
+        let ast = engine.compile_expression_with_scope(&scope, "K + 2")?;
+        assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+
+        let ast = engine.compile_with_scope(&scope, "K + 2")?;
+        assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+    }
+
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let ast = engine.compile_expression_with_scope(&scope, "K + 2")?;
+    assert!(engine.eval_ast::<INT>(&ast).is_err());
+
+    Ok(())
+}
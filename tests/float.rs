@@ -1,5 +1,5 @@
 #![cfg(not(feature = "no_float"))]
-use rhai::{Engine, EvalAltResult, RegisterFn, FLOAT};
+use rhai::{Engine, EvalAltResult, RegisterFn, FLOAT, INT};
 
 const EPSILON: FLOAT = 0.000_000_000_1;
 
@@ -87,3 +87,50 @@ fn test_float_func() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_float_promotion() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Debug, Clone, PartialEq)]
+    struct Meters(FLOAT);
+
+    let mut engine = Engine::new();
+
+    engine
+        .register_fn("meters", |x: FLOAT| Meters(x))
+        .register_fn("+", |a: Meters, b: FLOAT| Meters(a.0 + b))
+        .register_fn("scale", |x: FLOAT, y: FLOAT| x * y)
+        .register_fn("scale", |x: FLOAT, y: INT| x * y as FLOAT * 10.0)
+        .register_fn("pick", |_: FLOAT, _: INT| 1 as INT)
+        .register_fn("pick", |_: INT, _: FLOAT| 2 as INT)
+        .register_fn("truncate", |x: INT| x);
+
+    // Off by default
+    assert!(engine.eval::<Meters>("meters(1)").is_err());
+
+    engine.set_int_to_float_promotion(true);
+    assert!(engine.int_to_float_promotion());
+
+    assert_eq!(engine.eval::<Meters>("meters(1) + 2")?, Meters(3.0));
+    assert_eq!(
+        engine.eval::<Meters>("let x = 40; meters(x) + 2.0")?,
+        Meters(42.0)
+    );
+
+    // Exact matches are preferred, then the fewest promotions
+    assert_eq!(engine.eval::<FLOAT>("scale(2.0, 3.0)")?, 6.0);
+    assert_eq!(engine.eval::<FLOAT>("scale(2.0, 3)")?, 60.0);
+    assert_eq!(engine.eval::<FLOAT>("scale(2, 3)")?, 60.0);
+    assert_eq!(engine.eval::<FLOAT>("scale(2, 3.0)")?, 6.0);
+
+    // Leftmost arguments are promoted first
+    assert_eq!(engine.eval::<INT>("pick(1, 2)")?, 1);
+
+    // FLOAT is never converted to INT
+    assert!(engine.eval::<INT>("truncate(1.5)").is_err());
+
+    // A promoted variable passed by reference is not modified
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(engine.eval::<INT>("let x = 5; x.scale(2.0); x")?, 5);
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, RegisterFn, INT};
+use rhai::{Engine, EvalAltResult, FnPtr, RegisterFn, INT};
 
 #[test]
 fn test_fn_ptr() -> Result<(), Box<EvalAltResult>> {
@@ -73,7 +73,7 @@ fn test_fn_ptr() -> Result<(), Box<EvalAltResult>> {
             "#
             )
             .expect_err("should error"),
-        EvalAltResult::ErrorInFunctionCall(fn_name, err, _)
+        EvalAltResult::ErrorInFunctionCall(fn_name, _, err, _)
             if fn_name == "foo" && matches!(*err, EvalAltResult::ErrorUnboundThis(_))
     ));
 
@@ -111,3 +111,191 @@ fn test_fn_ptr_curry() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_fn_ptr_introspection() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_fn("sum3", |x: INT, y: INT, z: INT| x + y + z);
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let f = curry(Fn("sum3"), 1, 2);
+                curry_count(f) * 100 + call(f, 3)
+            "#
+        )?,
+        206
+    );
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert_eq!(
+            engine.eval::<String>(r#"let f = Fn("sum3"); f.name"#)?,
+            "sum3"
+        );
+        assert_eq!(
+            engine.eval::<INT>(
+                r#"
+                    let f = Fn("sum3").curry(1);
+                    let g = f.curry(2, 3);
+                    f.curry_count * 100 + g.curry_count * 10 + g.call()
+                "#
+            )?,
+            136
+        );
+    }
+
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert!(engine.eval::<bool>("let f = |x| x + 1; f.is_anonymous")?);
+        assert!(!engine.eval::<bool>(r#"Fn("sum3").is_anonymous"#)?);
+
+        #[cfg(not(feature = "no_closure"))]
+        assert_eq!(
+            engine.eval::<INT>(
+                r#"
+                    let x = 40;
+                    let f = |a, b| a + b + x;
+                    let g = f.curry(1);
+                    g.curry_count * 100 + g.call(1)
+                "#
+            )?,
+            242
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_fn_ptr_strict() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_fn("foo", |x: INT| x + 1);
+
+    assert!(engine.eval::<FnPtr>(r#"Fn("bar")"#).is_ok());
+
+    engine.set_strict_fn_pointers(true);
+
+    assert!(matches!(
+        *engine
+            .eval::<FnPtr>(r#"Fn("bar")"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(name, _) if name == "bar"
+    ));
+
+    assert_eq!(engine.eval::<INT>(r#"call(Fn("foo"), 41)"#)?, 42);
+    assert_eq!(engine.eval::<String>(r#"call(Fn("to_string"), 42)"#)?, "42");
+    assert!(engine.eval::<FnPtr>(r#"Fn("print")"#).is_ok());
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<INT>(r#"fn bar(x) { x * 2 } call(Fn("bar"), 21)"#)?,
+        42
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_fn_ptr_call_expr() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn add(x, y) { x + y }
+                fn mul(x, y) { x * y }
+
+                let handlers = [Fn("add"), Fn("mul")];
+
+                handlers[0](2, 3) + handlers[1](2, 3)
+            "#
+        )?,
+        11
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn add(x, y) { x + y }
+                fn get_handler() { Fn("add") }
+
+                get_handler()(1, 2) + (get_handler())(3, 4)
+            "#
+        )?,
+        10
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn add(x, y) { x + y }
+                let h = Fn("add");
+                (h)(40, 2)
+            "#
+        )?,
+        42
+    );
+
+    assert_eq!(engine.eval::<INT>("(|x| x * 2)(21)")?, 42);
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let x = 42; (x)(1)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_fn_ptr_call_property() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    // `obj.handler(x)` is a method call, with `this` bound to the object map
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn add(x) { this.base + x }
+                let obj = #{ base: 40, handler: Fn("add") };
+                obj.handler(2)
+            "#
+        )?,
+        42
+    );
+
+    // `(obj.handler)(x)` calls the function pointer stored in the property
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn add(x, y) { x + y }
+                let obj = #{ handler: Fn("add") };
+                (obj.handler)(40, 2)
+            "#
+        )?,
+        42
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn mul(x, y) { x * y }
+                fn get_ptr() { this.list[0] }
+                let obj = #{ list: [Fn("mul")] };
+                obj.list[0](6, 7) + obj.get_ptr()(1, 1)
+            "#
+        )?,
+        43
+    );
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, Module, INT};
+use rhai::{Dynamic, Engine, EvalAltResult, Module, ParseErrorType, RegisterFn, INT};
 
 #[cfg(not(feature = "no_index"))]
 #[test]
@@ -117,3 +117,187 @@ fn test_for_module_iterator() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_for_break_value() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let found = for x in [1, 3, 8, 5, 10] {
+                    if x % 2 == 0 { break x; }
+                };
+
+                found
+            "
+        )?,
+        8
+    );
+
+    engine.eval::<()>("let found = for x in [1, 3, 5] { if x > 10 { break x; } }; found")?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_for_counter() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let sum = 0;
+                for (i, x) in [10, 20, 30] { sum += i * x; }
+                sum
+            "
+        )?,
+        80
+    );
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let s = "";
+                for (i, ch) in "abc" { s += ch; s += i; }
+                s
+            "#
+        )?,
+        "a0b1c2"
+    );
+    assert_eq!(
+        engine.eval::<INT>("let x = 0; for (i, v) in range(5, 10) { x = i; } x")?,
+        4
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_for_counter_errors() {
+    let engine = Engine::new();
+
+    assert!(matches!(
+        *engine.compile("for (x, x) in range(0, 2) {}").expect_err("should error").0,
+        ParseErrorType::FnDuplicatedParam(f, x) if f == "" && x == "x"
+    ));
+    assert!(matches!(
+        *engine.compile("for (i x) in range(0, 2) {}").expect_err("should error").0,
+        ParseErrorType::MissingToken(t, _) if t == ","
+    ));
+    assert!(matches!(
+        *engine.compile("for (i, x in range(0, 2) {}").expect_err("should error").0,
+        ParseErrorType::MissingToken(t, _) if t == ")"
+    ));
+    assert!(matches!(
+        *engine
+            .compile("for (i, fn) in range(0, 2) {}")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::Reserved(_) | ParseErrorType::VariableExpected
+    ));
+}
+
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_for_object_pairs() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let map = #{ a: 1, b: 2, c: 3 };
+                let sum = 0;
+                for (k, v) in map {
+                    if k == "b" { sum += v * 10; } else { sum += v; }
+                }
+                sum
+            "#
+        )?,
+        24
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_object"))]
+#[derive(Debug)]
+struct Inventory {
+    items: Vec<INT>,
+    clones: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+}
+
+#[cfg(not(feature = "no_object"))]
+impl Clone for Inventory {
+    fn clone(&self) -> Self {
+        self.clones
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Self {
+            items: self.items.clone(),
+            clones: self.clones.clone(),
+        }
+    }
+}
+
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_for_iterator_fn() -> Result<(), Box<EvalAltResult>> {
+    let clones = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let clones2 = clones.clone();
+
+    let mut engine = Engine::new();
+
+    engine
+        .register_type_with_name::<Inventory>("Inventory")
+        .register_fn("new_inventory", move || Inventory {
+            items: vec![1, 2, 3, 0, 5],
+            clones: clones2.clone(),
+        })
+        .register_iterator_fn::<Inventory, _>(|inv| {
+            let items = inv.items.clone();
+            Box::new(items.into_iter().map(|n| {
+                if n > 0 {
+                    Ok(Dynamic::from(n * 10))
+                } else {
+                    Err("empty slot".into())
+                }
+            }))
+        });
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let inv = new_inventory();
+                let sum = 0;
+                for x in inv {
+                    sum += x;
+                    if x == 30 { break; }
+                }
+                for x in new_inventory() {
+                    sum += x;
+                    if x == 20 { break; }
+                }
+                sum
+            "
+        )?,
+        90
+    );
+    assert_eq!(clones.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+    let err = engine
+        .eval::<INT>(
+            r"
+                let sum = 0;
+                for x in new_inventory() { sum += x; }
+                sum
+            ",
+        )
+        .expect_err("should error");
+
+    assert!(matches!(*err, EvalAltResult::ErrorRuntime(ref v, pos)
+                    if v.clone().take_immutable_string().unwrap() == "empty slot"
+                    && pos == rhai::Position::new(3, 17)));
+
+    Ok(())
+}
@@ -1,5 +1,8 @@
 #![cfg(not(feature = "no_function"))]
-use rhai::{Engine, EvalAltResult, FnNamespace, Module, ParseErrorType, RegisterFn, INT};
+use rhai::{
+    Engine, EvalAltResult, FnNamespace, Module, ParseErrorType, Position, RegisterFn, Scope, AST,
+    INT,
+};
 
 #[test]
 fn test_functions() -> Result<(), Box<EvalAltResult>> {
@@ -193,7 +196,210 @@ fn test_function_captures() -> Result<(), Box<EvalAltResult>> {
             )
             .expect_err("should error")
             .0,
-        ParseErrorType::MalformedCapture(_)
+        ParseErrorType::CaptureNotSupported(_, f) if f == "foo"
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_closure"))]
+fn test_function_captures_syntax() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn foo() { x * 2 }
+
+                let x = 21;
+
+                foo!()
+            "#
+        )?,
+        42
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn foo() { x - 1 }
+
+                let a = [1, 2, 42];
+                let x = 3;
+
+                a[foo!()]
+            "#
+        )?,
+        42
+    );
+
+    #[cfg(not(feature = "no_module"))]
+    {
+        let err = *engine
+            .compile(r#"import "m" as m; m::foo!()"#)
+            .expect_err("should error")
+            .0;
+
+        assert!(matches!(&err, ParseErrorType::CaptureNotSupported(_, f) if f == "foo"));
+        assert!(err.to_string().contains("'foo!(...)'"));
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        let err = *engine
+            .compile("let x = 1; x.foo!()")
+            .expect_err("should error")
+            .0;
+
+        assert!(matches!(&err, ParseErrorType::CaptureNotSupported(_, f) if f == "foo"));
+        assert!(err.to_string().contains("'foo!(...)'"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_functions_default_params() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile(
+        r#"
+            fn greet(name, greeting = "hello", punct = "!") {
+                greeting + ", " + name + punct
+            }
+        "#,
+    )?;
+
+    assert_eq!(
+        engine.eval_ast::<String>(&ast.merge(&engine.compile(r#"greet("world")"#)?))?,
+        "hello, world!"
+    );
+    assert_eq!(
+        engine.eval_ast::<String>(&ast.merge(&engine.compile(r#"greet("world", "hi")"#)?))?,
+        "hi, world!"
+    );
+    assert_eq!(
+        engine.eval_ast::<String>(&ast.merge(&engine.compile(r#"greet("world", "hi", "?")"#)?))?,
+        "hi, world?"
+    );
+    assert_eq!(
+        engine.call_fn::<String>(&mut Scope::new(), &ast, "greet", ("there",))?,
+        "hello, there!"
+    );
+
+    // A function with exactly the number of arguments takes precedence
+    assert_eq!(
+        engine.eval::<INT>("fn foo(x, y = 1) { x + y } fn foo(x) { -x } foo(41) + foo(41, 2)")?,
+        2
+    );
+
+    // Missing non-defaulted parameters
+    assert!(matches!(
+        *engine.eval_ast::<String>(&ast.merge(&engine.compile("greet()")?)).expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(f, _) if f == "greet ()"
+    ));
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval::<INT>("fn add(y = 1) { this + y } let x = 41; x.add() + x.add(2)")?,
+        85
+    );
+
+    assert_eq!(
+        engine.eval::<INT>("let f = |x, y = -1| x + y; call(f, 42) + call(f, 42, 1)")?,
+        84
+    );
+
+    #[cfg(not(feature = "no_closure"))]
+    assert_eq!(
+        engine.eval::<INT>("let z = 40; let f = |x = 2| x + z; call(f) + call(f, 0)")?,
+        82
+    );
+
+    assert!(matches!(
+        *engine.compile("fn foo(x = 1, y) { x + y }").expect_err("should error").0,
+        ParseErrorType::FnMissingDefault(f, p) if f == "foo" && p == "y"
+    ));
+    assert!(matches!(
+        *engine
+            .compile("fn foo(x, y = x) { x + y }")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::ExprExpected(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+fn test_functions_variadic() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>("fn count(level, ...args) { level * 10 + len(args) } count(1)")?,
+        10
+    );
+    assert_eq!(
+        engine.eval::<INT>("fn count(level, ...args) { level * 10 + len(args) } count(1, 2, 3)")?,
+        12
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            "fn sum(...xs) { let s = 0; for x in xs { s += x; } s } sum() + sum(1, 2, 3, 4)"
+        )?,
+        10
+    );
+
+    // A function with exactly the number of arguments takes precedence
+    assert_eq!(
+        engine.eval::<INT>("fn foo(...xs) { len(xs) } fn foo(x, y) { -1 } foo(1) + foo(1, 2)")?,
+        0
+    );
+
+    // Missing fixed parameters
+    assert!(matches!(
+        *engine
+            .eval::<INT>("fn foo(x, ...xs) { x } foo()")
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(f, _) if f == "foo ()"
+    ));
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval::<INT>("fn add(...xs) { this + xs.len() } let x = 40; x.add(0, 0)")?,
+        42
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            "let f = |x, ...xs| x + len(xs); call(f, 40, 0, 0) + call(f, 40) + call(f, 0, 0)"
+        )?,
+        83
+    );
+
+    #[cfg(not(feature = "no_closure"))]
+    assert_eq!(
+        engine.eval::<INT>("let z = 40; let f = |...xs| z + len(xs); call(f) + call(f, 1, 2)")?,
+        82
+    );
+
+    let ast = engine.compile("fn log(level, ...args) { level + len(args) }")?;
+    assert_eq!(
+        engine.call_fn::<INT>(
+            &mut Scope::new(),
+            &ast,
+            "log",
+            (40 as INT, 1 as INT, 2 as INT)
+        )?,
+        42
+    );
+
+    assert!(matches!(
+        *engine.compile("fn foo(...xs, y) { y }").expect_err("should error").0,
+        ParseErrorType::FnMisplacedRestParam(f, p) if f == "foo" && p == "xs"
     ));
 
     Ok(())
@@ -224,3 +430,206 @@ fn test_function_is_def() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "no_object"))]
+fn test_functions_collect_fn_calls() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_custom_syntax(&["twice", "$expr$"], 0, |context, inputs| {
+        context.eval_expression_tree(&inputs[0])?;
+        context.eval_expression_tree(&inputs[0])
+    })?;
+
+    let ast = engine.compile(
+        r#"
+            fn helper(x) { log(x, "helper"); }
+            fn process(x, y) {
+                helper(x);
+                x.update(y, 1)
+            }
+            let f = |x| helper(x);
+            twice record(42);
+            process(1, 2);
+            calc::compute(1);
+        "#,
+    )?;
+
+    let fn_calls = ast.collect_fn_calls();
+
+    let list = |caller: Option<&str>| {
+        fn_calls
+            .iter()
+            .filter(|f| f.caller == caller)
+            .flat_map(|f| f.calls.iter().map(|c| c.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(fn_calls[0].caller, None);
+    assert_eq!(fn_calls[0].num_params, 0);
+
+    assert_eq!(list(None), ["record/1", "process/2", "calc::compute/1"]);
+    assert_eq!(list(Some("helper")), ["log/2"]);
+    assert_eq!(list(Some("process")), ["helper/1", "update/3"]);
+
+    // Closures are listed as anonymous functions
+    assert!(fn_calls
+        .iter()
+        .any(|f| f.caller.map_or(false, |name| name.starts_with("anon$"))
+            && f.num_params == 1
+            && f.calls
+                .iter()
+                .map(|c| c.to_string())
+                .eq(["helper/1"].iter().cloned())));
+
+    let call = &fn_calls
+        .iter()
+        .find(|f| f.caller == Some("process"))
+        .unwrap()
+        .calls[1];
+    assert_eq!(call.name, "update");
+    assert_eq!(call.num_args, 3);
+    assert_eq!(call.pos, Position::new(5, 19));
+
+    let call = &fn_calls[0].calls[2];
+    assert_eq!(call.namespace.as_deref(), Some("calc"));
+    assert_eq!(call.name, "compute");
+
+    Ok(())
+}
+
+#[test]
+fn test_functions_compile_into() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let mut scope = Scope::new();
+    let mut lib = AST::default();
+
+    let ast = engine.compile_into(&mut lib, "fn f(x) { x * 2 } let y = 1;")?;
+    engine.consume_ast_with_scope(&mut scope, &ast)?;
+    assert_eq!(lib.iter_functions().count(), 1);
+
+    let ast = engine.compile_into(&mut lib, "fn g(x) { f(x) + 1 } y = f(21); y")?;
+    assert_eq!(engine.eval_ast_with_scope::<INT>(&mut scope, &ast)?, 42);
+    assert_eq!(lib.iter_functions().count(), 2);
+
+    // Redefinition replaces the old function, overloads are kept
+    let ast = engine.compile_into(&mut lib, "fn f(x) { x + 1 } fn f(x, y) { x + y }")?;
+    assert_eq!(ast.iter_functions().count(), 3);
+    assert_eq!(lib.iter_functions().count(), 3);
+
+    let ast = engine.compile_into(&mut lib, "g(y) + f(1, 2)")?;
+    assert_eq!(engine.eval_ast_with_scope::<INT>(&mut scope, &ast)?, 47);
+
+    Ok(())
+}
+
+#[test]
+fn test_functions_backtrace() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let mut ast = engine.compile(
+        r#"
+            fn foo(x) {
+                if x > 0 { throw "boom"; }
+                let f = |y| bar(y + 1);
+                call(f, x)
+            }
+            fn bar(x) { foo(x) }
+            bar(0)
+        "#,
+    )?;
+    ast.set_source(Some("script"));
+
+    let err = engine.eval_ast::<()>(&ast).expect_err("should error");
+
+    let frames: Vec<_> = err
+        .backtrace()
+        .iter()
+        .map(|frame| (frame.name.to_string(), frame.source, frame.position))
+        .collect();
+
+    assert_eq!(frames.len(), 5);
+    assert_eq!(frames[0].0, "foo");
+    assert_eq!(frames[0].2, Position::new(7, 25));
+    assert_eq!(frames[1].0, "bar");
+    assert!(frames[2].0.starts_with("anon$"));
+    assert_eq!(frames[3].0, "foo");
+    assert_eq!(frames[4].0, "bar");
+    assert_eq!(frames[4].2, Position::new(8, 13));
+    assert!(frames
+        .iter()
+        .all(|(_, source, _)| *source == Some("script")));
+    assert_eq!(err.position(), Position::new(8, 13));
+
+    let text = err.to_string();
+    let lines: Vec<_> = text.lines().collect();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0], "Runtime error: boom (line 3, position 28)");
+    assert_eq!(
+        lines[1],
+        "    in call to function 'foo' from 'script' (line 7, position 25)"
+    );
+    assert!(lines[3].starts_with("    in call to closure 'anon$"));
+
+    // Module-qualified functions
+    #[cfg(not(feature = "no_module"))]
+    {
+        use rhai::module_resolvers::StaticModuleResolver;
+
+        let mut ast = engine.compile(r#"fn fail() { throw "oops"; }"#)?;
+        ast.set_source(Some("lib"));
+        let module = Module::eval_ast_as_new(Default::default(), &ast, &engine)?;
+
+        let mut resolver = StaticModuleResolver::new();
+        resolver.insert("lib", module);
+        engine.set_module_resolver(resolver);
+
+        let err = engine
+            .eval::<()>(r#"import "lib" as m; m::fail()"#)
+            .expect_err("should error");
+
+        let frames: Vec<_> = err
+            .backtrace()
+            .iter()
+            .map(|frame| (frame.name.to_string(), frame.source, frame.position))
+            .collect();
+
+        assert_eq!(
+            frames,
+            [("m::fail".to_string(), Some("lib"), Position::new(1, 23))]
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "unchecked"))]
+fn test_functions_backtrace_depth() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_max_call_levels(8);
+
+    let err = engine
+        .eval::<()>(
+            r#"
+                fn recurse(n) {
+                    if n == 0 { throw "bottom"; }
+                    recurse(n - 1)
+                }
+                recurse(6)
+            "#,
+        )
+        .expect_err("should error");
+
+    let frames = err.backtrace();
+
+    // The backtrace is capped (at 6 frames in debug builds) but keeps the outermost call
+    #[cfg(debug_assertions)]
+    assert_eq!(frames.len(), 6);
+    assert!(frames.iter().all(|frame| frame.name == "recurse"));
+    assert_eq!(frames.last().unwrap().position, Position::new(6, 17));
+    assert_eq!(frames[0].position, Position::new(4, 21));
+
+    Ok(())
+}
@@ -1,6 +1,9 @@
 #![cfg(not(feature = "no_object"))]
 
-use rhai::{Engine, EvalAltResult, ImmutableString, RegisterFn, INT};
+use rhai::{Dynamic, Engine, EvalAltResult, ImmutableString, Map, RegisterFn, INT};
+
+#[cfg(not(feature = "no_index"))]
+use rhai::{Array, Position};
 
 #[test]
 fn test_get_set() -> Result<(), Box<EvalAltResult>> {
@@ -43,6 +46,10 @@ fn test_get_set() -> Result<(), Box<EvalAltResult>> {
     engine.register_fn("new_ts", TestStruct::new);
 
     assert_eq!(engine.eval::<INT>("let a = new_ts(); a.x = 500; a.x")?, 500);
+    assert_eq!(
+        engine.eval::<INT>("let a = new_ts(); a.x += 500; a.x")?,
+        501
+    );
     assert_eq!(engine.eval::<INT>("let a = new_ts(); a.x.add(); a.x")?, 42);
     assert_eq!(engine.eval::<INT>("let a = new_ts(); a.y.add(); a.y")?, 0);
 
@@ -60,6 +67,10 @@ fn test_get_set() -> Result<(), Box<EvalAltResult>> {
             engine.eval::<INT>(r#"let a = new_ts(); a["abc"] = 42; a["abc"]"#)?,
             42
         );
+        assert_eq!(
+            engine.eval::<INT>(r#"let a = new_ts(); a["abc"] += 42; a["abc"]"#)?,
+            46
+        );
     }
     Ok(())
 }
@@ -128,3 +139,291 @@ fn test_get_set_chain() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_get_set_indexer_multi() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Clone)]
+    struct TestGrid {
+        width: INT,
+        cells: Vec<INT>,
+    }
+
+    impl TestGrid {
+        fn new(width: INT, height: INT) -> Self {
+            Self {
+                width,
+                cells: vec![0; (width * height) as usize],
+            }
+        }
+
+        fn get_cell(&mut self, x: INT, y: INT) -> INT {
+            self.cells[(y * self.width + x) as usize]
+        }
+
+        fn set_cell(&mut self, x: INT, y: INT, value: INT) {
+            self.cells[(y * self.width + x) as usize] = value;
+        }
+    }
+
+    let mut engine = Engine::new();
+
+    engine.register_type::<TestGrid>();
+    engine.register_fn("new_grid", TestGrid::new);
+    engine.register_indexer_get_set2(TestGrid::get_cell, TestGrid::set_cell);
+
+    assert_eq!(engine.eval::<INT>("let g = new_grid(3, 2); g[2, 1]")?, 0);
+    assert_eq!(
+        engine.eval::<INT>("let g = new_grid(3, 2); g[2, 1] = 42; g[2, 1]")?,
+        42
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let g = new_grid(3, 2);
+                for y in range(0, 2) {
+                    for x in range(0, 3) {
+                        g[x, y] = x + y * 10;
+                    }
+                }
+                g[1, 1] += 1;
+                g[2, 0] + g[1, 1]
+            "
+        )?,
+        2 + 12
+    );
+    assert_eq!(
+        engine.eval::<INT>("let g = new_grid(3, 2); g[0, 1] = 9; g[0, 0] + g[0, 1]")?,
+        9
+    );
+    assert_eq!(
+        engine
+            .eval::<TestGrid>("let g = new_grid(3, 2); g[1, 0] -= 5; g")?
+            .cells,
+        vec![0, -5, 0, 0, 0, 0]
+    );
+
+    // Single-index indexing is unaffected
+    assert_eq!(engine.eval::<INT>("let a = [1, 2, 3]; a[1]")?, 2);
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let g = new_grid(3, 2); g[1, 2, 3]")
+            .expect_err("should error"),
+        EvalAltResult::ErrorIndexingType(_, _)
+    ));
+
+    let err = engine
+        .eval::<INT>("let a = [1, 2, 3]; a[1, 2]")
+        .expect_err("should error");
+    assert!(matches!(
+        *err,
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Data type is incorrect: array cannot be indexed with 2 indices (line 1, position 21)"
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let a = [1, 2, 3]; a[1, 2] = 0; a[0]")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"let s = "hello"; s[1, 2]"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_get_set_map_override() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    // 'area' is a computed property, read-write
+    engine
+        .register_get("area", |map: &mut Map| -> Dynamic {
+            match (map.get("w"), map.get("h")) {
+                (Some(w), Some(h)) => (w.as_int().unwrap() * h.as_int().unwrap()).into(),
+                // Returning () falls back to the 'area' key
+                _ => Dynamic::UNIT,
+            }
+        })
+        .register_set("area", |map: &mut Map, area: INT| {
+            map.insert("w".into(), area.into());
+            map.insert("h".into(), (1 as INT).into());
+        });
+    // 'size' is a computed property, read-only
+    engine.register_get("size", |map: &mut Map| map.len() as INT);
+    // 'pos' is a computed property holding an object map
+    engine
+        .register_get("pos", |map: &mut Map| -> Dynamic {
+            let mut pos = Map::new();
+            pos.insert("x".into(), map.get("x").cloned().unwrap_or_default());
+            pos.insert("y".into(), map.get("y").cloned().unwrap_or_default());
+            pos.into()
+        })
+        .register_set("pos", |map: &mut Map, pos: Map| {
+            map.extend(pos);
+        });
+    // A method with the same name as a property holding a function pointer
+    engine.register_fn("describe", |_: &mut Map| "native");
+
+    // Dot access
+    assert_eq!(engine.eval::<INT>("let m = #{w: 6, h: 7}; m.area")?, 42);
+    assert_eq!(engine.eval::<INT>("let m = #{area: 1}; m.area")?, 1);
+    engine.eval::<()>("let m = #{}; m.area")?;
+    assert_eq!(engine.eval::<INT>("let m = #{w: 6, h: 7}; m.w")?, 6);
+    assert_eq!(engine.eval::<INT>("let m = #{a: 1, b: 2}; m.size")?, 2);
+    assert_eq!(
+        engine.eval::<INT>("let m = #{w: 6, h: 7}; m.area = 5; m.w * 10 + m.h")?,
+        51
+    );
+    assert_eq!(
+        engine.eval::<INT>("let m = #{w: 6, h: 7}; m.area += 8; m.w")?,
+        50
+    );
+    assert_eq!(engine.eval::<INT>("let m = #{}; m.x = 5; m.x")?, 5);
+
+    // Values of computed properties are fed back via the setter
+    assert_eq!(engine.eval::<INT>("let m = #{x: 1, y: 2}; m.pos.y")?, 2);
+    assert_eq!(
+        engine.eval::<INT>("let m = #{x: 1, y: 2}; m.pos.x = 10; m.pos.x + m.y")?,
+        12
+    );
+
+    // Only the setter of a property held by a nested object map is called
+    assert_eq!(
+        engine.eval::<INT>("let m = #{inner: #{w: 6, h: 7}}; m.inner.area = 3; m.inner.w")?,
+        3
+    );
+
+    // Index access always reads keys
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert_eq!(
+            engine.eval::<INT>(r#"let m = #{w: 6, h: 7, area: 1}; m["area"]"#)?,
+            1
+        );
+        assert_eq!(engine.eval::<INT>(r#"let m = #{size: 0}; m["size"]"#)?, 0);
+    }
+
+    // Method calls
+    #[cfg(not(feature = "no_function"))]
+    {
+        assert_eq!(
+            engine.eval::<String>(r#"let m = #{describe: || "fn ptr"}; m.describe()"#)?,
+            "native"
+        );
+        assert_eq!(
+            engine.eval::<String>(r#"let m = #{describe2: || "fn ptr"}; m.describe2()"#)?,
+            "fn ptr"
+        );
+
+        // Functions in the standard library do not override function pointers held by properties
+        assert_eq!(
+            engine.eval::<INT>("let m = #{ keys: || 42 }; m.keys()")?,
+            42
+        );
+        assert_eq!(
+            engine.eval::<String>(r#"let m = #{ len: || "custom" }; m.len()"#)?,
+            "custom"
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_get_set_indexer_override() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    // Negative indices count from the end
+    engine
+        .register_indexer_get(|list: &mut Array, index: INT| -> Dynamic {
+            let len = list.len() as INT;
+
+            if index < 0 && index >= -len {
+                list[(len + index) as usize].clone()
+            } else {
+                // Returning () falls back to the built-in indexing
+                Dynamic::UNIT
+            }
+        })
+        .register_indexer_set_result(|list: &mut Array, index: INT, value: INT| {
+            let len = list.len() as INT;
+            let offset = if index < 0 { len + index } else { index };
+
+            if offset < 0 || offset >= len {
+                return EvalAltResult::ErrorArrayBounds(list.len(), index, Position::NONE).into();
+            }
+
+            list[offset as usize] = value.into();
+            Ok(())
+        });
+    // Arrays can be indexed by strings
+    engine.register_indexer_get(|list: &mut Array, name: &str| match name {
+        "first" => list.first().cloned().unwrap_or_default(),
+        _ => Dynamic::UNIT,
+    });
+    // Object maps can be indexed by integers
+    engine.register_indexer_get(|map: &mut Map, index: INT| {
+        let mut keys: Vec<_> = map.keys().cloned().collect();
+        keys.sort();
+        keys.get(index as usize).cloned().unwrap_or_default()
+    });
+
+    assert_eq!(engine.eval::<INT>("let a = [1, 2, 3]; a[1]")?, 2);
+    assert_eq!(engine.eval::<INT>("let a = [1, 2, 3]; a[-1]")?, 3);
+    assert_eq!(engine.eval::<INT>("let a = [1, 2, 3]; a[-1] = 9; a[2]")?, 9);
+    assert_eq!(
+        engine.eval::<INT>("let a = [1, 2, 3]; a[0] += 9; a[0]")?,
+        10
+    );
+    assert_eq!(engine.eval::<INT>(r#"let a = [1, 2, 3]; a["first"]"#)?, 1);
+    assert_eq!(
+        engine.eval::<INT>("let a = [[1, 2], [3, 4]]; a[-1][-2]")?,
+        3
+    );
+    assert_eq!(engine.eval::<INT>("let a = [1, 2, 3]; a.len")?, 3);
+
+    assert_eq!(
+        engine.eval::<String>(r#"let m = #{b: 1, a: 2}; m[1]"#)?,
+        "b"
+    );
+    assert_eq!(engine.eval::<INT>(r#"let m = #{b: 1, a: 2}; m["a"]"#)?, 2);
+
+    // Values not handled by the indexer or the built-in indexing still raise an error
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let a = [1, 2, 3]; a[3]")
+            .expect_err("should error"),
+        EvalAltResult::ErrorArrayBounds(3, 3, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let a = [1, 2, 3]; a[-4]")
+            .expect_err("should error"),
+        EvalAltResult::ErrorArrayBounds(3, -4, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let a = [1, 2, 3]; a[-4] = 9; a[0]")
+            .expect_err("should error"),
+        EvalAltResult::ErrorArrayBounds(3, -4, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let a = [1, 2, 3]; let i = true; a[i]")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, INT};
+use rhai::{Engine, EvalAltResult, ParseErrorType, INT};
 
 #[test]
 fn test_if() -> Result<(), Box<EvalAltResult>> {
@@ -45,3 +45,35 @@ fn test_if_expr() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_if_ternary() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("true ? 42 : 123")?, 42);
+    assert_eq!(engine.eval::<INT>("false ? 42 : -123")?, -123);
+    assert_eq!(engine.eval::<INT>("let x = 5; x > 3 ? x * 2 : x")?, 10);
+    assert_eq!(engine.eval::<INT>("1 + (true ? 2 : 3) * 2")?, 5);
+    assert_eq!(engine.eval::<INT>("false || true ? 10 : 20")?, 10);
+
+    // Right associativity
+    assert_eq!(engine.eval::<INT>("false ? 1 : true ? 2 : 3")?, 2);
+    assert_eq!(engine.eval::<INT>("false ? 1 : false ? 2 : 3")?, 3);
+    assert_eq!(engine.eval::<INT>("true ? false ? 1 : 2 : 3")?, 2);
+    assert_eq!(engine.eval::<INT>("true ? 1 : 2 ? 3 : 4")?, 1);
+
+    // Only the taken branch is evaluated
+    assert_eq!(engine.eval::<INT>("true ? 42 : no_such_function()")?, 42);
+    assert_eq!(engine.eval::<INT>("false ? no_such_function() : 42")?, 42);
+
+    assert!(matches!(
+        *engine.eval::<INT>("42 ? 1 : 2").expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+    assert!(matches!(
+        *engine.compile("true ? 42").expect_err("should error").0,
+        ParseErrorType::MissingToken(_, _)
+    ));
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, ParseErrorType, INT};
+use rhai::{Engine, EvalAltResult, ParseErrorType, Position, INT};
 
 #[test]
 fn test_loop() -> Result<(), Box<EvalAltResult>> {
@@ -44,3 +44,149 @@ fn test_loop() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_loop_break_value() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = 0;
+
+                let y = loop {
+                    x += 1;
+                    if x > 5 { break x * 2; }
+                };
+
+                y
+            "
+        )?,
+        12
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = 0;
+
+                let y = loop {
+                    x += 1;
+                    loop { break 999; }
+                    if x >= 3 { break x; }
+                };
+
+                y
+            "
+        )?,
+        3
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = 0;
+                loop { x += 1; if x == 7 { break x; } }
+            "
+        )?,
+        7
+    );
+
+    engine.eval::<()>("let x = loop { break; }; x")?;
+    engine.eval::<()>("let x = 0; while x < 10 { x += 1; }")?;
+
+    assert_eq!(
+        engine.eval::<INT>("let x = while true { break 42; }; x")?,
+        42
+    );
+    assert_eq!(
+        engine.eval::<INT>("let x = do { break 42; } while false; x")?,
+        42
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_loop_labels() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = 0;
+
+                'outer: loop {
+                    x += 1;
+
+                    let y = 0;
+
+                    loop {
+                        y += 1;
+                        if y > 3 { continue 'outer; }
+                        if x > 5 { break 'outer; }
+                    }
+                }
+
+                x
+            "
+        )?,
+        6
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let sum = 0;
+
+                'rows: for i in range(0, 5) {
+                    'cols: while true {
+                        do {
+                            if i == 3 { continue 'rows; }
+                            if i == 4 { break 'rows; }
+                            sum += i;
+                            break 'cols;
+                        } while true;
+                    }
+                    sum += 100;
+                }
+
+                sum
+            "
+        )?,
+        303
+    );
+
+    assert_eq!(
+        engine.eval::<INT>("let x = 'a: loop { loop { break 'a 42; } }; x")?,
+        42
+    );
+
+    assert_eq!(engine.eval::<char>("'x'")?, 'x');
+
+    assert!(matches!(
+        *engine
+            .compile("'a: loop { break 'b; }")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::UndefinedLabel(ref s) if s == "b"
+    ));
+    assert_eq!(
+        engine
+            .compile("'a: loop { loop { continue 'b; } }")
+            .expect_err("should error")
+            .1,
+        Position::new(1, 28)
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    assert!(matches!(
+        *engine
+            .compile("'a: loop { let f = || { break 'a; }; }")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::LoopBreak
+    ));
+
+    Ok(())
+}
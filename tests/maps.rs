@@ -98,6 +98,64 @@ fn test_map_assign() -> Result<(), Box<EvalAltResult>> {
     assert_eq!(x["b"].clone().cast::<bool>(), true);
     assert_eq!(x["c$"].clone().cast::<String>(), "hello");
 
+    assert_eq!(engine.eval::<INT>("let x = #{a: -1}; x.a")?, -1);
+
+    Ok(())
+}
+
+#[test]
+fn test_map_shorthand_and_computed_keys() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let x = engine.eval::<Map>("let a = 1; let b = true; #{ a, b, c: 42 }")?;
+
+    assert_eq!(x["a"].clone().cast::<INT>(), 1);
+    assert_eq!(x["b"].clone().cast::<bool>(), true);
+    assert_eq!(x["c"].clone().cast::<INT>(), 42);
+
+    let x = engine.eval::<Map>(
+        r#"
+            let a = 1;
+            let name = "hello";
+            #{ a, [name + "!"]: 2, ['z']: 3, [name]: a + 3 }
+        "#,
+    )?;
+
+    assert_eq!(x.len(), 4);
+    assert_eq!(x["a"].clone().cast::<INT>(), 1);
+    assert_eq!(x["hello!"].clone().cast::<INT>(), 2);
+    assert_eq!(x["z"].clone().cast::<INT>(), 3);
+    assert_eq!(x["hello"].clone().cast::<INT>(), 4);
+
+    // Later computed keys overwrite earlier properties
+    assert_eq!(engine.eval::<INT>(r#"#{ a: 1, ["a"]: 2 }.a"#)?, 2);
+    assert_eq!(
+        engine.eval::<INT>(r#"let x = #{ ["a"]: 1, a: 2 }; x.a"#)?,
+        2
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<Map>("#{ [42]: 1 }")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(t, _, _) if t == "i64" || t == "i32"
+    ));
+    assert!(matches!(
+        *engine
+            .compile("let a = 1; #{ a, a: 2 }")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::DuplicatedProperty(p) if p == "a"
+    ));
+    assert!(matches!(
+        *engine.compile(r#"#{ "a" }"#).expect_err("should error").0,
+        ParseErrorType::MissingToken(_, _)
+    ));
+    assert!(matches!(
+        *engine.compile(r#"#{ ["a" }"#).expect_err("should error").0,
+        ParseErrorType::MissingToken(_, _)
+    ));
+
     Ok(())
 }
 
@@ -214,3 +272,228 @@ fn test_map_oop() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_map_entry() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let x = #{a: 1};
+                let y = x.get_or_insert("a", 42) + x.get_or_insert("b", 42);
+                y * 100 + x.b
+            "#
+        )?,
+        4342
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        let map = engine.eval::<Map>(
+            r#"
+                let counts = #{};
+                for word in ["a", "b", "a", "c", "a", "b"] {
+                    counts.increment(word, 1);
+                }
+                counts
+            "#,
+        )?;
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["a"].as_int().unwrap(), 3);
+        assert_eq!(map["b"].as_int().unwrap(), 2);
+        assert_eq!(map["c"].as_int().unwrap(), 1);
+    }
+
+    assert_eq!(
+        engine.eval::<INT>(r#"let x = #{a: 1}; x.increment("a", 41)"#)?,
+        42
+    );
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"let x = #{a: "hello"}; x.increment("a", 1)"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        assert_eq!(
+            engine.eval::<INT>(
+                r#"
+                    let x = #{};
+                    x.update("a", |v| if v == () { 1 } else { v + 1 });
+                    x.update("a", |v| if v == () { 1 } else { v + 1 });
+                    x.a
+                "#
+            )?,
+            2
+        );
+
+        #[cfg(not(feature = "no_closure"))]
+        assert_eq!(
+            engine.eval::<INT>(
+                r#"
+                    let step = 40;
+                    let x = #{a: 2};
+                    x.update("a", |v| v + step);
+                    x.a
+                "#
+            )?,
+            42
+        );
+
+        // A failed update leaves the map untouched
+        assert!(engine.eval::<bool>(
+            r#"
+                let x = #{a: 1};
+                try { x.update("b", |v| throw 42); } catch { }
+                !("b" in x) && x.a == 1
+            "#
+        )?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_map_null_propagation() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<()>("let x = (); x?.a")?, ());
+    assert_eq!(engine.eval::<()>("let x = #{a: 1}; x?.b")?, ());
+    assert_eq!(engine.eval::<INT>("let x = #{a: #{b: 42}}; x?.a?.b")?, 42);
+    assert_eq!(engine.eval::<()>("let x = #{a: 1}; x?.b.c")?, ());
+    assert_eq!(engine.eval::<()>("let x = #{a: #{}}; x.a?.b.c.d")?, ());
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert_eq!(engine.eval::<()>(r#"let x = #{a: 1}; x?["b"]"#)?, ());
+        assert_eq!(engine.eval::<INT>(r#"let x = #{a: 1}; x?["a"]"#)?, 1);
+    }
+
+    assert_eq!(engine.eval::<()>("let x = (); x?.len()")?, ());
+    assert_eq!(engine.eval::<INT>(r#"let x = "hello"; x?.len()"#)?, 5);
+    assert_eq!(engine.eval::<()>("let x = #{}; x.a?.len()")?, ());
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let x = #{a: 1}; x.b.c")
+            .expect_err("should error"),
+        EvalAltResult::ErrorDotExpr(_, _)
+    ));
+
+    assert!(matches!(
+        *engine
+            .compile("let x = #{}; x?.a = 1")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::AssignmentToInvalidLHS(_)
+    ));
+    assert!(matches!(
+        *engine
+            .compile("let x = #{a: #{}}; x.a?.b += 1")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::AssignmentToInvalidLHS(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_map_small() -> Result<(), Box<EvalAltResult>> {
+    let key = |i: usize| format!("k{}", i);
+
+    for size in 0..=Map::INLINE_SIZE * 3 {
+        let mut map = Map::new();
+
+        for i in 0..size {
+            assert!(map.insert(key(i).into(), (i as INT).into()).is_none());
+            assert_eq!(map.is_inline(), i < Map::INLINE_SIZE);
+        }
+
+        // Overwriting does not add properties
+        for i in 0..size {
+            let old = map.insert(key(i).into(), (i as INT * 10).into());
+            assert_eq!(old.unwrap().as_int().unwrap(), i as INT);
+        }
+        assert_eq!(map.len(), size);
+        assert_eq!(map.is_inline(), size <= Map::INLINE_SIZE);
+
+        for i in 0..size {
+            assert!(map.contains_key(key(i).as_str()));
+            assert_eq!(map[key(i).as_str()].as_int().unwrap(), i as INT * 10);
+        }
+        assert!(!map.contains_key("xyz"));
+        assert!(map.get("xyz").is_none());
+
+        for i in (0..size).step_by(2) {
+            assert!(map.remove(key(i).as_str()).is_some());
+            assert!(map.remove(key(i).as_str()).is_none());
+        }
+        assert_eq!(map.len(), size / 2);
+
+        for i in 0..size {
+            assert_eq!(map.get(key(i).as_str()).is_some(), i % 2 == 1);
+        }
+
+        map.clear();
+        assert!(map.is_empty());
+        assert!(map.is_inline());
+    }
+
+    let engine = Engine::new();
+
+    for size in 0..=Map::INLINE_SIZE * 3 {
+        let props: Vec<_> = (0..size).map(|i| format!("k{}: {}", i, i)).collect();
+        let script = format!("let x = #{{{}}}; x.k_new = 42; x", props.join(", "));
+
+        let map = engine.eval::<Map>(&script)?;
+        assert_eq!(map.len(), size + 1);
+        assert_eq!(map["k_new"].as_int().unwrap(), 42);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_map_small_iter() {
+    let key = |i: usize| format!("k{}", i);
+
+    for size in [Map::INLINE_SIZE, Map::INLINE_SIZE + 1].iter().copied() {
+        let mut map: Map = (0..size)
+            .map(|i| (key(i).into(), (i as INT).into()))
+            .collect();
+
+        assert_eq!(map.is_inline(), size <= Map::INLINE_SIZE);
+
+        map.iter_mut()
+            .for_each(|(_, v)| *v = (v.as_int().unwrap() + 1).into());
+
+        let mut keys: Vec<_> = map.keys().map(|k| k.to_string()).collect();
+        let mut values: Vec<_> = map.values().map(|v| v.as_int().unwrap()).collect();
+
+        // Inline maps keep insertion order
+        if map.is_inline() {
+            assert_eq!(keys, (0..size).map(key).collect::<Vec<_>>());
+        }
+
+        keys.sort();
+        values.sort();
+
+        let mut expected_keys: Vec<_> = (0..size).map(key).collect();
+        expected_keys.sort();
+
+        assert_eq!(keys, expected_keys);
+        assert_eq!(values, (1..=size as INT).collect::<Vec<_>>());
+
+        let mut owned: Vec<_> = map
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.as_int().unwrap()))
+            .collect();
+        owned.sort();
+        assert_eq!(owned.len(), size);
+        assert!(owned.iter().all(|(k, v)| k == &key(*v as usize - 1)));
+    }
+}
@@ -0,0 +1,82 @@
+#![cfg(not(feature = "no_object"))]
+#![cfg(not(feature = "no_index"))]
+
+use rhai::{Engine, EvalAltResult, INT};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Allocator that counts the allocations made by the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = Cell::new(0);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let start = ALLOCATIONS.with(|n| n.get());
+    let result = f();
+    (result, ALLOCATIONS.with(|n| n.get()) - start)
+}
+
+#[test]
+fn test_map_keyed_access_allocations() -> Result<(), Box<EvalAltResult>> {
+    const ITERATIONS: usize = 100_000;
+
+    let engine = Engine::new();
+
+    // Both inline and hashed maps
+    for map in &[
+        "#{a: 1, b: 2, c: 3}",
+        "#{a: 1, b: 2, c: 3, d: 4, e: 5, f: 6}",
+    ] {
+        let ast = engine.compile(&format!(
+            r#"
+                let m = {};
+                let x = 0;
+                for i in range(0, {}) {{
+                    x += m.a;
+                    x += m["b"];
+                    m.c = i;
+                    m["c"] += 1;
+                    if "a" in m {{ x += 1; }}
+                    if 'b' in m {{ x += 1; }}
+                    if m.has("c") {{ x += 1; }}
+                    m.remove("z");
+                }}
+                x
+            "#,
+            map, ITERATIONS
+        ))?;
+
+        let (result, allocations) = count_allocations(|| engine.eval_ast::<INT>(&ast));
+
+        assert_eq!(result?, 6 * ITERATIONS as INT);
+
+        // Looking up or updating a property must not allocate a key
+        assert!(
+            allocations < ITERATIONS / 10,
+            "{} allocations for {} iterations",
+            allocations,
+            ITERATIONS
+        );
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, INT};
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, INT};
 
 #[cfg(not(feature = "no_float"))]
 use rhai::FLOAT;
@@ -119,3 +119,99 @@ fn test_math_parse() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_math_safe_division() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    // (expression, x, y, default, result with INT arguments, result with FLOAT arguments)
+    // where a result of `None` means `()`
+    let table: &[(&str, INT, INT, INT, Option<INT>, Option<f64>)] = &[
+        ("div_or(x, y, d)", 7, 2, 0, Some(3), Some(3.5)),
+        ("div_or(x, y, d)", 7, 0, 42, Some(42), Some(42.0)),
+        ("div_or(x, y, d)", -7, 0, -1, Some(-1), Some(-1.0)),
+        (
+            "div_or(x, y, d)",
+            INT::MIN,
+            -1,
+            42,
+            Some(42),
+            Some(-(INT::MIN as f64)),
+        ),
+        ("mod_or(x, y, d)", 7, 3, 0, Some(1), Some(1.0)),
+        ("mod_or(x, y, d)", 7, 0, 42, Some(42), Some(42.0)),
+        ("mod_or(x, y, d)", INT::MIN, -1, 42, Some(42), Some(0.0)),
+        ("checked_div(x, y)", 7, 2, 0, Some(3), Some(3.5)),
+        ("checked_div(x, y)", 7, 0, 0, None, None),
+        (
+            "checked_div(x, y)",
+            INT::MIN,
+            -1,
+            0,
+            None,
+            Some(-(INT::MIN as f64)),
+        ),
+        ("checked_mod(x, y)", -7, 3, 0, Some(-1), Some(-1.0)),
+        ("checked_mod(x, y)", 7, 0, 0, None, None),
+        ("checked_mod(x, y)", INT::MIN, -1, 0, None, Some(0.0)),
+    ];
+
+    for &(expr, x, y, d, int_result, _float_result) in table {
+        let mut scope = Scope::new();
+        scope.push("x", x).push("y", y).push("d", d);
+
+        let result = engine.eval_expression_with_scope::<Dynamic>(&mut scope, expr)?;
+
+        match int_result {
+            Some(r) => assert_eq!(result.as_int(), Ok(r), "{} with {}, {}", expr, x, y),
+            None => assert!(result.is::<()>(), "{} with {}, {}", expr, x, y),
+        }
+
+        #[cfg(not(feature = "no_float"))]
+        {
+            let mut scope = Scope::new();
+            scope
+                .push("x", x as FLOAT)
+                .push("y", y as FLOAT)
+                .push("d", d as FLOAT);
+
+            let result = engine.eval_expression_with_scope::<Dynamic>(&mut scope, expr)?;
+
+            match _float_result {
+                Some(r) => assert_eq!(
+                    result.as_float(),
+                    Ok(r as FLOAT),
+                    "{} with {}, {}",
+                    expr,
+                    x,
+                    y
+                ),
+                None => assert!(result.is::<()>(), "{} with {}, {}", expr, x, y),
+            }
+        }
+    }
+
+    // NaN arguments propagate, only a zero divisor returns the default
+    #[cfg(not(feature = "no_float"))]
+    {
+        assert!(engine
+            .eval::<FLOAT>("div_or(0.0 / 0.0, 2.0, 1.0)")?
+            .is_nan());
+        assert!(engine
+            .eval::<FLOAT>("div_or(1.0, 0.0 / 0.0, 1.0)")?
+            .is_nan());
+        assert!(engine
+            .eval::<FLOAT>("mod_or(1.0, 0.0 / 0.0, 1.0)")?
+            .is_nan());
+        assert!(engine
+            .eval::<FLOAT>("div_or(1.0, 0.0, 0.0 / 0.0)")?
+            .is_nan());
+        assert!(engine
+            .eval::<FLOAT>("checked_div(0.0 / 0.0, 1.0)")?
+            .is_nan());
+        assert_eq!(engine.eval::<FLOAT>("div_or(1.0, -0.0, 42.0)")?, 42.0);
+        engine.eval::<()>("checked_div(0.0 / 0.0, 0.0)")?;
+    }
+
+    Ok(())
+}
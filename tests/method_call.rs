@@ -1,6 +1,6 @@
 #![cfg(not(feature = "no_object"))]
 
-use rhai::{Engine, EvalAltResult, RegisterFn, INT};
+use rhai::{Engine, EvalAltResult, ImmutableString, RegisterFn, INT};
 
 #[test]
 fn test_method_call() -> Result<(), Box<EvalAltResult>> {
@@ -47,3 +47,135 @@ fn test_method_call_style() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_method_call_primitives() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine
+        .register_fn("double", |x: INT| x * 2)
+        .register_fn("incr", |x: &mut INT| *x + 1)
+        .register_fn("shout", |s: ImmutableString| s.to_uppercase())
+        .register_fn("whisper", |s: &mut ImmutableString| s.to_lowercase())
+        .register_fn("flip", |b: bool| !b)
+        .register_fn("upper", |c: char| c.to_ascii_uppercase())
+        .register_fn("nothing", |_: ()| 42 as INT);
+
+    assert_eq!(engine.eval::<INT>("42.double()")?, 84);
+    assert_eq!(engine.eval::<INT>("41.incr()")?, 42);
+    assert_eq!(engine.eval::<String>(r#""hi".shout()"#)?, "HI");
+    assert_eq!(engine.eval::<String>(r#""HI".whisper()"#)?, "hi");
+    assert_eq!(engine.eval::<bool>("true.flip()")?, false);
+    assert_eq!(engine.eval::<char>("'a'.upper()")?, 'A');
+    assert_eq!(engine.eval::<INT>("().nothing()")?, 42);
+
+    assert_eq!(
+        engine.eval::<INT>("let x = 1; x.double().incr().double()")?,
+        6
+    );
+    assert_eq!(engine.eval::<INT>("const x = 20; x.incr().double()")?, 42);
+    assert_eq!(
+        engine.eval::<String>(r#"let s = "Hi"; s.shout().whisper()"#)?,
+        "hi"
+    );
+
+    #[cfg(not(feature = "no_float"))]
+    {
+        use rhai::FLOAT;
+
+        engine.register_fn("half", |x: FLOAT| x / 2.0);
+        assert_eq!(engine.eval::<FLOAT>("5.0.half()")?, 2.5);
+    }
+
+    assert!(matches!(
+        *engine.eval::<INT>("42.foo()").expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(sig, _) if sig == format!("foo ({})", std::any::type_name::<INT>())
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_method_call_fluent() -> Result<(), Box<EvalAltResult>> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, Default, Eq, PartialEq)]
+    struct Config {
+        x: INT,
+        y: INT,
+    }
+
+    impl Clone for Config {
+        fn clone(&self) -> Self {
+            CLONES.fetch_add(1, Ordering::SeqCst);
+            Self {
+                x: self.x,
+                y: self.y,
+            }
+        }
+    }
+
+    let mut engine = Engine::new();
+
+    engine
+        .register_type::<Config>()
+        .register_fn("make_config", Config::default)
+        .register_fn("set_x", |c: &mut Config, x: INT| c.x = x)
+        .register_fn("set_y", |c: &mut Config, y: INT| c.y = y)
+        .register_fn("with_x", |c: &mut Config, x: INT| {
+            c.x = x;
+            c.clone()
+        })
+        .register_get("x", |c: &mut Config| c.x)
+        .register_get("y", |c: &mut Config| c.y);
+
+    let clones = || CLONES.load(Ordering::SeqCst);
+
+    // Methods returning () modify the temporary in place, and the chain evaluates to it
+    let before = clones();
+    assert_eq!(
+        engine.eval::<Config>("make_config().set_x(1).set_y(2)")?,
+        Config { x: 1, y: 2 }
+    );
+    assert_eq!(clones(), before);
+
+    let before = clones();
+    assert_eq!(
+        engine.eval::<INT>("make_config().set_x(40).set_y(2).x")?,
+        40
+    );
+    assert_eq!(clones(), before);
+
+    // Methods returning () on a variable modify it in place
+    let before = clones();
+    assert_eq!(
+        engine.eval::<INT>("let c = make_config(); c.set_x(40).set_y(2); c.x + c.y")?,
+        42
+    );
+    assert_eq!(clones(), before);
+    engine.eval::<()>("let c = make_config(); c.set_x(1)")?;
+
+    // Methods returning a copy of the receiver continue the chain on the copy
+    let before = clones();
+    assert_eq!(
+        engine.eval::<Config>("make_config().with_x(1).with_x(2)")?,
+        Config { x: 2, y: 0 }
+    );
+    assert_eq!(clones(), before + 2);
+
+    assert_eq!(
+        engine.eval::<INT>("let c = make_config(); c.with_x(1).set_y(2); c.x * 10 + c.y")?,
+        10
+    );
+
+    // Built-in types keep their semantics
+    #[cfg(not(feature = "no_index"))]
+    {
+        engine.eval::<()>("[1, 2].push(3)")?;
+        engine.eval::<()>("[].pop()")?;
+    }
+
+    Ok(())
+}
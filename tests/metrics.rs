@@ -0,0 +1,104 @@
+use rhai::{Engine, EvalAltResult, EvalMetrics, Scope, INT};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn test_metrics() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let last_count = Arc::new(AtomicU64::new(0));
+    let counter = last_count.clone();
+
+    engine.on_progress(move |count| {
+        counter.store(count, Ordering::SeqCst);
+        None
+    });
+
+    let mut metrics = EvalMetrics::new();
+
+    assert_eq!(
+        engine.eval_with_metrics::<INT>(
+            &mut Scope::new(),
+            "let x = 0; while x < 20 { x += 1; } x",
+            &mut metrics,
+        )?,
+        20
+    );
+
+    // The same operations count as reported to the progress callback
+    assert!(metrics.operations > 20);
+    assert_eq!(metrics.operations, last_count.load(Ordering::SeqCst));
+    assert_eq!(metrics.max_call_depth, 0);
+    assert_eq!(metrics.modules, 0);
+    assert!(metrics.parse_time.is_some());
+    assert!(metrics.eval_time.is_some());
+
+    let ast = engine.compile("let x = 40; x + 2")?;
+    engine.eval_ast_with_metrics::<INT>(&mut Scope::new(), &ast, &mut metrics)?;
+    assert!(metrics.operations > 0);
+    assert!(metrics.parse_time.is_none());
+
+    // Metrics are recorded even when the evaluation fails
+    let mut metrics = EvalMetrics::new();
+    assert!(engine
+        .eval_with_metrics::<INT>(&mut Scope::new(), "let x = 1; throw x;", &mut metrics)
+        .is_err());
+    assert!(metrics.operations > 0);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_metrics_call_depth() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let mut metrics = EvalMetrics::new();
+
+    assert_eq!(
+        engine.eval_with_metrics::<INT>(
+            &mut Scope::new(),
+            r"
+                fn sum(n) { if n == 0 { 0 } else { n + sum(n - 1) } }
+                sum(5) + sum(2)
+            ",
+            &mut metrics,
+        )?,
+        18
+    );
+    assert_eq!(metrics.max_call_depth, 6);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_module"))]
+#[test]
+fn test_metrics_modules() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{module_resolvers::StaticModuleResolver, Module};
+
+    let mut engine = Engine::new();
+
+    let mut module = Module::new();
+    module.set_var("answer", 42 as INT);
+
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("hello", module);
+    engine.set_module_resolver(resolver);
+
+    let mut metrics = EvalMetrics::new();
+
+    assert_eq!(
+        engine.eval_with_metrics::<INT>(
+            &mut Scope::new(),
+            r#"
+                import "hello" as h;
+                import "hello" as h2;
+                h::answer + h2::answer
+            "#,
+            &mut metrics,
+        )?,
+        84
+    );
+    assert_eq!(metrics.modules, 2);
+
+    Ok(())
+}
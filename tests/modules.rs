@@ -1,9 +1,39 @@
 #![cfg(not(feature = "no_module"))]
 use rhai::{
-    module_resolvers::StaticModuleResolver, Dynamic, Engine, EvalAltResult, FnNamespace,
-    ImmutableString, Module, ParseError, ParseErrorType, Scope, INT,
+    module_resolvers::StaticModuleResolver, Dynamic, Engine, EvalAltResult, EvalOptions,
+    FnNamespace, ImmutableString, Module, ParseError, ParseErrorType, Position, Scope, INT,
 };
 
+/// Temporary directory for module script files, removed when dropped.
+#[cfg(not(feature = "no_std"))]
+struct TempDir(std::path::PathBuf);
+
+#[cfg(not(feature = "no_std"))]
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("rhai-test-module-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+    fn path(&self) -> &std::path::Path {
+        &self.0
+    }
+    /// Write a file, creating its parent directories if needed.
+    fn write(&self, file: &str, contents: &str) {
+        let path = self.0.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
 #[test]
 fn test_module() {
     let mut module = Module::new();
@@ -227,7 +257,7 @@ fn test_module_resolver() -> Result<(), Box<EvalAltResult>> {
                     "#
                 )
                 .expect_err("should error"),
-            EvalAltResult::ErrorInFunctionCall(fn_name, _, _) if fn_name == "foo"
+            EvalAltResult::ErrorInFunctionCall(fn_name, _, _, _) if fn_name == "foo"
         ));
 
         engine.set_max_modules(1000);
@@ -346,6 +376,70 @@ fn test_module_from_ast() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_module_export_fn() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let ast = engine.compile(
+        r#"
+            fn helper(x) { x * 2 }
+            export fn double(x) { helper(x) }
+            fn triple(x) { x * 3 }
+            fn hidden() { 0 }
+            let answer = 42;
+
+            export triple, answer;
+        "#,
+    )?;
+
+    let public_module = Module::eval_ast_as_new(Scope::new(), &ast, &engine)?;
+
+    assert!(!engine.module_fn_private_by_default());
+    engine.set_module_fn_private_by_default(true);
+
+    let module = Module::eval_ast_as_new(Scope::new(), &ast, &engine)?;
+
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("public", public_module);
+    resolver.insert("testing", module);
+    engine.set_module_resolver(resolver);
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "testing" as m; m::double(m::answer) + m::triple(1)"#)?,
+        87
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"import "public" as m; m::helper(1) + m::hidden()"#)?,
+        2
+    );
+
+    for (call, name) in &[("helper(1)", "m::helper (i"), ("hidden()", "m::hidden ()")] {
+        assert!(matches!(
+            *engine
+                .consume(&format!(r#"import "testing" as m; m::{};"#, call))
+                .expect_err("should error"),
+            EvalAltResult::ErrorFunctionNotFound(fn_name, _) if fn_name.starts_with(name)
+        ));
+    }
+
+    assert!(matches!(
+        *engine
+            .consume("fn foo() {} export foo as bar;")
+            .expect_err("should error"),
+        EvalAltResult::ErrorVariableNotFound(name, _) if name == "foo"
+    ));
+    assert_eq!(
+        *engine
+            .compile("/// doc\nexport let x = 1;")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::WrongDocComment
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_module_export() -> Result<(), Box<EvalAltResult>> {
     let engine = Engine::new();
@@ -408,6 +502,7 @@ fn test_module_ast_namespace() -> Result<(), Box<EvalAltResult>> {
     let script = r#"
         fn foo(x) { x + 1 }
         fn bar(x) { foo(x) }
+        fn baz(x, y = 1) { x + y }
     "#;
 
     let mut engine = Engine::new();
@@ -436,6 +531,10 @@ fn test_module_ast_namespace() -> Result<(), Box<EvalAltResult>> {
         engine.eval::<INT>(r#"fn foo(x) { x - 1 } import "testing" as t; t::bar(41)"#)?,
         42
     );
+    assert_eq!(
+        engine.eval::<INT>(r#"import "testing" as t; t::baz(41) + t::baz(40, 2)"#)?,
+        84
+    );
 
     Ok(())
 }
@@ -485,3 +584,438 @@ fn test_module_file() -> Result<(), Box<EvalAltResult>> {
     Module::eval_ast_as_new(Default::default(), &ast, &engine)?;
     Ok(())
 }
+
+#[test]
+fn test_module_alias_shadowing() -> Result<(), Box<EvalAltResult>> {
+    let mut module = Module::new();
+    module.set_fn_0("helper", || Ok(42 as INT));
+
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("util", module);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(resolver);
+
+    // Variables and module aliases live in separate namespaces
+    assert_eq!(
+        engine.eval::<INT>(r#"import "util" as util; let util = 5; util::helper()"#)?,
+        42
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"let util = 5; import "util" as util; util::helper() + util"#)?,
+        47
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"let util = 5; util::helper()"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorModuleShadowed(name, var_pos, _)
+            if name == "util" && var_pos == Position::new(1, 5)
+    ));
+
+    engine.set_strict_module_aliases(true);
+
+    assert_eq!(
+        *engine
+            .compile(r#"import "util" as util; let util = 5;"#)
+            .expect_err("should error")
+            .0,
+        ParseErrorType::ModuleAliasConflict("util".to_string())
+    );
+    assert_eq!(
+        *engine
+            .compile(r#"let util = 5; import "util" as util;"#)
+            .expect_err("should error")
+            .0,
+        ParseErrorType::ModuleAliasConflict("util".to_string())
+    );
+    assert_eq!(
+        *engine
+            .compile(r#"import "util" as util; for util in range(0, 3) {}"#)
+            .expect_err("should error")
+            .0,
+        ParseErrorType::ModuleAliasConflict("util".to_string())
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(r#"let x = 5; import "util" as util; util::helper() + x"#)?,
+        47
+    );
+
+    // Strict module aliases can be turned off for a single evaluation
+    let mut options = EvalOptions::new();
+    options.set_strict_module_aliases(false);
+
+    assert_eq!(
+        engine.eval_with_options::<INT>(
+            &mut Scope::new(),
+            r#"import "util" as util; let util = 5; util::helper() + util"#,
+            &options
+        )?,
+        47
+    );
+    assert!(engine.strict_module_aliases());
+
+    Ok(())
+}
+
+#[test]
+fn test_module_filter() -> Result<(), Box<EvalAltResult>> {
+    let mut module = Module::new();
+    module.set_var("answer", 42 as INT);
+
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("lib/answer", module.clone());
+    resolver.insert("../secrets", module);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(resolver);
+    engine.set_module_filter(|path| !path.contains(".."));
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "lib/answer" as a; a::answer"#)?,
+        42
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"let x = 1;    import "../secrets" as s; s::answer"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorModuleDenied(path, pos)
+            if path == "../secrets" && pos == Position::new(1, 15)
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_module_file_escape() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::FileModuleResolver;
+
+    let root = TempDir::new("escape");
+    root.write("secrets.rhai", "export const answer = 0;");
+    root.write("base/allowed.rhai", "export const answer = 42;");
+    let base = root.path().join("base");
+
+    let absolute = root
+        .path()
+        .join("secrets")
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    let resolver = FileModuleResolver::new_with_path(&base);
+    assert!(resolver.confine_to_base_path());
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(resolver);
+
+    let result = engine.eval::<INT>(r#"import "allowed" as a; a::answer"#);
+    let nested = engine.eval::<INT>(r#"import "../base/allowed" as a; a::answer"#);
+    let denied = engine.eval::<INT>(r#"import "../secrets" as s; s::answer"#);
+    let missing = engine.eval::<INT>(r#"import "../missing" as s; s::answer"#);
+    let missing_dir = engine.eval::<INT>(r#"import "missing/missing" as s; s::answer"#);
+    let denied_absolute = engine.eval::<INT>(&format!(r#"import "{}" as s; s::answer"#, absolute));
+
+    let mut resolver = FileModuleResolver::new_with_path(&base);
+    resolver.set_confine_to_base_path(false);
+    engine.set_module_resolver(resolver);
+
+    let allowed = engine.eval::<INT>(r#"import "../secrets" as s; s::answer"#);
+    let allowed_absolute = engine.eval::<INT>(&format!(r#"import "{}" as s; s::answer"#, absolute));
+
+    assert_eq!(result?, 42);
+    assert_eq!(nested?, 42);
+
+    for (err, path) in vec![
+        (denied, "../secrets"),
+        (missing, "../missing"),
+        (denied_absolute, absolute.as_str()),
+    ] {
+        match *err.expect_err("should error") {
+            EvalAltResult::ErrorInModule(p, err, _) if p == path => {
+                assert!(matches!(*err, EvalAltResult::ErrorSystem(_, _)));
+                assert!(err.to_string().contains("escapes module root"));
+            }
+            err => panic!("wrong error: {}", err),
+        }
+    }
+
+    assert!(matches!(
+        *missing_dir.expect_err("should error"),
+        EvalAltResult::ErrorModuleNotFound(path, _) if path == "missing/missing"
+    ));
+
+    assert_eq!(allowed?, 0);
+    assert_eq!(allowed_absolute?, 0);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(unix)]
+#[test]
+fn test_module_file_symlinks() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::FileModuleResolver;
+    use std::os::unix::fs::symlink;
+
+    let root = TempDir::new("symlinks");
+    root.write("base/lib/real.rhai", "export const answer = 42;");
+    root.write("outside/real.rhai", "export const answer = 0;");
+    let base = root.path().join("base");
+    symlink(base.join("lib/real.rhai"), base.join("link.rhai")).unwrap();
+    symlink(base.join("lib"), base.join("link_dir")).unwrap();
+    symlink(root.path().join("outside"), base.join("outside_dir")).unwrap();
+
+    let mut resolver = FileModuleResolver::new_with_path(&base);
+    assert!(resolver.follow_symlinks());
+    resolver.set_follow_symlinks(false);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(resolver);
+
+    let real = engine.eval::<INT>(r#"import "lib/real" as m; m::answer"#);
+    let link = engine.eval::<INT>(r#"import "link" as m; m::answer"#);
+    let link_dir = engine.eval::<INT>(r#"import "link_dir/real" as m; m::answer"#);
+
+    engine.set_module_resolver(FileModuleResolver::new_with_path(&base));
+
+    let link_followed = engine.eval::<INT>(r#"import "link" as m; m::answer"#);
+    let link_dir_followed = engine.eval::<INT>(r#"import "link_dir/real" as m; m::answer"#);
+    let outside_dir = engine.eval::<INT>(r#"import "outside_dir/real" as m; m::answer"#);
+
+    assert_eq!(real?, 42);
+
+    for (err, path, component) in vec![
+        (link, "link", "'link.rhai'"),
+        (link_dir, "link_dir/real", "'link_dir'"),
+    ] {
+        match *err.expect_err("should error") {
+            EvalAltResult::ErrorInModule(p, err, _) if p == path => {
+                assert!(matches!(*err, EvalAltResult::ErrorSystem(_, _)));
+                assert!(err.to_string().contains(component));
+            }
+            err => panic!("wrong error: {}", err),
+        }
+    }
+
+    assert_eq!(link_followed?, 42);
+    assert_eq!(link_dir_followed?, 42);
+
+    match *outside_dir.expect_err("should error") {
+        EvalAltResult::ErrorInModule(p, err, _) if p == "outside_dir/real" => {
+            assert!(err.to_string().contains("escapes module root"));
+        }
+        err => panic!("wrong error: {}", err),
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_module_error_chain() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::FileModuleResolver;
+
+    let root = TempDir::new("errors");
+    root.write("bad.rhai", "let x = 1;\nthrow \"bad module\";");
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(FileModuleResolver::new_with_path(root.path()));
+
+    let result = engine.consume(
+        r#"
+            fn load() {
+                import "bad" as m;
+                m::x
+            }
+            load();
+        "#,
+    );
+
+    let err = result.expect_err("should error");
+
+    let mut chain = Vec::new();
+    err.walk(|err| chain.push((err.kind_name(), err.position())));
+
+    assert_eq!(
+        chain,
+        [
+            ("ErrorInFunctionCall", Position::new(6, 13)),
+            ("ErrorInModule", Position::new(3, 24)),
+            ("ErrorRuntime", Position::new(2, 1)),
+        ]
+    );
+
+    let inner = err.source_error().unwrap();
+    assert!(matches!(inner, EvalAltResult::ErrorInModule(path, _, _) if path == "bad"));
+    assert!(inner.source_error().unwrap().source_error().is_none());
+
+    let mut err = err;
+    err.set_position(Position::new(42, 1));
+    assert_eq!(err.position(), Position::new(42, 1));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_module_error_excerpt() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::FileModuleResolver;
+
+    let root = TempDir::new("excerpt");
+    root.write(
+        "bad.rhai",
+        "let x = 1;\r\nlet y = 2;\r\n\tlet s = \"héllo\"; let z = foo_bar(x);\r\n",
+    );
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(FileModuleResolver::new_with_path(root.path()));
+
+    let ast = engine.compile(r#"import "bad" as m;"#)?;
+    assert!(ast.source_text().is_none());
+
+    let err = engine.consume_ast(&ast).expect_err("should error");
+    assert_eq!(err.excerpt(&engine, &ast), None);
+
+    engine.set_retain_source(true);
+
+    let ast = engine.compile("let a = 1;\n  import \"bad\" as m;")?;
+    assert_eq!(
+        ast.source_text(),
+        Some("let a = 1;\n  import \"bad\" as m;")
+    );
+
+    let err = engine.consume_ast(&ast).expect_err("should error");
+
+    assert_eq!(
+        err.excerpt(&engine, &ast).unwrap(),
+        "\tlet s = \"héllo\"; let z = foo_bar(x);\n\t                         ^^^^^^^^^^"
+    );
+
+    let ast = engine.compile("let a = 1;\nlet b = a + c;")?;
+    let err = engine.consume_ast(&ast).expect_err("should error");
+
+    assert_eq!(
+        err.excerpt(&engine, &ast).unwrap(),
+        "let b = a + c;\n            ^"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_module_import_once() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{ModuleResolver, Shared};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    struct CountingResolver(Arc<AtomicUsize>);
+
+    impl ModuleResolver for CountingResolver {
+        fn resolve(
+            &self,
+            _: &Engine,
+            path: &str,
+            pos: Position,
+        ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+            if path != "counter" {
+                return EvalAltResult::ErrorModuleNotFound(path.into(), pos).into();
+            }
+            let mut module = Module::new();
+            module.set_var("loads", self.0.fetch_add(1, Ordering::SeqCst) as INT + 1);
+            Ok(module.into())
+        }
+    }
+
+    let loads = Arc::new(AtomicUsize::new(0));
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(CountingResolver(loads.clone()));
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                import "counter" as a;
+                let x = { import "counter" as b; b::loads };
+                import "counter" as c;
+                a::loads + x + c::loads
+            "#
+        )?,
+        3
+    );
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+    // Each evaluation resolves the module again
+    assert_eq!(engine.eval::<INT>(r#"import "counter" as m; m::loads"#)?, 2);
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+    assert_eq!(
+        *engine
+            .compile(r#"let m = import "counter" as m;"#)
+            .expect_err("should error")
+            .0,
+        ParseErrorType::WrongImport
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_module_file_limits() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::FileModuleResolver;
+
+    let root = TempDir::new("limits");
+    root.write("ping.rhai", "import \"pong\" as pong;");
+    root.write("pong.rhai", "import \"ping\" as ping;");
+    root.write("busy.rhai", "let x = 0; while x < 50 { x += 1; } export x;");
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(FileModuleResolver::new_with_path(root.path()));
+    engine.set_max_operations(500);
+
+    // Operations within modules count towards the limit of the evaluation
+    // (the module is cached once it loads successfully)
+    let too_busy =
+        engine.eval::<INT>(r#"let y = 0; while y < 50 { y += 1; } import "busy" as b; b::x"#);
+    let busy = engine.eval::<INT>(r#"import "busy" as b; b::x"#);
+
+    #[cfg(not(feature = "no_function"))]
+    let recursive = {
+        engine.set_max_call_levels(10);
+        engine.eval::<()>(r#"import "ping" as ping;"#)
+    };
+
+    assert_eq!(busy?, 50);
+
+    assert!(matches!(
+        *too_busy.expect_err("should error"),
+        EvalAltResult::ErrorInModule(path, err, _)
+            if path == "busy" && matches!(*err, EvalAltResult::ErrorTooManyOperations(_))
+    ));
+
+    // Modules importing each other overflow the stack, with the import chain in the error
+    #[cfg(not(feature = "no_function"))]
+    {
+        let err = recursive.expect_err("should error");
+        assert!(!err.is_catchable());
+
+        let mut chain = Vec::new();
+        err.walk(|err| chain.push(err.kind_name()));
+
+        assert_eq!(chain.len(), 11);
+        assert!(chain[..10].iter().all(|&kind| kind == "ErrorInModule"));
+        assert_eq!(chain[10], "ErrorStackOverflow");
+    }
+
+    Ok(())
+}
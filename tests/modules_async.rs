@@ -0,0 +1,146 @@
+#![cfg(not(feature = "no_module"))]
+#![cfg(feature = "async")]
+use rhai::{
+    module_resolvers::ModuleFuture, Engine, EvalAltResult, Module, ModuleResolver, Position,
+    Shared, INT,
+};
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+/// A future that stays pending for a number of polls.
+struct Sleep(usize);
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 == 0 {
+            Poll::Ready(())
+        } else {
+            self.0 -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// A module resolver that sleeps before resolving any module.
+struct SleepyResolver(usize);
+
+impl ModuleResolver for SleepyResolver {
+    fn resolve(
+        &self,
+        _: &Engine,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        if path == "hello" {
+            let mut module = Module::new();
+            module.set_var("answer", 42 as INT);
+            Ok(module.into())
+        } else {
+            EvalAltResult::ErrorModuleNotFound(path.into(), pos).into()
+        }
+    }
+
+    fn resolve_async<'a>(
+        &'a self,
+        engine: &'a Engine,
+        path: &'a str,
+        pos: Position,
+    ) -> ModuleFuture<'a> {
+        Box::pin(async move {
+            Sleep(self.0).await;
+            self.resolve(engine, path, pos)
+        })
+    }
+}
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Poll a future to completion, running a ticking task in between each poll.
+fn run_with_ticker<T>(future: impl Future<Output = T>, ticks: &Rc<Cell<usize>>) -> T {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let cx = &mut Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+
+    loop {
+        if let Poll::Ready(value) = future.as_mut().poll(cx) {
+            return value;
+        }
+        ticks.set(ticks.get() + 1);
+    }
+}
+
+#[test]
+fn test_module_resolve_async() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(SleepyResolver(5));
+
+    let ticks = Rc::new(Cell::new(0));
+
+    let result = run_with_ticker(
+        engine.eval_async::<INT>(
+            r#"
+                import "hello" as h;
+                h::answer
+            "#,
+        ),
+        &ticks,
+    )?;
+
+    assert_eq!(result, 42);
+    assert_eq!(ticks.get(), 5);
+
+    ticks.set(0);
+
+    assert!(matches!(
+        *run_with_ticker(engine.eval_async::<INT>(r#"import "world" as w; 0"#), &ticks)
+            .expect_err("should error"),
+        EvalAltResult::ErrorModuleNotFound(p, _) if p == "world"
+    ));
+    assert_eq!(ticks.get(), 5);
+
+    // The blocking API is not affected
+    assert_eq!(engine.eval::<INT>(r#"import "hello" as h; h::answer"#)?, 42);
+
+    Ok(())
+}
+
+#[test]
+fn test_module_compile_file_async() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.on_print(|_| {});
+
+    let ticks = Rc::new(Cell::new(0));
+
+    let ast = run_with_ticker(
+        engine.compile_file_async("scripts/module.rhai".into()),
+        &ticks,
+    )?;
+    assert_eq!(ticks.get(), 0);
+
+    run_with_ticker(
+        engine.eval_ast_with_scope_async::<()>(&mut Default::default(), &ast),
+        &ticks,
+    )?;
+
+    assert!(matches!(
+        *run_with_ticker(
+            engine.compile_file_async("scripts/none.rhai".into()),
+            &ticks
+        )
+        .expect_err("should error"),
+        EvalAltResult::ErrorSystem(_, _)
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use rhai::{Engine, EvalAltResult, NumericOps, RegisterFn, INT};
+use std::cmp::Ordering;
+
+/// Checks shared by all numeric types, built via the script function `n` converting an `INT`.
+mod conformance {
+    use rhai::{Engine, EvalAltResult};
+
+    const CHECKS: &[&str] = &[
+        "n(2) + n(3) == n(5)",
+        "n(2) - n(3) == n(-1)",
+        "n(2) * n(3) == n(6)",
+        "n(6) / n(2) == n(3)",
+        "n(7) % n(4) == n(3)",
+        "-n(4) == n(-4)",
+        "+n(4) == n(4)",
+        "n(2) + 3 == n(5)",
+        "3 - n(2) == n(1)",
+        "let x = n(2); x += n(3); x == n(5)",
+        "let x = n(2); x *= 3; x == n(6)",
+        "n(1) == n(1)",
+        "n(1) != n(2)",
+        "n(1) < n(2)",
+        "n(2) <= n(2)",
+        "n(3) > n(2)",
+        "n(2) >= n(2)",
+        "!(n(2) < n(2))",
+        "n(2) < 3",
+        "3 >= n(3)",
+        "abs(n(-5)) == n(5)",
+        "abs(n(5)) == n(5)",
+        "sign(n(-5)) == -1 && sign(n(0)) == 0 && sign(n(5)) == 1",
+        "min(n(1), n(2)) == n(1)",
+        "max(n(1), n(2)) == n(2)",
+        "clamp(n(5), n(1), n(3)) == n(3)",
+        "clamp(n(-5), n(1), n(3)) == n(1)",
+        "clamp(n(2), n(1), n(3)) == n(2)",
+        r#""x" + n(5) == "x" + to_string(n(5))"#,
+        r#"n(5) + "x" == to_string(n(5)) + "x""#,
+    ];
+
+    #[cfg(not(feature = "no_index"))]
+    const ARRAY_CHECKS: &[&str] = &[
+        "let x = [n(3), n(1), n(2)]; sort(x); x == [n(1), n(2), n(3)]",
+        "min([n(3), n(1), n(2)]) == n(1)",
+        "max([n(3), n(1), n(2)]) == n(3)",
+        "type_of(min([])) == \"()\"",
+    ];
+
+    pub fn check(engine: &Engine) -> Result<(), Box<EvalAltResult>> {
+        for script in CHECKS {
+            assert!(engine.eval::<bool>(script)?, "{}", script);
+        }
+
+        #[cfg(not(feature = "no_index"))]
+        for script in ARRAY_CHECKS {
+            assert!(engine.eval::<bool>(script)?, "{}", script);
+        }
+
+        #[cfg(not(feature = "unchecked"))]
+        assert!(matches!(
+            *engine
+                .eval::<bool>("n(1) / n(0)")
+                .expect_err("should error"),
+            EvalAltResult::ErrorArithmetic(_, _)
+        ));
+        assert!(matches!(
+            *engine
+                .eval::<bool>("clamp(n(3), n(2), n(1))")
+                .expect_err("should error"),
+            EvalAltResult::ErrorArithmetic(_, _)
+        ));
+
+        Ok(())
+    }
+}
+
+/// Fixed-point number with three decimal places.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+struct Fixed(i64);
+
+impl Fixed {
+    const SCALE: i64 = 1000;
+}
+
+fn fixed_ops() -> NumericOps<Fixed> {
+    NumericOps::new()
+        .with_add(|x: Fixed, y: Fixed| x.0.checked_add(y.0).map(Fixed))
+        .with_sub(|x: Fixed, y: Fixed| x.0.checked_sub(y.0).map(Fixed))
+        .with_mul(|x: Fixed, y: Fixed| x.0.checked_mul(y.0).map(|v| Fixed(v / Fixed::SCALE)))
+        .with_div(|x: Fixed, y: Fixed| {
+            x.0.checked_mul(Fixed::SCALE)
+                .and_then(|v| v.checked_div(y.0))
+                .map(Fixed)
+        })
+        .with_rem(|x: Fixed, y: Fixed| x.0.checked_rem(y.0).map(Fixed))
+        .with_neg(|x: Fixed| x.0.checked_neg().map(Fixed))
+        .with_cmp(Fixed::cmp)
+        .with_to_string(|x: &Fixed| {
+            let sign = if x.0 < 0 { "-" } else { "" };
+            let v = x.0.abs();
+            format!("{}{}.{:03}", sign, v / Fixed::SCALE, v % Fixed::SCALE)
+        })
+        .with_from_int(|x| Fixed(x as i64 * Fixed::SCALE))
+}
+
+#[test]
+fn test_numeric_int() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_fn("n", |x: INT| x);
+
+    conformance::check(&engine)
+}
+
+#[test]
+fn test_numeric_fixed() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    #[cfg(not(feature = "no_object"))]
+    engine.register_type_with_name::<Fixed>("Fixed");
+
+    engine
+        .register_fn("n", |x: INT| Fixed(x as i64 * Fixed::SCALE))
+        .register_numeric_type(fixed_ops());
+
+    conformance::check(&engine)?;
+
+    assert_eq!(engine.eval::<Fixed>("n(7) / n(2)")?, Fixed(3500));
+    assert_eq!(engine.eval::<String>("to_string(n(-7) / 4)")?, "-1.750");
+    assert_eq!(
+        engine.eval::<String>(r#""total: " + n(3)"#)?,
+        "total: 3.000"
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    assert!(matches!(
+        *engine
+            .eval::<()>("let x = [n(1), 2]; sort(x)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorArrayElementType(_, _, 1, _)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_numeric_partial() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    #[cfg(not(feature = "no_object"))]
+    engine.register_type_with_name::<Fixed>("Fixed");
+
+    engine
+        .register_fn("n", |x: INT| Fixed(x as i64 * Fixed::SCALE))
+        .register_numeric_type(NumericOps::new().with_cmp(|x: &Fixed, y: &Fixed| x.0.cmp(&y.0)));
+
+    assert!(engine.eval::<bool>("n(1) < n(2)")?);
+    assert_eq!(engine.eval::<Fixed>("max(n(1), n(2))")?, Fixed(2000));
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(engine.eval::<Fixed>("min([n(2), n(1)])")?, Fixed(1000));
+
+    // No arithmetic
+    assert!(matches!(
+        *engine
+            .eval::<Fixed>("n(1) + n(2)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(_, _)
+    ));
+    // No comparison with INT, so the default for mismatched types applies
+    assert!(!engine.eval::<bool>("n(1) < 2")?);
+    // 'abs' needs negation and zero
+    assert!(matches!(
+        *engine.eval::<Fixed>("abs(n(1))").expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(_, _)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_numeric_unordered() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("n", |x: INT| Fixed(x as i64 * Fixed::SCALE));
+
+    #[cfg(not(feature = "no_index"))]
+    assert!(matches!(
+        *engine
+            .eval::<()>("let x = [n(2), n(1)]; sort(x)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+
+    engine.register_numeric_type(NumericOps::new().with_cmp(|x: &Fixed, y: &Fixed| {
+        match x.0.cmp(&y.0) {
+            Ordering::Equal => Ordering::Equal,
+            o => o.reverse(),
+        }
+    }));
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(
+        engine
+            .eval::<rhai::Array>("let x = [n(1), n(3), n(2)]; sort(x); x")
+            .unwrap()
+            .into_iter()
+            .map(|v| v.cast::<Fixed>().0)
+            .collect::<Vec<_>>(),
+        [3000, 2000, 1000]
+    );
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+fn test_numeric_metadata() {
+    let mut engine = Engine::new();
+
+    engine
+        .register_type_with_name::<Fixed>("Fixed")
+        .register_numeric_type(fixed_ops());
+
+    let signatures = engine.gen_fn_signatures(false);
+
+    for signature in &[
+        "+(x: Fixed, y: Fixed) -> Fixed",
+        "+(x: Fixed, y: INT) -> Fixed",
+        "+(x: INT, y: Fixed) -> Fixed",
+        "%(x: Fixed, y: Fixed) -> Fixed",
+        "-(x: Fixed) -> Fixed",
+        "<(x: Fixed, y: Fixed) -> bool",
+        "==(x: INT, y: Fixed) -> bool",
+        "abs(x: Fixed) -> Fixed",
+        "sign(x: Fixed) -> INT",
+        "min(x: Fixed, y: Fixed) -> Fixed",
+        "max(x: Fixed, y: Fixed) -> Fixed",
+        "clamp(x: Fixed, min: Fixed, max: Fixed) -> Fixed",
+        "to_string(x: Fixed) -> String",
+        "+(x: &str, y: Fixed) -> String",
+    ] {
+        assert!(
+            signatures.iter().any(|s| s == signature),
+            "missing {}",
+            signature
+        );
+    }
+}
@@ -134,3 +134,237 @@ fn test_max_operations_progress() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_max_operations_try_catch() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.on_progress(|count| {
+        if count < 1000 {
+            None
+        } else {
+            Some((42 as INT).into())
+        }
+    });
+
+    // Terminations cannot be caught
+    assert!(matches!(
+        *engine
+            .eval::<()>("loop { try { } catch { } }")
+            .expect_err("should error"),
+        EvalAltResult::ErrorTerminated(_, _)
+    ));
+
+    // ... not even when raised inside a function called by a native function
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_function"))]
+    assert!(!engine
+        .eval::<()>("loop { try { [1, 2].map(|x| { loop {} }); } catch { } }")
+        .expect_err("should error")
+        .is_catchable());
+
+    engine.set_max_operations(500);
+
+    assert!(matches!(
+        *engine
+            .eval::<()>("loop { try { } catch { } }")
+            .expect_err("should error"),
+        EvalAltResult::ErrorTooManyOperations(_)
+    ));
+
+    // A looser filter lets the script catch violations of limits
+    engine.set_max_string_size(10);
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"try { "x" * 100; 0 } catch { 1 }"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, _)
+    ));
+
+    engine.set_catch_filter(|err| {
+        err.is_catchable() || matches!(err, EvalAltResult::ErrorDataTooLarge(_, _))
+    });
+
+    assert_eq!(
+        engine.eval::<INT>(r#"try { "x" * 100; 0 } catch { 1 }"#)?,
+        1
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_max_operations_compiled_expr() -> Result<(), Box<EvalAltResult>> {
+    use rhai::Scope;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    let mut engine = Engine::new();
+    engine.set_allow_compile_expression(true);
+
+    let script = r#"
+        let e = compile_expression(expr);
+        let total = 0;
+        for i in range(0, 10) { total += e.eval(#{ x: i }); }
+        total
+    "#;
+
+    let count_operations = |engine: &mut Engine, expr: &str| {
+        let ops = Arc::new(AtomicU64::new(0));
+        let ops2 = ops.clone();
+        engine.on_progress(move |count| {
+            ops2.store(count, Ordering::SeqCst);
+            None
+        });
+
+        let mut scope = Scope::new();
+        scope.push("expr", expr.to_string());
+        engine.eval_with_scope::<INT>(&mut scope, script).unwrap();
+        ops.load(Ordering::SeqCst)
+    };
+
+    let small = count_operations(&mut engine, "x");
+    let large = count_operations(&mut engine, "x + x + x + x + x + x + x + x + x + x");
+
+    // Operations within the compiled expression count towards the calling script
+    assert!(large > small);
+
+    engine.set_max_operations(small + 20);
+
+    let mut scope = Scope::new();
+    scope.push("expr", "x + x + x + x + x + x + x + x + x + x".to_string());
+
+    assert!(matches!(
+        *engine
+            .eval_with_scope::<INT>(&mut scope, script)
+            .expect_err("should error"),
+        EvalAltResult::ErrorTooManyOperations(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_max_operations_options() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{EvalOptions, Scope};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    let mut engine = Engine::new();
+    engine.set_max_operations(500);
+
+    let engine = Arc::new(engine);
+
+    // Run a script with an operations limit, returning the last operations count reported
+    fn run(
+        engine: &Engine,
+        limit: u64,
+        script: &str,
+        nested: Option<(Arc<Engine>, Arc<AtomicU64>)>,
+    ) -> u64 {
+        let ops = Arc::new(AtomicU64::new(0));
+        let ops2 = ops.clone();
+
+        let mut options = EvalOptions::new();
+        options.set_max_operations(limit);
+        options.on_progress(move |count| {
+            ops2.store(count, Ordering::SeqCst);
+
+            // Interleave another evaluation with a different limit
+            if count == 50 {
+                if let Some((engine, nested_ops)) = &nested {
+                    let count = run(engine, 100, "for x in range(0, 1000) {}", None);
+                    nested_ops.store(count, Ordering::SeqCst);
+                }
+            }
+            None
+        });
+
+        assert!(matches!(
+            *engine
+                .eval_with_options::<()>(&mut Scope::new(), script, &options)
+                .expect_err("should error"),
+            EvalAltResult::ErrorTooManyOperations(_)
+        ));
+
+        ops.load(Ordering::SeqCst)
+    }
+
+    let nested_ops = Arc::new(AtomicU64::new(0));
+    let nested = Some((engine.clone(), nested_ops.clone()));
+
+    assert_eq!(
+        run(&engine, 2000, "for x in range(0, 5000) {}", nested),
+        2000
+    );
+    assert_eq!(nested_ops.load(Ordering::SeqCst), 100);
+
+    #[cfg(feature = "sync")]
+    {
+        let threads: Vec<_> = [100, 2000]
+            .iter()
+            .map(|&limit| {
+                let engine = engine.clone();
+                std::thread::spawn(move || run(&engine, limit, "for x in range(0, 5000) {}", None))
+            })
+            .collect();
+
+        let counts: Vec<_> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+
+        assert_eq!(counts, [100, 2000]);
+    }
+
+    // The engine's own settings are not changed
+    assert_eq!(engine.max_operations(), 500);
+    assert!(matches!(
+        *engine
+            .eval::<()>("for x in range(0, 1000) {}")
+            .expect_err("should error"),
+        EvalAltResult::ErrorTooManyOperations(_)
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_max_call_levels_options() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{EvalOptions, Scope};
+
+    let engine = Engine::new();
+    let max_call_levels = engine.max_call_levels();
+
+    let ast = engine.compile("fn foo(n) { if n == 0 { 0 } else { 1 + foo(n - 1) } }")?;
+
+    let mut options = EvalOptions::new();
+    options.set_max_call_levels(3);
+
+    assert_eq!(
+        engine.call_fn::<INT>(&mut Scope::new(), &ast, "foo", (5 as INT,))?,
+        5
+    );
+    let mut err = engine
+        .call_fn_with_options::<INT>(&mut Scope::new(), &ast, "foo", (5 as INT,), &options)
+        .expect_err("should error");
+
+    while let EvalAltResult::ErrorInFunctionCall(_, _, inner, _) = *err {
+        err = inner;
+    }
+    assert!(matches!(*err, EvalAltResult::ErrorStackOverflow(_)));
+
+    assert_eq!(
+        engine.call_fn_with_options::<INT>(
+            &mut Scope::new(),
+            &ast,
+            "foo",
+            (2 as INT,),
+            &options
+        )?,
+        2
+    );
+    assert_eq!(engine.max_call_levels(), max_call_levels);
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, INT};
+use rhai::{Engine, EvalAltResult, Position, RegisterFn, INT};
 
 #[test]
 fn test_ops() -> Result<(), Box<EvalAltResult>> {
@@ -21,3 +21,63 @@ fn test_op_precedence() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_op_assignment_fallback() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Debug, Clone)]
+    struct TestStruct(INT);
+
+    let mut engine = Engine::new();
+
+    engine.register_fn("new_ts", TestStruct);
+    engine.register_fn("value", |x: &mut TestStruct| x.0);
+    engine.register_fn("+", |x: TestStruct, y: TestStruct| TestStruct(x.0 + y.0));
+    engine.register_fn("*", |x: TestStruct, y: INT| TestStruct(x.0 * y));
+    engine.register_fn("*=", |x: &mut TestStruct, y: INT| x.0 *= y * 100);
+
+    // Only '+' is registered
+    assert_eq!(
+        engine.eval::<INT>("let x = new_ts(40); x += new_ts(2); value(x)")?,
+        42
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(
+        engine.eval::<INT>("let x = [new_ts(40)]; x[0] += new_ts(2); value(x[0])")?,
+        42
+    );
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval::<INT>("let x = #{a: new_ts(40)}; x.a += new_ts(2); value(x.a)")?,
+        42
+    );
+
+    // '*=' is registered and takes precedence over '*'
+    assert_eq!(
+        engine.eval::<INT>("let x = new_ts(2); x *= 3; value(x)")?,
+        600
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(
+        engine.eval::<INT>("let x = [new_ts(2)]; x[0] *= 3; value(x[0])")?,
+        600
+    );
+
+    // Neither '-=' nor '-' is registered
+    assert!(matches!(
+        *engine.eval::<INT>("let x = new_ts(1);\nx -= 1; value(x)").expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(f, pos)
+            if f.starts_with("- ") && pos == Position::new(2, 3)
+    ));
+
+    #[cfg(not(feature = "no_index"))]
+    assert!(matches!(
+        *engine.eval::<INT>("let x = [new_ts(1)];\nx[0] -= 1; value(x[0])").expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(f, pos)
+            if f.starts_with("- ") && pos == Position::new(2, 6)
+    ));
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 #![cfg(not(feature = "no_optimize"))]
 
-use rhai::{Engine, EvalAltResult, OptimizationLevel, RegisterFn, INT};
+use rhai::{Engine, EvalAltResult, OptimizationLevel, Position, RegisterFn, RegisterResultFn, INT};
 
 #[test]
 fn test_optimizer_run() -> Result<(), Box<EvalAltResult>> {
@@ -55,7 +55,7 @@ fn test_optimizer_parse() -> Result<(), Box<EvalAltResult>> {
 
     let ast = engine.compile("{ const DECISION = false; if DECISION { 42 } else { 123 } }")?;
 
-    assert!(format!("{:?}", ast).starts_with(r#"AST { source: None, statements: [Block([Const(Ident("DECISION" @ 1:9), Some(Unit(0:0)), false, 1:3), Expr(IntegerConstant(123, 1:53))], 1:1)]"#));
+    assert!(format!("{:?}", ast).starts_with(r#"AST { source: None, statements: [Block([Const(Ident("DECISION" @ 1:9), Some(BoolConstant(false, 1:20)), false, 1:3), Expr(IntegerConstant(123, 1:53))], 1:1)]"#));
 
     let ast = engine.compile("if 1 == 2 { 42 }")?;
 
@@ -72,3 +72,256 @@ fn test_optimizer_parse() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_optimizer_positions() -> Result<(), Box<EvalAltResult>> {
+    fn positions(script: &str) -> Vec<Position> {
+        let mut engine = Engine::new();
+
+        [
+            OptimizationLevel::None,
+            OptimizationLevel::Simple,
+            OptimizationLevel::Full,
+        ]
+        .iter()
+        .map(|&level| {
+            engine.set_optimization_level(level);
+            engine.eval::<INT>(script).expect_err(script).position()
+        })
+        .collect()
+    }
+
+    let scripts = vec![
+        "let x = if true { 40 + 2 } else { 0 }; foo(x)",
+        "let x = (1 + 2) * 3; throw x;",
+        "if false { 1 } let y = 1 + \"a\" - 2; y",
+        "if 1 > 2 { } throw 42;",
+        "while false { } throw 42;",
+        "let x = 1; while x > 0 { break; } throw x;",
+        "try { 1 + 2 } catch (e) { } throw 42;",
+        "const x = 1; x + \"a\" - 1",
+        "const x = 1; if x { 1 } else { 2 }",
+        "if (1 + 1) * 3 { 1 } else { 2 }",
+        "if (if true { 42 } else { 0 }) { 1 } else { 2 }",
+        "if (switch 1 { 1 => 42 }) { 1 } else { 2 }",
+        "let x = switch 1 { 1 => 1 + \"a\" - 2, _ => 0 }; x",
+        "switch 1 + 1 { 1 => 42, _ => throw 1 }",
+        #[cfg(not(feature = "no_index"))]
+        "let a = [1, 2]; a[1 + 2]",
+        #[cfg(not(feature = "no_index"))]
+        "if [1, 2][0] { 1 } else { 2 }",
+        #[cfg(not(feature = "no_index"))]
+        "let x = 1; if [x, 2][0] { 1 } else { 2 }",
+        #[cfg(not(feature = "no_object"))]
+        "let x = 1; if #{a: x}.a { 1 } else { 2 }",
+        #[cfg(not(feature = "no_object"))]
+        "if #{a: 1}[\"a\"] { 1 } else { 2 }",
+    ];
+
+    for script in scripts {
+        let result = positions(script);
+        assert!(result.iter().all(|&pos| pos == result[0]), "{}", script);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_optimizer_switch() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    for &level in &[
+        OptimizationLevel::None,
+        OptimizationLevel::Simple,
+        OptimizationLevel::Full,
+    ] {
+        engine.set_optimization_level(level);
+
+        assert_eq!(engine.eval::<INT>("switch 1 { 1 => 2, _ => 3 }")?, 2);
+        assert_eq!(engine.eval::<INT>("switch 5 { 1 => 2, _ => 3 }")?, 3);
+        assert_eq!(engine.eval::<()>("switch 5 { 1 => 2 }")?, ());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_optimizer_propagate_let() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_fn("inc", |x: &mut INT| *x += 1);
+
+    for &level in &[
+        OptimizationLevel::None,
+        OptimizationLevel::Simple,
+        OptimizationLevel::Full,
+    ] {
+        engine.set_optimization_level(level);
+
+        assert_eq!(engine.eval::<INT>("let x = 2; let y = x * 3; y")?, 6);
+        assert_eq!(engine.eval::<INT>("{ let x = 2; let y = x * 3; y }")?, 6);
+
+        // Reassigned
+        assert_eq!(
+            engine.eval::<INT>("let x = 2; let y = x * 3; x = 10; x + y")?,
+            16
+        );
+        assert_eq!(
+            engine.eval::<INT>("{ let x = 2; let y = x; while y < 5 { y += x; } y }")?,
+            6
+        );
+
+        // Shadowed
+        assert_eq!(
+            engine.eval::<INT>("let x = 2; { let x = 5; x += 1; } x")?,
+            2
+        );
+        assert_eq!(
+            engine.eval::<INT>("let x = 2; let y = { let x = x + 1; x * 10 }; x + y")?,
+            32
+        );
+        assert_eq!(
+            engine
+                .eval::<INT>("{ let x = 2; let s = 0; for x in range(0, 3) { s += x; } s + x }")?,
+            5
+        );
+        assert_eq!(
+            engine.eval::<INT>(r#"{ let e = 2; try { throw 40; } catch (e) { e + 2 } }"#)?,
+            42
+        );
+
+        // Passed by reference to a registered function
+        assert_eq!(engine.eval::<INT>("{ let x = 1; inc(x); x }")?, 2);
+        #[cfg(not(feature = "no_object"))]
+        assert_eq!(engine.eval::<INT>("{ let x = 1; x.inc(); x }")?, 2);
+        #[cfg(not(feature = "no_index"))]
+        assert_eq!(
+            engine.eval::<INT>("{ let a = [1]; { push(a, 2); } len(a) }")?,
+            2
+        );
+        #[cfg(not(feature = "no_index"))]
+        assert_eq!(engine.eval::<INT>("{ let a = [1]; a[0] = 42; a[0] }")?, 42);
+
+        // Captured by a closure
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(not(feature = "no_closure"))]
+        assert_eq!(
+            engine.eval::<INT>("{ let x = 1; let f = || x += 1; call(f); x }")?,
+            2
+        );
+
+        // Accessed dynamically
+        assert_eq!(engine.eval::<INT>(r#"{ let x = 1; eval("x = 2"); x }"#)?, 2);
+        assert!(engine.eval::<bool>(r#"{ let x = 1; is_def_var("x") }"#)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_optimizer_propagate_let_parse() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::Simple);
+
+    // Dead bindings are removed within blocks
+    let ast = engine.compile("{ let x = 2; let y = x * 3; y }")?;
+
+    assert!(format!("{:?}", ast)
+        .starts_with("AST { source: None, statements: [Expr(IntegerConstant(6, 1:29))]"));
+
+    // Variables at global level are kept
+    let ast = engine.compile("let x = 2; x * 3")?;
+
+    assert!(format!("{:?}", ast).starts_with(
+        r#"AST { source: None, statements: [Let(Ident("x" @ 1:5), Some(IntegerConstant(2, 1:9)), false, 1:1), Expr(IntegerConstant(6, 1:14))]"#
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_optimizer_dead_code() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    for &level in &[
+        OptimizationLevel::None,
+        OptimizationLevel::Simple,
+        OptimizationLevel::Full,
+    ] {
+        engine.set_optimization_level(level);
+
+        assert_eq!(
+            engine.eval::<INT>(
+                "let x = 0; loop { x += 1; if x < 3 { continue; } else { break; } x = 100; } x"
+            )?,
+            3
+        );
+        assert_eq!(
+            engine.eval::<INT>(
+                "let x = do { if true { break 42; } else { break 0; } } while false; x"
+            )?,
+            42
+        );
+        assert_eq!(
+            engine.eval::<INT>("let x = 1; let y = switch x { 1 => { x += 41; x } _ => 0 }; y")?,
+            42
+        );
+
+        #[cfg(not(feature = "no_function"))]
+        assert_eq!(
+            engine.eval::<INT>(
+                "fn f(x) { if x > 0 { return 42; } else { return 0; } throw 1; } f(1)"
+            )?,
+            42
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_optimizer_pure_fn() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_fn_pure("double", |x: INT| x * 2);
+    engine.register_fn("triple", |x: INT| x * 3);
+    engine.register_result_fn_pure("checked_half", |x: INT| {
+        if x % 2 == 0 {
+            Ok((x / 2).into())
+        } else {
+            Err("odd number".into())
+        }
+    });
+
+    engine.set_optimization_level(OptimizationLevel::Simple);
+
+    // Pure functions with constant arguments are folded
+    let ast = engine.compile("double(21)")?;
+    assert!(format!("{:?}", ast)
+        .starts_with("AST { source: None, statements: [Expr(IntegerConstant(42, 1:1))]"));
+
+    let ast = engine.compile("checked_half(double(42))")?;
+    assert!(format!("{:?}", ast)
+        .starts_with("AST { source: None, statements: [Expr(IntegerConstant(42, 1:1))]"));
+
+    // Other registered functions are not
+    let ast = engine.compile("triple(14)")?;
+    assert!(!format!("{:?}", ast).contains("IntegerConstant(42"));
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+
+    // Errors abort folding and are raised at run time instead
+    let ast = engine.compile("checked_half(double(21) + 1)")?;
+    assert!(matches!(
+        *engine.eval_ast::<INT>(&ast).expect_err("should error"),
+        EvalAltResult::ErrorRuntime(_, _)
+    ));
+
+    // Script-defined functions override pure functions
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<INT>("fn double(x) { x * 10 } double(21)")?,
+        210
+    );
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, RegisterFn, INT};
+use rhai::{Engine, EvalAltResult, OutputKind, Position, RegisterFn, Scope, INT};
 use std::sync::{Arc, RwLock};
 
 #[test]
@@ -43,6 +43,69 @@ fn test_print_debug() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_print_with_position() -> Result<(), Box<EvalAltResult>> {
+    let logbook = Arc::new(RwLock::new(Vec::<String>::new()));
+    let log = logbook.clone();
+
+    let mut engine = Engine::new();
+
+    engine.on_print_with_position(move |s, src, pos| {
+        log.write()
+            .unwrap()
+            .push(format!("{} @ {:?}: {}", src.unwrap_or("unknown"), pos, s))
+    });
+
+    let mut ast = engine.compile(
+        r#"
+            fn foo(x) {
+                print(x);
+            }
+            print("hello");
+            foo("world");
+        "#,
+    )?;
+    ast.set_source(Some("test"));
+    engine.consume_ast(&ast)?;
+
+    assert_eq!(
+        *logbook.read().unwrap(),
+        ["test @ 5:13: hello", "test @ 3:17: world"]
+    );
+
+    // Closures
+    logbook.write().unwrap().clear();
+
+    let mut ast = engine.compile(r#"let f = |x| print(x); call(f, 42);"#)?;
+    ast.set_source(Some("closure"));
+    engine.consume_ast(&ast)?;
+
+    assert_eq!(*logbook.read().unwrap(), ["closure @ 1:13: 42"]);
+
+    // Imported modules
+    #[cfg(not(feature = "no_module"))]
+    {
+        use rhai::{module_resolvers::StaticModuleResolver, Module};
+
+        logbook.write().unwrap().clear();
+
+        let mut ast = engine.compile(r#"fn greet(x) { print(x); }"#)?;
+        ast.set_source(Some("greetings"));
+        let module = Module::eval_ast_as_new(Default::default(), &ast, &engine)?;
+
+        let mut resolver = StaticModuleResolver::new();
+        resolver.insert("greetings", module);
+        engine.set_module_resolver(resolver);
+
+        engine.consume(r#"import "greetings" as g; g::greet("hi");"#)?;
+
+        assert_eq!(*logbook.read().unwrap(), ["greetings @ 1:15: hi"]);
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
 struct MyStruct {
     field: INT,
@@ -88,3 +151,94 @@ fn test_print_custom_type() -> Result<(), Box<EvalAltResult>> {
         .contains(r#""e": hello: 42"#));
     Ok(())
 }
+
+#[test]
+fn test_print_captured_output() -> Result<(), Box<EvalAltResult>> {
+    let logbook = Arc::new(RwLock::new(Vec::<String>::new()));
+    let log1 = logbook.clone();
+    let log2 = logbook.clone();
+
+    let mut engine = Engine::new();
+
+    engine
+        .on_print(move |s| log1.write().unwrap().push(s.to_string()))
+        .on_debug(move |s, _, _| log2.write().unwrap().push(s.to_string()));
+
+    let mut ast = engine.compile(
+        r#"
+            print("hello");
+            debug(40 + 2);
+            print("world");
+            true
+        "#,
+    )?;
+    ast.set_source(Some("test"));
+
+    let (result, output) = engine.eval_ast_with_captured_output(&mut Scope::new(), &ast)?;
+
+    assert!(result.cast::<bool>());
+    assert_eq!(output.len(), 3);
+
+    let entries = output.entries();
+    assert_eq!(entries[0].kind, OutputKind::Print);
+    assert_eq!(entries[0].text, "hello");
+    assert_eq!(entries[0].position, Position::new(2, 13));
+    assert_eq!(entries[1].kind, OutputKind::Debug);
+    assert_eq!(entries[1].text, "42");
+    assert_eq!(entries[1].position, Position::new(3, 13));
+    assert_eq!(entries[2].kind, OutputKind::Print);
+    assert_eq!(entries[2].text, "world");
+    assert!(output
+        .iter()
+        .all(|entry| entry.source.as_ref().map(|s| s.as_str()) == Some("test")));
+
+    // Callbacks are still called
+    assert_eq!(*logbook.read().unwrap(), ["hello", "42", "world"]);
+
+    // Output of other evaluations is not captured
+    engine.consume(r#"print("not captured")"#)?;
+    let (_, output) = engine.eval_with_captured_output(&mut Scope::new(), "42")?;
+    assert!(output.is_empty());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "sync")]
+fn test_print_captured_output_threads() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.on_print(|_| {});
+
+    let engine = Arc::new(engine);
+
+    let handles: Vec<_> = (0..2)
+        .map(|n| {
+            let engine = engine.clone();
+
+            std::thread::spawn(move || {
+                let mut scope = Scope::new();
+                scope.push("n", n as INT);
+
+                engine
+                    .eval_with_captured_output(
+                        &mut scope,
+                        r#"for i in range(0, 500) { print(n.to_string() + ":" + i); }"#,
+                    )
+                    .unwrap()
+                    .1
+            })
+        })
+        .collect();
+
+    for (n, handle) in handles.into_iter().enumerate() {
+        let output = handle.join().unwrap();
+
+        assert_eq!(output.len(), 500);
+
+        output.iter().enumerate().for_each(|(i, entry)| {
+            assert_eq!(entry.text, format!("{}:{}", n, i));
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,372 @@
+#![cfg(not(feature = "no_index"))]
+use rhai::{Array, Dynamic, Engine, EvalAltResult, RegisterFn, Scope, INT};
+
+#[test]
+fn test_semantics_ptr_eq() {
+    let x = Dynamic::from(42 as INT);
+    let y = x.clone();
+
+    assert!(!x.ptr_eq(&y));
+    assert!(!x.ptr_eq(&x));
+
+    #[cfg(not(feature = "no_closure"))]
+    {
+        let x = x.into_shared();
+        let y = x.clone();
+
+        assert!(x.ptr_eq(&y));
+        assert!(!x.ptr_eq(&Dynamic::from(42 as INT).into_shared()));
+        assert!(!x.ptr_eq(&x.flatten_clone()));
+    }
+}
+
+#[test]
+fn test_semantics_assignment() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>("let a = [1, 2]; let b = a; push(b, 3); len(a)")?,
+        2
+    );
+    assert_eq!(
+        engine.eval::<INT>("let a = [1, 2]; let b = a; a[0] = 42; b[0]")?,
+        1
+    );
+    assert!(!engine.eval::<bool>("let a = [1, 2]; let b = a; same_instance(a, b)")?);
+    assert!(!engine.eval::<bool>("let a = [1, 2]; same_instance(a, a)")?);
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval::<INT>("let a = #{x: 1}; let b = a; b.x = 42; a.x")?,
+        1
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_semantics_same_instance_override() -> Result<(), Box<EvalAltResult>> {
+    #[cfg(not(feature = "no_function"))]
+    assert!(Engine::new().eval::<bool>("fn same_instance(x, y) { true } same_instance(1, 2)")?);
+
+    let mut engine = Engine::new();
+    engine.register_fn("same_instance", |x: INT, y: INT| x == y);
+
+    assert!(engine.eval::<bool>("let x = 42; same_instance(x, 40 + 2)")?);
+    #[cfg(not(feature = "no_object"))]
+    assert!(engine.eval::<bool>("let x = 42; x.same_instance(42)")?);
+    assert!(!engine.eval::<bool>("let a = [1]; same_instance(a, a)")?);
+
+    Ok(())
+}
+
+#[test]
+fn test_semantics_fn_args() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    // Native functions receive the first argument by reference
+    assert_eq!(engine.eval::<INT>("let a = []; push(a, 1); len(a)")?, 1);
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        // Script-defined functions receive copies of their arguments
+        assert_eq!(
+            engine.eval::<INT>(
+                r"
+                    fn change(x) { push(x, 42); len(x) }
+                    let a = [];
+                    let n = change(a);
+                    n * 10 + len(a)
+                "
+            )?,
+            10
+        );
+
+        // ... except for `this` in method-call style
+        #[cfg(not(feature = "no_object"))]
+        assert_eq!(
+            engine.eval::<INT>(
+                r"
+                    fn change() { this.push(42); }
+                    let a = [];
+                    a.change();
+                    a.len()
+                "
+            )?,
+            1
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_semantics_containers() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>("let a = [1]; let b = [a]; push(a, 2); len(b[0])")?,
+        1
+    );
+    assert_eq!(
+        engine.eval::<INT>("let a = [1]; let b = [a]; b[0][0] = 42; a[0]")?,
+        1
+    );
+    assert_eq!(
+        engine.eval::<INT>("let a = [1]; let b = []; push(b, a); b[0][0] = 42; a[0]")?,
+        1
+    );
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert_eq!(
+            engine.eval::<INT>("let a = [1]; let m = #{x: a}; push(a, 2); len(m.x)")?,
+            1
+        );
+        assert_eq!(
+            engine.eval::<INT>("let a = [1]; let m = #{}; m.x = a; m.x[0] = 42; a[0]")?,
+            1
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_closure"))]
+#[test]
+fn test_semantics_closures() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    // Captured variables are shared between the closure and the outer scope
+    assert_eq!(
+        engine.eval::<INT>("let a = [1]; let f = || a += [2]; call(f); len(a)")?,
+        2
+    );
+    assert!(engine.eval::<bool>("let a = [1]; let f = || len(a); is_shared(a)")?);
+    assert!(engine.eval::<bool>("let a = [1]; let f = || len(a); same_instance(a, a)")?);
+    #[cfg(not(feature = "no_object"))]
+    assert!(engine.eval::<bool>("let a = [1]; let f = || len(a); a.same_instance(a)")?);
+
+    // Two closures capturing the same variable see the same storage
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = 1;
+                let inc = || x += 1;
+                let get = || x;
+                call(inc);
+                call(inc);
+                call(get)
+            "
+        )?,
+        3
+    );
+
+    // Assigning a shared value to another variable makes a copy
+    assert_eq!(
+        engine.eval::<INT>("let a = [1]; let f = || len(a); let b = a; b += [2]; len(a)")?,
+        1
+    );
+    assert!(!engine.eval::<bool>("let a = [1]; let f = || len(a); let b = a; same_instance(a, b)")?);
+
+    // Putting a shared value into a container makes a copy
+    assert_eq!(
+        engine.eval::<INT>("let a = [1]; let f = || len(a); let b = [a]; a += [2]; len(b[0])")?,
+        1
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            "let a = [1]; let f = || len(a); let b = [0]; b[0] = a; a += [2]; len(b[0])"
+        )?,
+        1
+    );
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert_eq!(
+            engine.eval::<INT>(
+                "let a = [1]; let f = || len(a); let m = #{x: a}; a += [2]; len(m.x)"
+            )?,
+            1
+        );
+        assert_eq!(
+            engine.eval::<INT>(
+                "let a = [1]; let f = || len(a); let m = #{}; m.x = a; a += [2]; len(m.x)"
+            )?,
+            1
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_semantics_scope() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let mut scope = Scope::new();
+
+    let array: Array = vec![Dynamic::from(1 as INT)];
+
+    // Pushing into a scope stores a copy of the value
+    scope.push("a", array.clone());
+
+    engine.consume_with_scope(&mut scope, "push(a, 2); let b = a; push(b, 3);")?;
+
+    assert_eq!(array.len(), 1);
+    assert_eq!(
+        scope.get_value::<Array>("a").expect("a should exist").len(),
+        2
+    );
+    assert_eq!(
+        scope.get_value::<Array>("b").expect("b should exist").len(),
+        3
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_module"))]
+#[test]
+fn test_semantics_module_constants() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{module_resolvers::StaticModuleResolver, Module};
+
+    let mut engine = Engine::new();
+
+    let ast = engine.compile("export const x = [1]; let y = [1]; export y;")?;
+    let module = Module::eval_ast_as_new(Scope::new(), &ast, &engine)?;
+
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("test", module);
+    engine.set_module_resolver(resolver);
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                import "test" as m;
+                let a = m::x;
+                push(a, 2);
+                len(a) * 10 + len(m::x)
+            "#
+        )?,
+        21
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                import "test" as m;
+                let a = m::y;
+                push(a, 2);
+                import "test" as m;
+                len(m::y)
+            "#
+        )?,
+        1
+    );
+    assert!(!engine.eval::<bool>(r#"import "test" as m; same_instance(m::x, m::x)"#)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_semantics_deep_copy() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>("let a = [1, [2, 3]]; let b = deep_copy(a); b[1][0] = 42; a[1][0]")?,
+        2
+    );
+    assert_eq!(
+        engine.eval::<INT>("let a = [1, [2, 3]]; deep_copy(a)[1][1]")?,
+        3
+    );
+
+    #[cfg(not(feature = "no_closure"))]
+    {
+        assert!(!engine.eval::<bool>("let a = [1]; let f = || len(a); is_shared(deep_copy(a))")?);
+
+        // A shared value that contains itself cannot be copied
+        let err = engine
+            .eval::<Dynamic>("let a = [1]; let f = || len(a); a += [f]; deep_copy(a)")
+            .expect_err("should error");
+        assert!(matches!(*err, EvalAltResult::ErrorRuntime(_, pos) if pos.position() == Some(43)));
+
+        #[cfg(not(feature = "no_object"))]
+        assert!(matches!(
+            *engine
+                .eval::<Dynamic>("let a = [1]; let f = || len(a); a += [f]; a.deep_copy()")
+                .expect_err("should error"),
+            EvalAltResult::ErrorRuntime(_, _)
+        ));
+    }
+
+    // Functions named deep_copy take precedence
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<INT>("fn deep_copy(x) { 42 } let a = [1]; deep_copy(a) + deep_copy([2])")?,
+        84
+    );
+
+    let mut engine = Engine::new();
+    engine.register_fn("deep_copy", |x: INT| x + 1);
+
+    assert_eq!(engine.eval::<INT>("let x = 41; deep_copy(x)")?, 42);
+    assert_eq!(engine.eval::<INT>("deep_copy(40 + 1)")?, 42);
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(engine.eval::<INT>("let x = 41; x.deep_copy()")?, 42);
+    assert_eq!(engine.eval::<INT>("let a = [1, 2]; len(deep_copy(a))")?, 2);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[cfg(not(feature = "no_closure"))]
+#[test]
+fn test_semantics_deep_copy_limits() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_max_array_size(20);
+
+    // Copying a shared value held many times multiplies its size
+    assert!(matches!(
+        *engine
+            .eval::<Dynamic>(
+                r"
+                    let a = [1, 2, 3, 4, 5];
+                    let f = || len(a);
+                    let b = [f, f, f, f, f];
+                    deep_copy(b)
+                "
+            )
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, _)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_semantics_large_copy() -> Result<(), Box<EvalAltResult>> {
+    let copies = std::sync::Arc::new(std::sync::RwLock::new(Vec::new()));
+    let log = copies.clone();
+
+    let mut engine = Engine::new();
+    engine.on_large_copy(3, move |name, size, pos| {
+        log.write()
+            .unwrap()
+            .push((name.to_string(), size, pos.position().unwrap()));
+    });
+
+    engine.consume(
+        r"
+            let a = [1, 2, 3, [4, 5]];
+            let small = [1];
+            let b = a;
+            let c = small;
+            push(a, 6);
+            let n = len(a) + len(deep_copy(a));
+        ",
+    )?;
+
+    assert_eq!(*copies.read().unwrap(), vec![("a".to_string(), 5, 21)]);
+
+    Ok(())
+}
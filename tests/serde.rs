@@ -635,3 +635,86 @@ fn test_serde_de_untagged_enum() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_index"))]
+fn test_serde_custom_types() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{RegisterFn, Scope};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Point {
+        x: INT,
+        y: INT,
+    }
+
+    /// Stand-in for a self-describing data format (e.g. JSON).
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Unit(()),
+        Bool(bool),
+        Int(INT),
+        Str(String),
+        Array(Vec<Value>),
+        Map(BTreeMap<String, Value>),
+    }
+
+    let mut engine = Engine::new();
+    engine
+        .register_type_with_name::<Point>("Point")
+        .register_fn("point", |x: INT, y: INT| Point { x, y });
+
+    let mut scope = Scope::new();
+    engine.consume_with_scope(
+        &mut scope,
+        "let p = point(1, 2); let list = [point(3, 4), 42]; let obj = #{ p: point(5, 6) };",
+    )?;
+
+    // Custom types without serde support
+    let err = engine
+        .from_dynamic::<Value>(&scope.get_value::<Dynamic>("p").unwrap())
+        .expect_err("should error");
+    assert!(err.to_string().contains("register_type_serde"));
+
+    engine.register_type_serde::<Point>();
+
+    // Persist the scope
+    let data = scope
+        .iter()
+        .map(|(name, _, value)| Ok((name.to_string(), engine.from_dynamic::<Value>(&value)?)))
+        .collect::<Result<Vec<_>, Box<EvalAltResult>>>()?;
+
+    let mut tagged = BTreeMap::new();
+    tagged.insert("$type".to_string(), Value::Str("Point".into()));
+    tagged.insert("$value".to_string(), {
+        let mut map = BTreeMap::new();
+        map.insert("x".to_string(), Value::Int(1));
+        map.insert("y".to_string(), Value::Int(2));
+        Value::Map(map)
+    });
+    assert_eq!(data[0], ("p".to_string(), Value::Map(tagged.clone())));
+
+    // Restore the scope
+    let mut scope = Scope::new();
+    for (name, value) in data {
+        scope.push_dynamic(name, engine.to_dynamic(value)?);
+    }
+
+    assert_eq!(scope.get_value::<Point>("p").unwrap(), Point { x: 1, y: 2 });
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "list[1] + type_of(obj.p).len")?,
+        47
+    );
+    assert!(engine.eval_with_scope::<bool>(&mut scope, r#"type_of(list[0]) == "Point""#)?);
+
+    // Unknown type names
+    tagged.insert("$type".to_string(), Value::Str("Line".into()));
+    let err = engine
+        .to_dynamic(Value::Array(vec![Value::Map(tagged)]))
+        .expect_err("should error");
+    assert!(err.to_string().contains("unknown custom type 'Line'"));
+
+    Ok(())
+}
@@ -1,4 +1,7 @@
-use rhai::{Dynamic, Engine, EvalAltResult, ImmutableString, RegisterFn, Scope, INT};
+use rhai::{
+    Dynamic, Engine, EvalAltResult, ImmutableString, LexError, ParseErrorType, Position,
+    RegisterFn, Scope, INT,
+};
 
 #[test]
 fn test_string() -> Result<(), Box<EvalAltResult>> {
@@ -223,3 +226,363 @@ fn test_string_split() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_string_split_max() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(r#"let x = "a,b,c,d"; x.split(",", 2).len"#)?,
+        2
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"let x = "a,b,c,d"; x.split(",", 2)[1]"#)?,
+        "b,c,d"
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"let x = "a,b,c,d"; x.split(",", 99).len"#)?,
+        4
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"let x = "a,b,c,d"; x.split(",", 0)[0]"#)?,
+        "a,b,c,d"
+    );
+    assert_eq!(
+        engine.eval::<String>(
+            r#"let x = "\u2764\u2764 hello \u2764 world \u2764"; x.split(' ', 3)[2]"#
+        )?,
+        "\u{2764} world \u{2764}"
+    );
+    assert_eq!(
+        engine.eval::<String>(
+            r#"let x = "\u2764\u2764\u2764\u2764"; x.split("\u2764\u2764", 2)[1]"#
+        )?,
+        "\u{2764}\u{2764}"
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"let x = "abc"; x.split("", 2).len"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_string_replace_fn() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let x = "one two one three one";
+                replace(x, "one", |m| len(m) + "!");
+                x
+            "#
+        )?,
+        "3! two 3! three 3!"
+    );
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let x = "\u2764a\u2764b\u2764";
+                replace(x, '\u2764', |m| "[" + m + "]");
+                x
+            "#
+        )?,
+        "[\u{2764}]a[\u{2764}]b[\u{2764}]"
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"let x = "a-b-c"; replace(x, "-", |m| '+'); x"#)?,
+        "a+b+c"
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"let x = "abc"; replace(x, "z", |m| "!"); x"#)?,
+        "abc"
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<String>(r#"let x = "abc"; replace(x, "", |m| "!"); x"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<String>(r#"let x = "abc"; replace(x, "b", |m| 42); x"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(f, _, err, _)
+            if f == "replace" && matches!(*err, EvalAltResult::ErrorMismatchOutputType(_, _, _))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_string_repeat() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<String>(r#""ab" * 3"#)?, "ababab");
+    assert_eq!(engine.eval::<String>(r#""ab" * 0"#)?, "");
+    assert_eq!(engine.eval::<String>(r#"let s = "x"; s *= 4; s"#)?, "xxxx");
+
+    assert!(matches!(
+        *engine.eval::<String>(r#""ab" * -1"#).expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(msg, _) if msg.contains("negative")
+    ));
+    #[cfg(not(feature = "only_i32"))]
+    assert!(matches!(
+        *engine.eval::<String>(&format!(r#""ab" * {}"#, INT::MAX)).expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(msg, _) if msg.contains("too many")
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_string_raw() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<String>(r#"r"C:\Users\test\n""#)?,
+        r"C:\Users\test\n"
+    );
+    assert_eq!(
+        engine.eval::<String>(r###"r#"say "hello""#"###)?,
+        r#"say "hello""#
+    );
+    assert_eq!(
+        engine.eval::<String>(r####"r##"a "# b"##"####)?,
+        r##"a "# b"##
+    );
+    assert_eq!(
+        engine.eval::<String>("r\"line 1\nline 2\"")?,
+        "line 1\nline 2"
+    );
+
+    // 'r' is still a valid variable name
+    assert_eq!(engine.eval::<INT>("let r = 40; r + 2")?, 42);
+
+    // Positions after a multi-line raw string stay accurate
+    let err = engine
+        .compile("let x = r\"line 1\nline 2\";\nlet y = ;")
+        .expect_err("should error");
+    assert_eq!(err.1, Position::new(3, 9));
+
+    // Unterminated raw strings are reported at the opening delimiter
+    let err = engine
+        .compile("let x = 1;\nlet y = r#\"hello\"\n\n")
+        .expect_err("should error");
+    assert_eq!(
+        *err.0,
+        ParseErrorType::BadInput(LexError::UnterminatedString)
+    );
+    assert_eq!(err.1, Position::new(2, 9));
+
+    Ok(())
+}
+
+#[test]
+fn test_string_continuation() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<String>("\"hello, \\\n        world!\"")?,
+        "hello, world!"
+    );
+    assert_eq!(
+        engine.eval::<String>("\"hello, \\\r\n\n   world!\"")?,
+        "hello, world!"
+    );
+
+    // A new-line without a continuation still ends the string
+    assert!(engine.compile("\"hello\nworld\"").is_err());
+
+    let err = engine
+        .compile("let x = \"hello, \\\n    world!\";\nlet y = ;")
+        .expect_err("should error");
+    assert_eq!(err.1, Position::new(3, 9));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_string_raw_max_size() {
+    let mut engine = Engine::new();
+    engine.set_max_string_size(5);
+
+    assert_eq!(
+        *engine
+            .compile(r#"r"hello, world!""#)
+            .expect_err("should error")
+            .0,
+        ParseErrorType::LiteralTooLarge("Length of string literal".to_string(), 5)
+    );
+}
+
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_string_render() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let vars = #{ name: "world", n: 41, items: "ab" };
+                render("Hello, {{ name }}! n = {{n + 1}}, {{ items.len() }} items.", vars)
+            "#
+        )?,
+        "Hello, world! n = 42, 2 items."
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"render("no placeholders", #{})"#)?,
+        "no placeholders"
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"render(r"\{{ x }} = {{ x }}", #{ x: 'c' })"#)?,
+        "{{ x }} = c"
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"render("[{{ () }}] {{ true }}", #{})"#)?,
+        "[] true"
+    );
+
+    let err = engine
+        .eval::<String>("render(\"line 1\\nis {{ x + }}\", #{ x: 1 })")
+        .expect_err("should error");
+    assert!(matches!(
+        *err,
+        EvalAltResult::ErrorInFunctionCall(ref f, _, ref err, _)
+            if f == "render"
+                && matches!(**err, EvalAltResult::ErrorParsing(_, _))
+                && err.position() == Position::new(2, 11)
+    ));
+
+    let err = engine
+        .eval::<String>(r#"render("{{ y }}", #{ x: 1 })"#)
+        .expect_err("should error");
+    assert!(matches!(
+        *err,
+        EvalAltResult::ErrorInFunctionCall(ref f, _, ref err, _)
+            if f == "render"
+                && matches!(**err, EvalAltResult::ErrorVariableNotFound(ref v, _) if v == "y")
+                && err.position() == Position::new(1, 4)
+    ));
+
+    assert!(matches!(
+        *engine
+            .eval::<String>(r#"render("a {{ x", #{ x: 1 })"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(_, _, err, _)
+            if matches!(*err, EvalAltResult::ErrorParsing(ParseErrorType::MissingToken(_, _), _))
+                && err.position() == Position::new(1, 3)
+    ));
+
+    // Disabled symbols are not allowed within placeholders
+    engine.disable_symbol("+");
+
+    assert!(matches!(
+        *engine
+            .eval::<String>(r#"render("{{ x + 1 }}", #{ x: 1 })"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(_, _, err, _)
+            if matches!(*err, EvalAltResult::ErrorParsing(_, _))
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_string_render_disabled() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    assert!(engine.allow_render());
+    engine.set_allow_render(false);
+
+    assert!(matches!(
+        *engine
+            .eval::<String>(r#"render("{{ x }}", #{ x: 42 })"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(f, _) if f.starts_with("render")
+    ));
+
+    assert!(!Engine::new_raw().allow_render());
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_string_render_limits() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    // Operations within placeholders count towards the limits of the calling script
+    engine.set_max_operations(100);
+
+    let script = format!(r#"render("{}", #{{ x: 1 }})"#, "{{ x + 1 }}".repeat(10));
+    assert_eq!(engine.eval::<String>(&script)?, "2".repeat(10));
+
+    let script = format!(r#"render("{}", #{{ x: 1 }})"#, "{{ x + 1 }}".repeat(100));
+    let mut too_many = false;
+    engine
+        .eval::<String>(&script)
+        .expect_err("should error")
+        .walk(|err| too_many |= matches!(err, EvalAltResult::ErrorTooManyOperations(_)));
+    assert!(too_many);
+
+    // Data created within placeholders counts towards the total data size of the calling script
+    engine.set_max_operations(0);
+    engine.set_max_total_data_size(1000);
+
+    let script = format!(
+        r#"render("{}", #{{ x: 1 }})"#,
+        "{{ #{ a: x, b: x, c: x, d: x }.len() }}".repeat(10)
+    );
+    let mut too_large = false;
+    engine
+        .eval::<String>(&script)
+        .expect_err("should error")
+        .walk(|err| too_large |= matches!(err, EvalAltResult::ErrorDataTooLarge(_, _)));
+    assert!(too_large);
+
+    engine.set_max_total_data_size(0);
+
+    // Rendering a template from within a placeholder is one call level deeper
+    #[cfg(not(feature = "no_function"))]
+    {
+        engine.set_max_operations(0);
+        engine.set_max_call_levels(10);
+
+        let mut overflow = false;
+        engine
+            .eval::<String>(
+                r#"
+                    let t = "{{ render(t, #{t: t}) }}";
+                    render(t, #{t: t})
+                "#,
+            )
+            .expect_err("should error")
+            .walk(|err| overflow |= matches!(err, EvalAltResult::ErrorStackOverflow(_)));
+        assert!(overflow);
+
+        assert_eq!(
+            engine.eval::<String>(
+                r#"
+                    let inner = "[{{ x }}]";
+                    render("<{{ render(inner, #{x: x}) }}>", #{inner: inner, x: 42})
+                "#
+            )?,
+            "<[42]>"
+        );
+    }
+
+    Ok(())
+}
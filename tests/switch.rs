@@ -63,6 +63,103 @@ fn test_switch() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_switch_char_ranges() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let mut scope = Scope::new();
+    scope.push("c", 'q');
+
+    // Overlapping ranges - the first match wins
+    assert_eq!(
+        engine.eval_with_scope::<INT>(
+            &mut scope,
+            "switch c { 'a'..='z' => 1, 'p'..='r' => 2, _ => 3 }"
+        )?,
+        1
+    );
+    assert_eq!(
+        engine.eval_with_scope::<INT>(
+            &mut scope,
+            "switch c { 'p'..='r' => 2, 'a'..='z' => 1, _ => 3 }"
+        )?,
+        2
+    );
+    // Exact matches take precedence over ranges
+    assert_eq!(
+        engine
+            .eval_with_scope::<INT>(&mut scope, "switch c { 'a'..='z' => 1, 'q' => 42, _ => 3 }")?,
+        42
+    );
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "switch c { 'a'..'q' => 1, _ => 3 }")?,
+        3
+    );
+    assert_eq!(
+        engine.eval::<INT>("switch 'Q' { 'a'..='z' => 1, 'A'..='Z' => 2, _ => 3 }")?,
+        2
+    );
+    assert_eq!(
+        engine.eval::<INT>("switch 42 { 'a'..='z' => 1, _ => 3 }")?,
+        3
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_switch_int_ranges() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let mut scope = Scope::new();
+    scope.push("x", 42 as INT);
+
+    // Overlapping ranges - the first match wins
+    assert_eq!(
+        engine.eval_with_scope::<INT>(
+            &mut scope,
+            "switch x { 0..10 => 1, 10..=100 => 2, 40..50 => 3, _ => 4 }"
+        )?,
+        2
+    );
+    assert_eq!(
+        engine.eval_with_scope::<INT>(
+            &mut scope,
+            "switch x { 40..50 => 3, 10..=100 => 2, _ => 4 }"
+        )?,
+        3
+    );
+    // Exact matches take precedence over ranges
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "switch x { 0..100 => 1, 42 => 0, _ => 4 }")?,
+        0
+    );
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "switch x { 0..42 => 1, _ => 4 }")?,
+        4
+    );
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "switch x { 0..=42 => 1, _ => 4 }")?,
+        1
+    );
+    assert_eq!(
+        engine.eval::<INT>("let y = -5; switch y { -10..0 => 1, 0..10 => 2, _ => 3 }")?,
+        1
+    );
+    assert_eq!(
+        engine.eval::<INT>("switch 'x' { 0..1000 => 1, _ => 3 }")?,
+        3
+    );
+
+    assert!(engine.compile("switch x { 0..x => 1, _ => 3 }").is_err());
+    #[cfg(not(feature = "no_float"))]
+    assert!(engine
+        .compile("switch x { 0.0..1.0 => 1, _ => 3 }")
+        .expect_err("should error")
+        .to_string()
+        .contains("Floating-point ranges are not supported"));
+
+    Ok(())
+}
+
 #[cfg(not(feature = "no_index"))]
 #[cfg(not(feature = "no_object"))]
 mod test_switch_enum {
@@ -85,6 +85,20 @@ fn test_custom_syntax() -> Result<(), Box<EvalAltResult>> {
         42
     );
 
+    // Many variables declared before the custom syntax must still be hidden behind it
+    let script = (0..100)
+        .map(|i| format!("let v{} = {};", i, i))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    assert_eq!(
+        engine.eval::<INT>(&format!(
+            "{}\nlet x = 0; exec |x| -> {{ x += 1 }} while x < 42; x + v99",
+            script
+        ))?,
+        141
+    );
+
     // The first symbol must be an identifier
     assert_eq!(
         *engine
@@ -100,6 +114,40 @@ fn test_custom_syntax() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_custom_syntax_list() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_custom_syntax(
+        &["sum_of", "(", "$expr$*,", ")", ":", "$expr$"],
+        0,
+        |context, inputs| {
+            let count = inputs[0].get_literal_value::<INT>().unwrap() as usize;
+            let mut sum: INT = 0;
+
+            for item in &inputs[1..=count] {
+                sum += context.eval_expression_tree(item)?.as_int().unwrap();
+            }
+
+            let factor = context.eval_expression_tree(&inputs[count + 1])?;
+
+            Ok((sum * factor.as_int().unwrap()).into())
+        },
+    )?;
+
+    assert_eq!(engine.eval::<INT>("sum_of(1, 2, 3 + 4) : 3")?, 30);
+    assert_eq!(engine.eval::<INT>("let x = 20; sum_of(x + 1) : 2")?, 42);
+    assert_eq!(engine.eval::<INT>("sum_of() : 2")?, 0);
+    assert!(engine.compile("sum_of(1, 2,) : 2").is_err());
+    assert!(engine.compile("sum_of(1 2) : 2").is_err());
+
+    assert!(engine
+        .register_custom_syntax(&["bad_list", "$expr$*"], 0, |_, _| Ok(Dynamic::UNIT))
+        .is_err());
+
+    Ok(())
+}
+
 #[test]
 fn test_custom_syntax_raw() -> Result<(), Box<EvalAltResult>> {
     let mut engine = Engine::new();
@@ -148,3 +196,108 @@ fn test_custom_syntax_raw() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_custom_syntax_with_context() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_custom_syntax_with_context_raw(
+        "inc",
+        |stream, _, context| match stream.len() {
+            0 => unreachable!(),
+            1 => Ok(Some("$ident$".into())),
+            2 => match context.is_constant(&stream[1]) {
+                Some(false) => Ok(None),
+                Some(true) => Err(ParseError(
+                    Box::new(ParseErrorType::AssignmentToConstant(stream[1].to_string())),
+                    Position::NONE,
+                )),
+                None => Err(ParseError(
+                    Box::new(ParseErrorType::VariableExpected),
+                    Position::NONE,
+                )),
+            },
+            _ => unreachable!(),
+        },
+        0,
+        |context, inputs| {
+            let var_name = inputs[0].get_variable_name().unwrap().to_string();
+            let value = context.scope_mut().get_mut(&var_name).unwrap();
+            *value = (value.as_int().unwrap() + 1).into();
+            Ok(Dynamic::UNIT)
+        },
+    );
+
+    assert_eq!(engine.eval::<INT>("let x = 41; inc x; x")?, 42);
+    assert_eq!(engine.eval::<INT>("let x = 40; { inc x; inc x; } x")?, 42);
+    assert_eq!(
+        *engine.compile("inc x").expect_err("should error").0,
+        ParseErrorType::VariableExpected
+    );
+    assert_eq!(
+        *engine
+            .compile("{ let x = 0; } inc x")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::VariableExpected
+    );
+    assert_eq!(
+        *engine
+            .compile("const x = 0; inc x")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::AssignmentToConstant("x".to_string())
+    );
+
+    engine.register_custom_syntax_with_context_raw(
+        "next",
+        |_, _, context| {
+            if context.is_in_loop() {
+                Ok(None)
+            } else {
+                Err(ParseError(
+                    Box::new(ParseErrorType::LoopBreak),
+                    Position::NONE,
+                ))
+            }
+        },
+        0,
+        |_, _| Ok(Dynamic::UNIT),
+    );
+
+    engine.compile("loop { next; break; }")?;
+    assert_eq!(
+        *engine.compile("next").expect_err("should error").0,
+        ParseErrorType::LoopBreak
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        engine.register_custom_syntax_with_context_raw(
+            "arg",
+            |_, _, context| {
+                if context.is_function_scope() && context.contains_var("x") {
+                    Ok(None)
+                } else {
+                    Err(ParseError(
+                        Box::new(ParseErrorType::VariableExpected),
+                        Position::NONE,
+                    ))
+                }
+            },
+            0,
+            |_, _| Ok(Dynamic::UNIT),
+        );
+
+        engine.compile("fn foo(x) { arg }")?;
+        assert_eq!(
+            *engine
+                .compile("let x = 0; fn foo(y) { arg }")
+                .expect_err("should error")
+                .0,
+            ParseErrorType::VariableExpected
+        );
+    }
+
+    Ok(())
+}
@@ -37,5 +37,186 @@ fn test_try_catch() -> Result<(), Box<EvalAltResult>> {
         EvalAltResult::ErrorArithmetic(_, _)
     ));
 
+    // `break`, `continue` and `return` pass through `try`
+    assert_eq!(
+        engine.eval::<INT>(
+            "let x = 0; loop { try { x += 1; if x < 3 { continue; } break; } catch { } } x"
+        )?,
+        3
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<INT>("fn foo() { try { return 42; } catch { } 0 } foo()")?,
+        42
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_try_catch_expr() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>("let x = try { 40 + 2 } catch { 0 }; x")?,
+        42
+    );
+    assert_eq!(
+        engine.eval::<INT>("let x = try { throw 41; } catch (err) { err + 1 }; x")?,
+        42
+    );
+    assert_eq!(engine.eval::<INT>("1 + try { 41 } catch { 0 }")?, 42);
+    assert_eq!(engine.eval::<INT>("try { 42 } catch { 0 }")?, 42);
+
+    // The catch variable is scoped to the catch block
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let err = 40;
+                let x = try { throw 1; } catch (err) { err + 1 };
+                err + x
+            "
+        )?,
+        42
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let x = try { throw 1; } catch (err) { 0 }; err")
+            .expect_err("should error"),
+        EvalAltResult::ErrorVariableNotFound(name, _) if name == "err"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_try_catch_guards() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        let script = r"
+            fn classify(x) {
+                try {
+                    throw x;
+                } catch (err if err < 0) {
+                    -1
+                } catch (err if err == 0) {
+                    0
+                } catch (err) {
+                    err * 10
+                }
+            }
+        ";
+
+        assert_eq!(engine.eval::<INT>(&format!("{} classify(-5)", script))?, -1);
+        assert_eq!(engine.eval::<INT>(&format!("{} classify(0)", script))?, 0);
+        assert_eq!(engine.eval::<INT>(&format!("{} classify(4)", script))?, 40);
+    }
+
+    // Unmatched exceptions propagate
+    assert!(matches!(
+        *engine
+            .eval::<INT>("try { throw 42; } catch (err if err < 0) { 0 }")
+            .expect_err("should error"),
+        EvalAltResult::ErrorRuntime(v, _) if v.as_int() == Ok(42)
+    ));
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = try {
+                    try { throw 42; } catch (err if err < 0) { 0 }
+                } catch (err) {
+                    err + 1
+                };
+                x
+            "
+        )?,
+        43
+    );
+
+    // Errors from the engine are caught as strings
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                try {
+                    no_such_function();
+                } catch (err if type_of(err) == "i64") {
+                    1
+                } catch (err if type_of(err) == "string") {
+                    2
+                }
+            "#
+        )?,
+        2
+    );
+
+    // Guards must be boolean
+    assert!(matches!(
+        *engine
+            .eval::<INT>("try { throw 42; } catch (err if err) { 0 }")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(_, _, _)
+    ));
+
+    // Nothing can follow a catch-all clause
+    assert!(engine
+        .compile("try { throw 42; } catch { 0 } catch (err if err > 0) { 1 }")
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+fn test_try_catch_position() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                try {
+                    let x = 1;
+                    throw #{ code: 404 };
+                } catch (err, pos) {
+                    err.code + pos.line * 1000 + pos.position
+                }
+            "
+        )?,
+        4404 + 21
+    );
+
+    // Errors from the engine are also located
+    #[cfg(not(feature = "unchecked"))]
+    assert_eq!(
+        engine.eval::<INT>("try { 42 / 0; } catch (err, pos) { pos.position }")?,
+        10
+    );
+
+    // The position variable can be used in guards
+    assert_eq!(
+        engine.eval::<INT>(
+            "try { throw 42; } catch (err, pos if pos.line > 1) { 0 } catch (err) { err }"
+        )?,
+        42
+    );
+
+    // Re-throwing the payload does not wrap it
+    assert!(matches!(
+        *engine
+            .eval::<()>("try { throw 42; } catch (err, pos) { throw err; }")
+            .expect_err("should error"),
+        EvalAltResult::ErrorRuntime(v, _) if v.as_int() == Ok(42)
+    ));
+
+    let mut ast = engine.compile(r#"try { throw 1; } catch (err, pos) { pos.source }"#)?;
+    assert!(engine.eval_ast::<()>(&ast).is_ok());
+    ast.set_source(Some("hello"));
+    assert_eq!(engine.eval_ast::<String>(&ast)?, "hello");
+
+    assert!(engine.compile("try { } catch (err, ) { }").is_err());
+
     Ok(())
 }
@@ -107,3 +107,71 @@ fn test_timestamp_op() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_timestamp_more() -> Result<(), Box<EvalAltResult>> {
+    use rhai::packages::{MoreTimePackage, Package};
+    use rhai::INT;
+
+    let mut engine = Engine::new();
+
+    // Not in the standard package
+    assert!(matches!(
+        *engine.eval::<()>("sleep(0)").expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(f, _) if f.starts_with("sleep")
+    ));
+
+    engine.register_global_module(MoreTimePackage::new().as_shared_module());
+
+    assert_eq!(engine.eval::<String>("format_duration(0)")?, "00:00:00");
+    assert_eq!(engine.eval::<String>("format_duration(3723)")?, "01:02:03");
+    assert_eq!(engine.eval::<String>("format_duration(-90)")?, "-00:01:30");
+    assert_eq!(
+        engine.eval::<String>("format_duration(360000)")?,
+        "100:00:00"
+    );
+
+    #[cfg(not(feature = "no_float"))]
+    {
+        assert_eq!(
+            engine.eval::<String>("format_duration(3723.45)")?,
+            "01:02:03.450"
+        );
+        assert_eq!(
+            engine.eval::<String>("format_duration(-0.0015)")?,
+            "-00:00:00.002"
+        );
+
+        assert!(engine.eval::<bool>(
+            r"
+                let time = timestamp();
+                sleep(0.01);
+                timestamp() - time >= 0.01
+            "
+        )?);
+
+        assert!(engine.eval::<()>("sleep(1.0e100)").is_err());
+    }
+
+    assert!(engine.eval::<bool>(
+        r"
+            let time = timestamp();
+            sleep(0);
+            sleep(-1);
+            time <= timestamp()
+        "
+    )?);
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let time1 = timestamp();
+                let time2 = time1 + 5;
+                if time2 > time1 && time1 < time2 && time1 != time2 { 42 } else { 0 }
+            "
+        )?,
+        42
+    );
+
+    Ok(())
+}
@@ -1,4 +1,8 @@
-use rhai::{Engine, EvalAltResult, LexError, ParseErrorType, RegisterFn, INT};
+use rhai::{
+    Associativity, Engine, EvalAltResult, LexError, ParseErrorType, Position, RegisterFn, Span,
+    TokenKind, INT,
+};
+use std::num::NonZeroU8;
 
 #[test]
 fn test_tokens_disabled() {
@@ -30,6 +34,56 @@ fn test_tokens_disabled() {
     ));
 }
 
+#[test]
+fn test_tokens_disabled_with_message() {
+    let mut engine = Engine::new();
+
+    engine
+        .disable_symbol_with_message("while", "use 'for' with a range")
+        .disable_symbol_with_message("+=", "write 'x = x + y'")
+        .disable_symbol_with_message("*", "no multiplication");
+
+    let err = engine
+        .compile("let x = 0; while x < 10 { x = x + 1; }")
+        .expect_err("should error");
+    assert_eq!(
+        *err.0,
+        ParseErrorType::DisabledSymbol("while".into(), "use 'for' with a range".into())
+    );
+    assert_eq!(err.1, Position::new(1, 12));
+    assert_eq!(
+        err.0.to_string(),
+        "'while' is disabled: use 'for' with a range"
+    );
+
+    assert_eq!(
+        *engine
+            .compile("let x = 40 + 2; x += 1;")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::DisabledSymbol("+=".into(), "write 'x = x + y'".into())
+    );
+    assert_eq!(
+        *engine.compile("let x = += 0;").expect_err("should error").0,
+        ParseErrorType::DisabledSymbol("+=".into(), "write 'x = x + y'".into())
+    );
+    assert_eq!(
+        *engine
+            .compile("let x = 6 * 7;")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::DisabledSymbol("*".into(), "no multiplication".into())
+    );
+
+    // Disabling again without a message reverts to the normal error
+    engine.disable_symbol("while");
+
+    assert!(matches!(
+        *engine.compile("while true {}").expect_err("should error").0,
+        ParseErrorType::Reserved(err) if err == "while"
+    ));
+}
+
 #[test]
 fn test_tokens_custom_operator_identifiers() -> Result<(), Box<EvalAltResult>> {
     let mut engine = Engine::new();
@@ -83,6 +137,45 @@ fn test_tokens_custom_operator_symbol() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_tokens_custom_operator_associativity() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    // Register a right-associative custom operator `**`
+    engine
+        .register_custom_operator_with_associativity("**", 190, Associativity::Right)
+        .unwrap();
+    engine.register_fn("**", |x: INT, y: INT| x.pow(y as u32));
+
+    assert_eq!(engine.eval_expression::<INT>("2 ** 3 ** 2")?, 512);
+    assert_eq!(engine.eval_expression::<INT>("2 * 2 ** 3 ** 2 + 1")?, 1025);
+
+    // Custom operators are left-associative by default
+    engine.register_custom_operator("minus", 150).unwrap();
+    engine.register_fn("minus", |x: INT, y: INT| x - y);
+
+    assert_eq!(engine.eval_expression::<INT>("10 minus 3 minus 2")?, 5);
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        engine
+            .register_custom_operator_with_associativity("pow", 190, Associativity::Right)
+            .unwrap();
+
+        assert_eq!(
+            engine.eval::<INT>(
+                r"
+                    fn pow(x, y) { x ~ y }
+                    2 pow 3 pow 2
+                "
+            )?,
+            512
+        );
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_tokens_unicode_xid_ident() -> Result<(), Box<EvalAltResult>> {
     let engine = Engine::new();
@@ -108,3 +201,197 @@ fn test_tokens_unicode_xid_ident() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_tokens_operator_precedence() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    // By default, `&` binds more loosely than `==`
+    assert!(!engine.eval_expression::<bool>("false & false == false")?);
+
+    // Make `&` bind more tightly than `==`
+    engine
+        .set_operator_precedence("&", NonZeroU8::new(100).unwrap())
+        .unwrap();
+    assert!(engine.eval_expression::<bool>("false & false == false")?);
+
+    // Lower `&` below `==` again: `a & b == c` is `a & (b == c)`
+    engine
+        .set_operator_precedence("&", NonZeroU8::new(50).unwrap())
+        .unwrap();
+    assert!(!engine.eval_expression::<bool>("false & false == false")?);
+    assert!(engine.eval_expression::<bool>("true & 1 == 1")?);
+
+    // Make `+` bind more tightly than `*`
+    engine
+        .set_operator_precedence("+", NonZeroU8::new(200).unwrap())
+        .unwrap();
+    assert_eq!(engine.eval_expression::<INT>("1 + 2 * 3")?, 9);
+    assert_eq!(engine.eval_expression::<INT>("2 * 3 + 1")?, 8);
+    assert_eq!(engine.eval::<INT>("let x = 1; x += 2 * 3; x")?, 7);
+
+    // Other engines are unaffected
+    let engine2 = Engine::new();
+    assert_eq!(engine2.eval_expression::<INT>("1 + 2 * 3")?, 7);
+    assert!(!engine2.eval_expression::<bool>("false & false == false")?);
+
+    let one = NonZeroU8::new(1).unwrap();
+    assert!(engine.set_operator_precedence("+=", one).is_err());
+    assert!(engine.set_operator_precedence("=", one).is_err());
+    assert!(engine.set_operator_precedence(".", one).is_err());
+    assert!(engine.set_operator_precedence("(", one).is_err());
+    assert!(engine.set_operator_precedence("foo", one).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_tokens_spans() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let script = "let x = goto;";
+    let pos = engine.compile(script).expect_err("should error").1;
+    assert_eq!(pos, Position::new(1, 9));
+    assert_eq!(engine.span_at(script, pos).end(), Position::new(1, 12));
+
+    let script = "let x = 40;\nx + foo(2)";
+    let pos = engine
+        .eval::<INT>(script)
+        .expect_err("should error")
+        .position();
+    assert_eq!(pos, Position::new(2, 5));
+    assert_eq!(engine.span_at(script, pos).end(), Position::new(2, 10));
+
+    let script = "foo(1,\n  2)";
+    let pos = engine
+        .eval::<INT>(script)
+        .expect_err("should error")
+        .position();
+    assert_eq!(pos, Position::new(1, 1));
+    assert_eq!(engine.span_at(script, pos).end(), Position::new(2, 4));
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        let script = r#"let x = "abc"; x.foo(1)"#;
+        let pos = engine
+            .eval::<INT>(script)
+            .expect_err("should error")
+            .position();
+        assert_eq!(pos, Position::new(1, 18));
+        assert_eq!(engine.span_at(script, pos).end(), Position::new(1, 23));
+    }
+
+    // Brackets are matched, and other tokens span themselves only
+    let script = "[1, [2], #{a: (3)}] + foo";
+    assert_eq!(
+        engine.span_at(script, Position::new(1, 1)).end(),
+        Position::new(1, 19)
+    );
+    assert_eq!(
+        engine.span_at(script, Position::new(1, 23)).end(),
+        Position::new(1, 25)
+    );
+    assert_eq!(
+        engine.span_at(script, Position::new(1, 2)).end(),
+        Position::new(1, 2)
+    );
+    assert!(engine.span_at(script, Position::new(1, 4)).is_none());
+
+    let span = Span::new(Position::new(1, 5), Position::new(1, 9));
+    assert_eq!(span.start(), Position::new(1, 5));
+    assert_eq!(
+        Span::new(Position::new(1, 9), Position::new(1, 5)).end(),
+        Position::new(1, 9)
+    );
+    assert_eq!(
+        span.merge(Position::new(2, 3).into()),
+        Span::new(Position::new(1, 5), Position::new(2, 3))
+    );
+    assert_eq!(Span::NONE.merge(span), span);
+
+    engine.set_retain_source(true);
+    let ast = engine.compile("let x = 40;\nx + foo(2)")?;
+    let err = engine.eval_ast::<INT>(&ast).expect_err("should error");
+    assert_eq!(
+        err.excerpt(&engine, &ast).unwrap(),
+        "x + foo(2)\n    ^^^^^^"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_tokens_tokenize() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let script = "let x = \"héllo\" + 'c';\n/* multi\nline */ foo(r\"raw\n\", 15) // end";
+
+    let tokens: Vec<_> = engine
+        .tokenize(script)
+        .map(|(kind, _, text)| (kind, text))
+        .collect();
+
+    assert_eq!(
+        tokens,
+        [
+            (TokenKind::Keyword, "let"),
+            (TokenKind::Identifier, "x"),
+            (TokenKind::Operator, "="),
+            (TokenKind::String, "\"héllo\""),
+            (TokenKind::Operator, "+"),
+            (TokenKind::Char, "'c'"),
+            (TokenKind::Operator, ";"),
+            (TokenKind::Comment, "/* multi\nline */"),
+            (TokenKind::Identifier, "foo"),
+            (TokenKind::Operator, "("),
+            (TokenKind::String, "r\"raw\n\""),
+            (TokenKind::Operator, ","),
+            (TokenKind::Number, "15"),
+            (TokenKind::Operator, ")"),
+            (TokenKind::Comment, "// end"),
+        ]
+    );
+
+    let (_, span, _) = engine.tokenize(script).nth(8).unwrap();
+    assert_eq!(span.start(), Position::new(3, 9));
+    assert_eq!(span.end(), Position::new(3, 11));
+
+    // Malformed input does not stop tokenizing
+    let kinds: Vec<_> = engine
+        .tokenize("x === 1; \"unterminated")
+        .map(|(kind, _, _)| kind)
+        .collect();
+
+    assert_eq!(
+        kinds,
+        [
+            TokenKind::Identifier,
+            TokenKind::Error,
+            TokenKind::Number,
+            TokenKind::Operator,
+            TokenKind::Error
+        ]
+    );
+
+    // Custom keywords and disabled symbols
+    engine.register_custom_operator("foo", 160).unwrap();
+    engine.disable_symbol("while");
+
+    let kinds: Vec<_> = engine
+        .tokenize("1 foo 2; while")
+        .map(|(kind, _, _)| kind)
+        .collect();
+
+    assert_eq!(
+        kinds,
+        [
+            TokenKind::Number,
+            TokenKind::Keyword,
+            TokenKind::Number,
+            TokenKind::Operator,
+            TokenKind::Error
+        ]
+    );
+
+    Ok(())
+}
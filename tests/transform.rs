@@ -0,0 +1,73 @@
+#![cfg(feature = "internals")]
+#![allow(deprecated)]
+use rhai::{Engine, EvalAltResult, Expr, RegisterFn, Stmt, INT};
+
+#[test]
+fn test_transform_loops() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_fn("tick", || ());
+
+    let mut ast = engine.compile(
+        r"
+            fn count(n) {
+                let x = 0;
+                while x < n { x += 1; }
+                x
+            }
+            let x = 0;
+            do { x += 1; } while x < 3;
+            x + count(4)
+        ",
+    )?;
+
+    let mut loops = 0;
+
+    ast.transform(|stmt| match stmt {
+        Stmt::While(_, x, _) | Stmt::Do(x, _, _, _) => {
+            loops += 1;
+            let pos = x.0.position();
+            let body = std::mem::take(&mut x.0);
+            let tick = Stmt::Expr(Expr::new_fn_call("tick", vec![], pos));
+            x.0 = Stmt::new_block(vec![tick, body], pos);
+        }
+        _ => (),
+    });
+
+    assert_eq!(loops, 2);
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 7);
+
+    Ok(())
+}
+
+#[test]
+fn test_transform_exprs() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_fn("double", |x: INT| x * 2);
+
+    let mut ast = engine.compile(
+        r"
+            fn foo(x) { half(x) + 1 }
+            foo(half(40))
+        ",
+    )?;
+
+    ast.transform_exprs(|expr| match expr {
+        Expr::FnCall(x, pos) if x.name == "half" => {
+            let args = std::mem::take(&mut x.args);
+            *expr = Expr::new_fn_call("double", args, *pos);
+        }
+        _ => (),
+    });
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 161);
+
+    #[cfg(not(feature = "no_optimize"))]
+    {
+        let ast = engine.optimize_ast(&Default::default(), ast, rhai::OptimizationLevel::Full);
+        assert_eq!(engine.eval_ast::<INT>(&ast)?, 161);
+    }
+
+    Ok(())
+}
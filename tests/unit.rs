@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult};
+use rhai::{Engine, EvalAltResult, INT};
 
 #[test]
 fn test_unit() -> Result<(), Box<EvalAltResult>> {
@@ -20,3 +20,64 @@ fn test_unit_with_spaces() -> Result<(), Box<EvalAltResult>> {
     engine.eval::<()>("let x = ( ); x")?;
     Ok(())
 }
+
+#[test]
+fn test_unit_coalesce() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("let x = (); x ?? 42")?, 42);
+    assert_eq!(engine.eval::<INT>("let x = 1; x ?? 42")?, 1);
+    assert_eq!(engine.eval::<bool>("let x = false; x ?? true")?, false);
+    assert_eq!(
+        engine.eval::<INT>("let x = (); let y = (); x ?? y ?? 3")?,
+        3
+    );
+    assert_eq!(engine.eval::<INT>("let x = (); x ?? -1")?, -1);
+
+    // Binds tighter than `||` but looser than comparisons
+    assert_eq!(engine.eval::<bool>("let x = (); x ?? false || true")?, true);
+    assert_eq!(engine.eval::<bool>("let x = (); x ?? 1 == 1")?, true);
+
+    // The right side is not evaluated unless the left side is ()
+    assert_eq!(
+        engine.eval::<INT>("let n = 0; let x = 1; x ?? { n += 1; 2 }; n")?,
+        0
+    );
+    assert_eq!(
+        engine.eval::<INT>("let n = 0; let x = (); x ?? { n += 1; 2 }; n")?,
+        1
+    );
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert_eq!(
+            engine.eval::<INT>("let m = #{a: #{b: 1}}; m?.a?.b ?? 0")?,
+            1
+        );
+        assert_eq!(engine.eval::<INT>("let m = #{a: 1}; m?.x?.b ?? 0")?, 0);
+        assert_eq!(engine.eval::<INT>("let m = (); m?.a ?? 0")?, 0);
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_optimize"))]
+#[test]
+fn test_unit_coalesce_optimizer() -> Result<(), Box<EvalAltResult>> {
+    use rhai::OptimizationLevel;
+
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::Simple);
+
+    assert_eq!(engine.eval::<INT>("() ?? 42")?, 42);
+    assert_eq!(engine.eval::<INT>("1 ?? 42")?, 1);
+
+    let ast = engine.compile("() ?? foo()")?;
+    assert!(format!("{:?}", ast).contains("FnCall"));
+    assert!(!format!("{:?}", ast).contains("Coalesce"));
+
+    let ast = engine.compile("1 ?? foo()")?;
+    assert!(!format!("{:?}", ast).contains("FnCall"));
+
+    Ok(())
+}
@@ -1,4 +1,6 @@
-use rhai::{Engine, EvalAltResult, Position, Scope, INT};
+use rhai::{Engine, EvalAltResult, ParseErrorType, Position, Scope, INT};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
 #[test]
 fn test_var_scope() -> Result<(), Box<EvalAltResult>> {
@@ -19,6 +21,112 @@ fn test_var_scope() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_var_scope_large() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let mut scope = Scope::new();
+
+    (0..100).for_each(|i| {
+        scope.push(format!("x{}", i), i as INT);
+    });
+    scope.push("x0", 999 as INT);
+    scope.push_constant("C", 1 as INT);
+
+    // The newest entry wins
+    assert_eq!(scope.get_value::<INT>("x0").unwrap(), 999);
+    assert_eq!(scope.get_value::<INT>("x42").unwrap(), 42);
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "x0 + x99 + C")?,
+        1099
+    );
+
+    scope.set_value("x0", 123 as INT);
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "x0")?, 123);
+
+    // Rewinding uncovers shadowed entries
+    scope.rewind(100);
+    assert!(!scope.contains("C"));
+    assert_eq!(scope.get_value::<INT>("x0").unwrap(), 0);
+    scope.rewind(10);
+    assert!(scope.contains("x9"));
+    assert!(!scope.contains("x10"));
+    scope.push("x10", 10 as INT);
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "x0 + x10")?, 10);
+
+    // Many variables declared in the script itself
+    let script = (0..100)
+        .map(|i| format!("let y{} = {};", i, i))
+        .chain(
+            ["let z = { let y0 = 1000; y0 + y1 }; z + y0 + y99".to_string()]
+                .iter()
+                .cloned(),
+        )
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    assert_eq!(engine.eval::<INT>(&script)?, 1100);
+
+    Ok(())
+}
+
+#[test]
+fn test_var_scope_entries() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let mut scope = Scope::new();
+
+    scope.push("x", 42 as INT);
+    scope.push_constant("C", "hello".to_string());
+
+    assert_eq!(*scope.get_value_ref::<INT>("x").unwrap(), 42);
+    assert_eq!(scope.get_value_ref::<String>("C").unwrap(), "hello");
+    assert!(scope.get_value_ref::<String>("x").is_none());
+    assert!(scope.get_value_ref::<INT>("y").is_none());
+
+    // Replace a variable with a value of another type, or add a new one
+    scope.set_or_push("x", true);
+    scope.set_or_push(format!("y{}", 1), 1 as INT);
+    assert_eq!(
+        engine.eval_with_scope::<bool>(&mut scope, "x && y1 == 1")?,
+        true
+    );
+    assert_eq!(scope.len(), 3);
+
+    // Constants stay constant
+    assert!(scope.get_mut("C").is_none());
+    assert!(scope
+        .iter_raw()
+        .all(|(name, constant, _)| constant == (name == "C")));
+
+    // Removing an entry keeps later entries intact
+    assert!(scope.remove("x").unwrap().cast::<bool>());
+    assert!(scope.remove("x").is_none());
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "len(C) + y1")?, 6);
+
+    assert_eq!(scope.remove("C").unwrap().cast::<String>(), "hello");
+    assert!(!scope.contains("C"));
+
+    // Removing entries from a large scope
+    (0..100).for_each(|i| {
+        scope.push(format!("x{}", i), i as INT);
+    });
+    assert_eq!(scope.remove("x10").unwrap().cast::<INT>(), 10);
+    assert!(!scope.contains("x10"));
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "x9 + x11 + x99 + y1")?,
+        120
+    );
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "variable C is constant")]
+fn test_var_scope_set_or_push_constant() {
+    let mut scope = Scope::new();
+    scope.push_constant("C", 42 as INT);
+    scope.set_or_push("C", 0 as INT);
+}
+
 #[test]
 fn test_scope_eval() -> Result<(), Box<EvalAltResult>> {
     let engine = Engine::new();
@@ -94,3 +202,295 @@ fn test_var_resolver() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_var_resolver_write() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let store = Arc::new(RwLock::new(HashMap::<String, INT>::new()));
+    store.write().unwrap().insert("hp".to_string(), 100);
+
+    let values = store.clone();
+    engine.on_var(move |name, _, _| Ok(values.read().unwrap().get(name).map(|&v| v.into())));
+
+    let values = store.clone();
+    engine.on_var_write(move |name, value, _| match name {
+        "LOCKED" => Err("LOCKED is read-only".into()),
+        "hp" | "score" => {
+            values
+                .write()
+                .unwrap()
+                .insert(name.to_string(), value.as_int()?);
+            Ok(true)
+        }
+        // Return Ok(false) to continue with the normal variable assignment.
+        _ => Ok(false),
+    });
+
+    let mut scope = Scope::new();
+
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "hp = 50; hp += 7; hp")?,
+        57
+    );
+    assert_eq!(store.read().unwrap()["hp"], 57);
+
+    engine.consume_with_scope(&mut scope, "score = 1; score *= 42;")?;
+    assert_eq!(store.read().unwrap()["score"], 42);
+    assert!(!scope.contains("score"));
+
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "let x = 1; x += 41; x")?,
+        42
+    );
+    assert!(!store.read().unwrap().contains_key("x"));
+
+    assert!(matches!(
+        *engine.consume("LOCKED = 1").expect_err("should error"),
+        EvalAltResult::ErrorRuntime(_, pos) if pos == Position::new(1, 1)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_var_strict_default() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert!(!engine.strict_variables());
+
+    // Redeclaring a variable shadows the previous one
+    assert_eq!(engine.eval::<INT>("let x = 1; let x = x + 41; x")?, 42);
+
+    // Undeclared variables are only detected at run-time
+    engine.compile("x + 1")?;
+
+    assert!(matches!(
+        *engine.eval::<INT>("x + 1").expect_err("should error"),
+        EvalAltResult::ErrorVariableNotFound(n, _) if n == "x"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_var_strict() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_strict_variables(true);
+
+    assert_eq!(
+        *engine
+            .compile("let x = 1; let x = 2;")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::VariableExists("x".to_string())
+    );
+    assert_eq!(
+        *engine
+            .compile("let x = 1; { let y = 2; const y = 3; }")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::VariableExists("y".to_string())
+    );
+    assert_eq!(
+        *engine
+            .compile("let x = 1; x + y")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::VariableUndefined("y".to_string())
+    );
+    assert_eq!(
+        *engine
+            .compile("{ let x = 1; } x")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::VariableUndefined("x".to_string())
+    );
+
+    // Shadowing in an inner block is allowed
+    assert_eq!(
+        engine.eval::<INT>("let x = 1; { let x = 41; x += 1; x } ")?,
+        42
+    );
+    assert_eq!(
+        engine.eval::<INT>("let x = 0; for x in range(0, 10) { } x")?,
+        0
+    );
+
+    // Variables in the scope are declared
+    let mut scope = Scope::new();
+    scope.push_constant("FOO", 40 as INT);
+    scope.push("bar", 2 as INT);
+
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "FOO + bar")?, 42);
+    assert!(engine.compile("FOO + bar").is_err());
+    assert_eq!(
+        engine.eval_expression_with_scope::<INT>(&mut scope, "FOO + bar")?,
+        42
+    );
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(engine.eval::<INT>("let x = #{a: 42}; x.a")?, 42);
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        assert_eq!(engine.eval::<INT>("fn foo(x) { x + 1 } foo(41)")?, 42);
+        assert_eq!(
+            *engine
+                .compile("let x = 1; fn foo() { x }")
+                .expect_err("should error")
+                .0,
+            ParseErrorType::VariableUndefined("x".to_string())
+        );
+        assert_eq!(
+            *engine
+                .compile("let f = |x| x + y;")
+                .expect_err("should error")
+                .0,
+            ParseErrorType::VariableUndefined("y".to_string())
+        );
+    }
+
+    #[cfg(not(feature = "no_closure"))]
+    {
+        assert_eq!(
+            engine.eval::<INT>("let x = 40; let f = |y| x + y; call(f, 2)")?,
+            42
+        );
+        assert_eq!(
+            engine
+                .eval::<INT>("let x = 40; let f = || { let g = |y| x + y; call(g, 2) }; call(f)")?,
+            42
+        );
+        assert_eq!(
+            *engine
+                .compile("let x = 1; let f = || || x + y;")
+                .expect_err("should error")
+                .0,
+            ParseErrorType::VariableUndefined("y".to_string())
+        );
+    }
+
+    // Variables may be provided by a variable resolver
+    engine.on_var(|name, _, _| match name {
+        "MYSTIC_NUMBER" => Ok(Some((42 as INT).into())),
+        _ => Ok(None),
+    });
+
+    assert_eq!(engine.eval::<INT>("MYSTIC_NUMBER")?, 42);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "unchecked"))]
+fn test_var_max_variables() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.set_max_variables(3);
+
+    engine.compile("let a = 1; let b = 2; let c = 3;")?;
+    engine.compile("let a = 1; { let b = 2; let c = 3; } { let d = 4; let e = 5; }")?;
+    engine.compile("for x in range(0, 10) { let y = x; let z = y; }")?;
+
+    assert_eq!(
+        *engine
+            .compile("let a = 1; let b = 2; let c = 3; let d = 4;")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::TooManyVariables(3)
+    );
+    assert_eq!(
+        *engine
+            .compile("let a = 1; for (i, x) in range(1, 3) { let y = x; }")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::TooManyVariables(3)
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        // Limits apply to each function separately
+        engine.compile("let a = 1; let b = 2; fn foo(x, y) { let z = x + y; z }")?;
+
+        assert_eq!(
+            *engine
+                .compile("fn foo(x, y) { let z = x + y; let w = z; w }")
+                .expect_err("should error")
+                .0,
+            ParseErrorType::TooManyVariables(3)
+        );
+    }
+
+    // Variables in the scope count at run-time
+    let mut scope = Scope::new();
+    scope.push("x", 40 as INT);
+    scope.push("y", 2 as INT);
+
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "let z = x + y; z")?,
+        42
+    );
+    scope.rewind(2);
+
+    assert!(matches!(
+        *engine
+            .eval_with_scope::<INT>(&mut scope, "let z = x + y; let w = z; w")
+            .expect_err("should error"),
+        EvalAltResult::ErrorTooManyVariables(_)
+    ));
+
+    engine.set_max_variables(0);
+
+    scope.rewind(2);
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "let z = x + y; let w = z; w")?,
+        42
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_var_this() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let mut value: rhai::Dynamic = (40 as INT).into();
+
+    assert_eq!(
+        engine.eval_with_this::<INT>(&mut value, "this += 1; this + 1")?,
+        42
+    );
+    assert_eq!(value.as_int().unwrap(), 41);
+
+    // 'this' is bound within blocks and loops
+    let ast =
+        engine.compile_with_this("if this > 0 { for i in range(0, 2) { this *= 2; } } this")?;
+    assert_eq!(engine.eval_ast_with_this::<INT>(&mut value, &ast)?, 164);
+    assert_eq!(engine.eval_ast_with_this::<INT>(&mut value, &ast)?, 656);
+    assert_eq!(value.as_int().unwrap(), 656);
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        let mut entity = rhai::Map::new();
+        entity.insert("hp".into(), (100 as INT).into());
+        let mut entity: rhai::Dynamic = entity.into();
+
+        engine.eval_with_this::<()>(&mut entity, "let damage = 42; this.hp -= damage;")?;
+        assert_eq!(entity.cast::<rhai::Map>()["hp"].as_int().unwrap(), 58);
+    }
+
+    // 'this' is still rejected outside functions by the normal entry points
+    assert!(matches!(
+        *engine.compile("this + 1").expect_err("should error").0,
+        ParseErrorType::BadInput(_)
+    ));
+
+    // ... and unbound when evaluating normally
+    assert!(matches!(
+        *engine.eval_ast::<INT>(&ast).expect_err("should error"),
+        EvalAltResult::ErrorUnboundThis(_)
+    ));
+
+    Ok(())
+}